@@ -1,7 +1,7 @@
 //! Account configuration — parse accounts.toml with provider presets.
 
 use anyhow::{bail, Result};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -49,6 +49,18 @@ pub struct OwnerConfig {
     pub github_user: String,
     #[serde(default)]
     pub name: String,
+    /// Shell command printing a GitHub token, used when `gh` is not installed.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub github_token_cmd: String,
+    /// Shell command run with the paths of drafts newly marked for review.
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub review_hook: String,
+    /// Pronouns used in mailbox templates, e.g. "she/her" (default they/them).
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub pronouns: String,
+    /// Language of mailbox templates (default "en").
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub language: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -57,20 +69,56 @@ pub struct WatchConfig {
     pub poll_interval: u64,
     #[serde(default)]
     pub notify: bool,
+    /// What notifications show: "full" (sender and subject), "sender", or "count".
+    #[serde(default)]
+    pub notify_detail: String,
+    /// Newest messages listed in a notification.
+    #[serde(default = "default_notify_count")]
+    pub notify_count: usize,
     #[serde(default)]
     pub auto_upgrade: bool,
+    /// Mailbox sync after new mail: "both", "pull", "push", or "off".
+    #[serde(default = "default_mailbox_sync")]
+    pub mailbox_sync: String,
+    /// Rotate `logs/watch.log` once it reaches this size (0 = never).
+    #[serde(default = "default_log_max_bytes")]
+    pub log_max_bytes: u64,
+    /// Rotated logs to keep (`watch.log.1` ... `watch.log.N`).
+    #[serde(default = "default_log_keep")]
+    pub log_keep: usize,
 }
 
 fn default_poll_interval() -> u64 {
     300
 }
 
+fn default_notify_count() -> usize {
+    3
+}
+
+fn default_mailbox_sync() -> String {
+    "both".to_string()
+}
+
+fn default_log_max_bytes() -> u64 {
+    1024 * 1024
+}
+
+fn default_log_keep() -> usize {
+    3
+}
+
 impl Default for WatchConfig {
     fn default() -> Self {
         Self {
             poll_interval: 300,
             notify: false,
+            notify_detail: String::new(),
+            notify_count: default_notify_count(),
             auto_upgrade: false,
+            mailbox_sync: default_mailbox_sync(),
+            log_max_bytes: default_log_max_bytes(),
+            log_keep: default_log_keep(),
         }
     }
 }
@@ -81,12 +129,26 @@ pub struct Account {
     pub provider: String,
     #[serde(default)]
     pub user: String,
+    /// Name shown in the From header (`"Brian T" <user>`); empty sends the
+    /// bare address.
+    #[serde(default)]
+    pub display_name: String,
     #[serde(default)]
     pub password: String,
     #[serde(default)]
     pub password_cmd: String,
+    /// How IMAP and SMTP log in: "" or "password" (the default), or
+    /// "oauth2" for SASL XOAUTH2 with the token stored by `corky sync-auth`.
     #[serde(default)]
+    pub auth: String,
+    /// Label names. Entries may also be tables like
+    /// `{ name = "inbox", days = 90 }`; see `label_overrides`.
+    #[serde(default, deserialize_with = "deserialize_labels")]
     pub labels: Vec<String>,
+    /// Per-label `days` / `max_messages` from table entries in `labels`,
+    /// filled in by [`load_accounts`].
+    #[serde(skip)]
+    pub label_overrides: HashMap<String, LabelOverride>,
     #[serde(default)]
     pub imap_host: String,
     #[serde(default = "default_imap_port")]
@@ -103,6 +165,112 @@ pub struct Account {
     pub sync_days: u32,
     #[serde(default)]
     pub default: bool,
+    /// Post-sync index hook: "" (off), "maildir", or "notmuch".
+    #[serde(default)]
+    pub index_hook: String,
+    /// Maildir mirror root (maildir) or folder under the notmuch root (notmuch).
+    #[serde(default)]
+    pub index_path: String,
+    /// Also deliver every message sent with `draft push --send` to the
+    /// account's own address, as a BCC.
+    #[serde(default)]
+    pub bcc_self: bool,
+    /// After a send, append a copy of the message (marked read) to this IMAP
+    /// folder. For providers that don't keep reliable Sent copies.
+    #[serde(default)]
+    pub archive_copy_folder: String,
+    /// Web UI link template for `thread open-web` (`{message_id}`, `{user}`,
+    /// `{folder}`, `{uid}`, `{subject}`); empty uses the provider's default.
+    #[serde(default)]
+    pub web_url: String,
+    /// OAuth client and provider for `corky sync-auth` (`[accounts.NAME.oauth]`).
+    #[serde(default)]
+    pub oauth: Option<OAuthConfig>,
+}
+
+/// OAuth settings for an account. Everything is optional: Gmail accounts
+/// fall back to the `[gmail]` client.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OAuthConfig {
+    /// "google", "microsoft", or "oidc"; empty infers from the account provider.
+    #[serde(default)]
+    pub provider: String,
+    #[serde(default)]
+    pub client_id: String,
+    #[serde(default)]
+    pub client_id_cmd: String,
+    #[serde(default)]
+    pub client_secret: String,
+    #[serde(default)]
+    pub client_secret_cmd: String,
+    /// Microsoft tenant (default "common").
+    #[serde(default)]
+    pub tenant: String,
+    /// OIDC issuer URL; endpoints come from its discovery document.
+    #[serde(default)]
+    pub issuer: String,
+    /// Scopes to request instead of the provider's defaults.
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    /// "browser" or "device"; empty uses the provider's default.
+    #[serde(default)]
+    pub flow: String,
+}
+
+/// Per-label sync limits that override the account's.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LabelOverride {
+    /// Sync window in days (instead of the account's `sync_days`).
+    pub days: Option<u32>,
+    /// Keep only the newest N messages on a full sync.
+    pub max_messages: Option<u32>,
+}
+
+/// One `labels` entry: a bare name or a table with overrides.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum LabelEntry {
+    Name(String),
+    Table {
+        name: String,
+        #[serde(default)]
+        days: Option<u32>,
+        #[serde(default)]
+        max_messages: Option<u32>,
+    },
+}
+
+impl LabelEntry {
+    fn name(&self) -> &str {
+        match self {
+            LabelEntry::Name(name) | LabelEntry::Table { name, .. } => name,
+        }
+    }
+}
+
+fn deserialize_labels<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<String>, D::Error> {
+    let entries = Vec::<LabelEntry>::deserialize(d)?;
+    Ok(entries.iter().map(|e| e.name().to_string()).collect())
+}
+
+/// Overrides from the table entries of an account's raw `labels` array.
+fn parse_label_overrides(data: &toml::Value) -> Result<HashMap<String, LabelOverride>> {
+    let Some(labels) = data.get("labels") else {
+        return Ok(HashMap::new());
+    };
+    let entries: Vec<LabelEntry> = labels.clone().try_into()?;
+    let mut overrides = HashMap::new();
+    for entry in entries {
+        if let LabelEntry::Table {
+            name,
+            days,
+            max_messages,
+        } = entry
+        {
+            overrides.insert(name, LabelOverride { days, max_messages });
+        }
+    }
+    Ok(overrides)
 }
 
 fn default_provider() -> String {
@@ -126,9 +294,12 @@ impl Default for Account {
         Self {
             provider: "imap".to_string(),
             user: String::new(),
+            display_name: String::new(),
             password: String::new(),
             password_cmd: String::new(),
+            auth: String::new(),
             labels: vec![],
+            label_overrides: HashMap::new(),
             imap_host: String::new(),
             imap_port: 993,
             imap_starttls: false,
@@ -137,10 +308,28 @@ impl Default for Account {
             drafts_folder: "Drafts".to_string(),
             sync_days: 3650,
             default: false,
+            index_hook: String::new(),
+            index_path: String::new(),
+            bcc_self: false,
+            archive_copy_folder: String::new(),
+            web_url: String::new(),
+            oauth: None,
         }
     }
 }
 
+impl Account {
+    /// The From address for outgoing mail: `display_name <user>`, or the
+    /// bare `user` without a display name.
+    pub fn from_address(&self) -> String {
+        crate::address::Address {
+            name: self.display_name.trim().to_string(),
+            email: self.user.clone(),
+        }
+        .to_string()
+    }
+}
+
 /// Apply provider preset defaults. Account values win over preset.
 fn apply_preset(account: &mut Account) {
     let presets = provider_presets();
@@ -177,6 +366,35 @@ pub fn resolve_password(account: &Account) -> Result<String> {
     )
 }
 
+/// What an account logs in with.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Secret {
+    /// A password (IMAP LOGIN, SMTP PLAIN/LOGIN).
+    Password(String),
+    /// An OAuth2 access token (SASL XOAUTH2).
+    OAuth2(String),
+}
+
+impl From<&str> for Secret {
+    fn from(password: &str) -> Self {
+        Secret::Password(password.to_string())
+    }
+}
+
+/// Resolve what account `name` logs in with: its password, or with
+/// `auth = "oauth2"` its stored access token, refreshed if it has expired.
+pub fn account_secret(name: &str, account: &Account) -> Result<Secret> {
+    match account.auth.as_str() {
+        "" | "password" => Ok(Secret::Password(resolve_password(account)?)),
+        "oauth2" => Ok(Secret::OAuth2(crate::sync::auth::access_token(name, account)?)),
+        other => bail!(
+            "Unknown auth '{}' for account '{}'. Use \"password\" or \"oauth2\".",
+            other,
+            name
+        ),
+    }
+}
+
 /// Parse accounts from .corky.toml → {name: Account} mapping.
 pub fn load_accounts(path: Option<&Path>) -> Result<HashMap<String, Account>> {
     let path = match path {
@@ -200,6 +418,7 @@ pub fn load_accounts(path: Option<&Path>) -> Result<HashMap<String, Account>> {
             continue;
         }
         let mut account: Account = data.clone().try_into()?;
+        account.label_overrides = parse_label_overrides(data)?;
         apply_preset(&mut account);
         result.insert(name.clone(), account);
     }
@@ -243,14 +462,14 @@ pub fn get_default_account(accounts: &HashMap<String, Account>) -> Result<(Strin
     Ok((name.clone(), acct.clone()))
 }
 
-/// Lookup account by email address.
+/// Lookup account by email address (see `address::same`: case, display
+/// name, and `+tag` are ignored).
 pub fn get_account_for_email(
     accounts: &HashMap<String, Account>,
     email_addr: &str,
 ) -> Option<(String, Account)> {
-    let email_lower = email_addr.to_lowercase();
     for (name, acct) in accounts {
-        if acct.user.to_lowercase() == email_lower {
+        if crate::address::same(&acct.user, email_addr) {
             return Some((name.clone(), acct.clone()));
         }
     }
@@ -269,6 +488,7 @@ pub fn add_label_to_account(account_name: &str, label: &str, path: Option<&Path>
     if !path.exists() {
         bail!("Config not found at {}", path.display());
     }
+    let _lock = crate::config::lock::lock(&path)?;
 
     // Verify account exists and label isn't already there
     let accounts = load_accounts(Some(&path))?;
@@ -287,17 +507,20 @@ pub fn add_label_to_account(account_name: &str, label: &str, path: Option<&Path>
     let content = std::fs::read_to_string(&path)?;
     let mut doc = content.parse::<toml_edit::DocumentMut>()?;
 
-    let labels_array = doc.get_mut("accounts")
-        .and_then(|t| t.get_mut(account_name))
-        .and_then(|t| t.get_mut("labels"));
+    let account_table = doc.get_mut("accounts")
+        .and_then(|t| t.get_mut(account_name));
 
-    if let Some(labels) = labels_array {
-        if let Some(arr) = labels.as_array_mut() {
+    if let Some(table) = account_table {
+        // An account written without `labels` gets the key created.
+        if table.get("labels").is_none() {
+            table["labels"] = toml_edit::value(toml_edit::Array::new());
+        }
+        if let Some(arr) = table.get_mut("labels").and_then(|l| l.as_array_mut()) {
             arr.push(label);
         }
     }
 
-    std::fs::write(&path, doc.to_string())?;
+    crate::config::lock::write(&path, &doc.to_string())?;
     Ok(true)
 }
 
@@ -0,0 +1,313 @@
+//! Email address parsing and normalization.
+//!
+//! Every place that compares addresses (contacts, manifest matching, reply
+//! recipients, owner detection, routing, unanswered) goes through
+//! [`normalize`] or [`same`], so `Alice <Alice@Example.COM>`,
+//! `alice+lists@example.com`, and `alice@bücher.example` vs its punycode
+//! form all compare the way a person would expect.
+
+use std::fmt;
+
+use crate::util::split_address_list;
+
+/// A parsed address: optional display name plus the bare email.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    pub name: String,
+    pub email: String,
+}
+
+impl Address {
+    /// Parse `Name <email>`, `"Last, First" <email>`, `<email>`, or a bare
+    /// `email`. Returns None when there is no syntactically valid address.
+    pub fn parse(s: &str) -> Option<Address> {
+        let s = s.trim();
+        let (name, email) = match (s.rfind('<'), s.ends_with('>')) {
+            (Some(start), true) => (s[..start].trim(), s[start + 1..s.len() - 1].trim()),
+            _ => ("", s),
+        };
+        if !is_valid(email) {
+            return None;
+        }
+        let name = name
+            .strip_prefix('"')
+            .and_then(|n| n.strip_suffix('"'))
+            .unwrap_or(name)
+            .replace("\\\"", "\"");
+        Some(Address {
+            name,
+            email: email.to_string(),
+        })
+    }
+
+    /// Comparison key: see [`normalize`].
+    pub fn key(&self) -> String {
+        normalize(&self.email)
+    }
+}
+
+impl fmt::Display for Address {
+    /// `Name <email>`, quoting the name when it has specials; bare email
+    /// without a name.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.name.is_empty() {
+            return write!(f, "{}", self.email);
+        }
+        if self.name.contains(|c: char| ",;:<>@\"()[]\\".contains(c)) {
+            write!(f, "\"{}\" <{}>", self.name.replace('"', "\\\""), self.email)
+        } else {
+            write!(f, "{} <{}>", self.name, self.email)
+        }
+    }
+}
+
+/// Parse an address header (`To`, `CC`, ...) into addresses, in order.
+/// Entries that aren't valid addresses are returned in the error list.
+pub fn parse_list(field: &str) -> (Vec<Address>, Vec<String>) {
+    let mut ok = Vec::new();
+    let mut bad = Vec::new();
+    for part in split_address_list(field) {
+        match Address::parse(&part) {
+            Some(a) => ok.push(a),
+            None => bad.push(part),
+        }
+    }
+    (ok, bad)
+}
+
+/// Whether `email` is a bare address: one `@`, a non-empty local part with
+/// no whitespace or angle brackets, and a domain of non-empty dot-separated
+/// labels. Non-ASCII (internationalized) local parts and domains are allowed.
+pub fn is_valid(email: &str) -> bool {
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        return false;
+    };
+    let bad_char = |c: char| c.is_whitespace() || "<>,;\"".contains(c);
+    if local.is_empty() || local.contains('@') || local.contains(bad_char) {
+        return false;
+    }
+    if domain.is_empty() || domain.contains(bad_char) || domain.contains('@') {
+        return false;
+    }
+    domain
+        .split('.')
+        .all(|label| !label.is_empty() && !label.starts_with('-') && !label.ends_with('-'))
+}
+
+/// ASCII form of a domain: lowercased, with internationalized labels in
+/// punycode (`bücher.example` → `xn--bcher-kva.example`). Falls back to the
+/// lowercased input when it isn't a valid IDN.
+pub fn domain_to_ascii(domain: &str) -> String {
+    let lower = domain.trim().trim_end_matches('.').to_lowercase();
+    if lower.is_ascii() {
+        return lower;
+    }
+    idna::domain_to_ascii(&lower).unwrap_or(lower)
+}
+
+/// Unicode form of a domain for display: punycode labels decoded
+/// (`xn--bcher-kva.example` → `bücher.example`). Labels that don't decode
+/// are kept as-is.
+pub fn domain_to_unicode(domain: &str) -> String {
+    if !domain.to_ascii_lowercase().contains("xn--") {
+        return domain.to_string();
+    }
+    match idna::domain_to_unicode(domain) {
+        (unicode, Ok(())) => unicode,
+        _ => domain.to_string(),
+    }
+}
+
+/// `email` with its domain in ASCII form, for SMTP envelopes and headers.
+/// The local part is left alone: a UTF-8 local part needs SMTPUTF8.
+pub fn to_ascii(email: &str) -> String {
+    match email.rsplit_once('@') {
+        Some((local, domain)) => format!("{}@{}", local, domain_to_ascii(domain)),
+        None => email.to_string(),
+    }
+}
+
+/// An address header rewritten for display, with punycode domains shown in
+/// Unicode. Headers without punycode are returned unchanged.
+pub fn display_header(value: &str) -> String {
+    if !value.to_ascii_lowercase().contains("xn--") {
+        return value.to_string();
+    }
+    split_address_list(value)
+        .into_iter()
+        .map(|part| match Address::parse(&part) {
+            Some(mut a) => {
+                if let Some((local, domain)) = a.email.rsplit_once('@') {
+                    a.email = format!("{}@{}", local, domain_to_unicode(domain));
+                }
+                a.to_string()
+            }
+            None => part,
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Comparison key for an address: the bare email (display name and angle
+/// brackets dropped), lowercased, with the domain in ASCII form.
+pub fn normalize(addr: &str) -> String {
+    let email = Address::parse(addr).map(|a| a.email).unwrap_or_else(|| {
+        addr.trim()
+            .trim_matches(|c: char| c == '<' || c == '>')
+            .to_string()
+    });
+    match email.rsplit_once('@') {
+        Some((local, domain)) => format!("{}@{}", local.to_lowercase(), domain_to_ascii(domain)),
+        None => email.to_lowercase(),
+    }
+}
+
+/// [`normalize`], then drop a `+tag` from the local part, so
+/// `alice+lists@example.com` identifies the same person as `alice@example.com`.
+pub fn base(addr: &str) -> String {
+    let key = normalize(addr);
+    match key.rsplit_once('@') {
+        Some((local, domain)) => {
+            let local = local.split('+').next().unwrap_or(local);
+            if local.is_empty() {
+                key
+            } else {
+                format!("{}@{}", local, domain)
+            }
+        }
+        None => key,
+    }
+}
+
+/// Whether two addresses (either form: `Name <email>` or bare) belong to
+/// the same mailbox, ignoring case, display names, and plus tags.
+pub fn same(a: &str, b: &str) -> bool {
+    base(a) == base(b)
+}
+
+/// Whether `addr` is one of `list` by [`same`].
+pub fn contains(list: &[String], addr: &str) -> bool {
+    let key = base(addr);
+    list.iter().any(|a| base(a) == key)
+}
+
+/// The domain of a `*@domain` wildcard (as in contact `emails`), in ASCII
+/// form. None for anything else.
+pub fn wildcard_domain(pattern: &str) -> Option<String> {
+    let domain = pattern.trim().strip_prefix("*@")?;
+    if domain.is_empty() || domain.contains(['@', '*']) {
+        return None;
+    }
+    Some(domain_to_ascii(domain))
+}
+
+/// The domain of `addr` in ASCII form, as [`normalize`] gives it.
+pub fn domain_of(addr: &str) -> Option<String> {
+    normalize(addr).rsplit_once('@').map(|(_, d)| d.to_string())
+}
+
+/// Whether `addr` matches `pattern`: a `*@domain` wildcard matches every
+/// address at that domain (not its subdomains); anything else by [`same`].
+pub fn matches(pattern: &str, addr: &str) -> bool {
+    match wildcard_domain(pattern) {
+        Some(domain) => domain_of(addr).is_some_and(|d| d == domain),
+        None => same(pattern, addr),
+    }
+}
+
+/// Whether `addr` matches any of `patterns` by [`matches`].
+pub fn matches_any(patterns: &[String], addr: &str) -> bool {
+    patterns.iter().any(|p| matches(p, addr))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_forms() {
+        assert_eq!(
+            Address::parse("Alice <alice@example.com>"),
+            Some(Address {
+                name: "Alice".to_string(),
+                email: "alice@example.com".to_string()
+            })
+        );
+        let quoted = Address::parse("\"Smith, Bob\" <bob@example.com>").unwrap();
+        assert_eq!(quoted.name, "Smith, Bob");
+        assert_eq!(quoted.to_string(), "\"Smith, Bob\" <bob@example.com>");
+        assert_eq!(
+            Address::parse("<carol@example.com>").unwrap().to_string(),
+            "carol@example.com"
+        );
+        assert_eq!(
+            Address::parse(" dan@example.com ").unwrap().email,
+            "dan@example.com"
+        );
+        assert!(Address::parse("Just A Name").is_none());
+        assert!(Address::parse("bad@").is_none());
+        assert!(Address::parse("a b@example.com").is_none());
+        assert!(Address::parse("x@example..com").is_none());
+        assert!(Address::parse("jörg@bücher.example").is_some());
+    }
+
+    #[test]
+    fn test_parse_list_reports_bad_entries() {
+        let (ok, bad) =
+            parse_list("Alice <alice@example.com>, \"Smith, Bob\" <bob@example.com>, nobody");
+        assert_eq!(ok.len(), 2);
+        assert_eq!(ok[1].email, "bob@example.com");
+        assert_eq!(bad, vec!["nobody"]);
+    }
+
+    #[test]
+    fn test_normalize_and_same() {
+        assert_eq!(normalize("Alice <Alice@Example.COM>"), "alice@example.com");
+        assert_eq!(
+            normalize("alice@bücher.example"),
+            "alice@xn--bcher-kva.example"
+        );
+        assert_eq!(
+            normalize("alice@BÜCHER.example"),
+            normalize("alice@xn--bcher-kva.example")
+        );
+        assert_eq!(base("alice+lists@example.com"), "alice@example.com");
+        assert_eq!(base("+only@example.com"), "+only@example.com");
+        assert!(same("Alice <alice+news@Example.com>", "alice@example.com"));
+        assert!(!same("alice@example.com", "alicia@example.com"));
+        assert!(contains(
+            &["bob@example.com".to_string()],
+            "Bob <BOB@example.com>"
+        ));
+    }
+
+    #[test]
+    fn test_idn_forms() {
+        assert_eq!(to_ascii("jörg@Bücher.example"), "jörg@xn--bcher-kva.example");
+        assert_eq!(to_ascii("bob@example.com"), "bob@example.com");
+        assert_eq!(domain_to_unicode("xn--bcher-kva.example"), "bücher.example");
+        assert_eq!(domain_to_unicode("example.com"), "example.com");
+        assert_eq!(
+            display_header("Jörg <jorg@xn--bcher-kva.example>, bob@example.com"),
+            "Jörg <jorg@bücher.example>, bob@example.com"
+        );
+        let plain = "\"Smith, Bob\" <bob@example.com>";
+        assert_eq!(display_header(plain), plain);
+        assert!(same("jorg@xn--bcher-kva.example", "jorg@bücher.example"));
+    }
+
+    #[test]
+    fn test_wildcard_domain_matches() {
+        assert_eq!(wildcard_domain("*@ClientCorp.com").as_deref(), Some("clientcorp.com"));
+        assert_eq!(wildcard_domain("alice@clientcorp.com"), None);
+        assert_eq!(wildcard_domain("*@"), None);
+        assert!(matches("*@clientcorp.com", "Bob <Bob@CLIENTCORP.com>"));
+        assert!(!matches("*@clientcorp.com", "eve@mail.clientcorp.com"));
+        assert!(!matches("*@clientcorp.com", "eve@clientcorp.co"));
+        assert!(matches("*@bücher.example", "ana@xn--bcher-kva.example"));
+        assert!(matches("alice@example.com", "alice+news@example.com"));
+        let patterns = vec!["alice@example.com".to_string(), "*@clientcorp.com".to_string()];
+        assert!(matches_any(&patterns, "dan@clientcorp.com"));
+        assert!(!matches_any(&patterns, "dan@example.com"));
+    }
+}
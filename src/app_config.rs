@@ -6,6 +6,7 @@ use anyhow::{bail, Result};
 use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use crate::error::CorkyError;
 use crate::resolve;
 
 /// Return the OS-native corky config directory.
@@ -106,14 +107,14 @@ pub fn resolve_mailbox(name: Option<&str>) -> Result<Option<PathBuf>> {
     }
 
     // Multiple mailboxes, no default
-    eprintln!("Multiple mailboxes configured. Use --mailbox NAME or set default_mailbox.");
-    eprintln!();
+    let mut msg =
+        String::from("Multiple mailboxes configured. Use --mailbox NAME or set default_mailbox.\n");
     for (mname, mconf) in &mailboxes {
         if let Some(p) = mconf.get("path").and_then(|v| v.as_str()) {
-            eprintln!("  {}  {}", mname, p);
+            msg.push_str(&format!("\n  {}  {}", mname, p));
         }
     }
-    std::process::exit(1);
+    Err(CorkyError::Config(msg).into())
 }
 
 /// Register a mailbox, auto-default if first.
@@ -0,0 +1,139 @@
+//! Audit log of actions that leave the machine or can't be undone.
+//!
+//! Every send, IMAP APPEND, server-side delete, Gmail filter change, social
+//! post, mailbox push, and GitHub repo deletion is appended to
+//! `logs/audit.log` in the data dir, one JSON object per line, with the
+//! corky command line that did it. The log is never trimmed or rotated.
+//! `corky audit tail` reads it.
+
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::journal::logs_dir;
+
+/// One recorded action.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// UTC time, RFC 3339.
+    pub time: String,
+    /// `send`, `imap append`, `imap delete`, `mailbox push`, `repo delete`.
+    pub action: String,
+    /// What it was done to: recipients, folder, mailbox, repo.
+    pub target: String,
+    /// The corky command line, without the program name.
+    pub command: Vec<String>,
+    /// `ok`, or `error: MESSAGE`.
+    pub result: String,
+}
+
+pub fn audit_file() -> PathBuf {
+    logs_dir().join("audit.log")
+}
+
+fn append(path: &Path, entry: &AuditEntry) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", serde_json::to_string(entry)?)?;
+    Ok(())
+}
+
+/// Record `action` on `target` and how it went. Best-effort: a log that
+/// can't be written is a warning, never a failure of the action itself.
+pub fn record<T>(action: &str, target: &str, result: &Result<T>) {
+    let entry = AuditEntry {
+        time: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+        action: action.to_string(),
+        target: target.to_string(),
+        command: std::env::args().skip(1).collect(),
+        result: match result {
+            Ok(_) => "ok".to_string(),
+            Err(e) => format!("error: {:#}", e),
+        },
+    };
+    let path = audit_file();
+    if let Err(e) = append(&path, &entry) {
+        eprintln!("Warning: could not write {}: {:#}", path.display(), e);
+    }
+}
+
+/// The newest `n` entries, oldest first. Unreadable lines are skipped.
+pub fn recent(path: &Path, n: usize) -> Vec<AuditEntry> {
+    let text = std::fs::read_to_string(path).unwrap_or_default();
+    let entries: Vec<AuditEntry> = text
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    let skip = entries.len().saturating_sub(n);
+    entries.into_iter().skip(skip).collect()
+}
+
+/// corky audit tail [-n N] [--json]
+pub fn tail(n: usize, json: bool) -> Result<()> {
+    let entries = recent(&audit_file(), n);
+    if json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+    if entries.is_empty() {
+        println!("No audited actions.");
+        return Ok(());
+    }
+    for e in &entries {
+        println!(
+            "{}  {}  {}  [{}]  corky {}",
+            e.time,
+            e.action,
+            e.target,
+            e.result,
+            e.command.join(" ")
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(action: &str, result: &str) -> AuditEntry {
+        AuditEntry {
+            time: "2025-02-10T10:00:00Z".to_string(),
+            action: action.to_string(),
+            target: "alice@example.com".to_string(),
+            command: vec!["draft".to_string(), "send".to_string()],
+            result: result.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_append_and_recent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs/audit.log");
+        assert!(recent(&path, 5).is_empty());
+        append(&path, &entry("send", "ok")).unwrap();
+        append(&path, &entry("imap append", "error: refused")).unwrap();
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(b"not json\n")
+            .unwrap();
+        append(&path, &entry("repo delete", "ok")).unwrap();
+
+        let all = recent(&path, 10);
+        assert_eq!(all.len(), 3);
+        assert_eq!(all[0], entry("send", "ok"));
+        let last = recent(&path, 2);
+        assert_eq!(last[0].action, "imap append");
+        assert_eq!(last[0].result, "error: refused");
+        assert_eq!(last[1].action, "repo delete");
+    }
+}
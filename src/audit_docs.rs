@@ -1,10 +1,548 @@
 //! Audit instruction files against the codebase.
 //!
-//! Delegates to the shared `instruction_files` crate with corky-specific config.
+//! Delegates to the shared `instruction_files` crate with corky-specific config,
+//! then runs corky's own rules, tunable via `[audit]` in .corky.toml.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use instruction_files::AuditConfig;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::json;
+#[cfg(unix)]
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-pub fn run() -> Result<()> {
-    instruction_files::run(&AuditConfig::corky(), None)
+use crate::config::corky_config::{self, AuditSettings};
+use crate::error::CorkyError;
+
+/// Combined line budget when `[audit] budget` is unset.
+pub const DEFAULT_BUDGET: usize = 1000;
+
+/// Instruction files audited by default (relative to the repo root).
+const DEFAULT_FILES: &[&str] = &["AGENTS.md", "README.md", "SKILL.md"];
+
+/// Files whose links and path references are checked by `dead-link`.
+const LINK_FILES: &[&str] = &["AGENTS.md", "SKILL.md"];
+
+static MD_LINK_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\]\(([^)\s]+)\)").unwrap());
+static CODE_SPAN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"`([^`\s]+)`").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// One audit result.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Finding {
+    pub rule: &'static str,
+    pub severity: Severity,
+    pub file: Option<PathBuf>,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// Effective severity for a rule: `[audit.severity]` override, else the default.
+/// Returns None when the rule is set to "off".
+fn rule_severity(
+    settings: &AuditSettings,
+    rule: &str,
+    default: Severity,
+) -> Result<Option<Severity>> {
+    match settings.severity.get(rule).map(|s| s.as_str()) {
+        None => Ok(Some(default)),
+        Some("error") => Ok(Some(Severity::Error)),
+        Some("warning") | Some("warn") => Ok(Some(Severity::Warning)),
+        Some("off") => Ok(None),
+        Some(other) => bail!(
+            "Invalid [audit.severity] {} = '{}' (expected error, warning, or off)",
+            rule,
+            other
+        ),
+    }
+}
+
+/// Default files plus `[audit] include` globs, deduplicated, existing files only.
+pub fn instruction_files(root: &Path, settings: &AuditSettings) -> Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = DEFAULT_FILES.iter().map(|f| root.join(f)).collect();
+    for pattern in &settings.include {
+        let full = root.join(pattern);
+        for entry in glob::glob(&full.to_string_lossy())? {
+            files.push(entry?);
+        }
+    }
+    let mut seen = std::collections::HashSet::new();
+    files.retain(|f| f.is_file() && seen.insert(f.clone()));
+    Ok(files)
+}
+
+/// Count lines, skipping sections whose heading is in `informational`
+/// (case-insensitive). A section ends at the next heading of the same or higher level.
+pub fn budget_lines(text: &str, informational: &[String]) -> usize {
+    let mut count = 0;
+    let mut skip_level: Option<usize> = None;
+    for line in text.lines() {
+        let level = line.chars().take_while(|&c| c == '#').count();
+        if level > 0 && line[level..].starts_with(' ') {
+            if skip_level.is_some_and(|l| level <= l) {
+                skip_level = None;
+            }
+            let title = line[level..].trim();
+            if skip_level.is_none() && informational.iter().any(|h| h.eq_ignore_ascii_case(title)) {
+                skip_level = Some(level);
+            }
+        }
+        if skip_level.is_none() {
+            count += 1;
+        }
+    }
+    count
+}
+
+/// Rule `line-budget`: combined instruction files stay within the budget.
+fn check_line_budget(
+    root: &Path,
+    files: &[PathBuf],
+    settings: &AuditSettings,
+) -> Result<Vec<Finding>> {
+    let Some(severity) = rule_severity(settings, "line-budget", Severity::Error)? else {
+        return Ok(Vec::new());
+    };
+    let budget = settings.budget.unwrap_or(DEFAULT_BUDGET);
+    let mut total = 0;
+    let mut parts = Vec::new();
+    for file in files {
+        let lines = budget_lines(
+            &std::fs::read_to_string(file)?,
+            &settings.informational_headings,
+        );
+        total += lines;
+        let name = file.strip_prefix(root).unwrap_or(file);
+        parts.push(format!("{} {}", name.display(), lines));
+    }
+    if total <= budget {
+        return Ok(Vec::new());
+    }
+    Ok(vec![Finding {
+        rule: "line-budget",
+        severity,
+        file: None,
+        line: None,
+        message: format!(
+            "Instruction files total {} lines, over the {}-line budget ({})",
+            total,
+            budget,
+            parts.join(", ")
+        ),
+    }])
+}
+
+/// Target of a relative markdown link, without its `#fragment`.
+/// None for URLs, mail links, and same-page anchors.
+fn link_target(href: &str) -> Option<&str> {
+    if href.starts_with('#') || href.contains("://") || href.starts_with("mailto:") {
+        return None;
+    }
+    let path = href.split('#').next().unwrap_or(href);
+    (!path.is_empty()).then_some(path)
+}
+
+/// A code span that looks like a repo path: has a `/`, no placeholders or
+/// globs, and isn't a command, flag, or Rust path.
+fn path_reference(span: &str) -> Option<&str> {
+    let looks_like_path = span.contains('/')
+        && !span.starts_with(['-', '~', '/', '$'])
+        && !span.contains("://")
+        && !span.contains("::")
+        && !span.contains(['*', '?', '{', '}', '<', '>', '[', ']', '=', '(', '|']);
+    looks_like_path.then(|| span.trim_end_matches([':', ',', '.']))
+}
+
+/// Rule `dead-link`: relative links and `path/to/file` code spans in
+/// AGENTS.md/SKILL.md must resolve. Links resolve against the file's directory,
+/// code spans against the repo root. Code spans whose first segment doesn't
+/// exist (e.g. runtime `mail/` paths) are skipped; fenced blocks are ignored.
+fn check_dead_links(root: &Path, settings: &AuditSettings) -> Result<Vec<Finding>> {
+    let Some(severity) = rule_severity(settings, "dead-link", Severity::Error)? else {
+        return Ok(Vec::new());
+    };
+    let mut findings = Vec::new();
+    for name in LINK_FILES {
+        let path = root.join(name);
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let base = path.parent().unwrap_or(root);
+        let mut in_fence = false;
+        for (idx, line) in text.lines().enumerate() {
+            if line.trim_start().starts_with("```") {
+                in_fence = !in_fence;
+                continue;
+            }
+            if in_fence {
+                continue;
+            }
+            let mut dead = Vec::new();
+            for cap in MD_LINK_RE.captures_iter(line) {
+                let target = link_target(&cap[1]).filter(|t| !base.join(t).exists());
+                if let Some(target) = target {
+                    dead.push(format!("Broken link: {}", target));
+                }
+            }
+            for cap in CODE_SPAN_RE.captures_iter(line) {
+                let Some(reference) = path_reference(&cap[1]) else {
+                    continue;
+                };
+                let first = reference.split('/').next().unwrap_or_default();
+                if root.join(first).exists() && !root.join(reference).exists() {
+                    dead.push(format!("Missing path: {}", reference));
+                }
+            }
+            findings.extend(dead.into_iter().map(|message| Finding {
+                rule: "dead-link",
+                severity,
+                file: Some(PathBuf::from(name)),
+                line: Some(idx + 1),
+                message,
+            }));
+        }
+    }
+    Ok(findings)
+}
+
+/// Run corky's rules against the repo at `root`.
+pub fn audit(root: &Path, settings: &AuditSettings) -> Result<Vec<Finding>> {
+    let files = instruction_files(root, settings)?;
+    let mut findings = check_line_budget(root, &files, settings)?;
+    findings.extend(check_dead_links(root, settings)?);
+    findings.sort_by(|a, b| (&a.file, a.line).cmp(&(&b.file, b.line)));
+    Ok(findings)
+}
+
+fn print_text(findings: &[Finding]) {
+    for f in findings {
+        let location = match (&f.file, f.line) {
+            (Some(file), Some(line)) => format!("{}:{}: ", file.display(), line),
+            (Some(file), None) => format!("{}: ", file.display()),
+            _ => String::new(),
+        };
+        println!(
+            "{}{} [{}] {}",
+            location,
+            f.severity.as_str(),
+            f.rule,
+            f.message
+        );
+    }
+}
+
+/// Render findings as a JSON object with error/warning counts.
+pub fn to_json(findings: &[Finding]) -> Result<String> {
+    let count = |sev: Severity| findings.iter().filter(|f| f.severity == sev).count();
+    let doc = json!({
+        "findings": findings,
+        "errors": count(Severity::Error),
+        "warnings": count(Severity::Warning),
+    });
+    Ok(serde_json::to_string_pretty(&doc)?)
+}
+
+/// Render findings as a SARIF 2.1.0 log (one run, tool `corky audit-docs`).
+pub fn to_sarif(findings: &[Finding]) -> Result<String> {
+    let mut rule_ids: Vec<&str> = findings.iter().map(|f| f.rule).collect();
+    rule_ids.sort();
+    rule_ids.dedup();
+    let rules: Vec<_> = rule_ids.iter().map(|id| json!({ "id": id })).collect();
+    let results: Vec<_> = findings
+        .iter()
+        .map(|f| {
+            let mut result = json!({
+                "ruleId": f.rule,
+                "level": f.severity.as_str(),
+                "message": { "text": f.message },
+            });
+            if let Some(file) = &f.file {
+                let mut location = json!({
+                    "artifactLocation": { "uri": file.to_string_lossy().replace('\\', "/") },
+                });
+                if let Some(line) = f.line {
+                    location["region"] = json!({ "startLine": line });
+                }
+                result["locations"] = json!([{ "physicalLocation": location }]);
+            }
+            result
+        })
+        .collect();
+    let doc = json!({
+        "$schema": "https://json.schemastore.org/sarif-2.1.0.json",
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "corky audit-docs",
+                    "informationUri": "https://github.com/btakita/corky",
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }],
+    });
+    Ok(serde_json::to_string_pretty(&doc)?)
+}
+
+/// Points stdout at stderr until dropped, so the shared audit's report
+/// doesn't interleave with machine-readable output.
+#[cfg(unix)]
+struct StdoutToStderr(libc::c_int);
+
+#[cfg(unix)]
+impl StdoutToStderr {
+    fn new() -> Option<Self> {
+        std::io::stdout().flush().ok();
+        let saved = unsafe { libc::dup(libc::STDOUT_FILENO) };
+        if saved < 0 {
+            return None;
+        }
+        unsafe { libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) };
+        Some(Self(saved))
+    }
+}
+
+#[cfg(unix)]
+impl Drop for StdoutToStderr {
+    fn drop(&mut self) {
+        std::io::stdout().flush().ok();
+        unsafe {
+            libc::dup2(self.0, libc::STDOUT_FILENO);
+            libc::close(self.0);
+        }
+    }
+}
+
+/// The shared audit's config: corky's defaults with the `[audit]` budget
+/// and informational headings.
+fn shared_config(settings: &AuditSettings) -> AuditConfig {
+    AuditConfig {
+        line_budget: settings.budget.unwrap_or(DEFAULT_BUDGET),
+        informational_headings: settings.informational_headings.clone(),
+        ..AuditConfig::corky()
+    }
+}
+
+/// Run the shared `instruction_files` audit; a failure becomes an
+/// `instruction-files` finding at its `[audit.severity]`.
+fn shared_audit(settings: &AuditSettings, quiet_stdout: bool) -> Result<Option<Finding>> {
+    let Some(severity) = rule_severity(settings, "instruction-files", Severity::Error)? else {
+        return Ok(None);
+    };
+    #[cfg(unix)]
+    let _guard = if quiet_stdout {
+        StdoutToStderr::new()
+    } else {
+        None
+    };
+    #[cfg(not(unix))]
+    let _ = quiet_stdout;
+    Ok(instruction_files::run(&shared_config(settings), None)
+        .err()
+        .map(|e| Finding {
+            rule: "instruction-files",
+            severity,
+            file: None,
+            line: None,
+            message: e.to_string(),
+        }))
+}
+
+/// corky audit-docs [--format text|json|sarif] [--warn-only]
+pub fn run(format: &str, warn_only: bool) -> Result<()> {
+    if !matches!(format, "text" | "json" | "sarif") {
+        bail!(
+            "Unsupported format: {}. Use 'text', 'json', or 'sarif'.",
+            format
+        );
+    }
+
+    let settings = corky_config::try_load_config(None)
+        .and_then(|c| c.audit)
+        .unwrap_or_default();
+    let mut findings: Vec<Finding> = shared_audit(&settings, format != "text")?
+        .into_iter()
+        .collect();
+    findings.extend(audit(Path::new("."), &settings)?);
+
+    match format {
+        "json" => println!("{}", to_json(&findings)?),
+        "sarif" => println!("{}", to_sarif(&findings)?),
+        _ => print_text(&findings),
+    }
+
+    let errors = findings
+        .iter()
+        .filter(|f| f.severity == Severity::Error)
+        .count();
+    if errors > 0 && !warn_only {
+        return Err(CorkyError::Validation(format!("audit-docs: {} error(s)", errors)).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_lines_skips_informational_sections() {
+        let text = "# Title\nintro\n## Changelog\n- a\n- b\n### Detail\nx\n## Usage\nrun it\n";
+        assert_eq!(budget_lines(text, &[]), 9);
+        // Changelog and its nested Detail subsection are skipped; Usage counts again.
+        assert_eq!(budget_lines(text, &["changelog".to_string()]), 4);
+    }
+
+    #[test]
+    fn test_line_budget_and_severity() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::write(dir.path().join("AGENTS.md"), "a\nb\nc\n").unwrap();
+        std::fs::create_dir_all(dir.path().join("docs")).unwrap();
+        std::fs::write(dir.path().join("docs/extra.md"), "d\ne\n").unwrap();
+
+        let mut settings = AuditSettings {
+            budget: Some(4),
+            ..Default::default()
+        };
+        assert!(audit(dir.path(), &settings).unwrap().is_empty());
+
+        settings.include = vec!["docs/*.md".to_string()];
+        let findings = audit(dir.path(), &settings).unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].rule, "line-budget");
+        assert_eq!(findings[0].severity, Severity::Error);
+
+        settings
+            .severity
+            .insert("line-budget".to_string(), "warning".to_string());
+        assert_eq!(
+            audit(dir.path(), &settings).unwrap()[0].severity,
+            Severity::Warning
+        );
+
+        settings
+            .severity
+            .insert("line-budget".to_string(), "off".to_string());
+        assert!(audit(dir.path(), &settings).unwrap().is_empty());
+
+        settings
+            .severity
+            .insert("line-budget".to_string(), "loud".to_string());
+        assert!(audit(dir.path(), &settings).is_err());
+    }
+
+    #[test]
+    fn test_shared_audit_settings() {
+        let mut settings = AuditSettings {
+            budget: Some(400),
+            informational_headings: vec!["Changelog".to_string()],
+            ..Default::default()
+        };
+        let config = shared_config(&settings);
+        assert_eq!(config.line_budget, 400);
+        assert_eq!(config.informational_headings, vec!["Changelog"]);
+        assert_eq!(
+            shared_config(&AuditSettings::default()).line_budget,
+            DEFAULT_BUDGET
+        );
+
+        settings
+            .severity
+            .insert("instruction-files".to_string(), "off".to_string());
+        assert!(shared_audit(&settings, false).unwrap().is_none());
+        settings
+            .severity
+            .insert("instruction-files".to_string(), "loud".to_string());
+        assert!(shared_audit(&settings, false).is_err());
+    }
+
+    #[test]
+    fn test_json_and_sarif_output() {
+        let findings = vec![
+            Finding {
+                rule: "line-budget",
+                severity: Severity::Warning,
+                file: None,
+                line: None,
+                message: "over budget".to_string(),
+            },
+            Finding {
+                rule: "dead-link",
+                severity: Severity::Error,
+                file: Some(PathBuf::from("AGENTS.md")),
+                line: Some(12),
+                message: "missing".to_string(),
+            },
+        ];
+
+        let doc: serde_json::Value = serde_json::from_str(&to_json(&findings).unwrap()).unwrap();
+        assert_eq!(doc["errors"], 1);
+        assert_eq!(doc["warnings"], 1);
+        assert_eq!(doc["findings"][0]["severity"], "warning");
+        assert_eq!(doc["findings"][1]["file"], "AGENTS.md");
+
+        let sarif: serde_json::Value = serde_json::from_str(&to_sarif(&findings).unwrap()).unwrap();
+        assert_eq!(sarif["version"], "2.1.0");
+        let results = &sarif["runs"][0]["results"];
+        assert_eq!(results[0]["level"], "warning");
+        assert!(results[0].get("locations").is_none());
+        let location = &results[1]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "AGENTS.md");
+        assert_eq!(location["region"]["startLine"], 12);
+        assert_eq!(
+            sarif["runs"][0]["tool"]["driver"]["rules"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn test_dead_links_report_line_numbers() {
+        let dir = tempfile::TempDir::new().unwrap();
+        std::fs::create_dir_all(dir.path().join("src")).unwrap();
+        std::fs::write(dir.path().join("src/lib.rs"), "").unwrap();
+        std::fs::write(dir.path().join("README.md"), "").unwrap();
+        std::fs::write(
+            dir.path().join("AGENTS.md"),
+            "# Agents\n\
+See [readme](README.md#usage) and [docs](https://example.com).\n\
+Code lives in `src/lib.rs` and `src/gone.rs`.\n\
+Data goes in `mail/conversations/` and `mailboxes/{name}/`.\n\
+Broken: [spec](docs/SPEC.md), flag `--dir/path`.\n\
+```\n\
+src/not-checked.rs\n\
+```\n",
+        )
+        .unwrap();
+
+        let findings = audit(dir.path(), &AuditSettings::default()).unwrap();
+        let dead: Vec<_> = findings.iter().filter(|f| f.rule == "dead-link").collect();
+        assert_eq!(dead.len(), 2);
+        assert_eq!(dead[0].file.as_deref(), Some(Path::new("AGENTS.md")));
+        assert_eq!(dead[0].line, Some(3));
+        assert_eq!(dead[0].message, "Missing path: src/gone.rs");
+        assert_eq!(dead[1].line, Some(5));
+        assert_eq!(dead[1].message, "Broken link: docs/SPEC.md");
+    }
 }
@@ -41,6 +41,9 @@ fn api_delete(token: &str, url: &str) -> Result<()> {
 /// With `--all`, deletes the entire recurring series (not just visible instances).
 /// This works by deleting the series root event using the `recurring_event_id`.
 pub fn run(query: &str, all_events: bool, dry_run: bool, account: Option<&str>) -> Result<()> {
+    if !dry_run {
+        crate::util::ensure_unrestricted("Deleting calendar events")?;
+    }
     let token = auth::get_access_token(account)?;
     let events = list::fetch_events(&token, Some(query), 25)?;
 
@@ -79,6 +82,11 @@ pub fn run(query: &str, all_events: bool, dry_run: bool, account: Option<&str>)
             println!("\n(dry run — would delete {} series)", series_ids.len());
             return Ok(());
         }
+        let question = format!("Delete {} recurring series?", series_ids.len());
+        if !crate::output::confirm(&question)? {
+            println!("\nKept {} series.", series_ids.len());
+            return Ok(());
+        }
 
         let mut deleted = 0;
         for series_id in &series_ids {
@@ -86,7 +94,9 @@ pub fn run(query: &str, all_events: bool, dry_run: bool, account: Option<&str>)
                 "{}/calendars/primary/events/{}",
                 CALENDAR_API, series_id,
             );
-            match api_delete(&token, &url) {
+            let result = api_delete(&token, &url);
+            crate::audit::record("calendar delete", &format!("series {}", series_id), &result);
+            match result {
                 Ok(()) => {
                     deleted += 1;
                     println!("  Deleted series: {}", series_id);
@@ -110,6 +120,10 @@ pub fn run(query: &str, all_events: bool, dry_run: bool, account: Option<&str>)
             println!("\n(dry run — no events deleted)");
             return Ok(());
         }
+        if !crate::output::confirm(&format!("Delete these {} event(s)?", events.len()))? {
+            println!("\nKept {} event(s).", events.len());
+            return Ok(());
+        }
 
         let mut deleted = 0;
         for event in &events {
@@ -117,7 +131,10 @@ pub fn run(query: &str, all_events: bool, dry_run: bool, account: Option<&str>)
                 "{}/calendars/primary/events/{}",
                 CALENDAR_API, event.id,
             );
-            match api_delete(&token, &url) {
+            let result = api_delete(&token, &url);
+            let target = format!("{} ({})", event.id, event.summary);
+            crate::audit::record("calendar delete", &target, &result);
+            match result {
                 Ok(()) => {
                     deleted += 1;
                     println!("  Deleted: {}", event.summary);
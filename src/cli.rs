@@ -8,6 +8,22 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub mailbox: Option<String>,
 
+    /// Disable colored output (also honors NO_COLOR and non-TTY stdout)
+    #[arg(long, global = true)]
+    pub no_color: bool,
+
+    /// Errors only: no progress lines or summaries
+    #[arg(long, short, global = true)]
+    pub quiet: bool,
+
+    /// Answer yes to confirmation prompts (orphan deletion, repo deletion, ...)
+    #[arg(long, short = 'y', global = true)]
+    pub yes: bool,
+
+    /// Disable sending, server-side deletion, and repo deletion (also CORKY_RESTRICTED=1)
+    #[arg(long, global = true)]
+    pub restricted: bool,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -55,21 +71,100 @@ pub enum Commands {
         /// Overwrite existing .corky.toml
         #[arg(long)]
         force: bool,
+
+        /// Also install a bundled skill besides corky (repeatable, e.g. triage)
+        #[arg(long = "with-skill", value_name = "NAME")]
+        with_skills: Vec<String>,
+
+        /// Add [accounts.NAME] to an existing .corky.toml instead of creating one
+        #[arg(long, value_name = "NAME")]
+        add_account: Option<String>,
+
+        /// With --add-account: list the account's IMAP folders to pick labels
+        #[arg(long, requires = "add_account")]
+        list_folders: bool,
+
+        /// Import accounts found in mbsync/offlineimap/Thunderbird configs without asking
+        #[arg(long, conflicts_with = "no_detect")]
+        import: bool,
+
+        /// Don't look for other mail tools' configs
+        #[arg(long)]
+        no_detect: bool,
     },
 
     /// Sync email threads to Markdown
     Sync {
+        /// Print the end-of-run summary as JSON (no progress output)
+        #[arg(long)]
+        json: bool,
+
+        /// Store headers only; fetch bodies later with `corky hydrate`
+        #[arg(long)]
+        headers_only: bool,
+
+        /// Stop at the first account that fails instead of syncing the rest
+        #[arg(long)]
+        fail_fast: bool,
+
+        /// Pick labels for accounts that have none, from the server's folders
+        #[arg(long, conflicts_with = "json")]
+        interactive: bool,
+
+        /// After each account syncs, push its pending drafts from DIR on the same connection
+        #[arg(long, value_name = "DIR")]
+        then_push: Option<PathBuf>,
+
+        /// Report where the time went (connect, search, fetch, parse, merge, ...) on stderr
+        #[arg(long)]
+        profile: bool,
+
         #[command(subcommand)]
         command: Option<SyncCommands>,
     },
 
-    /// Gmail OAuth setup
-    SyncAuth,
+    /// OAuth setup for an account (Google, Microsoft, or OIDC)
+    SyncAuth {
+        /// Account name (default: the default account)
+        #[arg(long)]
+        account: Option<String>,
+
+        /// Use the device-code flow (enter a code on another device)
+        #[arg(long, conflicts_with = "browser")]
+        device: bool,
+
+        /// Use the browser flow with a localhost redirect
+        #[arg(long)]
+        browser: bool,
+    },
+
+    /// Fetch bodies for messages stored by `sync --headers-only`
+    Hydrate {
+        /// Conversation slug
+        #[arg(required_unless_present = "label")]
+        slug: Option<String>,
+
+        /// Hydrate every conversation with this label
+        #[arg(long, conflicts_with = "slug")]
+        label: Option<String>,
+    },
 
     /// List IMAP folders for an account
     ListFolders {
         /// Account name from .corky.toml
         account: Option<String>,
+
+        /// Print folders (or accounts) as JSON
+        #[arg(long, conflicts_with_all = ["add", "select"])]
+        json: bool,
+
+        /// Pick folders from the listing to add to the account's labels
+        #[arg(long)]
+        add: bool,
+
+        /// Add these folders without prompting (implies --add)
+        #[arg(long, num_args = 1..)]
+        select: Vec<String>,
     },
 
     /// Push a draft markdown file as an email draft
@@ -81,6 +176,14 @@ pub enum Commands {
         /// Send the email immediately instead of saving as a draft
         #[arg(long)]
         send: bool,
+
+        /// Send from this account instead of the draft's
+        #[arg(long)]
+        account: Option<String>,
+
+        /// Send from the account with this address instead of the draft's
+        #[arg(long)]
+        from: Option<String>,
     },
 
     /// Add a label to an account's sync config
@@ -117,12 +220,31 @@ pub enum Commands {
     },
 
     /// IMAP polling daemon
+    #[command(args_conflicts_with_subcommands = true)]
     Watch {
         /// Poll interval in seconds
         #[arg(long)]
         interval: Option<u64>,
+
+        /// Run one poll cycle and exit (for cron or systemd timers)
+        #[arg(long, conflicts_with = "interval")]
+        once: bool,
+
+        #[command(subcommand)]
+        command: Option<WatchCommands>,
+    },
+
+    /// Show accounts, last sync, watch activity, review drafts, and recent errors
+    Status {
+        /// Number of recent errors to show
+        #[arg(short = 'n', long, default_value_t = 5)]
+        errors: usize,
     },
 
+    /// Review the log of sends, pushes, and deletions
+    #[command(subcommand)]
+    Audit(AuditCommands),
+
     /// Install an agent skill (legacy — use `skill install` instead)
     #[command(hide = true)]
     InstallSkill {
@@ -135,12 +257,28 @@ pub enum Commands {
     Skill(SkillCommands),
 
     /// Audit instruction files
-    AuditDocs,
+    AuditDocs {
+        /// Output format: text, json, or sarif
+        #[arg(long, default_value = "text")]
+        format: String,
+
+        /// Report findings but exit 0 even when errors are found
+        #[arg(long)]
+        warn_only: bool,
+    },
 
     /// Show command reference
     Help {
         /// Filter commands by name
         filter: Option<String>,
+
+        /// Write man pages (one per command) to OUT_DIR
+        #[arg(long, value_name = "OUT_DIR")]
+        man: Option<PathBuf>,
+
+        /// Write the command reference as markdown (OUT_DIR/corky.md)
+        #[arg(long, value_name = "OUT_DIR")]
+        markdown: Option<PathBuf>,
     },
 
     /// Find threads awaiting a reply
@@ -152,12 +290,74 @@ pub enum Commands {
         /// Name to match as 'your' messages
         #[arg(long = "from")]
         from_name: Option<String>,
+
+        /// Include threads tagged `newsletter`
+        #[arg(long)]
+        include_newsletters: bool,
+
+        /// Group by the last sender's contact (`*@domain` emails included)
+        #[arg(long)]
+        by_contact: bool,
+    },
+
+    /// List newsletter threads with their unsubscribe targets
+    Newsletters {
+        /// Scope: "." for root only, mailbox name, or omit for all
+        scope: Option<String>,
+    },
+
+    /// Unsubscribe from a newsletter thread (sends mailto: or prints the link)
+    Unsubscribe {
+        /// Conversation slug
+        slug: String,
+    },
+
+    /// Add a sender address or domain to [blocklist]
+    Block {
+        /// Address (user@host) or domain (@host or host)
+        addr: String,
+
+        /// Also delete existing threads whose messages are all from this sender
+        #[arg(long)]
+        delete: bool,
+    },
+
+    /// Render conversations into a static HTML site
+    Publish {
+        /// Scope: "." for root only, mailbox name, or omit for all
+        scope: Option<String>,
+
+        /// Output directory for the site
+        #[arg(long, short)]
+        output: PathBuf,
+
+        /// Only include threads with this label (repeatable)
+        #[arg(long)]
+        label: Vec<String>,
+    },
+
+    /// Export thread metadata for spreadsheets and BI tools
+    ExportIndex {
+        /// Scope: "." for root only, mailbox name, or omit for all
+        scope: Option<String>,
+
+        /// Output format: csv or json
+        #[arg(long, default_value = "csv")]
+        format: String,
+
+        /// Write to a file instead of stdout
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+
+        /// Owner name for the unanswered column (default: [owner] name)
+        #[arg(long = "from")]
+        from_name: Option<String>,
     },
 
     /// Validate draft markdown files
     #[command(hide = true)]
     ValidateDraft {
-        /// Draft markdown file(s) to validate
+        /// Draft markdown file(s) or directories to validate
         #[arg(required = true)]
         files: Vec<PathBuf>,
     },
@@ -166,6 +366,45 @@ pub enum Commands {
     #[command(subcommand)]
     Draft(DraftCommands),
 
+    /// Create a new draft and open it in $EDITOR
+    Compose {
+        /// Recipient email address (default: the contact's first email)
+        #[arg(long, required_unless_present_any = ["contact", "reply"])]
+        to: Option<String>,
+
+        /// Subject line
+        #[arg(long)]
+        subject: Option<String>,
+
+        /// Contact from .corky.toml to write to
+        #[arg(long)]
+        contact: Option<String>,
+
+        /// Start from drafts/templates/NAME.md
+        #[arg(long)]
+        template: Option<String>,
+
+        /// Reply to a conversation by slug (pre-fills to, subject, in_reply_to)
+        #[arg(long)]
+        reply: Option<String>,
+
+        /// Sending account name from .corky.toml
+        #[arg(long)]
+        account: Option<String>,
+
+        /// Create in a mailbox's drafts/ instead of root
+        #[arg(long)]
+        mailbox: Option<String>,
+
+        /// Don't open the editor
+        #[arg(long)]
+        no_edit: bool,
+    },
+
+    /// Email template commands
+    #[command(subcommand)]
+    Template(TemplateCommands),
+
     /// Mailbox commands
     #[command(subcommand, alias = "mb")]
     Mailbox(MailboxCommands),
@@ -194,6 +433,10 @@ pub enum Commands {
     #[command(subcommand)]
     Label(LabelCommands),
 
+    /// Conversation thread commands
+    #[command(subcommand)]
+    Thread(ThreadCommands),
+
     /// Google Calendar management
     #[command(subcommand)]
     Cal(CalCommands),
@@ -232,10 +475,85 @@ pub enum Commands {
         diarize: bool,
     },
 
+    /// Upgrade conversation files written in older markdown formats
+    Migrate {
+        /// Scope: "." for root only, mailbox name, or omit for all
+        scope: Option<String>,
+
+        /// Report files needing migration and exit non-zero; write nothing
+        #[arg(long)]
+        check: bool,
+
+        /// Print the full migration plan without writing anything
+        #[arg(long, conflicts_with = "check")]
+        dry_run: bool,
+
+        /// Snapshot the data dir first and roll back if any step fails
+        #[arg(long)]
+        backup: bool,
+    },
+
+    /// Find and fix duplicate, empty, leftover, and stale conversation files
+    Gc {
+        /// Scope: "." for root only, mailbox name, or omit for all
+        scope: Option<String>,
+
+        /// Report problems without fixing them
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Archive or delete old local conversations per [retention] (server mail untouched)
+    Prune {
+        /// Report what would be pruned without changing anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Email yourself a summary of new and unanswered threads now ([digest])
+    Digest {
+        /// Print the digest instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Search conversations through the SQLite mirror (subject, sender, body)
+    Search {
+        /// Text to find (case-insensitive)
+        query: String,
+
+        /// Scope: "." for root only, mailbox name, or omit for all
+        scope: Option<String>,
+
+        /// Only threads with this label
+        #[arg(long)]
+        label: Option<String>,
+    },
+
+    /// Thread, message, label, and sender counts from the SQLite mirror
+    Stats {
+        /// Scope: "." for root only, mailbox name, or omit for all
+        scope: Option<String>,
+
+        /// Number of top senders to show
+        #[arg(long, default_value_t = 10)]
+        top: usize,
+    },
+
     /// Check for updates and upgrade to the latest version.
     Upgrade,
 }
 
+#[derive(Subcommand)]
+pub enum WatchCommands {
+    /// Show the watch log location, last activity, and recent errors
+    Status {
+        /// Number of recent errors to show
+        #[arg(short = 'n', long, default_value_t = 10)]
+        errors: usize,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum ContactCommands {
     /// Add a new contact
@@ -260,6 +578,26 @@ pub enum ContactCommands {
 
     /// Sync CLAUDE.md files between root contacts/ and mailbox contacts/
     Sync,
+
+    /// List conversations involving a contact's emails
+    Threads {
+        /// Contact name
+        name: String,
+
+        /// Write the list to contacts/NAME/threads.md
+        #[arg(long)]
+        write: bool,
+
+        /// Symlink each conversation into contacts/NAME/threads/
+        #[arg(long)]
+        link: bool,
+    },
+
+    /// Regenerate AGENTS.md from the template, keeping filled-in sections
+    Reset {
+        /// Contact name (default: all)
+        name: Option<String>,
+    },
 }
 
 #[derive(Subcommand)]
@@ -276,10 +614,25 @@ pub enum SyncCommands {
     /// Apply routing rules to existing conversations
     Routes,
 
+    /// Recover sync state from its backup and re-derive last UIDs
+    Repair,
+
     /// Push/pull shared mailbox repos
     Mailbox {
         /// Mailbox name (default: all)
         name: Option<String>,
+
+        /// Only pull collaborator changes; don't commit or push
+        #[arg(long, conflicts_with = "push_only")]
+        pull_only: bool,
+
+        /// Only commit and push local changes; don't pull
+        #[arg(long)]
+        push_only: bool,
+
+        /// Push even if outgoing changes look like they contain credentials
+        #[arg(long)]
+        allow_secrets: bool,
     },
 
     /// Import Telegram Desktop JSON export
@@ -311,12 +664,13 @@ pub enum SyncCommands {
 pub enum DraftCommands {
     /// Scaffold a new draft file
     New {
-        /// Subject line
-        subject: String,
+        /// Subject line (defaults to "Re: <subject>" with --reply)
+        #[arg(required_unless_present = "reply")]
+        subject: Option<String>,
 
         /// Recipient email address
-        #[arg(long)]
-        to: String,
+        #[arg(long, required_unless_present = "reply")]
+        to: Option<String>,
 
         /// CC email address
         #[arg(long)]
@@ -341,10 +695,19 @@ pub enum DraftCommands {
         /// Attach a file (can be repeated)
         #[arg(long = "attach")]
         attachments: Vec<String>,
+
+        /// Reply to a conversation (slug): pre-fills subject, To, and In-Reply-To
+        #[arg(long, value_name = "SLUG")]
+        reply: Option<String>,
+
+        /// With --reply, also CC the other recipients of the message
+        #[arg(long, requires = "reply")]
+        reply_all: bool,
     },
     /// Validate draft markdown files
     Validate {
-        /// Files to validate, or scope: "." for root, mailbox name, omit for all
+        /// Files or directories to validate, or scope: "." for root, mailbox name, omit for all
+        /// (with --quiet, only drafts with problems are printed)
         args: Vec<String>,
     },
     /// Push a draft as an email draft or send it
@@ -355,6 +718,14 @@ pub enum DraftCommands {
         /// Send the email immediately instead of saving as a draft
         #[arg(long)]
         send: bool,
+
+        /// Send from this account instead of the draft's
+        #[arg(long)]
+        account: Option<String>,
+
+        /// Send from the account with this address instead of the draft's
+        #[arg(long)]
+        from: Option<String>,
     },
     /// Migrate legacy drafts to YAML frontmatter
     Migrate {
@@ -408,11 +779,64 @@ pub enum MailboxCommands {
     Sync {
         /// Mailbox name (default: all)
         name: Option<String>,
+
+        /// Only pull collaborator changes; don't commit or push
+        #[arg(long, conflicts_with = "push_only")]
+        pull_only: bool,
+
+        /// Only commit and push local changes; don't pull
+        #[arg(long)]
+        push_only: bool,
+
+        /// Push even if outgoing changes look like they contain credentials
+        #[arg(long)]
+        allow_secrets: bool,
+
+        /// Report where the time went (git per mailbox) on stderr
+        #[arg(long)]
+        profile: bool,
     },
 
     /// Check for pending changes
     Status,
 
+    /// Check mailbox structure: dirs, templates, .gitignore, large files, drafts
+    Verify {
+        /// Mailbox name (default: all)
+        name: Option<String>,
+    },
+
+    /// Show collaborator invite status for shared mailboxes
+    Invites {
+        /// Mailbox name (default: all)
+        name: Option<String>,
+    },
+
+    /// Install a pre-push hook that validates drafts and scans for secrets
+    InstallHooks {
+        /// Mailbox name (default: all)
+        name: Option<String>,
+
+        /// Replace an existing pre-push hook not written by corky
+        #[arg(long)]
+        force: bool,
+    },
+
+    /// Run the pre-push checks (called by the installed hook)
+    #[command(hide = true)]
+    PrePush,
+
+    /// Merge two copies of a conversation file (git merge driver)
+    #[command(hide = true)]
+    MergeThread {
+        /// Common ancestor (%O)
+        base: PathBuf,
+        /// Our version, overwritten with the result (%A)
+        ours: PathBuf,
+        /// Their version (%B)
+        theirs: PathBuf,
+    },
+
     /// Remove a mailbox
     Remove {
         /// Mailbox name to remove
@@ -447,6 +871,14 @@ pub enum MailboxCommands {
         /// Name to match as 'your' messages
         #[arg(long = "from")]
         from_name: Option<String>,
+
+        /// Include threads tagged `newsletter`
+        #[arg(long)]
+        include_newsletters: bool,
+
+        /// Group by the last sender's contact (`*@domain` emails included)
+        #[arg(long)]
+        by_contact: bool,
     },
 
     /// Draft commands
@@ -461,6 +893,10 @@ pub enum MailboxCommands {
         /// Regenerate files without pulling/pushing
         #[arg(long)]
         no_sync: bool,
+
+        /// Replace AGENTS.md/README.md even if they have local changes
+        #[arg(long)]
+        force: bool,
     },
 }
 
@@ -595,6 +1031,56 @@ pub enum ScheduleCommands {
     List,
 }
 
+#[derive(Subcommand)]
+pub enum TemplateCommands {
+    /// List templates in drafts/templates/
+    List,
+}
+
+#[derive(Subcommand)]
+pub enum ThreadCommands {
+    /// List conversations with their issue/ticket links
+    List {
+        /// Scope: "." for root only, mailbox name, or omit for all
+        scope: Option<String>,
+
+        /// Only threads linking here (e.g. github.com/org/repo, "#123")
+        #[arg(long)]
+        link: Option<String>,
+    },
+
+    /// Open a conversation in the provider's web UI (Gmail, Fastmail, web_url)
+    OpenWeb {
+        /// Conversation slug
+        slug: String,
+
+        /// Print the link without opening a browser
+        #[arg(long)]
+        print: bool,
+    },
+
+    /// Change a conversation's subject and filename (Thread ID is kept)
+    Rename {
+        /// Conversation slug
+        slug: String,
+
+        /// New subject
+        subject: String,
+    },
+
+    /// Pin a conversation so it is listed first
+    Pin {
+        /// Conversation slug
+        slug: String,
+    },
+
+    /// Remove a conversation's pin
+    Unpin {
+        /// Conversation slug
+        slug: String,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum TopicCommands {
     /// List configured topics
@@ -748,12 +1234,50 @@ pub enum LabelCommands {
     },
 }
 
+#[derive(Subcommand)]
+pub enum AuditCommands {
+    /// Show the newest audited actions
+    Tail {
+        /// Number of entries to show
+        #[arg(short = 'n', long, default_value_t = 20)]
+        n: usize,
+
+        /// Print entries as JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
 #[derive(Subcommand)]
 pub enum SkillCommands {
-    /// Install the skill definition to .claude/skills/corky/SKILL.md
-    Install,
+    /// Install the skill definition (default: .claude/skills/corky/SKILL.md)
+    Install {
+        /// Skill name: corky or triage
+        #[arg(default_value = "corky")]
+        name: String,
+
+        /// Agent tool: claude, opencode, cursor, or agents (AGENTS.md section)
+        #[arg(long, default_value = "claude")]
+        target: String,
+    },
     /// Check if the installed skill matches the binary version
     Check,
+    /// List installed and bundled skills with version and status
+    List,
+    /// Refresh installed skills from the binary (skips local edits)
+    Update {
+        /// Skill name (default: all bundled skills)
+        name: Option<String>,
+
+        /// Overwrite locally modified files
+        #[arg(long)]
+        force: bool,
+    },
+    /// Remove an installed skill
+    Uninstall {
+        /// Skill name
+        name: String,
+    },
 }
 
 #[derive(Subcommand)]
@@ -2,9 +2,10 @@
 
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashMap};
 use std::path::{Path, PathBuf};
 
+use crate::address;
 use crate::resolve;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -17,6 +18,53 @@ pub struct Contact {
     /// Aliases for matching sender names that don't slugify to the directory name.
     #[serde(default)]
     pub aliases: Vec<String>,
+    /// Labels added to threads with mail from this contact (trusted sender
+    /// auto-labeling), so `[routing]` applies whatever folder it arrived in.
+    #[serde(default)]
+    pub labels: Vec<String>,
+}
+
+impl Contact {
+    /// The first of `emails` that is an address to write to, skipping
+    /// `*@domain` wildcards.
+    pub fn first_address(&self) -> Option<&String> {
+        self.emails
+            .iter()
+            .find(|e| address::wildcard_domain(e).is_none())
+    }
+}
+
+/// Contact names by address, for matching many addresses. A contact that
+/// lists the address itself wins over a `*@domain` wildcard; among equals,
+/// the first contact by name.
+#[derive(Debug, Default)]
+pub struct ContactIndex {
+    by_address: HashMap<String, String>,
+    by_domain: HashMap<String, String>,
+}
+
+impl ContactIndex {
+    pub fn new(contacts: &BTreeMap<String, Contact>) -> Self {
+        let mut index = Self::default();
+        for (name, contact) in contacts {
+            for email in &contact.emails {
+                let (map, key) = match address::wildcard_domain(email) {
+                    Some(domain) => (&mut index.by_domain, domain),
+                    None => (&mut index.by_address, address::base(email)),
+                };
+                map.entry(key).or_insert_with(|| name.clone());
+            }
+        }
+        index
+    }
+
+    /// The contact `addr` (`Name <email>` or bare) belongs to.
+    pub fn lookup(&self, addr: &str) -> Option<&str> {
+        self.by_address
+            .get(&address::base(addr))
+            .or_else(|| address::domain_of(addr).and_then(|d| self.by_domain.get(&d)))
+            .map(String::as_str)
+    }
 }
 
 /// Load contacts from [contacts.*] in .corky.toml and return {name: Contact} mapping.
@@ -51,12 +99,7 @@ pub fn load_contacts(path: Option<&Path>) -> Result<BTreeMap<String, Contact>> {
 
 /// Write a single contact to [contacts.{name}] in .corky.toml (format-preserving).
 ///
-/// Not concurrency-safe: uses read-modify-write without file locking.
-/// This is fine for a single-user CLI — the only concurrent scenario would
-/// be `corky watch` (background) while the user runs another command, but
-/// watch only reads config, never writes. If corky ever becomes multi-process
-/// with concurrent writers, add file locking here and in the other
-/// .corky.toml writers (accounts::add_label_to_account, mailbox add/remove/rename).
+/// Holds the config lock for the whole read-modify-write (see `config::lock`).
 pub fn save_contact(
     name: &str,
     contact: &Contact,
@@ -65,6 +108,7 @@ pub fn save_contact(
     let path = path
         .map(PathBuf::from)
         .unwrap_or_else(resolve::corky_toml);
+    let _lock = super::lock::lock(&path)?;
     let content = if path.exists() {
         std::fs::read_to_string(&path)?
     } else {
@@ -101,8 +145,15 @@ pub fn save_contact(
         }
         table.insert("aliases", toml_edit::value(arr));
     }
+    if !contact.labels.is_empty() {
+        let mut arr = toml_edit::Array::new();
+        for l in &contact.labels {
+            arr.push(l.as_str());
+        }
+        table.insert("labels", toml_edit::value(arr));
+    }
     contacts.insert(name, toml_edit::Item::Table(table));
 
-    std::fs::write(&path, doc.to_string())?;
+    super::lock::write(&path, &doc.to_string())?;
     Ok(())
 }
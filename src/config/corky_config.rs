@@ -1,6 +1,6 @@
 //! Unified config type — parse .corky.toml (accounts + routing + mailboxes).
 
-use anyhow::{bail, Result};
+use anyhow::Result;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
@@ -8,11 +8,15 @@ use std::path::{Path, PathBuf};
 use crate::accounts::{Account, OwnerConfig, WatchConfig};
 use crate::config::contact::Contact;
 use crate::config::topic::TopicConfig;
+use crate::error::CorkyError;
 use crate::resolve;
 use crate::social::profiles::Profile;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct CorkyConfig {
+    /// `false` never prompts: confirmations are answered no unless `--yes`.
+    #[serde(default)]
+    pub interactive: Option<bool>,
     #[serde(default)]
     pub owner: Option<OwnerConfig>,
     #[serde(default)]
@@ -20,6 +24,8 @@ pub struct CorkyConfig {
     #[serde(default)]
     pub contacts: HashMap<String, Contact>,
     #[serde(default)]
+    pub groups: HashMap<String, Vec<String>>,
+    #[serde(default)]
     pub routing: HashMap<String, Vec<String>>,
     #[serde(default)]
     pub mailboxes: HashMap<String, MailboxConfig>,
@@ -37,6 +43,202 @@ pub struct CorkyConfig {
     pub transcription: Option<TranscriptionConfig>,
     #[serde(default)]
     pub profiles: HashMap<String, Profile>,
+    #[serde(default)]
+    pub sync: Option<SyncConfig>,
+    #[serde(default)]
+    pub blocklist: Option<BlocklistConfig>,
+    #[serde(default)]
+    pub redact: Option<SecretsConfig>,
+    #[serde(default)]
+    pub audit: Option<AuditSettings>,
+    #[serde(default)]
+    pub retention: Option<RetentionConfig>,
+    #[serde(default)]
+    pub digest: Option<DigestConfig>,
+}
+
+/// `corky audit-docs` tuning (lives in .corky.toml under [audit]).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct AuditSettings {
+    /// Combined line budget for instruction files (default 1000).
+    #[serde(default)]
+    pub budget: Option<usize>,
+    /// Extra instruction-file globs, relative to the repo root.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Headings whose sections don't count toward the budget.
+    #[serde(default)]
+    pub informational_headings: Vec<String>,
+    /// Per-rule severity: "error", "warning", or "off".
+    #[serde(default)]
+    pub severity: HashMap<String, String>,
+}
+
+/// Senders/subjects skipped during sync (lives in .corky.toml under [blocklist]).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct BlocklistConfig {
+    /// Exact sender addresses (case-insensitive).
+    #[serde(default)]
+    pub addresses: Vec<String>,
+    /// Sender domains; also matches subdomains.
+    #[serde(default)]
+    pub domains: Vec<String>,
+    /// Subject regexes.
+    #[serde(default)]
+    pub subjects: Vec<String>,
+}
+
+/// Secrets replaced in every incoming message body at merge time, before
+/// any copy is written (lives in .corky.toml under [redact]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretsConfig {
+    /// Built-in rule sets: "otp", "api_keys", "account_numbers".
+    #[serde(default)]
+    pub presets: Vec<String>,
+    /// Extra regexes; a `(?P<secret>...)` group limits what is replaced.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    #[serde(default = "default_secret_replacement")]
+    pub replacement: String,
+}
+
+fn default_secret_replacement() -> String {
+    "[REDACTED]".to_string()
+}
+
+impl Default for SecretsConfig {
+    fn default() -> Self {
+        Self {
+            presets: Vec::new(),
+            patterns: Vec::new(),
+            replacement: default_secret_replacement(),
+        }
+    }
+}
+
+/// Sync tuning (lives in .corky.toml under [sync]).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SyncConfig {
+    /// Truncate stored message bodies larger than this many bytes (0 = no limit).
+    #[serde(default)]
+    pub max_body_bytes: usize,
+    /// Displayed message date format: "rfc2822" (default) or "iso8601".
+    #[serde(default)]
+    pub date_format: String,
+    /// Displayed message timezone: "" (as sent), "local", "utc", or an offset like "+02:00"
+    /// (checked by [`load_config`]).
+    #[serde(default)]
+    pub timezone: String,
+    /// Conversation filename pattern: "{slug}" (default) or e.g. "{date}-{slug}".
+    #[serde(default)]
+    pub filename_scheme: String,
+    /// Mark fetched messages \Seen on the server (default: leave read state alone).
+    #[serde(default)]
+    pub mark_read: bool,
+    /// Reconnect attempts per account after the server throttles or drops the connection.
+    #[serde(default = "default_throttle_retries")]
+    pub throttle_retries: u32,
+    /// First backoff wait in seconds; doubles with each attempt (capped at 15 minutes).
+    #[serde(default = "default_throttle_backoff")]
+    pub throttle_backoff: u64,
+    /// After sync, refresh contact folders: "" (off), "list" (threads.md), or "link" (threads/).
+    #[serde(default)]
+    pub contact_threads: String,
+    /// Start a new `slug.partN.md` file after this many messages (0 = never split).
+    #[serde(default)]
+    pub split_messages: usize,
+    /// Start a new `slug.partN.md` file once a part's messages pass this many bytes (0 = never split).
+    #[serde(default)]
+    pub split_bytes: usize,
+    /// Extra subject prefixes to ignore in thread keys and slugs, e.g. "[JIRA]" or "[PROJ-*]".
+    #[serde(default)]
+    pub strip_prefixes: Vec<String>,
+    /// Also keep conversations in a query mirror: "" (off) or "sqlite" (needs the `sqlite` feature).
+    #[serde(default)]
+    pub mirror: String,
+    /// Pin threads that have a \Flagged message on the server (`corky thread pin`).
+    #[serde(default)]
+    pub pin_flagged: bool,
+}
+
+fn default_throttle_retries() -> u32 {
+    5
+}
+
+fn default_throttle_backoff() -> u64 {
+    30
+}
+
+impl Default for SyncConfig {
+    fn default() -> Self {
+        Self {
+            max_body_bytes: 0,
+            date_format: String::new(),
+            timezone: String::new(),
+            filename_scheme: String::new(),
+            mark_read: false,
+            throttle_retries: default_throttle_retries(),
+            throttle_backoff: default_throttle_backoff(),
+            contact_threads: String::new(),
+            split_messages: 0,
+            split_bytes: 0,
+            strip_prefixes: Vec::new(),
+            mirror: String::new(),
+            pin_flagged: false,
+        }
+    }
+}
+
+/// Local retention for `corky prune` (lives in .corky.toml under [retention]).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RetentionConfig {
+    /// Prune conversations not updated in this many days (0 = keep forever).
+    #[serde(default)]
+    pub keep_days: u32,
+    /// Keep at most this many conversations per label, newest first (0 = no limit).
+    #[serde(default)]
+    pub max_threads: usize,
+    /// What happens to pruned files: "archive" (default) or "delete".
+    #[serde(default)]
+    pub action: String,
+    /// Where archives are written, relative to the data dir (default "archive").
+    #[serde(default)]
+    pub archive_dir: String,
+    /// Per-label overrides of `keep_days` and `max_threads`.
+    #[serde(default)]
+    pub labels: HashMap<String, RetentionPolicy>,
+}
+
+/// One label's retention limits; unset fields fall back to `[retention]`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct RetentionPolicy {
+    #[serde(default)]
+    pub keep_days: Option<u32>,
+    #[serde(default)]
+    pub max_threads: Option<usize>,
+}
+
+/// Summary email sent by `corky watch` (lives in .corky.toml under [digest]).
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct DigestConfig {
+    /// How often: "hourly", "daily" (default), "weekly", or "off".
+    #[serde(default)]
+    pub schedule: String,
+    /// Local time daily and weekly digests go out, "HH:MM" (default "08:00").
+    #[serde(default)]
+    pub time: String,
+    /// Day weekly digests go out, e.g. "mon" (default) or "friday".
+    #[serde(default)]
+    pub weekday: String,
+    /// Only threads with one of these labels (default: all).
+    #[serde(default)]
+    pub labels: Vec<String>,
+    /// Sending account (default: the default account).
+    #[serde(default)]
+    pub account: String,
+    /// Recipient (default: the sending account's own address).
+    #[serde(default)]
+    pub to: String,
 }
 
 /// Gmail API config + filter rules (lives in .corky.toml under [gmail]).
@@ -92,6 +294,44 @@ pub struct MailboxConfig {
     pub auto_send: bool,
     #[serde(default)]
     pub permissions: HashMap<String, MailboxPermissions>,
+    /// Commit message for `mailbox sync`; supports `{mailbox}`, `{threads}`,
+    /// `{drafts}`, `{files}`, and `{summary}`.
+    #[serde(default)]
+    pub commit_message_template: String,
+    /// `Name <email>` used as author and committer for sync commits.
+    #[serde(default)]
+    pub git_author: String,
+    /// Redaction applied to conversations routed into this mailbox.
+    #[serde(default)]
+    pub redact: RedactConfig,
+}
+
+/// `[mailboxes.NAME.redact]`: rules applied to the mailbox's copy of routed
+/// conversations. The owner's own copy is never redacted.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactConfig {
+    /// Regexes whose matches are replaced with `replacement`.
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    #[serde(default = "default_redaction")]
+    pub replacement: String,
+    /// Regexes; body lines matching any of them are removed.
+    #[serde(default)]
+    pub drop_lines: Vec<String>,
+}
+
+fn default_redaction() -> String {
+    "[redacted]".to_string()
+}
+
+impl Default for RedactConfig {
+    fn default() -> Self {
+        Self {
+            patterns: Vec::new(),
+            replacement: default_redaction(),
+            drop_lines: Vec::new(),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -130,16 +370,37 @@ pub fn load_config(path: Option<&Path>) -> Result<CorkyConfig> {
         .map(PathBuf::from)
         .unwrap_or_else(resolve::corky_toml);
     if !path.exists() {
-        bail!(
+        return Err(CorkyError::Config(format!(
             ".corky.toml not found at {}.\nRun 'corky init' to create it.",
             path.display()
-        );
+        ))
+        .into());
     }
     let content = std::fs::read_to_string(&path)?;
-    let config: CorkyConfig = toml::from_str(&content)?;
+    let config: CorkyConfig = toml::from_str(&content)
+        .map_err(|e| CorkyError::Config(format!("Invalid {}: {}", path.display(), e)))?;
+    if let Some(sync) = &config.sync {
+        if !valid_timezone(&sync.timezone) {
+            return Err(CorkyError::Config(format!(
+                "Invalid [sync] timezone \"{}\" in {}: use \"local\", \"utc\", or an offset \
+                 like \"+02:00\"",
+                sync.timezone,
+                path.display()
+            ))
+            .into());
+        }
+    }
     Ok(config)
 }
 
+/// Whether `tz` is a `[sync] timezone` corky understands: "" (as sent),
+/// "local", "utc", or a fixed `+HH:MM`/`-HH:MM` offset. Zone names like
+/// "Europe/Paris" are not supported.
+pub fn valid_timezone(tz: &str) -> bool {
+    let tz = tz.trim().to_lowercase();
+    matches!(tz.as_str(), "" | "local" | "utc" | "z") || tz.parse::<chrono::FixedOffset>().is_ok()
+}
+
 /// Try loading config, returning None if the file doesn't exist.
 pub fn try_load_config(path: Option<&Path>) -> Option<CorkyConfig> {
     let path = path
@@ -0,0 +1,199 @@
+//! Recipient groups — parse [groups] from .corky.toml and expand group
+//! names in draft To/CC fields.
+//!
+//! ```toml
+//! [groups]
+//! team-leads = ["alex", "sam@example.com", "board"]
+//! ```
+//!
+//! Members are contact names (their first `emails` entry), other group
+//! names, or literal addresses.
+
+use anyhow::{bail, Result};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use crate::address::{self, Address};
+use crate::config::contact::Contact;
+use crate::resolve;
+use crate::util::split_address_list;
+
+/// One group name replaced during expansion.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expansion {
+    pub group: String,
+    pub addresses: Vec<String>,
+}
+
+/// Load groups from [groups] in .corky.toml and return {name: members} mapping.
+pub fn load_groups(path: Option<&Path>) -> Result<BTreeMap<String, Vec<String>>> {
+    let path = path
+        .map(PathBuf::from)
+        .unwrap_or_else(resolve::corky_toml);
+    if !path.exists() {
+        return Ok(BTreeMap::new());
+    }
+    let content = std::fs::read_to_string(&path)?;
+    if content.trim().is_empty() {
+        return Ok(BTreeMap::new());
+    }
+    let raw: toml::Value = toml::from_str(&content)?;
+    let groups_table = raw
+        .as_table()
+        .and_then(|t| t.get("groups"))
+        .and_then(|v| v.as_table());
+    match groups_table {
+        Some(table) => {
+            let mut result = BTreeMap::new();
+            for (name, data) in table {
+                let members: Vec<String> = data.clone().try_into()?;
+                result.insert(name.clone(), members);
+            }
+            Ok(result)
+        }
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// Addresses of `group`'s members, nested groups flattened, in order.
+fn members(
+    group: &str,
+    groups: &BTreeMap<String, Vec<String>>,
+    contacts: &BTreeMap<String, Contact>,
+    stack: &mut Vec<String>,
+    out: &mut Vec<String>,
+) -> Result<()> {
+    if stack.iter().any(|g| g == group) {
+        bail!("Group '{}' includes itself ({} → {})", group, stack.join(" → "), group);
+    }
+    stack.push(group.to_string());
+    for member in &groups[group] {
+        let member = member.trim();
+        if groups.contains_key(member) {
+            members(member, groups, contacts, stack, out)?;
+        } else if let Some(contact) = contacts.get(member) {
+            match contact.first_address() {
+                Some(email) => out.push(email.clone()),
+                None => bail!("Contact '{}' in group '{}' has no emails", member, group),
+            }
+        } else if Address::parse(member).is_some() {
+            out.push(member.to_string());
+        } else {
+            bail!(
+                "Group '{}' member '{}' is not a group, contact, or address",
+                group,
+                member
+            );
+        }
+    }
+    stack.pop();
+    Ok(())
+}
+
+fn push_unique(entries: &mut Vec<String>, addr: String) {
+    if !entries.iter().any(|e| address::same(e, &addr)) {
+        entries.push(addr);
+    }
+}
+
+/// Replace group names in an address field (`To`, `CC`) with their
+/// members' addresses. Other entries are kept as written; an address
+/// already in the field is not repeated. Returns the new field value and
+/// one [`Expansion`] per group used.
+pub fn expand_field(
+    value: &str,
+    groups: &BTreeMap<String, Vec<String>>,
+    contacts: &BTreeMap<String, Contact>,
+) -> Result<(String, Vec<Expansion>)> {
+    let mut entries: Vec<String> = Vec::new();
+    let mut expansions = Vec::new();
+    for entry in split_address_list(value) {
+        if !groups.contains_key(&entry) {
+            push_unique(&mut entries, entry);
+            continue;
+        }
+        let mut addresses = Vec::new();
+        members(&entry, groups, contacts, &mut Vec::new(), &mut addresses)?;
+        for addr in &addresses {
+            push_unique(&mut entries, addr.clone());
+        }
+        expansions.push(Expansion {
+            group: entry,
+            addresses,
+        });
+    }
+    Ok((entries.join(", "), expansions))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup() -> (BTreeMap<String, Vec<String>>, BTreeMap<String, Contact>) {
+        let mut groups = BTreeMap::new();
+        groups.insert(
+            "team-leads".to_string(),
+            vec!["alex".to_string(), "sam@example.com".to_string()],
+        );
+        groups.insert(
+            "everyone".to_string(),
+            vec!["team-leads".to_string(), "Pat <pat@example.com>".to_string()],
+        );
+        let mut contacts = BTreeMap::new();
+        contacts.insert(
+            "alex".to_string(),
+            Contact {
+                emails: vec!["alex@example.com".to_string(), "alex@work.com".to_string()],
+                ..Default::default()
+            },
+        );
+        (groups, contacts)
+    }
+
+    #[test]
+    fn test_expand_group_contacts_and_addresses() {
+        let (groups, contacts) = setup();
+        let (value, expansions) =
+            expand_field("team-leads, bob@example.com", &groups, &contacts).unwrap();
+        assert_eq!(value, "alex@example.com, sam@example.com, bob@example.com");
+        assert_eq!(
+            expansions,
+            vec![Expansion {
+                group: "team-leads".to_string(),
+                addresses: vec!["alex@example.com".to_string(), "sam@example.com".to_string()],
+            }]
+        );
+    }
+
+    #[test]
+    fn test_expand_nested_and_dedup() {
+        let (groups, contacts) = setup();
+        let (value, expansions) =
+            expand_field("Sam <SAM@example.com>, everyone", &groups, &contacts).unwrap();
+        assert_eq!(
+            value,
+            "Sam <SAM@example.com>, alex@example.com, Pat <pat@example.com>"
+        );
+        assert_eq!(expansions[0].addresses.len(), 3);
+    }
+
+    #[test]
+    fn test_expand_without_groups_is_unchanged() {
+        let (groups, contacts) = setup();
+        let (value, expansions) =
+            expand_field("\"Smith, Bob\" <bob@example.com>", &groups, &contacts).unwrap();
+        assert_eq!(value, "\"Smith, Bob\" <bob@example.com>");
+        assert!(expansions.is_empty());
+    }
+
+    #[test]
+    fn test_expand_errors() {
+        let (mut groups, contacts) = setup();
+        groups.insert("loop".to_string(), vec!["loop".to_string()]);
+        groups.insert("bad".to_string(), vec!["nobody".to_string()]);
+        let err = expand_field("loop", &groups, &contacts).unwrap_err();
+        assert!(err.to_string().contains("includes itself"));
+        let err = expand_field("bad", &groups, &contacts).unwrap_err();
+        assert!(err.to_string().contains("'nobody' is not a group, contact, or address"));
+    }
+}
@@ -0,0 +1,139 @@
+//! Advisory locking for config file writers.
+//!
+//! Every read-modify-write of `.corky.toml` (accounts, contacts, topics,
+//! mailboxes, routing, blocklist, profiles) holds an exclusive lock on a
+//! sidecar `{file}.lock` for the whole edit, so `corky watch` and an
+//! interactive command can't interleave and drop each other's changes.
+//! The lock lives in a sidecar because [`write`] replaces the config file
+//! by rename, and a lock on the old inode wouldn't serialize anything.
+
+use anyhow::{bail, Context, Result};
+use fs2::FileExt;
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// How long to wait for another process to finish its edit.
+const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+const LOCK_POLL: Duration = Duration::from_millis(50);
+
+/// Held for the duration of a config edit; the lock is released on drop.
+#[derive(Debug)]
+pub struct ConfigLock {
+    file: File,
+}
+
+impl Drop for ConfigLock {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// The sidecar lock file for a config file: `.corky.toml` → `.corky.toml.lock`.
+pub fn lock_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".lock");
+    path.with_file_name(name)
+}
+
+/// Take the exclusive edit lock for `path`, waiting up to 10 seconds for
+/// another corky process to finish.
+pub fn lock(path: &Path) -> Result<ConfigLock> {
+    let lock_file = lock_path(path);
+    if let Some(parent) = lock_file.parent() {
+        if !parent.as_os_str().is_empty() {
+            std::fs::create_dir_all(parent)?;
+        }
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_file)
+        .with_context(|| format!("Opening lock file {}", lock_file.display()))?;
+    let deadline = Instant::now() + LOCK_TIMEOUT;
+    loop {
+        match file.try_lock_exclusive() {
+            Ok(()) => return Ok(ConfigLock { file }),
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => {
+                if Instant::now() >= deadline {
+                    bail!(
+                        "Another corky process is editing {} (lock held on {})",
+                        path.display(),
+                        lock_file.display()
+                    );
+                }
+                std::thread::sleep(LOCK_POLL);
+            }
+            Err(e) => {
+                return Err(e).with_context(|| format!("Locking {}", lock_file.display()));
+            }
+        }
+    }
+}
+
+/// Replace `path` with `contents` via a temp file and rename, keeping the
+/// file's permissions, so readers never see a half-written config.
+pub fn write(path: &Path, contents: &str) -> Result<()> {
+    let dir = match path.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p,
+        _ => Path::new("."),
+    };
+    let mut tmp = tempfile::NamedTempFile::new_in(dir)?;
+    tmp.write_all(contents.as_bytes())?;
+    tmp.as_file().sync_all()?;
+    if let Ok(meta) = std::fs::metadata(path) {
+        std::fs::set_permissions(tmp.path(), meta.permissions())?;
+    }
+    tmp.persist(path)
+        .with_context(|| format!("Writing {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_path() {
+        assert_eq!(
+            lock_path(Path::new("/data/.corky.toml")),
+            PathBuf::from("/data/.corky.toml.lock")
+        );
+    }
+
+    #[test]
+    fn test_lock_excludes_second_holder() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".corky.toml");
+        let held = lock(&path).unwrap();
+        let other = File::open(lock_path(&path)).unwrap();
+        assert!(other.try_lock_exclusive().is_err());
+        drop(held);
+        assert!(other.try_lock_exclusive().is_ok());
+    }
+
+    #[test]
+    fn test_write_replaces_and_keeps_permissions() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(".corky.toml");
+        std::fs::write(&path, "[owner]\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o640)).unwrap();
+        }
+        write(&path, "[owner]\nname = \"Me\"\n").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "[owner]\nname = \"Me\"\n"
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o640);
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub mod contact;
 pub mod corky_config;
+pub mod group;
+pub mod lock;
 pub mod topic;
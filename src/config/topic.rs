@@ -70,6 +70,7 @@ pub fn save_topic(
     let path = path
         .map(PathBuf::from)
         .unwrap_or_else(resolve::corky_toml);
+    let _lock = super::lock::lock(&path)?;
     let content = if path.exists() {
         std::fs::read_to_string(&path)?
     } else {
@@ -111,7 +112,7 @@ pub fn save_topic(
     }
     topics.insert(name, toml_edit::Item::Table(table));
 
-    std::fs::write(&path, doc.to_string())?;
+    super::lock::write(&path, &doc.to_string())?;
     Ok(())
 }
 
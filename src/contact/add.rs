@@ -121,9 +121,9 @@ professional
     )
 }
 
-/// Create a contact with the default AGENTS.md template.
+/// Create a contact with the AGENTS.md template (custom or default).
 pub fn run(name: &str, emails: &[String]) -> Result<()> {
-    run_with_agents_md(name, emails, &default_agents_md(name))
+    run_with_agents_md(name, emails, &super::reset::agents_md_template(name)?)
 }
 
 /// Create a contact with custom AGENTS.md content (used by from_conversation).
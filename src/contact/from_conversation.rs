@@ -7,7 +7,7 @@ use regex::Regex;
 use super::add;
 use crate::config::corky_config;
 use crate::resolve;
-use crate::sync::markdown::parse_thread_markdown;
+use crate::sync::markdown::read_thread;
 use crate::util::slugify;
 
 static EMAIL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([^>]+)>").unwrap());
@@ -24,7 +24,7 @@ struct Participant {
 /// Algorithm:
 /// 1. Find conversation file — search conversations/{slug}.md, then
 ///    mailboxes/*/conversations/{slug}.md.
-/// 2. Parse thread via parse_thread_markdown().
+/// 2. Parse thread via read_thread() (all parts of a split conversation).
 /// 3. Load config for owner account emails.
 /// 4. Extract non-owner participants from from, to, cc fields.
 /// 5. Handle participant count: 0 = bail, 1 = auto-derive, 2+ = require name.
@@ -34,11 +34,10 @@ struct Participant {
 /// 9. Delegate to add::run_with_agents_md().
 pub fn run(slug: &str, name: Option<&str>) -> Result<()> {
     // 1. Find conversation file
-    let file_path = find_conversation(slug)?;
+    let file_path = resolve::find_conversation(slug)?;
 
     // 2. Parse thread
-    let text = std::fs::read_to_string(&file_path)?;
-    let thread = parse_thread_markdown(&text)
+    let thread = read_thread(&file_path)?
         .ok_or_else(|| anyhow::anyhow!("Failed to parse conversation: {}", file_path.display()))?;
 
     // 3. Load owner emails from config
@@ -90,38 +89,6 @@ pub fn run(slug: &str, name: Option<&str>) -> Result<()> {
     add::run_with_agents_md(&contact_name, &emails, &agents_md)
 }
 
-/// Find a conversation file by slug, searching root then mailboxes.
-fn find_conversation(slug: &str) -> Result<std::path::PathBuf> {
-    let data_dir = resolve::data_dir();
-    let filename = format!("{}.md", slug);
-
-    // Search root conversations/
-    let root_path = data_dir.join("conversations").join(&filename);
-    if root_path.exists() {
-        return Ok(root_path);
-    }
-
-    // Search mailboxes/*/conversations/
-    let mailboxes_dir = data_dir.join("mailboxes");
-    if mailboxes_dir.exists() {
-        if let Ok(entries) = std::fs::read_dir(&mailboxes_dir) {
-            for entry in entries.flatten() {
-                let mb_path = entry.path().join("conversations").join(&filename);
-                if mb_path.exists() {
-                    return Ok(mb_path);
-                }
-            }
-        }
-    }
-
-    anyhow::bail!(
-        "Conversation '{}' not found.\nSearched:\n  - {}\n  - {}",
-        slug,
-        data_dir.join("conversations").display(),
-        mailboxes_dir.join("*/conversations").display()
-    )
-}
-
 /// Load owner email addresses from .corky.toml accounts.
 fn load_owner_emails() -> Vec<String> {
     let config = match corky_config::try_load_config(None) {
@@ -131,7 +98,7 @@ fn load_owner_emails() -> Vec<String> {
     config
         .accounts
         .values()
-        .map(|a| a.user.to_lowercase())
+        .map(|a| a.user.clone())
         .collect()
 }
 
@@ -160,8 +127,8 @@ fn extract_from_field(
     for cap in EMAIL_RE.captures_iter(field) {
         let email = cap[1].to_lowercase();
 
-        // Skip owner emails
-        if owner_emails.iter().any(|o| o == &email) {
+        // Skip owner emails (including +tag aliases)
+        if crate::address::contains(owner_emails, &email) {
             continue;
         }
 
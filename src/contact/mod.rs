@@ -1,4 +1,6 @@
 pub mod add;
 pub mod from_conversation;
 pub mod info;
+pub mod reset;
 pub mod sync;
+pub mod threads;
@@ -0,0 +1,201 @@
+//! Regenerate contact AGENTS.md files from the current template.
+//!
+//! The template is `templates/contact-AGENTS.md` in the data dir when
+//! present (with `{name}` replaced), else the built-in default. Sections the
+//! user has filled in are kept; only the title, untouched sections, and
+//! sections new to the template change. Sections the template doesn't have
+//! are kept at the end.
+
+use anyhow::{bail, Result};
+use std::path::Path;
+
+use crate::config::contact::load_contacts;
+use crate::resolve;
+
+use super::add::default_agents_md;
+
+/// File name of the custom contact template under `templates/`.
+pub const TEMPLATE_FILE: &str = "contact-AGENTS.md";
+
+/// AGENTS.md for a new or reset contact: the custom template if present,
+/// else the default.
+pub fn agents_md_template(name: &str) -> Result<String> {
+    let custom = resolve::templates_dir().join(TEMPLATE_FILE);
+    if custom.exists() {
+        let text = std::fs::read_to_string(&custom)?;
+        return Ok(text.replace("{name}", name));
+    }
+    Ok(default_agents_md(name))
+}
+
+/// A markdown file split on `## ` headings: the text before the first
+/// heading, then (heading line, body) pairs.
+fn split_sections(text: &str) -> (String, Vec<(String, String)>) {
+    let mut preamble = String::new();
+    let mut sections: Vec<(String, String)> = Vec::new();
+    for line in text.split_inclusive('\n') {
+        if line.starts_with("## ") {
+            sections.push((line.trim_end().to_string(), String::new()));
+        } else if let Some((_, body)) = sections.last_mut() {
+            body.push_str(line);
+        } else {
+            preamble.push_str(line);
+        }
+    }
+    (preamble, sections)
+}
+
+/// Whether a section body has anything besides HTML comments and whitespace.
+fn is_filled(body: &str) -> bool {
+    let mut rest = body;
+    let mut text = String::new();
+    while let Some(start) = rest.find("<!--") {
+        text.push_str(&rest[..start]);
+        rest = match rest[start..].find("-->") {
+            Some(end) => &rest[start + end + 3..],
+            None => "",
+        };
+    }
+    text.push_str(rest);
+    !text.trim().is_empty()
+}
+
+/// Merge an existing AGENTS.md into a freshly rendered template: the
+/// template's title and section order, with the user's filled-in sections.
+pub fn merge_agents_md(existing: &str, fresh: &str) -> String {
+    let (_, old_sections) = split_sections(existing);
+    let (preamble, new_sections) = split_sections(fresh);
+
+    let mut out = preamble;
+    for (i, (heading, body)) in new_sections.iter().enumerate() {
+        out.push_str(heading);
+        out.push('\n');
+        let kept = old_sections
+            .iter()
+            .find(|(h, b)| h == heading && is_filled(b));
+        match kept {
+            Some((_, old)) => {
+                out.push_str(old);
+                // A kept section may have been last in the old file.
+                while i + 1 < new_sections.len() && !out.ends_with("\n\n") {
+                    out.push('\n');
+                }
+            }
+            None => out.push_str(body),
+        }
+    }
+    for (heading, body) in &old_sections {
+        if new_sections.iter().any(|(h, _)| h == heading) || !is_filled(body) {
+            continue;
+        }
+        while !out.ends_with("\n\n") {
+            out.push('\n');
+        }
+        out.push_str(heading);
+        out.push('\n');
+        out.push_str(body);
+    }
+    out
+}
+
+/// Regenerate AGENTS.md and the CLAUDE.md symlink in one contact directory.
+/// Returns whether AGENTS.md changed.
+fn regenerate(name: &str, dir: &Path) -> Result<bool> {
+    let agents_md = dir.join("AGENTS.md");
+    let fresh = agents_md_template(name)?;
+    let existing = std::fs::read_to_string(&agents_md).unwrap_or_default();
+    let merged = merge_agents_md(&existing, &fresh);
+    let changed = merged != existing;
+    if changed {
+        std::fs::write(&agents_md, &merged)?;
+    }
+
+    let claude_md = dir.join("CLAUDE.md");
+    if !claude_md.is_symlink() {
+        if claude_md.exists() {
+            std::fs::remove_file(&claude_md)?;
+        }
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("AGENTS.md", &claude_md)?;
+    }
+    Ok(changed)
+}
+
+/// corky contact reset [NAME]
+pub fn run(name: Option<&str>) -> Result<()> {
+    let contacts = load_contacts(None)?;
+    if contacts.is_empty() {
+        println!("No contacts configured in .corky.toml");
+        return Ok(());
+    }
+
+    let names: Vec<String> = match name {
+        Some(n) => {
+            if !contacts.contains_key(n) {
+                bail!("Unknown contact: {}", n);
+            }
+            vec![n.to_string()]
+        }
+        None => contacts.keys().cloned().collect(),
+    };
+
+    for n in &names {
+        let dir = resolve::contacts_dir().join(n);
+        if !dir.is_dir() {
+            println!("  {}: not found at {} -- skipping", n, dir.display());
+            continue;
+        }
+        if regenerate(n, &dir)? {
+            println!("  {}: updated AGENTS.md", n);
+        } else {
+            println!("  {}: already up to date", n);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_filled() {
+        assert!(!is_filled("\n<!-- How you know this person -->\n\n"));
+        assert!(!is_filled("<!-- a\n   multi-line comment -->"));
+        assert!(is_filled("<!-- hint -->\nMet at RustConf 2023.\n"));
+        assert!(is_filled("casual\n"));
+    }
+
+    #[test]
+    fn test_merge_keeps_filled_sections() {
+        let existing = "# Contact: alice\n\n\
+                        ## Relationship\n\nMet at RustConf.\n\n\
+                        ## Tone\n\n<!-- old hint -->\n\n\
+                        ## Pets\n\nTwo cats.\n";
+        let fresh = "# Contact: alice\n\nContext for alice.\n\n\
+                     ## Relationship\n\n<!-- new hint -->\n\n\
+                     ## Tone\n\n<!-- new tone hint -->\n\n\
+                     ## Research\n\n<!-- where to look -->\n";
+        let merged = merge_agents_md(existing, fresh);
+        assert_eq!(
+            merged,
+            "# Contact: alice\n\nContext for alice.\n\n\
+             ## Relationship\n\nMet at RustConf.\n\n\
+             ## Tone\n\n<!-- new tone hint -->\n\n\
+             ## Research\n\n<!-- where to look -->\n\n\
+             ## Pets\n\nTwo cats.\n"
+        );
+        // Resetting again changes nothing.
+        assert_eq!(merge_agents_md(&merged, fresh), merged);
+
+        // A filled section that ended the old file still gets its blank line.
+        let existing = "# Contact: alice\n\n## Relationship\n\nMet at RustConf.";
+        assert!(merge_agents_md(existing, fresh).contains("Met at RustConf.\n\n## Tone\n"));
+    }
+
+    #[test]
+    fn test_merge_into_empty() {
+        let fresh = default_agents_md("bob");
+        assert_eq!(merge_agents_md("", &fresh), fresh);
+    }
+}
@@ -0,0 +1,394 @@
+//! Conversations involving a contact, listed or linked into its folder.
+//!
+//! A thread involves a contact when any message's From, To, or CC carries
+//! one of the contact's `emails` (compared with `address::same`; a
+//! `*@domain` entry matches every address at that domain). Root
+//! `conversations/` and every `mailboxes/*/conversations/` are scanned.
+//! `contacts/{name}/threads.md` lists them with relative links;
+//! `contacts/{name}/threads/` holds one symlink per thread.
+
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+
+use crate::config::contact::{load_contacts, Contact};
+use crate::config::corky_config;
+use crate::resolve;
+use crate::sync::imap_sync::parse_msg_date;
+use crate::sync::markdown::{is_part_file, read_thread};
+use crate::sync::types::Thread;
+use crate::address;
+use crate::util::split_address_list;
+
+/// Generated index of a contact's threads.
+pub const THREADS_MD: &str = "threads.md";
+/// Directory of symlinks to a contact's threads.
+pub const THREADS_DIR: &str = "threads";
+
+/// A conversation file involving a contact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContactThread {
+    /// "" for root conversations, else the mailbox name.
+    pub mailbox: String,
+    pub slug: String,
+    pub subject: String,
+    pub last_date: String,
+    /// Path relative to the data dir, e.g. `mailboxes/alex/conversations/lunch.md`.
+    pub rel_path: PathBuf,
+}
+
+impl ContactThread {
+    /// File name in `threads/`: the slug, prefixed with the mailbox when
+    /// the thread lives in one.
+    fn link_name(&self) -> String {
+        if self.mailbox.is_empty() {
+            format!("{}.md", self.slug)
+        } else {
+            format!("{}--{}.md", self.mailbox, self.slug)
+        }
+    }
+}
+
+/// Whether any message in the thread involves one of `emails` (by
+/// `address::matches_any`).
+fn involves(thread: Thread, emails: &[String]) -> Option<(String, String)> {
+    let hit = thread.messages.iter().any(|m| {
+        [&m.from, &m.to, &m.cc].iter().any(|field| {
+            split_address_list(field)
+                .iter()
+                .any(|a| address::matches_any(emails, a))
+        })
+    });
+    hit.then_some((thread.subject, thread.last_date))
+}
+
+fn scan_dir(
+    data_dir: &Path,
+    rel_dir: &Path,
+    mailbox: &str,
+    emails: &[String],
+    out: &mut Vec<ContactThread>,
+) -> Result<()> {
+    let dir = data_dir.join(rel_dir);
+    if !dir.is_dir() {
+        return Ok(());
+    }
+    for entry in std::fs::read_dir(&dir)?.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("md") || is_part_file(&path) {
+            continue;
+        }
+        let Some(thread) = read_thread(&path)? else {
+            continue;
+        };
+        if let Some((subject, last_date)) = involves(thread, emails) {
+            let slug = path
+                .file_stem()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            out.push(ContactThread {
+                mailbox: mailbox.to_string(),
+                rel_path: rel_dir.join(format!("{}.md", slug)),
+                slug,
+                subject,
+                last_date,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Every conversation under `data_dir` involving the contact, newest first.
+pub fn find_threads(data_dir: &Path, contact: &Contact) -> Result<Vec<ContactThread>> {
+    let emails = &contact.emails;
+    let mut out = Vec::new();
+    if emails.is_empty() {
+        return Ok(out);
+    }
+    scan_dir(data_dir, Path::new("conversations"), "", emails, &mut out)?;
+    let mailboxes = data_dir.join("mailboxes");
+    if mailboxes.is_dir() {
+        let mut names: Vec<String> = std::fs::read_dir(&mailboxes)?
+            .flatten()
+            .filter(|e| e.path().is_dir())
+            .map(|e| e.file_name().to_string_lossy().to_string())
+            .collect();
+        names.sort();
+        for name in names {
+            let rel = Path::new("mailboxes").join(&name).join("conversations");
+            scan_dir(data_dir, &rel, &name, emails, &mut out)?;
+        }
+    }
+    out.sort_by(|a, b| {
+        parse_msg_date(&b.last_date)
+            .cmp(&parse_msg_date(&a.last_date))
+            .then_with(|| a.rel_path.cmp(&b.rel_path))
+    });
+    Ok(out)
+}
+
+/// `contacts/{name}/threads.md`: one bullet per thread, linked relative to
+/// the contact folder.
+pub fn render_threads_md(name: &str, threads: &[ContactThread]) -> String {
+    let mut out = format!(
+        "# Threads: {}\n\n<!-- Generated by `corky contact threads {} --write`; edits are overwritten. -->\n\n",
+        name, name
+    );
+    if threads.is_empty() {
+        out.push_str("No conversations yet.\n");
+        return out;
+    }
+    for t in threads {
+        let scope = if t.mailbox.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", t.mailbox)
+        };
+        out.push_str(&format!(
+            "- {} — [{}](../../{}){}\n",
+            t.last_date,
+            t.subject,
+            t.rel_path.display(),
+            scope
+        ));
+    }
+    out
+}
+
+/// Write `threads.md` in the contact folder. Returns whether it changed.
+pub fn write_threads_md(contact_dir: &Path, name: &str, threads: &[ContactThread]) -> Result<bool> {
+    let path = contact_dir.join(THREADS_MD);
+    let text = render_threads_md(name, threads);
+    if std::fs::read_to_string(&path).ok().as_deref() == Some(text.as_str()) {
+        return Ok(false);
+    }
+    std::fs::write(&path, text)?;
+    Ok(true)
+}
+
+/// Point `threads/` in the contact folder at exactly `threads`: add missing
+/// symlinks and remove ones for threads no longer involved. Files that
+/// aren't symlinks are left alone. Returns (added, removed).
+#[cfg(unix)]
+pub fn link_threads(contact_dir: &Path, threads: &[ContactThread]) -> Result<(usize, usize)> {
+    let dir = contact_dir.join(THREADS_DIR);
+    std::fs::create_dir_all(&dir)?;
+    let wanted: std::collections::HashSet<String> = threads.iter().map(|t| t.link_name()).collect();
+
+    let mut removed = 0;
+    for entry in std::fs::read_dir(&dir)?.flatten() {
+        let path = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if path.is_symlink() && !wanted.contains(&name) {
+            std::fs::remove_file(&path)?;
+            removed += 1;
+        }
+    }
+
+    let mut added = 0;
+    for t in threads {
+        let link = dir.join(t.link_name());
+        let target = Path::new("../../..").join(&t.rel_path);
+        if link.is_symlink() {
+            if std::fs::read_link(&link)? == target {
+                continue;
+            }
+            std::fs::remove_file(&link)?;
+        } else if link.exists() {
+            continue;
+        }
+        std::os::unix::fs::symlink(&target, &link)?;
+        added += 1;
+    }
+    Ok((added, removed))
+}
+
+#[cfg(not(unix))]
+pub fn link_threads(_contact_dir: &Path, _threads: &[ContactThread]) -> Result<(usize, usize)> {
+    bail!("Linking threads needs symlinks; use --write for threads.md instead")
+}
+
+/// corky contact threads NAME [--write] [--link]
+pub fn run(name: &str, write: bool, link: bool) -> Result<()> {
+    let contacts = load_contacts(None)?;
+    let Some(contact) = contacts.get(name) else {
+        bail!("Contact '{}' not found in .corky.toml", name);
+    };
+    let threads = find_threads(&resolve::data_dir(), contact)?;
+
+    if threads.is_empty() {
+        println!("No conversations involving {}.", name);
+    } else {
+        println!("Threads ({}):", threads.len());
+        for t in &threads {
+            println!("  {}  {}", t.last_date, t.rel_path.display());
+            println!("      {}", t.subject);
+        }
+    }
+
+    if write || link {
+        let contact_dir = resolve::contacts_dir().join(name);
+        std::fs::create_dir_all(&contact_dir)?;
+        if write {
+            write_threads_md(&contact_dir, name, &threads)?;
+            println!("Wrote {}", contact_dir.join(THREADS_MD).display());
+        }
+        if link {
+            let (added, removed) = link_threads(&contact_dir, &threads)?;
+            println!(
+                "Linked {}: {} added, {} removed",
+                contact_dir.join(THREADS_DIR).display(),
+                added,
+                removed
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Post-sync step: refresh every contact folder per `[sync] contact_threads`
+/// ("list" writes threads.md, "link" maintains threads/, "" is off).
+/// Contacts without a folder are skipped. Failures are warnings.
+pub fn refresh_all() {
+    let mode = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .map(|s| s.contact_threads)
+        .unwrap_or_default();
+    if mode.is_empty() {
+        return;
+    }
+    if let Err(e) = refresh_all_with(&mode) {
+        eprintln!("  Warning: updating contact threads failed: {}", e);
+    }
+}
+
+fn refresh_all_with(mode: &str) -> Result<()> {
+    if mode != "list" && mode != "link" {
+        bail!(
+            "Unknown [sync] contact_threads '{}'. Use \"list\" or \"link\".",
+            mode
+        );
+    }
+    let data_dir = resolve::data_dir();
+    for (name, contact) in load_contacts(None)? {
+        let contact_dir = resolve::contacts_dir().join(&name);
+        if !contact_dir.is_dir() {
+            continue;
+        }
+        let threads = find_threads(&data_dir, &contact)?;
+        if mode == "list" {
+            if write_threads_md(&contact_dir, &name, &threads)? {
+                crate::progress!("  Updated contacts/{}/{}", name, THREADS_MD);
+            }
+        } else {
+            let (added, removed) = link_threads(&contact_dir, &threads)?;
+            if added + removed > 0 {
+                crate::progress!(
+                    "  Linked contacts/{}/{}: {} added, {} removed",
+                    name, THREADS_DIR, added, removed
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_thread(path: &Path, id: &str, date: &str, from: &str, to: &str) {
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(
+            path,
+            format!(
+                "# {id}\n\n**Thread ID**: {id}\n**Last updated**: {date}\n\n---\n\n\
+                 ## {from} \u{2014} {date}\n\n**To**: {to}\n\nHello\n"
+            ),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_find_threads_matches_any_address_field() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path();
+        write_thread(
+            &data.join("conversations/lunch.md"),
+            "Lunch",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "Alice <Alice@Example.com>",
+            "me@example.com",
+        );
+        write_thread(
+            &data.join("mailboxes/alex/conversations/plans.md"),
+            "Plans",
+            "Tue, 11 Feb 2025 10:00:00 +0000",
+            "Me <me@example.com>",
+            "Bob <bob@example.com>, alice@example.com",
+        );
+        write_thread(
+            &data.join("conversations/other.md"),
+            "Other",
+            "Wed, 12 Feb 2025 10:00:00 +0000",
+            "Carol <carol@example.com>",
+            "me@example.com",
+        );
+        let alice = Contact {
+            emails: vec!["alice@example.com".to_string()],
+            ..Default::default()
+        };
+
+        let threads = find_threads(data, &alice).unwrap();
+        let paths: Vec<String> = threads
+            .iter()
+            .map(|t| t.rel_path.display().to_string())
+            .collect();
+        assert_eq!(
+            paths,
+            vec![
+                "mailboxes/alex/conversations/plans.md",
+                "conversations/lunch.md"
+            ]
+        );
+        assert_eq!(threads[0].mailbox, "alex");
+
+        let md = render_threads_md("alice", &threads);
+        assert!(md.contains("[Plans](../../mailboxes/alex/conversations/plans.md) [alex]"));
+        assert!(md.contains("[Lunch](../../conversations/lunch.md)\n"));
+
+        let everyone = Contact {
+            emails: vec!["*@example.com".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(find_threads(data, &everyone).unwrap().len(), 3);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_link_threads_adds_and_prunes() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path();
+        write_thread(
+            &data.join("conversations/lunch.md"),
+            "Lunch",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "Alice <alice@example.com>",
+            "me@example.com",
+        );
+        let contact_dir = data.join("contacts/alice");
+        std::fs::create_dir_all(&contact_dir).unwrap();
+        let alice = Contact {
+            emails: vec!["alice@example.com".to_string()],
+            ..Default::default()
+        };
+        let threads = find_threads(data, &alice).unwrap();
+
+        assert_eq!(link_threads(&contact_dir, &threads).unwrap(), (1, 0));
+        let link = contact_dir.join("threads/lunch.md");
+        assert!(std::fs::read_to_string(&link).unwrap().contains("Hello"));
+        assert_eq!(link_threads(&contact_dir, &threads).unwrap(), (0, 0));
+        assert_eq!(link_threads(&contact_dir, &[]).unwrap(), (0, 1));
+        assert!(!link.is_symlink());
+    }
+}
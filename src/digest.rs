@@ -0,0 +1,457 @@
+//! `corky digest` — a summary email of new and unanswered threads.
+//!
+//! With `[digest]` set, `corky watch` sends the owner one email per
+//! `schedule` period, at or after `time` (local). It lists the root
+//! conversations updated since the last digest and those still awaiting a
+//! reply, and goes out through the same SMTP path as `draft push --send`.
+//! When the last digest went out is kept in `.digest-state.json` in the
+//! data dir.
+
+use anyhow::Result;
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDateTime, NaiveTime, TimeZone, Timelike, Utc, Weekday,
+};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::accounts::{load_accounts, load_owner};
+use crate::config::corky_config::{self, DigestConfig};
+use crate::error::CorkyError;
+use crate::mailbox::find_unanswered::{is_from_owner, owner_aliases};
+use crate::resolve;
+use crate::sync::imap_sync::parse_msg_date;
+use crate::sync::store::{open_store, ConversationStore};
+
+/// When the last digest went out, in the data dir.
+pub const STATE_FILE: &str = ".digest-state.json";
+
+/// Threads listed under "Still awaiting your reply" before `... and N more`.
+const MAX_WAITING: usize = 25;
+
+/// How often digests go out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Schedule {
+    Hourly,
+    Daily,
+    Weekly(Weekday),
+}
+
+impl Schedule {
+    /// `[digest] schedule` (and `weekday`); None for "off".
+    pub fn parse(config: &DigestConfig) -> Result<Option<Self>> {
+        let schedule = match config.schedule.trim() {
+            "off" => return Ok(None),
+            "hourly" => Schedule::Hourly,
+            "" | "daily" => Schedule::Daily,
+            "weekly" => {
+                let day = match config.weekday.trim() {
+                    "" => Weekday::Mon,
+                    day => day.parse::<Weekday>().map_err(|_| {
+                        CorkyError::Config(format!("Invalid [digest] weekday '{}'", day))
+                    })?,
+                };
+                Schedule::Weekly(day)
+            }
+            other => {
+                return Err(CorkyError::Config(format!(
+                    "Invalid [digest] schedule '{}': expected hourly, daily, weekly, or off",
+                    other
+                ))
+                .into())
+            }
+        };
+        Ok(Some(schedule))
+    }
+
+    pub fn period(self) -> Duration {
+        match self {
+            Schedule::Hourly => Duration::hours(1),
+            Schedule::Daily => Duration::days(1),
+            Schedule::Weekly(_) => Duration::days(7),
+        }
+    }
+
+    /// The latest scheduled send time at or before `now`. Hourly digests
+    /// go out on the hour; `at` is for daily and weekly ones.
+    pub fn last_slot(self, at: NaiveTime, now: DateTime<Local>) -> DateTime<Utc> {
+        let local = |naive: NaiveDateTime| {
+            Local
+                .from_local_datetime(&naive)
+                .earliest()
+                .unwrap_or_else(|| Local.from_utc_datetime(&naive))
+                .with_timezone(&Utc)
+        };
+        let today = now.date_naive();
+        let slot = match self {
+            Schedule::Hourly => {
+                let hour = NaiveTime::from_hms_opt(now.hour(), 0, 0).unwrap_or(at);
+                return local(today.and_time(hour));
+            }
+            Schedule::Daily => local(today.and_time(at)),
+            Schedule::Weekly(day) => {
+                let back =
+                    (now.weekday().num_days_from_monday() + 7 - day.num_days_from_monday()) % 7;
+                local((today - Duration::days(back as i64)).and_time(at))
+            }
+        };
+        if slot <= now.with_timezone(&Utc) {
+            slot
+        } else {
+            slot - self.period()
+        }
+    }
+}
+
+/// `[digest] time`, default 08:00.
+fn send_time(config: &DigestConfig) -> Result<NaiveTime> {
+    let time = config.time.trim();
+    if time.is_empty() {
+        return Ok(NaiveTime::from_hms_opt(8, 0, 0).expect("valid time"));
+    }
+    NaiveTime::parse_from_str(time, "%H:%M").map_err(|_| {
+        CorkyError::Config(format!("Invalid [digest] time '{}': expected HH:MM", time)).into()
+    })
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct DigestState {
+    #[serde(default)]
+    last_sent: Option<DateTime<Utc>>,
+}
+
+fn state_file() -> PathBuf {
+    resolve::data_dir().join(STATE_FILE)
+}
+
+fn load_state() -> DigestState {
+    std::fs::read_to_string(state_file())
+        .ok()
+        .and_then(|text| serde_json::from_str(&text).ok())
+        .unwrap_or_default()
+}
+
+fn save_state(state: &DigestState) -> Result<()> {
+    std::fs::write(state_file(), serde_json::to_string_pretty(state)?)?;
+    Ok(())
+}
+
+/// One thread in a digest.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub subject: String,
+    /// Sender of the last message.
+    pub sender: String,
+    pub date: DateTime<Utc>,
+    pub labels: Vec<String>,
+    pub file: String,
+    /// The last message isn't from the owner.
+    pub unanswered: bool,
+}
+
+/// Threads updated since the last digest, and older ones still awaiting a
+/// reply, newest first.
+#[derive(Debug, Clone, Default)]
+pub struct Digest {
+    pub since: Option<DateTime<Utc>>,
+    pub recent: Vec<Entry>,
+    pub waiting: Vec<Entry>,
+}
+
+impl Digest {
+    pub fn is_empty(&self) -> bool {
+        self.recent.is_empty() && self.waiting.is_empty()
+    }
+
+    pub fn subject(&self) -> String {
+        let awaiting = self.recent.iter().filter(|e| e.unanswered).count() + self.waiting.len();
+        format!(
+            "corky digest: {} new, {} awaiting reply",
+            self.recent.len(),
+            awaiting
+        )
+    }
+
+    pub fn body(&self) -> String {
+        let line = |e: &Entry| {
+            let mut text = format!(
+                "- {} \u{2014} {}, {}",
+                e.subject,
+                e.sender,
+                e.date.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            );
+            if !e.labels.is_empty() {
+                text.push_str(&format!(" [{}]", e.labels.join(", ")));
+            }
+            if e.unanswered {
+                text.push_str(" (awaiting reply)");
+            }
+            format!("{}\n  {}\n", text, e.file)
+        };
+        let mut out = String::new();
+        let since = match self.since {
+            Some(t) => t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string(),
+            None => "the start".to_string(),
+        };
+        out.push_str(&format!("New since {} ({}):\n", since, self.recent.len()));
+        if self.recent.is_empty() {
+            out.push_str("- nothing new\n");
+        }
+        for e in &self.recent {
+            out.push_str(&line(e));
+        }
+        if !self.waiting.is_empty() {
+            out.push_str(&format!(
+                "\nStill awaiting your reply ({}):\n",
+                self.waiting.len()
+            ));
+            for e in self.waiting.iter().take(MAX_WAITING) {
+                out.push_str(&line(e));
+            }
+            if self.waiting.len() > MAX_WAITING {
+                out.push_str(&format!(
+                    "... and {} more\n",
+                    self.waiting.len() - MAX_WAITING
+                ));
+            }
+        }
+        out
+    }
+}
+
+/// Build the digest of `store` since `since`, keeping threads with one of
+/// `labels` (all when empty). `owners` are as in `unanswered`; newsletters
+/// are never listed as awaiting a reply.
+pub fn collect(
+    store: &dyn ConversationStore,
+    labels: &[String],
+    owners: &[String],
+    since: Option<DateTime<Utc>>,
+) -> Result<Digest> {
+    let mut digest = Digest {
+        since,
+        ..Default::default()
+    };
+    for (key, thread) in store.threads()? {
+        if !labels.is_empty() && !thread.labels.iter().any(|l| labels.contains(l)) {
+            continue;
+        }
+        let Some(last) = thread.messages.last() else {
+            continue;
+        };
+        let sender = last.from.trim().to_string();
+        let unanswered = !sender.is_empty()
+            && !thread.tags.iter().any(|t| t == "newsletter")
+            && !owners.iter().any(|o| is_from_owner(&sender, o));
+        let date = parse_msg_date(&thread.last_date);
+        let entry = Entry {
+            subject: thread.subject.clone(),
+            sender,
+            date,
+            labels: thread.labels.clone(),
+            file: store.display_name(&key),
+            unanswered,
+        };
+        if since.is_none_or(|s| date > s) {
+            digest.recent.push(entry);
+        } else if unanswered {
+            digest.waiting.push(entry);
+        }
+    }
+    digest.recent.sort_by(|a, b| b.date.cmp(&a.date));
+    digest.waiting.sort_by(|a, b| b.date.cmp(&a.date));
+    Ok(digest)
+}
+
+/// The digest of root conversations since `since`, per `config`.
+fn compose(config: &DigestConfig, since: Option<DateTime<Utc>>) -> Result<Digest> {
+    let accounts = load_accounts(None).unwrap_or_default();
+    let owner = load_owner(None).map(|o| o.name).unwrap_or_default();
+    let mut owners = owner_aliases(&owner, &owner, &accounts);
+    owners.retain(|o| !o.is_empty());
+    let store = open_store(&resolve::conversations_dir(), false);
+    collect(store.as_ref(), &config.labels, &owners, since)
+}
+
+/// Send `digest` per `config`. Returns the recipient.
+#[cfg(feature = "smtp")]
+fn send(config: &DigestConfig, digest: &Digest) -> Result<String> {
+    use crate::accounts::{account_secret, get_default_account};
+
+    let accounts = load_accounts(None)?;
+    let (name, acct) = match config.account.trim() {
+        "" => get_default_account(&accounts)?,
+        name => accounts
+            .get(name)
+            .cloned()
+            .map(|acct| (name.to_string(), acct))
+            .ok_or_else(|| CorkyError::Config(format!("[digest] account '{}' not found", name)))?,
+    };
+    let secret = account_secret(&name, &acct)?;
+    let to_addr = match config.to.trim() {
+        "" => acct.from_address(),
+        to => to.to_string(),
+    };
+    let email = lettre::Message::builder()
+        .from(crate::draft::parse_mailbox("from", &acct.from_address())?)
+        .to(crate::draft::parse_mailbox("to", &to_addr)?)
+        .subject(digest.subject())
+        .body(digest.body())?;
+    crate::draft::send_email(
+        &email,
+        &acct.smtp_host,
+        acct.smtp_port,
+        &acct.user,
+        &secret,
+    )?;
+    Ok(to_addr)
+}
+
+#[cfg(not(feature = "smtp"))]
+fn send(_config: &DigestConfig, _digest: &Digest) -> Result<String> {
+    Err(crate::util::missing_feature("smtp"))
+}
+
+/// Send the digest if `[digest]` is set and a scheduled time has passed
+/// since the last one (`corky watch`). An empty digest isn't sent, but
+/// still counts as sent. Returns the recipient when one went out.
+pub fn run_if_due(now: DateTime<Local>) -> Result<Option<String>> {
+    let Some(config) = corky_config::try_load_config(None).and_then(|c| c.digest) else {
+        return Ok(None);
+    };
+    let Some(schedule) = Schedule::parse(&config)? else {
+        return Ok(None);
+    };
+    let slot = schedule.last_slot(send_time(&config)?, now);
+    let mut state = load_state();
+    if state.last_sent.is_some_and(|t| t >= slot) {
+        return Ok(None);
+    }
+    let since = state.last_sent.unwrap_or(slot - schedule.period());
+    let digest = compose(&config, Some(since))?;
+    let sent = if digest.is_empty() {
+        None
+    } else {
+        Some(send(&config, &digest)?)
+    };
+    state.last_sent = Some(now.with_timezone(&Utc));
+    save_state(&state)?;
+    Ok(sent)
+}
+
+/// corky digest [--dry-run]
+pub fn run(dry_run: bool) -> Result<()> {
+    let config = corky_config::try_load_config(None)
+        .and_then(|c| c.digest)
+        .unwrap_or_default();
+    let period = Schedule::parse(&config)?
+        .unwrap_or(Schedule::Daily)
+        .period();
+    let mut state = load_state();
+    let now = Utc::now();
+    let since = state.last_sent.unwrap_or(now - period);
+    let digest = compose(&config, Some(since))?;
+    if dry_run {
+        println!("Subject: {}\n", digest.subject());
+        print!("{}", digest.body());
+        return Ok(());
+    }
+    if digest.is_empty() {
+        println!("Nothing new or awaiting reply; no digest sent.");
+    } else {
+        let to = send(&config, &digest)?;
+        println!("Sent digest to {}", to);
+    }
+    state.last_sent = Some(now);
+    save_state(&state)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::corky_config::SyncConfig;
+    use crate::sync::store::MarkdownStore;
+    use crate::sync::types::Thread;
+    use crate::sync::types::fixtures::{message, thread};
+
+    fn labelled(id: &str, from: &str, date: &str, labels: &[&str]) -> Thread {
+        Thread {
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            ..thread(id, id, vec![message(from, date, "Hi")])
+        }
+    }
+
+    #[test]
+    fn test_collect_recent_and_waiting() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MarkdownStore::with_config(dir.path(), SyncConfig::default());
+        let alice = "Alice <alice@example.com>";
+        let me = "Me <me@example.com>";
+        for t in [
+            labelled(
+                "lunch",
+                alice,
+                "Tue, 11 Feb 2025 09:00:00 +0000",
+                &["inbox"],
+            ),
+            labelled("report", me, "Tue, 11 Feb 2025 10:00:00 +0000", &["inbox"]),
+            labelled(
+                "invoice",
+                alice,
+                "Mon, 03 Feb 2025 09:00:00 +0000",
+                &["inbox"],
+            ),
+            labelled("done", me, "Mon, 03 Feb 2025 10:00:00 +0000", &["inbox"]),
+            labelled("other", alice, "Tue, 11 Feb 2025 11:00:00 +0000", &["work"]),
+        ] {
+            store.save(&t.id, &t).unwrap();
+        }
+        let since = parse_msg_date("Mon, 10 Feb 2025 08:00:00 +0000");
+        let owners = vec!["me@example.com".to_string()];
+        let digest = collect(&store, &["inbox".to_string()], &owners, Some(since)).unwrap();
+        let names = |v: &[Entry]| v.iter().map(|e| e.subject.clone()).collect::<Vec<_>>();
+        assert_eq!(names(&digest.recent), vec!["report", "lunch"]);
+        assert_eq!(names(&digest.waiting), vec!["invoice"]);
+        assert_eq!(digest.subject(), "corky digest: 2 new, 2 awaiting reply");
+        assert!(digest.body().contains("lunch.md"));
+    }
+
+    #[test]
+    fn test_last_slot() {
+        let at = NaiveTime::from_hms_opt(8, 0, 0).unwrap();
+        // Wednesday 2025-02-12
+        let morning = Local.with_ymd_and_hms(2025, 2, 12, 7, 30, 0).unwrap();
+        let noon = Local.with_ymd_and_hms(2025, 2, 12, 12, 15, 0).unwrap();
+        let local = |d: u32, h: u32| {
+            Local
+                .with_ymd_and_hms(2025, 2, d, h, 0, 0)
+                .unwrap()
+                .with_timezone(&Utc)
+        };
+        assert_eq!(Schedule::Daily.last_slot(at, morning), local(11, 8));
+        assert_eq!(Schedule::Daily.last_slot(at, noon), local(12, 8));
+        assert_eq!(Schedule::Hourly.last_slot(at, noon), local(12, 12));
+        assert_eq!(
+            Schedule::Weekly(Weekday::Mon).last_slot(at, noon),
+            local(10, 8)
+        );
+        assert_eq!(
+            Schedule::Weekly(Weekday::Wed).last_slot(at, morning),
+            local(5, 8)
+        );
+
+        let config = DigestConfig {
+            schedule: "weekly".to_string(),
+            weekday: "friday".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Schedule::parse(&config).unwrap(),
+            Some(Schedule::Weekly(Weekday::Fri))
+        );
+        let config = DigestConfig {
+            schedule: "monthly".to_string(),
+            ..Default::default()
+        };
+        assert!(Schedule::parse(&config).is_err());
+    }
+}
@@ -0,0 +1,287 @@
+//! `corky compose` — scaffold a fresh draft and open it in `$EDITOR`.
+
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::io::IsTerminal;
+use std::path::Path;
+
+use super::set_draft_field;
+use super::template;
+use crate::accounts::{get_default_account, load_accounts};
+use crate::config::contact::{self, Contact};
+use crate::config::corky_config;
+use crate::mailbox::validate_draft::validate_draft;
+use crate::output;
+use crate::resolve;
+use crate::util;
+
+/// `$VISUAL`, else `$EDITOR`, if set.
+fn editor() -> Option<String> {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.trim().is_empty())
+}
+
+/// Open `path` in `editor`, which may carry its own arguments (`code -w`).
+fn open_in_editor(editor: &str, path: &Path) -> Result<()> {
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(path)
+        .status()?;
+    if !status.success() {
+        bail!("Editor '{}' exited with {}", editor, status);
+    }
+    Ok(())
+}
+
+/// The contact whose `emails` include `addr` (by `address::same`), else
+/// one whose `*@domain` wildcard matches it.
+fn contact_for_address<'a>(contacts: &'a BTreeMap<String, Contact>, addr: &str) -> Option<&'a str> {
+    let email = util::extract_email(addr)?;
+    contacts
+        .iter()
+        .find(|(_, c)| crate::address::contains(&c.emails, &email))
+        .or_else(|| {
+            contacts
+                .iter()
+                .find(|(_, c)| crate::address::matches_any(&c.emails, &email))
+        })
+        .map(|(name, _)| name.as_str())
+}
+
+/// Slug for the draft filename: the subject, else `to-{contact or local part}`.
+fn draft_slug(subject: &str, contact: Option<&str>, to: &str) -> String {
+    if !subject.trim().is_empty() {
+        return util::slugify(subject);
+    }
+    let who = contact.map(str::to_string).unwrap_or_else(|| {
+        util::extract_email(to)
+            .and_then(|e| e.split('@').next().map(str::to_string))
+            .unwrap_or_default()
+    });
+    util::slugify(&format!("to {}", who))
+}
+
+/// Name to greet: the address's display name, else the contact name,
+/// else the local part.
+fn to_name(to: &str, contact: Option<&str>) -> String {
+    let display = to
+        .split_once('<')
+        .map(|(name, _)| name.trim().trim_matches('"').trim())
+        .filter(|name| !name.is_empty());
+    if let Some(name) = display {
+        return name.to_string();
+    }
+    if let Some(name) = contact {
+        return name.to_string();
+    }
+    util::extract_email(to)
+        .and_then(|e| e.split('@').next().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// `display_name` of the sending account (`--account`, else the default
+/// account), if it has one.
+fn from_name(account: Option<&str>) -> Option<String> {
+    let accounts = load_accounts(None).ok()?;
+    let acct = match account {
+        Some(name) => accounts.get(name)?.clone(),
+        None => get_default_account(&accounts).ok()?.1,
+    };
+    Some(acct.display_name.trim().to_string()).filter(|n| !n.is_empty())
+}
+
+/// Placeholder values for `--template`.
+fn template_vars(
+    to: &str,
+    contact: Option<&str>,
+    thread_subject: &str,
+    account: Option<&str>,
+) -> HashMap<&'static str, String> {
+    let owner = corky_config::try_load_config(None)
+        .and_then(|cfg| cfg.owner)
+        .map(|o| o.name)
+        .unwrap_or_default();
+    let from_name = from_name(account).unwrap_or_else(|| owner.clone());
+    let mut vars = HashMap::new();
+    vars.insert("to_name", to_name(to, contact));
+    vars.insert("to_email", util::extract_email(to).unwrap_or_default());
+    vars.insert("contact", contact.unwrap_or_default().to_string());
+    vars.insert("thread_subject", thread_subject.to_string());
+    vars.insert("owner", owner);
+    vars.insert("from_name", from_name);
+    vars.insert("date", chrono::Local::now().format("%Y-%m-%d").to_string());
+    vars
+}
+
+/// corky compose [--to ADDR] [--subject S] [--contact NAME] [--template NAME]
+///               [--reply SLUG] [--mailbox NAME] [--no-edit]
+#[allow(clippy::too_many_arguments)]
+pub fn run(
+    to: Option<&str>,
+    subject: Option<&str>,
+    contact_name: Option<&str>,
+    template_name: Option<&str>,
+    reply: Option<&str>,
+    account: Option<&str>,
+    mailbox: Option<&str>,
+    no_edit: bool,
+) -> Result<()> {
+    let contacts = contact::load_contacts(None)?;
+    let contact = match contact_name {
+        Some(name) => {
+            let c = contacts
+                .get(name)
+                .ok_or_else(|| anyhow::anyhow!("Contact '{}' not found in .corky.toml", name))?;
+            Some((name, c))
+        }
+        None => None,
+    };
+    let scaffold = reply
+        .map(|slug| super::new::reply_scaffold(slug, false))
+        .transpose()?;
+
+    let to = match (to, contact, &scaffold) {
+        (Some(addr), _, _) => addr.to_string(),
+        (None, Some((name, c)), _) => match c.first_address() {
+            Some(email) => email.clone(),
+            None => bail!("Contact '{}' has no emails; pass --to", name),
+        },
+        (None, None, Some(fields)) => fields.to.clone(),
+        (None, None, None) => bail!("Pass --to ADDR, --contact NAME, or --reply SLUG"),
+    };
+    let contact_name = contact
+        .map(|(name, _)| name)
+        .or_else(|| contact_for_address(&contacts, &to));
+
+    let thread_subject = scaffold
+        .as_ref()
+        .map(|f| f.subject.trim_start_matches("Re: ").to_string())
+        .unwrap_or_default();
+    let (template_subject, body) = match template_name {
+        Some(name) => {
+            let text = template::load_template(&template::templates_dir(), name)?;
+            let vars = template_vars(&to, contact_name, &thread_subject, account);
+            template::split_template(&template::render(&text, &vars))
+        }
+        None => (String::new(), String::new()),
+    };
+
+    let subject = subject
+        .map(str::to_string)
+        .or_else(|| scaffold.as_ref().map(|f| f.subject.clone()))
+        .unwrap_or(template_subject);
+    let slug = draft_slug(&subject, contact_name, &to);
+    let in_reply_to = scaffold.as_ref().and_then(|f| f.in_reply_to.as_deref());
+    let path = super::new::create(
+        &subject,
+        &slug,
+        &to,
+        None,
+        account,
+        None,
+        in_reply_to,
+        mailbox,
+        &[],
+    )?;
+    if let Some(name) = contact_name {
+        set_draft_field(&path, "Contact", name)?;
+    }
+    if !body.is_empty() {
+        let mut content = std::fs::read_to_string(&path)?;
+        content.push_str(&body);
+        if !content.ends_with('\n') {
+            content.push('\n');
+        }
+        std::fs::write(&path, content)?;
+    }
+    println!("{}", path.display());
+
+    if let Some(name) = contact_name {
+        let agents_md = resolve::contacts_dir().join(name).join("AGENTS.md");
+        if agents_md.exists() {
+            println!("Context: {}", agents_md.display());
+        }
+    }
+
+    let interactive = std::io::stdin().is_terminal() && std::io::stdout().is_terminal();
+    if no_edit || !interactive {
+        return Ok(());
+    }
+    let Some(editor) = editor() else {
+        println!("Set $EDITOR to open new drafts automatically.");
+        return Ok(());
+    };
+    open_in_editor(&editor, &path)?;
+    report_errors(&path);
+    Ok(())
+}
+
+/// Print validation errors (not warnings) for the edited draft.
+fn report_errors(path: &Path) {
+    let errors: Vec<String> = validate_draft(path)
+        .into_iter()
+        .filter(|i| !i.starts_with("Warning:"))
+        .collect();
+    for issue in &errors {
+        println!("  {} {}", output::error("ERROR:"), issue);
+    }
+    if !errors.is_empty() {
+        println!("Fix with: $EDITOR {}", path.display());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_draft_slug() {
+        assert_eq!(draft_slug("Lunch Friday?", None, "a@b.com"), "lunch-friday");
+        assert_eq!(draft_slug("", Some("alex"), "a@b.com"), "to-alex");
+        assert_eq!(
+            draft_slug(" ", None, "Jane <jane.doe@x.com>"),
+            "to-jane-doe"
+        );
+    }
+
+    #[test]
+    fn test_contact_for_address() {
+        let mut contacts = BTreeMap::new();
+        contacts.insert(
+            "alex".to_string(),
+            Contact {
+                emails: vec!["Alex@Example.com".into()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            contact_for_address(&contacts, "Alex <alex@example.com>"),
+            Some("alex")
+        );
+        assert_eq!(contact_for_address(&contacts, "bob@example.com"), None);
+
+        contacts.insert(
+            "example".to_string(),
+            Contact {
+                emails: vec!["*@example.com".into()],
+                ..Default::default()
+            },
+        );
+        assert_eq!(contact_for_address(&contacts, "alex@example.com"), Some("alex"));
+        assert_eq!(contact_for_address(&contacts, "bob@example.com"), Some("example"));
+    }
+
+    #[test]
+    fn test_to_name() {
+        assert_eq!(
+            to_name("\"Jane Doe\" <jane@x.com>", Some("jane")),
+            "Jane Doe"
+        );
+        assert_eq!(to_name("jane@x.com", Some("jane")), "jane");
+        assert_eq!(to_name("<jane.doe@x.com>", None), "jane.doe");
+    }
+}
@@ -124,6 +124,10 @@ fn convert_legacy_to_yaml(content: &str) -> Result<String> {
     let mut from: Option<String> = None;
     let mut in_reply_to: Option<String> = None;
     let mut scheduled_at: Option<chrono::DateTime<chrono::Utc>> = None;
+    let mut source: Option<String> = None;
+    let mut sent_at: Option<chrono::DateTime<chrono::Utc>> = None;
+    let mut contact: Option<String> = None;
+    let mut thread: Option<String> = None;
 
     for cap in META_RE.captures_iter(content) {
         let key = cap[1].to_string();
@@ -139,6 +143,10 @@ fn convert_legacy_to_yaml(content: &str) -> Result<String> {
             "Scheduled-At" => {
                 scheduled_at = val.parse().ok();
             }
+            "Source" => source = Some(val),
+            "Sent-At" => sent_at = val.parse().ok(),
+            "Contact" => contact = Some(val),
+            "Thread" => thread = Some(val),
             _ => {} // ignore unknown fields
         }
     }
@@ -158,6 +166,10 @@ fn convert_legacy_to_yaml(content: &str) -> Result<String> {
         in_reply_to,
         scheduled_at,
         attachments: Vec::new(),
+        source,
+        sent_at,
+        contact,
+        thread,
     };
 
     let yaml = serde_yaml::to_string(&meta)?;
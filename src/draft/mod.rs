@@ -1,26 +1,42 @@
 //! Push a draft markdown file as an email draft, or send it directly.
 
+pub mod compose;
 pub mod migrate;
 pub mod new;
+pub mod pending;
+pub mod template;
 
 use anyhow::{bail, Result};
 use chrono::{DateTime, Utc};
-use lettre::message::header::ContentType;
-use lettre::message::{Attachment, Mailbox, MultiPart, SinglePart};
-use lettre::transport::smtp::authentication::Credentials;
+#[cfg(all(feature = "net", feature = "smtp"))]
+use lettre::message::{header::ContentType, Attachment, MultiPart, SinglePart};
+#[cfg(feature = "smtp")]
+use lettre::message::Mailbox;
+#[cfg(feature = "smtp")]
+use lettre::transport::smtp::authentication::{Credentials, Mechanism, DEFAULT_MECHANISMS};
+#[cfg(feature = "smtp")]
 use lettre::{Message, SmtpTransport, Transport};
 use once_cell::sync::Lazy;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use crate::accounts::{
-    get_account_for_email, get_default_account, load_accounts, resolve_password,
-};
+#[cfg(all(feature = "net", feature = "smtp"))]
+use crate::accounts::{account_secret, get_account_for_email, get_default_account, load_accounts};
+#[cfg(feature = "smtp")]
+use crate::accounts::Secret;
+use crate::config::contact::load_contacts;
+use crate::config::group::{expand_field, load_groups, Expansion};
+use crate::resolve;
+use crate::sync::markdown::read_thread;
 
 static META_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\*\*(.+?)\*\*:\s*(.+)$").unwrap());
 
+/// Heading of the push/send history appended to the end of a draft.
+const LOG_HEADING: &str = "**Log**:";
+
+#[cfg(all(feature = "net", feature = "smtp"))]
 const VALID_SEND_STATUSES: &[&str] = &["review", "approved", "scheduled"];
 
 fn default_draft_status() -> String {
@@ -49,6 +65,18 @@ pub struct EmailDraftMeta {
     pub scheduled_at: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub attachments: Vec<String>,
+    /// Mailbox draft this one was copied from, relative to the data dir.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sent_at: Option<DateTime<Utc>>,
+    /// Contact whose `contacts/{name}/AGENTS.md` gives context for this draft.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact: Option<String>,
+    /// Conversation this draft replies to (`conversations/{slug}.md`); fills
+    /// `In-Reply-To` and `References` at push time.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thread: Option<String>,
 }
 
 /// Returns true if the content starts with YAML frontmatter.
@@ -100,6 +128,8 @@ fn parse_yaml_draft(text: &str) -> Result<(EmailDraftMeta, HashMap<String, Strin
         }
     };
 
+    let body = strip_log(body);
+
     // Build HashMap for backward compatibility with compose_email / resolve_account
     let mut map = HashMap::new();
     map.insert("To".to_string(), meta.to.clone());
@@ -122,6 +152,18 @@ fn parse_yaml_draft(text: &str) -> Result<(EmailDraftMeta, HashMap<String, Strin
     if let Some(ref scheduled_at) = meta.scheduled_at {
         map.insert("Scheduled-At".to_string(), scheduled_at.to_rfc3339());
     }
+    if let Some(ref source) = meta.source {
+        map.insert("Source".to_string(), source.clone());
+    }
+    if let Some(ref sent_at) = meta.sent_at {
+        map.insert("Sent-At".to_string(), sent_at.to_rfc3339());
+    }
+    if let Some(ref contact) = meta.contact {
+        map.insert("Contact".to_string(), contact.clone());
+    }
+    if let Some(ref thread) = meta.thread {
+        map.insert("Thread".to_string(), thread.clone());
+    }
 
     Ok((meta, map, subject, body))
 }
@@ -165,7 +207,7 @@ pub fn parse_draft(path: &Path) -> Result<(HashMap<String, String>, String, Stri
         bail!("Draft is missing --- separator: {}", path.display());
     };
 
-    let body = lines[body_start + 1..].join("\n").trim().to_string();
+    let body = strip_log(lines[body_start + 1..].join("\n")).trim().to_string();
     Ok((meta, subject, body))
 }
 
@@ -181,35 +223,74 @@ pub fn parse_draft_yaml(content: &str) -> Option<EmailDraftMeta> {
     serde_yaml::from_str(yaml_str).ok()
 }
 
+/// Parse one address (`Name <email>` or bare) into a mailbox, with the
+/// domain in punycode so internationalized domains survive SMTP.
+#[cfg(feature = "smtp")]
+pub(crate) fn parse_mailbox(field: &str, value: &str) -> Result<Mailbox> {
+    let a = crate::address::Address::parse(value)
+        .ok_or_else(|| anyhow::anyhow!("Invalid {} address: {}", field, value))?;
+    let email = crate::address::to_ascii(&a.email)
+        .parse()
+        .map_err(|_| anyhow::anyhow!("Invalid {} address: {}", field, a.email))?;
+    Ok(Mailbox::new(Some(a.name).filter(|n| !n.is_empty()), email))
+}
+
+/// Parse a To/CC value (one or more comma-separated addresses) into
+/// mailboxes, naming the field in errors.
+#[cfg(all(feature = "net", feature = "smtp"))]
+fn parse_mailboxes(field: &str, value: &str) -> Result<Vec<Mailbox>> {
+    let (addrs, bad) = crate::address::parse_list(value);
+    if !bad.is_empty() {
+        bail!("Invalid {} address: {}", field, bad.join(", "));
+    }
+    addrs
+        .iter()
+        .map(|a| parse_mailbox(field, &a.to_string()))
+        .collect()
+}
+
 /// Compose an email from draft metadata.
+#[cfg(all(feature = "net", feature = "smtp"))]
 fn compose_email(
     meta: &HashMap<String, String>,
     subject: &str,
     body: &str,
     from_addr: &str,
     attachment_paths: &[String],
+    bcc_self: bool,
 ) -> Result<Message> {
-    let from: Mailbox = from_addr.parse().map_err(|_| anyhow::anyhow!("Invalid from address: {}", from_addr))?;
-    let to: Mailbox = meta["To"]
-        .parse()
-        .map_err(|_| anyhow::anyhow!("Invalid To address: {}", meta["To"]))?;
+    let from = parse_mailbox("from", from_addr)?;
+    let to = parse_mailboxes("To", &meta["To"])?;
+    if to.is_empty() {
+        bail!("Missing To address");
+    }
 
     let mut builder = Message::builder()
-        .from(from)
-        .to(to)
-        .subject(subject);
+        .from(from.clone())
+        .subject(subject)
+        .message_id(None);
+    if bcc_self {
+        // Envelope only: lettre drops the Bcc header before sending.
+        builder = builder.bcc(from);
+    }
+    for mailbox in to {
+        builder = builder.to(mailbox);
+    }
 
     if let Some(cc) = meta.get("CC") {
-        if !cc.is_empty() {
-            let cc_box: Mailbox = cc.parse().map_err(|_| anyhow::anyhow!("Invalid CC address: {}", cc))?;
-            builder = builder.cc(cc_box);
+        for mailbox in parse_mailboxes("CC", cc)? {
+            builder = builder.cc(mailbox);
         }
     }
 
     if let Some(in_reply_to) = meta.get("In-Reply-To") {
         if !in_reply_to.is_empty() {
+            let references = meta
+                .get("References")
+                .filter(|r| !r.is_empty())
+                .unwrap_or(in_reply_to);
             builder = builder.in_reply_to(in_reply_to.to_string());
-            builder = builder.references(in_reply_to.to_string());
+            builder = builder.references(references.to_string());
         }
     }
 
@@ -247,80 +328,353 @@ fn compose_email(
     }
 }
 
-/// Push draft to IMAP drafts folder.
-fn push_to_drafts(
+/// Push draft to IMAP drafts folder, flagged `\Draft`. When `drafts_folder`
+/// doesn't exist on the server, the folder marked `\Drafts` or one with a
+/// usual drafts name is used instead (`sync::folders::find_drafts_folder`).
+/// Returns the folder the draft went to.
+#[cfg(all(feature = "net", feature = "smtp"))]
+pub fn push_to_drafts(
     email: &Message,
     imap_host: &str,
     imap_port: u16,
     starttls: bool,
     user: &str,
-    password: &str,
+    secret: &Secret,
     drafts_folder: &str,
-) -> Result<()> {
-    let mut tls_builder = native_tls::TlsConnector::builder();
-    if starttls || imap_host == "127.0.0.1" || imap_host == "localhost" {
-        tls_builder.danger_accept_invalid_certs(true);
-        tls_builder.danger_accept_invalid_hostnames(true);
-    }
-    let tls = tls_builder.build()?;
+) -> Result<String> {
+    let mut session =
+        crate::sync::imap_sync::connect_imap_pub(imap_host, imap_port, starttls, user, secret)?;
+    let folder = append_draft(&mut session, email, drafts_folder)?;
+    session.logout()?;
+    Ok(folder)
+}
 
-    let client = if starttls {
-        imap::connect_starttls((imap_host, imap_port), imap_host, &tls)?
-    } else {
-        imap::connect((imap_host, imap_port), imap_host, &tls)?
-    };
+/// APPEND `email` to the drafts folder over an open session, as
+/// [`push_to_drafts`] does. Returns the folder used.
+#[cfg(all(feature = "net", feature = "smtp"))]
+pub fn append_draft(
+    session: &mut crate::sync::imap_sync::ImapSession,
+    email: &Message,
+    drafts_folder: &str,
+) -> Result<String> {
+    use crate::sync::folders::{find_drafts_folder, list_folders};
 
-    let mut session = client.login(user, password).map_err(|e| e.0)?;
+    let folders = list_folders(session, &[], &HashMap::new())?;
+    let Some(folder) = find_drafts_folder(drafts_folder, &folders).map(|f| f.name.clone()) else {
+        let names: Vec<&str> = folders.iter().map(|f| f.name.as_str()).collect();
+        bail!(
+            "No drafts folder on the server: '{}' doesn't exist and no folder is marked \\Drafts. \
+             Folders: {}. Set drafts_folder for the account in .corky.toml.",
+            drafts_folder,
+            names.join(", ")
+        );
+    };
+    if folder != drafts_folder {
+        eprintln!("Warning: drafts folder '{}' not found; using '{}'", drafts_folder, folder);
+    }
 
     let email_bytes = email.formatted();
-    session.append(drafts_folder, &email_bytes)?;
+    session.append_with_flags(&folder, &email_bytes, &[imap::types::Flag::Draft])?;
+    Ok(folder)
+}
+
+/// Append a sent message to `folder`, marked `\Seen` (`archive_copy_folder`).
+#[cfg(all(feature = "net", feature = "smtp"))]
+fn append_archive_copy(
+    email: &Message,
+    acct: &crate::accounts::Account,
+    secret: &Secret,
+    folder: &str,
+) -> Result<()> {
+    let mut session = crate::sync::imap_sync::connect_imap_pub(
+        &acct.imap_host,
+        acct.imap_port,
+        acct.imap_starttls,
+        &acct.user,
+        secret,
+    )?;
+    let result = session
+        .append_with_flags(folder, email.formatted(), &[imap::types::Flag::Seen])
+        .map_err(anyhow::Error::from);
+    crate::audit::record("imap append", &format!("{}:{}", acct.user, folder), &result);
+    result?;
     session.logout()?;
     Ok(())
 }
 
-/// Send email via SMTP.
-fn send_email(
+/// Send email via SMTP, logging in with the password or XOAUTH2. Domains
+/// are already punycode (see [`parse_mailbox`]); a UTF-8 local part makes
+/// lettre ask for SMTPUTF8, and a server without it rejects the message
+/// before any recipient is tried.
+#[cfg(feature = "smtp")]
+pub(crate) fn send_email(
     email: &Message,
     smtp_host: &str,
     smtp_port: u16,
     user: &str,
-    password: &str,
+    secret: &Secret,
 ) -> Result<()> {
-    let creds = Credentials::new(user.to_string(), password.to_string());
-    let mailer = SmtpTransport::relay(smtp_host)?
+    crate::util::ensure_unrestricted("Sending email")?;
+    let (value, mechanisms) = match secret {
+        Secret::Password(password) => (password, DEFAULT_MECHANISMS.to_vec()),
+        Secret::OAuth2(token) => (token, vec![Mechanism::Xoauth2]),
+    };
+    let creds = Credentials::new(user.to_string(), value.clone());
+    #[cfg(not(feature = "tls-rustls"))]
+    let builder = SmtpTransport::relay(smtp_host)?;
+    #[cfg(feature = "tls-rustls")]
+    let builder = {
+        use lettre::transport::smtp::client::{Tls, TlsParameters};
+        let tls = TlsParameters::builder(smtp_host.to_string()).build_rustls()?;
+        SmtpTransport::builder_dangerous(smtp_host).tls(Tls::Wrapper(tls))
+    };
+    let mailer = builder
         .port(smtp_port)
         .credentials(creds)
+        .authentication(mechanisms)
         .build();
-    mailer.send(email)?;
+    let envelope = email.envelope();
+    let utf8_envelope = envelope
+        .to()
+        .iter()
+        .chain(envelope.from())
+        .any(|a| !a.to_string().is_ascii());
+    let result = match mailer.send(email) {
+        Ok(_) => Ok(()),
+        Err(e) if utf8_envelope => Err(anyhow::anyhow!(
+            "{} (a non-ASCII address needs a server with SMTPUTF8 support)",
+            e
+        )),
+        Err(e) => Err(e.into()),
+    };
+    let recipients: Vec<String> = envelope.to().iter().map(|a| a.to_string()).collect();
+    crate::audit::record("send", &recipients.join(", "), &result);
+    result
+}
+
+/// Byte offset of the trailing `**Log**:` block: the last heading line
+/// followed only by `- ` entries and blank lines.
+fn log_start(text: &str) -> Option<usize> {
+    let mut offset = 0;
+    let mut start = None;
+    for line in text.split_inclusive('\n') {
+        let l = line.trim_end();
+        if l == LOG_HEADING {
+            start = Some(offset);
+        } else if !l.is_empty() && !l.starts_with("- ") {
+            start = None;
+        }
+        offset += line.len();
+    }
+    start
+}
+
+/// Drop the `**Log**:` block so it is never sent as part of the body.
+fn strip_log(body: String) -> String {
+    match log_start(&body) {
+        Some(i) if body[..i].trim().is_empty() => String::new(),
+        Some(i) => format!("{}\n", body[..i].trim_end()),
+        None => body,
+    }
+}
+
+/// Append one `- ` entry to the draft's `**Log**:` block, starting the
+/// block at the end of the file if there isn't one.
+pub fn append_log(path: &Path, entry: &str) -> Result<()> {
+    let mut text = std::fs::read_to_string(path)?;
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+    if log_start(&text).is_none() {
+        text.push('\n');
+        text.push_str(LOG_HEADING);
+        text.push('\n');
+    }
+    text.push_str(&format!("- {}\n", entry.replace('\n', " ")));
+    std::fs::write(path, text)?;
+    Ok(())
+}
+
+/// A log entry: `{timestamp} {action} via {account} ({user}){detail}`.
+#[cfg_attr(not(all(feature = "net", feature = "smtp")), allow(dead_code))]
+fn log_entry(action: &str, account: &str, user: &str, detail: &str) -> String {
+    format!(
+        "{} {} via {} ({}){}",
+        Utc::now().format("%Y-%m-%dT%H:%M:%SZ"),
+        action,
+        account,
+        user,
+        detail
+    )
+}
+
+/// Threading headers derived from a draft's `Thread` reference.
+#[derive(Debug, Default, PartialEq)]
+pub struct ThreadHeaders {
+    /// Message-ID of the thread's latest message.
+    pub in_reply_to: String,
+    /// Every stored Message-ID in the thread, oldest first.
+    pub references: Vec<String>,
+}
+
+/// Find the conversation a `Thread` field points at.
+///
+/// Tried in order: the path as given, relative to the directory holding the
+/// draft's `drafts/` (so mailbox drafts resolve inside their mailbox),
+/// relative to the data dir, then the file stem as a conversation slug.
+fn find_thread_file(thread_ref: &str, draft_path: &Path) -> Result<PathBuf> {
+    let rel = Path::new(thread_ref);
+    let mut candidates = vec![rel.to_path_buf()];
+    if rel.is_relative() {
+        if let Some(base) = draft_path.parent().and_then(|p| p.parent()) {
+            candidates.push(base.join(rel));
+        }
+        candidates.push(resolve::data_dir().join(rel));
+    }
+    if let Some(found) = candidates.into_iter().find(|p| p.is_file()) {
+        return Ok(found);
+    }
+    let slug = rel
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default();
+    resolve::find_conversation(&slug)
+}
+
+/// Resolve `Thread` to the headers a reply needs, from the stored
+/// `**Message-ID**` lines of the conversation.
+pub fn thread_headers(thread_ref: &str, draft_path: &Path) -> Result<ThreadHeaders> {
+    let path = find_thread_file(thread_ref, draft_path)?;
+    let thread = read_thread(&path)?
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse conversation: {}", path.display()))?;
+    let references: Vec<String> = thread
+        .messages
+        .iter()
+        .map(|m| m.message_id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .collect();
+    let Some(last) = references.last().cloned() else {
+        bail!(
+            "Conversation {} has no stored Message-ID; re-sync it or set in_reply_to",
+            path.display()
+        );
+    };
+    Ok(ThreadHeaders {
+        in_reply_to: last,
+        references,
+    })
+}
+
+/// Fill `In-Reply-To` (unless set) and `References` from a `Thread` field.
+pub fn apply_thread_headers(meta: &mut HashMap<String, String>, draft_path: &Path) -> Result<()> {
+    let Some(thread_ref) = meta.get("Thread").filter(|t| !t.is_empty()).cloned() else {
+        return Ok(());
+    };
+    let headers = thread_headers(&thread_ref, draft_path)?;
+    if meta.get("In-Reply-To").is_none_or(|s| s.is_empty()) {
+        meta.insert("In-Reply-To".to_string(), headers.in_reply_to);
+    }
+    meta.insert("References".to_string(), headers.references.join(" "));
     Ok(())
 }
 
+/// Replace `[groups]` names in `To` and `CC` with their members' addresses.
+#[cfg_attr(not(all(feature = "net", feature = "smtp")), allow(dead_code))]
+fn expand_groups(meta: &mut HashMap<String, String>) -> Result<Vec<Expansion>> {
+    let groups = load_groups(None)?;
+    if groups.is_empty() {
+        return Ok(Vec::new());
+    }
+    let contacts = load_contacts(None)?;
+    let mut expansions = Vec::new();
+    for field in ["To", "CC"] {
+        let Some(value) = meta.get(field).cloned() else {
+            continue;
+        };
+        let (expanded, used) = expand_field(&value, &groups, &contacts)
+            .map_err(|e| anyhow::anyhow!("{}: {}", field, e))?;
+        meta.insert(field.to_string(), expanded);
+        expansions.extend(used);
+    }
+    Ok(expansions)
+}
+
+/// Apply `push-draft --account/--from` over the draft's own fields.
+/// `--from` alone drops the draft's `Account` so the address decides.
+#[cfg_attr(not(all(feature = "net", feature = "smtp")), allow(dead_code))]
+fn apply_overrides(meta: &mut HashMap<String, String>, account: Option<&str>, from: Option<&str>) {
+    if let Some(from) = from {
+        meta.insert("From".to_string(), from.to_string());
+        if account.is_none() {
+            meta.remove("Account");
+        }
+    }
+    if let Some(account) = account {
+        meta.insert("Account".to_string(), account.to_string());
+    }
+}
+
 /// Update the status field in a draft file (supports both YAML and legacy formats).
+/// Marking a draft `sent` also records `Sent-At`.
+#[cfg_attr(not(all(feature = "net", feature = "smtp")), allow(dead_code))]
 fn update_draft_status(path: &Path, new_status: &str) -> Result<()> {
-    let text = std::fs::read_to_string(path)?;
+    set_draft_field(path, "Status", new_status)?;
+    if new_status == "sent" {
+        set_draft_field(path, "Sent-At", &Utc::now().to_rfc3339())?;
+    }
+    Ok(())
+}
 
-    if is_yaml_format(&text) {
-        let after_first = &text[4..]; // skip "---\n"
-        let end = after_first.find("\n---").ok_or_else(|| {
-            anyhow::anyhow!("Missing closing YAML frontmatter delimiter")
-        })?;
-        let yaml_str = &after_first[..end];
-        let rest = &after_first[end..]; // includes "\n---" and body
-
-        let mut meta: EmailDraftMeta = serde_yaml::from_str(yaml_str)?;
-        meta.status = new_status.to_string();
-        let new_yaml = serde_yaml::to_string(&meta)?;
-        let updated = format!("---\n{}{}", new_yaml, rest);
-        std::fs::write(path, updated)?;
+/// Set one metadata field in a draft file, adding it if missing.
+///
+/// `key` is the legacy name (`Status`, `Sent-At`); YAML drafts use its
+/// snake_case form (`status`, `sent_at`). Other fields are left untouched.
+pub fn set_draft_field(path: &Path, key: &str, value: &str) -> Result<()> {
+    let text = std::fs::read_to_string(path)?;
+    let updated = if is_yaml_format(&text) {
+        set_yaml_field(&text, key, value)?
     } else {
-        let re = Regex::new(r"(?m)^(\*\*Status\*\*:\s*).+$")?;
-        let updated = re
-            .replace(&text, format!("${{1}}{}", new_status))
+        set_legacy_field(&text, key, value)
+    };
+    std::fs::write(path, updated)?;
+    Ok(())
+}
+
+fn set_yaml_field(text: &str, key: &str, value: &str) -> Result<String> {
+    let after_first = &text[4..]; // skip "---\n"
+    let end = after_first.find("\n---").ok_or_else(|| {
+        anyhow::anyhow!("Missing closing YAML frontmatter delimiter")
+    })?;
+    let yaml_str = &after_first[..end];
+    let rest = &after_first[end..]; // includes "\n---" and body
+
+    let mut map: serde_yaml::Mapping = serde_yaml::from_str(yaml_str)?;
+    let yaml_key = key.to_lowercase().replace('-', "_");
+    map.insert(yaml_key.into(), value.into());
+    Ok(format!("---\n{}{}", serde_yaml::to_string(&map)?, rest))
+}
+
+fn set_legacy_field(text: &str, key: &str, value: &str) -> String {
+    let re = Regex::new(&format!(r"(?m)^(\*\*{}\*\*:\s*).+$", regex::escape(key))).unwrap();
+    if re.is_match(text) {
+        return re
+            .replace(text, |caps: &regex::Captures| format!("{}{}", &caps[1], value))
             .to_string();
-        std::fs::write(path, updated)?;
     }
-
-    Ok(())
+    // Missing: add it after the last header field, before the --- separator.
+    let new_line = format!("**{}**: {}", key, value);
+    let mut lines: Vec<&str> = text.split('\n').collect();
+    let sep = lines
+        .iter()
+        .position(|line| line.trim() == "---")
+        .unwrap_or(lines.len());
+    let at = lines[..sep]
+        .iter()
+        .rposition(|line| META_RE.is_match(line))
+        .map(|i| i + 1)
+        .unwrap_or(sep);
+    lines.insert(at, &new_line);
+    lines.join("\n")
 }
 
 /// Resolve sending account from draft metadata.
@@ -328,19 +682,38 @@ fn update_draft_status(path: &Path, new_status: &str) -> Result<()> {
 /// Supports credential bubbling: if the draft lives inside a `mailboxes/` subtree,
 /// walk parent directories upward looking for `.corky.toml` files with matching
 /// account credentials. First match wins.
+#[cfg(all(feature = "net", feature = "smtp"))]
 fn resolve_account(
     meta: &HashMap<String, String>,
     draft_path: &Path,
-) -> Result<(String, crate::accounts::Account, String)> {
+) -> Result<(String, crate::accounts::Account, Secret)> {
     // Try local accounts first (from resolved .corky.toml)
     let accounts = load_accounts(None)?;
+    if let Some((name, acct)) = account_for_meta(meta, &accounts) {
+        let secret = account_secret(&name, &acct)?;
+        return Ok((name, acct, secret));
+    }
 
+    // Credential bubbling: walk parent directories for .corky.toml with matching account
+    if let Some(result) = bubble_credentials(meta, draft_path) {
+        return Ok(result);
+    }
+
+    bail!("No account found for draft. Check .corky.toml or add **Account**/**From** to the draft.")
+}
+
+/// The configured account a draft goes out from: its **Account** field,
+/// else the account whose user is its **From** address, else the default.
+#[cfg(all(feature = "net", feature = "smtp"))]
+fn account_for_meta(
+    meta: &HashMap<String, String>,
+    accounts: &HashMap<String, crate::accounts::Account>,
+) -> Option<(String, crate::accounts::Account)> {
     // Try **Account** field first
     if let Some(acct_name) = meta.get("Account") {
         if !acct_name.is_empty() {
             if let Some(acct) = accounts.get(acct_name) {
-                let pwd = resolve_password(acct)?;
-                return Ok((acct_name.clone(), acct.clone(), pwd));
+                return Some((acct_name.clone(), acct.clone()));
             }
         }
     }
@@ -348,33 +721,23 @@ fn resolve_account(
     // Try **From** field to match by email
     if let Some(from_addr) = meta.get("From") {
         if !from_addr.is_empty() {
-            if let Some((name, acct)) = get_account_for_email(&accounts, from_addr) {
-                let pwd = resolve_password(&acct)?;
-                return Ok((name, acct, pwd));
+            if let Some(found) = get_account_for_email(accounts, from_addr) {
+                return Some(found);
             }
         }
     }
 
     // Fall back to default from local config
-    if let Ok((name, acct)) = get_default_account(&accounts) {
-        let pwd = resolve_password(&acct)?;
-        return Ok((name, acct, pwd));
-    }
-
-    // Credential bubbling: walk parent directories for .corky.toml with matching account
-    if let Some(result) = bubble_credentials(meta, draft_path) {
-        return Ok(result);
-    }
-
-    bail!("No account found for draft. Check .corky.toml or add **Account**/**From** to the draft.")
+    get_default_account(accounts).ok()
 }
 
 /// Walk parent directories from the draft's location, looking for `.corky.toml`
 /// files with account credentials matching the From address.
+#[cfg(all(feature = "net", feature = "smtp"))]
 fn bubble_credentials(
     meta: &HashMap<String, String>,
     draft_path: &Path,
-) -> Option<(String, crate::accounts::Account, String)> {
+) -> Option<(String, crate::accounts::Account, Secret)> {
     let from_addr = meta.get("From").filter(|s| !s.is_empty())?;
 
     // Start from the draft's parent directory and walk up
@@ -385,8 +748,8 @@ fn bubble_credentials(
         if config_path.exists() {
             if let Ok(parent_accounts) = load_accounts(Some(&config_path)) {
                 if let Some((name, acct)) = get_account_for_email(&parent_accounts, from_addr) {
-                    if let Ok(pwd) = resolve_password(&acct) {
-                        return Some((name, acct, pwd));
+                    if let Ok(secret) = account_secret(&name, &acct) {
+                        return Some((name, acct, secret));
                     }
                 }
             }
@@ -399,8 +762,12 @@ fn bubble_credentials(
     None
 }
 
-/// corky push-draft FILE [--send]
-pub fn run(file: &Path, send: bool) -> Result<()> {
+/// corky push-draft FILE [--send] [--account NAME] [--from ADDR]
+#[cfg(all(feature = "net", feature = "smtp"))]
+pub fn run(file: &Path, send: bool, account: Option<&str>, from: Option<&str>) -> Result<()> {
+    if send {
+        crate::util::ensure_unrestricted("Sending email")?;
+    }
     if !file.exists() {
         bail!("File not found: {}", file.display());
     }
@@ -414,7 +781,25 @@ pub fn run(file: &Path, send: bool) -> Result<()> {
         Vec::new()
     };
 
-    let (meta, subject, body) = parse_draft(file)?;
+    let (mut meta, subject, body) = parse_draft(file)?;
+    apply_thread_headers(&mut meta, file)?;
+    if account.is_some() || from.is_some() {
+        // Explicit flags must name a configured account; don't fall back.
+        let accounts = load_accounts(None)?;
+        if let Some(name) = account.filter(|n| !accounts.contains_key(*n)) {
+            let msg = format!("Account '{}' not found in .corky.toml", name);
+            return Err(crate::error::CorkyError::Config(msg).into());
+        }
+        let unknown_from = from.filter(|a| {
+            account.is_none() && get_account_for_email(&accounts, a).is_none()
+        });
+        if let Some(addr) = unknown_from {
+            let msg = format!("No account in .corky.toml has user '{}'", addr);
+            return Err(crate::error::CorkyError::Config(msg).into());
+        }
+        apply_overrides(&mut meta, account, from);
+    }
+    let expansions = expand_groups(&mut meta)?;
 
     // Validate Status for --send
     let status = meta
@@ -429,10 +814,14 @@ pub fn run(file: &Path, send: bool) -> Result<()> {
         );
     }
 
-    let (acct_name, acct, password) = resolve_account(&meta, file)?;
+    let (acct_name, acct, secret) = resolve_account(&meta, file)?;
+    crate::sync::auth::refresh_stored(&acct_name, &acct);
 
-    println!("Account: {} ({})", acct_name, acct.user);
+    println!("Account: {} ({})", acct_name, acct.from_address());
     println!("To:      {}", meta["To"]);
+    for e in &expansions {
+        println!("Group:   {} → {}", e.group, e.addresses.join(", "));
+    }
     println!("Subject: {}", subject);
     if let Some(author) = meta.get("Author") {
         println!("Author:  {}", author);
@@ -457,28 +846,91 @@ pub fn run(file: &Path, send: bool) -> Result<()> {
     println!("Body:    {}", body_preview);
     println!();
 
-    let email = compose_email(&meta, &subject, &body, &acct.user, &attachments)?;
-
-    if send {
-        send_email(&email, &acct.smtp_host, acct.smtp_port, &acct.user, &password)?;
-        update_draft_status(file, "sent")?;
-        println!("Email sent. Status updated to 'sent'.");
+    let bcc_self = send && acct.bcc_self;
+    if bcc_self {
+        println!("Bcc:     {} (bcc_self)", acct.user);
+    }
+    let from = acct.from_address();
+    let email = compose_email(&meta, &subject, &body, &from, &attachments, bcc_self)?;
+
+    let message_id = email
+        .headers()
+        .get_raw("Message-ID")
+        .unwrap_or_default()
+        .to_string();
+    let (action, result) = if send {
+        let result = send_email(&email, &acct.smtp_host, acct.smtp_port, &acct.user, &secret);
+        let result = result.map(|_| {
+            let mut detail = format!("; Message-ID {}", message_id);
+            if bcc_self {
+                detail.push_str("; bcc self");
+            }
+            let folder = &acct.archive_copy_folder;
+            if !folder.is_empty() {
+                // The message is already sent; a failed copy is only a warning.
+                match append_archive_copy(&email, &acct, &secret, folder) {
+                    Ok(()) => detail.push_str(&format!("; copy in {}", folder)),
+                    Err(e) => {
+                        eprintln!("Warning: could not append copy to {}: {:#}", folder, e);
+                        detail.push_str(&format!("; copy to {} failed: {:#}", folder, e));
+                    }
+                }
+            }
+            detail
+        });
+        ("sent", result)
     } else {
-        push_to_drafts(
+        let result = push_to_drafts(
             &email,
             &acct.imap_host,
             acct.imap_port,
             acct.imap_starttls,
             &acct.user,
-            &password,
+            &secret,
             &acct.drafts_folder,
-        )?;
+        );
+        let target = format!("{}:{}", acct.user, result.as_deref().unwrap_or(&acct.drafts_folder));
+        crate::audit::record("imap append", &target, &result);
+        ("pushed", result.map(|folder| format!(" to {}", folder)))
+    };
+    for e in &expansions {
+        let entry = log_entry(
+            &format!("expanded {}", e.group),
+            &acct_name,
+            &acct.user,
+            &format!(": {}", e.addresses.join(", ")),
+        );
+        if let Err(err) = append_log(file, &entry) {
+            eprintln!("Warning: could not log to {}: {:#}", file.display(), err);
+        }
+    }
+    let entry = match &result {
+        Ok(detail) => log_entry(action, &acct_name, &acct.user, detail),
+        Err(e) => {
+            let attempt = if send { "send" } else { "push" };
+            log_entry(&format!("{} failed", attempt), &acct_name, &acct.user, &format!(": {:#}", e))
+        }
+    };
+    if let Err(e) = append_log(file, &entry) {
+        eprintln!("Warning: could not log to {}: {:#}", file.display(), e);
+    }
+    result?;
+
+    if send {
+        update_draft_status(file, "sent")?;
+        println!("Email sent. Status updated to 'sent'.");
+    } else {
         println!("Draft created. Open your email drafts to review and send.");
     }
 
     Ok(())
 }
 
+#[cfg(not(all(feature = "net", feature = "smtp")))]
+pub fn run(_file: &Path, _send: bool, _account: Option<&str>, _from: Option<&str>) -> Result<()> {
+    Err(crate::util::missing_feature("net,smtp"))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -558,6 +1010,23 @@ mod tests {
         let updated = std::fs::read_to_string(tmp.path()).unwrap();
         assert!(updated.contains("**Status**: sent"));
         assert!(!updated.contains("**Status**: draft"));
+        assert!(updated.contains("**Sent-At**: "));
+    }
+
+    #[test]
+    fn test_set_draft_field_adds_missing_fields() {
+        let legacy = "# Hi\n\n**To**: a@b.com\n**Status**: review\n\n---\n\nBody\n";
+        assert_eq!(
+            set_legacy_field(legacy, "Source", "mailboxes/lucas/drafts/hi.md"),
+            "# Hi\n\n**To**: a@b.com\n**Status**: review\n**Source**: mailboxes/lucas/drafts/hi.md\n\n---\n\nBody\n"
+        );
+
+        let yaml =
+            set_yaml_field(&yaml_draft_content(), "Sent-At", "2025-02-10T10:00:00+00:00").unwrap();
+        let meta = parse_draft_yaml(&yaml).unwrap();
+        assert_eq!(meta.sent_at.unwrap().to_rfc3339(), "2025-02-10T10:00:00+00:00");
+        assert_eq!(meta.in_reply_to.as_deref(), Some("<msg-1>"));
+        assert!(yaml.ends_with("# Test Subject\n\nHello, this is the body.\n"));
     }
 
     #[test]
@@ -591,4 +1060,118 @@ mod tests {
         assert_eq!(subject, "Hello");
         assert!(body.contains("Body here"));
     }
+
+    fn thread_msg(message_id: &str) -> crate::sync::types::Message {
+        crate::sync::types::Message {
+            to: "me@example.com".to_string(),
+            message_id: message_id.to_string(),
+            ..crate::sync::types::fixtures::message(
+                "Alice <alice@example.com>",
+                "Mon, 10 Feb 2025 10:00:00 +0000",
+                "Body",
+            )
+        }
+    }
+
+    #[test]
+    fn test_thread_reference_fills_reply_headers() {
+        let dir = tempfile::tempdir().unwrap();
+        let mb = dir.path().join("mailboxes/alex");
+        std::fs::create_dir_all(mb.join("conversations")).unwrap();
+        std::fs::create_dir_all(mb.join("drafts")).unwrap();
+        let messages = vec![
+            thread_msg("<1@example.com>"),
+            thread_msg(""),
+            thread_msg("<3@example.com>"),
+        ];
+        let thread = crate::sync::types::fixtures::thread("lunch", "Lunch", messages);
+        std::fs::write(
+            mb.join("conversations/lunch.md"),
+            crate::sync::markdown::thread_to_markdown(&thread),
+        )
+        .unwrap();
+        let draft = mb.join("drafts/reply.md");
+        std::fs::write(
+            &draft,
+            "---\nto: alice@example.com\nthread: conversations/lunch.md\n---\n\n# Re: Lunch\n\nYes\n",
+        )
+        .unwrap();
+
+        let (mut meta, _, _) = parse_draft(&draft).unwrap();
+        assert_eq!(meta["Thread"], "conversations/lunch.md");
+        apply_thread_headers(&mut meta, &draft).unwrap();
+        assert_eq!(meta["In-Reply-To"], "<3@example.com>");
+        assert_eq!(meta["References"], "<1@example.com> <3@example.com>");
+
+        // An explicit in_reply_to wins; References still come from the thread.
+        let mut meta = HashMap::new();
+        meta.insert("Thread".to_string(), "conversations/lunch.md".to_string());
+        meta.insert("In-Reply-To".to_string(), "<1@example.com>".to_string());
+        apply_thread_headers(&mut meta, &draft).unwrap();
+        assert_eq!(meta["In-Reply-To"], "<1@example.com>");
+        assert_eq!(meta["References"], "<1@example.com> <3@example.com>");
+    }
+
+    #[test]
+    fn test_apply_overrides() {
+        let mut meta = HashMap::new();
+        meta.insert("Account".to_string(), "work".to_string());
+        meta.insert("From".to_string(), "me@work.com".to_string());
+
+        let mut from_only = meta.clone();
+        apply_overrides(&mut from_only, None, Some("me@home.com"));
+        assert_eq!(from_only.get("Account"), None);
+        assert_eq!(from_only["From"], "me@home.com");
+
+        let mut account_only = meta.clone();
+        apply_overrides(&mut account_only, Some("personal"), None);
+        assert_eq!(account_only["Account"], "personal");
+        assert_eq!(account_only["From"], "me@work.com");
+
+        apply_overrides(&mut meta, None, None);
+        assert_eq!(meta["Account"], "work");
+    }
+
+    #[test]
+    fn test_log_block_is_appended_and_kept_out_of_body() {
+        let mut tmp = NamedTempFile::new().unwrap();
+        write!(tmp, "{}", yaml_draft_content()).unwrap();
+
+        append_log(tmp.path(), "2025-02-10T10:00:00Z pushed via personal (me@x.com) to Drafts").unwrap();
+        append_log(tmp.path(), "2025-02-10T10:05:00Z send failed via personal (me@x.com): refused\nretry").unwrap();
+        let text = std::fs::read_to_string(tmp.path()).unwrap();
+        assert!(text.ends_with(
+            "body.\n\n**Log**:\n- 2025-02-10T10:00:00Z pushed via personal (me@x.com) to Drafts\n- 2025-02-10T10:05:00Z send failed via personal (me@x.com): refused retry\n"
+        ));
+
+        let (_, _, body) = parse_draft(tmp.path()).unwrap();
+        assert_eq!(body, "Hello, this is the body.\n");
+
+        // Status updates still land in the frontmatter.
+        update_draft_status(tmp.path(), "sent").unwrap();
+        let (meta, _, body) = parse_draft(tmp.path()).unwrap();
+        assert_eq!(meta["Status"], "sent");
+        assert_eq!(body, "Hello, this is the body.\n");
+    }
+
+    #[test]
+    fn test_log_heading_mid_body_is_not_a_log() {
+        let body = "Notes\n**Log**:\n- item\nMore text\n".to_string();
+        assert_eq!(strip_log(body.clone()), body);
+        let mut tmp = NamedTempFile::new().unwrap();
+        write!(tmp, "{}", legacy_draft_content()).unwrap();
+        append_log(tmp.path(), "entry").unwrap();
+        let (_, _, body) = parse_draft(tmp.path()).unwrap();
+        assert_eq!(body, "Hello, this is the body.");
+    }
+
+    #[cfg(feature = "smtp")]
+    #[test]
+    fn test_parse_mailbox_punycodes_domain() {
+        let mb = parse_mailbox("to", "Jörg <jörg@bücher.example>").unwrap();
+        assert_eq!(mb.email.to_string(), "jörg@xn--bcher-kva.example");
+        assert_eq!(mb.name.as_deref(), Some("Jörg"));
+        let err = parse_mailbox("to", "nobody").unwrap_err();
+        assert_eq!(err.to_string(), "Invalid to address: nobody");
+    }
 }
@@ -4,11 +4,99 @@ use anyhow::Result;
 use chrono::Local;
 use std::path::PathBuf;
 
+use crate::accounts;
+use crate::address;
 use crate::config::corky_config;
 use crate::resolve;
+use crate::sync::markdown::read_thread;
+use crate::sync::types::Thread;
 use crate::util;
 
-/// Create a new draft file with the given metadata fields.
+/// Draft fields pre-filled from a conversation by `--reply`.
+#[derive(Debug, Default, PartialEq)]
+pub struct ReplyFields {
+    pub subject: String,
+    pub to: String,
+    pub cc: Option<String>,
+    pub in_reply_to: Option<String>,
+}
+
+/// Load a conversation by slug and derive reply fields from its latest message.
+pub fn reply_scaffold(slug: &str, reply_all: bool) -> Result<ReplyFields> {
+    let path = resolve::find_conversation(slug)?;
+    let thread = read_thread(&path)?
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse conversation: {}", path.display()))?;
+    let owner_emails: Vec<String> = accounts::load_accounts(None)
+        .map(|accts| accts.values().map(|a| a.user.clone()).collect())
+        .unwrap_or_default();
+    let fields = reply_fields(&thread, &owner_emails, reply_all)
+        .ok_or_else(|| anyhow::anyhow!("Conversation '{}' has no messages", slug))?;
+    if fields.to.is_empty() {
+        anyhow::bail!("Conversation '{}' has no recipient to reply to; pass --to", slug);
+    }
+    Ok(fields)
+}
+
+/// Derive reply fields from a thread.
+///
+/// Replies to the latest message not sent by the owner. When the owner
+/// sent every message, follows up to the latest message's recipients.
+/// With `reply_all`, the other To/CC recipients (minus the owner) go to CC.
+pub fn reply_fields(thread: &Thread, owner_emails: &[String], reply_all: bool) -> Option<ReplyFields> {
+    let is_owner = |addr: &str| {
+        util::extract_email(addr)
+            .map(|e| address::contains(owner_emails, &e))
+            .unwrap_or(false)
+    };
+    let target = thread
+        .messages
+        .iter()
+        .rev()
+        .find(|m| !is_owner(&m.from))
+        .or_else(|| thread.messages.last())?;
+
+    let (to, candidates) = if is_owner(&target.from) {
+        (target.to.clone(), util::split_address_list(&target.cc))
+    } else {
+        let mut candidates = util::split_address_list(&target.to);
+        candidates.extend(util::split_address_list(&target.cc));
+        (target.from.clone(), candidates)
+    };
+
+    let cc = if reply_all {
+        let mut seen: Vec<String> = util::split_address_list(&to)
+            .iter()
+            .map(|a| address::normalize(a))
+            .collect();
+        let mut cc_addrs = Vec::new();
+        for addr in candidates {
+            let key = address::normalize(&addr);
+            if is_owner(&addr) || seen.contains(&key) {
+                continue;
+            }
+            seen.push(key);
+            cc_addrs.push(addr);
+        }
+        Some(cc_addrs.join(", ")).filter(|s| !s.is_empty())
+    } else {
+        None
+    };
+
+    let subject = if thread.subject.to_lowercase().starts_with("re:") {
+        thread.subject.clone()
+    } else {
+        format!("Re: {}", thread.subject)
+    };
+
+    Some(ReplyFields {
+        subject,
+        to,
+        cc,
+        in_reply_to: Some(target.message_id.clone()).filter(|s| !s.is_empty()),
+    })
+}
+
+/// Create a new draft file with the given metadata fields and print its path.
 #[allow(clippy::too_many_arguments)]
 pub fn run(
     subject: &str,
@@ -20,6 +108,35 @@ pub fn run(
     mailbox: Option<&str>,
     attachments: &[String],
 ) -> Result<()> {
+    let path = create(
+        subject,
+        &util::slugify(subject),
+        to,
+        cc,
+        account,
+        from,
+        in_reply_to,
+        mailbox,
+        attachments,
+    )?;
+    println!("{}", path.display());
+    Ok(())
+}
+
+/// Write `drafts/YYYY-MM-DD-{slug}.md` (or into a mailbox's drafts/) and
+/// return its path.
+#[allow(clippy::too_many_arguments)]
+pub fn create(
+    subject: &str,
+    slug: &str,
+    to: &str,
+    cc: Option<&str>,
+    account: Option<&str>,
+    from: Option<&str>,
+    in_reply_to: Option<&str>,
+    mailbox: Option<&str>,
+    attachments: &[String],
+) -> Result<PathBuf> {
     let drafts_dir = match mailbox {
         Some(name) => resolve::mailbox_dir(name).join("drafts"),
         None => resolve::drafts_dir(),
@@ -33,13 +150,11 @@ pub fn run(
         .unwrap_or_default();
 
     let date = Local::now().format("%Y-%m-%d").to_string();
-    let slug = util::slugify(subject);
-    let path = unique_path(&drafts_dir, &date, &slug);
+    let path = unique_path(&drafts_dir, &date, slug);
 
     let content = render(subject, to, cc, account, from, in_reply_to, &author, attachments);
     std::fs::write(&path, content)?;
-    println!("{}", path.display());
-    Ok(())
+    Ok(path)
 }
 
 /// Find a unique filename, appending -2, -3, etc. on collision.
@@ -71,9 +186,9 @@ fn render(
     attachments: &[String],
 ) -> String {
     let mut fm_lines = Vec::new();
-    fm_lines.push(format!("to: {}", to));
+    fm_lines.push(format!("to: {}", yaml_scalar(to)));
     if let Some(cc) = cc {
-        fm_lines.push(format!("cc: {}", cc));
+        fm_lines.push(format!("cc: {}", yaml_scalar(cc)));
     }
     fm_lines.push("status: draft".to_string());
     if !author.is_empty() {
@@ -105,9 +220,24 @@ fn render(
     lines.join("\n") + "\n"
 }
 
+/// Quote a frontmatter value when a plain YAML scalar would misparse it
+/// (e.g. `"Doe, Jane" <jane@example.com>`).
+fn yaml_scalar(value: &str) -> String {
+    let needs_quotes = value.starts_with(['"', '\'', '&', '*', '!', '|', '>', '%', '@', '`', '[', '{', '#'])
+        || value.contains(": ")
+        || value.contains(" #");
+    if needs_quotes {
+        format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+    } else {
+        value.to_string()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sync::types::Message;
+    use crate::sync::types::fixtures::{message, thread};
 
     #[test]
     fn test_render_minimal() {
@@ -209,4 +339,66 @@ mod tests {
         assert_eq!(p.file_name().unwrap().to_str().unwrap(), "2026-02-22-hello-2.md");
         std::fs::remove_dir_all(&dir).unwrap();
     }
+
+    fn msg(from: &str, to: &str, cc: &str, message_id: &str) -> Message {
+        Message {
+            to: to.to_string(),
+            cc: cc.to_string(),
+            message_id: message_id.to_string(),
+            ..message(from, "Mon, 10 Feb 2025 10:00:00 +0000", "Body")
+        }
+    }
+
+    #[test]
+    fn test_reply_fields_reply_all() {
+        let t = thread("budget", "Budget", vec![
+            msg("Me <me@x.com>", "Alice <alice@example.com>", "", "<1@x.com>"),
+            msg(
+                "Alice <alice@example.com>",
+                "Me <me@x.com>, Bob <bob@example.com>",
+                "\"Doe, Carol\" <carol@example.com>",
+                "<2@example.com>",
+            ),
+        ]);
+        let owner = vec!["me@x.com".to_string()];
+        let fields = reply_fields(&t, &owner, true).unwrap();
+        assert_eq!(fields.subject, "Re: Budget");
+        assert_eq!(fields.to, "Alice <alice@example.com>");
+        assert_eq!(
+            fields.cc.as_deref(),
+            Some("Bob <bob@example.com>, \"Doe, Carol\" <carol@example.com>")
+        );
+        assert_eq!(fields.in_reply_to.as_deref(), Some("<2@example.com>"));
+
+        let single = reply_fields(&t, &owner, false).unwrap();
+        assert_eq!(single.cc, None);
+    }
+
+    #[test]
+    fn test_reply_fields_owner_follow_up() {
+        let t = thread(
+            "budget",
+            "Budget",
+            vec![msg(
+                "Me <me@x.com>",
+                "Alice <alice@example.com>",
+                "bob@example.com",
+                "",
+            )],
+        );
+        let fields = reply_fields(&t, &["me@x.com".to_string()], true).unwrap();
+        assert_eq!(fields.to, "Alice <alice@example.com>");
+        assert_eq!(fields.cc.as_deref(), Some("bob@example.com"));
+        assert_eq!(fields.in_reply_to, None);
+        assert!(reply_fields(&thread("budget", "Budget", vec![]), &[], false).is_none());
+    }
+
+    #[test]
+    fn test_render_quotes_display_name_with_comma() {
+        let out = render("Re: X", "\"Doe, Jane\" <jane@example.com>", None, None, None, None, "", &[]);
+        let after_first = &out[4..];
+        let end = after_first.find("\n---").unwrap();
+        let meta: crate::draft::EmailDraftMeta = serde_yaml::from_str(&after_first[..end]).unwrap();
+        assert_eq!(meta.to, "\"Doe, Jane\" <jane@example.com>");
+    }
 }
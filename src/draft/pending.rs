@@ -0,0 +1,177 @@
+//! Drafts waiting to be pushed, for `corky sync --then-push DIR`.
+//!
+//! A draft is pending when its status is `review` or `approved` and its
+//! `**Log**:` block has no `pushed` entry yet. Sync pushes each account's
+//! pending drafts over the IMAP session it already has open, so the account
+//! logs in once for both.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use super::{log_start, parse_draft};
+
+/// Statuses of drafts that `--then-push` pushes.
+const PENDING_STATUSES: &[&str] = &["review", "approved"];
+
+/// Whether the draft text has a `pushed` entry in its log block.
+fn was_pushed(text: &str) -> bool {
+    log_start(text).is_some_and(|start| {
+        text[start..]
+            .lines()
+            .any(|line| line.split_whitespace().nth(2) == Some("pushed"))
+    })
+}
+
+/// Whether the draft at `path` is pending. Unparseable drafts aren't.
+pub fn is_pending(path: &Path) -> bool {
+    let Ok(text) = std::fs::read_to_string(path) else {
+        return false;
+    };
+    let Ok((meta, _, _)) = parse_draft(path) else {
+        return false;
+    };
+    let status = meta
+        .get("Status")
+        .map(|s| s.to_lowercase())
+        .unwrap_or_default();
+    PENDING_STATUSES.contains(&status.as_str()) && !was_pushed(&text)
+}
+
+/// Pending drafts directly in `dir` (not its subdirectories, so
+/// `drafts/templates/` is left out), sorted by path.
+pub fn pending_drafts(dir: &Path) -> Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let path = entry?.path();
+        let is_md = path.extension().and_then(|e| e.to_str()) == Some("md");
+        if path.is_file() && is_md && is_pending(&path) {
+            out.push(path);
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+/// The drafts in `drafts` that go out from `account_name`, resolved like
+/// `draft push` does without `--account` (**Account**, **From**, default).
+#[cfg(all(feature = "net", feature = "smtp"))]
+pub fn for_account(drafts: &[PathBuf], account_name: &str) -> Result<Vec<PathBuf>> {
+    let accounts = crate::accounts::load_accounts(None)?;
+    Ok(drafts
+        .iter()
+        .filter(|path| {
+            parse_draft(path).is_ok_and(|(meta, _, _)| {
+                super::account_for_meta(&meta, &accounts).is_some_and(|(n, _)| n == account_name)
+            })
+        })
+        .cloned()
+        .collect())
+}
+
+/// Push `drafts` over `session`, logging each attempt in the draft like
+/// `draft push`. A draft that fails is reported and skipped. Returns
+/// (pushed, failed).
+#[cfg(all(feature = "net", feature = "smtp"))]
+pub fn push_pending(
+    drafts: &[PathBuf],
+    account_name: &str,
+    acct: &crate::accounts::Account,
+    session: &mut crate::sync::imap_sync::ImapSession,
+) -> (usize, usize) {
+    let (mut pushed, mut failed) = (0, 0);
+    for path in drafts {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let result = push_one(path, account_name, acct, session);
+        let folder = result.as_deref().unwrap_or(&acct.drafts_folder);
+        crate::audit::record("imap append", &format!("{}:{}", acct.user, folder), &result);
+        let (action, detail) = match result {
+            Ok(folder) => {
+                pushed += 1;
+                crate::progress!("  Pushed draft {} to {}", name, folder);
+                ("pushed", format!(" to {}", folder))
+            }
+            Err(e) => {
+                failed += 1;
+                let msg = format!("Pushing draft {} failed: {:#}", name, e);
+                eprintln!("{}", crate::output::error(&msg));
+                ("push failed", format!(": {:#}", e))
+            }
+        };
+        let entry = super::log_entry(action, account_name, &acct.user, &detail);
+        if let Err(e) = super::append_log(path, &entry) {
+            eprintln!("Warning: could not log to {}: {:#}", path.display(), e);
+        }
+    }
+    (pushed, failed)
+}
+
+/// Build the message for the draft at `path` and APPEND it, logging any
+/// group expansions first. Returns the folder used.
+#[cfg(all(feature = "net", feature = "smtp"))]
+fn push_one(
+    path: &Path,
+    account_name: &str,
+    acct: &crate::accounts::Account,
+    session: &mut crate::sync::imap_sync::ImapSession,
+) -> Result<String> {
+    let text = std::fs::read_to_string(path)?;
+    let attachments = super::parse_draft_yaml(&text)
+        .map(|m| m.attachments)
+        .unwrap_or_default();
+    let (mut meta, subject, body) = parse_draft(path)?;
+    super::apply_thread_headers(&mut meta, path)?;
+    for e in super::expand_groups(&mut meta)? {
+        let action = format!("expanded {}", e.group);
+        let detail = format!(": {}", e.addresses.join(", "));
+        let entry = super::log_entry(&action, account_name, &acct.user, &detail);
+        if let Err(err) = super::append_log(path, &entry) {
+            eprintln!("Warning: could not log to {}: {:#}", path.display(), err);
+        }
+    }
+    let from = acct.from_address();
+    let email = super::compose_email(&meta, &subject, &body, &from, &attachments, false)?;
+    super::append_draft(session, &email, &acct.drafts_folder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pending_drafts_by_status_and_log() {
+        let dir = tempfile::tempdir().unwrap();
+        let draft = |name: &str, status: &str, log: &str| {
+            std::fs::write(
+                dir.path().join(name),
+                format!(
+                    "---\nto: alice@example.com\nstatus: {}\n---\n\n# Hi\n\nBody.\n{}",
+                    status, log
+                ),
+            )
+            .unwrap();
+        };
+        draft("a-review.md", "review", "");
+        draft("b-approved.md", "Approved", "");
+        draft("c-draft.md", "draft", "");
+        draft("d-sent.md", "sent", "");
+        draft(
+            "e-pushed.md",
+            "review",
+            "\n**Log**:\n- 2025-02-10T10:00:00Z pushed via personal (me@x.com) to Drafts\n",
+        );
+        draft(
+            "f-failed.md",
+            "review",
+            "\n**Log**:\n- 2025-02-10T10:00:00Z push failed via personal (me@x.com): refused\n",
+        );
+        std::fs::create_dir(dir.path().join("templates")).unwrap();
+        std::fs::write(dir.path().join("templates/reply.md"), "# Reply\n").unwrap();
+
+        let names: Vec<String> = pending_drafts(dir.path())
+            .unwrap()
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["a-review.md", "b-approved.md", "f-failed.md"]);
+    }
+}
@@ -0,0 +1,170 @@
+//! Reusable email templates in `drafts/templates/*.md`.
+//!
+//! A template is plain markdown: an optional `# Subject` heading followed by
+//! the body. `{{name}}` placeholders are filled in by `corky compose
+//! --template NAME`; unknown placeholders are left as written.
+
+use anyhow::{bail, Result};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::output;
+use crate::resolve;
+
+static PLACEHOLDER_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{\{\s*([A-Za-z0-9_]+)\s*\}\}").unwrap());
+
+/// `drafts/templates/` under the data dir.
+pub fn templates_dir() -> PathBuf {
+    resolve::drafts_dir().join("templates")
+}
+
+/// A template file on disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DraftTemplate {
+    pub name: String,
+    pub path: PathBuf,
+    /// The unrendered `# ` heading, if any.
+    pub subject: String,
+}
+
+/// Every `*.md` template in `dir`, sorted by name.
+pub fn list_templates(dir: &Path) -> Vec<DraftTemplate> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut templates: Vec<DraftTemplate> = entries
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.is_file() && p.extension().and_then(|e| e.to_str()) == Some("md"))
+        .filter_map(|path| {
+            let name = path.file_stem()?.to_string_lossy().to_string();
+            let text = std::fs::read_to_string(&path).unwrap_or_default();
+            let (subject, _) = split_template(&text);
+            Some(DraftTemplate {
+                name,
+                path,
+                subject,
+            })
+        })
+        .collect();
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    templates
+}
+
+/// Read template `name` from `dir`.
+pub fn load_template(dir: &Path, name: &str) -> Result<String> {
+    let name = name.trim_end_matches(".md");
+    let path = dir.join(format!("{}.md", name));
+    if !path.is_file() {
+        let known: Vec<String> = list_templates(dir).into_iter().map(|t| t.name).collect();
+        if known.is_empty() {
+            bail!(
+                "Template '{}' not found; no templates in {}",
+                name,
+                dir.display()
+            );
+        }
+        bail!(
+            "Template '{}' not found in {}. Available: {}",
+            name,
+            dir.display(),
+            known.join(", ")
+        );
+    }
+    Ok(std::fs::read_to_string(&path)?)
+}
+
+/// Replace `{{key}}` placeholders with values from `vars`.
+pub fn render(text: &str, vars: &HashMap<&str, String>) -> String {
+    PLACEHOLDER_RE
+        .replace_all(text, |caps: &regex::Captures| {
+            vars.get(&caps[1])
+                .cloned()
+                .unwrap_or_else(|| caps[0].to_string())
+        })
+        .to_string()
+}
+
+/// Split a template into its `# ` heading (if first) and the body after it.
+pub fn split_template(text: &str) -> (String, String) {
+    let trimmed = text.trim_start();
+    if let Some(rest) = trimmed.strip_prefix("# ") {
+        let (subject, body) = rest.split_once('\n').unwrap_or((rest, ""));
+        return (
+            subject.trim().to_string(),
+            body.trim_start_matches('\n').to_string(),
+        );
+    }
+    (String::new(), trimmed.to_string())
+}
+
+/// corky template list
+pub fn run_list() -> Result<()> {
+    let dir = templates_dir();
+    let templates = list_templates(&dir);
+    if templates.is_empty() {
+        println!("No templates in {}", dir.display());
+        println!("Add one as {}/NAME.md", dir.display());
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = templates
+        .into_iter()
+        .map(|t| vec![t.name, t.subject])
+        .collect();
+    output::print_table(&rows);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_substitutes_known_vars() {
+        let mut vars = HashMap::new();
+        vars.insert("to_name", "Jane".to_string());
+        vars.insert("owner", "Brian".to_string());
+        let out = render("Hi {{to_name}},\n\n{{ owner }} / {{unknown}}", &vars);
+        assert_eq!(out, "Hi Jane,\n\nBrian / {{unknown}}");
+    }
+
+    #[test]
+    fn test_split_template() {
+        assert_eq!(
+            split_template("# Following up: {{thread_subject}}\n\nHi\n"),
+            (
+                "Following up: {{thread_subject}}".to_string(),
+                "Hi\n".to_string()
+            )
+        );
+        assert_eq!(
+            split_template("\nJust a body\n"),
+            (String::new(), "Just a body\n".to_string())
+        );
+    }
+
+    #[test]
+    fn test_list_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("intro.md"), "# Intro\n\nHello\n").unwrap();
+        std::fs::write(dir.path().join("follow-up.md"), "Body only\n").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "x").unwrap();
+
+        let names: Vec<String> = list_templates(dir.path())
+            .into_iter()
+            .map(|t| t.name)
+            .collect();
+        assert_eq!(names, vec!["follow-up", "intro"]);
+        assert_eq!(
+            load_template(dir.path(), "intro.md").unwrap(),
+            "# Intro\n\nHello\n"
+        );
+        let err = load_template(dir.path(), "missing")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("Available: follow-up, intro"), "{}", err);
+    }
+}
@@ -0,0 +1,129 @@
+//! Error taxonomy and process exit codes.
+//!
+//! Most code returns `anyhow::Result`; failures that callers or scripts need
+//! to tell apart are raised as a [`CorkyError`] and mapped to an exit code in
+//! `main` via [`exit_code`].
+
+use std::io::ErrorKind;
+
+/// Success.
+pub const EXIT_OK: i32 = 0;
+/// Any failure without a more specific code.
+pub const EXIT_FAILURE: i32 = 1;
+/// Invalid command-line usage (emitted by clap).
+pub const EXIT_USAGE: i32 = 2;
+/// Missing or invalid configuration.
+pub const EXIT_CONFIG: i32 = 3;
+/// Could not reach or authenticate with a remote service.
+pub const EXIT_NETWORK: i32 = 4;
+/// Input was checked and found invalid (drafts, skills, migrations).
+pub const EXIT_VALIDATION: i32 = 5;
+/// Sync finished but some items were skipped.
+pub const EXIT_PARTIAL_SYNC: i32 = 6;
+/// `watch --once` finished cleanly and new mail arrived.
+pub const EXIT_NEW_MAIL: i32 = 7;
+/// Sync finished for some accounts; others failed.
+pub const EXIT_ACCOUNTS_FAILED: i32 = 8;
+/// The action is disabled in restricted mode.
+pub const EXIT_RESTRICTED: i32 = 9;
+
+#[derive(Debug, thiserror::Error)]
+pub enum CorkyError {
+    #[error("{0}")]
+    Config(String),
+    #[error("{0}")]
+    Network(String),
+    #[error("{0}")]
+    Validation(String),
+    #[error("{0}")]
+    PartialSync(String),
+    #[error("{0}")]
+    AccountsFailed(String),
+    #[error("{0}")]
+    Restricted(String),
+}
+
+/// Failure to run an external program.
+#[derive(Debug, thiserror::Error)]
+pub enum CommandError {
+    #[error("Failed to run {program}: {source}")]
+    Spawn {
+        program: String,
+        source: std::io::Error,
+    },
+    #[error("Timed out after {secs}s: {command}")]
+    Timeout { command: String, secs: u64 },
+}
+
+impl CorkyError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CorkyError::Config(_) => EXIT_CONFIG,
+            CorkyError::Network(_) => EXIT_NETWORK,
+            CorkyError::Validation(_) => EXIT_VALIDATION,
+            CorkyError::PartialSync(_) => EXIT_PARTIAL_SYNC,
+            CorkyError::AccountsFailed(_) => EXIT_ACCOUNTS_FAILED,
+            CorkyError::Restricted(_) => EXIT_RESTRICTED,
+        }
+    }
+}
+
+/// Exit code for an error: the first [`CorkyError`] in the chain wins,
+/// then command timeouts and connection-level I/O errors count as network
+/// failures.
+pub fn exit_code(err: &anyhow::Error) -> i32 {
+    if let Some(e) = err.chain().find_map(|c| c.downcast_ref::<CorkyError>()) {
+        return e.exit_code();
+    }
+    let timed_out = err.chain().any(|c| {
+        matches!(
+            c.downcast_ref::<CommandError>(),
+            Some(CommandError::Timeout { .. })
+        )
+    });
+    if timed_out {
+        return EXIT_NETWORK;
+    }
+    let network = err
+        .chain()
+        .filter_map(|c| c.downcast_ref::<std::io::Error>())
+        .any(|e| {
+            matches!(
+                e.kind(),
+                ErrorKind::ConnectionRefused
+                    | ErrorKind::ConnectionReset
+                    | ErrorKind::ConnectionAborted
+                    | ErrorKind::NotConnected
+                    | ErrorKind::TimedOut
+                    | ErrorKind::HostUnreachable
+                    | ErrorKind::NetworkUnreachable
+            )
+        });
+    if network { EXIT_NETWORK } else { EXIT_FAILURE }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_exit_code_from_chain() {
+        let err = anyhow::Error::new(CorkyError::Validation("bad draft".into()));
+        assert_eq!(exit_code(&err), EXIT_VALIDATION);
+
+        let wrapped: anyhow::Result<()> =
+            Err(CorkyError::Config("no .corky.toml".into())).context("Loading config");
+        assert_eq!(exit_code(&wrapped.unwrap_err()), EXIT_CONFIG);
+    }
+
+    #[test]
+    fn test_exit_code_io_errors() {
+        let refused = std::io::Error::new(ErrorKind::ConnectionRefused, "refused");
+        assert_eq!(exit_code(&anyhow::Error::new(refused)), EXIT_NETWORK);
+
+        let missing = std::io::Error::new(ErrorKind::NotFound, "missing");
+        assert_eq!(exit_code(&anyhow::Error::new(missing)), EXIT_FAILURE);
+        assert_eq!(exit_code(&anyhow::anyhow!("boom")), EXIT_FAILURE);
+    }
+}
@@ -73,6 +73,10 @@ pub(crate) struct ApiAction {
 // --- Main entry point ---
 
 pub fn run(account: Option<&str>, dry_run: bool) -> Result<()> {
+    if !dry_run {
+        crate::util::ensure_unrestricted("Replacing Gmail filters")?;
+    }
+
     // 1. Load config
     let config = corky_config::load_config(None)?;
     let gmail = config
@@ -145,7 +149,9 @@ pub fn run(account: Option<&str>, dry_run: bool) -> Result<()> {
     if !existing.is_empty() {
         println!("Deleting {} existing filter(s)...", existing.len());
         for f in &existing {
-            delete_filter(&access_token, &f.id)?;
+            let result = delete_filter(&access_token, &f.id);
+            crate::audit::record("filter delete", &f.id, &result);
+            result?;
         }
         println!("  Done.");
     }
@@ -160,7 +166,9 @@ pub fn run(account: Option<&str>, dry_run: bool) -> Result<()> {
             .or(f.criteria.to.as_deref())
             .or(f.criteria.query.as_deref())
             .unwrap_or("(no criteria)");
-        create_filter(&access_token, f)?;
+        let result = create_filter(&access_token, f);
+        crate::audit::record("filter create", hint, &result);
+        result?;
         println!("  [{}] {}", i + 1, truncate(hint, 60));
     }
 
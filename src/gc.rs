@@ -0,0 +1,707 @@
+//! `corky gc` — archive hygiene for conversation directories.
+//!
+//! Finds threads stored twice under one Thread ID, `-2`/`-3` files left over
+//! from slug collisions, empty conversation files, mailbox copies that
+//! `[routing]` no longer sends there, and attachment folders whose thread is
+//! gone, then fixes them. Everything removed or rewritten is saved to
+//! `.corky-backup/gc-{timestamp}/` first; `--dry-run` only reports.
+
+use anyhow::{Context, Result};
+use chrono::Local;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+
+use crate::config::corky_config::{self, SyncConfig};
+use crate::mailbox::find_unanswered::{Scope, collect_md_files, resolve_dirs};
+use crate::migrate::snapshot;
+use crate::output;
+use crate::resolve;
+use crate::sync::imap_sync::{build_label_routes, merge_threads, set_mtime, thread_file_stem};
+use crate::sync::manifest::generate_manifest;
+use crate::sync::markdown::{is_part_file, part_path, read_thread, thread_files, write_thread};
+use crate::sync::types::Thread;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueKind {
+    /// A second file holding the same Thread ID.
+    Duplicate,
+    /// `{slug}-N.md` whose `{slug}.md` no longer exists.
+    Leftover,
+    /// A conversation file with no messages.
+    Empty,
+    /// A mailbox copy none of whose labels route to that mailbox anymore.
+    StaleRoute,
+    /// A folder with no conversation files and no matching thread.
+    DanglingDir,
+}
+
+impl IssueKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            IssueKind::Duplicate => "duplicate",
+            IssueKind::Leftover => "slug leftover",
+            IssueKind::Empty => "empty",
+            IssueKind::StaleRoute => "stale route",
+            IssueKind::DanglingDir => "dangling folder",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Fix {
+    Remove,
+    RemoveDir,
+    /// Merge into the kept copy of the thread, then remove.
+    MergeInto(PathBuf),
+    Rename(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub kind: IssueKind,
+    pub path: PathBuf,
+    pub fix: Fix,
+}
+
+impl Issue {
+    fn describe(&self) -> String {
+        let name = |p: &Path| {
+            p.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        };
+        match &self.fix {
+            Fix::Remove => "remove".to_string(),
+            Fix::RemoveDir => "remove folder".to_string(),
+            Fix::MergeInto(keep) => format!("merge into {}", name(keep)),
+            Fix::Rename(to) => format!("rename to {}", name(to)),
+        }
+    }
+}
+
+/// What routing knows about a mailbox: the owner's root threads by ID and
+/// the `[routing]` table.
+pub struct RouteCheck<'a> {
+    pub root: &'a HashMap<String, Thread>,
+    pub routes: &'a HashMap<String, Vec<PathBuf>>,
+}
+
+struct ConvFile {
+    path: PathBuf,
+    stem: String,
+    thread: Thread,
+}
+
+/// Top-level `.md` files (continuation parts left out) and folders of
+/// `dir`, sorted.
+fn dir_entries(dir: &Path) -> Result<(Vec<PathBuf>, Vec<PathBuf>)> {
+    let mut files = Vec::new();
+    let mut folders = Vec::new();
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if !entry.file_name().to_string_lossy().starts_with('.') {
+                folders.push(path);
+            }
+        } else if path.extension().and_then(|e| e.to_str()) == Some("md") && !is_part_file(&path) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    folders.sort();
+    Ok((files, folders))
+}
+
+/// Parsed threads in `dir`, by Thread ID.
+pub fn load_threads(dir: &Path) -> Result<HashMap<String, Thread>> {
+    let mut threads = HashMap::new();
+    if !dir.is_dir() {
+        return Ok(threads);
+    }
+    let (files, _) = dir_entries(dir)?;
+    for path in files {
+        if let Some(thread) = read_thread(&path)?.filter(|t| !t.id.is_empty()) {
+            threads.insert(thread.id.clone(), thread);
+        }
+    }
+    Ok(threads)
+}
+
+/// `stem` is `base` plus a `-N` collision suffix.
+fn is_collision_of(stem: &str, base: &str) -> bool {
+    stem.strip_prefix(base)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .is_some_and(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+}
+
+/// Problems in one conversations dir. Pass `routing` for mailbox dirs, to
+/// find copies of the owner's threads that routing no longer covers.
+pub fn scan_dir(
+    dir: &Path,
+    sync_config: &SyncConfig,
+    routing: Option<&RouteCheck>,
+) -> Result<Vec<Issue>> {
+    let mut issues = Vec::new();
+    if !dir.is_dir() {
+        return Ok(issues);
+    }
+    let (files, folders) = dir_entries(dir)?;
+
+    let mut by_id: BTreeMap<String, Vec<ConvFile>> = BTreeMap::new();
+    for path in files {
+        let thread = read_thread(&path)?;
+        let empty = match &thread {
+            Some(t) => t.messages.is_empty(),
+            None => std::fs::read_to_string(&path)?.trim().is_empty(),
+        };
+        if empty {
+            issues.push(Issue {
+                kind: IssueKind::Empty,
+                path,
+                fix: Fix::Remove,
+            });
+            continue;
+        }
+        // Unparseable and pre-Thread-ID files are left to `corky migrate`.
+        let Some(thread) = thread.filter(|t| !t.id.is_empty()) else {
+            continue;
+        };
+        let stem = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        by_id
+            .entry(thread.id.clone())
+            .or_default()
+            .push(ConvFile { path, stem, thread });
+    }
+
+    for (id, mut group) in by_id {
+        // Keep the plainest name: `slug.md` over `slug-2.md`.
+        group.sort_by(|a, b| (a.stem.len(), &a.stem).cmp(&(b.stem.len(), &b.stem)));
+
+        let stale = routing.is_some_and(|r| {
+            r.root.get(&id).is_some_and(|root_thread| {
+                !root_thread.labels.iter().any(|label| {
+                    r.routes
+                        .get(label)
+                        .is_some_and(|dirs| dirs.iter().any(|d| d == dir))
+                })
+            })
+        });
+        if stale {
+            for f in group {
+                issues.push(Issue {
+                    kind: IssueKind::StaleRoute,
+                    path: f.path,
+                    fix: Fix::Remove,
+                });
+            }
+            continue;
+        }
+
+        let keep = &group[0];
+        for f in &group[1..] {
+            issues.push(Issue {
+                kind: IssueKind::Duplicate,
+                path: f.path.clone(),
+                fix: Fix::MergeInto(keep.path.clone()),
+            });
+        }
+
+        let first_date = keep
+            .thread
+            .messages
+            .first()
+            .map(|m| m.date.as_str())
+            .unwrap_or("");
+        let base = thread_file_stem(
+            &sync_config.filename_scheme,
+            &keep.thread.subject,
+            first_date,
+            &sync_config.strip_prefixes,
+        );
+        let target = dir.join(format!("{}.md", base));
+        if is_collision_of(&keep.stem, &base) && !target.exists() {
+            issues.push(Issue {
+                kind: IssueKind::Leftover,
+                path: keep.path.clone(),
+                fix: Fix::Rename(target),
+            });
+        }
+    }
+
+    for folder in folders {
+        let name = folder
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let mut md = Vec::new();
+        collect_md_files(&folder, &mut md)?;
+        if md.is_empty() && !dir.join(format!("{}.md", name)).exists() {
+            issues.push(Issue {
+                kind: IssueKind::DanglingDir,
+                path: folder,
+                fix: Fix::RemoveDir,
+            });
+        }
+    }
+    Ok(issues)
+}
+
+/// Copy `path` (file or folder) into `backup_root`, mirroring its location
+/// under `data_dir`.
+fn backup(path: &Path, data_dir: &Path, backup_root: &Path) -> Result<()> {
+    let rel = path.strip_prefix(data_dir).unwrap_or(path);
+    let dest = backup_root.join(rel.to_string_lossy().trim_start_matches('/'));
+    if path.is_dir() {
+        snapshot(path, &dest)?;
+        return Ok(());
+    }
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(path, dest)?;
+    Ok(())
+}
+
+fn load(path: &Path) -> Result<Thread> {
+    read_thread(path)?.ok_or_else(|| anyhow::anyhow!("Not a conversation file: {}", path.display()))
+}
+
+/// Back up and remove the conversation at `main`, with its parts.
+fn remove_thread(main: &Path, data_dir: &Path, backup_root: &Path) -> Result<()> {
+    for file in thread_files(main) {
+        backup(&file, data_dir, backup_root)?;
+        std::fs::remove_file(file)?;
+    }
+    Ok(())
+}
+
+/// `name` in `dir`, or `{stem}-N{.ext}` for the first N not taken.
+fn free_name(dir: &Path, name: &str) -> PathBuf {
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem, format!(".{}", ext)),
+        _ => (name, String::new()),
+    };
+    let mut path = dir.join(name);
+    let mut n = 2;
+    while path.exists() {
+        path = dir.join(format!("{}-{}{}", stem, n, ext));
+        n += 1;
+    }
+    path
+}
+
+/// Move the attachments folder of the conversation at `from` (`{slug}/`
+/// beside it) to that of `to`. Into an existing folder, entries move across
+/// one by one: an identical file is dropped, a name already taken gets a
+/// `-N` suffix. The source folder is backed up first.
+fn move_attachments(from: &Path, to: &Path, data_dir: &Path, backup_root: &Path) -> Result<()> {
+    let src = from.with_extension("");
+    if !src.is_dir() {
+        return Ok(());
+    }
+    let dest = to.with_extension("");
+    if !dest.exists() {
+        std::fs::rename(&src, &dest)?;
+        return Ok(());
+    }
+    backup(&src, data_dir, backup_root)?;
+    for entry in std::fs::read_dir(&src)? {
+        let path = entry?.path();
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let taken = dest.join(&name);
+        if taken.is_file() && path.is_file() && std::fs::read(&taken)? == std::fs::read(&path)? {
+            std::fs::remove_file(&path)?;
+            continue;
+        }
+        std::fs::rename(&path, free_name(&dest, &name))?;
+    }
+    std::fs::remove_dir(&src)?;
+    Ok(())
+}
+
+fn apply_one(
+    issue: &Issue,
+    data_dir: &Path,
+    backup_root: &Path,
+    sync_config: &SyncConfig,
+) -> Result<()> {
+    match &issue.fix {
+        Fix::Remove => remove_thread(&issue.path, data_dir, backup_root)?,
+        Fix::RemoveDir => {
+            backup(&issue.path, data_dir, backup_root)?;
+            std::fs::remove_dir_all(&issue.path)?;
+        }
+        Fix::MergeInto(keep) => {
+            let merged = merge_threads(&load(keep)?, &load(&issue.path)?);
+            for file in thread_files(keep) {
+                backup(&file, data_dir, backup_root)?;
+            }
+            for written in write_thread(keep, &merged, sync_config)? {
+                let _ = set_mtime(&written, &merged.last_date);
+            }
+            remove_thread(&issue.path, data_dir, backup_root)?;
+            move_attachments(&issue.path, keep, data_dir, backup_root)?;
+        }
+        Fix::Rename(to) => {
+            let files = thread_files(&issue.path);
+            for (i, file) in files.iter().enumerate() {
+                std::fs::rename(file, part_path(to, i + 1))?;
+            }
+            // Parts link each other by file name
+            if files.len() > 1 {
+                let thread = load(to)?;
+                for written in write_thread(to, &thread, sync_config)? {
+                    let _ = set_mtime(&written, &thread.last_date);
+                }
+            }
+            move_attachments(&issue.path, to, data_dir, backup_root)?;
+        }
+    }
+    Ok(())
+}
+
+/// Fix `issues` in order, backing up what is removed or rewritten.
+pub fn apply(issues: &[Issue], data_dir: &Path, backup_root: &Path) -> Result<()> {
+    let sync_config = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    for issue in issues {
+        apply_one(issue, data_dir, backup_root, &sync_config)
+            .with_context(|| format!("Failed to fix {}", issue.path.display()))?;
+    }
+    Ok(())
+}
+
+/// corky gc [SCOPE] [--dry-run]
+pub fn run(scope: Option<&str>, dry_run: bool) -> Result<()> {
+    let data_dir = resolve::data_dir();
+    let root_dir = resolve::conversations_dir();
+    let sync_config = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    let root = load_threads(&root_dir)?;
+    let routes = build_label_routes("")?;
+    let routing = RouteCheck {
+        root: &root,
+        routes: &routes,
+    };
+
+    let mut issues = Vec::new();
+    for (name, dir) in resolve_dirs(&Scope::from_arg(scope))? {
+        let check = (name != "Root").then_some(&routing);
+        issues.extend(scan_dir(&dir, &sync_config, check)?);
+    }
+    if issues.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = issues
+        .iter()
+        .map(|i| {
+            vec![
+                i.kind.label().to_string(),
+                i.path
+                    .strip_prefix(&data_dir)
+                    .unwrap_or(&i.path)
+                    .display()
+                    .to_string(),
+                i.describe(),
+            ]
+        })
+        .collect();
+    output::print_table(&rows);
+    println!();
+
+    if dry_run {
+        println!("Dry run: {} problem(s) would be fixed", issues.len());
+        return Ok(());
+    }
+
+    let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let backup_root = data_dir.join(".corky-backup").join(format!("gc-{}", stamp));
+    apply(&issues, &data_dir, &backup_root)?;
+    if issues.iter().any(|i| i.path.starts_with(&root_dir)) {
+        generate_manifest(&root_dir)?;
+    }
+    println!(
+        "Fixed {} problem(s). Removed and rewritten files saved to {}",
+        issues.len(),
+        backup_root.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::markdown::thread_to_markdown;
+    use crate::sync::types::Message;
+    use crate::sync::types::fixtures::{message, thread};
+    use std::fs;
+
+    fn labelled(id: &str, subject: &str, labels: &[&str], message_ids: &[&str]) -> Thread {
+        let messages = message_ids
+            .iter()
+            .enumerate()
+            .map(|(n, mid)| Message {
+                message_id: mid.to_string(),
+                ..message(
+                    "Alice <alice@example.com>",
+                    &format!("Mon, 1{} Feb 2025 10:00:00 +0000", n),
+                    &format!("Body {}", mid),
+                )
+            })
+            .collect();
+        Thread {
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+            ..thread(id, subject, messages)
+        }
+    }
+
+    fn write(dir: &Path, name: &str, thread: &Thread) {
+        fs::write(dir.join(name), thread_to_markdown(thread)).unwrap();
+    }
+
+    #[test]
+    fn test_is_collision_of() {
+        assert!(is_collision_of("lunch-2", "lunch"));
+        assert!(is_collision_of("lunch-12", "lunch"));
+        assert!(!is_collision_of("lunch", "lunch"));
+        assert!(!is_collision_of("lunch-v2", "lunch"));
+        assert!(!is_collision_of("lunches-2", "lunch"));
+    }
+
+    #[test]
+    fn test_scan_and_apply() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("conversations");
+        fs::create_dir_all(dir.join("old-thread")).unwrap();
+        fs::write(dir.join("old-thread/photo.jpg"), "x").unwrap();
+        fs::create_dir_all(dir.join("lunch")).unwrap();
+        fs::write(dir.join("lunch/photo.jpg"), "x").unwrap();
+        write(
+            &dir,
+            "lunch.md",
+            &labelled("lunch", "Lunch", &["inbox"], &["<1@x>"]),
+        );
+        write(
+            &dir,
+            "lunch-2.md",
+            &labelled("lunch", "Lunch", &["work"], &["<1@x>", "<2@x>"]),
+        );
+        write(
+            &dir,
+            "plans-3.md",
+            &labelled("plans", "Plans", &["inbox"], &["<3@x>"]),
+        );
+        write(
+            &dir,
+            "nothing.md",
+            &labelled("nothing", "Nothing", &[], &[]),
+        );
+        fs::write(dir.join("blank.md"), "\n").unwrap();
+
+        let issues = scan_dir(&dir, &SyncConfig::default(), None).unwrap();
+        let found: Vec<(IssueKind, String)> = issues
+            .iter()
+            .map(|i| {
+                (
+                    i.kind,
+                    i.path.file_name().unwrap().to_string_lossy().to_string(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (IssueKind::Empty, "blank.md".to_string()),
+                (IssueKind::Empty, "nothing.md".to_string()),
+                (IssueKind::Duplicate, "lunch-2.md".to_string()),
+                (IssueKind::Leftover, "plans-3.md".to_string()),
+                (IssueKind::DanglingDir, "old-thread".to_string()),
+            ]
+        );
+
+        let backup_root = tmp.path().join(".corky-backup/gc");
+        apply(&issues, tmp.path(), &backup_root).unwrap();
+        let merged = load(&dir.join("lunch.md")).unwrap();
+        assert_eq!(merged.messages.len(), 2);
+        assert_eq!(merged.labels, vec!["inbox", "work"]);
+        assert!(!dir.join("lunch-2.md").exists());
+        assert!(dir.join("plans.md").exists());
+        assert!(!dir.join("blank.md").exists());
+        assert!(!dir.join("old-thread").exists());
+        assert!(dir.join("lunch/photo.jpg").exists());
+        assert!(backup_root.join("conversations/lunch-2.md").exists());
+        assert!(
+            backup_root
+                .join("conversations/old-thread/photo.jpg")
+                .exists()
+        );
+        assert!(
+            scan_dir(&dir, &SyncConfig::default(), None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_split_threads() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("conversations");
+        fs::create_dir_all(&dir).unwrap();
+        let split = SyncConfig {
+            split_messages: 1,
+            ..Default::default()
+        };
+        let lunch = labelled("lunch", "Lunch", &["inbox"], &["<1@x>", "<2@x>"]);
+        write_thread(&dir.join("lunch.md"), &lunch, &split).unwrap();
+        let plans = labelled("plans", "Plans", &["inbox"], &["<4@x>", "<5@x>"]);
+        write_thread(&dir.join("plans-3.md"), &plans, &split).unwrap();
+        write(
+            &dir,
+            "lunch-2.md",
+            &labelled("lunch", "Lunch", &["inbox"], &["<1@x>", "<2@x>", "<3@x>"]),
+        );
+
+        // Parts are neither duplicates nor leftovers of their first file.
+        let issues = scan_dir(&dir, &SyncConfig::default(), None).unwrap();
+        let found: Vec<(IssueKind, &Path)> = issues
+            .iter()
+            .map(|i| (i.kind, i.path.strip_prefix(&dir).unwrap()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (IssueKind::Duplicate, Path::new("lunch-2.md")),
+                (IssueKind::Leftover, Path::new("plans-3.md")),
+            ]
+        );
+
+        let backup_root = tmp.path().join(".corky-backup/gc");
+        apply(&issues, tmp.path(), &backup_root).unwrap();
+        assert_eq!(load(&dir.join("lunch.md")).unwrap().messages.len(), 3);
+        assert!(!dir.join("lunch-2.md").exists());
+        assert!(backup_root.join("conversations/lunch.part2.md").exists());
+        assert!(!dir.join("plans-3.md").exists());
+        assert!(!dir.join("plans-3.part2.md").exists());
+        assert_eq!(load(&dir.join("plans.md")).unwrap().messages.len(), 2);
+        assert!(
+            scan_dir(&dir, &SyncConfig::default(), None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_attachments_follow_merge_and_rename() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("conversations");
+        for (folder, file, content) in [
+            ("lunch", "menu.pdf", "menu"),
+            ("lunch-2", "menu.pdf", "new menu"),
+            ("lunch-2", "map.png", "map"),
+            ("lunch-2", "same.txt", "same"),
+            ("lunch", "same.txt", "same"),
+            ("plans-3", "agenda.pdf", "agenda"),
+        ] {
+            fs::create_dir_all(dir.join(folder)).unwrap();
+            fs::write(dir.join(folder).join(file), content).unwrap();
+        }
+        write(
+            &dir,
+            "lunch.md",
+            &labelled("lunch", "Lunch", &["inbox"], &["<1@x>"]),
+        );
+        write(
+            &dir,
+            "lunch-2.md",
+            &labelled("lunch", "Lunch", &["inbox"], &["<2@x>"]),
+        );
+        write(
+            &dir,
+            "plans-3.md",
+            &labelled("plans", "Plans", &["inbox"], &["<3@x>"]),
+        );
+
+        let issues = scan_dir(&dir, &SyncConfig::default(), None).unwrap();
+        let backup_root = tmp.path().join(".corky-backup/gc");
+        apply(&issues, tmp.path(), &backup_root).unwrap();
+
+        let read = |p: &str| fs::read_to_string(dir.join(p)).unwrap();
+        assert!(!dir.join("lunch-2").exists());
+        assert_eq!(read("lunch/menu.pdf"), "menu");
+        assert_eq!(read("lunch/menu-2.pdf"), "new menu");
+        assert_eq!(read("lunch/map.png"), "map");
+        assert_eq!(read("lunch/same.txt"), "same");
+        assert!(!dir.join("lunch/same-2.txt").exists());
+        assert!(backup_root.join("conversations/lunch-2/menu.pdf").exists());
+        assert!(!dir.join("plans-3").exists());
+        assert_eq!(read("plans/agenda.pdf"), "agenda");
+        assert!(
+            scan_dir(&dir, &SyncConfig::default(), None)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_stale_routed_copies() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mb = tmp.path().join("mailboxes/team/conversations");
+        fs::create_dir_all(&mb).unwrap();
+        write(
+            &mb,
+            "lunch.md",
+            &labelled("lunch", "Lunch", &["team"], &["<1@x>"]),
+        );
+        write(
+            &mb,
+            "launch.md",
+            &labelled("launch", "Launch", &["team"], &["<2@x>"]),
+        );
+        // Only in the mailbox (e.g. another owner's): never stale.
+        write(
+            &mb,
+            "theirs.md",
+            &labelled("theirs", "Theirs", &[], &["<3@x>"]),
+        );
+
+        let mut root = HashMap::new();
+        root.insert(
+            "lunch".to_string(),
+            labelled("lunch", "Lunch", &["inbox"], &["<1@x>"]),
+        );
+        root.insert(
+            "launch".to_string(),
+            labelled("launch", "Launch", &["team"], &["<2@x>"]),
+        );
+        let mut routes = HashMap::new();
+        routes.insert("team".to_string(), vec![mb.clone()]);
+        let check = RouteCheck {
+            root: &root,
+            routes: &routes,
+        };
+
+        let issues = scan_dir(&mb, &SyncConfig::default(), Some(&check)).unwrap();
+        assert_eq!(
+            issues,
+            vec![Issue {
+                kind: IssueKind::StaleRoute,
+                path: mb.join("lunch.md"),
+                fix: Fix::Remove,
+            }]
+        );
+    }
+}
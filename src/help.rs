@@ -1,43 +1,14 @@
 //! Command reference for corky.
+//!
+//! The reference, man pages, and markdown are all generated from the clap
+//! definitions in `cli.rs`, so they can't drift from the actual CLI.
 
-use anyhow::Result;
-
-const COMMANDS: &[(&str, &str)] = &[
-    ("init --user EMAIL [PATH]", "Initialize a new project directory"),
-    ("install-skill NAME", "Install an agent skill (e.g. email)"),
-    ("sync", "Incremental IMAP sync (default)"),
-    ("sync full", "Full IMAP resync"),
-    ("sync account NAME", "Sync one account"),
-    ("sync routes", "Apply routing to existing conversations"),
-    ("sync mailbox [NAME]", "Push/pull shared mailboxes"),
-    ("sync-auth", "Gmail OAuth setup"),
-    ("list-folders [ACCOUNT]", "List IMAP folders for an account"),
-    ("draft new SUBJECT --to EMAIL", "Scaffold a new draft file"),
-    ("draft validate [FILE|SCOPE...]", "Validate draft markdown files"),
-    ("draft push FILE [--send]", "Save draft to email"),
-    ("add-label LABEL --account NAME", "Add a label to an account's sync config"),
-    ("contact add NAME --email EMAIL", "Add a contact with context docs"),
-    ("contact add --from SLUG", "Create contact from a conversation"),
-    ("contact info NAME", "Show contact info and thread history"),
-    ("watch [--interval N]", "Poll IMAP and sync on an interval"),
-    ("unanswered [SCOPE] [--from NAME]", "Find threads awaiting a reply"),
-    ("audit-docs", "Audit instruction files"),
-    ("migrate", "Migrate from accounts.toml to .corky.toml"),
-    ("help", "Show this reference"),
-];
+use anyhow::{bail, Result};
+use clap::{Command, CommandFactory};
+use std::path::Path;
 
-const MAILBOX_COMMANDS: &[(&str, &str)] = &[
-    ("mailbox list", "List registered mailboxes"),
-    ("mailbox add NAME --label LABEL", "Add a mailbox (--github for shared repo)"),
-    ("mailbox sync [NAME]", "Push/pull shared mailboxes"),
-    ("mailbox status", "Check for pending changes"),
-    ("mailbox remove NAME [--delete-repo]", "Remove a mailbox"),
-    ("mailbox rename OLD NEW", "Rename a mailbox"),
-    ("mailbox draft new SUBJECT --to EMAIL", "Scaffold a new draft file (scoped)"),
-    ("mailbox draft validate [FILE|SCOPE]", "Validate draft files (scoped)"),
-    ("mailbox draft push FILE [--send]", "Push a draft (scoped)"),
-    ("mailbox reset [NAME] [--no-sync]", "Pull, regenerate templates, commit & push"),
-];
+use crate::cli::Cli;
+use crate::output::{self, Style};
 
 const DEV_COMMANDS: &[(&str, &str)] = &[
     ("cargo test", "Run tests"),
@@ -45,12 +16,99 @@ const DEV_COMMANDS: &[(&str, &str)] = &[
     ("cargo fmt", "Format"),
 ];
 
+/// Usage hint for a command: positionals (`NAME`, `[NAME]`) and required `--options`.
+fn usage_args(cmd: &Command) -> String {
+    let mut parts = Vec::new();
+    for arg in cmd.get_arguments() {
+        if arg.is_hide_set() {
+            continue;
+        }
+        let value = arg
+            .get_value_names()
+            .and_then(|names| names.first())
+            .map(|n| n.to_string())
+            .unwrap_or_else(|| arg.get_id().as_str().to_uppercase());
+        if arg.is_positional() {
+            if arg.is_required_set() {
+                parts.push(value);
+            } else {
+                parts.push(format!("[{}]", value));
+            }
+        } else if let Some(long) = arg.get_long().filter(|_| arg.is_required_set()) {
+            parts.push(format!("--{} {}", long, value));
+        }
+    }
+    parts.join(" ")
+}
+
+/// Flatten visible commands into (usage, description) rows. A command with
+/// subcommands gets its own row only when the subcommand is optional.
+fn collect_rows(cmd: &Command, prefix: &str, rows: &mut Vec<(String, String)>) {
+    for sub in cmd.get_subcommands() {
+        if sub.is_hide_set() || sub.get_name() == "help" {
+            continue;
+        }
+        let path = if prefix.is_empty() {
+            sub.get_name().to_string()
+        } else {
+            format!("{} {}", prefix, sub.get_name())
+        };
+        let has_subs = sub.get_subcommands().next().is_some();
+        if !has_subs || !sub.is_subcommand_required_set() {
+            let args = usage_args(sub);
+            let usage = if args.is_empty() {
+                path.clone()
+            } else {
+                format!("{} {}", path, args)
+            };
+            let about = sub.get_about().map(|a| a.to_string()).unwrap_or_default();
+            rows.push((usage, about));
+        }
+        collect_rows(sub, &path, rows);
+    }
+}
+
+/// (corky commands, mailbox commands) generated from the CLI definition.
+fn reference() -> (Vec<(String, String)>, Vec<(String, String)>) {
+    let mut rows = Vec::new();
+    collect_rows(&Cli::command(), "", &mut rows);
+    rows.into_iter()
+        .partition(|(usage, _)| usage != "mailbox" && !usage.starts_with("mailbox "))
+}
+
+/// Write one man page per command and subcommand (`corky.1`, `corky-sync.1`, ...).
+pub fn write_man(out_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    clap_mangen::generate_to(Cli::command(), out_dir)?;
+    println!("Wrote man pages to {}", out_dir.display());
+    Ok(())
+}
+
+/// Write the full command reference as `corky.md`.
+pub fn write_markdown(out_dir: &Path) -> Result<()> {
+    std::fs::create_dir_all(out_dir)?;
+    let path = out_dir.join("corky.md");
+    std::fs::write(&path, clap_markdown::help_markdown::<Cli>())?;
+    println!("Wrote {}", path.display());
+    Ok(())
+}
+
 pub fn run(filter: Option<&str>) -> Result<()> {
+    let (commands, mailbox_commands) = reference();
+    let commands: Vec<(&str, &str)> = commands
+        .iter()
+        .map(|(a, b)| (a.as_str(), b.as_str()))
+        .collect();
+    let mailbox_commands: Vec<(&str, &str)> = mailbox_commands
+        .iter()
+        .map(|(a, b)| (a.as_str(), b.as_str()))
+        .collect();
+
     if let Some(filter) = filter {
         if filter != "--dev" {
-            let all_cmds: Vec<(&str, &str)> = COMMANDS
+            let all_cmds: Vec<(&str, &str)> = commands
                 .iter()
-                .chain(MAILBOX_COMMANDS.iter())
+                .chain(mailbox_commands.iter())
                 .chain(DEV_COMMANDS.iter())
                 .copied()
                 .collect();
@@ -59,8 +117,7 @@ pub fn run(filter: Option<&str>) -> Result<()> {
                 .filter(|(name, _)| name.contains(filter))
                 .collect();
             if matches.is_empty() {
-                println!("No command matching '{}'", filter);
-                std::process::exit(1);
+                bail!("No command matching '{}'", filter);
             }
             print_table(&matches.iter().map(|&&(a, b)| (a, b)).collect::<Vec<_>>());
             return Ok(());
@@ -68,10 +125,10 @@ pub fn run(filter: Option<&str>) -> Result<()> {
     }
 
     println!("corky commands\n");
-    print_table(COMMANDS);
+    print_table(&commands);
 
     println!("\nmailbox commands (alias: mb)\n");
-    print_table(MAILBOX_COMMANDS);
+    print_table(&mailbox_commands);
 
     if filter == Some("--dev") || filter.is_none() {
         println!("\ndev commands\n");
@@ -82,8 +139,42 @@ pub fn run(filter: Option<&str>) -> Result<()> {
 }
 
 fn print_table(rows: &[(&str, &str)]) {
-    let name_w = rows.iter().map(|(n, _)| n.len()).max().unwrap_or(0);
-    for (name, desc) in rows {
-        println!("  {:<width$}  {}", name, desc, width = name_w);
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|(name, desc)| vec![output::paint(name, Style::Bold), desc.to_string()])
+        .collect();
+    output::print_table(&rows);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_covers_cli_commands() {
+        let (commands, mailbox_commands) = reference();
+        let names: Vec<&str> = commands.iter().map(|(u, _)| u.as_str()).collect();
+        assert!(names.contains(&"sync"));
+        assert!(names.contains(&"sync full"));
+        assert!(names.iter().any(|n| n.starts_with("unanswered [SCOPE]")));
+        assert!(names.iter().any(|n| n.starts_with("init") && n.contains("--user")));
+        // Hidden legacy aliases stay out of the reference.
+        assert!(!names.iter().any(|n| n.starts_with("install-skill")));
+        // `skill` requires a subcommand, so only its children are listed.
+        assert!(!names.contains(&"skill"));
+        assert!(mailbox_commands.iter().all(|(u, _)| u.starts_with("mailbox")));
+        assert!(mailbox_commands.iter().any(|(u, _)| u.starts_with("mailbox add")));
+    }
+
+    #[test]
+    fn write_man_and_markdown() {
+        let dir = tempfile::TempDir::new().unwrap();
+        write_man(dir.path()).unwrap();
+        assert!(dir.path().join("corky.1").exists());
+        assert!(dir.path().join("corky-sync.1").exists());
+
+        write_markdown(dir.path()).unwrap();
+        let md = std::fs::read_to_string(dir.path().join("corky.md")).unwrap();
+        assert!(md.contains("corky sync"));
     }
 }
@@ -1,10 +1,12 @@
 //! Initialize a new corky project directory with config and folder structure.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use std::path::{Path, PathBuf};
 
 use crate::accounts::provider_presets;
 use crate::app_config;
+use crate::error::CorkyError;
+use crate::mail_clients;
 
 const VOICE_MD: &str = include_str!("../voice.md");
 
@@ -46,22 +48,192 @@ fn generate_corky_toml(
 
     // Account section
     let mut accounts = toml_edit::Table::new();
-    let mut default_acct = toml_edit::Table::new();
-    default_acct.insert("provider", toml_edit::value(provider));
-    default_acct.insert("user", toml_edit::value(user));
+    let default_acct = account_table(user, provider, password_cmd, labels, true);
+    accounts.insert("default", toml_edit::Item::Table(default_acct));
+    doc.insert("accounts", toml_edit::Item::Table(accounts));
+
+    doc.to_string()
+}
+
+/// Build an `[accounts.X]` table.
+fn account_table(
+    user: &str,
+    provider: &str,
+    password_cmd: &str,
+    labels: &[String],
+    default: bool,
+) -> toml_edit::Table {
+    let mut acct = toml_edit::Table::new();
+    acct.insert("provider", toml_edit::value(provider));
+    acct.insert("user", toml_edit::value(user));
     let mut labels_arr = toml_edit::Array::new();
     for label in labels {
         labels_arr.push(label.as_str());
     }
-    default_acct.insert("labels", toml_edit::value(labels_arr));
-    default_acct.insert("default", toml_edit::value(true));
+    acct.insert("labels", toml_edit::value(labels_arr));
+    if default {
+        acct.insert("default", toml_edit::value(true));
+    }
     if !password_cmd.is_empty() {
-        default_acct.insert("password_cmd", toml_edit::value(password_cmd));
+        acct.insert("password_cmd", toml_edit::value(password_cmd));
     }
-    accounts.insert("default", toml_edit::Item::Table(default_acct));
-    doc.insert("accounts", toml_edit::Item::Table(accounts));
+    acct
+}
 
-    doc.to_string()
+/// Append `[accounts.NAME]` to an existing .corky.toml.
+///
+/// Uses toml_edit so the rest of the file (comments, ordering) is preserved.
+/// An existing account of the same name is replaced only with `force`.
+pub fn add_account(
+    config_path: &Path,
+    account_name: &str,
+    user: &str,
+    provider: &str,
+    password_cmd: &str,
+    labels: &[String],
+    force: bool,
+) -> Result<()> {
+    if !config_path.exists() {
+        bail!(
+            "Config not found at {}\nRun 'corky init --user EMAIL' first.",
+            config_path.display()
+        );
+    }
+    let _lock = crate::config::lock::lock(config_path)?;
+    let content = std::fs::read_to_string(config_path)?;
+    let mut doc = content.parse::<toml_edit::DocumentMut>()?;
+
+    let accounts = doc
+        .entry("accounts")
+        .or_insert_with(|| toml_edit::Item::Table(toml_edit::Table::new()));
+    let Some(accounts) = accounts.as_table_mut() else {
+        bail!("[accounts] in {} is not a table", config_path.display());
+    };
+    if accounts.contains_key(account_name) && !force {
+        bail!(
+            "Account '{}' already exists in {}. Use --force to replace it.",
+            account_name,
+            config_path.display()
+        );
+    }
+    // Keep [accounts] as a header-less parent so the new table renders as [accounts.NAME].
+    accounts.set_implicit(true);
+    let is_first = accounts.is_empty();
+    let table = account_table(user, provider, password_cmd, labels, is_first);
+    accounts.insert(account_name, toml_edit::Item::Table(table));
+
+    crate::config::lock::write(config_path, &doc.to_string())?;
+    Ok(())
+}
+
+/// corky init --add-account NAME --user EMAIL [PATH] [--list-folders]
+#[allow(clippy::too_many_arguments)]
+pub fn run_add_account(
+    account_name: &str,
+    user: &str,
+    path: &Path,
+    provider: &str,
+    password_cmd: &str,
+    labels_str: &str,
+    list_folders: bool,
+    force: bool,
+) -> Result<()> {
+    let path = if path.starts_with("~") {
+        crate::resolve::expand_tilde(&path.to_string_lossy())
+    } else {
+        path.to_path_buf()
+    };
+    let data_dir = path.join("mail");
+    let config_path = data_dir.join(".corky.toml");
+    let labels: Vec<String> = labels_str
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+
+    add_account(
+        &config_path,
+        account_name,
+        user,
+        provider,
+        password_cmd,
+        &labels,
+        force,
+    )?;
+    println!(
+        "Added [accounts.{}] ({}) to {}",
+        account_name,
+        user,
+        config_path.display()
+    );
+
+    if list_folders {
+        list_account_folders(&data_dir, account_name)?;
+    } else {
+        println!("  List folders to choose labels: corky list-folders {}", account_name);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "net")]
+fn list_account_folders(data_dir: &Path, account_name: &str) -> Result<()> {
+    // SAFETY: Single-threaded CLI path; no other threads read the environment yet.
+    unsafe { std::env::set_var("CORKY_DATA", data_dir.to_string_lossy().as_ref()) };
+    println!();
+    crate::sync::folders::run(Some(account_name), false, false, &[])?;
+    println!();
+    println!("Add labels with: corky add-label LABEL --account {}", account_name);
+    Ok(())
+}
+
+#[cfg(not(feature = "net"))]
+fn list_account_folders(_data_dir: &Path, _account_name: &str) -> Result<()> {
+    Err(crate::util::missing_feature("net"))
+}
+
+/// Detect accounts configured in other mail tools and import them into
+/// .corky.toml. Asks first unless `assume_yes`; never asks without a terminal.
+fn import_detected_accounts(config_path: &Path, assume_yes: bool) -> Result<()> {
+    use std::io::{IsTerminal, Write};
+
+    let detected = mail_clients::detect(&crate::resolve::home_dir());
+    if detected.is_empty() {
+        return Ok(());
+    }
+    println!();
+    println!("Found {} account(s) in other mail tools:", detected.len());
+    for acct in &detected {
+        println!(
+            "  {:<12} {} ({}) {}:{}",
+            acct.source,
+            mail_clients::account_name(acct),
+            acct.user,
+            acct.imap_host,
+            acct.imap_port.map(|p| p.to_string()).unwrap_or_default()
+        );
+    }
+    if !assume_yes {
+        if !std::io::stdin().is_terminal() {
+            println!("Re-run with --import to add them.");
+            return Ok(());
+        }
+        print!("Import their server settings? [y/N] ");
+        std::io::stdout().flush()?;
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        if input.trim().to_lowercase() != "y" {
+            println!("Skipped import");
+            return Ok(());
+        }
+    }
+    let added = mail_clients::import(config_path, &detected)?;
+    if added.is_empty() {
+        println!("All detected accounts are already configured");
+    } else {
+        println!("Imported accounts: {}", added.join(", "));
+        println!("  Pick labels with: corky list-folders NAME, then corky add-label LABEL --account NAME");
+    }
+    Ok(())
 }
 
 /// Find the git repo root containing `start`.
@@ -124,6 +296,8 @@ pub fn run(
     sync: bool,
     mailbox: &str,
     force: bool,
+    with_skills: &[String],
+    import: Option<bool>,
 ) -> Result<()> {
     // 1. Resolve project path
     let path = if path.starts_with("~") {
@@ -144,9 +318,11 @@ pub fn run(
 
     let config_path = data_dir.join(".corky.toml");
     if config_path.exists() && !force {
-        eprintln!(".corky.toml already exists at {}", config_path.display());
-        eprintln!("Use --force to overwrite.");
-        std::process::exit(1);
+        return Err(CorkyError::Config(format!(
+            ".corky.toml already exists at {}\nUse --force to overwrite.",
+            config_path.display()
+        ))
+        .into());
     }
 
     // 2. Create mail/{conversations,drafts,contacts}/
@@ -162,6 +338,11 @@ pub fn run(
     std::fs::write(&config_path, &content)?;
     println!("Created {}", config_path.display());
 
+    // 3b. Offer to import accounts from mbsync/offlineimap/Thunderbird
+    if import != Some(false) {
+        import_detected_accounts(&config_path, import == Some(true))?;
+    }
+
     // 4. Install voice.md inside mail/
     install_voice_md(&data_dir)?;
 
@@ -170,8 +351,12 @@ pub fn run(
         ensure_gitignore_entry(&repo_root, "mail")?;
     }
 
-    // 6. Install email skill
+    // 6. Install email skill (plus any --with-skill extras)
     crate::skill::install_at(Some(&path))?;
+    for skill in with_skills {
+        crate::skill::install_target_at(Some(&path), skill, crate::skill::Target::Claude)?;
+        println!("Installed .claude/skills/{}/SKILL.md", skill);
+    }
 
     // 7. Register mailbox in app config
     app_config::add_mailbox(mailbox, &path.to_string_lossy())?;
@@ -196,7 +381,7 @@ pub fn run(
         // SAFETY: This runs during single-threaded init before any sync threads start.
         unsafe { std::env::set_var("CORKY_DATA", data_dir.to_string_lossy().as_ref()) };
         println!();
-        crate::sync::run(false, None)?;
+        crate::sync::run(false, None, false, false, false, false, None)?;
     }
 
     if !sync {
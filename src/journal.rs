@@ -0,0 +1,328 @@
+//! Persistent watch log and error journal.
+//!
+//! `corky watch` appends one line per event to `logs/watch.log` in the data
+//! dir, rotating it to `watch.log.1`, `watch.log.2`, ... once it passes
+//! `[watch] log_max_bytes`. Failures also go to `logs/errors.jsonl`, one
+//! JSON object per line, which `corky watch status` and `corky status` read.
+
+use anyhow::Result;
+use chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::accounts::{load_accounts, load_watch_config};
+use crate::config::corky_config;
+use crate::mailbox::review;
+use crate::resolve;
+
+/// The journal is trimmed to the newest `JOURNAL_KEEP` entries once it
+/// grows past `JOURNAL_MAX`.
+const JOURNAL_MAX: usize = 1000;
+const JOURNAL_KEEP: usize = 500;
+
+/// One failure recorded by the watch daemon.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ErrorEntry {
+    /// UTC time, RFC 3339.
+    pub time: String,
+    /// What failed: `account NAME`, `mailbox NAME`, `schedule`, ...
+    pub source: String,
+    pub message: String,
+}
+
+pub fn logs_dir() -> PathBuf {
+    resolve::data_dir().join("logs")
+}
+
+pub fn watch_log_file() -> PathBuf {
+    logs_dir().join("watch.log")
+}
+
+pub fn errors_file() -> PathBuf {
+    logs_dir().join("errors.jsonl")
+}
+
+fn now() -> String {
+    Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+/// `watch.log` → `watch.log.1`, shifting older files up and dropping the
+/// one past `keep`. `keep = 0` just truncates.
+fn rotate(path: &Path, keep: usize) -> Result<()> {
+    let numbered = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+    if keep == 0 {
+        std::fs::remove_file(path)?;
+        return Ok(());
+    }
+    let _ = std::fs::remove_file(numbered(keep));
+    for n in (1..keep).rev() {
+        let from = numbered(n);
+        if from.exists() {
+            std::fs::rename(&from, numbered(n + 1))?;
+        }
+    }
+    std::fs::rename(path, numbered(1))?;
+    Ok(())
+}
+
+/// Append `line` to a log file, rotating first when it has reached
+/// `max_bytes` (0 = never rotate).
+fn append_rotating(path: &Path, line: &str, max_bytes: u64, keep: usize) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    if max_bytes > 0 {
+        if let Ok(meta) = std::fs::metadata(path) {
+            if meta.len() >= max_bytes {
+                rotate(path, keep)?;
+            }
+        }
+    }
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    writeln!(file, "{}", line)?;
+    Ok(())
+}
+
+/// The watch daemon's log: timestamped lines with size-based rotation.
+/// Clones share one error count.
+#[derive(Debug, Clone)]
+pub struct WatchLog {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    errors: Arc<AtomicUsize>,
+}
+
+impl WatchLog {
+    pub fn new(path: PathBuf, max_bytes: u64, keep: usize) -> Self {
+        Self {
+            path,
+            max_bytes,
+            keep,
+            errors: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    /// Write one line. Logging is best-effort: a failure to write is
+    /// reported on stderr but never stops the daemon.
+    pub fn line(&self, msg: &str) {
+        let line = format!("{} {}", now(), msg.replace('\n', " "));
+        if let Err(e) = append_rotating(&self.path, &line, self.max_bytes, self.keep) {
+            eprintln!("corky watch: cannot write {}: {}", self.path.display(), e);
+        }
+    }
+
+    /// Print an error, log it, and add it to the error journal.
+    pub fn error(&self, source: &str, message: &str) {
+        self.errors.fetch_add(1, Ordering::Relaxed);
+        eprintln!("{}: {}", source, message);
+        self.line(&format!("ERROR {}: {}", source, message));
+        if let Err(e) = record_error(&errors_file(), source, message) {
+            eprintln!("corky watch: cannot write error journal: {}", e);
+        }
+    }
+
+    /// How many errors this log (or a clone) has recorded.
+    pub fn error_count(&self) -> usize {
+        self.errors.load(Ordering::Relaxed)
+    }
+}
+
+/// Append an entry to the error journal at `path`, trimming old entries.
+pub fn record_error(path: &Path, source: &str, message: &str) -> Result<()> {
+    let entry = ErrorEntry {
+        time: now(),
+        source: source.to_string(),
+        message: message.to_string(),
+    };
+    append_rotating(path, &serde_json::to_string(&entry)?, 0, 0)?;
+
+    let text = std::fs::read_to_string(path)?;
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() > JOURNAL_MAX {
+        let kept = lines[lines.len() - JOURNAL_KEEP..].join("\n");
+        crate::config::lock::write(path, &format!("{}\n", kept))?;
+    }
+    Ok(())
+}
+
+/// The newest `n` journal entries, oldest first. Unreadable lines are skipped.
+pub fn recent_errors(path: &Path, n: usize) -> Vec<ErrorEntry> {
+    let text = std::fs::read_to_string(path).unwrap_or_default();
+    let entries: Vec<ErrorEntry> = text
+        .lines()
+        .filter_map(|l| serde_json::from_str(l).ok())
+        .collect();
+    let skip = entries.len().saturating_sub(n);
+    entries.into_iter().skip(skip).collect()
+}
+
+/// Last line of the watch log, if any.
+fn last_log_line(path: &Path) -> Option<String> {
+    let text = std::fs::read_to_string(path).ok()?;
+    text.lines().last().map(|l| l.to_string())
+}
+
+fn print_errors(n: usize) {
+    let errors = recent_errors(&errors_file(), n);
+    if errors.is_empty() {
+        println!("Recent errors: none");
+        return;
+    }
+    println!("Recent errors ({}):", errors.len());
+    for e in &errors {
+        println!("  {}  {}: {}", e.time, e.source, e.message);
+    }
+}
+
+/// corky watch status [-n N]
+pub fn watch_status(errors: usize) -> Result<()> {
+    let config = load_watch_config(None)?;
+    let log = watch_log_file();
+    println!("Log: {}", log.display());
+    match last_log_line(&log) {
+        Some(line) => println!("Last activity: {}", line),
+        None => println!("Last activity: none (watch has not run)"),
+    }
+    println!("Poll interval: {}s", config.poll_interval);
+    print_errors(errors);
+    Ok(())
+}
+
+/// Drafts awaiting review in each `(name, path)` mailbox, one
+/// `  NAME  drafts/FILE.md  Subject (Author)` line per draft.
+fn review_lines(mailboxes: &[(String, PathBuf)]) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (name, mb_path) in mailboxes {
+        for draft in review::review_queue(mb_path) {
+            let rel = draft.path.strip_prefix(mb_path).unwrap_or(&draft.path);
+            let mut line = format!("  {}  {}  {}", name, rel.display(), draft.subject);
+            if !draft.author.is_empty() {
+                line.push_str(&format!(" ({})", draft.author));
+            }
+            lines.push(line);
+        }
+    }
+    lines
+}
+
+/// corky status [-n N]
+pub fn status(errors: usize) -> Result<()> {
+    println!("Data dir: {}", resolve::data_dir().display());
+    let accounts = load_accounts(None).unwrap_or_default();
+    if accounts.is_empty() {
+        println!("Accounts: none");
+    } else {
+        let mut names: Vec<&str> = accounts.keys().map(|k| k.as_str()).collect();
+        names.sort();
+        println!("Accounts: {}", names.join(", "));
+    }
+    let state = resolve::sync_state_read_file();
+    match std::fs::metadata(&state).and_then(|m| m.modified()) {
+        Ok(t) => {
+            let t: chrono::DateTime<Utc> = t.into();
+            println!(
+                "Last sync: {}",
+                t.to_rfc3339_opts(SecondsFormat::Secs, true)
+            );
+        }
+        Err(_) => println!("Last sync: never"),
+    }
+    match last_log_line(&watch_log_file()) {
+        Some(line) => println!("Watch: {}", line),
+        None => println!("Watch: no log"),
+    }
+    let pinned = crate::thread::pin::pinned_in_manifest(&resolve::manifest_file());
+    if !pinned.is_empty() {
+        println!("Pinned ({}):", pinned.len());
+        for (slug, subject) in &pinned {
+            println!("  {}.md  {}", slug, subject);
+        }
+    }
+    let mut names: Vec<String> = corky_config::try_load_config(None)
+        .map(|c| c.mailboxes.keys().cloned().collect())
+        .unwrap_or_default();
+    names.sort();
+    let mailboxes: Vec<(String, PathBuf)> = names
+        .into_iter()
+        .map(|n| {
+            let path = resolve::mailbox_dir(&n);
+            (n, path)
+        })
+        .collect();
+    let review = review_lines(&mailboxes);
+    if !review.is_empty() {
+        println!("Awaiting review ({}):", review.len());
+        for line in &review {
+            println!("{}", line);
+        }
+    }
+    print_errors(errors);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rotation_keeps_numbered_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("watch.log");
+        for i in 0..5 {
+            append_rotating(&log, &format!("line {}", i), 1, 2).unwrap();
+        }
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "line 4\n");
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("watch.log.1")).unwrap(),
+            "line 3\n"
+        );
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join("watch.log.2")).unwrap(),
+            "line 2\n"
+        );
+        assert!(!dir.path().join("watch.log.3").exists());
+    }
+
+    #[test]
+    fn test_error_journal_recent_and_trim() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("errors.jsonl");
+        assert!(recent_errors(&path, 5).is_empty());
+        for i in 0..JOURNAL_MAX + 1 {
+            record_error(&path, "account gmail", &format!("failure {}", i)).unwrap();
+        }
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(text.lines().count(), JOURNAL_KEEP);
+        let recent = recent_errors(&path, 2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[0].message, format!("failure {}", JOURNAL_MAX - 1));
+        assert_eq!(recent[1].message, format!("failure {}", JOURNAL_MAX));
+        assert_eq!(recent[1].source, "account gmail");
+    }
+
+    #[test]
+    fn test_review_lines_list_mailbox_review_drafts() {
+        let dir = tempfile::tempdir().unwrap();
+        let lucas = dir.path().join("lucas");
+        std::fs::create_dir_all(lucas.join("drafts")).unwrap();
+        let draft = "# Re: Lunch\n\n**To**: alice@example.com\n**Status**: review\n\
+                     **Author**: Lucas\n\n---\n\nNoon works.\n";
+        std::fs::write(lucas.join("drafts/lunch.md"), draft).unwrap();
+        std::fs::write(
+            lucas.join("drafts/wip.md"),
+            draft.replace("review", "draft"),
+        )
+        .unwrap();
+        let empty = dir.path().join("empty");
+
+        let lines = review_lines(&[("lucas".to_string(), lucas), ("empty".to_string(), empty)]);
+        assert_eq!(lines, vec!["  lucas  drafts/lunch.md  Re: Lunch (Lucas)"]);
+    }
+}
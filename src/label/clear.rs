@@ -1,5 +1,5 @@
 use anyhow::{Context, Result};
-use crate::accounts::{load_accounts, resolve_password};
+use crate::accounts::{account_secret, load_accounts};
 use crate::sync::imap_sync::connect_imap_pub;
 
 /// Remove a Gmail/IMAP label from all messages (or those matching a search query).
@@ -7,6 +7,9 @@ use crate::sync::imap_sync::connect_imap_pub;
 /// For Gmail: selecting a label folder, marking messages \Deleted, and expunging
 /// removes the label without deleting the messages themselves.
 pub fn run(label: &str, account: Option<&str>, search: Option<&str>, dry_run: bool) -> Result<()> {
+    if !dry_run {
+        crate::util::ensure_unrestricted("Clearing labels on the server")?;
+    }
     let accounts = load_accounts(None)?;
 
     let target_accounts: Vec<(&str, &crate::accounts::Account)> = if let Some(name) = account {
@@ -20,7 +23,7 @@ pub fn run(label: &str, account: Option<&str>, search: Option<&str>, dry_run: bo
     };
 
     for (acct_name, acct) in &target_accounts {
-        let password = resolve_password(acct)?;
+        let secret = account_secret(acct_name, acct)?;
         println!("Connecting to {}:{} as {}", acct.imap_host, acct.imap_port, acct.user);
 
         let mut session = connect_imap_pub(
@@ -28,7 +31,7 @@ pub fn run(label: &str, account: Option<&str>, search: Option<&str>, dry_run: bo
             acct.imap_port,
             acct.imap_starttls,
             &acct.user,
-            &password,
+            &secret,
         )?;
 
         // Select the label folder
@@ -65,11 +68,24 @@ pub fn run(label: &str, account: Option<&str>, search: Option<&str>, dry_run: bo
             continue;
         }
 
+        let question = format!(
+            "Clear label \"{}\" from {} message(s) on account '{}'?",
+            label,
+            uids.len(),
+            acct_name
+        );
+        if !crate::output::confirm(&question)? {
+            println!("  Skipped account '{}'", acct_name);
+            let _ = session.logout();
+            continue;
+        }
+
         // Process in batches of 500 UIDs to avoid IMAP command length limits
         let batch_size = 500;
         let total = uids.len();
         let mut cleared = 0;
 
+        let mut result = Ok(());
         for chunk in uids.chunks(batch_size) {
             let uid_set = chunk
                 .iter()
@@ -77,17 +93,25 @@ pub fn run(label: &str, account: Option<&str>, search: Option<&str>, dry_run: bo
                 .collect::<Vec<_>>()
                 .join(",");
 
-            session
+            result = session
                 .uid_store(&uid_set, "+FLAGS (\\Deleted)")
-                .with_context(|| "Failed to mark messages as deleted")?;
+                .map(|_| ())
+                .with_context(|| "Failed to mark messages as deleted");
+            if result.is_err() {
+                break;
+            }
 
             cleared += chunk.len();
             if total > batch_size {
                 println!("  Progress: {}/{}", cleared, total);
             }
         }
-
-        session.expunge().context("EXPUNGE failed")?;
+        if result.is_ok() {
+            result = session.expunge().map(|_| ()).context("EXPUNGE failed");
+        }
+        let target = format!("{}:{} ({} messages)", acct_name, label, total);
+        crate::audit::record("imap delete", &target, &result);
+        result?;
 
         println!(
             "  Cleared label \"{}\" from {} message(s) (account: {})",
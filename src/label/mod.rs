@@ -1 +1,2 @@
+#[cfg(feature = "net")]
 pub mod clear;
@@ -1,23 +1,48 @@
+#[cfg(all(
+    any(feature = "net", feature = "smtp"),
+    not(any(feature = "tls-native", feature = "tls-rustls"))
+))]
+compile_error!("The `net` and `smtp` features need a TLS backend: enable `tls-native` or `tls-rustls`.");
+
 pub mod accounts;
+pub mod address;
+pub mod audit;
 pub mod cal;
 pub mod app_config;
 pub mod cli;
 pub mod config;
 pub mod contact;
+pub mod digest;
 pub mod doc;
 pub mod draft;
+pub mod error;
+pub mod gc;
 pub mod init;
 pub mod filter;
+pub mod github;
+pub mod journal;
 pub mod label;
+pub mod mail_clients;
 pub mod mailbox;
+pub mod migrate;
+pub mod output;
+pub mod profile;
+pub mod prune;
+pub mod publish;
 pub mod resolve;
 pub mod skill;
 pub mod sync;
+#[cfg(feature = "testserver")]
+pub mod testserver;
+pub mod thread;
 pub mod util;
+#[cfg(feature = "watch")]
 pub mod watch;
 pub mod help;
 pub mod audit_docs;
 pub mod schedule;
+#[cfg(feature = "sqlite")]
+pub mod search;
 pub mod social;
 pub mod topics;
 pub mod transcribe;
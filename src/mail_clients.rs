@@ -0,0 +1,422 @@
+//! Detect IMAP accounts configured in other mail tools (mbsync, offlineimap,
+//! Thunderbird) so `corky init` can import their server settings.
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// An IMAP account found in another tool's config.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DetectedAccount {
+    /// "mbsync", "offlineimap", or "thunderbird".
+    pub source: &'static str,
+    /// Account name in the source config.
+    pub name: String,
+    pub user: String,
+    pub imap_host: String,
+    pub imap_port: Option<u16>,
+    pub imap_starttls: bool,
+    pub smtp_host: String,
+    pub smtp_port: Option<u16>,
+    /// Shell command that prints the password (mbsync `PassCmd`).
+    pub password_cmd: String,
+}
+
+impl DetectedAccount {
+    /// corky provider for the host: a preset when it matches one, else "imap".
+    pub fn provider(&self) -> &'static str {
+        match (self.imap_host.as_str(), self.imap_port) {
+            ("imap.gmail.com", _) => "gmail",
+            ("127.0.0.1", Some(1143)) => "protonmail-bridge",
+            _ => "imap",
+        }
+    }
+}
+
+fn unquote(s: &str) -> &str {
+    let s = s.trim();
+    s.strip_prefix('"')
+        .and_then(|s| s.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+/// Parse `IMAPAccount` blocks from an mbsync config (~/.mbsyncrc).
+pub fn parse_mbsync(text: &str) -> Vec<DetectedAccount> {
+    let mut accounts = Vec::new();
+    let mut current: Option<DetectedAccount> = None;
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            accounts.extend(current.take());
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let value = value.trim();
+        if key.eq_ignore_ascii_case("IMAPAccount") {
+            accounts.extend(current.take());
+            current = Some(DetectedAccount {
+                source: "mbsync",
+                name: value.to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(acct) = current.as_mut() else {
+            continue;
+        };
+        match key.to_ascii_lowercase().as_str() {
+            "host" => acct.imap_host = value.to_string(),
+            "port" => acct.imap_port = value.parse().ok(),
+            "user" => acct.user = value.to_string(),
+            "passcmd" => acct.password_cmd = unquote(value).to_string(),
+            "ssltype" => acct.imap_starttls = value.eq_ignore_ascii_case("STARTTLS"),
+            // Any other section keyword ends the account block.
+            "imapstore" | "maildirstore" | "channel" | "group" => accounts.extend(current.take()),
+            _ => {}
+        }
+    }
+    accounts.extend(current);
+    accounts.retain(|a| !a.imap_host.is_empty());
+    accounts
+}
+
+/// Parse an offlineimap config (~/.offlineimaprc): each `[Account X]` and its
+/// `remoterepository`.
+pub fn parse_offlineimap(text: &str) -> Vec<DetectedAccount> {
+    let mut sections: Vec<(String, HashMap<String, String>)> = Vec::new();
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            sections.push((name.trim().to_string(), HashMap::new()));
+        } else if let (Some((_, map)), Some((key, value))) =
+            (sections.last_mut(), line.split_once('='))
+        {
+            map.insert(key.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    let section = |name: &str| sections.iter().find(|(n, _)| n == name).map(|(_, m)| m);
+
+    let mut accounts = Vec::new();
+    for (header, map) in &sections {
+        let Some(name) = header.strip_prefix("Account ") else {
+            continue;
+        };
+        let Some(repo) = map
+            .get("remoterepository")
+            .and_then(|r| section(&format!("Repository {}", r)))
+        else {
+            continue;
+        };
+        let get = |k: &str| repo.get(k).cloned().unwrap_or_default();
+        let imap_host = match get("type").to_lowercase().as_str() {
+            "gmail" => "imap.gmail.com".to_string(),
+            "imap" => get("remotehost"),
+            _ => continue,
+        };
+        if imap_host.is_empty() {
+            continue;
+        }
+        accounts.push(DetectedAccount {
+            source: "offlineimap",
+            name: name.trim().to_string(),
+            user: get("remoteuser"),
+            imap_host,
+            imap_port: get("remoteport").parse().ok(),
+            imap_starttls: get("starttls").eq_ignore_ascii_case("yes"),
+            ..Default::default()
+        });
+    }
+    accounts
+}
+
+/// Parse IMAP servers from a Thunderbird profile's prefs.js.
+pub fn parse_thunderbird_prefs(text: &str) -> Vec<DetectedAccount> {
+    // user_pref("mail.server.server1.hostname", "imap.example.com");
+    let mut prefs: HashMap<&str, &str> = HashMap::new();
+    for line in text.lines() {
+        let Some(rest) = line
+            .trim()
+            .strip_prefix("user_pref(")
+            .and_then(|r| r.strip_suffix(");"))
+        else {
+            continue;
+        };
+        if let Some((key, value)) = rest.split_once(',') {
+            prefs.insert(unquote(key), unquote(value));
+        }
+    }
+
+    let mut servers: Vec<&str> = prefs
+        .keys()
+        .filter_map(|k| k.strip_prefix("mail.server.")?.strip_suffix(".type"))
+        .collect();
+    servers.sort();
+
+    // First SMTP server, if any, applies to every account (Thunderbird's default).
+    let smtp = prefs
+        .get("mail.smtp.defaultserver")
+        .or_else(|| prefs.get("mail.smtpservers"))
+        .and_then(|s| s.split(',').next())
+        .map(|id| id.trim().to_string());
+    let smtp_pref = |field: &str| {
+        smtp.as_ref()
+            .and_then(|id| prefs.get(format!("mail.smtpserver.{}.{}", id, field).as_str()))
+            .map(|v| v.to_string())
+    };
+
+    let mut accounts = Vec::new();
+    for server in servers {
+        let pref = |field: &str| {
+            prefs
+                .get(format!("mail.server.{}.{}", server, field).as_str())
+                .copied()
+                .unwrap_or_default()
+        };
+        if pref("type") != "imap" || pref("hostname").is_empty() {
+            continue;
+        }
+        let name = match pref("name") {
+            "" => server,
+            n => n,
+        };
+        accounts.push(DetectedAccount {
+            source: "thunderbird",
+            name: name.to_string(),
+            user: pref("userName").to_string(),
+            imap_host: pref("hostname").to_string(),
+            imap_port: pref("port").parse().ok(),
+            // socketType 2 = STARTTLS, 3 = SSL/TLS
+            imap_starttls: pref("socketType") == "2",
+            smtp_host: smtp_pref("hostname").unwrap_or_default(),
+            smtp_port: smtp_pref("port").and_then(|p| p.parse().ok()),
+            password_cmd: String::new(),
+        });
+    }
+    accounts
+}
+
+/// Scan `home` for mbsync, offlineimap, and Thunderbird (Linux and macOS) configs.
+pub fn detect(home: &Path) -> Vec<DetectedAccount> {
+    let read = |p: &Path| std::fs::read_to_string(p).ok();
+    let mut accounts = Vec::new();
+    for rc in [".mbsyncrc", ".config/isyncrc"] {
+        if let Some(text) = read(&home.join(rc)) {
+            accounts.extend(parse_mbsync(&text));
+        }
+    }
+    for rc in [".offlineimaprc", ".config/offlineimap/config"] {
+        if let Some(text) = read(&home.join(rc)) {
+            accounts.extend(parse_offlineimap(&text));
+        }
+    }
+    for profiles in [
+        ".thunderbird/*/prefs.js",
+        "Library/Thunderbird/Profiles/*/prefs.js",
+    ] {
+        let pattern = home.join(profiles);
+        let Ok(paths) = glob::glob(&pattern.to_string_lossy()) else {
+            continue;
+        };
+        for path in paths.flatten() {
+            if let Some(text) = read(&path) {
+                accounts.extend(parse_thunderbird_prefs(&text));
+            }
+        }
+    }
+    accounts
+}
+
+/// corky account name for an imported account (slugified source name).
+pub fn account_name(acct: &DetectedAccount) -> String {
+    let base = if acct.name.is_empty() {
+        &acct.user
+    } else {
+        &acct.name
+    };
+    crate::util::slugify(base)
+}
+
+/// Append detected accounts to .corky.toml as `[accounts.NAME]` tables with
+/// empty `labels` (pick them with `corky list-folders`). Accounts whose user
+/// or name is already configured are skipped. Returns the names added.
+pub fn import(config_path: &Path, detected: &[DetectedAccount]) -> Result<Vec<String>> {
+    let _lock = crate::config::lock::lock(config_path)?;
+    let content = std::fs::read_to_string(config_path)?;
+    let mut doc = content.parse::<toml_edit::DocumentMut>()?;
+    let accounts = doc
+        .entry("accounts")
+        .or_insert_with(|| toml_edit::Item::Table(toml_edit::Table::new()));
+    let Some(accounts) = accounts.as_table_mut() else {
+        bail!("[accounts] in {} is not a table", config_path.display());
+    };
+    accounts.set_implicit(true);
+
+    let mut added = Vec::new();
+    for acct in detected {
+        let name = account_name(acct);
+        let user_taken = accounts
+            .iter()
+            .any(|(_, t)| t.get("user").and_then(|u| u.as_str()) == Some(acct.user.as_str()));
+        if accounts.contains_key(&name) || user_taken {
+            continue;
+        }
+        let provider = acct.provider();
+        let mut table = toml_edit::Table::new();
+        table.insert("provider", toml_edit::value(provider));
+        table.insert("user", toml_edit::value(acct.user.as_str()));
+        table.insert("labels", toml_edit::value(toml_edit::Array::new()));
+        if provider == "imap" {
+            table.insert("imap_host", toml_edit::value(acct.imap_host.as_str()));
+            if let Some(port) = acct.imap_port {
+                table.insert("imap_port", toml_edit::value(i64::from(port)));
+            }
+            if acct.imap_starttls {
+                table.insert("imap_starttls", toml_edit::value(true));
+            }
+            if !acct.smtp_host.is_empty() {
+                table.insert("smtp_host", toml_edit::value(acct.smtp_host.as_str()));
+            }
+            if let Some(port) = acct.smtp_port {
+                table.insert("smtp_port", toml_edit::value(i64::from(port)));
+            }
+        }
+        if !acct.password_cmd.is_empty() {
+            table.insert("password_cmd", toml_edit::value(acct.password_cmd.as_str()));
+        }
+        accounts.insert(&name, toml_edit::Item::Table(table));
+        added.push(name);
+    }
+
+    crate::config::lock::write(config_path, &doc.to_string())?;
+    Ok(added)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mbsync() {
+        let rc = "\
+IMAPAccount work
+Host mail.example.com
+Port 143
+User me@example.com
+PassCmd \"pass show work\"
+SSLType STARTTLS
+
+IMAPStore work-remote
+Account work
+
+IMAPAccount gmail
+Host imap.gmail.com
+User me@gmail.com
+SSLType IMAPS
+";
+        let accounts = parse_mbsync(rc);
+        assert_eq!(accounts.len(), 2);
+        assert_eq!(accounts[0].name, "work");
+        assert_eq!(accounts[0].imap_port, Some(143));
+        assert!(accounts[0].imap_starttls);
+        assert_eq!(accounts[0].password_cmd, "pass show work");
+        assert_eq!(accounts[1].provider(), "gmail");
+        assert!(!accounts[1].imap_starttls);
+    }
+
+    #[test]
+    fn test_parse_offlineimap() {
+        let rc = "\
+[general]
+accounts = Personal
+
+[Account Personal]
+localrepository = Local
+remoterepository = Remote
+
+[Repository Local]
+type = Maildir
+
+[Repository Remote]
+type = IMAP
+remotehost = imap.fastmail.com
+remoteport = 993
+remoteuser = me@fastmail.com
+";
+        let accounts = parse_offlineimap(rc);
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].name, "Personal");
+        assert_eq!(accounts[0].imap_host, "imap.fastmail.com");
+        assert_eq!(accounts[0].user, "me@fastmail.com");
+        assert_eq!(accounts[0].provider(), "imap");
+    }
+
+    #[test]
+    fn test_parse_thunderbird_prefs() {
+        let prefs = r#"
+user_pref("mail.server.server1.type", "none");
+user_pref("mail.server.server2.hostname", "imap.example.org");
+user_pref("mail.server.server2.name", "me@example.org");
+user_pref("mail.server.server2.port", 993);
+user_pref("mail.server.server2.socketType", 3);
+user_pref("mail.server.server2.type", "imap");
+user_pref("mail.server.server2.userName", "me@example.org");
+user_pref("mail.smtp.defaultserver", "smtp1");
+user_pref("mail.smtpserver.smtp1.hostname", "smtp.example.org");
+user_pref("mail.smtpserver.smtp1.port", 465);
+"#;
+        let accounts = parse_thunderbird_prefs(prefs);
+        assert_eq!(accounts.len(), 1);
+        let a = &accounts[0];
+        assert_eq!(a.name, "me@example.org");
+        assert_eq!(a.imap_port, Some(993));
+        assert!(!a.imap_starttls);
+        assert_eq!(a.smtp_host, "smtp.example.org");
+        assert_eq!(a.smtp_port, Some(465));
+    }
+
+    #[test]
+    fn test_import_skips_existing_and_writes_server_settings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config = dir.path().join(".corky.toml");
+        std::fs::write(
+            &config,
+            "[accounts.default]\nprovider = \"gmail\"\nuser = \"me@gmail.com\"\nlabels = [\"inbox\"]\n",
+        )
+        .unwrap();
+        let detected = vec![
+            DetectedAccount {
+                source: "mbsync",
+                name: "gmail".into(),
+                user: "me@gmail.com".into(),
+                imap_host: "imap.gmail.com".into(),
+                ..Default::default()
+            },
+            DetectedAccount {
+                source: "mbsync",
+                name: "Work Mail".into(),
+                user: "me@example.com".into(),
+                imap_host: "mail.example.com".into(),
+                imap_port: Some(143),
+                imap_starttls: true,
+                password_cmd: "pass work".into(),
+                ..Default::default()
+            },
+        ];
+        let added = import(&config, &detected).unwrap();
+        assert_eq!(added, vec!["work-mail"]);
+
+        let accounts = crate::accounts::load_accounts(Some(&config)).unwrap();
+        let work = &accounts["work-mail"];
+        assert_eq!(work.imap_host, "mail.example.com");
+        assert_eq!(work.imap_port, 143);
+        assert!(work.imap_starttls);
+        assert_eq!(work.password_cmd, "pass work");
+        assert!(work.labels.is_empty());
+    }
+}
@@ -3,10 +3,11 @@
 use anyhow::Result;
 
 use crate::accounts::load_owner;
+use crate::github;
 use crate::resolve;
 use crate::util::run_cmd_checked;
 
-use super::templates::{generate_agents_md, generate_readme_md};
+use super::templates::{generate_agents_md, generate_readme_md, stamp, TemplateOwner, GITIGNORE};
 
 #[allow(clippy::too_many_arguments)]
 pub fn run(
@@ -28,11 +29,7 @@ pub fn run(
     }
 
     let owner = load_owner(None)?;
-    let owner_name = if owner.name.is_empty() {
-        &owner.github_user
-    } else {
-        &owner.name
-    };
+    let template_owner = TemplateOwner::from_config(&owner)?;
     let mb_display = if display_name.is_empty() {
         &mb_name
     } else {
@@ -61,19 +58,12 @@ pub fn run(
             repo_full,
             visibility.trim_start_matches("--")
         );
-        run_cmd_checked(&["gh", "repo", "create", &repo_full, visibility, "--confirm"])?;
+        github::create_repo(&repo_full, !public)?;
 
         // 2. Add collaborator if not --pat
         if !pat {
             println!("Adding {} as collaborator on {}", gh_user, repo_full);
-            run_cmd_checked(&[
-                "gh",
-                "api",
-                &format!("repos/{}/collaborators/{}", repo_full, gh_user),
-                "-X",
-                "PUT",
-                "--silent",
-            ])?;
+            github::add_collaborator(&repo_full, gh_user)?;
         } else {
             println!();
             println!("PAT access mode selected. The collaborator should:");
@@ -93,24 +83,24 @@ pub fn run(
         let tmpdir = tempfile::tempdir()?;
         let tmp = tmpdir.path();
 
-        run_cmd_checked(&["gh", "repo", "clone", &repo_full, &tmp.to_string_lossy()])?;
+        github::clone_repo(&repo_full, tmp)?;
 
         // AGENTS.md + CLAUDE.md symlink + README.md
         std::fs::write(
             tmp.join("AGENTS.md"),
-            generate_agents_md(mb_display, owner_name),
+            stamp("agents", &generate_agents_md(mb_display, &template_owner)),
         )?;
         #[cfg(unix)]
         std::os::unix::fs::symlink("AGENTS.md", tmp.join("CLAUDE.md"))?;
         std::fs::write(
             tmp.join("README.md"),
-            generate_readme_md(mb_display, owner_name),
+            stamp("readme", &generate_readme_md(mb_display, &template_owner)),
         )?;
 
         // .gitignore
         std::fs::write(
             tmp.join(".gitignore"),
-            "AGENTS.local.md\nCLAUDE.local.md\n__pycache__/\n",
+            GITIGNORE,
         )?;
 
         // voice.md
@@ -139,7 +129,9 @@ pub fn run(
             "-m",
             &format!("Initialize shared mailbox for {}", mb_display),
         ])?;
-        run_cmd_checked(&["git", "-C", &tmp_str, "push"])?;
+        let pushed = run_cmd_checked(&["git", "-C", &tmp_str, "push"]);
+        crate::audit::record("mailbox push", &repo_full, &pushed);
+        pushed?;
 
         // 4. Add as git submodule
         let repo_url = format!("git@github.com:{}.git", repo_full);
@@ -158,13 +150,13 @@ pub fn run(
         // AGENTS.md + CLAUDE.md symlink + README.md
         std::fs::write(
             mb_dir.join("AGENTS.md"),
-            generate_agents_md(mb_display, owner_name),
+            stamp("agents", &generate_agents_md(mb_display, &template_owner)),
         )?;
         #[cfg(unix)]
         std::os::unix::fs::symlink("AGENTS.md", mb_dir.join("CLAUDE.md"))?;
         std::fs::write(
             mb_dir.join("README.md"),
-            generate_readme_md(mb_display, owner_name),
+            stamp("readme", &generate_readme_md(mb_display, &template_owner)),
         )?;
 
         // voice.md
@@ -204,6 +196,7 @@ fn update_config(name: &str, labels: &[String], account: &str) -> Result<()> {
         return Ok(());
     }
 
+    let _lock = crate::config::lock::lock(&config_path)?;
     let content = std::fs::read_to_string(&config_path)?;
     let mut doc = content.parse::<toml_edit::DocumentMut>()?;
 
@@ -248,7 +241,7 @@ fn update_config(name: &str, labels: &[String], account: &str) -> Result<()> {
         }
     }
 
-    std::fs::write(&config_path, doc.to_string())?;
+    crate::config::lock::write(&config_path, &doc.to_string())?;
     println!("Updated {}", config_path.display());
 
     Ok(())
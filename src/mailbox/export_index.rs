@@ -0,0 +1,210 @@
+//! Export thread metadata (manifest + computed fields) as CSV or JSON.
+
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::path::Path;
+
+use super::find_unanswered::{resolve_dirs, Scope};
+use crate::sync::manifest::manifest_threads;
+use crate::sync::markdown::read_thread;
+
+/// One row of `corky export-index`.
+#[derive(Debug, Clone, Serialize)]
+pub struct IndexRow {
+    pub scope: String,
+    pub slug: String,
+    pub subject: String,
+    pub labels: Vec<String>,
+    pub accounts: Vec<String>,
+    pub contacts: Vec<String>,
+    pub tags: Vec<String>,
+    pub first_date: String,
+    pub last_updated: String,
+    pub message_count: usize,
+    pub participant_count: usize,
+    pub last_from: String,
+    pub unanswered: bool,
+}
+
+const CSV_HEADER: &[&str] = &[
+    "scope",
+    "slug",
+    "subject",
+    "labels",
+    "accounts",
+    "contacts",
+    "tags",
+    "first_date",
+    "last_updated",
+    "message_count",
+    "participant_count",
+    "last_from",
+    "unanswered",
+];
+
+fn string_list(entry: &toml::Value, key: &str) -> Vec<String> {
+    entry
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn string_field(entry: &toml::Value, key: &str) -> String {
+    entry
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Build rows for one conversations directory.
+///
+/// A thread is unanswered when its last sender doesn't contain `from_name`
+/// (case-insensitive) and it isn't tagged `newsletter`, matching `corky unanswered`.
+pub fn collect_rows(scope_label: &str, dir: &Path, from_name: &str) -> Result<Vec<IndexRow>> {
+    let from_lower = from_name.to_lowercase();
+    let mut rows = Vec::new();
+    for (slug, entry) in manifest_threads(dir)? {
+        let Some(thread) = read_thread(&dir.join(format!("{}.md", slug)))? else {
+            continue;
+        };
+        let tags = string_list(&entry, "tags");
+        let last_from = thread
+            .messages
+            .last()
+            .map(|m| m.from.clone())
+            .unwrap_or_default();
+        let unanswered = !last_from.is_empty()
+            && !last_from.to_lowercase().contains(&from_lower)
+            && !tags.iter().any(|t| t == "newsletter");
+        rows.push(IndexRow {
+            scope: scope_label.to_string(),
+            subject: string_field(&entry, "subject"),
+            labels: string_list(&entry, "labels"),
+            accounts: string_list(&entry, "accounts"),
+            contacts: string_list(&entry, "contacts"),
+            tags,
+            first_date: thread
+                .messages
+                .first()
+                .map(|m| m.date.clone())
+                .unwrap_or_default(),
+            last_updated: string_field(&entry, "last_updated"),
+            message_count: thread.messages.len(),
+            participant_count: thread.participants.len(),
+            last_from,
+            unanswered,
+            slug,
+        });
+    }
+    Ok(rows)
+}
+
+/// Quote a CSV field when it contains a delimiter, quote, or newline (RFC 4180).
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Render rows as CSV. List fields are joined with "; ".
+pub fn to_csv(rows: &[IndexRow]) -> String {
+    let mut out = CSV_HEADER.join(",");
+    out.push('\n');
+    for r in rows {
+        let fields = [
+            r.scope.clone(),
+            r.slug.clone(),
+            r.subject.clone(),
+            r.labels.join("; "),
+            r.accounts.join("; "),
+            r.contacts.join("; "),
+            r.tags.join("; "),
+            r.first_date.clone(),
+            r.last_updated.clone(),
+            r.message_count.to_string(),
+            r.participant_count.to_string(),
+            r.last_from.clone(),
+            r.unanswered.to_string(),
+        ];
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        out.push_str(&line.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// corky export-index [SCOPE] --format csv|json [--output FILE] [--from NAME]
+pub fn run(scope: Scope, format: &str, output: Option<&Path>, from_name: &str) -> Result<()> {
+    let mut rows = Vec::new();
+    for (label, dir) in resolve_dirs(&scope)? {
+        rows.extend(collect_rows(&label, &dir, from_name)?);
+    }
+
+    let content = match format {
+        "csv" => to_csv(&rows),
+        "json" => format!("{}\n", serde_json::to_string_pretty(&rows)?),
+        _ => bail!("Unsupported format: {}. Use 'csv' or 'json'.", format),
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(path, content)?;
+            eprintln!("Exported {} thread(s) to {}", rows.len(), path.display());
+        }
+        None => print!("{}", content),
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_csv_field_quoting() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a, b"), "\"a, b\"");
+        assert_eq!(csv_field("say \"hi\""), "\"say \"\"hi\"\"\"");
+    }
+
+    #[test]
+    fn test_collect_rows_and_csv() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let convos = dir.path().join("conversations");
+        std::fs::create_dir_all(&convos).unwrap();
+        std::fs::write(
+            convos.join("hello.md"),
+            "# Hello, world\n\n\
+**Labels**: inbox\n\
+**Accounts**: personal\n\
+**Thread ID**: hello world\n\
+**Last updated**: Tue, 11 Feb 2025 10:00:00 +0000\n\n\
+---\n\n\
+## Me <me@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\nHi\n\n\
+---\n\n\
+## Alice <alice@example.com> \u{2014} Tue, 11 Feb 2025 10:00:00 +0000\n\nHello back\n",
+        )
+        .unwrap();
+
+        let rows = collect_rows("Root", &convos, "Me").unwrap();
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row.slug, "hello");
+        assert_eq!(row.labels, vec!["inbox"]);
+        assert_eq!(row.message_count, 2);
+        assert_eq!(row.first_date, "Mon, 10 Feb 2025 10:00:00 +0000");
+        assert!(row.unanswered);
+
+        let csv = to_csv(&rows);
+        assert!(csv.starts_with("scope,slug,subject,"));
+        assert!(csv.contains("Root,hello,\"Hello, world\",inbox,personal"));
+    }
+}
@@ -1,18 +1,14 @@
 //! Find threads where the last message is not from the owner.
 
 use anyhow::{bail, Result};
-use once_cell::sync::Lazy;
-use regex::Regex;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
 
+use crate::accounts::{load_accounts, load_owner, Account};
+use crate::address::{self, Address};
+use crate::config::contact::{load_contacts, ContactIndex};
 use crate::resolve;
-
-static SENDER_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(?m)^## (.+?) \u{2014}").unwrap());
-static DATE_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\*\*Last updated\*\*:\s*(\S+)").unwrap());
-static LABELS_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"\*\*Labels?\*\*:\s*(.+)").unwrap());
+use crate::sync::store::{open_store, ConversationStore};
 
 /// Scope for unanswered thread search.
 pub enum Scope {
@@ -34,80 +30,94 @@ impl Scope {
     }
 }
 
-fn last_sender(text: &str) -> String {
-    SENDER_RE
-        .captures_iter(text)
-        .last()
-        .map(|cap| cap[1].trim().to_string())
-        .unwrap_or_default()
-}
-
-fn thread_date(text: &str) -> String {
-    DATE_RE
-        .captures(text)
-        .map(|cap| cap[1].to_string())
-        .unwrap_or_default()
+/// Whether the last sender is the owner. An address in `--from` is compared
+/// as an address (`address::same`); a name is matched as a substring.
+pub(crate) fn is_from_owner(sender: &str, from_lower: &str) -> bool {
+    if from_lower.contains('@') {
+        if let Some(addr) = Address::parse(sender) {
+            return address::same(&addr.email, from_lower);
+        }
+    }
+    sender.to_lowercase().contains(from_lower)
 }
 
-fn thread_labels(text: &str) -> String {
-    LABELS_RE
-        .captures(text)
-        .map(|cap| cap[1].trim().to_string())
-        .unwrap_or_default()
+/// Lowercased names and addresses that count as the owner. When `from_name`
+/// is the `[owner]` name or an account's `display_name`, every account's
+/// `display_name` and address is included, so replies sent under another
+/// name still count as answered.
+pub(crate) fn owner_aliases(
+    from_name: &str,
+    owner_name: &str,
+    accounts: &HashMap<String, Account>,
+) -> Vec<String> {
+    let from_lower = from_name.to_lowercase();
+    let mut aliases = vec![from_lower.clone()];
+    let is_owner = from_lower == owner_name.trim().to_lowercase()
+        || accounts
+            .values()
+            .any(|a| a.display_name.trim().to_lowercase() == from_lower);
+    if !is_owner {
+        return aliases;
+    }
+    let mut names: Vec<&String> = accounts.keys().collect();
+    names.sort();
+    for acct in names.into_iter().map(|n| &accounts[n]) {
+        for alias in [acct.display_name.trim(), acct.user.trim()] {
+            let alias = alias.to_lowercase();
+            if !alias.is_empty() && !aliases.contains(&alias) {
+                aliases.push(alias);
+            }
+        }
+    }
+    aliases
 }
 
-/// Scan a conversations directory and return unanswered threads.
+/// Scan a conversation store and return unanswered threads.
 /// Each entry: (date, labels, filename, sender).
-fn scan_dir(
-    dir: &std::path::Path,
-    from_lower: &str,
+fn scan_store(
+    store: &dyn ConversationStore,
+    owners: &[String],
+    include_newsletters: bool,
 ) -> Result<Vec<(String, String, String, String)>> {
     let mut results = Vec::new();
-    if !dir.is_dir() {
-        return Ok(results);
-    }
-
-    let mut md_files = Vec::new();
-    collect_md_files(dir, &mut md_files)?;
-    md_files.sort();
-
-    for thread_file in &md_files {
-        let text = std::fs::read_to_string(thread_file)?;
-        let sender = last_sender(&text);
-        if !sender.is_empty() && !sender.to_lowercase().contains(from_lower) {
-            let labels = {
-                let l = thread_labels(&text);
-                if l.is_empty() {
-                    thread_file
-                        .parent()
-                        .and_then(|p| p.file_name())
-                        .map(|n| n.to_string_lossy().to_string())
-                        .unwrap_or_default()
-                } else {
-                    l
-                }
-            };
-            let date = {
-                let d = thread_date(&text);
-                if d.is_empty() {
-                    "unknown".to_string()
-                } else {
-                    d
-                }
-            };
-            let filename = thread_file
+    for (key, thread) in store.threads()? {
+        if !include_newsletters && thread.tags.iter().any(|t| t == "newsletter") {
+            continue;
+        }
+        let sender = thread
+            .messages
+            .last()
+            .map(|m| m.from.trim().to_string())
+            .unwrap_or_default();
+        if sender.is_empty() || owners.iter().any(|o| is_from_owner(&sender, o)) {
+            continue;
+        }
+        // Unlabeled threads are grouped by the folder they're in.
+        let labels = if thread.labels.is_empty() {
+            let location = store.location();
+            Path::new(&key)
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(Path::new(&location))
                 .file_name()
                 .map(|n| n.to_string_lossy().to_string())
-                .unwrap_or_default();
-            results.push((date, labels, filename, sender));
-        }
+                .unwrap_or_default()
+        } else {
+            thread.labels.join(", ")
+        };
+        let date = thread
+            .last_date
+            .split_whitespace()
+            .next()
+            .unwrap_or("unknown")
+            .to_string();
+        results.push((date, labels, store.display_name(&key), sender));
     }
-
     Ok(results)
 }
 
 /// Build list of (group_label, conversations_dir) pairs based on scope.
-fn resolve_dirs(scope: &Scope) -> Result<Vec<(String, PathBuf)>> {
+pub(crate) fn resolve_dirs(scope: &Scope) -> Result<Vec<(String, PathBuf)>> {
     let data = resolve::data_dir();
     let root_convos = data.join("conversations");
     let mailboxes_base = resolve::mailboxes_base_dir();
@@ -149,39 +159,87 @@ fn resolve_dirs(scope: &Scope) -> Result<Vec<(String, PathBuf)>> {
     Ok(dirs)
 }
 
-/// corky unanswered [SCOPE] [--from NAME]
-pub fn run(scope: Scope, from_name: &str) -> Result<()> {
+/// Group unanswered entries by the contact of their last sender (exact
+/// address first, then `*@domain`). Senders without a contact go under
+/// "(no contact)", listed last.
+fn group_by_contact(
+    entries: Vec<(String, String, String, String)>,
+    index: &ContactIndex,
+) -> Vec<(String, Vec<(String, String, String, String)>)> {
+    let mut groups: BTreeMap<String, Vec<_>> = BTreeMap::new();
+    let mut unknown = Vec::new();
+    for entry in entries {
+        match index.lookup(&entry.3) {
+            Some(name) => groups.entry(name.to_string()).or_default().push(entry),
+            None => unknown.push(entry),
+        }
+    }
+    let mut out: Vec<_> = groups.into_iter().collect();
+    if !unknown.is_empty() {
+        out.push(("(no contact)".to_string(), unknown));
+    }
+    out
+}
+
+fn print_entries(entries: &[(String, String, String, String)]) {
+    for (date, labels, filename, sender) in entries {
+        println!("  [{}] {}", labels, filename);
+        println!("           Last from: {} ({})", sender, date);
+        println!();
+    }
+}
+
+/// corky unanswered [SCOPE] [--from NAME] [--include-newsletters] [--by-contact]
+pub fn run(
+    scope: Scope,
+    from_name: &str,
+    include_newsletters: bool,
+    by_contact: bool,
+) -> Result<()> {
     let dirs = resolve_dirs(&scope)?;
 
     if dirs.is_empty() {
-        eprintln!("No conversations directories found.");
-        std::process::exit(1);
+        bail!("No conversations directories found.");
     }
 
-    let from_lower = from_name.to_lowercase();
+    let owner_name = load_owner(None).map(|o| o.name).unwrap_or_default();
+    let accounts = load_accounts(None).unwrap_or_default();
+    let owners = owner_aliases(from_name, &owner_name, &accounts);
     let multi = dirs.len() > 1;
 
     let mut total = 0usize;
 
-    for (label, dir) in &dirs {
-        let mut unanswered = scan_dir(dir, &from_lower)?;
-        if unanswered.is_empty() {
-            continue;
+    if by_contact {
+        let mut unanswered = Vec::new();
+        for (_, dir) in &dirs {
+            let store = open_store(dir, true);
+            unanswered.extend(scan_store(store.as_ref(), &owners, include_newsletters)?);
         }
-        // Sort by date descending (newest first)
         unanswered.sort_by(|a, b| b.0.cmp(&a.0));
-        total += unanswered.len();
-
-        if multi {
-            println!("{} ({} unanswered):\n", label, unanswered.len());
-        } else {
-            println!("Unanswered threads ({}):\n", unanswered.len());
+        total = unanswered.len();
+        let index = ContactIndex::new(&load_contacts(None).unwrap_or_default());
+        for (contact, entries) in group_by_contact(unanswered, &index) {
+            println!("{} ({} unanswered):\n", contact, entries.len());
+            print_entries(&entries);
         }
+    } else {
+        for (label, dir) in &dirs {
+            let store = open_store(dir, true);
+            let mut unanswered = scan_store(store.as_ref(), &owners, include_newsletters)?;
+            if unanswered.is_empty() {
+                continue;
+            }
+            // Sort by date descending (newest first)
+            unanswered.sort_by(|a, b| b.0.cmp(&a.0));
+            total += unanswered.len();
+
+            if multi {
+                println!("{} ({} unanswered):\n", label, unanswered.len());
+            } else {
+                println!("Unanswered threads ({}):\n", unanswered.len());
+            }
 
-        for (date, labels, filename, sender) in &unanswered {
-            println!("  [{}] {}", labels, filename);
-            println!("           Last from: {} ({})", sender, date);
-            println!();
+            print_entries(&unanswered);
         }
     }
 
@@ -192,7 +250,7 @@ pub fn run(scope: Scope, from_name: &str) -> Result<()> {
     Ok(())
 }
 
-fn collect_md_files(dir: &std::path::Path, out: &mut Vec<PathBuf>) -> Result<()> {
+pub(crate) fn collect_md_files(dir: &std::path::Path, out: &mut Vec<PathBuf>) -> Result<()> {
     if !dir.is_dir() {
         return Ok(());
     }
@@ -207,3 +265,56 @@ fn collect_md_files(dir: &std::path::Path, out: &mut Vec<PathBuf>) -> Result<()>
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner_aliases_include_account_identities() {
+        let mut accounts = HashMap::new();
+        accounts.insert(
+            "work".to_string(),
+            Account {
+                user: "brian@work.com".to_string(),
+                display_name: "Brian T".to_string(),
+                ..Default::default()
+            },
+        );
+        let owners = owner_aliases("Brian Takita", "Brian Takita", &accounts);
+        assert_eq!(owners, vec!["brian takita", "brian t", "brian@work.com"]);
+        assert!(owners.iter().any(|o| is_from_owner("Brian T <brian@work.com>", o)));
+        assert!(owners.iter().any(|o| is_from_owner("bt <Brian@Work.com>", o)));
+
+        // Someone else's name doesn't pick up the owner's accounts
+        assert_eq!(owner_aliases("Alex", "Brian Takita", &accounts), vec!["alex"]);
+    }
+
+    #[test]
+    fn test_group_by_contact() {
+        let mut contacts = BTreeMap::new();
+        contacts.insert(
+            "clientcorp".to_string(),
+            crate::config::contact::Contact {
+                emails: vec!["*@clientcorp.com".to_string()],
+                ..Default::default()
+            },
+        );
+        let index = ContactIndex::new(&contacts);
+        let entry = |file: &str, sender: &str| {
+            let f = |s: &str| s.to_string();
+            (f("2025-02-10"), f("inbox"), f(file), f(sender))
+        };
+        let groups = group_by_contact(
+            vec![
+                entry("a.md", "Jo <jo@clientcorp.com>"),
+                entry("b.md", "sam@example.com"),
+                entry("c.md", "pat@ClientCorp.com"),
+            ],
+            &index,
+        );
+        let names: Vec<(&str, usize)> =
+            groups.iter().map(|(n, e)| (n.as_str(), e.len())).collect();
+        assert_eq!(names, vec![("clientcorp", 2), ("(no contact)", 1)]);
+    }
+}
@@ -0,0 +1,379 @@
+//! Git pre-push hook for shared mailbox repos.
+//!
+//! `mailbox install-hooks` writes a `pre-push` hook that calls back into
+//! `corky mailbox pre-push`, which validates drafts changed by the push and
+//! runs the secret scan over the pushed diff. Collaborators can't push
+//! malformed drafts or credentials without `git push --no-verify`.
+
+use anyhow::{bail, Result};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use super::secrets::{self, Finding};
+use super::validate_draft::validate_draft_text;
+use crate::config::corky_config;
+use crate::error::CorkyError;
+use crate::output;
+use crate::resolve;
+use crate::util;
+
+/// Marks hooks written by corky, so reinstalling can overwrite them.
+const HOOK_MARKER: &str = "# Installed by `corky mailbox install-hooks`";
+
+/// Git's empty tree, the base for a push with no remote history.
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+const ZERO_SHA: &str = "0000000000000000000000000000000000000000";
+
+/// Set to `1` by `mailbox sync --allow-secrets` so the hook skips its
+/// secret scan for that push.
+pub const ALLOW_SECRETS_ENV: &str = "CORKY_ALLOW_SECRETS";
+
+/// `git push` in `repo`. With `allow_secrets`, corky's pre-push hook still
+/// validates drafts but skips the secret scan.
+pub fn push(repo: &Path, allow_secrets: bool) -> Result<(String, String, i32)> {
+    let sp = repo.to_string_lossy().to_string();
+    let env: &[(&str, &str)] = if allow_secrets {
+        &[(ALLOW_SECRETS_ENV, "1")]
+    } else {
+        &[]
+    };
+    util::run_git_env(&["-C", &sp, "push"], env)
+}
+
+fn hook_script() -> String {
+    format!(
+        "#!/bin/sh\n\
+         {}; delete this file to disable.\n\
+         # Bypass once with: git push --no-verify\n\
+         if ! command -v corky >/dev/null 2>&1; then\n\
+         \x20   echo \"corky not found; skipping mailbox pre-push checks\" >&2\n\
+         \x20   exit 0\n\
+         fi\n\
+         exec corky mailbox pre-push \"$@\"\n",
+        HOOK_MARKER
+    )
+}
+
+/// The repo's hooks directory (honors `core.hooksPath` and submodule git dirs).
+fn hooks_dir(repo: &Path) -> Result<PathBuf> {
+    let sp = repo.to_string_lossy().to_string();
+    let (stdout, stderr, code) = util::run_git(&["-C", &sp, "rev-parse", "--git-path", "hooks"])?;
+    if code != 0 {
+        bail!("{} is not a git repo: {}", repo.display(), stderr.trim());
+    }
+    Ok(repo.join(stdout.trim()))
+}
+
+/// Write the pre-push hook into `repo`. A hook not written by corky is left
+/// alone unless `force`. Returns the hook path.
+pub fn install_hook(repo: &Path, force: bool) -> Result<PathBuf> {
+    let dir = hooks_dir(repo)?;
+    let path = dir.join("pre-push");
+    if path.exists() && !force {
+        let existing = std::fs::read_to_string(&path).unwrap_or_default();
+        if !existing.contains(HOOK_MARKER) {
+            bail!(
+                "{} already exists and was not written by corky; pass --force to replace it",
+                path.display()
+            );
+        }
+    }
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(&path, hook_script())?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+    }
+    Ok(path)
+}
+
+/// corky mailbox install-hooks [NAME] [--force]
+pub fn run_install(name: Option<&str>, force: bool) -> Result<()> {
+    let mailbox_names: Vec<String> = corky_config::try_load_config(None)
+        .map(|c| c.mailboxes.keys().cloned().collect())
+        .unwrap_or_default();
+    if mailbox_names.is_empty() {
+        println!("No mailboxes configured in .corky.toml");
+        return Ok(());
+    }
+    let names: Vec<String> = match name {
+        Some(n) if !mailbox_names.contains(&n.to_string()) => bail!("Unknown mailbox: {}", n),
+        Some(n) => vec![n.to_string()],
+        None => mailbox_names,
+    };
+
+    let mut failed = false;
+    for n in &names {
+        let mb_path = resolve::mailbox_dir(n);
+        if !mb_path.join(".git").exists() {
+            println!(
+                "  {}: {}",
+                n,
+                output::warning("plain directory (not shared), skipped")
+            );
+            continue;
+        }
+        match install_hook(&mb_path, force) {
+            Ok(path) => println!("  {}: {}", n, path.display()),
+            Err(e) => {
+                failed = true;
+                println!("  {}: {}", n, output::error(&format!("{:#}", e)));
+            }
+        }
+    }
+    if failed {
+        bail!("Some hooks were not installed");
+    }
+    Ok(())
+}
+
+/// One line of pre-push stdin: `<local ref> <local sha> <remote ref> <remote sha>`.
+#[derive(Debug, PartialEq)]
+struct PushRef {
+    local_sha: String,
+    remote_sha: String,
+}
+
+fn parse_push_refs(input: &str) -> Vec<PushRef> {
+    input
+        .lines()
+        .filter_map(|line| {
+            let parts: Vec<&str> = line.split_whitespace().collect();
+            match parts.as_slice() {
+                [_, local, _, remote] => Some(PushRef {
+                    local_sha: local.to_string(),
+                    remote_sha: remote.to_string(),
+                }),
+                _ => None,
+            }
+        })
+        // Deletions push no content.
+        .filter(|r| r.local_sha != ZERO_SHA)
+        .collect()
+}
+
+/// Commit the pushed range starts from: the remote's old tip, or for a new
+/// branch the parent of the first commit no remote has yet.
+fn push_base(repo: &str, push: &PushRef) -> Result<Option<String>> {
+    if push.remote_sha != ZERO_SHA {
+        return Ok(Some(push.remote_sha.clone()));
+    }
+    let (stdout, stderr, code) = util::run_git(&[
+        "-C",
+        repo,
+        "rev-list",
+        "--reverse",
+        &push.local_sha,
+        "--not",
+        "--remotes",
+    ])?;
+    if code != 0 {
+        bail!("git rev-list failed: {}", stderr.trim());
+    }
+    let Some(first) = stdout.lines().next() else {
+        return Ok(None);
+    };
+    let parent = format!("{}^", first);
+    let has_parent = util::run_git(&["-C", repo, "rev-parse", "--verify", "--quiet", &parent])
+        .map(|(_, _, code)| code == 0)
+        .unwrap_or(false);
+    Ok(Some(if has_parent {
+        parent
+    } else {
+        EMPTY_TREE.to_string()
+    }))
+}
+
+fn git_out(args: &[&str]) -> Result<String> {
+    let (stdout, stderr, code) = util::run_git(args)?;
+    if code != 0 {
+        bail!("git {} failed: {}", args.join(" "), stderr.trim());
+    }
+    Ok(stdout)
+}
+
+/// Draft files added or modified between `base` and `tip`, minus templates.
+fn changed_drafts(repo: &str, base: &str, tip: &str) -> Result<Vec<String>> {
+    let stdout = git_out(&[
+        "-C",
+        repo,
+        "diff",
+        "--name-only",
+        "--diff-filter=AM",
+        base,
+        tip,
+        "--",
+        "drafts",
+    ])?;
+    Ok(stdout
+        .lines()
+        .filter(|l| l.ends_with(".md") && !l.starts_with("drafts/templates/"))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Validation errors (not warnings) for a draft as of commit `tip`.
+fn draft_errors(repo: &str, tip: &str, file: &str) -> Result<Vec<String>> {
+    let text = git_out(&["-C", repo, "show", &format!("{}:{}", tip, file)])?;
+    Ok(validate_draft_text(&text)
+        .into_iter()
+        .filter(|i| !i.starts_with("Warning:"))
+        .collect())
+}
+
+/// corky mailbox pre-push — run by the hook in the repo being pushed,
+/// with git's ref lines on stdin.
+pub fn run_pre_push() -> Result<()> {
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input)?;
+    let toplevel = git_out(&["rev-parse", "--show-toplevel"])?;
+    let sp = toplevel.trim().to_string();
+    let allow_secrets = std::env::var(ALLOW_SECRETS_ENV).is_ok_and(|v| v == "1");
+
+    let mut bad_drafts: Vec<(String, Vec<String>)> = Vec::new();
+    let mut findings: Vec<Finding> = Vec::new();
+    for push in parse_push_refs(&input) {
+        let Some(base) = push_base(&sp, &push)? else {
+            continue;
+        };
+        for file in changed_drafts(&sp, &base, &push.local_sha)? {
+            let errors = draft_errors(&sp, &push.local_sha, &file)?;
+            if !errors.is_empty() && !bad_drafts.iter().any(|(f, _)| *f == file) {
+                bad_drafts.push((file, errors));
+            }
+        }
+        if allow_secrets {
+            continue;
+        }
+        let diff = git_out(&[
+            "-C",
+            &sp,
+            "diff",
+            "-U0",
+            "--no-color",
+            &base,
+            &push.local_sha,
+        ])?;
+        findings.extend(secrets::scan_diff(&diff));
+    }
+
+    if bad_drafts.is_empty() && findings.is_empty() {
+        return Ok(());
+    }
+    for (file, errors) in &bad_drafts {
+        eprintln!("{}:", file);
+        for e in errors {
+            eprintln!("  {} {}", output::error("ERROR:"), e);
+        }
+    }
+    if !findings.is_empty() {
+        eprintln!("Possible credentials in pushed changes:");
+        eprintln!("{}", secrets::report(&findings));
+    }
+    Err(CorkyError::Validation(
+        "Push blocked by corky pre-push checks (bypass with git push --no-verify)".to_string(),
+    )
+    .into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_push_refs_skips_deletes() {
+        let input = format!(
+            "refs/heads/main abc123 refs/heads/main {zero}\n\
+             (delete) {zero} refs/heads/old def456\n\
+             garbage\n",
+            zero = ZERO_SHA
+        );
+        assert_eq!(
+            parse_push_refs(&input),
+            vec![PushRef {
+                local_sha: "abc123".to_string(),
+                remote_sha: ZERO_SHA.to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_install_hook_respects_foreign_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let sp = dir.path().to_string_lossy().to_string();
+        util::run_git(&["init", "-q", &sp]).unwrap();
+
+        let path = install_hook(dir.path(), false).unwrap();
+        assert_eq!(path, dir.path().join(".git/hooks/pre-push"));
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains(HOOK_MARKER)
+        );
+        // Our own hook is replaced without --force.
+        install_hook(dir.path(), false).unwrap();
+
+        std::fs::write(&path, "#!/bin/sh\nexit 0\n").unwrap();
+        assert!(install_hook(dir.path(), false).is_err());
+        install_hook(dir.path(), true).unwrap();
+        assert!(
+            std::fs::read_to_string(&path)
+                .unwrap()
+                .contains(HOOK_MARKER)
+        );
+    }
+
+    #[test]
+    fn test_changed_drafts_for_new_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let sp = dir.path().to_string_lossy().to_string();
+        let git = |args: &[&str]| {
+            let mut full = vec![
+                "-C",
+                sp.as_str(),
+                "-c",
+                "user.name=T",
+                "-c",
+                "user.email=t@x",
+            ];
+            full.extend_from_slice(args);
+            let (_, stderr, code) = util::run_git(&full).unwrap();
+            assert_eq!(code, 0, "{}", stderr);
+        };
+        git(&["init", "-q"]);
+        std::fs::create_dir_all(dir.path().join("drafts/templates")).unwrap();
+        std::fs::write(dir.path().join("drafts/bad.md"), "# No metadata\n").unwrap();
+        std::fs::write(dir.path().join("drafts/templates/intro.md"), "Hi\n").unwrap();
+        std::fs::write(dir.path().join("notes.md"), "password = hunter2hunter2\n").unwrap();
+        git(&["add", "-A"]);
+        git(&["commit", "-qm", "first"]);
+
+        let tip = git_out(&["-C", &sp, "rev-parse", "HEAD"]).unwrap();
+        let push = PushRef {
+            local_sha: tip.trim().to_string(),
+            remote_sha: ZERO_SHA.to_string(),
+        };
+        let base = push_base(&sp, &push).unwrap().unwrap();
+        assert_eq!(base, EMPTY_TREE);
+        let changed = changed_drafts(&sp, &base, &push.local_sha).unwrap();
+        assert_eq!(changed, vec!["drafts/bad.md"]);
+        assert!(
+            !draft_errors(&sp, &push.local_sha, &changed[0])
+                .unwrap()
+                .is_empty()
+        );
+        let diff = git_out(&[
+            "-C",
+            &sp,
+            "diff",
+            "-U0",
+            "--no-color",
+            &base,
+            &push.local_sha,
+        ])
+        .unwrap();
+        assert_eq!(secrets::scan_diff(&diff).len(), 1);
+    }
+}
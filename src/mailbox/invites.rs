@@ -0,0 +1,116 @@
+//! Show whether collaborators have accepted their shared-mailbox invites.
+
+use anyhow::Result;
+use std::path::Path;
+
+use crate::config::corky_config;
+use crate::github::{self, RepoAccess};
+use crate::output::{self, Style};
+use crate::resolve;
+use crate::util;
+
+/// GitHub repo behind a mailbox: its `origin` remote, else `{github_user}/to-{name}`.
+fn mailbox_repo(name: &str, mb_path: &Path) -> Option<String> {
+    let sp = mb_path.to_string_lossy().to_string();
+    let from_remote = util::run_git(&["-C", &sp, "remote", "get-url", "origin"])
+        .ok()
+        .filter(|(_, _, code)| *code == 0)
+        .and_then(|(stdout, _, _)| github::repo_from_remote(&stdout));
+    from_remote.or_else(|| {
+        crate::accounts::load_owner(None)
+            .ok()
+            .filter(|o| !o.github_user.is_empty())
+            .map(|o| format!("{}/to-{}", o.github_user, name.to_lowercase()))
+    })
+}
+
+/// One colored status cell for a repo's access state.
+fn describe(access: &RepoAccess) -> String {
+    let mut parts = Vec::new();
+    if !access.accepted.is_empty() {
+        parts.push(output::ok(&format!(
+            "accepted: {}",
+            access.accepted.join(", ")
+        )));
+    }
+    if !access.pending.is_empty() {
+        parts.push(output::warning(&format!(
+            "pending: {}",
+            access.pending.join(", ")
+        )));
+    }
+    if parts.is_empty() {
+        return output::paint("no collaborators or invites (PAT access?)", Style::Dim);
+    }
+    parts.join("; ")
+}
+
+/// corky mailbox invites [NAME]
+pub fn run(name: Option<&str>) -> Result<()> {
+    let config = corky_config::try_load_config(None);
+    let mailbox_names: Vec<String> = config
+        .as_ref()
+        .map(|c| c.mailboxes.keys().cloned().collect())
+        .unwrap_or_default();
+
+    if mailbox_names.is_empty() {
+        println!("No mailboxes configured in .corky.toml");
+        return Ok(());
+    }
+
+    let names: Vec<String> = if let Some(n) = name {
+        if !mailbox_names.contains(&n.to_string()) {
+            anyhow::bail!("Unknown mailbox: {}", n);
+        }
+        vec![n.to_string()]
+    } else {
+        mailbox_names
+    };
+
+    let mut rows = Vec::new();
+    for n in &names {
+        let mb_path = resolve::mailbox_dir(n);
+        if !mb_path.join(".git").exists() {
+            rows.push(vec![
+                n.clone(),
+                String::new(),
+                output::paint("plain directory (not shared)", Style::Dim),
+            ]);
+            continue;
+        }
+        let Some(repo) = mailbox_repo(n, &mb_path) else {
+            rows.push(vec![
+                n.clone(),
+                String::new(),
+                output::error("GitHub repo unknown"),
+            ]);
+            continue;
+        };
+        let state = match github::repo_access(&repo) {
+            Ok(access) => describe(&access),
+            Err(e) => output::error(&format!("{:#}", e)),
+        };
+        rows.push(vec![n.clone(), repo, state]);
+    }
+    output::print_table(&rows);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe_plain_text() {
+        output::disable_color();
+        let access = RepoAccess {
+            accepted: vec!["bob".into()],
+            pending: vec!["carol".into()],
+        };
+        assert_eq!(describe(&access), "accepted: bob; pending: carol");
+        assert_eq!(
+            describe(&RepoAccess::default()),
+            "no collaborators or invites (PAT access?)"
+        );
+    }
+}
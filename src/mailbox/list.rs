@@ -3,6 +3,7 @@
 use anyhow::Result;
 
 use crate::app_config;
+use crate::output::{self, Style};
 
 pub fn run() -> Result<()> {
     let mailboxes = app_config::list_mailboxes()?;
@@ -14,10 +15,17 @@ pub fn run() -> Result<()> {
     }
 
     println!("corky mailboxes\n");
-    let name_w = mailboxes.iter().map(|(n, _, _)| n.len()).max().unwrap_or(0);
-    for (name, path, is_default) in &mailboxes {
-        let marker = if *is_default { " (default)" } else { "" };
-        println!("  {:<width$}  {}{}", name, path, marker, width = name_w);
-    }
+    let rows: Vec<Vec<String>> = mailboxes
+        .iter()
+        .map(|(name, path, is_default)| {
+            let marker = if *is_default {
+                output::ok("(default)")
+            } else {
+                String::new()
+            };
+            vec![output::paint(name, Style::Bold), path.clone(), marker]
+        })
+        .collect();
+    output::print_table(&rows);
     Ok(())
 }
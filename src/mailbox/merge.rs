@@ -0,0 +1,214 @@
+//! Merging conversation files when several owners sync into one shared
+//! mailbox repo.
+//!
+//! Each owner's `corky sync` routes mail into the same `conversations/`
+//! files. `mailbox sync` registers a git merge driver for them, so a pull
+//! that meets the other owner's copy of a thread unions the messages
+//! instead of stopping on a conflict.
+
+use anyhow::{bail, Context, Result};
+use std::path::Path;
+
+use crate::config::corky_config;
+use crate::sync::imap_sync::merge_threads;
+use crate::sync::markdown::{parse_thread_markdown, part_links, part_to_markdown};
+use crate::util;
+
+/// `.gitattributes` line routing conversation files to the driver.
+const ATTRIBUTES_LINE: &str = "conversations/*.md merge=corky-thread";
+
+/// Register the driver in the repo's local git config and, if `attributes`,
+/// add it to `.gitattributes` (committed, so every clone uses it). Returns
+/// whether `.gitattributes` changed.
+pub fn ensure_merge_driver(mb_path: &Path, attributes: bool) -> Result<bool> {
+    let sp = mb_path.to_string_lossy().to_string();
+    for (key, value) in [
+        ("merge.corky-thread.name", "corky conversation merge"),
+        (
+            "merge.corky-thread.driver",
+            "corky mailbox merge-thread %O %A %B",
+        ),
+    ] {
+        let (_, stderr, code) = util::run_git(&["-C", &sp, "config", key, value])?;
+        if code != 0 {
+            bail!("git config {} in {} failed: {}", key, sp, stderr.trim());
+        }
+    }
+    if !attributes {
+        return Ok(false);
+    }
+
+    let path = mb_path.join(".gitattributes");
+    let existing = std::fs::read_to_string(&path).unwrap_or_default();
+    if existing.lines().any(|l| l.trim() == ATTRIBUTES_LINE) {
+        return Ok(false);
+    }
+    let mut content = existing;
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(ATTRIBUTES_LINE);
+    content.push('\n');
+    std::fs::write(&path, content)?;
+    Ok(true)
+}
+
+/// Merge `theirs` into `ours` in place. Fails (leaving `ours` untouched) if
+/// either side doesn't parse or they are different threads. Git merges each
+/// file of a split conversation on its own, so the result stays one file
+/// and keeps the part links of `ours`.
+pub fn merge_thread_files(ours: &Path, theirs: &Path) -> Result<()> {
+    let read = |path: &Path| {
+        std::fs::read_to_string(path).with_context(|| format!("Reading {}", path.display()))
+    };
+    let parse = |path: &Path, text: &str| {
+        parse_thread_markdown(text)
+            .ok_or_else(|| anyhow::anyhow!("Not a conversation file: {}", path.display()))
+    };
+    let our_text = read(ours)?;
+    let our_thread = parse(ours, &our_text)?;
+    let their_thread = parse(theirs, &read(theirs)?)?;
+    if our_thread.id != their_thread.id {
+        bail!(
+            "Thread IDs differ ('{}' vs '{}'); leaving the conflict for manual resolution",
+            our_thread.id,
+            their_thread.id
+        );
+    }
+    let sync_config = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    let merged = merge_threads(&our_thread, &their_thread);
+    let links = part_links(&our_text);
+    std::fs::write(ours, part_to_markdown(&merged, &sync_config, &links))?;
+    Ok(())
+}
+
+/// corky mailbox merge-thread BASE OURS THEIRS — git merge driver. The
+/// result goes to OURS; an error makes git report a normal conflict.
+pub fn run_merge_thread(_base: &Path, ours: &Path, theirs: &Path) -> Result<()> {
+    merge_thread_files(ours, theirs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::corky_config::SyncConfig;
+    use crate::sync::markdown::{read_thread, thread_to_markdown, write_thread};
+    use crate::sync::types::fixtures::{message, thread};
+    use crate::sync::types::{Message, Thread};
+
+    fn msg(id: &str, date: &str, account: &str) -> Message {
+        Message {
+            to: "team@example.com".to_string(),
+            message_id: id.to_string(),
+            received_by: vec![account.to_string()],
+            ..message(
+                "Alice <alice@example.com>",
+                date,
+                &format!("Body of {}", id),
+            )
+        }
+    }
+
+    fn plans(messages: Vec<Message>, account: &str) -> Thread {
+        Thread {
+            labels: vec!["shared".to_string()],
+            accounts: vec![account.to_string()],
+            ..thread("plans", "Plans", messages)
+        }
+    }
+
+    #[test]
+    fn merge_thread_files_unions_both_owners() {
+        let dir = tempfile::tempdir().unwrap();
+        let ours = dir.path().join("ours.md");
+        let theirs = dir.path().join("theirs.md");
+        let first = "Mon, 10 Feb 2025 10:00:00 +0000";
+        let second = "Tue, 11 Feb 2025 10:00:00 +0000";
+        std::fs::write(
+            &ours,
+            thread_to_markdown(&plans(vec![msg("<1@x>", first, "brian")], "brian")),
+        )
+        .unwrap();
+        std::fs::write(
+            &theirs,
+            thread_to_markdown(&plans(
+                vec![msg("<1@x>", first, "dana"), msg("<2@x>", second, "dana")],
+                "dana",
+            )),
+        )
+        .unwrap();
+
+        merge_thread_files(&ours, &theirs).unwrap();
+        let merged = parse_thread_markdown(&std::fs::read_to_string(&ours).unwrap()).unwrap();
+        assert_eq!(merged.messages.len(), 2);
+        assert_eq!(merged.messages[0].received_by, vec!["brian", "dana"]);
+        assert_eq!(merged.accounts, vec!["brian", "dana"]);
+        assert_eq!(merged.last_date, second);
+    }
+
+    #[test]
+    fn merge_thread_files_refuses_other_threads() {
+        let dir = tempfile::tempdir().unwrap();
+        let ours = dir.path().join("ours.md");
+        let theirs = dir.path().join("theirs.md");
+        let date = "Mon, 10 Feb 2025 10:00:00 +0000";
+        let mut other = plans(vec![msg("<9@x>", date, "dana")], "dana");
+        other.id = "other".to_string();
+        std::fs::write(
+            &ours,
+            thread_to_markdown(&plans(vec![msg("<1@x>", date, "brian")], "brian")),
+        )
+        .unwrap();
+        std::fs::write(&theirs, thread_to_markdown(&other)).unwrap();
+        let before = std::fs::read_to_string(&ours).unwrap();
+        assert!(merge_thread_files(&ours, &theirs).is_err());
+        assert_eq!(std::fs::read_to_string(&ours).unwrap(), before);
+    }
+
+    #[test]
+    fn merge_thread_files_keeps_part_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let sync = SyncConfig {
+            split_messages: 1,
+            ..Default::default()
+        };
+        let first = "Mon, 10 Feb 2025 10:00:00 +0000";
+        let second = "Tue, 11 Feb 2025 10:00:00 +0000";
+        let mut mains = Vec::new();
+        for owner in ["brian", "dana"] {
+            std::fs::create_dir(dir.path().join(owner)).unwrap();
+            let main = dir.path().join(owner).join("plans.md");
+            let messages = vec![msg("<1@x>", first, owner), msg("<2@x>", second, owner)];
+            write_thread(&main, &plans(messages, owner), &sync).unwrap();
+            mains.push(main);
+        }
+        let part = |main: &Path| main.with_file_name("plans.part2.md");
+
+        merge_thread_files(&part(&mains[0]), &part(&mains[1])).unwrap();
+        let text = std::fs::read_to_string(part(&mains[0])).unwrap();
+        assert!(text.contains("**Part**: 2 of 2\n**Previous part**: plans.md\n"));
+        let merged = read_thread(&mains[0]).unwrap().unwrap();
+        assert_eq!(merged.messages.len(), 2);
+        assert_eq!(merged.messages[1].received_by, vec!["brian", "dana"]);
+    }
+
+    #[test]
+    fn ensure_merge_driver_is_idempotent() {
+        let dir = tempfile::tempdir().unwrap();
+        let sp = dir.path().to_string_lossy().to_string();
+        util::run_git(&["init", "-q", &sp]).unwrap();
+        std::fs::write(dir.path().join(".gitattributes"), "*.png binary").unwrap();
+
+        assert!(ensure_merge_driver(dir.path(), true).unwrap());
+        assert!(!ensure_merge_driver(dir.path(), true).unwrap());
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".gitattributes")).unwrap(),
+            "*.png binary\nconversations/*.md merge=corky-thread\n"
+        );
+        let (driver, _, _) =
+            util::run_git(&["-C", &sp, "config", "merge.corky-thread.driver"]).unwrap();
+        assert_eq!(driver.trim(), "corky mailbox merge-thread %O %A %B");
+    }
+}
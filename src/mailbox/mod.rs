@@ -1,9 +1,17 @@
 pub mod add;
+pub mod export_index;
 pub mod find_unanswered;
+pub mod hooks;
+pub mod invites;
 pub mod list;
+pub mod merge;
+pub mod newsletters;
 pub mod remove;
 pub mod rename;
 pub mod reset;
+pub mod review;
+pub mod secrets;
 pub mod sync;
 pub mod templates;
 pub mod validate_draft;
+pub mod verify;
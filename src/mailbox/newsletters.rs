@@ -0,0 +1,284 @@
+//! List threads tagged `newsletter` with their List-Unsubscribe targets.
+
+use anyhow::Result;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+use super::find_unanswered::{collect_md_files, resolve_dirs, Scope};
+use crate::sync::manifest::latest_list_unsubscribe;
+use crate::sync::markdown::{is_part_file, read_thread};
+#[cfg(feature = "smtp")]
+use {
+    crate::accounts::{account_secret, get_default_account, load_accounts, Account},
+    crate::config::corky_config,
+    crate::resolve,
+    crate::sync::markdown::write_thread,
+    anyhow::bail,
+    chrono::Local,
+};
+
+static ANGLE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([^>]+)>").unwrap());
+
+/// A newsletter thread found on disk.
+#[derive(Debug, Clone)]
+pub struct Newsletter {
+    pub path: PathBuf,
+    pub subject: String,
+    pub sender: String,
+    pub list_unsubscribe: String,
+}
+
+/// Split a List-Unsubscribe header into its targets (`mailto:` / `https:` URIs).
+///
+/// RFC 2369 wraps each target in angle brackets; a bare value is returned as-is.
+pub fn unsubscribe_targets(header: &str) -> Vec<String> {
+    let targets: Vec<String> = ANGLE_RE
+        .captures_iter(header)
+        .map(|cap| cap[1].trim().to_string())
+        .filter(|t| !t.is_empty())
+        .collect();
+    if targets.is_empty() && !header.trim().is_empty() {
+        vec![header.trim().to_string()]
+    } else {
+        targets
+    }
+}
+
+/// Scan a conversations directory for threads tagged `newsletter`.
+pub fn scan_dir(dir: &Path) -> Result<Vec<Newsletter>> {
+    let mut md_files = Vec::new();
+    collect_md_files(dir, &mut md_files)?;
+    md_files.sort();
+
+    let mut results = Vec::new();
+    for path in md_files {
+        if is_part_file(&path) {
+            continue;
+        }
+        let thread = match read_thread(&path)? {
+            Some(t) => t,
+            None => continue,
+        };
+        if !thread.tags.iter().any(|t| t == "newsletter") {
+            continue;
+        }
+        let sender = thread
+            .messages
+            .iter()
+            .rev()
+            .find(|m| !m.list_unsubscribe.is_empty())
+            .or(thread.messages.last())
+            .map(|m| m.from.clone())
+            .unwrap_or_default();
+        results.push(Newsletter {
+            list_unsubscribe: latest_list_unsubscribe(&thread).unwrap_or_default(),
+            path,
+            subject: thread.subject,
+            sender,
+        });
+    }
+    Ok(results)
+}
+
+/// corky newsletters [SCOPE]
+pub fn run(scope: Scope) -> Result<()> {
+    let dirs = resolve_dirs(&scope)?;
+    let multi = dirs.len() > 1;
+    let mut total = 0usize;
+
+    for (label, dir) in &dirs {
+        let found = scan_dir(dir)?;
+        if found.is_empty() {
+            continue;
+        }
+        total += found.len();
+
+        if multi {
+            println!("{} ({} newsletters):\n", label, found.len());
+        } else {
+            println!("Newsletters ({}):\n", found.len());
+        }
+
+        for n in &found {
+            let filename = n
+                .path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default();
+            println!("  {} \u{2014} {}", filename, n.subject);
+            println!("           From: {}", n.sender);
+            for target in unsubscribe_targets(&n.list_unsubscribe) {
+                println!("           Unsubscribe: {}", target);
+            }
+            println!();
+        }
+    }
+
+    if total == 0 {
+        println!("No newsletter threads found.");
+    }
+
+    Ok(())
+}
+
+/// Split a `mailto:` URI into (address, subject, body).
+///
+/// Subject defaults to "unsubscribe" when the URI doesn't set one.
+pub fn parse_mailto(uri: &str) -> (String, String, String) {
+    let rest = uri
+        .get(..7)
+        .filter(|p| p.eq_ignore_ascii_case("mailto:"))
+        .map(|_| &uri[7..])
+        .unwrap_or(uri);
+    let (addr, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let mut subject = "unsubscribe".to_string();
+    let mut body = String::new();
+    for pair in query.split('&') {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        match key.to_lowercase().as_str() {
+            "subject" => subject = percent_decode(value),
+            "body" => body = percent_decode(value),
+            _ => {}
+        }
+    }
+    (percent_decode(addr), subject, body)
+}
+
+/// Decode `%XX` escapes (`+` is left as-is, per RFC 6068).
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%'
+            && i + 2 < bytes.len()
+            && bytes[i + 1].is_ascii_hexdigit()
+            && bytes[i + 2].is_ascii_hexdigit()
+        {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).unwrap_or("00");
+            out.push(u8::from_str_radix(hex, 16).unwrap_or(0));
+            i += 3;
+            continue;
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Pick the account to unsubscribe from: one that received the message,
+/// then one of the thread's accounts, then the default account.
+#[cfg(feature = "smtp")]
+fn owning_account(received_by: &[String], thread_accounts: &[String]) -> Result<(String, Account)> {
+    let accounts = load_accounts(None)?;
+    for name in received_by.iter().chain(thread_accounts) {
+        if let Some(acct) = accounts.get(name) {
+            return Ok((name.clone(), acct.clone()));
+        }
+    }
+    get_default_account(&accounts)
+}
+
+/// corky unsubscribe SLUG
+///
+/// Sends the mailto: unsubscribe request from the owning account once
+/// confirmed, or prints the https: link(s), then records the action in the
+/// thread's Notes.
+#[cfg(feature = "smtp")]
+pub fn run_unsubscribe(slug: &str) -> Result<()> {
+    let path = resolve::find_conversation(slug)?;
+    let mut thread = read_thread(&path)?
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse conversation: {}", path.display()))?;
+
+    let (header, received_by) = match thread
+        .messages
+        .iter()
+        .rev()
+        .find(|m| !m.list_unsubscribe.is_empty())
+    {
+        Some(m) => (m.list_unsubscribe.clone(), m.received_by.clone()),
+        None => bail!("No List-Unsubscribe header in '{}'", slug),
+    };
+    let targets = unsubscribe_targets(&header);
+
+    let note = if let Some(mailto) = targets
+        .iter()
+        .find(|t| t.to_lowercase().starts_with("mailto:"))
+    {
+        let (to_addr, subject, body) = parse_mailto(mailto);
+        let (acct_name, acct) = owning_account(&received_by, &thread.accounts)?;
+        let question = format!(
+            "Send unsubscribe request to {} (subject \"{}\") from account '{}'?",
+            to_addr, subject, acct_name
+        );
+        if !crate::output::confirm(&question)? {
+            println!("Not sent.");
+            return Ok(());
+        }
+        let secret = account_secret(&acct_name, &acct)?;
+        let from = crate::draft::parse_mailbox("from", &acct.from_address())?;
+        let to = crate::draft::parse_mailbox("unsubscribe", &to_addr)?;
+        let email = lettre::Message::builder()
+            .from(from)
+            .to(to)
+            .subject(subject)
+            .body(body)?;
+        crate::draft::send_email(&email, &acct.smtp_host, acct.smtp_port, &acct.user, &secret)?;
+        println!("Sent unsubscribe request to {} from account '{}'", to_addr, acct_name);
+        format!("Unsubscribed via {} (sent from account {})", mailto, acct_name)
+    } else if !targets.is_empty() {
+        for target in &targets {
+            println!("Open to unsubscribe: {}", target);
+        }
+        format!("Unsubscribe link: {}", targets.join(", "))
+    } else {
+        bail!("No unsubscribe target in '{}'", slug);
+    };
+
+    thread
+        .notes
+        .push(format!("{}: {}", Local::now().format("%Y-%m-%d"), note));
+    let sync_config = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    write_thread(&path, &thread, &sync_config)?;
+    println!("Noted in {}", path.display());
+    Ok(())
+}
+
+#[cfg(not(feature = "smtp"))]
+pub fn run_unsubscribe(_slug: &str) -> Result<()> {
+    Err(crate::util::missing_feature("smtp"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unsubscribe_targets() {
+        assert_eq!(
+            unsubscribe_targets("<mailto:leave@list.example.com?subject=unsub>, <https://example.com/u/1>"),
+            vec!["mailto:leave@list.example.com?subject=unsub", "https://example.com/u/1"]
+        );
+        assert_eq!(unsubscribe_targets("https://example.com/u"), vec!["https://example.com/u"]);
+        assert!(unsubscribe_targets("").is_empty());
+    }
+
+    #[test]
+    fn test_parse_mailto() {
+        assert_eq!(
+            parse_mailto("mailto:leave%2B42@list.example.com?subject=Remove%20me&body=bye"),
+            (
+                "leave+42@list.example.com".to_string(),
+                "Remove me".to_string(),
+                "bye".to_string()
+            )
+        );
+        let (addr, subject, body) = parse_mailto("MAILTO:x@example.com");
+        assert_eq!(addr, "x@example.com");
+        assert_eq!(subject, "unsubscribe");
+        assert_eq!(body, "");
+    }
+}
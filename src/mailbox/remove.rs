@@ -1,13 +1,16 @@
 //! Remove a mailbox: plain directory or submodule.
 
 use anyhow::Result;
-use std::io::Write;
 use std::path::PathBuf;
 
+use crate::github;
 use crate::resolve;
 use crate::util::run_cmd_checked;
 
 pub fn run(name: &str, delete_repo: bool) -> Result<()> {
+    if delete_repo {
+        crate::util::ensure_unrestricted("Deleting a GitHub repo")?;
+    }
     let mb_path = resolve::mailbox_dir(name);
 
     if mb_path.exists() {
@@ -51,15 +54,9 @@ pub fn run(name: &str, delete_repo: bool) -> Result<()> {
             .unwrap_or_default();
 
         if !repo_full.is_empty() {
-            print!(
-                "Delete GitHub repo {}? This cannot be undone. [y/N] ",
-                repo_full
-            );
-            std::io::stdout().flush()?;
-            let mut input = String::new();
-            std::io::stdin().read_line(&mut input)?;
-            if input.trim().to_lowercase() == "y" {
-                run_cmd_checked(&["gh", "repo", "delete", &repo_full, "--yes"])?;
+            let question = format!("Delete GitHub repo {}? This cannot be undone.", repo_full);
+            if crate::output::confirm(&question)? {
+                github::delete_repo(&repo_full)?;
                 println!("Deleted GitHub repo: {}", repo_full);
             } else {
                 println!("Skipped repo deletion");
@@ -78,6 +75,7 @@ fn remove_from_config(name: &str) -> Result<()> {
         return Ok(());
     }
 
+    let _lock = crate::config::lock::lock(&config_path)?;
     let content = std::fs::read_to_string(&config_path)?;
     let mut doc = content.parse::<toml_edit::DocumentMut>()?;
 
@@ -106,7 +104,7 @@ fn remove_from_config(name: &str) -> Result<()> {
         }
     }
 
-    std::fs::write(&config_path, doc.to_string())?;
+    crate::config::lock::write(&config_path, &doc.to_string())?;
     println!("Removed '{}' from .corky.toml", name);
 
     Ok(())
@@ -2,6 +2,7 @@
 
 use anyhow::Result;
 
+use crate::github;
 use crate::resolve;
 use crate::util::run_cmd_checked;
 
@@ -51,15 +52,7 @@ pub fn run(old_name: &str, new_name: &str, rename_repo: bool) -> Result<()> {
                 "Renaming GitHub repo {} \u{2192} {}",
                 old_repo, new_repo_name
             );
-            run_cmd_checked(&[
-                "gh",
-                "repo",
-                "rename",
-                &new_repo_name,
-                "-R",
-                &old_repo,
-                "--yes",
-            ])?;
+            github::rename_repo(&old_repo, &new_repo_name)?;
         }
     }
 
@@ -80,6 +73,7 @@ fn update_config(old_name: &str, new_name: &str) -> Result<()> {
         return Ok(());
     }
 
+    let _lock = crate::config::lock::lock(&config_path)?;
     let content = std::fs::read_to_string(&config_path)?;
     let mut doc = content.parse::<toml_edit::DocumentMut>()?;
 
@@ -109,7 +103,7 @@ fn update_config(old_name: &str, new_name: &str) -> Result<()> {
         }
     }
 
-    std::fs::write(&config_path, doc.to_string())?;
+    crate::config::lock::write(&config_path, &doc.to_string())?;
     println!(
         "Renamed '{}' \u{2192} '{}' in .corky.toml",
         old_name, new_name
@@ -2,33 +2,75 @@
 
 use anyhow::Result;
 use std::path::Path;
-use std::process::Command;
 
 use crate::accounts::load_owner;
 use crate::config::corky_config;
 use crate::resolve;
-
-use super::templates::{generate_agents_md, generate_readme_md};
-
-fn run_git(args: &[&str]) -> (String, String, i32) {
-    let output = Command::new(args[0])
-        .args(&args[1..])
-        .output()
-        .unwrap_or_else(|_| panic!("Failed to run: {}", args.join(" ")));
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let code = output.status.code().unwrap_or(-1);
-    (stdout, stderr, code)
+use crate::util;
+
+use super::templates::{
+    generate_agents_md, generate_readme_md, stamp, template_status, TemplateOwner,
+    TemplateStatus, GITIGNORE, TEMPLATE_VERSION,
+};
+
+/// Write a generated file unless it has local changes (or `force`).
+/// Returns false when the file was kept.
+fn write_template(
+    mb_path: &Path,
+    file: &str,
+    kind: &str,
+    content: &str,
+    force: bool,
+) -> Result<bool> {
+    let path = mb_path.join(file);
+    let installed = std::fs::read_to_string(&path).ok();
+    let status = template_status(installed.as_deref(), content);
+    let replace = status == TemplateStatus::Modified
+        && force
+        && crate::output::confirm(&format!(
+            "  Replace {} in {}? Its local changes will be lost.",
+            file,
+            mb_path.display()
+        ))?;
+    match status {
+        TemplateStatus::Modified if !replace => {
+            let hint = if force {
+                "pass --yes to replace"
+            } else {
+                "--force to replace"
+            };
+            println!("  Kept {} -- has local changes ({})", file, hint);
+            return Ok(false);
+        }
+        TemplateStatus::Current => {
+            println!("  {} is current (v{})", file, TEMPLATE_VERSION);
+            return Ok(true);
+        }
+        TemplateStatus::Outdated { version } if version == TEMPLATE_VERSION => {
+            println!("  Updated {} ([owner] settings changed)", file);
+        }
+        TemplateStatus::Outdated { version } => {
+            println!("  Updated {} (v{} -> v{})", file, version, TEMPLATE_VERSION);
+        }
+        TemplateStatus::Missing | TemplateStatus::Modified => {
+            println!("  Updated {}", file);
+        }
+    }
+    std::fs::write(&path, stamp(kind, content))?;
+    Ok(true)
 }
 
-/// Regenerate template files for one mailbox.
-fn regenerate(display_name: &str, owner_name: &str, mb_path: &Path) -> Result<()> {
+/// Regenerate template files for one mailbox. Returns false when a
+/// locally modified AGENTS.md or README.md was kept.
+fn regenerate(
+    display_name: &str,
+    owner: &TemplateOwner,
+    mb_path: &Path,
+    force: bool,
+) -> Result<bool> {
     // AGENTS.md
-    std::fs::write(
-        mb_path.join("AGENTS.md"),
-        generate_agents_md(display_name, owner_name),
-    )?;
-    println!("  Updated AGENTS.md");
+    let agents = generate_agents_md(display_name, owner);
+    let mut clean = write_template(mb_path, "AGENTS.md", "agents", &agents, force)?;
 
     // CLAUDE.md symlink
     let claude_md = mb_path.join("CLAUDE.md");
@@ -40,16 +82,13 @@ fn regenerate(display_name: &str, owner_name: &str, mb_path: &Path) -> Result<()
     println!("  Updated CLAUDE.md -> AGENTS.md");
 
     // README.md
-    std::fs::write(
-        mb_path.join("README.md"),
-        generate_readme_md(display_name, owner_name),
-    )?;
-    println!("  Updated README.md");
+    let readme = generate_readme_md(display_name, owner);
+    clean &= write_template(mb_path, "README.md", "readme", &readme, force)?;
 
     // .gitignore
     std::fs::write(
         mb_path.join(".gitignore"),
-        "AGENTS.local.md\nCLAUDE.local.md\n__pycache__/\n",
+        GITIGNORE,
     )?;
     println!("  Updated .gitignore");
 
@@ -64,11 +103,12 @@ fn regenerate(display_name: &str, owner_name: &str, mb_path: &Path) -> Result<()
     crate::skill::install_at(Some(mb_path))?;
     println!("  Updated .claude/skills/corky/SKILL.md");
 
-    Ok(())
+    Ok(clean)
 }
 
-/// Pull, regenerate templates, commit, and push for one mailbox.
-fn reset_one(name: &str, owner_name: &str, do_sync: bool) -> Result<()> {
+/// Pull, regenerate templates, commit, and push for one mailbox. Returns
+/// false when locally modified templates were kept.
+fn reset_one(name: &str, owner: &TemplateOwner, do_sync: bool, force: bool) -> Result<bool> {
     let mb_path = resolve::mailbox_dir(name);
     if !mb_path.exists() {
         println!(
@@ -76,7 +116,7 @@ fn reset_one(name: &str, owner_name: &str, do_sync: bool) -> Result<()> {
             name,
             mb_path.display()
         );
-        return Ok(());
+        return Ok(true);
     }
 
     let is_git = mb_path.join(".git").exists();
@@ -86,7 +126,7 @@ fn reset_one(name: &str, owner_name: &str, do_sync: bool) -> Result<()> {
 
     // 1. Pull latest (only for git repos)
     if do_sync && is_git {
-        let (stdout, _, code) = run_git(&["git", "-C", &sp, "pull", "--rebase"]);
+        let (stdout, _, code) = util::run_git(&["-C", &sp, "pull", "--rebase"])?;
         if code == 0 {
             if !stdout.contains("Already up to date") {
                 println!("  Pulled changes");
@@ -97,26 +137,25 @@ fn reset_one(name: &str, owner_name: &str, do_sync: bool) -> Result<()> {
     }
 
     // 2. Regenerate template files
-    regenerate(name, owner_name, &mb_path)?;
+    let clean = regenerate(name, owner, &mb_path, force)?;
 
     if !do_sync || !is_git {
-        return Ok(());
+        return Ok(clean);
     }
 
     // 3. Stage, commit, push
-    run_git(&["git", "-C", &sp, "add", "-A"]);
+    util::run_git(&["-C", &sp, "add", "-A"])?;
 
-    let (status_out, _, _) = run_git(&["git", "-C", &sp, "status", "--porcelain"]);
+    let (status_out, _, _) = util::run_git(&["-C", &sp, "status", "--porcelain"])?;
     if !status_out.trim().is_empty() {
-        run_git(&[
-            "git",
-            "-C",
-            &sp,
-            "commit",
-            "-m",
-            "Reset template files to current version",
-        ]);
-        let (_, stderr, code) = run_git(&["git", "-C", &sp, "push"]);
+        util::run_git(&["-C", &sp, "commit", "-m", "Reset template files to current version"])?;
+        let (_, stderr, code) = util::run_git(&["-C", &sp, "push"])?;
+        let pushed = if code == 0 {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{}", stderr.trim()))
+        };
+        crate::audit::record("mailbox push", name, &pushed);
         if code == 0 {
             println!("  Pushed changes");
         } else {
@@ -127,13 +166,13 @@ fn reset_one(name: &str, owner_name: &str, do_sync: bool) -> Result<()> {
     }
 
     // 4. Update submodule ref in parent
-    run_git(&["git", "add", &sp]);
+    util::run_git(&["add", &sp])?;
 
-    Ok(())
+    Ok(clean)
 }
 
-/// corky mailbox reset [NAME] [--no-sync]
-pub fn run(name: Option<&str>, no_sync: bool) -> Result<()> {
+/// corky mailbox reset [NAME] [--no-sync] [--force]
+pub fn run(name: Option<&str>, no_sync: bool, force: bool) -> Result<()> {
     let config = corky_config::try_load_config(None);
     let mailbox_names: Vec<String> = config
         .as_ref()
@@ -154,15 +193,21 @@ pub fn run(name: Option<&str>, no_sync: bool) -> Result<()> {
         mailbox_names
     };
 
-    let owner = load_owner(None)?;
-    let owner_name = if owner.name.is_empty() {
-        &owner.github_user
-    } else {
-        &owner.name
-    };
+    let owner = TemplateOwner::from_config(&load_owner(None)?)?;
 
+    let mut kept = Vec::new();
     for n in &names {
-        reset_one(n, owner_name, !no_sync)?;
+        if !reset_one(n, &owner, !no_sync, force)? {
+            kept.push(n.as_str());
+        }
+    }
+    if !kept.is_empty() {
+        let flags = if force { "--force --yes" } else { "--force" };
+        println!(
+            "Kept locally modified templates in: {} (corky mailbox reset {} to replace)",
+            kept.join(", "),
+            flags
+        );
     }
 
     Ok(())
@@ -0,0 +1,327 @@
+//! The owner's review queue: collaborator drafts marked `Status: review`.
+//!
+//! `mailbox sync` reports drafts that arrived or changed in the pull, copies
+//! them into the root `drafts/` with a `Source` back-reference, and runs
+//! `[owner] review_hook` with their paths; `mailbox status` lists every
+//! draft still waiting. When the owner approves or sends a copy, the next
+//! sync writes that status (and `Sent-At`) back to the mailbox draft.
+
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::draft::{parse_draft, set_draft_field};
+use crate::resolve;
+use crate::util;
+
+/// Time limit for `[owner] review_hook`.
+const REVIEW_HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Owner-side statuses that are written back to the mailbox draft.
+const PROPAGATED_STATUSES: &[&str] = &["approved", "scheduled", "sent"];
+
+/// A draft waiting for the owner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReviewDraft {
+    pub path: PathBuf,
+    pub subject: String,
+    pub author: String,
+}
+
+/// The draft at `path`, if it parses and its status is `review`.
+pub fn review_draft(path: &Path) -> Option<ReviewDraft> {
+    let (meta, subject, _) = parse_draft(path).ok()?;
+    if !meta
+        .get("Status")
+        .is_some_and(|s| s.eq_ignore_ascii_case("review"))
+    {
+        return None;
+    }
+    Some(ReviewDraft {
+        path: path.to_path_buf(),
+        subject,
+        author: meta.get("Author").cloned().unwrap_or_default(),
+    })
+}
+
+fn collect_drafts(dir: &Path, out: &mut Vec<PathBuf>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_drafts(&path, out);
+        } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
+            out.push(path);
+        }
+    }
+}
+
+/// Every review draft under `{mb_path}/drafts/`, sorted by path.
+pub fn review_queue(mb_path: &Path) -> Vec<ReviewDraft> {
+    let mut files = Vec::new();
+    collect_drafts(&mb_path.join("drafts"), &mut files);
+    files.sort();
+    files.iter().filter_map(|p| review_draft(p)).collect()
+}
+
+/// Current commit of a mailbox repo, if it has one.
+pub fn head(mb_path: &Path) -> Option<String> {
+    let sp = mb_path.to_string_lossy().to_string();
+    util::run_git(&["-C", &sp, "rev-parse", "HEAD"])
+        .ok()
+        .filter(|(_, _, code)| *code == 0)
+        .map(|(stdout, _, _)| stdout.trim().to_string())
+}
+
+/// Review drafts added or changed between commit `since` and HEAD.
+pub fn changed_review_drafts(mb_path: &Path, since: &str) -> Result<Vec<ReviewDraft>> {
+    let sp = mb_path.to_string_lossy().to_string();
+    let range = format!("{}..HEAD", since);
+    let (stdout, stderr, code) = util::run_git(&[
+        "-C",
+        &sp,
+        "diff",
+        "--name-only",
+        "--diff-filter=AM",
+        &range,
+        "--",
+        "drafts",
+    ])?;
+    if code != 0 {
+        anyhow::bail!("git diff in {} failed: {}", sp, stderr.trim());
+    }
+    Ok(stdout
+        .lines()
+        .filter(|l| l.ends_with(".md"))
+        .filter_map(|l| review_draft(&mb_path.join(l)))
+        .collect())
+}
+
+/// Run `hook` through `sh -c` with the draft paths as arguments and
+/// `CORKY_MAILBOX` set to the mailbox name.
+pub fn run_hook(hook: &str, mailbox: &str, drafts: &[ReviewDraft]) -> Result<()> {
+    let paths: Vec<String> = drafts
+        .iter()
+        .map(|d| d.path.to_string_lossy().to_string())
+        .collect();
+    let mut args = vec!["sh", "-c", hook, "corky-review"];
+    args.extend(paths.iter().map(String::as_str));
+    let (_, stderr, code) =
+        util::run_cmd_timeout(&args, &[("CORKY_MAILBOX", mailbox)], REVIEW_HOOK_TIMEOUT)?;
+    if code != 0 {
+        anyhow::bail!("review_hook exited with {}: {}", code, stderr.trim());
+    }
+    Ok(())
+}
+
+/// `Source` value for a mailbox draft: its path relative to `data_dir`.
+fn source_ref(path: &Path, data_dir: &Path) -> String {
+    path.strip_prefix(data_dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .to_string()
+}
+
+/// Root drafts that carry a `Source`, as (root path, source, meta).
+fn sourced_drafts(root_drafts: &Path) -> Vec<(PathBuf, String, HashMap<String, String>)> {
+    let mut files = Vec::new();
+    collect_drafts(root_drafts, &mut files);
+    files.sort();
+    files
+        .into_iter()
+        .filter_map(|path| {
+            let (meta, _, _) = parse_draft(&path).ok()?;
+            let source = meta.get("Source")?.clone();
+            Some((path, source, meta))
+        })
+        .collect()
+}
+
+/// Copy review drafts into `root_drafts` as `{mailbox}-{file}`, recording
+/// where each came from. Drafts already copied are skipped. Returns the copies.
+pub fn ingest(
+    mailbox: &str,
+    drafts: &[ReviewDraft],
+    data_dir: &Path,
+    root_drafts: &Path,
+) -> Result<Vec<PathBuf>> {
+    let known: Vec<String> = sourced_drafts(root_drafts)
+        .into_iter()
+        .map(|(_, source, _)| source)
+        .collect();
+    let mut copied = Vec::new();
+    for draft in drafts {
+        let source = source_ref(&draft.path, data_dir);
+        if known.contains(&source) {
+            continue;
+        }
+        let Some(file_name) = draft.path.file_name() else {
+            continue;
+        };
+        let dest = root_drafts.join(format!("{}-{}", mailbox, file_name.to_string_lossy()));
+        if dest.exists() {
+            eprintln!(
+                "  Warning: {} exists; not copying {}",
+                dest.display(),
+                source
+            );
+            continue;
+        }
+        std::fs::create_dir_all(root_drafts)?;
+        std::fs::copy(&draft.path, &dest)?;
+        set_draft_field(&dest, "Source", &source)?;
+        copied.push(dest);
+    }
+    Ok(copied)
+}
+
+/// Write the owner's status (and `Sent-At`) from root copies back to the
+/// mailbox drafts they came from. Returns how many mailbox drafts changed.
+pub fn reconcile(mailbox: &str, data_dir: &Path, root_drafts: &Path) -> Result<usize> {
+    let prefix = format!("mailboxes/{}/", mailbox);
+    let mut updated = 0;
+    for (_, source, meta) in sourced_drafts(root_drafts) {
+        if !source.starts_with(&prefix) {
+            continue;
+        }
+        let status = meta
+            .get("Status")
+            .map(|s| s.to_lowercase())
+            .unwrap_or_default();
+        if !PROPAGATED_STATUSES.contains(&status.as_str()) {
+            continue;
+        }
+        let target = data_dir.join(&source);
+        let Ok((target_meta, _, _)) = parse_draft(&target) else {
+            continue;
+        };
+        let mut changed = false;
+        if target_meta.get("Status").map(|s| s.to_lowercase()) != Some(status.clone()) {
+            set_draft_field(&target, "Status", &status)?;
+            changed = true;
+        }
+        let sent_at = meta
+            .get("Sent-At")
+            .filter(|s| target_meta.get("Sent-At") != Some(*s));
+        if let Some(sent_at) = sent_at {
+            set_draft_field(&target, "Sent-At", sent_at)?;
+            changed = true;
+        }
+        if changed {
+            updated += 1;
+        }
+    }
+    Ok(updated)
+}
+
+/// Report review drafts that arrived in a pull, copy them into the root
+/// `drafts/`, and fire `[owner] review_hook`. Failures here are warnings.
+pub fn announce(mailbox: &str, drafts: &[ReviewDraft]) {
+    if drafts.is_empty() {
+        return;
+    }
+    println!("  {} draft(s) ready for review:", drafts.len());
+    for d in drafts {
+        println!("    {}", d.path.display());
+    }
+    match ingest(
+        mailbox,
+        drafts,
+        &resolve::data_dir(),
+        &resolve::drafts_dir(),
+    ) {
+        Ok(copied) => {
+            for path in copied {
+                println!("    -> {}", path.display());
+            }
+        }
+        Err(e) => eprintln!("  Warning: copying review drafts failed: {:#}", e),
+    }
+    let hook = crate::accounts::load_owner(None)
+        .map(|o| o.review_hook)
+        .unwrap_or_default();
+    if hook.trim().is_empty() {
+        return;
+    }
+    if let Err(e) = run_hook(&hook, mailbox, drafts) {
+        eprintln!("  Warning: {:#}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    const REVIEW: &str = "# Re: Lunch\n\n**To**: alice@example.com\n**Status**: review\n**Author**: Lucas\n\n---\n\nNoon works.\n";
+
+    #[test]
+    fn review_queue_lists_only_review_drafts() {
+        let dir = tempfile::tempdir().unwrap();
+        let drafts = dir.path().join("drafts");
+        fs::create_dir_all(drafts.join("nested")).unwrap();
+        fs::write(drafts.join("nested/lunch.md"), REVIEW).unwrap();
+        fs::write(drafts.join("wip.md"), REVIEW.replace("review", "draft")).unwrap();
+        fs::write(drafts.join("notes.txt"), REVIEW).unwrap();
+
+        let queue = review_queue(dir.path());
+        assert_eq!(
+            queue,
+            vec![ReviewDraft {
+                path: drafts.join("nested/lunch.md"),
+                subject: "Re: Lunch".to_string(),
+                author: "Lucas".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn ingest_then_reconcile_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let data = dir.path();
+        let mb_drafts = data.join("mailboxes/lucas/drafts");
+        let root_drafts = data.join("drafts");
+        fs::create_dir_all(&mb_drafts).unwrap();
+        fs::write(mb_drafts.join("lunch.md"), REVIEW).unwrap();
+
+        let queue = review_queue(&data.join("mailboxes/lucas"));
+        let copied = ingest("lucas", &queue, data, &root_drafts).unwrap();
+        assert_eq!(copied, vec![root_drafts.join("lucas-lunch.md")]);
+        let (meta, _, _) = parse_draft(&copied[0]).unwrap();
+        assert_eq!(meta["Source"], "mailboxes/lucas/drafts/lunch.md");
+        assert!(
+            ingest("lucas", &queue, data, &root_drafts)
+                .unwrap()
+                .is_empty()
+        );
+
+        // Still in review on the owner side: nothing to propagate.
+        assert_eq!(reconcile("lucas", data, &root_drafts).unwrap(), 0);
+
+        set_draft_field(&copied[0], "Status", "sent").unwrap();
+        set_draft_field(&copied[0], "Sent-At", "2025-02-10T10:00:00+00:00").unwrap();
+        assert_eq!(reconcile("lucas", data, &root_drafts).unwrap(), 1);
+        let (meta, _, _) = parse_draft(&mb_drafts.join("lunch.md")).unwrap();
+        assert_eq!(meta["Status"], "sent");
+        assert_eq!(meta["Sent-At"], "2025-02-10T10:00:00+00:00");
+        assert_eq!(reconcile("lucas", data, &root_drafts).unwrap(), 0);
+    }
+
+    #[test]
+    fn run_hook_passes_paths_and_mailbox() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out.txt");
+        let drafts = vec![ReviewDraft {
+            path: PathBuf::from("/tmp/a.md"),
+            subject: String::new(),
+            author: String::new(),
+        }];
+        let hook = format!("echo \"$CORKY_MAILBOX $@\" > {}", out.display());
+        run_hook(&hook, "lucas", &drafts).unwrap();
+        assert_eq!(fs::read_to_string(&out).unwrap(), "lucas /tmp/a.md\n");
+        assert!(run_hook("exit 3", "lucas", &drafts).is_err());
+    }
+}
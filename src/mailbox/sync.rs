@@ -2,32 +2,27 @@
 
 use anyhow::Result;
 use std::path::{Path, PathBuf};
-use std::process::Command;
 
 use crate::config::{corky_config, topic};
+use crate::error::CorkyError;
+use crate::mailbox::hooks;
+use crate::mailbox::merge;
+use crate::mailbox::review::{self, ReviewDraft};
+use crate::mailbox::secrets;
+use crate::mailbox::verify;
+use crate::output::{self, Style};
 use crate::resolve;
+use crate::util;
 
-fn run_git(args: &[&str]) -> (String, String, i32) {
-    let output = Command::new(args[0])
-        .args(&args[1..])
-        .output()
-        .unwrap_or_else(|_| {
-            panic!("Failed to run: {}", args.join(" "));
-        });
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let code = output.status.code().unwrap_or(-1);
-    (stdout, stderr, code)
-}
-
-fn mailbox_status(name: &str, mb_path: &Path) {
+/// Incoming/outgoing commit counts for a mailbox repo, colored by state.
+fn mailbox_status(mb_path: &Path) -> Result<String> {
     let sp = mb_path.to_string_lossy().to_string();
-    run_git(&["git", "-C", &sp, "fetch"]);
+    util::run_git(&["-C", &sp, "fetch"])?;
 
     let (incoming, _, inc_code) =
-        run_git(&["git", "-C", &sp, "rev-list", "--count", "HEAD..@{u}"]);
+        util::run_git(&["-C", &sp, "rev-list", "--count", "HEAD..@{u}"])?;
     let (outgoing, _, out_code) =
-        run_git(&["git", "-C", &sp, "rev-list", "--count", "@{u}..HEAD"]);
+        util::run_git(&["-C", &sp, "rev-list", "--count", "@{u}..HEAD"])?;
 
     let inc = if inc_code == 0 {
         incoming.trim().to_string()
@@ -41,7 +36,7 @@ fn mailbox_status(name: &str, mb_path: &Path) {
     };
 
     if inc == "0" && out == "0" {
-        println!("  {}: up to date", name);
+        Ok(output::ok("up to date"))
     } else {
         let mut parts = Vec::new();
         if inc != "0" {
@@ -50,7 +45,7 @@ fn mailbox_status(name: &str, mb_path: &Path) {
         if out != "0" {
             parts.push(format!("{} outgoing", out));
         }
-        println!("  {}: {}", name, parts.join(", "));
+        Ok(output::warning(&parts.join(", ")))
     }
 }
 
@@ -165,7 +160,7 @@ fn sync_topics(
     }
 
     if synced > 0 {
-        println!(
+        crate::progress!(
             "  Synced {} topic file(s) ({})",
             synced,
             topics.keys().cloned().collect::<Vec<_>>().join(", ")
@@ -174,77 +169,410 @@ fn sync_topics(
     Ok(())
 }
 
-/// Full sync for one mailbox.
-pub fn sync_one(name: &str) -> Result<()> {
+/// Default sync commit message: the historical subject, a change summary,
+/// and the per-thread and per-draft details.
+pub const DEFAULT_COMMIT_TEMPLATE: &str = "Sync shared conversations\n\n{summary}\n\n{details}";
+
+/// At most this many lines of `{details}`; the rest are counted.
+const MAX_DETAILS: usize = 50;
+
+/// Counts of changed files, from `git status --porcelain`, and what changed
+/// in them, from the staged diff ([`ChangeSummary::add_details`]).
+#[derive(Debug, Default, PartialEq)]
+pub struct ChangeSummary {
+    pub threads: usize,
+    pub drafts: usize,
+    pub other: usize,
+    /// One line per changed thread or draft, e.g. `- Lunch: +2 messages`.
+    pub details: Vec<String>,
+}
+
+/// One file's section of a `git diff -U0` (conversations and drafts only).
+#[derive(Debug, Default)]
+struct FileDiff {
+    path: String,
+    renamed_from: Option<String>,
+    created: bool,
+    deleted: bool,
+    /// `## Sender — Date` message headings added and removed.
+    added: usize,
+    removed: usize,
+    old_status: Option<String>,
+    new_status: Option<String>,
+}
+
+/// The value of a draft's `status:` frontmatter line (or legacy
+/// `**Status**:` line).
+fn draft_status(line: &str) -> Option<String> {
+    let value = line
+        .strip_prefix("status:")
+        .or_else(|| line.strip_prefix("**Status**:"))?;
+    Some(value.trim().trim_matches('"').to_string())
+}
+
+/// A `## Sender — Date` message heading.
+fn is_message_heading(line: &str) -> bool {
+    line.starts_with("## ") && line.contains(" \u{2014} ")
+}
+
+/// Split a unified diff into per-file changes.
+fn parse_diff(diff: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    for line in diff.lines() {
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            let path = paths.rsplit_once(" b/").map(|(_, b)| b).unwrap_or(paths);
+            files.push(FileDiff {
+                path: path.to_string(),
+                ..Default::default()
+            });
+            continue;
+        }
+        let Some(file) = files.last_mut() else {
+            continue;
+        };
+        if line.starts_with("new file mode") {
+            file.created = true;
+        } else if line.starts_with("deleted file mode") {
+            file.deleted = true;
+        } else if let Some(from) = line.strip_prefix("rename from ") {
+            file.renamed_from = Some(from.to_string());
+        } else if line.starts_with("+++ ") || line.starts_with("--- ") {
+            continue;
+        } else if let Some(added) = line.strip_prefix('+') {
+            if let Some(status) = draft_status(added) {
+                file.new_status = Some(status);
+            } else if is_message_heading(added) {
+                file.added += 1;
+            }
+        } else if let Some(removed) = line.strip_prefix('-') {
+            if let Some(status) = draft_status(removed) {
+                file.old_status = Some(status);
+            } else if is_message_heading(removed) {
+                file.removed += 1;
+            }
+        }
+    }
+    files
+}
+
+/// The `# Title` of the Markdown file at `path`.
+fn title_of(path: &Path) -> Option<String> {
+    let text = std::fs::read_to_string(path).ok()?;
+    text.lines()
+        .find_map(|l| l.strip_prefix("# "))
+        .map(|t| t.trim().to_string())
+}
+
+fn plural(n: usize, word: &str) -> String {
+    format!("{} {}{}", n, word, if n == 1 { "" } else { "s" })
+}
+
+impl ChangeSummary {
+    pub fn from_porcelain(status: &str) -> Self {
+        let mut summary = ChangeSummary::default();
+        for line in status.lines().filter(|l| l.len() > 3) {
+            let path = &line[3..];
+            // Renames are reported as "old -> new".
+            let path = path.rsplit(" -> ").next().unwrap_or(path).trim_matches('"');
+            if path.starts_with("conversations/") && path.ends_with(".md") {
+                summary.threads += 1;
+            } else if path.starts_with("drafts/") && path.ends_with(".md") {
+                summary.drafts += 1;
+            } else {
+                summary.other += 1;
+            }
+        }
+        summary
+    }
+
+    pub fn files(&self) -> usize {
+        self.threads + self.drafts + self.other
+    }
+
+    /// Fill in [`ChangeSummary::details`] from the staged diff of the
+    /// mailbox at `root` (`git diff --cached -U0 -M`): which threads gained
+    /// messages, and which drafts are new or changed status.
+    pub fn add_details(&mut self, diff: &str, root: &Path) {
+        let mut threads = Vec::new();
+        let mut drafts = Vec::new();
+        for file in parse_diff(diff) {
+            if !file.path.ends_with(".md") {
+                continue;
+            }
+            let name = file.path.rsplit('/').next().unwrap_or(&file.path).to_string();
+            if file.path.starts_with("conversations/") {
+                let title = title_of(&root.join(&file.path)).unwrap_or(name);
+                let change = if file.deleted {
+                    "removed".to_string()
+                } else if file.created {
+                    format!("new thread, {}", plural(file.added, "message"))
+                } else if file.added > file.removed {
+                    format!("+{}", plural(file.added - file.removed, "message"))
+                } else if let Some(from) = &file.renamed_from {
+                    format!("renamed from {}", from.rsplit('/').next().unwrap_or(from))
+                } else {
+                    "updated".to_string()
+                };
+                threads.push(format!("- {}: {}", title, change));
+            } else if file.path.starts_with("drafts/") {
+                let change = match (&file.old_status, &file.new_status) {
+                    _ if file.deleted => "removed".to_string(),
+                    (_, Some(status)) if file.created => format!("new ({})", status),
+                    _ if file.created => "new".to_string(),
+                    (Some(from), Some(to)) if from != to => format!("{} -> {}", from, to),
+                    (None, Some(to)) => format!("status {}", to),
+                    _ => "edited".to_string(),
+                };
+                drafts.push(format!("- {}: {}", name, change));
+            }
+        }
+        let mut lines = Vec::new();
+        for (heading, entries) in [("Threads:", threads), ("Drafts:", drafts)] {
+            if !entries.is_empty() {
+                lines.push(heading.to_string());
+                lines.extend(entries);
+            }
+        }
+        if lines.len() > MAX_DETAILS {
+            let more = lines.len() - MAX_DETAILS;
+            lines.truncate(MAX_DETAILS);
+            lines.push(format!("... and {} more", more));
+        }
+        self.details = lines;
+    }
+
+    /// e.g. `2 threads, 1 draft, 1 other file changed`.
+    pub fn describe(&self) -> String {
+        let mut parts = Vec::new();
+        for (n, word) in [
+            (self.threads, "thread"),
+            (self.drafts, "draft"),
+            (self.other, "other file"),
+        ] {
+            if n > 0 {
+                parts.push(plural(n, word));
+            }
+        }
+        if parts.is_empty() {
+            return "No changes".to_string();
+        }
+        format!("{} changed", parts.join(", "))
+    }
+}
+
+/// Expand a commit message template for one mailbox.
+pub fn commit_message(template: &str, mailbox: &str, summary: &ChangeSummary) -> String {
+    let template = if template.trim().is_empty() {
+        DEFAULT_COMMIT_TEMPLATE
+    } else {
+        template
+    };
+    template
+        .replace("{mailbox}", mailbox)
+        .replace("{threads}", &summary.threads.to_string())
+        .replace("{drafts}", &summary.drafts.to_string())
+        .replace("{files}", &summary.files().to_string())
+        .replace("{summary}", &summary.describe())
+        .replace("{details}", &summary.details.join("\n"))
+        .trim_end()
+        .to_string()
+}
+
+/// `git -c` overrides setting author and committer from `Name <email>`.
+fn author_config(author: &str) -> Result<Vec<String>> {
+    let (name, email) = author
+        .trim()
+        .strip_suffix('>')
+        .and_then(|s| s.split_once('<'))
+        .map(|(n, e)| (n.trim(), e.trim()))
+        .filter(|(n, e)| !n.is_empty() && e.contains('@'))
+        .ok_or_else(|| {
+            anyhow::anyhow!("Invalid git_author '{}': expected 'Name <email>'", author)
+        })?;
+    Ok(vec![
+        "-c".to_string(),
+        format!("user.name={}", name),
+        "-c".to_string(),
+        format!("user.email={}", email),
+    ])
+}
+
+/// Which halves of a mailbox sync to run.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SyncMode {
+    /// Pull, then commit and push local changes.
+    Both,
+    /// Pull collaborator changes only; nothing is committed or pushed.
+    PullOnly,
+    /// Commit and push local changes without pulling first.
+    PushOnly,
+}
+
+impl SyncMode {
+    /// Mode from the `--pull-only` / `--push-only` flags.
+    pub fn from_flags(pull_only: bool, push_only: bool) -> Self {
+        match (pull_only, push_only) {
+            (true, _) => SyncMode::PullOnly,
+            (_, true) => SyncMode::PushOnly,
+            _ => SyncMode::Both,
+        }
+    }
+
+    fn pulls(self) -> bool {
+        self != SyncMode::PushOnly
+    }
+
+    fn pushes(self) -> bool {
+        self != SyncMode::PullOnly
+    }
+}
+
+/// Full sync for one mailbox, or just its pull or push half.
+/// Returns the review drafts that arrived or changed in the pull.
+///
+/// Unless `allow_secrets` is set, nothing is committed or pushed while the
+/// outgoing changes look like they contain credentials.
+pub fn sync_one(name: &str, mode: SyncMode, allow_secrets: bool) -> Result<Vec<ReviewDraft>> {
     let mb_path = resolve::mailbox_dir(name);
     if !mb_path.exists() {
-        println!(
+        crate::progress!(
             "  {}: mailbox not found at {} -- skipping",
             name,
             mb_path.display()
         );
-        return Ok(());
+        return Ok(Vec::new());
     }
 
     if !is_git_repo(&mb_path) {
-        println!("  {}: plain directory -- skipping git sync", name);
-        return Ok(());
+        crate::progress!("  {}: plain directory -- skipping git sync", name);
+        return Ok(Vec::new());
     }
 
-    println!("Syncing {}...", name);
+    crate::progress!("Syncing {}...", name);
     let sp = mb_path.to_string_lossy().to_string();
 
+    // Several owners may route into the same conversations; merge them
+    // instead of conflicting. A new .gitattributes goes out with the commit.
+    if merge::ensure_merge_driver(&mb_path, mode.pushes())? {
+        crate::progress!("  Added conversation merge driver to .gitattributes");
+    }
+
     // Pull changes
-    let (stdout, _stderr, code) = run_git(&["git", "-C", &sp, "pull", "--rebase"]);
-    if code == 0 {
-        if !stdout.contains("Already up to date") {
-            println!("  Pulled changes");
+    let mut incoming = Vec::new();
+    if mode.pulls() {
+        let before = review::head(&mb_path);
+        let (stdout, stderr, code) =
+            util::run_git(&["-C", &sp, "pull", "--rebase", "--autostash"])?;
+        if code != 0 {
+            if !mode.pushes() {
+                anyhow::bail!("git pull in {} failed: {}", name, stderr.trim());
+            }
+            println!("  Pull failed -- continuing with push");
+        } else if !stdout.contains("Already up to date") {
+            crate::progress!("  Pulled changes");
+            if let Some(before) = before {
+                incoming = review::changed_review_drafts(&mb_path, &before)?;
+                review::announce(name, &incoming);
+            }
         }
-    } else {
-        println!("  Pull failed -- continuing with push");
+    }
+
+    if !mode.pushes() {
+        // Pull-only: leave local edits uncommitted for review
+        util::run_git(&["add", &sp])?;
+        return Ok(incoming);
     }
 
     // Copy voice.md if root copy is newer
     let voice_file = resolve::voice_md();
     let mb_voice = mb_path.join("voice.md");
     if voice_file.exists() && copy_if_newer(&voice_file, &mb_voice)? {
-        println!("  Updated voice.md");
+        crate::progress!("  Updated voice.md");
     }
 
     // Bidirectional topic sync
     sync_topics(name, &mb_path, None, None)?;
 
+    // Write the owner's decisions on copied drafts back to the mailbox
+    let reconciled = review::reconcile(name, &resolve::data_dir(), &resolve::drafts_dir())?;
+    if reconciled > 0 {
+        crate::progress!("  Updated status of {} draft(s)", reconciled);
+    }
+
     // Stage, commit, push any local changes
-    run_git(&["git", "-C", &sp, "add", "-A"]);
+    util::run_git(&["-C", &sp, "add", "-A"])?;
+
+    if !allow_secrets {
+        let findings = secrets::outgoing_findings(&mb_path)?;
+        if !findings.is_empty() {
+            println!("  Possible secrets in outgoing changes:");
+            println!("{}", secrets::report(&findings));
+            return Err(CorkyError::Validation(format!(
+                "Not pushing {}: {} possible secret(s). Remove them, or re-run with --allow-secrets",
+                name,
+                findings.len()
+            ))
+            .into());
+        }
+    }
 
-    let (status_out, _, _) = run_git(&["git", "-C", &sp, "status", "--porcelain"]);
+    let (status_out, _, _) = util::run_git(&["-C", &sp, "status", "--porcelain"])?;
     if !status_out.trim().is_empty() {
-        run_git(&[
-            "git",
+        let mb_config = corky_config::try_load_config(None)
+            .and_then(|c| c.mailboxes.get(name).cloned())
+            .unwrap_or_default();
+        let mut summary = ChangeSummary::from_porcelain(&status_out);
+        let (diff, _, _) = util::run_git(&[
             "-C",
             &sp,
-            "commit",
-            "-m",
-            "Sync shared conversations",
-        ]);
-        let (_, stderr, code) = run_git(&["git", "-C", &sp, "push"]);
+            "diff",
+            "--cached",
+            "--no-color",
+            "--no-ext-diff",
+            "-U0",
+            "-M",
+            "--",
+            "conversations",
+            "drafts",
+        ])?;
+        summary.add_details(&diff, &mb_path);
+        let message = commit_message(&mb_config.commit_message_template, name, &summary);
+        let mut args = if mb_config.git_author.is_empty() {
+            Vec::new()
+        } else {
+            author_config(&mb_config.git_author)?
+        };
+        args.extend(["-C", sp.as_str(), "commit", "-m", message.as_str()].map(String::from));
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        let (_, stderr, code) = util::run_git(&args)?;
+        if code != 0 {
+            anyhow::bail!("git commit in {} failed: {}", name, stderr.trim());
+        }
+        crate::progress!("  Committed: {}", summary.describe());
+        let (_, stderr, code) = hooks::push(&mb_path, allow_secrets)?;
+        let pushed = if code == 0 {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("{}", stderr.trim()))
+        };
+        crate::audit::record("mailbox push", name, &pushed);
         if code == 0 {
-            println!("  Pushed changes");
+            crate::progress!("  Pushed changes");
         } else {
             println!("  Push failed: {}", stderr.trim());
         }
     } else {
-        println!("  No local changes to push");
+        crate::progress!("  No local changes to push");
     }
 
     // Update submodule ref in parent
-    run_git(&["git", "add", &sp]);
+    util::run_git(&["add", &sp])?;
 
-    Ok(())
+    Ok(incoming)
 }
 
-/// corky mailbox sync [NAME]
-pub fn run(name: Option<&str>) -> Result<()> {
+/// corky mailbox sync [NAME] [--pull-only | --push-only] [--allow-secrets]
+pub fn run(name: Option<&str>, mode: SyncMode, allow_secrets: bool) -> Result<()> {
     let config = corky_config::try_load_config(None);
     let mailbox_names: Vec<String> = config
         .as_ref()
@@ -266,7 +594,7 @@ pub fn run(name: Option<&str>) -> Result<()> {
     };
 
     for n in &names {
-        sync_one(n)?;
+        sync_one(n, mode, allow_secrets)?;
     }
 
     Ok(())
@@ -286,18 +614,65 @@ pub fn status() -> Result<()> {
     }
 
     println!("Mailbox status:");
+    let owner = verify::template_owner();
+    let mut rows = Vec::new();
+    let mut queue = Vec::new();
+    let mut problems = Vec::new();
+    let mut stale = Vec::new();
     for name in &mailbox_names {
         let mb_path = resolve::mailbox_dir(name);
+        let state = if !mb_path.exists() {
+            output::error("not found")
+        } else if is_git_repo(&mb_path) {
+            mailbox_status(&mb_path)?
+        } else {
+            output::paint("plain directory", Style::Dim)
+        };
+        let mut row = vec![format!("{}:", name), state];
         if mb_path.exists() {
-            if is_git_repo(&mb_path) {
-                mailbox_status(name, &mb_path);
-            } else {
-                println!("  {}: plain directory", name);
+            let issues = verify::check(name, owner.as_ref(), &mb_path);
+            row.push(verify::describe(&issues));
+            if !issues.is_empty() {
+                problems.push((name.clone(), issues));
+            }
+            if owner
+                .as_ref()
+                .is_some_and(|o| verify::needs_reset(name, o, &mb_path))
+            {
+                stale.push(name.as_str());
             }
-        } else {
-            println!("  {}: not found", name);
+        }
+        rows.push(row);
+        for draft in review::review_queue(&mb_path) {
+            let rel = draft.path.strip_prefix(&mb_path).unwrap_or(&draft.path);
+            queue.push(vec![
+                name.clone(),
+                rel.display().to_string(),
+                draft.subject.clone(),
+                output::paint(&draft.author, Style::Dim),
+            ]);
         }
     }
+    output::print_table(&rows);
+
+    if !problems.is_empty() {
+        println!("\nStructure problems (corky mailbox verify):");
+        for (name, issues) in &problems {
+            verify::print_issues(name, issues);
+        }
+    }
+
+    if !stale.is_empty() {
+        println!(
+            "\nTemplates out of date or modified (corky mailbox reset): {}",
+            stale.join(", ")
+        );
+    }
+
+    if !queue.is_empty() {
+        println!("\nDrafts awaiting review:");
+        output::print_table(&queue);
+    }
 
     Ok(())
 }
@@ -309,6 +684,124 @@ mod tests {
     use std::thread;
     use std::time::Duration;
 
+    #[test]
+    fn sync_mode_from_flags() {
+        assert_eq!(SyncMode::from_flags(false, false), SyncMode::Both);
+        assert_eq!(SyncMode::from_flags(true, false), SyncMode::PullOnly);
+        assert_eq!(SyncMode::from_flags(false, true), SyncMode::PushOnly);
+        assert!(SyncMode::Both.pulls() && SyncMode::Both.pushes());
+        assert!(!SyncMode::PullOnly.pushes());
+        assert!(!SyncMode::PushOnly.pulls());
+    }
+
+    #[test]
+    fn change_summary_counts_threads_and_drafts() {
+        let status = " M conversations/a.md\n?? conversations/b.md\nA  drafts/reply.md\nR  notes.txt -> voice.md\n";
+        let summary = ChangeSummary::from_porcelain(status);
+        assert_eq!(
+            summary,
+            ChangeSummary {
+                threads: 2,
+                drafts: 1,
+                other: 1,
+                details: vec![],
+            }
+        );
+        assert_eq!(summary.describe(), "2 threads, 1 draft, 1 other file changed");
+    }
+
+    #[test]
+    fn commit_message_expands_placeholders() {
+        let mut summary = ChangeSummary {
+            threads: 3,
+            drafts: 0,
+            other: 0,
+            details: vec![],
+        };
+        assert_eq!(
+            commit_message("", "alex", &summary),
+            "Sync shared conversations\n\n3 threads changed"
+        );
+        assert_eq!(
+            commit_message("corky: {mailbox} +{threads}/{drafts} ({files})", "alex", &summary),
+            "corky: alex +3/0 (3)"
+        );
+        summary.details = vec!["Threads:".to_string(), "- Lunch: +1 message".to_string()];
+        assert_eq!(
+            commit_message("", "alex", &summary),
+            "Sync shared conversations\n\n3 threads changed\n\nThreads:\n- Lunch: +1 message"
+        );
+    }
+
+    #[test]
+    fn change_summary_details_from_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("conversations")).unwrap();
+        fs::write(dir.path().join("conversations/lunch.md"), "# Lunch plans\n").unwrap();
+        fs::write(dir.path().join("conversations/kickoff.md"), "# Kickoff\n").unwrap();
+        let diff = "\
+diff --git a/conversations/lunch.md b/conversations/lunch.md
+index 1111111..2222222 100644
+--- a/conversations/lunch.md
++++ b/conversations/lunch.md
+@@ -4 +4 @@
+-**Last updated**: Mon, 10 Feb 2025 10:00:00 +0000
++**Last updated**: Tue, 11 Feb 2025 09:00:00 +0000
+@@ -20,0 +21,8 @@
++## Bob <bob@example.com> \u{2014} Tue, 11 Feb 2025 08:00:00 +0000
++
++Noon works.
++## Alice <alice@example.com> \u{2014} Tue, 11 Feb 2025 09:00:00 +0000
++## Not a message heading
+diff --git a/conversations/kickoff.md b/conversations/kickoff.md
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/conversations/kickoff.md
+@@ -0,0 +1,9 @@
++# Kickoff
++## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000
+diff --git a/conversations/old.md b/conversations/old.md
+deleted file mode 100644
+diff --git a/drafts/reply.md b/drafts/reply.md
+index 4444444..5555555 100644
+--- a/drafts/reply.md
++++ b/drafts/reply.md
+@@ -3 +3 @@
+-status: review
++status: approved
+diff --git a/drafts/intro.md b/drafts/intro.md
+new file mode 100644
+--- /dev/null
++++ b/drafts/intro.md
+@@ -0,0 +1,3 @@
++**Status**: draft
+";
+        let mut summary = ChangeSummary::default();
+        summary.add_details(diff, dir.path());
+        assert_eq!(
+            summary.details,
+            vec![
+                "Threads:",
+                "- Lunch plans: +2 messages",
+                "- Kickoff: new thread, 1 message",
+                "- old.md: removed",
+                "Drafts:",
+                "- reply.md: review -> approved",
+                "- intro.md: new (draft)",
+            ]
+        );
+    }
+
+    #[test]
+    fn author_config_parses_name_and_email() {
+        assert_eq!(
+            author_config("Corky Bot <bot@example.com>").unwrap(),
+            vec!["-c", "user.name=Corky Bot", "-c", "user.email=bot@example.com"]
+        );
+        assert!(author_config("bot@example.com").is_err());
+    }
+
     #[test]
     fn copy_if_newer_creates_missing_dst() {
         let dir = tempfile::tempdir().unwrap();
@@ -1,4 +1,220 @@
 //! Template generators for mailbox repos (AGENTS.md, README.md).
+//!
+//! Text is rendered for a [`TemplateOwner`]: the owner's display name,
+//! pronouns and language from `[owner]`. English is built in; other
+//! languages come from `templates/mailbox-AGENTS.{lang}.md` and
+//! `templates/mailbox-README.{lang}.md` in the data dir.
+
+use anyhow::{bail, Result};
+
+use crate::accounts::OwnerConfig;
+use crate::resolve;
+
+/// .gitignore for shared mailbox repos: keeps collaborators' local agent notes out.
+pub const GITIGNORE: &str = "AGENTS.local.md\nCLAUDE.local.md\n__pycache__/\n";
+
+/// Version of the generated AGENTS.md / README.md text. Bump it whenever
+/// either template changes, so `mailbox status` can flag stale copies.
+/// Files written before version markers existed count as version 1.
+pub const TEMPLATE_VERSION: u32 = 3;
+
+const MARKER_PREFIX: &str = "<!-- corky-template ";
+
+/// State of a generated file in a mailbox.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TemplateStatus {
+    Missing,
+    /// Matches what `mailbox reset` would write.
+    Current,
+    /// Unchanged since it was generated, but from an older template.
+    Outdated { version: u32 },
+    /// Edited after it was generated, or has no marker.
+    Modified,
+}
+
+/// 64-bit FNV-1a, stable across builds (unlike `DefaultHasher`).
+fn content_hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Generated content plus a trailing marker line recording the template
+/// version and a hash of the content: `<!-- corky-template agents v2 HASH -->`.
+pub fn stamp(kind: &str, content: &str) -> String {
+    let mut out = content.to_string();
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "{}{} v{} {} -->\n",
+        MARKER_PREFIX,
+        kind,
+        TEMPLATE_VERSION,
+        content_hash(content)
+    ));
+    out
+}
+
+/// Split a file into (body, Some((version, hash))) when it ends in a marker.
+fn parse_marker(text: &str) -> (&str, Option<(u32, String)>) {
+    let trimmed = text.trim_end_matches('\n');
+    let (body, last) = match trimmed.rfind('\n') {
+        Some(i) => (&text[..i + 1], &trimmed[i + 1..]),
+        None => ("", trimmed),
+    };
+    let Some(fields) = last
+        .strip_prefix(MARKER_PREFIX)
+        .and_then(|s| s.strip_suffix(" -->"))
+    else {
+        return (text, None);
+    };
+    let parts: Vec<&str> = fields.split_whitespace().collect();
+    match parts.as_slice() {
+        [_, version, hash] => match version.trim_start_matches('v').parse() {
+            Ok(v) => (body, Some((v, hash.to_string()))),
+            Err(_) => (text, None),
+        },
+        _ => (text, None),
+    }
+}
+
+/// Compare a mailbox's copy of a generated file with the current template
+/// output (`expected`, without marker).
+pub fn template_status(installed: Option<&str>, expected: &str) -> TemplateStatus {
+    let Some(installed) = installed else {
+        return TemplateStatus::Missing;
+    };
+    let (body, marker) = parse_marker(installed);
+    match marker {
+        Some((version, _)) if body == expected && version == TEMPLATE_VERSION => {
+            TemplateStatus::Current
+        }
+        Some((version, hash)) if hash == content_hash(body) => {
+            TemplateStatus::Outdated { version }
+        }
+        None if body == expected => TemplateStatus::Outdated { version: 1 },
+        _ => TemplateStatus::Modified,
+    }
+}
+
+/// Pronoun forms used in templates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pronouns {
+    pub subject: String,
+    pub object: String,
+    pub possessive: String,
+}
+
+impl Default for Pronouns {
+    fn default() -> Self {
+        Pronouns {
+            subject: "they".to_string(),
+            object: "them".to_string(),
+            possessive: "their".to_string(),
+        }
+    }
+}
+
+impl Pronouns {
+    /// Parse `[owner] pronouns`: "he/him", "she/her", "they/them", or
+    /// explicit "subject/object/possessive" (e.g. "xe/xem/xyr"). Empty
+    /// means they/them.
+    pub fn parse(value: &str) -> Result<Self> {
+        let parts: Vec<String> = value
+            .split('/')
+            .map(|p| p.trim().to_lowercase())
+            .filter(|p| !p.is_empty())
+            .collect();
+        let forms = |s: &str, o: &str, p: &str| Pronouns {
+            subject: s.to_string(),
+            object: o.to_string(),
+            possessive: p.to_string(),
+        };
+        match parts.as_slice() {
+            [] => Ok(Pronouns::default()),
+            [s, o, p] => Ok(forms(s, o, p)),
+            [s, ..] if s == "he" => Ok(forms("he", "him", "his")),
+            [s, ..] if s == "she" => Ok(forms("she", "her", "her")),
+            [s, ..] if s == "they" => Ok(Pronouns::default()),
+            _ => bail!(
+                "Unrecognized [owner] pronouns \"{}\" -- use he/him, she/her, they/them, or subject/object/possessive",
+                value
+            ),
+        }
+    }
+}
+
+/// Whose mailbox the templates describe, from `[owner]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TemplateOwner {
+    pub name: String,
+    pub pronouns: Pronouns,
+    pub language: String,
+    /// `templates/mailbox-AGENTS.{lang}.md` for non-English languages.
+    pub agents_template: Option<String>,
+    /// `templates/mailbox-README.{lang}.md` for non-English languages.
+    pub readme_template: Option<String>,
+}
+
+impl TemplateOwner {
+    /// English templates with they/them pronouns.
+    pub fn new(name: &str) -> Self {
+        TemplateOwner {
+            name: name.to_string(),
+            pronouns: Pronouns::default(),
+            language: "en".to_string(),
+            agents_template: None,
+            readme_template: None,
+        }
+    }
+
+    /// Settings from `[owner]`. The display name falls back to
+    /// `github_user`; a language other than English needs both custom
+    /// template files.
+    pub fn from_config(owner: &OwnerConfig) -> Result<Self> {
+        let name = if owner.name.is_empty() {
+            &owner.github_user
+        } else {
+            &owner.name
+        };
+        let mut result = TemplateOwner::new(name);
+        result.pronouns = Pronouns::parse(&owner.pronouns)?;
+        let language = owner.language.trim().to_lowercase();
+        if language.is_empty() || language == "en" {
+            return Ok(result);
+        }
+        let dir = resolve::templates_dir();
+        let read = |kind: &str| {
+            let path = dir.join(format!("mailbox-{}.{}.md", kind, language));
+            std::fs::read_to_string(&path).map_err(|_| {
+                anyhow::anyhow!(
+                    "[owner] language = \"{}\" needs {} (built-in templates are English only)",
+                    language,
+                    path.display()
+                )
+            })
+        };
+        result.agents_template = Some(read("AGENTS")?);
+        result.readme_template = Some(read("README")?);
+        result.language = language;
+        Ok(result)
+    }
+
+    /// Fill a custom template's placeholders.
+    fn render(&self, template: &str, name: &str) -> String {
+        template
+            .replace("{title_name}", &capitalize(name))
+            .replace("{name}", name)
+            .replace("{owner_name}", &self.name)
+            .replace("{they}", &self.pronouns.subject)
+            .replace("{them}", &self.pronouns.object)
+            .replace("{their}", &self.pronouns.possessive)
+    }
+}
 
 fn capitalize(s: &str) -> String {
     let mut c = s.chars();
@@ -9,7 +225,10 @@ fn capitalize(s: &str) -> String {
 }
 
 /// Generate AGENTS.md for a mailbox repo.
-pub fn generate_agents_md(name: &str, owner_name: &str) -> String {
+pub fn generate_agents_md(name: &str, owner: &TemplateOwner) -> String {
+    if let Some(template) = &owner.agents_template {
+        return owner.render(template, name);
+    }
     let title_name = capitalize(name);
     format!(
         r#"# {owner_name}'s Mailbox for {title_name}
@@ -112,7 +331,7 @@ corky draft validate drafts/2026-02-19-example.md
 ## Voice guidelines
 
 See `voice.md` for {owner_name}'s writing voice. Match this style when drafting
-on his behalf.
+on {their} behalf.
 
 ## What you can do
 
@@ -128,18 +347,22 @@ on his behalf.
 - Change draft Status to `sent`
 "#,
         name = name,
-        owner_name = owner_name,
+        owner_name = owner.name,
+        their = owner.pronouns.possessive,
     )
 }
 
 /// Generate README.md for a mailbox repo.
-pub fn generate_readme_md(name: &str, owner_name: &str) -> String {
+pub fn generate_readme_md(name: &str, owner: &TemplateOwner) -> String {
+    if let Some(template) = &owner.readme_template {
+        return owner.render(template, name);
+    }
     let title_name = capitalize(name);
     format!(
         r#"# {owner_name}'s Mailbox for {title_name}
 
 This repo contains email threads {owner_name} has shared with you and a place for you
-to draft replies on his behalf.
+to draft replies on {their} behalf.
 
 ## Quick start
 
@@ -196,7 +419,7 @@ git commit -m "Draft reply to ..."
 git push
 ```
 
-{owner_name} will review your draft, and if approved, send it from his email account.
+{owner_name} will review your draft, and if approved, send it from {their} email account.
 
 ## Reference
 
@@ -204,6 +427,70 @@ See `AGENTS.md` for the full draft format (CC, In-Reply-To, Account, From),
 conversation format, status flow, and voice guidelines.
 "#,
         name = name,
-        owner_name = owner_name,
+        owner_name = owner.name,
+        their = owner.pronouns.possessive,
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_status() {
+        let current = generate_agents_md("alex", &TemplateOwner::new("Brian"));
+        assert_eq!(template_status(None, &current), TemplateStatus::Missing);
+        assert_eq!(
+            template_status(Some(&stamp("agents", &current)), &current),
+            TemplateStatus::Current
+        );
+
+        // Generated from an older template, untouched since
+        let old = stamp("agents", "# Old template\n")
+            .replace(&format!(" v{} ", TEMPLATE_VERSION), " v1 ");
+        assert_eq!(
+            template_status(Some(&old), &current),
+            TemplateStatus::Outdated { version: 1 }
+        );
+        // Written before markers existed
+        assert_eq!(
+            template_status(Some(&current), &current),
+            TemplateStatus::Outdated { version: 1 }
+        );
+
+        let edited = stamp("agents", &current).replacen("## Workflow", "## Our workflow", 1);
+        assert_eq!(template_status(Some(&edited), &current), TemplateStatus::Modified);
+        assert_eq!(
+            template_status(Some("# Hand-written\n"), &current),
+            TemplateStatus::Modified
+        );
+    }
+
+    #[test]
+    fn test_pronouns() {
+        assert_eq!(Pronouns::parse("").unwrap(), Pronouns::default());
+        assert_eq!(Pronouns::parse("She/Her").unwrap().possessive, "her");
+        assert_eq!(Pronouns::parse("he/they").unwrap().possessive, "his");
+        let xe = Pronouns::parse("xe/xem/xyr").unwrap();
+        assert_eq!((xe.subject.as_str(), xe.possessive.as_str()), ("xe", "xyr"));
+        assert!(Pronouns::parse("zz/top").is_err());
+    }
+
+    #[test]
+    fn test_templates_use_owner_settings() {
+        let mut owner = TemplateOwner::new("Brian");
+        let text = generate_readme_md("alex", &owner);
+        assert!(text.contains("draft replies on their behalf"));
+        assert!(!text.contains(" his "));
+
+        owner.pronouns = Pronouns::parse("he/him").unwrap();
+        assert!(generate_agents_md("alex", &owner).contains("drafting\non his behalf"));
+
+        owner.agents_template =
+            Some("# Postfach von {owner_name} für {title_name} ({their})\n".to_string());
+        assert_eq!(
+            generate_agents_md("alex", &owner),
+            "# Postfach von Brian für Alex (his)\n"
+        );
+    }
+}
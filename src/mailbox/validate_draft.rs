@@ -7,6 +7,8 @@ use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
 use crate::draft;
+use crate::error::CorkyError;
+use crate::output;
 
 static META_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\*\*(.+?)\*\*:\s*(.+)$").unwrap());
 
@@ -25,11 +27,16 @@ pub fn validate_draft(path: &Path) -> Vec<String> {
         Err(e) => return vec![format!("Cannot read {}: {}", path.display(), e)],
     };
 
-    if draft::is_yaml_format(&text) {
-        return validate_yaml_draft(&text);
+    validate_draft_text(&text)
+}
+
+/// Validate draft content that isn't (or isn't yet) a file on disk.
+pub fn validate_draft_text(text: &str) -> Vec<String> {
+    if draft::is_yaml_format(text) {
+        return validate_yaml_draft(text);
     }
 
-    validate_legacy_draft(&text)
+    validate_legacy_draft(text)
 }
 
 /// Validate a YAML frontmatter draft.
@@ -69,6 +76,10 @@ fn validate_yaml_draft(text: &str) -> Vec<String> {
     if meta.to.is_empty() {
         issues.push("Missing required field: to".to_string());
     }
+    issues.extend(address_issues("to", &meta.to));
+    if let Some(cc) = &meta.cc {
+        issues.extend(address_issues("cc", cc));
+    }
 
     // Recommended: status, author
     if meta.author.is_none() {
@@ -99,6 +110,20 @@ fn validate_yaml_draft(text: &str) -> Vec<String> {
     issues
 }
 
+/// One issue per entry in an address field that isn't a valid address or
+/// a `[groups]` name.
+fn address_issues(field: &str, value: &str) -> Vec<String> {
+    let (_, bad) = crate::address::parse_list(value);
+    if bad.is_empty() {
+        return Vec::new();
+    }
+    let groups = crate::config::group::load_groups(None).unwrap_or_default();
+    bad.into_iter()
+        .filter(|b| !groups.contains_key(b))
+        .map(|b| format!("Invalid address in {}: {}", field, b))
+        .collect()
+}
+
 /// Validate a legacy `**Key**: value` format draft.
 fn validate_legacy_draft(text: &str) -> Vec<String> {
     let mut issues = Vec::new();
@@ -122,6 +147,11 @@ fn validate_legacy_draft(text: &str) -> Vec<String> {
             issues.push(format!("Missing required field: **{}**", field));
         }
     }
+    for field in ["To", "CC"] {
+        if let Some(value) = meta.get(field) {
+            issues.extend(address_issues(field, value));
+        }
+    }
 
     // Recommended fields (warn, don't error)
     for field in RECOMMENDED_FIELDS {
@@ -218,7 +248,7 @@ fn resolve_draft_dirs(scope: &super::find_unanswered::Scope) -> Result<Vec<(Stri
 }
 
 /// Collect all .md files under a directory.
-fn collect_draft_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+pub(crate) fn collect_draft_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
     if !dir.is_dir() {
         return Ok(());
     }
@@ -226,6 +256,10 @@ fn collect_draft_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
         let entry = entry?;
         let path = entry.path();
         if path.is_dir() {
+            // drafts/templates/ holds compose templates, not drafts
+            if path.file_name().is_some_and(|n| n == "templates") {
+                continue;
+            }
             collect_draft_files(&path, out)?;
         } else if path.extension().and_then(|e| e.to_str()) == Some("md") {
             out.push(path);
@@ -235,7 +269,7 @@ fn collect_draft_files(dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
 }
 
 /// corky draft validate [ARGS...] — scope-based or file-based validation.
-pub fn run_scoped(args: &[String]) -> Result<()> {
+pub fn run_scoped(args: &[String], quiet: bool) -> Result<()> {
     use super::find_unanswered::Scope;
     use crate::resolve;
 
@@ -247,7 +281,7 @@ pub fn run_scoped(args: &[String]) -> Result<()> {
 
     if as_files {
         let files: Vec<PathBuf> = args.iter().map(PathBuf::from).collect();
-        return run(&files);
+        return run(&files, quiet);
     }
 
     // Otherwise parse as scope
@@ -263,12 +297,12 @@ pub fn run_scoped(args: &[String]) -> Result<()> {
         } else {
             // Treat as a file path
             let files: Vec<PathBuf> = args.iter().map(PathBuf::from).collect();
-            return run(&files);
+            return run(&files, quiet);
         }
     } else {
         // Multiple args that don't look like files — treat as files anyway
         let files: Vec<PathBuf> = args.iter().map(PathBuf::from).collect();
-        return run(&files);
+        return run(&files, quiet);
     };
 
     let dirs = resolve_draft_dirs(&scope)?;
@@ -289,14 +323,58 @@ pub fn run_scoped(args: &[String]) -> Result<()> {
     }
 
     all_files.sort();
-    run(&all_files)
+    run(&all_files, quiet)
 }
 
-/// corky validate-draft FILE [FILE...]
-pub fn run(files: &[PathBuf]) -> Result<()> {
+/// Expand directory arguments into the `.md` files under them, sorted.
+fn expand_paths(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            let mut found = Vec::new();
+            collect_draft_files(path, &mut found)?;
+            found.sort();
+            files.extend(found);
+        } else {
+            files.push(path.clone());
+        }
+    }
+    Ok(files)
+}
+
+/// Per-run tally: each file counts once, under its worst issue.
+#[derive(Debug, Default, PartialEq)]
+struct Summary {
+    ok: usize,
+    warnings: usize,
+    errors: usize,
+}
+
+impl Summary {
+    fn rows(&self) -> Vec<Vec<String>> {
+        vec![
+            vec!["Files".to_string(), (self.ok + self.warnings + self.errors).to_string()],
+            vec![output::ok("OK"), self.ok.to_string()],
+            vec![output::warning("Warnings"), self.warnings.to_string()],
+            vec![output::error("Errors"), self.errors.to_string()],
+        ]
+    }
+}
+
+/// corky validate-draft PATH [PATH...] [--quiet]
+///
+/// Directories are searched recursively (skipping `templates/`). `quiet`
+/// prints only files with issues and drops the summary.
+pub fn run(paths: &[PathBuf], quiet: bool) -> Result<()> {
+    let files = expand_paths(paths)?;
+    if files.is_empty() {
+        println!("No draft files found.");
+        return Ok(());
+    }
     let mut all_ok = true;
+    let mut summary = Summary::default();
 
-    for path in files {
+    for path in &files {
         let issues = validate_draft(path);
         if !issues.is_empty() {
             all_ok = false;
@@ -305,21 +383,34 @@ pub fn run(files: &[PathBuf]) -> Result<()> {
                 .filter(|i| !i.starts_with("Warning:"))
                 .collect();
             let warnings: Vec<_> = issues.iter().filter(|i| i.starts_with("Warning:")).collect();
+            if errors.is_empty() {
+                summary.warnings += 1;
+            } else {
+                summary.errors += 1;
+            }
             println!("{}:", path.display());
             for issue in errors {
-                println!("  ERROR: {}", issue);
+                println!("  {} {}", output::error("ERROR:"), issue);
             }
             for issue in warnings {
-                println!("  {}", issue);
+                println!("  {}", output::warning(issue));
             }
             println!();
         } else {
-            println!("{}: OK", path.display());
+            summary.ok += 1;
+            if !quiet {
+                println!("{}: {}", path.display(), output::ok("OK"));
+            }
         }
     }
 
+    if !quiet && files.len() > 1 {
+        println!();
+        output::print_table(&summary.rows());
+    }
+
     if !all_ok {
-        std::process::exit(1);
+        return Err(CorkyError::Validation("Draft validation failed".to_string()).into());
     }
     Ok(())
 }
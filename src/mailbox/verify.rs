@@ -0,0 +1,303 @@
+//! Structural lint for mailbox repos.
+//!
+//! `corky mailbox verify` checks that a mailbox still has the layout
+//! `mailbox add` creates: required dirs, AGENTS.md/README.md generated from
+//! the current template version, the CLAUDE.md symlink, `.gitignore` entries (shared repos), no
+//! stray large files, and drafts that pass `draft validate`. `mailbox
+//! status` runs the same checks. As in draft validation, issues starting
+//! with `Warning:` don't fail the check.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use super::templates::{
+    generate_agents_md, generate_readme_md, template_status, TemplateOwner, TemplateStatus,
+    GITIGNORE, TEMPLATE_VERSION,
+};
+use super::validate_draft::{collect_draft_files, validate_draft};
+use crate::accounts::load_owner;
+use crate::config::corky_config;
+use crate::error::CorkyError;
+use crate::output;
+use crate::resolve;
+
+/// Files at least this large are reported as stray large files.
+pub const LARGE_FILE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Directories every mailbox must have.
+const REQUIRED_DIRS: &[&str] = &["conversations", "drafts"];
+
+/// Template settings from `[owner]`, if it is configured and usable.
+pub(crate) fn template_owner() -> Option<TemplateOwner> {
+    let owner = TemplateOwner::from_config(&load_owner(None).ok()?).ok()?;
+    Some(owner).filter(|o| !o.name.is_empty())
+}
+
+/// Template status of the mailbox's AGENTS.md and README.md.
+pub fn template_statuses(
+    name: &str,
+    owner: &TemplateOwner,
+    mb_path: &Path,
+) -> [(&'static str, TemplateStatus); 2] {
+    let status = |file: &str, expected: String| {
+        let installed = std::fs::read_to_string(mb_path.join(file)).ok();
+        template_status(installed.as_deref(), &expected)
+    };
+    [
+        ("AGENTS.md", status("AGENTS.md", generate_agents_md(name, owner))),
+        ("README.md", status("README.md", generate_readme_md(name, owner))),
+    ]
+}
+
+/// Whether `mailbox reset` would change the mailbox's generated files.
+pub fn needs_reset(name: &str, owner: &TemplateOwner, mb_path: &Path) -> bool {
+    template_statuses(name, owner, mb_path)
+        .iter()
+        .any(|(_, s)| matches!(s, TemplateStatus::Outdated { .. } | TemplateStatus::Modified))
+}
+
+/// Files under `dir` (skipping `.git`) of at least `min_bytes`.
+fn large_files(dir: &Path, min_bytes: u64, out: &mut Vec<(PathBuf, u64)>) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.file_name().is_some_and(|n| n == ".git") {
+            continue;
+        }
+        let Ok(meta) = std::fs::symlink_metadata(&path) else {
+            continue;
+        };
+        if meta.is_dir() {
+            large_files(&path, min_bytes, out);
+        } else if meta.is_file() && meta.len() >= min_bytes {
+            out.push((path, meta.len()));
+        }
+    }
+}
+
+/// Problems with the mailbox `name` at `mb_path`, one line each. The
+/// template version checks are skipped when `owner` is None.
+pub fn check(name: &str, owner: Option<&TemplateOwner>, mb_path: &Path) -> Vec<String> {
+    let mut issues = Vec::new();
+    if !mb_path.is_dir() {
+        issues.push(format!("Mailbox directory not found: {}", mb_path.display()));
+        return issues;
+    }
+    let rel = |p: &Path| p.strip_prefix(mb_path).unwrap_or(p).display().to_string();
+
+    for dir in REQUIRED_DIRS {
+        if !mb_path.join(dir).is_dir() {
+            issues.push(format!("Missing directory: {}/", dir));
+        }
+    }
+
+    if !mb_path.join("AGENTS.md").is_file() {
+        issues.push("Missing AGENTS.md".to_string());
+    }
+    if !mb_path.join("README.md").is_file() {
+        issues.push("Warning: Missing README.md".to_string());
+    }
+    if let Some(owner) = owner {
+        for (file, status) in template_statuses(name, owner, mb_path) {
+            match status {
+                TemplateStatus::Outdated { version } if version == TEMPLATE_VERSION => {
+                    issues.push(format!(
+                        "Warning: {} doesn't match the [owner] settings (corky mailbox reset {})",
+                        file, name
+                    ))
+                }
+                TemplateStatus::Outdated { version } => issues.push(format!(
+                    "Warning: {} is from template v{} (current v{}; corky mailbox reset {})",
+                    file, version, TEMPLATE_VERSION, name
+                )),
+                TemplateStatus::Modified => issues.push(format!(
+                    "Warning: {} has local changes; reset will keep it (corky mailbox reset --force {} to replace)",
+                    file, name
+                )),
+                TemplateStatus::Missing | TemplateStatus::Current => {}
+            }
+        }
+    }
+    #[cfg(unix)]
+    {
+        let target = std::fs::read_link(mb_path.join("CLAUDE.md")).ok();
+        if target.as_deref() != Some(Path::new("AGENTS.md")) {
+            issues.push("Warning: CLAUDE.md is not a symlink to AGENTS.md".to_string());
+        }
+    }
+
+    // Shared repos only: plain directories are never pushed
+    if mb_path.join(".git").exists() {
+        let gitignore = std::fs::read_to_string(mb_path.join(".gitignore")).unwrap_or_default();
+        for entry in GITIGNORE.lines() {
+            if !gitignore.lines().any(|l| l.trim() == entry) {
+                issues.push(format!("Missing .gitignore entry: {}", entry));
+            }
+        }
+    }
+
+    let mut large = Vec::new();
+    large_files(mb_path, LARGE_FILE_BYTES, &mut large);
+    large.sort();
+    for (path, len) in large {
+        issues.push(format!(
+            "Large file ({:.1} MiB): {}",
+            len as f64 / (1024.0 * 1024.0),
+            rel(&path)
+        ));
+    }
+
+    let mut drafts = Vec::new();
+    let _ = collect_draft_files(&mb_path.join("drafts"), &mut drafts);
+    drafts.sort();
+    for draft in drafts {
+        for issue in validate_draft(&draft) {
+            if !issue.starts_with("Warning:") {
+                issues.push(format!("{}: {}", rel(&draft), issue));
+            }
+        }
+    }
+
+    issues
+}
+
+/// Short form of [`check`] for `mailbox status`.
+pub fn describe(issues: &[String]) -> String {
+    let errors = issues.iter().filter(|i| !i.starts_with("Warning:")).count();
+    let warnings = issues.len() - errors;
+    match (errors, warnings) {
+        (0, 0) => output::ok("structure ok"),
+        (0, w) => output::warning(&format!("{} warning(s)", w)),
+        (e, _) => output::error(&format!("{} problem(s)", e)),
+    }
+}
+
+/// Print one mailbox's issues, errors first, in `draft validate` style.
+pub fn print_issues(name: &str, issues: &[String]) {
+    println!("{}:", name);
+    for issue in issues.iter().filter(|i| !i.starts_with("Warning:")) {
+        println!("  {} {}", output::error("ERROR:"), issue);
+    }
+    for issue in issues.iter().filter(|i| i.starts_with("Warning:")) {
+        println!("  {}", output::warning(issue));
+    }
+}
+
+/// corky mailbox verify [NAME]
+pub fn run(name: Option<&str>) -> Result<()> {
+    let mut names: Vec<String> = corky_config::try_load_config(None)
+        .map(|c| c.mailboxes.keys().cloned().collect())
+        .unwrap_or_default();
+    if names.is_empty() {
+        println!("No mailboxes configured in .corky.toml");
+        return Ok(());
+    }
+    if let Some(n) = name {
+        if !names.iter().any(|m| m == n) {
+            anyhow::bail!("Unknown mailbox: {}", n);
+        }
+        names = vec![n.to_string()];
+    }
+    names.sort();
+
+    let owner = template_owner();
+    let mut failed = false;
+    for n in &names {
+        let issues = check(n, owner.as_ref(), &resolve::mailbox_dir(n));
+        if issues.is_empty() {
+            println!("{}: {}", n, output::ok("OK"));
+            continue;
+        }
+        failed |= issues.iter().any(|i| !i.starts_with("Warning:"));
+        print_issues(n, &issues);
+    }
+    if failed {
+        return Err(CorkyError::Validation("Mailbox verification failed".to_string()).into());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mailbox::templates::{stamp, Pronouns};
+
+    fn owner() -> TemplateOwner {
+        TemplateOwner::new("Brian")
+    }
+
+    fn mailbox(dir: &Path) -> PathBuf {
+        let mb = dir.join("alex");
+        std::fs::create_dir_all(mb.join("conversations")).unwrap();
+        std::fs::create_dir_all(mb.join("drafts")).unwrap();
+        let agents = stamp("agents", &generate_agents_md("alex", &owner()));
+        std::fs::write(mb.join("AGENTS.md"), agents).unwrap();
+        let readme = stamp("readme", &generate_readme_md("alex", &owner()));
+        std::fs::write(mb.join("README.md"), readme).unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("AGENTS.md", mb.join("CLAUDE.md")).unwrap();
+        mb
+    }
+
+    #[test]
+    fn test_fresh_mailbox_is_clean() {
+        let dir = tempfile::tempdir().unwrap();
+        let mb = mailbox(dir.path());
+        assert_eq!(check("alex", Some(&owner()), &mb), Vec::<String>::new());
+        assert!(!needs_reset("alex", &owner(), &mb));
+    }
+
+    #[test]
+    fn test_reports_structure_problems() {
+        let dir = tempfile::tempdir().unwrap();
+        let mb = mailbox(dir.path());
+        std::fs::remove_dir_all(mb.join("conversations")).unwrap();
+        std::fs::write(mb.join("AGENTS.md"), "# Edited\n").unwrap();
+        std::fs::create_dir_all(mb.join(".git")).unwrap();
+        std::fs::write(mb.join(".gitignore"), "AGENTS.local.md\n").unwrap();
+        let big = std::fs::File::create(mb.join("drafts/video.mov")).unwrap();
+        big.set_len(LARGE_FILE_BYTES).unwrap();
+        std::fs::write(mb.join("drafts/bad.md"), "# No metadata\n").unwrap();
+
+        let issues = check("alex", Some(&owner()), &mb);
+        assert!(issues.contains(&"Missing directory: conversations/".to_string()));
+        assert!(issues.iter().any(|i| i.starts_with("Warning: AGENTS.md has local changes")));
+        assert!(issues.contains(&"Missing .gitignore entry: CLAUDE.local.md".to_string()));
+        assert!(!issues.contains(&"Missing .gitignore entry: AGENTS.local.md".to_string()));
+        assert!(issues.contains(&"Large file (5.0 MiB): drafts/video.mov".to_string()));
+        assert!(issues.iter().any(|i| i.starts_with("drafts/bad.md: ")));
+        assert!(describe(&issues).contains("problem(s)"));
+
+        // Without an owner the template check is skipped
+        let issues = check("alex", None, &mb);
+        assert!(!issues.iter().any(|i| i.contains("AGENTS.md has local changes")));
+    }
+
+    #[test]
+    fn test_reports_outdated_templates() {
+        let dir = tempfile::tempdir().unwrap();
+        let mb = mailbox(dir.path());
+        // Written by a corky without template markers
+        std::fs::write(mb.join("README.md"), generate_readme_md("alex", &owner())).unwrap();
+        let issues = check("alex", Some(&owner()), &mb);
+        assert_eq!(
+            issues,
+            vec![format!(
+                "Warning: README.md is from template v1 (current v{}; corky mailbox reset alex)",
+                TEMPLATE_VERSION
+            )]
+        );
+        assert!(needs_reset("alex", &owner(), &mb));
+
+        // Pronouns changed since AGENTS.md was generated
+        let mut he = owner();
+        he.pronouns = Pronouns::parse("he/him").unwrap();
+        let issues = check("alex", Some(&he), &mb);
+        assert!(issues.contains(
+            &"Warning: AGENTS.md doesn't match the [owner] settings (corky mailbox reset alex)"
+                .to_string()
+        ));
+    }
+}
@@ -1,10 +1,34 @@
 use anyhow::Result;
 use clap::Parser;
 
-use corky::cli::{CalCommands, Cli, Commands, ContactCommands, DocCommands, DraftCommands, FilterCommands, LabelCommands, LinkedinCommands, MailboxCommands, ScheduleCommands, SkillCommands, SlackCommands, SyncCommands, TopicCommands, YoutubeCommands};
+use corky::cli::{AuditCommands, CalCommands, Cli, Commands, ContactCommands, DocCommands, DraftCommands, FilterCommands, LabelCommands, LinkedinCommands, MailboxCommands, ScheduleCommands, SkillCommands, SlackCommands, SyncCommands, TemplateCommands, ThreadCommands, TopicCommands, WatchCommands, YoutubeCommands};
+use corky::error::CorkyError;
 
-fn main() -> Result<()> {
+fn main() {
     let cli = Cli::parse();
+    let result = run(cli);
+    corky::profile::print();
+    if let Err(e) = result {
+        eprintln!("Error: {:?}", e);
+        std::process::exit(corky::error::exit_code(&e));
+    }
+}
+
+fn run(cli: Cli) -> Result<()> {
+
+    if cli.no_color {
+        corky::output::disable_color();
+    }
+    if cli.quiet {
+        corky::output::set_quiet();
+    }
+    if cli.yes {
+        corky::output::set_assume_yes();
+    }
+    if cli.restricted {
+        // SAFETY: This runs at the very start of main before any threads are spawned.
+        unsafe { std::env::set_var(corky::util::RESTRICTED_ENV, "1") };
+    }
 
     // Handle --mailbox: resolve named mailbox and set CORKY_DATA
     if let Some(ref mailbox_name) = cli.mailbox {
@@ -13,18 +37,54 @@ fn main() -> Result<()> {
             // SAFETY: This runs at the very start of main before any threads are spawned.
             unsafe { std::env::set_var("CORKY_DATA", p.to_string_lossy().as_ref()) };
         } else {
-            eprintln!("No mailboxes configured. Run 'corky init' first.");
-            std::process::exit(1);
+            return Err(CorkyError::Config(
+                "No mailboxes configured. Run 'corky init' first.".to_string(),
+            )
+            .into());
         }
     }
 
 
+    // --profile on the commands that support it (rejected for the rest below)
+    let profiled = match &cli.command {
+        Commands::Sync {
+            profile,
+            command: None | Some(SyncCommands::Full | SyncCommands::Account { .. }),
+            ..
+        } => *profile,
+        Commands::Mailbox(MailboxCommands::Sync { profile, .. }) => *profile,
+        _ => false,
+    };
+    if profiled {
+        corky::profile::enable();
+    }
+
     // Warn about available upgrades (skip if running the upgrade command itself)
     if !matches!(cli.command, Commands::Upgrade) {
         corky::upgrade::warn_if_outdated();
     }
 
     match cli.command {
+        Commands::Init {
+            path,
+            user,
+            provider,
+            password_cmd,
+            labels,
+            force,
+            add_account: Some(account),
+            list_folders,
+            ..
+        } => corky::init::run_add_account(
+            &account,
+            &user,
+            &path,
+            &provider,
+            &password_cmd,
+            &labels,
+            list_folders,
+            force,
+        ),
         Commands::Init {
             path,
             user,
@@ -36,6 +96,10 @@ fn main() -> Result<()> {
             sync,
             mailbox_name,
             force,
+            with_skills,
+            import,
+            no_detect,
+            ..
         } => corky::init::run(
             &user,
             &path,
@@ -47,13 +111,75 @@ fn main() -> Result<()> {
             sync,
             &mailbox_name,
             force,
+            &with_skills,
+            if no_detect { Some(false) } else if import { Some(true) } else { None },
         ),
-        Commands::Sync { command } => match command {
-            None => corky::sync::run(false, None),
-            Some(SyncCommands::Full) => corky::sync::run(true, None),
-            Some(SyncCommands::Account { name }) => corky::sync::run(false, Some(&name)),
+        Commands::Sync {
+            json,
+            headers_only,
+            fail_fast,
+            interactive,
+            then_push,
+            profile,
+            command,
+        } => match command {
+            None => corky::sync::run(
+                false,
+                None,
+                json,
+                headers_only,
+                fail_fast,
+                interactive,
+                then_push.as_deref(),
+            ),
+            Some(SyncCommands::Full) => corky::sync::run(
+                true,
+                None,
+                json,
+                headers_only,
+                fail_fast,
+                interactive,
+                then_push.as_deref(),
+            ),
+            Some(SyncCommands::Account { name }) => corky::sync::run(
+                false,
+                Some(&name),
+                json,
+                headers_only,
+                fail_fast,
+                interactive,
+                then_push.as_deref(),
+            ),
+            Some(_) if json => Err(anyhow::anyhow!(
+                "--json is only supported by `sync`, `sync full` and `sync account`"
+            )),
+            Some(_) if headers_only => Err(anyhow::anyhow!(
+                "--headers-only is only supported by `sync`, `sync full` and `sync account`"
+            )),
+            Some(_) if fail_fast => Err(anyhow::anyhow!(
+                "--fail-fast is only supported by `sync`, `sync full` and `sync account`"
+            )),
+            Some(_) if interactive => Err(anyhow::anyhow!(
+                "--interactive is only supported by `sync`, `sync full` and `sync account`"
+            )),
+            Some(_) if then_push.is_some() => Err(anyhow::anyhow!(
+                "--then-push is only supported by `sync`, `sync full` and `sync account`"
+            )),
+            Some(_) if profile => Err(anyhow::anyhow!(
+                "--profile is only supported by `sync`, `sync full` and `sync account`"
+            )),
             Some(SyncCommands::Routes) => corky::sync::routes::run(),
-            Some(SyncCommands::Mailbox { name }) => corky::mailbox::sync::run(name.as_deref()),
+            Some(SyncCommands::Repair) => corky::sync::repair(),
+            Some(SyncCommands::Mailbox {
+                name,
+                pull_only,
+                push_only,
+                allow_secrets,
+            }) => corky::mailbox::sync::run(
+                name.as_deref(),
+                corky::mailbox::sync::SyncMode::from_flags(pull_only, push_only),
+                allow_secrets,
+            ),
             Some(SyncCommands::TelegramImport { path, label, account }) => {
                 let out_dir = corky::resolve::conversations_dir();
                 corky::sync::telegram_import::run(&path, &label, &out_dir, &account)
@@ -63,9 +189,40 @@ fn main() -> Result<()> {
                 corky::sync::sms_import::run(&path, &label, &out_dir, &account)
             }
         },
-        Commands::SyncAuth => corky::sync::auth::run(),
-        Commands::ListFolders { account } => corky::sync::folders::run(account.as_deref()),
-        Commands::PushDraft { file, send } => corky::draft::run(&file, send),
+        Commands::SyncAuth {
+            account,
+            device,
+            browser,
+        } => {
+            use corky::sync::auth::Flow;
+            let flow = match (device, browser) {
+                (true, _) => Some(Flow::Device),
+                (_, true) => Some(Flow::Browser),
+                _ => None,
+            };
+            corky::sync::auth::run(account.as_deref(), flow)
+        }
+        #[cfg(feature = "net")]
+        Commands::Hydrate { slug, label } => {
+            corky::sync::hydrate::run(slug.as_deref(), label.as_deref())
+        }
+        #[cfg(not(feature = "net"))]
+        Commands::Hydrate { .. } => Err(corky::util::missing_feature("net")),
+        #[cfg(feature = "net")]
+        Commands::ListFolders {
+            account,
+            json,
+            add,
+            select,
+        } => corky::sync::folders::run(account.as_deref(), json, add, &select),
+        #[cfg(not(feature = "net"))]
+        Commands::ListFolders { .. } => Err(corky::util::missing_feature("net")),
+        Commands::PushDraft {
+            file,
+            send,
+            account,
+            from,
+        } => corky::draft::run(&file, send, account.as_deref(), from.as_deref()),
         Commands::AddLabel { label, account } => corky::accounts::add_label_cmd(&label, &account),
         Commands::Contact(cmd) => match cmd {
             ContactCommands::Add { name, emails, from } => {
@@ -80,6 +237,10 @@ fn main() -> Result<()> {
             }
             ContactCommands::Info { name } => corky::contact::info::run(&name),
             ContactCommands::Sync => corky::contact::sync::run(),
+            ContactCommands::Threads { name, write, link } => {
+                corky::contact::threads::run(&name, write, link)
+            }
+            ContactCommands::Reset { name } => corky::contact::reset::run(name.as_deref()),
         },
         Commands::ContactAdd {
             name,
@@ -87,21 +248,92 @@ fn main() -> Result<()> {
             labels: _,
             account: _,
         } => corky::contact::add::run(&name, &emails),
-        Commands::Watch { interval } => corky::watch::run(interval),
+        Commands::Watch {
+            command: Some(WatchCommands::Status { errors }),
+            ..
+        } => corky::journal::watch_status(errors),
+        #[cfg(feature = "watch")]
+        Commands::Watch { once: true, .. } => {
+            if corky::watch::run_once()? {
+                std::process::exit(corky::error::EXIT_NEW_MAIL);
+            }
+            Ok(())
+        }
+        #[cfg(feature = "watch")]
+        Commands::Watch { interval, .. } => corky::watch::run(interval),
+        #[cfg(not(feature = "watch"))]
+        Commands::Watch { .. } => Err(corky::util::missing_feature("watch")),
+        Commands::Status { errors } => corky::journal::status(errors),
+        Commands::Audit(AuditCommands::Tail { n, json }) => corky::audit::tail(n, json),
         Commands::InstallSkill { name } => corky::skill::run(&name),
         Commands::Skill(cmd) => match cmd {
-            SkillCommands::Install => corky::skill::install(),
+            SkillCommands::Install { name, target } => corky::skill::install_target(&name, &target),
             SkillCommands::Check => corky::skill::check(),
+            SkillCommands::List => corky::skill::list(),
+            SkillCommands::Update { name, force } => corky::skill::update(name.as_deref(), force),
+            SkillCommands::Uninstall { name } => corky::skill::uninstall(&name),
         },
-        Commands::AuditDocs => corky::audit_docs::run(),
-        Commands::Help { filter } => corky::help::run(filter.as_deref()),
-        Commands::Unanswered { scope, from_name } => {
+        Commands::AuditDocs { format, warn_only } => {
+            corky::audit_docs::run(&format, warn_only)
+        }
+        Commands::Help { filter, man, markdown } => {
+            if let Some(dir) = &man {
+                corky::help::write_man(dir)?;
+            }
+            if let Some(dir) = &markdown {
+                corky::help::write_markdown(dir)?;
+            }
+            if man.is_none() && markdown.is_none() {
+                corky::help::run(filter.as_deref())?;
+            }
+            Ok(())
+        }
+        Commands::Unanswered { scope, from_name, include_newsletters, by_contact } => {
+            let from = resolve_from_name(from_name)?;
+            let scope = corky::mailbox::find_unanswered::Scope::from_arg(scope.as_deref());
+            corky::mailbox::find_unanswered::run(scope, &from, include_newsletters, by_contact)
+        }
+        Commands::Newsletters { scope } => {
+            let scope = corky::mailbox::find_unanswered::Scope::from_arg(scope.as_deref());
+            corky::mailbox::newsletters::run(scope)
+        }
+        Commands::Unsubscribe { slug } => corky::mailbox::newsletters::run_unsubscribe(&slug),
+        Commands::Block { addr, delete } => corky::sync::blocklist::run(&addr, delete),
+        Commands::Publish { scope, output, label } => {
+            let scope = corky::mailbox::find_unanswered::Scope::from_arg(scope.as_deref());
+            corky::publish::run(scope, &label, &output)
+        }
+        Commands::ExportIndex { scope, format, output, from_name } => {
             let from = resolve_from_name(from_name)?;
             let scope = corky::mailbox::find_unanswered::Scope::from_arg(scope.as_deref());
-            corky::mailbox::find_unanswered::run(scope, &from)
+            corky::mailbox::export_index::run(scope, &format, output.as_deref(), &from)
+        }
+        Commands::ValidateDraft { files } => {
+            corky::mailbox::validate_draft::run(&files, corky::output::quiet())
         }
-        Commands::ValidateDraft { files } => corky::mailbox::validate_draft::run(&files),
         Commands::Draft(cmd) => run_draft_command(cmd),
+        Commands::Compose {
+            to,
+            subject,
+            contact,
+            template,
+            reply,
+            account,
+            mailbox,
+            no_edit,
+        } => corky::draft::compose::run(
+            to.as_deref(),
+            subject.as_deref(),
+            contact.as_deref(),
+            template.as_deref(),
+            reply.as_deref(),
+            account.as_deref(),
+            mailbox.as_deref(),
+            no_edit,
+        ),
+        Commands::Template(cmd) => match cmd {
+            TemplateCommands::List => corky::draft::template::run_list(),
+        },
         Commands::Mailbox(cmd) => match cmd {
             MailboxCommands::List => corky::mailbox::list::run(),
             MailboxCommands::Add {
@@ -125,8 +357,27 @@ fn main() -> Result<()> {
                 &account,
                 &org,
             ),
-            MailboxCommands::Sync { name } => corky::mailbox::sync::run(name.as_deref()),
+            MailboxCommands::Sync {
+                name,
+                pull_only,
+                push_only,
+                allow_secrets,
+                ..
+            } => corky::mailbox::sync::run(
+                name.as_deref(),
+                corky::mailbox::sync::SyncMode::from_flags(pull_only, push_only),
+                allow_secrets,
+            ),
             MailboxCommands::Status => corky::mailbox::sync::status(),
+            MailboxCommands::Verify { name } => corky::mailbox::verify::run(name.as_deref()),
+            MailboxCommands::Invites { name } => corky::mailbox::invites::run(name.as_deref()),
+            MailboxCommands::InstallHooks { name, force } => {
+                corky::mailbox::hooks::run_install(name.as_deref(), force)
+            }
+            MailboxCommands::PrePush => corky::mailbox::hooks::run_pre_push(),
+            MailboxCommands::MergeThread { base, ours, theirs } => {
+                corky::mailbox::merge::run_merge_thread(&base, &ours, &theirs)
+            }
             MailboxCommands::Remove { name, delete_repo } => {
                 corky::mailbox::remove::run(&name, delete_repo)
             }
@@ -135,14 +386,24 @@ fn main() -> Result<()> {
                 new_name,
                 rename_repo,
             } => corky::mailbox::rename::run(&old_name, &new_name, rename_repo),
-            MailboxCommands::Reset { name, no_sync } => {
-                corky::mailbox::reset::run(name.as_deref(), no_sync)
+            MailboxCommands::Reset { name, no_sync, force } => {
+                corky::mailbox::reset::run(name.as_deref(), no_sync, force)
             }
-            MailboxCommands::Unanswered { scope, from_name } => {
+            MailboxCommands::Unanswered {
+                scope,
+                from_name,
+                include_newsletters,
+                by_contact,
+            } => {
                 let from = resolve_from_name(from_name)?;
                 let scope =
                     corky::mailbox::find_unanswered::Scope::from_arg(scope.as_deref());
-                corky::mailbox::find_unanswered::run(scope, &from)
+                corky::mailbox::find_unanswered::run(
+                    scope,
+                    &from,
+                    include_newsletters,
+                    by_contact,
+                )
             }
             MailboxCommands::Draft(cmd) => run_draft_command(cmd),
         },
@@ -213,10 +474,25 @@ fn main() -> Result<()> {
                 corky::sync::slack_import::run(&path, &label, &out_dir, &account)
             }
         },
+        Commands::Thread(cmd) => match cmd {
+            ThreadCommands::List { scope, link } => {
+                let scope = corky::mailbox::find_unanswered::Scope::from_arg(scope.as_deref());
+                corky::thread::list::run(scope, link.as_deref())
+            }
+            ThreadCommands::OpenWeb { slug, print } => corky::thread::open_web::run(&slug, print),
+            ThreadCommands::Rename { slug, subject } => {
+                corky::thread::rename::run(&slug, &subject)
+            }
+            ThreadCommands::Pin { slug } => corky::thread::pin::run(&slug, true),
+            ThreadCommands::Unpin { slug } => corky::thread::pin::run(&slug, false),
+        },
         Commands::Label(cmd) => match cmd {
+            #[cfg(feature = "net")]
             LabelCommands::Clear { label, account, search, dry_run } => {
                 corky::label::clear::run(&label, account.as_deref(), search.as_deref(), dry_run)
             }
+            #[cfg(not(feature = "net"))]
+            LabelCommands::Clear { .. } => Err(corky::util::missing_feature("net")),
         },
         Commands::Cal(cmd) => match cmd {
             CalCommands::Auth { account } => {
@@ -265,6 +541,29 @@ fn main() -> Result<()> {
         Commands::Transcribe { file, model, language, output, speakers, diarize } => {
             corky::transcribe::run(&file, model.as_deref(), language.as_deref(), output.as_deref(), &speakers, diarize)
         }
+        Commands::Migrate {
+            scope,
+            check,
+            dry_run,
+            backup,
+        } => corky::migrate::run(scope.as_deref(), check, dry_run, backup),
+        Commands::Gc { scope, dry_run } => corky::gc::run(scope.as_deref(), dry_run),
+        Commands::Prune { dry_run } => corky::prune::run(dry_run),
+        Commands::Digest { dry_run } => corky::digest::run(dry_run),
+        #[cfg(feature = "sqlite")]
+        Commands::Search { query, scope, label } => {
+            let scope = corky::mailbox::find_unanswered::Scope::from_arg(scope.as_deref());
+            corky::search::run(&query, scope, label.as_deref())
+        }
+        #[cfg(not(feature = "sqlite"))]
+        Commands::Search { .. } => Err(corky::util::missing_feature("sqlite")),
+        #[cfg(feature = "sqlite")]
+        Commands::Stats { scope, top } => {
+            let scope = corky::mailbox::find_unanswered::Scope::from_arg(scope.as_deref());
+            corky::search::stats(scope, top)
+        }
+        #[cfg(not(feature = "sqlite"))]
+        Commands::Stats { .. } => Err(corky::util::missing_feature("sqlite")),
         Commands::Upgrade => corky::upgrade::run(),
     }
 }
@@ -280,20 +579,33 @@ fn run_draft_command(cmd: DraftCommands) -> anyhow::Result<()> {
             in_reply_to,
             mailbox,
             attachments,
-        } => corky::draft::new::run(
-            &subject,
-            &to,
-            cc.as_deref(),
-            account.as_deref(),
-            from.as_deref(),
-            in_reply_to.as_deref(),
-            mailbox.as_deref(),
-            &attachments,
-        ),
+            reply,
+            reply_all,
+        } => {
+            let scaffold = match reply.as_deref() {
+                Some(slug) => corky::draft::new::reply_scaffold(slug, reply_all)?,
+                None => Default::default(),
+            };
+            corky::draft::new::run(
+                &subject.unwrap_or(scaffold.subject),
+                &to.unwrap_or(scaffold.to),
+                cc.as_deref().or(scaffold.cc.as_deref()),
+                account.as_deref(),
+                from.as_deref(),
+                in_reply_to.as_deref().or(scaffold.in_reply_to.as_deref()),
+                mailbox.as_deref(),
+                &attachments,
+            )
+        }
         DraftCommands::Validate { args } => {
-            corky::mailbox::validate_draft::run_scoped(&args)
+            corky::mailbox::validate_draft::run_scoped(&args, corky::output::quiet())
         }
-        DraftCommands::Push { file, send } => corky::draft::run(&file, send),
+        DraftCommands::Push {
+            file,
+            send,
+            account,
+            from,
+        } => corky::draft::run(&file, send, account.as_deref(), from.as_deref()),
         DraftCommands::Migrate { dry_run } => corky::draft::migrate::run(dry_run),
     }
 }
@@ -310,8 +622,15 @@ fn resolve_from_name(from_name: Option<String>) -> anyhow::Result<String> {
             }
         }
     }
+    if let Ok(accounts) = corky::accounts::load_accounts(None) {
+        if let Ok((_, acct)) = corky::accounts::get_default_account(&accounts) {
+            if !acct.display_name.trim().is_empty() {
+                return Ok(acct.display_name.trim().to_string());
+            }
+        }
+    }
     anyhow::bail!(
         "No --from name provided and no [owner] name in .corky.toml.\n\
-         Use --from NAME or set name in [owner] section of .corky.toml."
+         Use --from NAME, or set name in [owner] or display_name on an account in .corky.toml."
     )
 }
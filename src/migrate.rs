@@ -0,0 +1,371 @@
+//! `corky migrate` — upgrade conversation markdown written by older versions.
+//!
+//! Each file's format version is detected from its header, then the
+//! migrations from that version up to [`CURRENT_VERSION`] run in order.
+//! Files already current are never rewritten, so the command is idempotent.
+//! `--dry-run` prints the plan; `--backup` snapshots the data dir first and
+//! restores it if any step fails.
+
+use anyhow::{bail, Context, Result};
+use chrono::Local;
+use std::path::{Path, PathBuf};
+
+use crate::config::corky_config::{self, SyncConfig};
+use crate::error::CorkyError;
+use crate::mailbox::find_unanswered::{Scope, collect_md_files, resolve_dirs};
+use crate::resolve;
+use crate::sync::imap_sync::{collect_participants, tag_newsletter};
+use crate::sync::markdown::{is_part_file, read_thread, thread_files, write_thread};
+use crate::sync::types::Thread;
+use crate::util::thread_key_from_subject;
+
+/// Conversation format written by this version of corky.
+pub const CURRENT_VERSION: u32 = 3;
+
+/// Format version of a parsed conversation:
+/// 1 = no `**Thread ID**`; 2 = derived `**Participants**`/`**Tags**` missing; 3 = current.
+pub fn detect_version(thread: &Thread) -> u32 {
+    if thread.id.is_empty() {
+        return 1;
+    }
+    let participants_stale =
+        thread.participants.is_empty() && !collect_participants(&thread.messages).is_empty();
+    let newsletter_untagged = thread
+        .messages
+        .iter()
+        .any(|m| !m.list_unsubscribe.is_empty())
+        && !thread.tags.iter().any(|t| t == "newsletter");
+    if participants_stale || newsletter_untagged {
+        return 2;
+    }
+    CURRENT_VERSION
+}
+
+/// v1 → v2: key the thread by its normalized subject, as sync does.
+fn add_thread_id(thread: &mut Thread) {
+    thread.id = thread_key_from_subject(&thread.subject);
+}
+
+/// v2 → v3: fill derived header fields.
+fn derive_header_fields(thread: &mut Thread) {
+    thread.participants = collect_participants(&thread.messages);
+    tag_newsletter(thread);
+}
+
+/// Run every migration from the thread's version up to current.
+/// Returns the version it started at.
+pub fn migrate_thread(thread: &mut Thread) -> u32 {
+    let from = detect_version(thread);
+    if from < 2 {
+        add_thread_id(thread);
+    }
+    if from < 3 {
+        derive_header_fields(thread);
+    }
+    from
+}
+
+/// Human-readable header changes the migrations would make to `thread`.
+pub fn describe_changes(thread: &Thread) -> Vec<String> {
+    let mut migrated = thread.clone();
+    migrate_thread(&mut migrated);
+    let mut changes = Vec::new();
+    if migrated.id != thread.id {
+        changes.push(format!("set Thread ID: {}", migrated.id));
+    }
+    if migrated.participants != thread.participants {
+        changes.push(format!(
+            "set Participants: {}",
+            migrated.participants.join(", ")
+        ));
+    }
+    for tag in migrated.tags.iter().filter(|t| !thread.tags.contains(t)) {
+        changes.push(format!("add tag: {}", tag));
+    }
+    changes
+}
+
+/// A conversation file that needs migrating.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlannedFile {
+    pub path: PathBuf,
+    pub from: u32,
+    pub changes: Vec<String>,
+}
+
+/// Files under `dirs` whose format is older than current. Unparseable files are skipped.
+pub fn plan(dirs: &[PathBuf]) -> Result<Vec<PlannedFile>> {
+    let mut files = Vec::new();
+    for dir in dirs {
+        collect_md_files(dir, &mut files)?;
+    }
+    files.sort();
+    let mut planned = Vec::new();
+    for path in files {
+        if is_part_file(&path) {
+            continue;
+        }
+        let Some(thread) = read_thread(&path)? else {
+            continue;
+        };
+        let from = detect_version(&thread);
+        if from < CURRENT_VERSION {
+            let changes = describe_changes(&thread);
+            planned.push(PlannedFile {
+                path,
+                from,
+                changes,
+            });
+        }
+    }
+    Ok(planned)
+}
+
+/// Copy `path` into `backup_root`, mirroring its location under `data_dir`.
+fn backup_file(path: &Path, data_dir: &Path, backup_root: &Path) -> Result<()> {
+    let rel = path.strip_prefix(data_dir).unwrap_or(path);
+    let dest = backup_root.join(rel.to_string_lossy().trim_start_matches('/'));
+    if let Some(parent) = dest.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::copy(path, dest)?;
+    Ok(())
+}
+
+/// Migrate planned files in place, backing each up first.
+pub fn apply(planned: &[PlannedFile], data_dir: &Path, backup_root: &Path) -> Result<()> {
+    let sync_config = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    for file in planned {
+        migrate_file(file, data_dir, backup_root, &sync_config)
+            .with_context(|| format!("Failed to migrate {}", file.path.display()))?;
+    }
+    Ok(())
+}
+
+fn migrate_file(
+    file: &PlannedFile,
+    data_dir: &Path,
+    backup_root: &Path,
+    sync_config: &SyncConfig,
+) -> Result<()> {
+    let Some(mut thread) = read_thread(&file.path)? else {
+        return Ok(());
+    };
+    migrate_thread(&mut thread);
+    for path in thread_files(&file.path) {
+        backup_file(&path, data_dir, backup_root)?;
+    }
+    write_thread(&file.path, &thread, sync_config)?;
+    Ok(())
+}
+
+/// Entries never copied into (or restored from) a snapshot.
+const SNAPSHOT_SKIP: &[&str] = &[".corky-backup", ".git"];
+
+/// Copy the tree at `src` into `dest`, skipping backups and git metadata.
+/// Returns the number of files copied.
+pub fn snapshot(src: &Path, dest: &Path) -> Result<usize> {
+    std::fs::create_dir_all(dest)?;
+    let mut count = 0;
+    for entry in std::fs::read_dir(src)? {
+        let entry = entry?;
+        let name = entry.file_name();
+        if SNAPSHOT_SKIP.iter().any(|s| name == *s) {
+            continue;
+        }
+        let path = entry.path();
+        if path.is_dir() {
+            count += snapshot(&path, &dest.join(&name))?;
+        } else {
+            std::fs::copy(&path, dest.join(&name))?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+/// Copy every file in `snapshot_dir` back over `data_dir`.
+pub fn restore(snapshot_dir: &Path, data_dir: &Path) -> Result<usize> {
+    snapshot(snapshot_dir, data_dir)
+}
+
+/// Print what `corky migrate` would do, without writing anything.
+fn print_plan(planned: &[PlannedFile], backup: bool) {
+    for file in planned {
+        println!(
+            "  v{} -> v{}  {}",
+            file.from,
+            CURRENT_VERSION,
+            file.path.display()
+        );
+        for change in &file.changes {
+            println!("      {}", change);
+        }
+    }
+    println!();
+    if backup {
+        println!("  snapshot data dir to .corky-backup/snapshot-<timestamp>/");
+    }
+    println!(
+        "  save originals to .corky-backup/migrate-<timestamp>/ and rewrite {} file(s)",
+        planned.len()
+    );
+}
+
+/// corky migrate [SCOPE] [--check] [--dry-run] [--backup]
+pub fn run(scope: Option<&str>, check: bool, dry_run: bool, backup: bool) -> Result<()> {
+    let dirs: Vec<PathBuf> = resolve_dirs(&Scope::from_arg(scope))?
+        .into_iter()
+        .map(|(_, dir)| dir)
+        .collect();
+    let planned = plan(&dirs)?;
+    if planned.is_empty() {
+        println!("All conversations are at format v{}.", CURRENT_VERSION);
+        return Ok(());
+    }
+
+    if dry_run {
+        print_plan(&planned, backup);
+        println!();
+        println!("Dry run: {} conversation(s) would migrate", planned.len());
+        return Ok(());
+    }
+
+    for file in &planned {
+        println!(
+            "  v{} -> v{}  {}",
+            file.from,
+            CURRENT_VERSION,
+            file.path.display()
+        );
+    }
+    if check {
+        return Err(CorkyError::Validation(format!(
+            "{} conversation(s) need migration. Run: corky migrate",
+            planned.len()
+        ))
+        .into());
+    }
+
+    let data_dir = resolve::data_dir();
+    let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+    let backups = data_dir.join(".corky-backup");
+    let backup_root = backups.join(format!("migrate-{}", stamp));
+
+    let snapshot_dir = backups.join(format!("snapshot-{}", stamp));
+    if backup {
+        let count = snapshot(&data_dir, &snapshot_dir)?;
+        println!(
+            "Snapshot of {} file(s) saved to {}",
+            count,
+            snapshot_dir.display()
+        );
+    }
+
+    if let Err(e) = apply(&planned, &data_dir, &backup_root) {
+        if !backup {
+            return Err(e);
+        }
+        let restored = restore(&snapshot_dir, &data_dir)
+            .context("Rollback failed; restore manually from the snapshot")?;
+        bail!(
+            "{:#}\nRolled back {} file(s) from {}",
+            e,
+            restored,
+            snapshot_dir.display()
+        );
+    }
+    println!(
+        "Migrated {} conversation(s). Originals saved to {}",
+        planned.len(),
+        backup_root.display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::markdown::parse_thread_markdown;
+
+    const V1: &str = "# Lunch plans\n\n\
+**Labels**: inbox\n\
+**Accounts**: personal\n\
+**Last updated**: Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+---\n\n\
+## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+**List-Unsubscribe**: <mailto:off@example.com>\n\n\
+Noon?\n";
+
+    #[test]
+    fn test_detect_and_migrate_v1() {
+        let mut thread = parse_thread_markdown(V1).unwrap();
+        assert_eq!(detect_version(&thread), 1);
+        assert_eq!(migrate_thread(&mut thread), 1);
+        assert_eq!(thread.id, "lunch plans");
+        assert_eq!(thread.participants, vec!["Alice <alice@example.com>"]);
+        assert_eq!(thread.tags, vec!["newsletter"]);
+        assert_eq!(detect_version(&thread), CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_apply_backs_up_and_is_idempotent() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let convos = dir.path().join("conversations");
+        std::fs::create_dir_all(&convos).unwrap();
+        let file = convos.join("lunch-plans.md");
+        std::fs::write(&file, V1).unwrap();
+
+        let planned = plan(std::slice::from_ref(&convos)).unwrap();
+        assert_eq!(planned.len(), 1);
+        assert_eq!(planned[0].from, 1);
+
+        let backup = dir.path().join("backup");
+        apply(&planned, dir.path(), &backup).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(backup.join("conversations/lunch-plans.md")).unwrap(),
+            V1
+        );
+        let migrated = std::fs::read_to_string(&file).unwrap();
+        assert!(migrated.contains("**Thread ID**: lunch plans"));
+        assert!(migrated.contains("**Tags**: newsletter"));
+
+        assert!(plan(&[convos]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_describe_changes() {
+        let thread = parse_thread_markdown(V1).unwrap();
+        assert_eq!(
+            describe_changes(&thread),
+            vec![
+                "set Thread ID: lunch plans",
+                "set Participants: Alice <alice@example.com>",
+                "add tag: newsletter",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_snapshot_and_restore() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let data = dir.path().join("data");
+        std::fs::create_dir_all(data.join("conversations")).unwrap();
+        std::fs::create_dir_all(data.join(".git")).unwrap();
+        std::fs::write(data.join("conversations/a.md"), "original").unwrap();
+        std::fs::write(data.join(".git/HEAD"), "ref").unwrap();
+
+        let snap = data.join(".corky-backup/snapshot-1");
+        assert_eq!(snapshot(&data, &snap).unwrap(), 1);
+        assert!(!snap.join(".git").exists());
+
+        std::fs::write(data.join("conversations/a.md"), "half-migrated").unwrap();
+        assert_eq!(restore(&snap, &data).unwrap(), 1);
+        assert_eq!(
+            std::fs::read_to_string(data.join("conversations/a.md")).unwrap(),
+            "original"
+        );
+    }
+}
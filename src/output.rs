@@ -0,0 +1,203 @@
+//! Terminal output helpers: status colors, column-aligned tables, and
+//! suppressible progress lines.
+//!
+//! Color is used only when stdout is a terminal, `NO_COLOR` is unset or empty,
+//! and `--no-color` was not passed.
+
+use std::io::{IsTerminal, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static COLOR_DISABLED: AtomicBool = AtomicBool::new(false);
+static QUIET: AtomicBool = AtomicBool::new(false);
+static ASSUME_YES: AtomicBool = AtomicBool::new(false);
+
+/// Turn color off for the rest of the process (`--no-color`).
+pub fn disable_color() {
+    COLOR_DISABLED.store(true, Ordering::Relaxed);
+}
+
+/// Drop progress output for the rest of the process, e.g. when stdout
+/// carries JSON. Errors still go to stderr.
+pub fn set_quiet() {
+    QUIET.store(true, Ordering::Relaxed);
+}
+
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Answer yes to every confirmation for the rest of the process (`--yes`).
+pub fn set_assume_yes() {
+    ASSUME_YES.store(true, Ordering::Relaxed);
+}
+
+/// Ask `question` with `[y/N]` before something that can't be undone.
+/// `--yes` answers yes. Without a terminal on stdin, or with
+/// `interactive = false` in .corky.toml, nothing is asked and the answer
+/// is no.
+pub fn confirm(question: &str) -> std::io::Result<bool> {
+    if ASSUME_YES.load(Ordering::Relaxed) {
+        return Ok(true);
+    }
+    let interactive = crate::config::corky_config::try_load_config(None)
+        .and_then(|c| c.interactive)
+        .unwrap_or(true);
+    if !interactive || !std::io::stdin().is_terminal() {
+        eprintln!("{} Not confirmed: pass --yes to proceed.", question);
+        return Ok(false);
+    }
+    print!("{} [y/N] ", question);
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    Ok(matches!(input.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// `println!` for progress lines, silenced by [`set_quiet`].
+#[macro_export]
+macro_rules! progress {
+    ($($arg:tt)*) => {
+        if !$crate::output::quiet() {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// Whether styled output should be emitted.
+pub fn color_enabled() -> bool {
+    !COLOR_DISABLED.load(Ordering::Relaxed)
+        && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        && std::io::stdout().is_terminal()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Style {
+    Error,
+    Warning,
+    Ok,
+    Bold,
+    Dim,
+}
+
+impl Style {
+    fn code(self) -> &'static str {
+        match self {
+            Style::Error => "31",
+            Style::Warning => "33",
+            Style::Ok => "32",
+            Style::Bold => "1",
+            Style::Dim => "2",
+        }
+    }
+}
+
+/// Wrap `text` in the ANSI codes for `style` (plain text when color is off).
+pub fn paint(text: &str, style: Style) -> String {
+    if color_enabled() {
+        styled(text, style)
+    } else {
+        text.to_string()
+    }
+}
+
+fn styled(text: &str, style: Style) -> String {
+    format!("\x1b[{}m{}\x1b[0m", style.code(), text)
+}
+
+pub fn error(text: &str) -> String {
+    paint(text, Style::Error)
+}
+
+pub fn warning(text: &str) -> String {
+    paint(text, Style::Warning)
+}
+
+pub fn ok(text: &str) -> String {
+    paint(text, Style::Ok)
+}
+
+/// Display width of `text`, ignoring ANSI escape sequences.
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+    for c in text.chars() {
+        if in_escape {
+            in_escape = !c.is_ascii_alphabetic();
+        } else if c == '\x1b' {
+            in_escape = true;
+        } else {
+            width += 1;
+        }
+    }
+    width
+}
+
+/// Render rows as an indented table, padding every column but the last
+/// to its widest cell.
+pub fn table(rows: &[Vec<String>]) -> String {
+    let cols = rows.iter().map(Vec::len).max().unwrap_or(0);
+    let widths: Vec<usize> = (0..cols)
+        .map(|i| {
+            rows.iter()
+                .filter_map(|r| r.get(i))
+                .map(|c| visible_width(c))
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+
+    let mut out = String::new();
+    for row in rows {
+        let mut line = String::from("  ");
+        for (i, cell) in row.iter().enumerate() {
+            line.push_str(cell);
+            if i + 1 < row.len() {
+                let pad = widths[i] - visible_width(cell);
+                line.push_str(&" ".repeat(pad + 2));
+            }
+        }
+        out.push_str(line.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+pub fn print_table(rows: &[Vec<String>]) {
+    print!("{}", table(rows));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_visible_width_ignores_ansi() {
+        assert_eq!(visible_width("ok"), 2);
+        assert_eq!(visible_width(&styled("ok", Style::Ok)), 2);
+        assert_eq!(visible_width("caf\u{e9}"), 4);
+    }
+
+    #[test]
+    fn test_table_aligns_styled_cells() {
+        let rows = vec![
+            vec![styled("a", Style::Bold), "first".to_string()],
+            vec!["long-name".to_string(), styled("second", Style::Error)],
+        ];
+        let out = table(&rows);
+        let lines: Vec<&str> = out.lines().collect();
+        assert_eq!(
+            lines[0],
+            format!("  {}          first", styled("a", Style::Bold))
+        );
+        assert_eq!(
+            lines[1],
+            format!("  long-name  {}", styled("second", Style::Error))
+        );
+    }
+
+    #[test]
+    fn test_paint_plain_when_disabled() {
+        disable_color();
+        assert_eq!(paint("x", Style::Error), "x");
+    }
+}
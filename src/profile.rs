@@ -0,0 +1,232 @@
+//! `--profile` for `sync` and `mailbox sync`: where the time went.
+//!
+//! Instrumented steps add their time under a scope (`ACCOUNT`,
+//! `ACCOUNT/LABEL`, a mailbox, or `run`) and a phase. Each phase is spent
+//! on the network (connect, git pull/push/fetch/clone), waiting on the
+//! IMAP server (select, search, fetch), or on this machine (parse, scan,
+//! merge, manifest, state, other git commands). The table goes to stderr
+//! when the command ends, so `--json` output stays clean. When profiling is
+//! off, timing a step is a flag check.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use once_cell::sync::Lazy;
+
+use crate::output;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static PROFILE: Lazy<Mutex<Profile>> = Lazy::new(|| Mutex::new(Profile::default()));
+
+/// Phases in the order they are listed, with where their time is spent.
+const PHASES: &[(&str, &str)] = &[
+    ("connect", "network"),
+    ("select", "server"),
+    ("search", "server"),
+    ("fetch", "server"),
+    ("parse", "local"),
+    ("scan", "local"),
+    ("merge", "local"),
+    ("manifest", "local"),
+    ("state", "local"),
+    ("git", "network"),
+    ("git-local", "local"),
+];
+
+/// Time spent in one phase of one scope.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Entry {
+    pub scope: String,
+    pub phase: &'static str,
+    pub calls: usize,
+    pub elapsed: Duration,
+}
+
+#[derive(Default)]
+struct Profile {
+    started: Option<Instant>,
+    entries: Vec<Entry>,
+}
+
+/// Start profiling for the rest of the process.
+pub fn enable() {
+    ENABLED.store(true, Ordering::Relaxed);
+    PROFILE.lock().unwrap().started = Some(Instant::now());
+}
+
+pub fn enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Add `elapsed` to `phase` of `scope`.
+pub fn add(scope: &str, phase: &'static str, elapsed: Duration) {
+    if !enabled() {
+        return;
+    }
+    let mut profile = PROFILE.lock().unwrap();
+    match profile
+        .entries
+        .iter_mut()
+        .find(|e| e.scope == scope && e.phase == phase)
+    {
+        Some(entry) => {
+            entry.calls += 1;
+            entry.elapsed += elapsed;
+        }
+        None => profile.entries.push(Entry {
+            scope: scope.to_string(),
+            phase,
+            calls: 1,
+            elapsed,
+        }),
+    }
+}
+
+/// Run `f`, adding its time to `phase` of `scope`.
+pub fn time<T>(scope: &str, phase: &'static str, f: impl FnOnce() -> T) -> T {
+    if !enabled() {
+        return f();
+    }
+    let started = Instant::now();
+    let result = f();
+    add(scope, phase, started.elapsed());
+    result
+}
+
+/// Where a phase's time is spent: `network`, `server`, or `local`.
+pub fn kind(phase: &str) -> &'static str {
+    PHASES
+        .iter()
+        .find(|(p, _)| *p == phase)
+        .map_or("local", |(_, k)| k)
+}
+
+fn secs(d: Duration) -> String {
+    format!("{:.2}s", d.as_secs_f64())
+}
+
+fn share(part: Duration, wall: Duration) -> String {
+    if wall.is_zero() {
+        return "-".to_string();
+    }
+    format!("{:.0}%", 100.0 * part.as_secs_f64() / wall.as_secs_f64())
+}
+
+/// The profile table: one row per scope and phase, scopes in the order
+/// they were first timed, then one row per kind and the untracked rest of
+/// `wall`.
+pub fn table(entries: &[Entry], wall: Duration) -> String {
+    let mut scopes: Vec<&str> = Vec::new();
+    for e in entries {
+        if !scopes.contains(&e.scope.as_str()) {
+            scopes.push(&e.scope);
+        }
+    }
+    let mut rows = vec![vec![
+        output::paint("Scope", output::Style::Bold),
+        output::paint("Phase", output::Style::Bold),
+        output::paint("Calls", output::Style::Bold),
+        output::paint("Time", output::Style::Bold),
+        output::paint("Share", output::Style::Bold),
+    ]];
+    for scope in &scopes {
+        for (phase, _) in PHASES {
+            if let Some(e) = entries
+                .iter()
+                .find(|e| e.scope == *scope && e.phase == *phase)
+            {
+                rows.push(vec![
+                    e.scope.clone(),
+                    e.phase.to_string(),
+                    e.calls.to_string(),
+                    secs(e.elapsed),
+                    share(e.elapsed, wall),
+                ]);
+            }
+        }
+    }
+    let mut tracked = Duration::ZERO;
+    for k in ["network", "server", "local"] {
+        let total: Duration = entries
+            .iter()
+            .filter(|e| kind(e.phase) == k)
+            .map(|e| e.elapsed)
+            .sum();
+        tracked += total;
+        rows.push(vec![
+            "total".to_string(),
+            k.to_string(),
+            String::new(),
+            secs(total),
+            share(total, wall),
+        ]);
+    }
+    let rest = wall.saturating_sub(tracked);
+    rows.push(vec![
+        "total".to_string(),
+        "other".to_string(),
+        String::new(),
+        secs(rest),
+        share(rest, wall),
+    ]);
+    output::table(&rows)
+}
+
+/// Print the profile to stderr, if profiling is on.
+pub fn print() {
+    if !enabled() {
+        return;
+    }
+    let profile = PROFILE.lock().unwrap();
+    let wall = profile.started.map(|s| s.elapsed()).unwrap_or_default();
+    eprintln!("\n{}", output::paint("Profile", output::Style::Bold));
+    eprint!("{}", table(&profile.entries, wall));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(scope: &str, phase: &'static str, calls: usize, ms: u64) -> Entry {
+        Entry {
+            scope: scope.to_string(),
+            phase,
+            calls,
+            elapsed: Duration::from_millis(ms),
+        }
+    }
+
+    #[test]
+    fn test_table_rows_and_kinds() {
+        output::disable_color();
+        let entries = vec![
+            entry("personal/INBOX", "merge", 12, 300),
+            entry("personal", "connect", 1, 800),
+            entry("personal/INBOX", "fetch", 13, 600),
+            entry("run", "manifest", 1, 100),
+        ];
+        let table = table(&entries, Duration::from_secs(2));
+        let lines: Vec<Vec<&str>> = table
+            .lines()
+            .map(|l| l.split_whitespace().collect())
+            .collect();
+        assert_eq!(lines[1], ["personal/INBOX", "fetch", "13", "0.60s", "30%"]);
+        assert_eq!(lines[2], ["personal/INBOX", "merge", "12", "0.30s", "15%"]);
+        assert_eq!(lines[3], ["personal", "connect", "1", "0.80s", "40%"]);
+        assert_eq!(lines[4], ["run", "manifest", "1", "0.10s", "5%"]);
+        assert_eq!(lines[5], ["total", "network", "0.80s", "40%"]);
+        assert_eq!(lines[6], ["total", "server", "0.60s", "30%"]);
+        assert_eq!(lines[7], ["total", "local", "0.40s", "20%"]);
+        assert_eq!(lines[8], ["total", "other", "0.20s", "10%"]);
+    }
+
+    #[test]
+    fn test_kind() {
+        assert_eq!(kind("connect"), "network");
+        assert_eq!(kind("search"), "server");
+        assert_eq!(kind("scan"), "local");
+        assert_eq!(kind("git"), "network");
+        assert_eq!(kind("git-local"), "local");
+    }
+}
@@ -0,0 +1,416 @@
+//! `corky prune` — local retention for root conversations.
+//!
+//! `[retention]` limits how long (`keep_days`) and how many (`max_threads`)
+//! conversations are kept per label. A conversation is pruned when none of
+//! its labels keeps it. Pruned files, with their continuation parts and
+//! attachment folder, are moved into a zip archive or deleted; mail on the
+//! server is never touched. `--dry-run` only reports.
+
+use anyhow::Result;
+use chrono::{DateTime, Datelike, Local, Utc};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::config::corky_config::{self, RetentionConfig};
+use crate::error::CorkyError;
+use crate::output;
+use crate::resolve;
+use crate::sync::imap_sync::parse_msg_date;
+use crate::sync::manifest::generate_manifest;
+use crate::sync::markdown::{is_part_file, parse_thread_markdown, thread_files};
+
+/// What happens to pruned conversations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Archive,
+    Delete,
+}
+
+impl Action {
+    pub fn parse(value: &str) -> Result<Self> {
+        match value.trim() {
+            "" | "archive" => Ok(Action::Archive),
+            "delete" => Ok(Action::Delete),
+            other => Err(CorkyError::Config(format!(
+                "[retention] action must be \"archive\" or \"delete\", not \"{}\"",
+                other
+            ))
+            .into()),
+        }
+    }
+
+    fn past_tense(self) -> &'static str {
+        match self {
+            Action::Archive => "archived",
+            Action::Delete => "deleted",
+        }
+    }
+}
+
+/// A conversation no label keeps.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Candidate {
+    pub path: PathBuf,
+    pub last_date: DateTime<Utc>,
+    /// Why the first of its labels let it go.
+    pub reason: String,
+}
+
+/// `(keep_days, max_threads)` for `label`, per-label overrides first.
+fn limits(config: &RetentionConfig, label: &str) -> (u32, usize) {
+    let policy = config.labels.get(label);
+    (
+        policy.and_then(|p| p.keep_days).unwrap_or(config.keep_days),
+        policy
+            .and_then(|p| p.max_threads)
+            .unwrap_or(config.max_threads),
+    )
+}
+
+/// Whether any limit is set.
+pub fn has_limits(config: &RetentionConfig) -> bool {
+    config.keep_days > 0
+        || config.max_threads > 0
+        || config
+            .labels
+            .values()
+            .any(|p| p.keep_days.unwrap_or(0) > 0 || p.max_threads.unwrap_or(0) > 0)
+}
+
+/// Conversations in `dir` that `config` doesn't keep as of `now`, oldest
+/// first. Conversations without a parseable `**Last updated**` are kept.
+pub fn plan(dir: &Path, config: &RetentionConfig, now: DateTime<Utc>) -> Result<Vec<Candidate>> {
+    let mut threads: Vec<(PathBuf, DateTime<Utc>, Vec<String>)> = Vec::new();
+    if dir.is_dir() {
+        for path in std::fs::read_dir(dir)?.flatten().map(|e| e.path()) {
+            if path.extension().and_then(|e| e.to_str()) != Some("md") || is_part_file(&path) {
+                continue;
+            }
+            let Some(thread) = parse_thread_markdown(&std::fs::read_to_string(&path)?) else {
+                continue;
+            };
+            let date = parse_msg_date(&thread.last_date);
+            if date.year() <= 1970 {
+                continue;
+            }
+            threads.push((path, date, thread.labels));
+        }
+    }
+
+    // Unlabeled conversations fall under the top-level limits.
+    let mut by_label: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (n, (_, _, labels)) in threads.iter().enumerate() {
+        if labels.is_empty() {
+            by_label.entry("").or_default().push(n);
+        }
+        for label in labels {
+            by_label.entry(label).or_default().push(n);
+        }
+    }
+
+    let mut kept = vec![false; threads.len()];
+    let mut reasons: Vec<Option<String>> = vec![None; threads.len()];
+    for (label, mut members) in by_label {
+        let (keep_days, max_threads) = limits(config, label);
+        members.sort_by(|a, b| threads[*b].1.cmp(&threads[*a].1));
+        let shown = if label.is_empty() { "unlabeled" } else { label };
+        for (rank, n) in members.into_iter().enumerate() {
+            let age_days = (now - threads[n].1).num_days();
+            let reason = if keep_days > 0 && age_days > keep_days as i64 {
+                Some(format!("older than {} days ({})", keep_days, shown))
+            } else if max_threads > 0 && rank >= max_threads {
+                Some(format!("beyond newest {} ({})", max_threads, shown))
+            } else {
+                None
+            };
+            match reason {
+                None => kept[n] = true,
+                Some(r) => {
+                    reasons[n].get_or_insert(r);
+                }
+            }
+        }
+    }
+
+    let mut candidates: Vec<Candidate> = threads
+        .into_iter()
+        .zip(kept.into_iter().zip(reasons))
+        .filter(|(_, (kept, _))| !kept)
+        .map(|((path, last_date, _), (_, reason))| Candidate {
+            path,
+            last_date,
+            reason: reason.unwrap_or_default(),
+        })
+        .collect();
+    candidates.sort_by(|a, b| (a.last_date, &a.path).cmp(&(b.last_date, &b.path)));
+    Ok(candidates)
+}
+
+/// Files and folders that make up the conversation at `main`: its parts
+/// and the attachments folder beside it.
+fn conversation_paths(main: &Path) -> Vec<PathBuf> {
+    let mut paths = thread_files(main);
+    let folder = main.with_extension("");
+    if folder.is_dir() {
+        paths.push(folder);
+    }
+    paths
+}
+
+/// Every file under `path` (or `path` itself).
+fn files_under(path: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+    if path.is_dir() {
+        let mut entries: Vec<PathBuf> = std::fs::read_dir(path)?
+            .flatten()
+            .map(|e| e.path())
+            .collect();
+        entries.sort();
+        for entry in entries {
+            files_under(&entry, out)?;
+        }
+    } else {
+        out.push(path.to_path_buf());
+    }
+    Ok(())
+}
+
+/// Write the conversations of `candidates` into a new zip at `zip_path`,
+/// with paths relative to `data_dir`.
+pub fn write_archive(candidates: &[Candidate], data_dir: &Path, zip_path: &Path) -> Result<()> {
+    if let Some(parent) = zip_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut zip = zip::ZipWriter::new(std::fs::File::create(zip_path)?);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for candidate in candidates {
+        let mut files = Vec::new();
+        for path in conversation_paths(&candidate.path) {
+            files_under(&path, &mut files)?;
+        }
+        for file in files {
+            let name = file.strip_prefix(data_dir).unwrap_or(&file);
+            zip.start_file(name.to_string_lossy().replace('\\', "/"), options)?;
+            zip.write_all(&std::fs::read(&file)?)?;
+        }
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Remove the conversations of `candidates` from disk.
+fn remove(candidates: &[Candidate]) -> Result<()> {
+    for candidate in candidates {
+        for path in conversation_paths(&candidate.path) {
+            if path.is_dir() {
+                std::fs::remove_dir_all(&path)?;
+            } else {
+                std::fs::remove_file(&path)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// corky prune [--dry-run]
+pub fn run(dry_run: bool) -> Result<()> {
+    let config = corky_config::try_load_config(None)
+        .and_then(|c| c.retention)
+        .unwrap_or_default();
+    if !has_limits(&config) {
+        println!("No [retention] limits configured in .corky.toml");
+        return Ok(());
+    }
+    let action = Action::parse(&config.action)?;
+    let data_dir = resolve::data_dir();
+    let conv_dir = resolve::conversations_dir();
+    let candidates = plan(&conv_dir, &config, Utc::now())?;
+    if candidates.is_empty() {
+        println!("Nothing to prune.");
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = candidates
+        .iter()
+        .map(|c| {
+            vec![
+                c.last_date.format("%Y-%m-%d").to_string(),
+                c.path
+                    .strip_prefix(&data_dir)
+                    .unwrap_or(&c.path)
+                    .display()
+                    .to_string(),
+                c.reason.clone(),
+            ]
+        })
+        .collect();
+    output::print_table(&rows);
+    println!();
+
+    if dry_run {
+        println!(
+            "Dry run: {} conversation(s) would be {}",
+            candidates.len(),
+            action.past_tense()
+        );
+        return Ok(());
+    }
+
+    match action {
+        Action::Archive => {
+            let archive_dir = match config.archive_dir.trim() {
+                "" => "archive",
+                dir => dir,
+            };
+            let stamp = Local::now().format("%Y%m%d-%H%M%S").to_string();
+            let zip_path = data_dir
+                .join(resolve::expand_tilde(archive_dir))
+                .join(format!("prune-{}.zip", stamp));
+            write_archive(&candidates, &data_dir, &zip_path)?;
+            remove(&candidates)?;
+            generate_manifest(&conv_dir)?;
+            println!(
+                "Archived {} conversation(s) to {}",
+                candidates.len(),
+                zip_path.display()
+            );
+        }
+        Action::Delete => {
+            remove(&candidates)?;
+            generate_manifest(&conv_dir)?;
+            println!("Deleted {} conversation(s)", candidates.len());
+        }
+    }
+    println!("Mail on the server is unchanged.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::corky_config::RetentionPolicy;
+    use std::fs;
+
+    fn write(dir: &Path, slug: &str, labels: &str, date: &str) {
+        fs::write(
+            dir.join(format!("{}.md", slug)),
+            format!(
+                "# {slug}\n\n**Labels**: {labels}\n**Thread ID**: {slug}\n\
+                 **Last updated**: {date}\n\n---\n\n\
+                 ## Alice <alice@example.com> \u{2014} {date}\n\nHello\n"
+            ),
+        )
+        .unwrap();
+    }
+
+    fn now() -> DateTime<Utc> {
+        parse_msg_date("Sat, 01 Mar 2025 00:00:00 +0000")
+    }
+
+    fn names(candidates: &[Candidate]) -> Vec<String> {
+        candidates
+            .iter()
+            .map(|c| c.path.file_stem().unwrap().to_string_lossy().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_plan_keep_days_and_max_threads() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path();
+        write(
+            dir,
+            "old-news",
+            "newsletters",
+            "Mon, 06 Jan 2025 10:00:00 +0000",
+        );
+        write(
+            dir,
+            "news-1",
+            "newsletters",
+            "Mon, 24 Feb 2025 10:00:00 +0000",
+        );
+        write(
+            dir,
+            "news-2",
+            "newsletters",
+            "Tue, 25 Feb 2025 10:00:00 +0000",
+        );
+        write(
+            dir,
+            "news-3",
+            "newsletters",
+            "Wed, 26 Feb 2025 10:00:00 +0000",
+        );
+        write(dir, "old-work", "work", "Mon, 06 Jan 2025 10:00:00 +0000");
+        // Kept by work even though newsletters would drop it
+        write(
+            dir,
+            "both",
+            "newsletters, work",
+            "Mon, 06 Jan 2025 10:00:00 +0000",
+        );
+        write(dir, "undated", "newsletters", "");
+
+        let mut config = RetentionConfig::default();
+        config.labels.insert(
+            "newsletters".to_string(),
+            RetentionPolicy {
+                keep_days: Some(30),
+                max_threads: Some(2),
+            },
+        );
+        let candidates = plan(dir, &config, now()).unwrap();
+        assert_eq!(names(&candidates), vec!["old-news", "news-1"]);
+        assert_eq!(candidates[0].reason, "older than 30 days (newsletters)");
+        assert_eq!(candidates[1].reason, "beyond newest 2 (newsletters)");
+
+        // A top-level limit applies to labels without an override
+        config.keep_days = 30;
+        let candidates = plan(dir, &config, now()).unwrap();
+        assert_eq!(
+            names(&candidates),
+            vec!["both", "old-news", "old-work", "news-1"]
+        );
+    }
+
+    #[test]
+    fn test_archive_and_remove() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("conversations");
+        fs::create_dir_all(dir.join("old/attachments")).unwrap();
+        write(&dir, "old", "inbox", "Mon, 06 Jan 2025 10:00:00 +0000");
+        fs::write(dir.join("old/attachments/a.txt"), "attached").unwrap();
+
+        let config = RetentionConfig {
+            keep_days: 7,
+            ..Default::default()
+        };
+        let candidates = plan(&dir, &config, now()).unwrap();
+        let zip_path = tmp.path().join("archive/prune.zip");
+        write_archive(&candidates, tmp.path(), &zip_path).unwrap();
+        remove(&candidates).unwrap();
+
+        assert!(!dir.join("old.md").exists());
+        assert!(!dir.join("old").exists());
+        let mut archive = zip::ZipArchive::new(fs::File::open(&zip_path).unwrap()).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(
+            names,
+            vec![
+                "conversations/old.md",
+                "conversations/old/attachments/a.txt"
+            ]
+        );
+        assert!(archive.by_name("conversations/old.md").is_ok());
+    }
+
+    #[test]
+    fn test_action_parse() {
+        assert_eq!(Action::parse("").unwrap(), Action::Archive);
+        assert_eq!(Action::parse("delete").unwrap(), Action::Delete);
+        assert!(Action::parse("shred").is_err());
+    }
+}
@@ -0,0 +1,249 @@
+//! Render conversations into a static, read-only HTML site.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use crate::mailbox::find_unanswered::{collect_md_files, resolve_dirs, Scope};
+use crate::sync::imap_sync::parse_msg_date;
+use crate::sync::markdown::{is_part_file, read_thread};
+use crate::sync::types::Thread;
+use crate::util::slugify;
+
+const STYLE_CSS: &str = "\
+body { font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; color: #222; }
+a { color: #0550ae; text-decoration: none; }
+a:hover { text-decoration: underline; }
+table { border-collapse: collapse; width: 100%; }
+th, td { text-align: left; padding: 0.4rem 0.5rem; border-bottom: 1px solid #ddd; vertical-align: top; }
+.meta { color: #666; font-size: 0.9rem; }
+.label { display: inline-block; background: #eef; border-radius: 3px; padding: 0 0.3rem; margin-right: 0.2rem; font-size: 0.8rem; }
+#search { width: 100%; padding: 0.5rem; font-size: 1rem; margin-bottom: 1rem; box-sizing: border-box; }
+.message { border-top: 1px solid #ddd; padding: 1rem 0; }
+.message h2 { font-size: 1rem; margin: 0 0 0.3rem; }
+.body { white-space: pre-wrap; font-family: inherit; margin: 0.8rem 0 0; }
+";
+
+const SEARCH_JS: &str = "\
+document.getElementById('search').addEventListener('input', function (e) {
+  var terms = e.target.value.toLowerCase().split(/\\s+/).filter(Boolean);
+  document.querySelectorAll('tr[data-search]').forEach(function (row) {
+    var text = row.getAttribute('data-search');
+    row.style.display = terms.every(function (t) { return text.indexOf(t) !== -1; }) ? '' : 'none';
+  });
+});
+";
+
+/// A thread selected for publishing, with its page path relative to the site root.
+struct Page {
+    href: String,
+    thread: Thread,
+}
+
+/// Escape text for HTML element content and double-quoted attributes.
+pub fn escape_html(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Whether a thread passes the `--label` filter (empty filter = everything).
+fn matches_labels(thread: &Thread, labels: &[String]) -> bool {
+    labels.is_empty()
+        || thread
+            .labels
+            .iter()
+            .any(|l| labels.iter().any(|f| f.eq_ignore_ascii_case(l)))
+}
+
+fn render_labels(labels: &[String]) -> String {
+    labels
+        .iter()
+        .map(|l| format!("<span class=\"label\">{}</span>", escape_html(l)))
+        .collect()
+}
+
+fn render_thread_page(thread: &Thread) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str(&format!("<title>{}</title>\n", escape_html(&thread.subject)));
+    html.push_str("<link rel=\"stylesheet\" href=\"../../style.css\">\n</head>\n<body>\n");
+    html.push_str("<p><a href=\"../../index.html\">&larr; All conversations</a></p>\n");
+    html.push_str(&format!("<h1>{}</h1>\n", escape_html(&thread.subject)));
+    html.push_str(&format!(
+        "<p class=\"meta\">{} &middot; Last updated {}</p>\n",
+        render_labels(&thread.labels),
+        escape_html(&thread.last_date)
+    ));
+    if !thread.participants.is_empty() {
+        html.push_str(&format!(
+            "<p class=\"meta\">Participants: {}</p>\n",
+            escape_html(&thread.participants.join(", "))
+        ));
+    }
+    for msg in &thread.messages {
+        html.push_str("<div class=\"message\">\n");
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(&msg.from)));
+        html.push_str(&format!("<div class=\"meta\">{}</div>\n", escape_html(&msg.date)));
+        if !msg.to.is_empty() {
+            html.push_str(&format!("<div class=\"meta\">To: {}</div>\n", escape_html(&msg.to)));
+        }
+        if !msg.cc.is_empty() {
+            html.push_str(&format!("<div class=\"meta\">CC: {}</div>\n", escape_html(&msg.cc)));
+        }
+        html.push_str(&format!("<pre class=\"body\">{}</pre>\n", escape_html(msg.body.trim())));
+        html.push_str("</div>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+fn render_index(pages: &[Page]) -> String {
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>Conversations</title>\n");
+    html.push_str("<link rel=\"stylesheet\" href=\"style.css\">\n</head>\n<body>\n");
+    html.push_str(&format!("<h1>Conversations ({})</h1>\n", pages.len()));
+    html.push_str("<input id=\"search\" type=\"search\" placeholder=\"Search conversations\u{2026}\">\n");
+    html.push_str("<table>\n<thead><tr><th>Subject</th><th>Participants</th><th>Last updated</th></tr></thead>\n<tbody>\n");
+    for page in pages {
+        let t = &page.thread;
+        let mut search = vec![t.subject.clone(), t.participants.join(" "), t.labels.join(" ")];
+        search.extend(t.messages.iter().map(|m| format!("{} {}", m.from, m.body)));
+        html.push_str(&format!(
+            "<tr data-search=\"{}\"><td><a href=\"{}\">{}</a> {}</td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&search.join(" ").to_lowercase()),
+            escape_html(&page.href),
+            escape_html(&t.subject),
+            render_labels(&t.labels),
+            escape_html(&t.participants.join(", ")),
+            escape_html(&t.last_date),
+        ));
+    }
+    html.push_str("</tbody>\n</table>\n");
+    html.push_str(&format!("<script>\n{}</script>\n", SEARCH_JS));
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Render threads from `dirs` into `output`. Returns the number of thread pages written.
+///
+/// Layout: `index.html`, `style.css`, and `threads/{dir-slug}/{stem}.html`.
+pub fn publish_dirs(dirs: &[(String, PathBuf)], labels: &[String], output: &Path) -> Result<usize> {
+    let mut pages = Vec::new();
+    for (dir_label, dir) in dirs {
+        let mut md_files = Vec::new();
+        collect_md_files(dir, &mut md_files)?;
+        md_files.sort();
+
+        let dir_slug = slugify(dir_label);
+        for path in md_files {
+            if is_part_file(&path) {
+                continue;
+            }
+            let Some(thread) = read_thread(&path)? else {
+                continue;
+            };
+            if !matches_labels(&thread, labels) {
+                continue;
+            }
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let href = format!("threads/{}/{}.html", dir_slug, stem);
+            let page_path = output.join(&href);
+            if let Some(parent) = page_path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&page_path, render_thread_page(&thread))?;
+            pages.push(Page { href, thread });
+        }
+    }
+
+    pages.sort_by_key(|p| std::cmp::Reverse(parse_msg_date(&p.thread.last_date)));
+    std::fs::create_dir_all(output)?;
+    std::fs::write(output.join("style.css"), STYLE_CSS)?;
+    std::fs::write(output.join("index.html"), render_index(&pages))?;
+    Ok(pages.len())
+}
+
+/// corky publish --output DIR [--label LABEL...] [SCOPE]
+pub fn run(scope: Scope, labels: &[String], output: &Path) -> Result<()> {
+    let dirs = resolve_dirs(&scope)?;
+    let count = publish_dirs(&dirs, labels, output)?;
+    println!(
+        "Published {} conversation(s) to {}",
+        count,
+        output.join("index.html").display()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THREAD: &str = "# Launch <plan>\n\n\
+**Labels**: work\n\
+**Accounts**: personal\n\
+**Thread ID**: launch plan\n\
+**Last updated**: Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+---\n\n\
+## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+Ship it & celebrate.\n";
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape_html("<a href=\"x\">&'"), "&lt;a href=&quot;x&quot;&gt;&amp;&#39;");
+    }
+
+    #[test]
+    fn test_publish_dirs_respects_label_filter() {
+        let src = tempfile::TempDir::new().unwrap();
+        let out = tempfile::TempDir::new().unwrap();
+        std::fs::write(src.path().join("launch-plan.md"), THREAD).unwrap();
+        let dirs = vec![("Root".to_string(), src.path().to_path_buf())];
+
+        let count = publish_dirs(&dirs, &["personal-only".to_string()], out.path()).unwrap();
+        assert_eq!(count, 0);
+
+        let count = publish_dirs(&dirs, &["Work".to_string()], out.path()).unwrap();
+        assert_eq!(count, 1);
+        let index = std::fs::read_to_string(out.path().join("index.html")).unwrap();
+        assert!(index.contains("href=\"threads/root/launch-plan.html\""));
+        assert!(index.contains("Launch &lt;plan&gt;"));
+        let page =
+            std::fs::read_to_string(out.path().join("threads/root/launch-plan.html")).unwrap();
+        assert!(page.contains("Ship it &amp; celebrate."));
+        assert!(out.path().join("style.css").exists());
+    }
+
+    #[test]
+    fn test_publish_dirs_sorts_newest_first_by_date() {
+        let src = tempfile::TempDir::new().unwrap();
+        let out = tempfile::TempDir::new().unwrap();
+        // As strings, "Tue, 25 Feb" sorts after "Mon, 3 Mar"
+        let older = THREAD.replace("Mon, 10 Feb 2025", "Tue, 25 Feb 2025");
+        let newer = THREAD
+            .replace("Mon, 10 Feb 2025", "Mon, 3 Mar 2025")
+            .replace("launch plan", "launch recap");
+        std::fs::write(src.path().join("older.md"), older).unwrap();
+        std::fs::write(src.path().join("newer.md"), newer).unwrap();
+        let dirs = vec![("Root".to_string(), src.path().to_path_buf())];
+
+        assert_eq!(publish_dirs(&dirs, &[], out.path()).unwrap(), 2);
+        let index = std::fs::read_to_string(out.path().join("index.html")).unwrap();
+        let newer_at = index.find("threads/root/newer.html").unwrap();
+        let older_at = index.find("threads/root/older.html").unwrap();
+        assert!(newer_at < older_at);
+    }
+}
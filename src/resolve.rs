@@ -65,14 +65,67 @@ pub fn templates_dir() -> PathBuf {
     data_dir().join("templates")
 }
 
+/// Sync state for this owner: `.sync-state.{github_user}.json` when
+/// `[owner] github_user` is set, so several owners can share one data repo.
 pub fn sync_state_file() -> PathBuf {
-    data_dir().join(".sync-state.json")
+    let owner = crate::config::corky_config::try_load_config(None)
+        .and_then(|c| c.owner)
+        .map(|o| crate::util::slugify(&o.github_user))
+        .filter(|s| !s.is_empty());
+    match owner {
+        Some(owner) => data_dir().join(format!(".sync-state.{}.json", owner)),
+        None => data_dir().join(".sync-state.json"),
+    }
+}
+
+/// State file to load: the owner's, else the un-namespaced file written
+/// before state was split per owner.
+pub fn sync_state_read_file() -> PathBuf {
+    let sf = sync_state_file();
+    let legacy = data_dir().join(".sync-state.json");
+    if !sf.exists() && legacy.exists() {
+        return legacy;
+    }
+    sf
 }
 
 pub fn manifest_file() -> PathBuf {
     data_dir().join("manifest.toml")
 }
 
+/// Find a conversation file by slug: conversations/{slug}.md, then
+/// mailboxes/*/conversations/{slug}.md.
+pub fn find_conversation(slug: &str) -> anyhow::Result<PathBuf> {
+    let data_dir = data_dir();
+    let filename = format!("{}.md", slug);
+
+    // Search root conversations/
+    let root_path = data_dir.join("conversations").join(&filename);
+    if root_path.exists() {
+        return Ok(root_path);
+    }
+
+    // Search mailboxes/*/conversations/
+    let mailboxes_dir = data_dir.join("mailboxes");
+    if mailboxes_dir.exists() {
+        if let Ok(entries) = std::fs::read_dir(&mailboxes_dir) {
+            for entry in entries.flatten() {
+                let mb_path = entry.path().join("conversations").join(&filename);
+                if mb_path.exists() {
+                    return Ok(mb_path);
+                }
+            }
+        }
+    }
+
+    anyhow::bail!(
+        "Conversation '{}' not found.\nSearched:\n  - {}\n  - {}",
+        slug,
+        data_dir.join("conversations").display(),
+        mailboxes_dir.join("*/conversations").display()
+    )
+}
+
 // --- Derived helpers: config paths ---
 
 /// Resolve .corky.toml path: check .corky.toml then corky.toml in config_dir().
@@ -262,7 +262,7 @@ pub fn run(dry_run: bool) -> Result<()> {
                 }
             }
             ScheduledKind::Email => {
-                match crate::draft::run(&item.path, true) {
+                match crate::draft::run(&item.path, true, None, None) {
                     Ok(()) => ProcessResult {
                         path: item.path.clone(),
                         kind: item.kind,
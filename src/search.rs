@@ -0,0 +1,113 @@
+//! `corky search` and `corky stats` — queries over the SQLite mirror.
+//!
+//! Both bring the mirror up to date for the scope's directories first,
+//! re-reading only conversations whose files changed since they were
+//! mirrored, so they work whether or not `[sync] mirror = "sqlite"` kept
+//! it current during sync.
+
+use anyhow::Result;
+use chrono::DateTime;
+use std::path::PathBuf;
+
+use crate::mailbox::find_unanswered::{resolve_dirs, Scope};
+use crate::output::{self, Style};
+use crate::sync::mirror::Mirror;
+use crate::sync::store::MarkdownStore;
+
+/// Open the mirror and refresh it for every dir in `scope`.
+fn refreshed(scope: &Scope) -> Result<(Mirror, Vec<(String, PathBuf)>)> {
+    let dirs = resolve_dirs(scope)?;
+    let mirror = Mirror::open_default()?;
+    let mut reread = 0;
+    for (_, dir) in &dirs {
+        reread += mirror.refresh(&MarkdownStore::new(dir).recursive())?;
+    }
+    if reread > 0 {
+        crate::progress!("Mirrored {} changed conversation(s)", reread);
+    }
+    Ok((mirror, dirs))
+}
+
+fn day(ts: Option<i64>) -> String {
+    ts.and_then(|t| DateTime::from_timestamp(t, 0))
+        .map(|dt| dt.format("%Y-%m-%d").to_string())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+/// corky search QUERY [SCOPE] [--label LABEL]
+pub fn run(query: &str, scope: Scope, label: Option<&str>) -> Result<()> {
+    let (mirror, dirs) = refreshed(&scope)?;
+    let multi = dirs.len() > 1;
+    let mut total = 0;
+    for (group, dir) in &dirs {
+        let hits = mirror.search(std::slice::from_ref(dir), query, label)?;
+        if hits.is_empty() {
+            continue;
+        }
+        total += hits.len();
+        if multi {
+            println!("{} ({} threads):", group, hits.len());
+        }
+        let mut rows = vec![vec![
+            output::paint("File", Style::Bold),
+            output::paint("Subject", Style::Bold),
+            output::paint("Last updated", Style::Bold),
+            output::paint("Matches", Style::Bold),
+        ]];
+        for hit in hits {
+            rows.push(vec![
+                format!("{}.md", hit.key),
+                hit.subject,
+                output::paint(&hit.last_date, Style::Dim),
+                hit.matches.to_string(),
+            ]);
+        }
+        output::print_table(&rows);
+        if multi {
+            println!();
+        }
+    }
+    if total == 0 {
+        println!("No threads match \"{}\".", query);
+    }
+    Ok(())
+}
+
+/// corky stats [SCOPE] [--top N]
+pub fn stats(scope: Scope, top: usize) -> Result<()> {
+    let (mirror, dirs) = refreshed(&scope)?;
+    let paths: Vec<PathBuf> = dirs.into_iter().map(|(_, dir)| dir).collect();
+    let stats = mirror.stats(&paths, top)?;
+    println!("Threads:  {}", stats.threads);
+    println!("Messages: {}", stats.messages);
+    if stats.messages > 0 {
+        println!(
+            "Dates:    {} to {}",
+            day(stats.first_ts),
+            day(stats.last_ts)
+        );
+    }
+    if !stats.labels.is_empty() {
+        println!();
+        let mut rows = vec![vec![
+            output::paint("Label", Style::Bold),
+            output::paint("Threads", Style::Bold),
+        ]];
+        for (label, n) in &stats.labels {
+            rows.push(vec![label.clone(), n.to_string()]);
+        }
+        output::print_table(&rows);
+    }
+    if !stats.senders.is_empty() {
+        println!();
+        let mut rows = vec![vec![
+            output::paint("Top senders", Style::Bold),
+            output::paint("Messages", Style::Bold),
+        ]];
+        for (sender, n) in &stats.senders {
+            rows.push(vec![sender.clone(), n.to_string()]);
+        }
+        output::print_table(&rows);
+    }
+    Ok(())
+}
@@ -1,41 +1,355 @@
 //! `corky skill` — Manage the Claude Code skill definition.
 //!
-//! Delegates to `agent_kit::skill::SkillConfig` for the actual install/check logic.
 //! The SKILL.md content is bundled into the binary at build time via `include_str!`.
+//! Installed files end with a version stamp comment recording the corky version
+//! and a hash of the content as written, so `skill update` can tell an outdated
+//! copy from one edited locally.
 
-use anyhow::Result;
-use std::path::Path;
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
 
-use agent_kit::skill::SkillConfig;
+use crate::error::CorkyError;
 
 /// The SKILL.md content bundled at build time.
 const BUNDLED_SKILL: &str = include_str!("../SKILL.md");
 
+/// The triage skill (prioritizing replies), bundled at build time.
+const TRIAGE_SKILL: &str = include_str!("../skills/triage/SKILL.md");
+
 /// Current binary version (from Cargo.toml).
 const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-fn config() -> SkillConfig {
-    SkillConfig::new("corky", BUNDLED_SKILL, VERSION)
+/// A skill shipped with the binary.
+struct BundledSkill {
+    name: &'static str,
+    /// One-line summary for targets whose format requires it (frontmatter).
+    description: &'static str,
+    /// (file, content) pairs written under `.claude/skills/{name}/`.
+    files: &'static [(&'static str, &'static str)],
+}
+
+const BUNDLED: &[BundledSkill] = &[
+    BundledSkill {
+        name: "corky",
+        description: "Manage email, drafts, contacts, and correspondence with the corky CLI",
+        files: &[("SKILL.md", BUNDLED_SKILL)],
+    },
+    BundledSkill {
+        name: "triage",
+        description: "Prioritize which email threads need a reply using corky metadata",
+        files: &[("SKILL.md", TRIAGE_SKILL)],
+    },
+];
+
+const STAMP_PREFIX: &str = "<!-- corky-skill ";
+
+/// Install state of one skill file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileStatus {
+    Missing,
+    /// Matches the bundled content.
+    Current,
+    /// Unchanged since install, but an older bundle.
+    Outdated {
+        version: String,
+    },
+    /// Edited after install, or never stamped.
+    Modified,
+}
+
+fn skills_dir(root: Option<&Path>) -> PathBuf {
+    root.unwrap_or(Path::new(".")).join(".claude/skills")
+}
+
+fn bundled(name: &str) -> Option<&'static BundledSkill> {
+    BUNDLED.iter().find(|s| s.name == name)
+}
+
+fn available() -> String {
+    BUNDLED
+        .iter()
+        .map(|s| s.name)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// 64-bit FNV-1a, stable across builds (unlike `DefaultHasher`).
+fn content_hash(content: &str) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in content.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Bundled content plus the trailing stamp line.
+fn stamped(name: &str, content: &str) -> String {
+    let mut out = content.to_string();
+    if !out.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(&format!(
+        "{}{} v{} {} -->\n",
+        STAMP_PREFIX,
+        name,
+        VERSION,
+        content_hash(content)
+    ));
+    out
+}
+
+/// Split an installed file into (body, Some((version, hash))) when stamped.
+fn parse_stamp(installed: &str) -> (&str, Option<(String, String)>) {
+    let trimmed = installed.trim_end_matches('\n');
+    let (body, last) = match trimmed.rfind('\n') {
+        Some(i) => (&installed[..i + 1], &trimmed[i + 1..]),
+        None => ("", trimmed),
+    };
+    let Some(fields) = last
+        .strip_prefix(STAMP_PREFIX)
+        .and_then(|s| s.strip_suffix(" -->"))
+    else {
+        return (installed, None);
+    };
+    let parts: Vec<&str> = fields.split_whitespace().collect();
+    match parts.as_slice() {
+        [_, version, hash] => (
+            body,
+            Some((
+                version.trim_start_matches('v').to_string(),
+                hash.to_string(),
+            )),
+        ),
+        _ => (installed, None),
+    }
+}
+
+/// Strip the version stamp, returning the skill content as bundled.
+pub fn strip_stamp(installed: &str) -> &str {
+    parse_stamp(installed).0
+}
+
+/// Compare an installed file against its bundled content.
+pub fn file_status(installed: Option<&str>, bundled: &str) -> FileStatus {
+    let Some(installed) = installed else {
+        return FileStatus::Missing;
+    };
+    let (body, stamp) = parse_stamp(installed);
+    if body == bundled {
+        return FileStatus::Current;
+    }
+    match stamp {
+        Some((version, hash)) if hash == content_hash(body) => FileStatus::Outdated { version },
+        _ => FileStatus::Modified,
+    }
 }
 
-/// Install the bundled SKILL.md to the project.
+fn write_file(path: &Path, name: &str, content: &str) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, stamped(name, content))?;
+    Ok(())
+}
+
+/// Lines removed from / added to `installed` to reach `bundled` (order-insensitive).
+fn diff_summary(installed: &str, bundled: &str) -> (Vec<String>, Vec<String>) {
+    let old: Vec<&str> = installed.lines().collect();
+    let new: Vec<&str> = bundled.lines().collect();
+    let removed = old
+        .iter()
+        .filter(|l| !new.contains(l))
+        .map(|l| l.to_string())
+        .collect();
+    let added = new
+        .iter()
+        .filter(|l| !old.contains(l))
+        .map(|l| l.to_string())
+        .collect();
+    (removed, added)
+}
+
+/// Install the bundled SKILL.md to the project, overwriting any existing copy.
 /// When `root` is None, paths are relative to CWD.
 pub fn install_at(root: Option<&Path>) -> Result<()> {
-    config().install(root)
+    let dir = skills_dir(root).join("corky");
+    for (file, content) in bundled("corky").map(|s| s.files).unwrap_or_default() {
+        write_file(&dir.join(file), "corky", content)?;
+    }
+    Ok(())
 }
 
 /// Public entry point (CWD-relative, called from main).
 pub fn install() -> Result<()> {
-    install_at(None)
+    install_at(None)?;
+    println!("Installed .claude/skills/corky/SKILL.md (v{})", VERSION);
+    Ok(())
+}
+
+/// Agent tool to install a skill for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    /// `.claude/skills/{name}/SKILL.md`
+    Claude,
+    /// `.opencode/skill/{name}/SKILL.md` with name/description frontmatter
+    Opencode,
+    /// `.cursor/rules/{name}.mdc` rule with description frontmatter
+    Cursor,
+    /// Managed section in `AGENTS.md`, read by Codex, Aider, Zed, and others
+    Agents,
+}
+
+impl Target {
+    pub fn parse(s: &str) -> Result<Self> {
+        match s {
+            "claude" => Ok(Target::Claude),
+            "opencode" => Ok(Target::Opencode),
+            "cursor" => Ok(Target::Cursor),
+            "agents" => Ok(Target::Agents),
+            _ => bail!(
+                "Unknown target '{}'. Use claude, opencode, cursor, or agents.",
+                s
+            ),
+        }
+    }
+}
+
+/// Prefix `body` with a YAML frontmatter block.
+fn with_frontmatter(fields: &[(&str, &str)], body: &str) -> String {
+    let mut out = String::from("---\n");
+    for (key, value) in fields {
+        out.push_str(&format!("{}: {}\n", key, value));
+    }
+    out.push_str("---\n\n");
+    out.push_str(body);
+    out
+}
+
+/// Shift headings down one level (outside code fences) so an embedded skill
+/// nests under the host document's title.
+fn demote_headings(body: &str) -> String {
+    let mut in_fence = false;
+    let mut out = String::new();
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if !in_fence && line.starts_with('#') && line.trim_start_matches('#').starts_with(' ') {
+            out.push('#');
+        }
+        out.push_str(line);
+        out.push('\n');
+    }
+    out
+}
+
+/// Replace the `name` section of an AGENTS.md (between begin/end markers),
+/// or append it when absent.
+fn upsert_agents_section(existing: &str, name: &str, body: &str) -> String {
+    let begin = format!("{}{} begin -->", STAMP_PREFIX, name);
+    let end = format!("{}{} end -->", STAMP_PREFIX, name);
+    let section = format!(
+        "{}\n<!-- Generated by corky v{}; edits are overwritten by `corky skill install` -->\n\n{}\n{}\n",
+        begin,
+        VERSION,
+        demote_headings(body).trim_end(),
+        end
+    );
+    let range = existing
+        .find(&begin)
+        .zip(existing.find(&end))
+        .filter(|(start, stop)| start < stop);
+    if let Some((start, stop)) = range {
+        let after = existing[stop + end.len()..].trim_start_matches('\n');
+        return format!("{}{}{}", &existing[..start], section, after);
+    }
+    let mut out = existing.trim_end().to_string();
+    if !out.is_empty() {
+        out.push_str("\n\n");
+    }
+    out.push_str(&section);
+    out
+}
+
+/// Install a bundled skill for one agent tool. Returns the path written.
+/// When `root` is None, paths are relative to CWD.
+pub fn install_target_at(root: Option<&Path>, name: &str, target: Target) -> Result<PathBuf> {
+    let Some(skill) = bundled(name) else {
+        bail!("Unknown skill '{}'. Available: {}", name, available());
+    };
+    let base = root.unwrap_or(Path::new("."));
+    let body = skill
+        .files
+        .iter()
+        .find(|(file, _)| *file == "SKILL.md")
+        .map(|(_, content)| *content)
+        .unwrap_or_default();
+    match target {
+        Target::Claude => {
+            let dir = skills_dir(root).join(name);
+            for (file, content) in skill.files {
+                write_file(&dir.join(file), name, content)?;
+            }
+            Ok(dir.join("SKILL.md"))
+        }
+        Target::Opencode => {
+            let path = base.join(".opencode/skill").join(name).join("SKILL.md");
+            let content =
+                with_frontmatter(&[("name", name), ("description", skill.description)], body);
+            write_file(&path, name, &content)?;
+            Ok(path)
+        }
+        Target::Cursor => {
+            let path = base.join(".cursor/rules").join(format!("{}.mdc", name));
+            let content = with_frontmatter(
+                &[("description", skill.description), ("alwaysApply", "false")],
+                body,
+            );
+            write_file(&path, name, &content)?;
+            Ok(path)
+        }
+        Target::Agents => {
+            let path = base.join("AGENTS.md");
+            let existing = std::fs::read_to_string(&path).unwrap_or_default();
+            std::fs::write(&path, upsert_agents_section(&existing, name, body))?;
+            Ok(path)
+        }
+    }
+}
+
+/// corky skill install [--target claude|opencode|cursor|agents]
+pub fn install_target(name: &str, target: &str) -> Result<()> {
+    let path = install_target_at(None, name, Target::parse(target)?)?;
+    let shown = path.strip_prefix(".").unwrap_or(&path);
+    println!("Installed {} (v{})", shown.display(), VERSION);
+    Ok(())
+}
+
+/// Whether every bundled file of the corky skill is current.
+fn is_current(root: Option<&Path>) -> bool {
+    let dir = skills_dir(root).join("corky");
+    bundled("corky")
+        .map(|s| s.files)
+        .unwrap_or_default()
+        .iter()
+        .all(|(file, content)| {
+            let installed = std::fs::read_to_string(dir.join(file)).ok();
+            file_status(installed.as_deref(), content) == FileStatus::Current
+        })
 }
 
 /// Check if the installed skill matches the bundled version.
 /// When `root` is None, paths are relative to CWD.
 pub fn check_at(root: Option<&Path>) -> Result<()> {
-    let up_to_date = config().check(root)?;
-    if !up_to_date {
-        std::process::exit(1);
+    if !is_current(root) {
+        return Err(CorkyError::Validation(format!(
+            "Skill is missing or out of date (binary v{}). Run: corky skill update",
+            VERSION
+        ))
+        .into());
     }
+    println!("Skill is up to date (v{})", VERSION);
     Ok(())
 }
 
@@ -44,20 +358,201 @@ pub fn check() -> Result<()> {
     check_at(None)
 }
 
+/// One row of `corky skill list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkillEntry {
+    pub name: String,
+    /// Stamped version of the installed SKILL.md, if any.
+    pub version: Option<String>,
+    /// "current", "outdated", "modified", "not installed", or "unmanaged".
+    pub status: String,
+}
+
+/// Installed skills under `.claude/skills/` plus bundled skills not yet installed.
+pub fn list_at(root: Option<&Path>) -> Result<Vec<SkillEntry>> {
+    let dir = skills_dir(root);
+    let mut names: Vec<String> = BUNDLED.iter().map(|s| s.name.to_string()).collect();
+    if dir.is_dir() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            if entry.path().is_dir() && !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names.sort();
+
+    let mut entries = Vec::new();
+    for name in names {
+        let skill_md = std::fs::read_to_string(dir.join(&name).join("SKILL.md")).ok();
+        let version = skill_md
+            .as_deref()
+            .and_then(|s| parse_stamp(s).1)
+            .map(|(v, _)| v);
+        let status = match bundled(&name) {
+            None => "unmanaged".to_string(),
+            Some(skill) => {
+                let statuses: Vec<FileStatus> = skill
+                    .files
+                    .iter()
+                    .map(|(file, content)| {
+                        let installed = std::fs::read_to_string(dir.join(&name).join(file)).ok();
+                        file_status(installed.as_deref(), content)
+                    })
+                    .collect();
+                let status = if statuses.iter().all(|s| *s == FileStatus::Missing) {
+                    "not installed"
+                } else if statuses.contains(&FileStatus::Modified) {
+                    "modified"
+                } else if statuses.iter().all(|s| *s == FileStatus::Current) {
+                    "current"
+                } else {
+                    "outdated"
+                };
+                status.to_string()
+            }
+        };
+        entries.push(SkillEntry {
+            name,
+            version,
+            status,
+        });
+    }
+    Ok(entries)
+}
+
+/// corky skill list
+pub fn list() -> Result<()> {
+    let entries = list_at(None)?;
+    let width = entries.iter().map(|e| e.name.len()).max().unwrap_or(0);
+    for e in &entries {
+        let version = e
+            .version
+            .as_deref()
+            .map(|v| format!("v{}", v))
+            .unwrap_or_default();
+        println!("  {:<width$}  {:<9}  {}", e.name, version, e.status);
+    }
+    Ok(())
+}
+
+/// Refresh installed bundled skills. Locally modified files are left alone
+/// (with a diff summary) unless `force`. Returns the number of files written.
+pub fn update_at(root: Option<&Path>, name: Option<&str>, force: bool) -> Result<usize> {
+    let targets: Vec<&BundledSkill> = match name {
+        Some(n) => match bundled(n) {
+            Some(skill) => vec![skill],
+            None => bail!("Unknown skill '{}'. Available: {}", n, available()),
+        },
+        // Without a name, only refresh skills that are already installed.
+        None => BUNDLED
+            .iter()
+            .filter(|s| skills_dir(root).join(s.name).exists())
+            .collect(),
+    };
+
+    let mut written = 0;
+    for bundle in targets {
+        let skill = bundle.name;
+        let dir = skills_dir(root).join(skill);
+        for (file, content) in bundle.files {
+            let path = dir.join(file);
+            let installed = std::fs::read_to_string(&path).ok();
+            let label = format!("{}/{}", skill, file);
+            match file_status(installed.as_deref(), content) {
+                FileStatus::Current => {
+                    // Refresh the stamp if only the binary version changed.
+                    if installed.as_deref() != Some(stamped(skill, content).as_str()) {
+                        write_file(&path, skill, content)?;
+                    }
+                    println!("  {}: up to date", label);
+                    continue;
+                }
+                FileStatus::Missing => println!("  {}: installed v{}", label, VERSION),
+                FileStatus::Outdated { version } => {
+                    println!("  {}: updated v{} -> v{}", label, version, VERSION)
+                }
+                FileStatus::Modified => {
+                    let body = strip_stamp(installed.as_deref().unwrap_or_default());
+                    let (removed, added) = diff_summary(body, content);
+                    if !force {
+                        println!(
+                            "  {}: locally modified (-{} +{} lines), skipped. Use --force to overwrite.",
+                            label,
+                            removed.len(),
+                            added.len()
+                        );
+                        for line in removed.iter().take(5) {
+                            println!("    - {}", line);
+                        }
+                        for line in added.iter().take(5) {
+                            println!("    + {}", line);
+                        }
+                        continue;
+                    }
+                    println!("  {}: overwrote local changes (v{})", label, VERSION);
+                }
+            }
+            write_file(&path, skill, content)?;
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// corky skill update [NAME] [--force]
+pub fn update(name: Option<&str>, force: bool) -> Result<()> {
+    update_at(None, name, force)?;
+    Ok(())
+}
+
+/// Remove an installed bundled skill directory.
+pub fn uninstall_at(root: Option<&Path>, name: &str) -> Result<()> {
+    if bundled(name).is_none() {
+        bail!(
+            "'{}' is not a corky skill. Available: {}",
+            name,
+            available()
+        );
+    }
+    let dir = skills_dir(root).join(name);
+    if !dir.exists() {
+        bail!("Skill '{}' is not installed", name);
+    }
+    std::fs::remove_dir_all(&dir)?;
+    Ok(())
+}
+
+/// corky skill uninstall NAME
+pub fn uninstall(name: &str) -> Result<()> {
+    uninstall_at(None, name)?;
+    println!("Removed .claude/skills/{}/", name);
+    Ok(())
+}
+
 /// CLI entry point for `corky install-skill`.
-/// The `name` parameter is accepted for backward compatibility but ignored —
-/// all corky capabilities are bundled into a single skill.
+/// `email` is accepted for backward compatibility and installs `corky`.
 pub fn run(name: &str) -> Result<()> {
-    if name != "email" && name != "corky" {
-        anyhow::bail!("Unknown skill '{}'. Available: corky (or 'email' for compat)", name);
+    match name {
+        "email" | "corky" => install(),
+        _ if bundled(name).is_some() => install_target(name, "claude"),
+        _ => anyhow::bail!(
+            "Unknown skill '{}'. Available: {} (or 'email' for compat)",
+            name,
+            available()
+        ),
     }
-    install()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn skill_path(dir: &tempfile::TempDir) -> PathBuf {
+        dir.path().join(".claude/skills/corky/SKILL.md")
+    }
+
     #[test]
     fn bundled_skill_is_not_empty() {
         assert!(!BUNDLED_SKILL.is_empty());
@@ -77,7 +572,8 @@ mod tests {
         let path = dir.path().join(".claude/skills/corky/SKILL.md");
         assert!(path.exists());
         let content = std::fs::read_to_string(&path).unwrap();
-        assert_eq!(content, BUNDLED_SKILL);
+        assert_eq!(strip_stamp(&content), BUNDLED_SKILL);
+        assert!(content.contains(&format!("corky v{}", VERSION)));
     }
 
     #[test]
@@ -89,7 +585,8 @@ mod tests {
 
         let path = dir.path().join(".claude/skills/corky/SKILL.md");
         let content = std::fs::read_to_string(&path).unwrap();
-        assert_eq!(content, BUNDLED_SKILL);
+        assert_eq!(strip_stamp(&content), BUNDLED_SKILL);
+        assert_eq!(content.matches(STAMP_PREFIX).count(), 1);
     }
 
     #[test]
@@ -103,7 +600,7 @@ mod tests {
         install_at(Some(dir.path())).unwrap();
 
         let content = std::fs::read_to_string(&path).unwrap();
-        assert_eq!(content, BUNDLED_SKILL);
+        assert_eq!(strip_stamp(&content), BUNDLED_SKILL);
     }
 
     #[test]
@@ -112,4 +609,135 @@ mod tests {
         // verify it doesn't bail with "unknown skill").
         // We can't easily test the full install here without mocking CWD.
     }
+
+    #[test]
+    fn file_status_distinguishes_outdated_from_modified() {
+        assert_eq!(file_status(None, "new\n"), FileStatus::Missing);
+        assert_eq!(
+            file_status(Some(&stamped("corky", "new\n")), "new\n"),
+            FileStatus::Current
+        );
+
+        let old = stamped("corky", "old\n").replace(&format!("v{}", VERSION), "v0.1.0");
+        assert_eq!(
+            file_status(Some(&old), "new\n"),
+            FileStatus::Outdated {
+                version: "0.1.0".to_string()
+            }
+        );
+
+        let edited = stamped("corky", "old\n").replacen("old", "mine", 1);
+        assert_eq!(file_status(Some(&edited), "new\n"), FileStatus::Modified);
+        assert_eq!(
+            file_status(Some("unstamped\n"), "new\n"),
+            FileStatus::Modified
+        );
+    }
+
+    #[test]
+    fn update_skips_modified_unless_forced() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = skill_path(&dir);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "my own notes\n").unwrap();
+
+        assert_eq!(update_at(Some(dir.path()), None, false).unwrap(), 0);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "my own notes\n");
+
+        assert_eq!(update_at(Some(dir.path()), Some("corky"), true).unwrap(), 1);
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(strip_stamp(&content), BUNDLED_SKILL);
+
+        assert!(update_at(Some(dir.path()), Some("nope"), false).is_err());
+    }
+
+    #[test]
+    fn list_and_uninstall() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join(".claude/skills/other")).unwrap();
+
+        let entries = list_at(Some(dir.path())).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].name, "corky");
+        assert_eq!(entries[0].status, "not installed");
+        assert_eq!(entries[1].status, "unmanaged");
+
+        install_at(Some(dir.path())).unwrap();
+        let entries = list_at(Some(dir.path())).unwrap();
+        assert_eq!(entries[0].status, "current");
+        assert_eq!(entries[0].version.as_deref(), Some(VERSION));
+
+        assert!(uninstall_at(Some(dir.path()), "other").is_err());
+        uninstall_at(Some(dir.path()), "corky").unwrap();
+        assert!(!skill_path(&dir).exists());
+        assert!(uninstall_at(Some(dir.path()), "corky").is_err());
+    }
+
+    #[test]
+    fn install_targets_write_tool_specific_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = Some(dir.path());
+
+        let path = install_target_at(root, "corky", Target::Opencode).unwrap();
+        assert!(path.ends_with(".opencode/skill/corky/SKILL.md"));
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("---\nname: corky\ndescription: "));
+        assert!(strip_stamp(&content).ends_with(BUNDLED_SKILL));
+
+        let path = install_target_at(root, "corky", Target::Cursor).unwrap();
+        assert!(path.ends_with(".cursor/rules/corky.mdc"));
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("alwaysApply: false\n---\n"));
+
+        let path = install_target_at(root, "corky", Target::Claude).unwrap();
+        assert_eq!(path, skill_path(&dir));
+
+        assert!(install_target_at(root, "nope", Target::Claude).is_err());
+        assert!(Target::parse("vim").is_err());
+    }
+
+    #[test]
+    fn agents_target_replaces_its_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let agents = dir.path().join("AGENTS.md");
+        std::fs::write(&agents, "# My Project\n\nHouse rules.\n").unwrap();
+
+        install_target_at(Some(dir.path()), "corky", Target::Agents).unwrap();
+        install_target_at(Some(dir.path()), "corky", Target::Agents).unwrap();
+
+        let content = std::fs::read_to_string(&agents).unwrap();
+        assert!(
+            content.starts_with("# My Project\n\nHouse rules.\n\n<!-- corky-skill corky begin -->")
+        );
+        assert_eq!(content.matches("corky-skill corky begin").count(), 1);
+        assert!(content.contains("\n## Corky"));
+        assert!(content.ends_with("<!-- corky-skill corky end -->\n"));
+    }
+
+    #[test]
+    fn demote_headings_skips_code_fences() {
+        let body = "# Title\n```\n# comment\n```\n## Sub\n";
+        assert_eq!(
+            demote_headings(body),
+            "## Title\n```\n# comment\n```\n### Sub\n"
+        );
+    }
+
+    #[test]
+    fn triage_skill_installs_alongside_corky() {
+        let dir = tempfile::tempdir().unwrap();
+        install_at(Some(dir.path())).unwrap();
+        let path = install_target_at(Some(dir.path()), "triage", Target::Claude).unwrap();
+        assert!(path.ends_with(".claude/skills/triage/SKILL.md"));
+        assert_eq!(
+            strip_stamp(&std::fs::read_to_string(&path).unwrap()),
+            TRIAGE_SKILL
+        );
+        assert!(TRIAGE_SKILL.contains("corky unanswered"));
+
+        let entries = list_at(Some(dir.path())).unwrap();
+        let names: Vec<&str> = entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, vec!["corky", "triage"]);
+        assert!(entries.iter().all(|e| e.status == "current"));
+    }
 }
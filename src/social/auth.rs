@@ -370,6 +370,7 @@ pub fn run(platform: Platform, profile_name: Option<&str>) -> Result<()> {
 /// Uses toml_edit for format-preserving writes.
 fn update_profile_urn(profile_name: &str, platform: Platform, urn: &str) -> Result<()> {
     let path = crate::resolve::corky_toml();
+    let _lock = crate::config::lock::lock(&path)?;
     let content = if path.exists() {
         std::fs::read_to_string(&path)?
     } else {
@@ -399,10 +400,7 @@ fn update_profile_urn(profile_name: &str, platform: Platform, urn: &str) -> Resu
     let platform_table = profile[platform_key].as_table_mut().unwrap();
     platform_table.insert("urn", toml_edit::value(urn));
 
-    if let Some(parent) = path.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
-    std::fs::write(&path, doc.to_string())?;
+    crate::config::lock::write(&path, &doc.to_string())?;
     println!("Updated .corky.toml: profiles.{}.{}.urn = {}", profile_name, platform_key, urn);
     Ok(())
 }
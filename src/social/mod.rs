@@ -289,12 +289,13 @@ pub fn run_rename_author(old: &str, new: &str) -> Result<()> {
     // Rename in .corky.toml [profiles] section (or fallback profiles.toml)
     let corky_path = resolve::corky_toml();
     if corky_path.exists() {
+        let _lock = crate::config::lock::lock(&corky_path)?;
         let content = std::fs::read_to_string(&corky_path)?;
         let mut doc = content.parse::<toml_edit::DocumentMut>()?;
         if let Some(profiles_table) = doc.get_mut("profiles").and_then(|v| v.as_table_mut()) {
             if let Some(item) = profiles_table.remove(old) {
                 profiles_table.insert(new, item);
-                std::fs::write(&corky_path, doc.to_string())?;
+                crate::config::lock::write(&corky_path, &doc.to_string())?;
                 println!("Renamed profile '{}' -> '{}' in .corky.toml", old, new);
                 count += 1;
             }
@@ -1,6 +1,6 @@
 //! Publish orchestration: draft → resolve author → get token → upload images → API → update draft.
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Result};
 use chrono::Utc;
 use std::path::Path;
 
@@ -14,6 +14,9 @@ use super::token_store::TokenStore;
 /// Publish a social draft file. When `dry_run` is true, validates everything
 /// (auth, images) but prints the payload instead of creating the post.
 pub fn publish(path: &Path, dry_run: bool) -> Result<()> {
+    if !dry_run {
+        crate::util::ensure_unrestricted("Publishing posts")?;
+    }
     let content = std::fs::read_to_string(path)?;
     let draft = SocialDraft::parse(&content)?;
 
@@ -94,7 +97,7 @@ pub fn publish(path: &Path, dry_run: bool) -> Result<()> {
     }
 
     // Call platform API
-    let (post_id, post_url) = match platform {
+    let result = match platform {
         Platform::LinkedIn => {
             linkedin::create_post(
                 &token.access_token,
@@ -102,13 +105,16 @@ pub fn publish(path: &Path, dry_run: bool) -> Result<()> {
                 &draft.body,
                 &draft.meta.visibility,
                 &image_urns,
-            )?
+            )
         }
         Platform::Youtube => {
-            publish_youtube(path, &draft, &token.access_token)?
+            publish_youtube(path, &draft, &token.access_token)
         }
-        _ => bail!("Publishing not yet implemented for {}", platform),
+        _ => Err(anyhow!("Publishing not yet implemented for {}", platform)),
     };
+    let target = format!("{} {}", platform, path.display());
+    crate::audit::record("post publish", &target, &result);
+    let (post_id, post_url) = result?;
 
     // Update draft frontmatter
     let mut draft = draft;
@@ -1,31 +1,759 @@
-//! Gmail OAuth setup.
+//! OAuth setup for mail accounts (`corky sync-auth`).
 //!
-//! For now, this prints instructions to use the Python fallback.
-//! Full OAuth flow can be implemented later.
+//! Supports Google, Microsoft, and any OIDC provider with a discovery
+//! document, via a localhost-redirect (browser) flow or the device-code flow.
+//! Tokens are kept in the system keyring, falling back to the shared token
+//! store when no keyring is available, and refreshed automatically before
+//! sync and draft sending. Accounts with `auth = "oauth2"` log in with the
+//! access token over SASL XOAUTH2 (`accounts::account_secret`).
 
-use anyhow::Result;
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Utc};
+use std::collections::HashMap;
 
+use crate::accounts::{get_default_account, load_accounts, Account, OAuthConfig};
+use crate::config::corky_config;
 use crate::resolve;
+use crate::social::token_store::{tokens_path, StoredToken, TokenStore};
 
-pub fn run() -> Result<()> {
+const REDIRECT_URI: &str = "http://127.0.0.1:8484/callback";
+const CALLBACK_TIMEOUT_SECS: u64 = 120;
+
+/// Keyring service name; entries are keyed by [`token_key`].
+#[cfg(feature = "keyring")]
+const KEYRING_SERVICE: &str = "corky";
+
+const GOOGLE_SCOPES: &[&str] = &["https://mail.google.com/"];
+const MICROSOFT_SCOPES: &[&str] = &[
+    "https://outlook.office.com/IMAP.AccessAsUser.All",
+    "https://outlook.office.com/SMTP.Send",
+    "offline_access",
+];
+const OIDC_SCOPES: &[&str] = &["openid", "email", "offline_access"];
+
+/// How the user authorizes corky.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flow {
+    /// Open a browser; the provider redirects back to a local callback.
+    Browser,
+    /// Show a code to enter on another device; poll until approved.
+    Device,
+}
+
+/// An OAuth identity provider.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Provider {
+    Google,
+    Microsoft { tenant: String },
+    Oidc { issuer: String },
+}
+
+/// Where to send the user and the token requests.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Endpoints {
+    pub auth_url: String,
+    pub token_url: String,
+    pub device_url: Option<String>,
+    pub scopes: Vec<String>,
+}
+
+impl Provider {
+    /// The provider for an account: `[accounts.NAME.oauth] provider`, or
+    /// inferred from the account's `provider` preset.
+    pub fn for_account(acct: &Account) -> Result<Self> {
+        let cfg = acct.oauth.clone().unwrap_or_default();
+        let name = if cfg.provider.is_empty() {
+            match acct.provider.as_str() {
+                "gmail" => "google",
+                "outlook" | "office365" | "microsoft" => "microsoft",
+                _ if !cfg.issuer.is_empty() => "oidc",
+                other => bail!(
+                    "No OAuth provider for account provider '{}'.\n\
+                     Set provider = \"google\", \"microsoft\", or \"oidc\" under [accounts.NAME.oauth].",
+                    other
+                ),
+            }
+        } else {
+            cfg.provider.as_str()
+        };
+        Ok(match name {
+            "google" | "gmail" => Provider::Google,
+            "microsoft" | "office365" | "outlook" => Provider::Microsoft {
+                tenant: if cfg.tenant.is_empty() {
+                    "common".to_string()
+                } else {
+                    cfg.tenant.clone()
+                },
+            },
+            "oidc" => {
+                if cfg.issuer.is_empty() {
+                    bail!(
+                        "OAuth provider 'oidc' needs issuer = \"https://...\" under [accounts.NAME.oauth]"
+                    );
+                }
+                Provider::Oidc {
+                    issuer: cfg.issuer.trim_end_matches('/').to_string(),
+                }
+            }
+            other => bail!(
+                "Unknown OAuth provider '{}'. Use \"google\", \"microsoft\", or \"oidc\".",
+                other
+            ),
+        })
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Provider::Google => "google",
+            Provider::Microsoft { .. } => "microsoft",
+            Provider::Oidc { .. } => "oidc",
+        }
+    }
+
+    /// Flow used when neither `flow` nor `--device`/`--browser` says otherwise.
+    /// Google doesn't allow the mail scope over the device flow.
+    pub fn default_flow(&self) -> Flow {
+        match self {
+            Provider::Google => Flow::Browser,
+            Provider::Microsoft { .. } => Flow::Device,
+            Provider::Oidc { .. } => Flow::Browser,
+        }
+    }
+
+    /// Endpoints and default scopes. OIDC fetches its discovery document.
+    pub fn endpoints(&self) -> Result<Endpoints> {
+        let scopes = |s: &[&str]| s.iter().map(|s| s.to_string()).collect();
+        match self {
+            Provider::Google => Ok(Endpoints {
+                auth_url: "https://accounts.google.com/o/oauth2/v2/auth".to_string(),
+                token_url: "https://oauth2.googleapis.com/token".to_string(),
+                device_url: Some("https://oauth2.googleapis.com/device/code".to_string()),
+                scopes: scopes(GOOGLE_SCOPES),
+            }),
+            Provider::Microsoft { tenant } => {
+                let base = format!("https://login.microsoftonline.com/{}/oauth2/v2.0", tenant);
+                Ok(Endpoints {
+                    auth_url: format!("{}/authorize", base),
+                    token_url: format!("{}/token", base),
+                    device_url: Some(format!("{}/devicecode", base)),
+                    scopes: scopes(MICROSOFT_SCOPES),
+                })
+            }
+            Provider::Oidc { issuer } => {
+                let url = format!("{}/.well-known/openid-configuration", issuer);
+                let doc: serde_json::Value = ureq::get(&url)
+                    .call()
+                    .with_context(|| format!("Fetching {}", url))?
+                    .into_json()?;
+                parse_discovery(&doc, scopes(OIDC_SCOPES))
+            }
+        }
+    }
+}
+
+/// Endpoints from an OIDC discovery document.
+pub fn parse_discovery(doc: &serde_json::Value, scopes: Vec<String>) -> Result<Endpoints> {
+    let field = |name: &str| doc[name].as_str().map(str::to_string);
+    Ok(Endpoints {
+        auth_url: field("authorization_endpoint")
+            .ok_or_else(|| anyhow::anyhow!("Discovery document has no authorization_endpoint"))?,
+        token_url: field("token_endpoint")
+            .ok_or_else(|| anyhow::anyhow!("Discovery document has no token_endpoint"))?,
+        device_url: field("device_authorization_endpoint"),
+        scopes,
+    })
+}
+
+/// OAuth client registration. Public clients (device flow, PKCE) may have
+/// no secret.
+struct ClientCredentials {
+    client_id: String,
+    client_secret: Option<String>,
+}
+
+/// Resolve the client for an account: `[accounts.NAME.oauth]` first, then
+/// for Google the `[gmail]` section, `credentials.json`, or env vars.
+fn client_credentials(acct: &Account, provider: &Provider) -> Result<ClientCredentials> {
+    let cfg = acct.oauth.clone().unwrap_or_default();
+    if !cfg.client_id.is_empty() || !cfg.client_id_cmd.is_empty() {
+        let client_id = crate::util::resolve_secret(
+            &cfg.client_id,
+            &cfg.client_id_cmd,
+            "OAuth client_id (check [accounts.NAME.oauth] in .corky.toml)",
+        )?;
+        let client_secret = if cfg.client_secret.is_empty() && cfg.client_secret_cmd.is_empty() {
+            None
+        } else {
+            Some(crate::util::resolve_secret(
+                &cfg.client_secret,
+                &cfg.client_secret_cmd,
+                "OAuth client_secret (check [accounts.NAME.oauth] in .corky.toml)",
+            )?)
+        };
+        return Ok(ClientCredentials {
+            client_id,
+            client_secret,
+        });
+    }
+    if *provider != Provider::Google {
+        bail!(
+            "No OAuth client for this account.\n\
+             Register an app with {} and set client_id under [accounts.NAME.oauth].",
+            provider.name()
+        );
+    }
+
+    if let Some(gmail) = corky_config::try_load_config(None).and_then(|c| c.gmail) {
+        if !gmail.client_id.is_empty() || !gmail.client_id_cmd.is_empty() {
+            return Ok(ClientCredentials {
+                client_id: crate::util::resolve_secret(
+                    &gmail.client_id,
+                    &gmail.client_id_cmd,
+                    "Gmail client_id (check [gmail] in .corky.toml)",
+                )?,
+                client_secret: Some(crate::util::resolve_secret(
+                    &gmail.client_secret,
+                    &gmail.client_secret_cmd,
+                    "Gmail client_secret (check [gmail] in .corky.toml)",
+                )?),
+            });
+        }
+    }
     let creds_file = resolve::credentials_json();
-    if !creds_file.exists() {
-        anyhow::bail!(
-            "credentials.json not found.\n\
-             Download it from Google Cloud Console → Clients → your Desktop app → Download JSON\n\
-             and save it as credentials.json in the project root."
+    if creds_file.exists() {
+        let doc: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&creds_file)?)
+                .with_context(|| format!("Parsing {}", creds_file.display()))?;
+        return parse_credentials_json(&doc)
+            .ok_or_else(|| anyhow::anyhow!("{} has no client_id", creds_file.display()));
+    }
+    let client_id = std::env::var("CORKY_GMAIL_CLIENT_ID").context(
+        "Gmail OAuth client not found.\n\
+         Set [gmail] or [accounts.NAME.oauth] in .corky.toml, save credentials.json from\n\
+         Google Cloud Console, or set CORKY_GMAIL_CLIENT_ID.",
+    )?;
+    Ok(ClientCredentials {
+        client_id,
+        client_secret: std::env::var("CORKY_GMAIL_CLIENT_SECRET").ok(),
+    })
+}
+
+/// Client from a Google Cloud Console download (`installed` or `web` app).
+fn parse_credentials_json(doc: &serde_json::Value) -> Option<ClientCredentials> {
+    let app = doc.get("installed").or_else(|| doc.get("web"))?;
+    Some(ClientCredentials {
+        client_id: app["client_id"].as_str()?.to_string(),
+        client_secret: app["client_secret"].as_str().map(str::to_string),
+    })
+}
+
+/// Token store key for an account.
+pub fn token_key(account: &str) -> String {
+    format!("imap:{}", account)
+}
+
+#[cfg(feature = "keyring")]
+fn keyring_entry(key: &str) -> Result<keyring::Entry> {
+    Ok(keyring::Entry::new(KEYRING_SERVICE, key)?)
+}
+
+/// The stored token for an account, from the keyring or the token store.
+pub fn load_token(account: &str) -> Result<Option<StoredToken>> {
+    let key = token_key(account);
+    #[cfg(feature = "keyring")]
+    {
+        match keyring_entry(&key).and_then(|e| Ok(e.get_password()?)) {
+            Ok(json) => return Ok(Some(serde_json::from_str(&json)?)),
+            Err(e) => {
+                let missing = matches!(
+                    e.downcast_ref::<keyring::Error>(),
+                    Some(keyring::Error::NoEntry)
+                );
+                if !missing {
+                    eprintln!(
+                        "  Keyring unavailable ({}); checking {}",
+                        e,
+                        tokens_path().display()
+                    );
+                }
+            }
+        }
+    }
+    Ok(TokenStore::load()?.tokens.get(&key).cloned())
+}
+
+/// Store an account's token: in the keyring when possible, else in the
+/// token store. Returns where it went.
+pub fn save_token(account: &str, token: &StoredToken) -> Result<String> {
+    let key = token_key(account);
+    #[cfg(feature = "keyring")]
+    {
+        let json = serde_json::to_string(token)?;
+        match keyring_entry(&key).and_then(|e| Ok(e.set_password(&json)?)) {
+            Ok(()) => {
+                // Don't leave a stale plaintext copy behind.
+                let mut store = TokenStore::load()?;
+                if store.remove(&key).is_some() {
+                    store.save()?;
+                }
+                return Ok("system keyring".to_string());
+            }
+            Err(e) => eprintln!(
+                "  Keyring unavailable ({}); using {}",
+                e,
+                tokens_path().display()
+            ),
+        }
+    }
+    let mut store = TokenStore::load()?;
+    store.upsert(key, token.clone());
+    store.save()?;
+    Ok(tokens_path().display().to_string())
+}
+
+/// Percent-encode for query strings and form bodies.
+fn urlencode(s: &str) -> String {
+    crate::cal::auth::urlencode_pub(s)
+}
+
+fn form(pairs: &[(&str, &str)]) -> String {
+    pairs
+        .iter()
+        .map(|(k, v)| format!("{}={}", k, urlencode(v)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+/// POST a form to a token endpoint, returning the JSON body. OAuth error
+/// responses come back as `Err` carrying the body.
+fn post_form(url: &str, body: &str) -> Result<serde_json::Value, serde_json::Value> {
+    match ureq::post(url)
+        .set("Content-Type", "application/x-www-form-urlencoded")
+        .set("Accept", "application/json")
+        .send_string(body)
+    {
+        Ok(resp) => resp
+            .into_json()
+            .map_err(|e| serde_json::json!({ "error": e.to_string() })),
+        Err(ureq::Error::Status(status, resp)) => Err(resp
+            .into_json()
+            .unwrap_or_else(|_| serde_json::json!({ "error": format!("HTTP {}", status) }))),
+        Err(e) => Err(serde_json::json!({ "error": e.to_string() })),
+    }
+}
+
+fn oauth_error(body: &serde_json::Value) -> String {
+    let error = body["error"].as_str().unwrap_or("unknown error");
+    match body["error_description"].as_str() {
+        Some(desc) => format!("{}: {}", error, desc),
+        None => error.to_string(),
+    }
+}
+
+/// Token from a token-endpoint response.
+pub fn parse_token_response(
+    body: &serde_json::Value,
+    provider: &str,
+    scopes: &[String],
+) -> Result<StoredToken> {
+    let access_token = body["access_token"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing access_token in response"))?
+        .to_string();
+    let expires_in = body["expires_in"].as_i64().unwrap_or(3600);
+    Ok(StoredToken {
+        access_token,
+        refresh_token: body["refresh_token"].as_str().map(str::to_string),
+        expires_at: Utc::now() + Duration::seconds(expires_in),
+        scopes: scopes.to_vec(),
+        platform: provider.to_string(),
+    })
+}
+
+fn client_pairs<'a>(creds: &'a ClientCredentials, pairs: &mut Vec<(&'a str, &'a str)>) {
+    pairs.push(("client_id", &creds.client_id));
+    if let Some(secret) = &creds.client_secret {
+        pairs.push(("client_secret", secret));
+    }
+}
+
+/// Unpadded base64url (RFC 4648 §5), as PKCE wants it.
+fn base64url(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, b)| n | ((*b as u32) << (16 - 8 * i)));
+        for i in 0..=chunk.len() {
+            out.push(ALPHABET[((n >> (18 - 6 * i)) & 63) as usize] as char);
+        }
+    }
+    out
+}
+
+/// 32 bytes from the OS random source, base64url: for `state` and the PKCE
+/// verifier (43 characters, within RFC 7636's 43-128).
+fn random_token() -> Result<String> {
+    let mut buf = [0u8; 32];
+    getrandom::getrandom(&mut buf)
+        .map_err(|e| anyhow::anyhow!("No system randomness available: {}", e))?;
+    Ok(base64url(&buf))
+}
+
+/// The PKCE `S256` challenge for `verifier`.
+fn pkce_challenge(verifier: &str) -> String {
+    use sha2::{Digest, Sha256};
+    base64url(&Sha256::digest(verifier.as_bytes()))
+}
+
+fn browser_flow(
+    provider: &Provider,
+    endpoints: &Endpoints,
+    creds: &ClientCredentials,
+) -> Result<StoredToken> {
+    let state = random_token()?;
+    let verifier = random_token()?;
+    let scope = endpoints.scopes.join(" ");
+    let mut url = format!(
+        "{}?response_type=code&client_id={}&redirect_uri={}&state={}&scope={}\
+         &code_challenge={}&code_challenge_method=S256",
+        endpoints.auth_url,
+        urlencode(&creds.client_id),
+        urlencode(REDIRECT_URI),
+        urlencode(&state),
+        urlencode(&scope),
+        urlencode(&pkce_challenge(&verifier)),
+    );
+    if *provider == Provider::Google {
+        url.push_str("&access_type=offline&prompt=consent");
+    }
+
+    println!("Opening browser for {} authorization...", provider.name());
+    println!("If the browser doesn't open, visit:\n  {}\n", url);
+    if open::that(&url).is_err() {
+        eprintln!("Could not open browser automatically.");
+    }
+
+    println!("Waiting for callback on {}...", REDIRECT_URI);
+    let server = tiny_http::Server::http("127.0.0.1:8484")
+        .map_err(|e| anyhow::anyhow!("Failed to start callback server: {}", e))?;
+    let request = server
+        .recv_timeout(std::time::Duration::from_secs(CALLBACK_TIMEOUT_SECS))
+        .map_err(|e| anyhow::anyhow!("Callback server error: {}", e))?
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "Timed out waiting for OAuth callback ({}s)",
+                CALLBACK_TIMEOUT_SECS
+            )
+        })?;
+    let url_str = request.url().to_string();
+    let query = url_str.split('?').nth(1).unwrap_or("");
+    let (code, cb_state) = crate::social::auth::parse_callback(query)?;
+    let _ = request.respond(tiny_http::Response::from_string(
+        "Mail authorization successful! You can close this tab.",
+    ));
+    if cb_state != state {
+        bail!(
+            "State mismatch (CSRF). Expected '{}', got '{}'",
+            state,
+            cb_state
         );
     }
 
-    println!("Gmail OAuth setup is not yet implemented in the Rust port.");
-    println!();
-    println!("For now, use the Python version for one-time OAuth setup:");
-    println!("  pip install corky==0.6.1");
-    println!("  corky sync-auth");
-    println!();
-    println!("Or use an app password instead:");
-    println!("  https://myaccount.google.com/apppasswords");
-    println!("  Add password_cmd to accounts.toml");
+    println!("Exchanging authorization code...");
+    let mut pairs = vec![
+        ("grant_type", "authorization_code"),
+        ("code", code.as_str()),
+        ("redirect_uri", REDIRECT_URI),
+        ("code_verifier", verifier.as_str()),
+    ];
+    client_pairs(creds, &mut pairs);
+    let body = post_form(&endpoints.token_url, &form(&pairs))
+        .map_err(|e| anyhow::anyhow!("Token exchange failed: {}", oauth_error(&e)))?;
+    parse_token_response(&body, provider.name(), &endpoints.scopes)
+}
 
+fn device_flow(
+    provider: &Provider,
+    endpoints: &Endpoints,
+    creds: &ClientCredentials,
+) -> Result<StoredToken> {
+    let Some(device_url) = &endpoints.device_url else {
+        bail!(
+            "{} has no device authorization endpoint; use --browser",
+            provider.name()
+        );
+    };
+    let scope = endpoints.scopes.join(" ");
+    let mut pairs = vec![("scope", scope.as_str())];
+    client_pairs(creds, &mut pairs);
+    let start = post_form(device_url, &form(&pairs))
+        .map_err(|e| anyhow::anyhow!("Device authorization failed: {}", oauth_error(&e)))?;
+    let device_code = start["device_code"]
+        .as_str()
+        .ok_or_else(|| anyhow::anyhow!("Missing device_code in response"))?
+        .to_string();
+    let user_code = start["user_code"].as_str().unwrap_or_default();
+    // Google says verification_url; RFC 8628 says verification_uri.
+    let verify = start["verification_uri"]
+        .as_str()
+        .or_else(|| start["verification_url"].as_str())
+        .unwrap_or_default();
+    let mut interval = start["interval"].as_u64().unwrap_or(5);
+    let expires_in = start["expires_in"].as_u64().unwrap_or(900);
+
+    println!(
+        "To authorize corky, visit:\n  {}\nand enter the code: {}\n",
+        verify, user_code
+    );
+    println!("Waiting for approval...");
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(expires_in);
+    let mut pairs = vec![
+        ("grant_type", "urn:ietf:params:oauth:grant-type:device_code"),
+        ("device_code", device_code.as_str()),
+    ];
+    client_pairs(creds, &mut pairs);
+    let body = form(&pairs);
+    loop {
+        if std::time::Instant::now() >= deadline {
+            bail!("Device code expired before it was approved; run sync-auth again");
+        }
+        std::thread::sleep(std::time::Duration::from_secs(interval));
+        match post_form(&endpoints.token_url, &body) {
+            Ok(resp) => return parse_token_response(&resp, provider.name(), &endpoints.scopes),
+            Err(e) => match e["error"].as_str() {
+                Some("authorization_pending") => {}
+                Some("slow_down") => interval += 5,
+                _ => bail!("Device authorization failed: {}", oauth_error(&e)),
+            },
+        }
+    }
+}
+
+/// Exchange a refresh token for a new access token. Providers that don't
+/// rotate refresh tokens keep the old one.
+fn refresh(
+    provider: &Provider,
+    endpoints: &Endpoints,
+    creds: &ClientCredentials,
+    refresh_token: &str,
+) -> Result<StoredToken> {
+    let mut pairs = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token),
+    ];
+    client_pairs(creds, &mut pairs);
+    let body = post_form(&endpoints.token_url, &form(&pairs))
+        .map_err(|e| anyhow::anyhow!("Token refresh failed: {}", oauth_error(&e)))?;
+    let mut token = parse_token_response(&body, provider.name(), &endpoints.scopes)?;
+    if token.refresh_token.is_none() {
+        token.refresh_token = Some(refresh_token.to_string());
+    }
+    Ok(token)
+}
+
+/// Endpoints with any `scopes` override from the account applied.
+fn account_endpoints(acct: &Account, provider: &Provider) -> Result<Endpoints> {
+    let mut endpoints = provider.endpoints()?;
+    if let Some(OAuthConfig { scopes, .. }) = &acct.oauth {
+        if !scopes.is_empty() {
+            endpoints.scopes = scopes.clone();
+        }
+    }
+    Ok(endpoints)
+}
+
+/// A valid access token for the account, refreshing (and re-storing) the
+/// stored one if it has expired.
+pub fn access_token(account_name: &str, acct: &Account) -> Result<String> {
+    let Some(token) = load_token(account_name)? else {
+        bail!(
+            "No OAuth token for account '{}'. Run: corky sync-auth --account {}",
+            account_name,
+            account_name
+        );
+    };
+    if token.is_valid() {
+        return Ok(token.access_token);
+    }
+    let Some(refresh_token) = token.refresh_token else {
+        bail!(
+            "OAuth token for account '{}' expired and can't be refreshed. Run: corky sync-auth --account {}",
+            account_name,
+            account_name
+        );
+    };
+    let provider = Provider::for_account(acct)?;
+    let endpoints = account_endpoints(acct, &provider)?;
+    let creds = client_credentials(acct, &provider)?;
+    let fresh = refresh(&provider, &endpoints, &creds, &refresh_token)?;
+    save_token(account_name, &fresh)?;
+    Ok(fresh.access_token)
+}
+
+/// Refresh an account's token ahead of a sync or send, if it was ever
+/// authorized with sync-auth. Failures are warnings: the account may still
+/// work with its password.
+pub fn refresh_stored(account_name: &str, acct: &Account) {
+    if !matches!(load_token(account_name), Ok(Some(_))) {
+        return;
+    }
+    if let Err(e) = access_token(account_name, acct) {
+        eprintln!(
+            "  Warning: OAuth token for '{}' could not be refreshed: {:#}\n  Run: corky sync-auth --account {}",
+            account_name, e, account_name
+        );
+    }
+}
+
+/// corky sync-auth [--account NAME] [--device | --browser]
+pub fn run(account: Option<&str>, flow: Option<Flow>) -> Result<()> {
+    let accounts: HashMap<String, Account> = load_accounts(None)?;
+    let (name, acct) = match account {
+        Some(name) => match accounts.get(name) {
+            Some(acct) => (name.to_string(), acct.clone()),
+            None => bail!(
+                "Unknown account: {}\nAvailable: {}",
+                name,
+                accounts.keys().cloned().collect::<Vec<_>>().join(", ")
+            ),
+        },
+        None => get_default_account(&accounts)?,
+    };
+
+    let provider = Provider::for_account(&acct)?;
+    let endpoints = account_endpoints(&acct, &provider)?;
+    let creds = client_credentials(&acct, &provider)?;
+    let configured_flow = match acct.oauth.as_ref().map(|o| o.flow.as_str()) {
+        Some("device") => Some(Flow::Device),
+        Some("browser") => Some(Flow::Browser),
+        Some("") | None => None,
+        Some(other) => bail!(
+            "Unknown OAuth flow '{}'. Use \"browser\" or \"device\".",
+            other
+        ),
+    };
+    let flow = flow
+        .or(configured_flow)
+        .unwrap_or_else(|| provider.default_flow());
+
+    println!("Account: {} ({}) via {}", name, acct.user, provider.name());
+    let token = match flow {
+        Flow::Browser => browser_flow(&provider, &endpoints, &creds)?,
+        Flow::Device => device_flow(&provider, &endpoints, &creds)?,
+    };
+    if token.refresh_token.is_none() {
+        eprintln!(
+            "  Warning: no refresh token returned; you'll need to re-run sync-auth when it expires"
+        );
+    }
+    let location = save_token(&name, &token)?;
+    println!("Token for '{}' stored in {}", name, location);
+    if acct.auth != "oauth2" {
+        println!(
+            "Set auth = \"oauth2\" under [accounts.{}] to log in with it instead of a password.",
+            name
+        );
+    }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_s256_challenge() {
+        assert_eq!(base64url(b"f"), "Zg");
+        assert_eq!(base64url(b"fo"), "Zm8");
+        assert_eq!(base64url(b"foo"), "Zm9v");
+        assert_eq!(base64url(&[0xfb, 0xff]), "-_8");
+        // SHA-256("abc") = ba7816bf 8f01cfea ..., base64url-encoded.
+        assert_eq!(
+            pkce_challenge("abc"),
+            "ungWv48Bz-pBQUDeXa4iI7ADYaOWF3qctBD_YfIAFa0"
+        );
+        let (a, b) = (random_token().unwrap(), random_token().unwrap());
+        assert_eq!(a.len(), 43);
+        assert_ne!(a, b);
+    }
+
+    fn account(provider: &str, oauth: Option<OAuthConfig>) -> Account {
+        Account {
+            provider: provider.to_string(),
+            oauth,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_provider_for_account() {
+        assert_eq!(
+            Provider::for_account(&account("gmail", None)).unwrap(),
+            Provider::Google
+        );
+        let ms = OAuthConfig {
+            provider: "microsoft".to_string(),
+            tenant: "contoso.onmicrosoft.com".to_string(),
+            ..Default::default()
+        };
+        let provider = Provider::for_account(&account("imap", Some(ms))).unwrap();
+        assert_eq!(provider.default_flow(), Flow::Device);
+        assert_eq!(
+            provider.endpoints().unwrap().token_url,
+            "https://login.microsoftonline.com/contoso.onmicrosoft.com/oauth2/v2.0/token"
+        );
+        let oidc = OAuthConfig {
+            issuer: "https://id.example.com/".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            Provider::for_account(&account("imap", Some(oidc))).unwrap(),
+            Provider::Oidc {
+                issuer: "https://id.example.com".to_string()
+            }
+        );
+        assert!(Provider::for_account(&account("imap", None)).is_err());
+    }
+
+    #[test]
+    fn test_parse_discovery() {
+        let doc = serde_json::json!({
+            "issuer": "https://id.example.com",
+            "authorization_endpoint": "https://id.example.com/authorize",
+            "token_endpoint": "https://id.example.com/token",
+        });
+        let endpoints = parse_discovery(&doc, vec!["openid".to_string()]).unwrap();
+        assert_eq!(endpoints.token_url, "https://id.example.com/token");
+        assert_eq!(endpoints.device_url, None);
+        assert!(parse_discovery(&serde_json::json!({}), Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_parse_credentials_json() {
+        let doc = serde_json::json!({
+            "installed": { "client_id": "abc.apps.googleusercontent.com", "client_secret": "shh" }
+        });
+        let creds = parse_credentials_json(&doc).unwrap();
+        assert_eq!(creds.client_id, "abc.apps.googleusercontent.com");
+        assert_eq!(creds.client_secret.as_deref(), Some("shh"));
+        assert!(parse_credentials_json(&serde_json::json!({})).is_none());
+    }
+
+    #[test]
+    fn test_parse_token_response() {
+        let body = serde_json::json!({
+            "access_token": "ya29.test",
+            "expires_in": 3599,
+            "refresh_token": "1//test",
+        });
+        let token = parse_token_response(&body, "google", &["s".to_string()]).unwrap();
+        assert_eq!(token.access_token, "ya29.test");
+        assert_eq!(token.refresh_token.as_deref(), Some("1//test"));
+        assert_eq!(token.platform, "google");
+        assert!(token.is_valid());
+        assert!(parse_token_response(&serde_json::json!({"error": "x"}), "google", &[]).is_err());
+    }
+}
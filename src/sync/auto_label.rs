@@ -0,0 +1,131 @@
+//! Trusted sender auto-labels: `[contacts.NAME] labels` are added to a
+//! thread when a message from one of the contact's `emails` is merged, so
+//! `[routing]` for those labels applies even when the mail arrived in a
+//! folder that doesn't carry the label (e.g. the plain inbox).
+
+use anyhow::Result;
+use std::collections::{BTreeMap, HashMap};
+
+use crate::address;
+use crate::config::contact::{load_contacts, Contact};
+
+/// Contact labels by sender address key ([`address::base`]), and by
+/// domain for `*@domain` wildcards.
+#[derive(Debug, Default)]
+pub struct AutoLabels {
+    by_address: HashMap<String, Vec<String>>,
+    by_domain: HashMap<String, Vec<String>>,
+}
+
+impl AutoLabels {
+    /// Index the labels of every contact that has both `emails` and `labels`.
+    pub fn from_contacts(contacts: &BTreeMap<String, Contact>) -> Self {
+        let mut by_address: HashMap<String, Vec<String>> = HashMap::new();
+        let mut by_domain: HashMap<String, Vec<String>> = HashMap::new();
+        for contact in contacts.values() {
+            for email in &contact.emails {
+                let labels = match address::wildcard_domain(email) {
+                    Some(domain) => by_domain.entry(domain).or_default(),
+                    None => by_address.entry(address::base(email)).or_default(),
+                };
+                for label in &contact.labels {
+                    let label = label.trim();
+                    if !label.is_empty() && !labels.iter().any(|l| l == label) {
+                        labels.push(label.to_string());
+                    }
+                }
+            }
+        }
+        by_address.retain(|_, labels| !labels.is_empty());
+        by_domain.retain(|_, labels| !labels.is_empty());
+        Self {
+            by_address,
+            by_domain,
+        }
+    }
+
+    /// Load from `[contacts.*]` in .corky.toml (empty if there are none).
+    pub fn load() -> Result<Self> {
+        Ok(Self::from_contacts(&load_contacts(None)?))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.by_address.is_empty() && self.by_domain.is_empty()
+    }
+
+    /// Labels for a message from `from` (`Name <email>` or bare), other
+    /// than `label` (the folder it was synced from). An address listed by
+    /// a contact wins over a `*@domain` wildcard.
+    pub fn labels_for(&self, from: &str, label: &str) -> Vec<String> {
+        self.by_address
+            .get(&address::base(from))
+            .or_else(|| address::domain_of(from).and_then(|d| self.by_domain.get(&d)))
+            .map(|labels| labels.iter().filter(|l| *l != label).cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_labels_for_trusted_sender() {
+        let mut contacts = BTreeMap::new();
+        contacts.insert(
+            "alex".to_string(),
+            Contact {
+                emails: vec!["alex@example.com".to_string(), "alex@work.com".to_string()],
+                labels: vec!["for-alex".to_string(), "clients".to_string()],
+                ..Default::default()
+            },
+        );
+        contacts.insert(
+            "sam".to_string(),
+            Contact {
+                emails: vec!["sam@example.com".to_string()],
+                ..Default::default()
+            },
+        );
+        let auto = AutoLabels::from_contacts(&contacts);
+        assert_eq!(
+            auto.labels_for("Alex <Alex+news@Example.com>", "INBOX"),
+            vec!["for-alex", "clients"]
+        );
+        assert_eq!(
+            auto.labels_for("alex@work.com", "clients"),
+            vec!["for-alex"]
+        );
+        assert!(auto.labels_for("Sam <sam@example.com>", "INBOX").is_empty());
+        assert!(auto.labels_for("", "INBOX").is_empty());
+    }
+
+    #[test]
+    fn test_labels_for_wildcard_domain() {
+        let mut contacts = BTreeMap::new();
+        contacts.insert(
+            "clientcorp".to_string(),
+            Contact {
+                emails: vec!["*@clientcorp.com".to_string()],
+                labels: vec!["clients".to_string()],
+                ..Default::default()
+            },
+        );
+        contacts.insert(
+            "pat".to_string(),
+            Contact {
+                emails: vec!["pat@clientcorp.com".to_string()],
+                labels: vec!["for-pat".to_string()],
+                ..Default::default()
+            },
+        );
+        let auto = AutoLabels::from_contacts(&contacts);
+        assert!(!auto.is_empty());
+        assert_eq!(
+            auto.labels_for("Jo <jo@ClientCorp.com>", "INBOX"),
+            vec!["clients"]
+        );
+        assert_eq!(auto.labels_for("pat@clientcorp.com", "INBOX"), vec!["for-pat"]);
+        assert!(auto.labels_for("jo@sub.clientcorp.com", "INBOX").is_empty());
+    }
+}
@@ -0,0 +1,272 @@
+//! Sender/subject blocklist: messages matching `[blocklist]` are skipped during sync.
+
+use anyhow::{bail, Result};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+use super::manifest::generate_manifest;
+use super::markdown::{is_part_file, read_thread, thread_files};
+use crate::address;
+use crate::config::corky_config::{self, BlocklistConfig};
+use crate::mailbox::find_unanswered::{collect_md_files, resolve_dirs, Scope};
+use crate::resolve;
+use crate::util::extract_email;
+
+/// Compiled `[blocklist]` rules.
+#[derive(Debug, Default)]
+pub struct Blocklist {
+    addresses: Vec<String>,
+    domains: Vec<String>,
+    subjects: Vec<Regex>,
+}
+
+impl Blocklist {
+    /// Compile a `[blocklist]` config. Errors on an invalid subject regex.
+    pub fn from_config(config: &BlocklistConfig) -> Result<Self> {
+        let mut subjects = Vec::new();
+        for pattern in &config.subjects {
+            match Regex::new(pattern) {
+                Ok(re) => subjects.push(re),
+                Err(e) => bail!("Invalid [blocklist] subject regex '{}': {}", pattern, e),
+            }
+        }
+        Ok(Self {
+            addresses: config.addresses.iter().map(|a| address::normalize(a)).collect(),
+            domains: config
+                .domains
+                .iter()
+                .map(|d| address::domain_to_ascii(d.trim().trim_start_matches('@')))
+                .collect(),
+            subjects,
+        })
+    }
+
+    /// Load and compile `[blocklist]` from .corky.toml (empty if absent).
+    pub fn load() -> Result<Self> {
+        let config = corky_config::try_load_config(None)
+            .and_then(|c| c.blocklist)
+            .unwrap_or_default();
+        Self::from_config(&config)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty() && self.domains.is_empty() && self.subjects.is_empty()
+    }
+
+    /// Whether the sender address is blocked (by address or domain).
+    pub fn blocks_sender(&self, from: &str) -> bool {
+        let Some(email) = extract_email(from).map(|e| address::normalize(&e)) else {
+            return false;
+        };
+        if self.addresses.contains(&email) {
+            return true;
+        }
+        let domain = email.rsplit('@').next().unwrap_or("");
+        self.domains
+            .iter()
+            .any(|d| domain == d.as_str() || domain.ends_with(&format!(".{}", d)))
+    }
+
+    /// Whether a message with this sender and subject should be skipped.
+    pub fn matches(&self, from: &str, subject: &str) -> bool {
+        self.blocks_sender(from) || self.subjects.iter().any(|re| re.is_match(subject))
+    }
+}
+
+/// A `corky block` argument: `user@host` is an address; `@host` or `host` is a domain.
+#[derive(Debug, PartialEq)]
+pub enum BlockEntry {
+    Address(String),
+    Domain(String),
+}
+
+impl BlockEntry {
+    pub fn parse(arg: &str) -> Self {
+        let arg = arg.trim().to_lowercase();
+        match arg.strip_prefix('@') {
+            Some(domain) => BlockEntry::Domain(domain.to_string()),
+            None if arg.contains('@') => BlockEntry::Address(arg),
+            None => BlockEntry::Domain(arg),
+        }
+    }
+
+    fn key_and_value(&self) -> (&'static str, &str) {
+        match self {
+            BlockEntry::Address(a) => ("addresses", a),
+            BlockEntry::Domain(d) => ("domains", d),
+        }
+    }
+}
+
+/// Add an entry to `[blocklist]` in .corky.toml.
+///
+/// Uses toml_edit for format-preserving edits.
+/// Returns Ok(true) if added, Ok(false) if already present.
+pub fn add_to_blocklist(entry: &BlockEntry, path: Option<&Path>) -> Result<bool> {
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => resolve::corky_toml(),
+    };
+    if !path.exists() {
+        bail!("Config not found at {}", path.display());
+    }
+
+    let _lock = crate::config::lock::lock(&path)?;
+    let content = std::fs::read_to_string(&path)?;
+    let mut doc = content.parse::<toml_edit::DocumentMut>()?;
+    let (key, value) = entry.key_and_value();
+
+    let table = doc
+        .entry("blocklist")
+        .or_insert_with(|| toml_edit::Item::Table(toml_edit::Table::new()));
+    let Some(table) = table.as_table_mut() else {
+        bail!("[blocklist] in {} is not a table", path.display());
+    };
+    let list = table
+        .entry(key)
+        .or_insert_with(|| toml_edit::value(toml_edit::Array::new()));
+    let Some(arr) = list.as_array_mut() else {
+        bail!("[blocklist] {} in {} is not an array", key, path.display());
+    };
+    if arr
+        .iter()
+        .any(|v| v.as_str().map(|s| s.eq_ignore_ascii_case(value)).unwrap_or(false))
+    {
+        return Ok(false);
+    }
+    arr.push(value);
+
+    crate::config::lock::write(&path, &doc.to_string())?;
+    Ok(true)
+}
+
+/// Conversation files in `dir` whose messages are all from blocked senders.
+pub fn blocked_threads(dir: &Path, blocklist: &Blocklist) -> Result<Vec<PathBuf>> {
+    let mut md_files = Vec::new();
+    collect_md_files(dir, &mut md_files)?;
+    md_files.sort();
+
+    let mut blocked = Vec::new();
+    for path in md_files {
+        if is_part_file(&path) {
+            continue;
+        }
+        let Some(thread) = read_thread(&path)? else {
+            continue;
+        };
+        if !thread.messages.is_empty()
+            && thread.messages.iter().all(|m| blocklist.blocks_sender(&m.from))
+        {
+            blocked.push(path);
+        }
+    }
+    Ok(blocked)
+}
+
+/// corky block ADDR [--delete]
+pub fn run(arg: &str, delete: bool) -> Result<()> {
+    let entry = BlockEntry::parse(arg);
+    let (key, value) = entry.key_and_value();
+    if add_to_blocklist(&entry, None)? {
+        println!("Added '{}' to [blocklist] {}", value, key);
+    } else {
+        println!("'{}' already in [blocklist] {}", value, key);
+    }
+
+    if delete {
+        let only_entry = Blocklist::from_config(&match &entry {
+            BlockEntry::Address(a) => BlocklistConfig {
+                addresses: vec![a.clone()],
+                ..Default::default()
+            },
+            BlockEntry::Domain(d) => BlocklistConfig {
+                domains: vec![d.clone()],
+                ..Default::default()
+            },
+        })?;
+        let mut found = Vec::new();
+        for (_, dir) in resolve_dirs(&Scope::All)? {
+            let threads = blocked_threads(&dir, &only_entry)?;
+            if !threads.is_empty() {
+                found.push((dir, threads));
+            }
+        }
+        let total: usize = found.iter().map(|(_, threads)| threads.len()).sum();
+        if total == 0 {
+            println!("No threads to delete");
+            return Ok(());
+        }
+        for path in found.iter().flat_map(|(_, threads)| threads) {
+            println!("  {}", path.display());
+        }
+        if !crate::output::confirm(&format!("Delete these {} thread(s)?", total))? {
+            println!("Kept {} thread(s)", total);
+            return Ok(());
+        }
+        for (dir, threads) in &found {
+            for path in threads {
+                for file in thread_files(path) {
+                    std::fs::remove_file(file)?;
+                }
+                println!("  Deleted: {}", path.display());
+            }
+            generate_manifest(dir)?;
+        }
+        println!("Deleted {} thread(s)", total);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blocklist() -> Blocklist {
+        Blocklist::from_config(&BlocklistConfig {
+            addresses: vec!["Spam@Example.com".to_string()],
+            domains: vec!["@junk.test".to_string()],
+            subjects: vec![r"(?i)^\[ad\]".to_string()],
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_blocklist_matches() {
+        let b = blocklist();
+        assert!(b.matches("Spammer <spam@example.com>", "Hello"));
+        assert!(b.matches("x@junk.test", "Hello"));
+        assert!(b.matches("x@mail.junk.test", "Hello"));
+        assert!(!b.matches("x@notjunk.test", "Hello"));
+        assert!(b.matches("Alice <alice@example.com>", "[AD] Buy now"));
+        assert!(!b.matches("Alice <alice@example.com>", "Re: [ad] question"));
+    }
+
+    #[test]
+    fn test_invalid_subject_regex_errors() {
+        let config = BlocklistConfig {
+            subjects: vec!["(".to_string()],
+            ..Default::default()
+        };
+        assert!(Blocklist::from_config(&config).is_err());
+    }
+
+    #[test]
+    fn test_block_entry_parse() {
+        assert_eq!(BlockEntry::parse("Bob@X.com"), BlockEntry::Address("bob@x.com".to_string()));
+        assert_eq!(BlockEntry::parse("@x.com"), BlockEntry::Domain("x.com".to_string()));
+        assert_eq!(BlockEntry::parse("x.com"), BlockEntry::Domain("x.com".to_string()));
+    }
+
+    #[test]
+    fn test_add_to_blocklist_creates_table_and_dedups() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join(".corky.toml");
+        std::fs::write(&path, "[owner]\nname = \"Me\"\n").unwrap();
+        let entry = BlockEntry::parse("spam@example.com");
+        assert!(add_to_blocklist(&entry, Some(&path)).unwrap());
+        assert!(!add_to_blocklist(&entry, Some(&path)).unwrap());
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("[blocklist]"));
+        assert!(content.contains("addresses = [\"spam@example.com\"]"));
+    }
+}
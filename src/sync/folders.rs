@@ -1,13 +1,372 @@
 //! List IMAP folders for a configured account.
 
-use anyhow::Result;
+use anyhow::{bail, Result};
+use imap::types::NameAttribute;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{IsTerminal, Write};
+use std::path::PathBuf;
 
-use crate::accounts::{load_accounts, resolve_password};
+use super::imap_sync::{build_label_routes, connect_imap_pub, ImapSession};
+use crate::accounts::{account_secret, add_label_to_account, load_accounts, Account, Secret};
+use crate::output;
 
-pub fn run(account: Option<&str>) -> Result<()> {
+/// One folder from `LIST`, with `STATUS` counts and config cross-references.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FolderInfo {
+    pub name: String,
+    /// Hierarchy delimiter reported by the server (e.g. "/" or ".").
+    pub delimiter: Option<String>,
+    /// Nesting level: the number of delimiters in `name`.
+    pub depth: usize,
+    /// Last path component.
+    pub leaf: String,
+    pub attributes: Vec<String>,
+    /// False for `\Noselect` containers, which have no messages or counts.
+    pub selectable: bool,
+    pub messages: Option<u32>,
+    pub unseen: Option<u32>,
+    /// Listed in the account's `labels`.
+    pub label: bool,
+    /// A `[routing]` key for the account.
+    pub route: bool,
+}
+
+/// Whether folder `name` is configured as `label`. INBOX is case-insensitive.
+fn same_folder(name: &str, label: &str) -> bool {
+    name == label || (name.eq_ignore_ascii_case("INBOX") && label.eq_ignore_ascii_case("INBOX"))
+}
+
+/// Build a `FolderInfo` without counts.
+pub fn folder_info(
+    name: &str,
+    delimiter: Option<&str>,
+    attributes: Vec<String>,
+    labels: &[String],
+    routes: &HashMap<String, Vec<PathBuf>>,
+) -> FolderInfo {
+    let (depth, leaf) = match delimiter.filter(|d| !d.is_empty()) {
+        Some(d) => (
+            name.matches(d).count(),
+            name.rsplit(d).next().unwrap_or(name).to_string(),
+        ),
+        None => (0, name.to_string()),
+    };
+    let selectable = !attributes.iter().any(|a| a == "NoSelect");
+    FolderInfo {
+        name: name.to_string(),
+        delimiter: delimiter.map(str::to_string),
+        depth,
+        leaf,
+        attributes,
+        selectable,
+        messages: None,
+        unseen: None,
+        label: labels.iter().any(|l| same_folder(name, l)),
+        route: routes.keys().any(|r| same_folder(name, r)),
+    }
+}
+
+/// Every folder on the server, sorted by name, without counts.
+pub fn list_folders(
+    session: &mut ImapSession,
+    labels: &[String],
+    routes: &HashMap<String, Vec<PathBuf>>,
+) -> Result<Vec<FolderInfo>> {
+    let names = session.list(None, Some("*"))?;
+    let mut folders: Vec<FolderInfo> = names
+        .iter()
+        .map(|n| {
+            let attributes = n
+                .attributes()
+                .iter()
+                .map(|a| match a {
+                    NameAttribute::Custom(s) => s.trim_start_matches('\\').to_string(),
+                    other => format!("{:?}", other),
+                })
+                .collect();
+            folder_info(n.name(), n.delimiter(), attributes, labels, routes)
+        })
+        .collect();
+    folders.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(folders)
+}
+
+/// Every folder on the server, sorted by name, with `STATUS` message and
+/// unseen counts for selectable ones.
+pub fn fetch_folders(
+    session: &mut ImapSession,
+    labels: &[String],
+    routes: &HashMap<String, Vec<PathBuf>>,
+) -> Result<Vec<FolderInfo>> {
+    let mut folders = list_folders(session, labels, routes)?;
+    for folder in folders.iter_mut().filter(|f| f.selectable) {
+        // A folder the server refuses STATUS for is still worth listing.
+        if let Ok(status) = session.status(&folder.name, "(MESSAGES UNSEEN)") {
+            folder.messages = Some(status.exists);
+            folder.unseen = status.unseen;
+        }
+    }
+    Ok(folders)
+}
+
+/// Table rows: the hierarchy indented under its parents.
+pub fn render_rows(folders: &[FolderInfo]) -> Vec<Vec<String>> {
+    let count = |n: Option<u32>| n.map(|n| n.to_string()).unwrap_or_else(|| "-".to_string());
+    let mut rows = vec![vec![
+        "FOLDER".to_string(),
+        "MESSAGES".to_string(),
+        "UNSEEN".to_string(),
+        "CONFIGURED".to_string(),
+        "FLAGS".to_string(),
+    ]];
+    for f in folders {
+        let mut configured = Vec::new();
+        if f.label {
+            configured.push("label");
+        }
+        if f.route {
+            configured.push("route");
+        }
+        let configured = configured.join(", ");
+        rows.push(vec![
+            format!("{}{}", "  ".repeat(f.depth), f.leaf),
+            count(f.messages),
+            count(f.unseen),
+            if configured.is_empty() {
+                configured
+            } else {
+                output::ok(&configured)
+            },
+            f.attributes.join(", "),
+        ]);
+    }
+    rows
+}
+
+/// Folders `--add` can offer: selectable and not already a label.
+pub fn addable(folders: &[FolderInfo]) -> Vec<&FolderInfo> {
+    folders
+        .iter()
+        .filter(|f| f.selectable && !f.label)
+        .collect()
+}
+
+/// Parse a picker answer: numbers into `candidates` (1-based), ranges like
+/// `2-4`, or folder names, separated by commas or spaces.
+pub fn parse_picks(input: &str, candidates: &[&FolderInfo]) -> Result<Vec<String>> {
+    let mut picks: Vec<String> = Vec::new();
+    let by_number = |n: &str| -> Result<usize> {
+        match n.parse::<usize>() {
+            Ok(i) if (1..=candidates.len()).contains(&i) => Ok(i - 1),
+            _ => bail!("No folder numbered {} (1-{})", n, candidates.len()),
+        }
+    };
+    for token in input.split([',', ' ']).filter(|t| !t.is_empty()) {
+        let indexes: Vec<usize> = if let Some((from, to)) = token
+            .split_once('-')
+            .filter(|(a, b)| a.parse::<usize>().is_ok() && b.parse::<usize>().is_ok())
+        {
+            (by_number(from)?..=by_number(to)?).collect()
+        } else if token.chars().all(|c| c.is_ascii_digit()) {
+            vec![by_number(token)?]
+        } else {
+            match candidates.iter().position(|f| same_folder(&f.name, token)) {
+                Some(i) => vec![i],
+                None => bail!("Not an addable folder: {}", token),
+            }
+        };
+        for i in indexes {
+            let name = &candidates[i].name;
+            if !picks.contains(name) {
+                picks.push(name.clone());
+            }
+        }
+    }
+    Ok(picks)
+}
+
+/// Folders to add: `select` if given (checked against the listing),
+/// otherwise asked for interactively.
+fn choose(folders: &[FolderInfo], select: &[String]) -> Result<Vec<String>> {
+    let candidates = addable(folders);
+    if !select.is_empty() {
+        let mut picks = Vec::new();
+        for name in select {
+            let Some(folder) = folders.iter().find(|f| same_folder(&f.name, name)) else {
+                bail!("Not a folder on the server: {}", name);
+            };
+            if !folder.selectable {
+                bail!("{} cannot hold messages (\\Noselect)", folder.name);
+            }
+            picks.push(folder.name.clone());
+        }
+        return Ok(picks);
+    }
+    if candidates.is_empty() {
+        println!("\nEvery folder is already a label");
+        return Ok(Vec::new());
+    }
+    if !std::io::stdin().is_terminal() {
+        bail!("--add needs a terminal to pick from; pass --select NAME... instead");
+    }
+    println!("\nFolders not yet synced:");
+    for (i, folder) in candidates.iter().enumerate() {
+        println!("  {:>3}. {}", i + 1, folder.name);
+    }
+    print!("Add which? (numbers, ranges like 2-4, or names; blank to skip) ");
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    parse_picks(input.trim(), &candidates)
+}
+
+/// SPECIAL-USE attributes (RFC 6154) of folders worth syncing by default.
+const SUGGESTED_USES: &[&str] = &["Sent", "Archive", "Flagged", "Important"];
+
+/// Likely labels for an account that has none: INBOX, then selectable
+/// folders marked `\Sent`, `\Archive`, `\Flagged` or `\Important`.
+pub fn suggest(folders: &[FolderInfo]) -> Vec<&FolderInfo> {
+    let is_inbox = |f: &FolderInfo| f.name.eq_ignore_ascii_case("INBOX");
+    let inbox = folders.iter().filter(|f| f.selectable && is_inbox(f));
+    let special = folders.iter().filter(|f| {
+        f.selectable
+            && !is_inbox(f)
+            && f.attributes.iter().any(|a| SUGGESTED_USES.contains(&a.as_str()))
+    });
+    inbox.chain(special).collect()
+}
+
+/// Leaf names tried, case-insensitively, when no folder is marked
+/// `\Drafts`. Names are matched as the server lists them, so non-ASCII ones
+/// are in modified UTF-7 (`Entw&APw-rfe` is "Entwürfe").
+const DRAFTS_NAMES: &[&str] = &[
+    "Drafts",
+    "Draft",
+    "Entw&APw-rfe",
+    "Brouillons",
+    "Borradores",
+    "Bozze",
+    "Concepten",
+    "Rascunhos",
+    "Utkast",
+    "Kladder",
+    "Luonnokset",
+    "Szkice",
+];
+
+/// The folder to APPEND drafts to: `configured` if the server has it, else
+/// the folder marked `\Drafts`, else the shallowest one named like a
+/// drafts folder (`INBOX.Drafts`, `[Gmail]/Drafts`, `Brouillons`, ...).
+pub fn find_drafts_folder<'a>(
+    configured: &str,
+    folders: &'a [FolderInfo],
+) -> Option<&'a FolderInfo> {
+    let selectable = || folders.iter().filter(|f| f.selectable);
+    selectable()
+        .find(|f| f.name == configured)
+        .or_else(|| selectable().find(|f| f.attributes.iter().any(|a| a == "Drafts")))
+        .or_else(|| {
+            DRAFTS_NAMES.iter().find_map(|name| {
+                selectable()
+                    .filter(|f| f.leaf.eq_ignore_ascii_case(name))
+                    .min_by_key(|f| f.depth)
+            })
+        })
+}
+
+/// Labels for an account with none configured. Lists the server's folders
+/// and the suggested ones; with `interactive`, asks which to sync and adds
+/// them to `.corky.toml`. Otherwise nothing is added and the result is
+/// empty.
+pub fn discover(
+    account_name: &str,
+    acct: &Account,
+    secret: &Secret,
+    interactive: bool,
+) -> Result<Vec<String>> {
+    let mut session = connect_imap_pub(
+        &acct.imap_host,
+        acct.imap_port,
+        acct.imap_starttls,
+        &acct.user,
+        secret,
+    )?;
+    let folders = fetch_folders(&mut session, &[], &HashMap::new())?;
+    session.logout()?;
+    let suggested: Vec<String> = suggest(&folders).iter().map(|f| f.name.clone()).collect();
+
+    if !interactive {
+        if !output::quiet() {
+            println!("  No labels configured. Folders on the server:\n");
+            output::print_table(&render_rows(&folders));
+        }
+        let shown = if suggested.is_empty() {
+            "none".to_string()
+        } else {
+            suggested.join(", ")
+        };
+        let msg = format!(
+            "No labels configured for account '{}'; skipping. Suggested: {}",
+            account_name, shown
+        );
+        eprintln!("{}", output::warning(&msg));
+        let pick = format!("corky sync account {} --interactive", account_name);
+        eprintln!(
+            "  Pick labels with `{}` or `corky list-folders {} --add`",
+            pick, account_name
+        );
+        return Ok(Vec::new());
+    }
+
+    println!("No labels configured for account '{}'. Folders on the server:\n", account_name);
+    output::print_table(&render_rows(&folders));
+    let candidates = addable(&folders);
+    if candidates.is_empty() {
+        println!("\nNo folder can hold messages; skipping account '{}'", account_name);
+        return Ok(Vec::new());
+    }
+    println!("\nSync which folders? (* = suggested)");
+    for (i, folder) in candidates.iter().enumerate() {
+        let mark = if suggested.contains(&folder.name) { "*" } else { " " };
+        println!("  {:>3}.{} {}", i + 1, mark, folder.name);
+    }
+    print!("Numbers, ranges like 2-4, or names; blank for the suggested ones: ");
+    std::io::stdout().flush()?;
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+    let picks = if input.trim().is_empty() {
+        suggested
+    } else {
+        parse_picks(input.trim(), &candidates)?
+    };
+    if picks.is_empty() {
+        println!("No labels added; skipping account '{}'", account_name);
+        return Ok(Vec::new());
+    }
+    for label in &picks {
+        add_label_to_account(account_name, label, None)?;
+        println!("Added '{}' to account '{}'", label, account_name);
+    }
+    Ok(picks)
+}
+
+pub fn run(account: Option<&str>, json: bool, add: bool, select: &[String]) -> Result<()> {
     let accounts = load_accounts(None)?;
 
+    let add = add || !select.is_empty();
     let Some(account_name) = account else {
+        if add {
+            bail!("--add needs an ACCOUNT");
+        }
+        if json {
+            let names: HashMap<&String, &String> = accounts
+                .iter()
+                .map(|(name, acct)| (name, &acct.user))
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&names)?);
+            return Ok(());
+        }
         println!("Available accounts:");
         for (name, acct) in &accounts {
             println!("  {:<20} {}", name, acct.user);
@@ -22,46 +381,175 @@ pub fn run(account: Option<&str>) -> Result<()> {
             accounts.keys().cloned().collect::<Vec<_>>().join(", ")
         )
     })?;
-    let password = resolve_password(acct)?;
+    let secret = account_secret(account_name, acct)?;
 
-    let mut tls_builder = native_tls::TlsConnector::builder();
-    if acct.imap_starttls || acct.imap_host == "127.0.0.1" || acct.imap_host == "localhost" {
-        tls_builder.danger_accept_invalid_certs(true);
-        tls_builder.danger_accept_invalid_hostnames(true);
-    }
-    let tls = tls_builder.build()?;
-
-    println!(
+    // Keep stdout clean for --json.
+    let connecting = format!(
         "Connecting to {}:{} as {}\n",
         acct.imap_host, acct.imap_port, acct.user
     );
-
-    let client = if acct.imap_starttls {
-        imap::connect_starttls(
-            (acct.imap_host.as_str(), acct.imap_port),
-            &acct.imap_host,
-            &tls,
-        )?
+    if json {
+        eprintln!("{}", connecting);
     } else {
-        imap::connect(
-            (acct.imap_host.as_str(), acct.imap_port),
-            &acct.imap_host,
-            &tls,
-        )?
-    };
+        println!("{}", connecting);
+    }
+
+    let mut session = connect_imap_pub(
+        &acct.imap_host,
+        acct.imap_port,
+        acct.imap_starttls,
+        &acct.user,
+        &secret,
+    )?;
+    let routes = build_label_routes(account_name)?;
+    let folders = fetch_folders(&mut session, &acct.labels, &routes)?;
+    session.logout()?;
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&folders)?);
+        return Ok(());
+    }
+    if let Some(delim) = folders.iter().find_map(|f| f.delimiter.as_deref()) {
+        println!("Hierarchy delimiter: {:?}\n", delim);
+    }
+    output::print_table(&render_rows(&folders));
+
+    if !add {
+        return Ok(());
+    }
+    let picks = choose(&folders, select)?;
+    if picks.is_empty() {
+        println!("No labels added");
+        return Ok(());
+    }
+    for label in &picks {
+        if add_label_to_account(account_name, label, None)? {
+            println!("Added '{}' to account '{}'", label, account_name);
+        } else {
+            println!("Label '{}' already in account '{}'", label, account_name);
+        }
+    }
+    Ok(())
+}
 
-    let mut session = client.login(&acct.user, &password).map_err(|e| e.0)?;
-    let folders = session.list(None, Some("*"))?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_folder_info_hierarchy_and_config() {
+        let labels = vec!["inbox".to_string(), "Projects/2025".to_string()];
+        let routes = HashMap::from([("[Gmail]/Starred".to_string(), Vec::new())]);
+
+        let inbox = folder_info("INBOX", Some("/"), vec![], &labels, &routes);
+        assert_eq!((inbox.depth, inbox.leaf.as_str()), (0, "INBOX"));
+        assert!(inbox.label && !inbox.route);
+
+        let nested = folder_info("Projects/2025", Some("/"), vec![], &labels, &routes);
+        assert_eq!((nested.depth, nested.leaf.as_str()), (1, "2025"));
+        assert!(nested.label);
+
+        let gmail = folder_info(
+            "[Gmail]",
+            Some("/"),
+            vec!["NoSelect".to_string()],
+            &labels,
+            &routes,
+        );
+        assert!(!gmail.selectable);
+        let starred = folder_info("[Gmail]/Starred", Some("/"), vec![], &labels, &routes);
+        assert!(starred.route && !starred.label);
+
+        let dotted = folder_info("INBOX.Lists.rust", Some("."), vec![], &labels, &routes);
+        assert_eq!((dotted.depth, dotted.leaf.as_str()), (2, "rust"));
+        let flat = folder_info("Archive", None, vec![], &labels, &routes);
+        assert_eq!((flat.depth, flat.leaf.as_str()), (0, "Archive"));
+    }
 
-    for folder in folders.iter() {
-        let flags: Vec<String> = folder
-            .attributes()
+    #[test]
+    fn test_render_rows_indents_children() {
+        let mut parent = folder_info("Projects", Some("/"), vec![], &[], &HashMap::new());
+        parent.messages = Some(3);
+        parent.unseen = Some(1);
+        let child = folder_info("Projects/2025", Some("/"), vec![], &[], &HashMap::new());
+        let rows = render_rows(&[parent, child]);
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[1][..3], ["Projects", "3", "1"]);
+        assert_eq!(rows[2][..3], ["  2025", "-", "-"]);
+    }
+
+    #[test]
+    fn test_parse_picks() {
+        let labels = vec!["INBOX".to_string()];
+        let none = HashMap::new();
+        let folders: Vec<FolderInfo> = ["Archive", "INBOX", "Lists", "Lists/rust", "Sent"]
             .iter()
-            .map(|a| format!("{:?}", a))
+            .map(|n| folder_info(n, Some("/"), vec![], &labels, &none))
             .collect();
-        println!("  {:<40} [{}]", folder.name(), flags.join(", "));
+        let candidates = addable(&folders);
+        assert_eq!(candidates.len(), 4);
+
+        assert_eq!(
+            parse_picks("1, 3-4", &candidates).unwrap(),
+            vec!["Archive", "Lists/rust", "Sent"]
+        );
+        assert_eq!(
+            parse_picks("Sent 4 Lists", &candidates).unwrap(),
+            vec!["Sent", "Lists"]
+        );
+        assert!(parse_picks("", &candidates).unwrap().is_empty());
+        assert!(parse_picks("9", &candidates).is_err());
+        assert!(parse_picks("INBOX", &candidates).is_err());
     }
 
-    session.logout()?;
-    Ok(())
+    #[test]
+    fn test_suggest_inbox_and_special_use() {
+        let none = HashMap::new();
+        let folder = |name: &str, attrs: &[&str]| {
+            let attrs = attrs.iter().map(|a| a.to_string()).collect();
+            folder_info(name, Some("/"), attrs, &[], &none)
+        };
+        let folders = vec![
+            folder("Archive", &["Archive"]),
+            folder("Drafts", &["Drafts"]),
+            folder("Inbox", &[]),
+            folder("Lists", &[]),
+            folder("Sent", &["Sent"]),
+            folder("Spam", &["Junk"]),
+            folder("[Gmail]", &["NoSelect"]),
+            folder("[Gmail]/All Mail", &["All"]),
+            folder("[Gmail]/Starred", &["Flagged"]),
+        ];
+        let names: Vec<&str> = suggest(&folders).iter().map(|f| f.name.as_str()).collect();
+        assert_eq!(names, vec!["Inbox", "Archive", "Sent", "[Gmail]/Starred"]);
+        assert!(suggest(&folders[5..7]).is_empty());
+    }
+
+    #[test]
+    fn test_find_drafts_folder_fallbacks() {
+        let none = HashMap::new();
+        let folder = |name: &str, attrs: &[&str]| {
+            let attrs = attrs.iter().map(|a| a.to_string()).collect();
+            folder_info(name, Some("."), attrs, &[], &none)
+        };
+        let find = |configured: &str, folders: &[FolderInfo]| {
+            find_drafts_folder(configured, folders).map(|f| f.name.clone())
+        };
+
+        let dovecot = vec![
+            folder("INBOX", &[]),
+            folder("INBOX.Archive.Drafts", &[]),
+            folder("INBOX.Drafts", &[]),
+        ];
+        assert_eq!(find("INBOX.Drafts", &dovecot).as_deref(), Some("INBOX.Drafts"));
+        assert_eq!(find("Drafts", &dovecot).as_deref(), Some("INBOX.Drafts"));
+
+        let marked = vec![folder("Brouillons", &[]), folder("Work", &["Drafts"])];
+        assert_eq!(find("Drafts", &marked).as_deref(), Some("Work"));
+        assert_eq!(find("Drafts", &marked[..1]).as_deref(), Some("Brouillons"));
+
+        let german = vec![folder("Entw&APw-rfe", &[]), folder("Drafts", &["NoSelect"])];
+        assert_eq!(find("Drafts", &german).as_deref(), Some("Entw&APw-rfe"));
+        assert_eq!(find("Drafts", &german[1..]), None);
+    }
 }
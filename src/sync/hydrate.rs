@@ -0,0 +1,307 @@
+//! `corky hydrate` — fetch bodies for messages stored by `sync --headers-only`.
+//!
+//! Headers-only messages carry `imap_sync::HEADERS_ONLY_BODY`. Hydrating a thread
+//! looks up the labels and UIDs it was synced from in sync-state
+//! provenance (§6.5), fetches those messages, and fills in the matching
+//! bodies (by Message-ID, else From + Date) in the root conversation and
+//! its routed mailbox copies.
+
+use anyhow::{bail, Result};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::imap_sync::{
+    build_label_routes, connect_imap_pub, extract_body, find_thread_file, is_headers_only,
+    message_headers, truncate_body, ImapSession,
+};
+use super::manifest::generate_manifest;
+use super::markdown::{is_part_file, read_thread, write_thread};
+use super::redact::{Redactor, Redactors};
+use super::types::{Message, Thread};
+use crate::accounts::{account_secret, load_accounts};
+use crate::config::corky_config::{self, SyncConfig};
+use crate::resolve;
+
+/// UIDs per `UID FETCH` command.
+const FETCH_BATCH: usize = 50;
+
+/// Key matching a fetched message to its headers-only copy.
+fn message_key(msg: &Message) -> String {
+    if msg.message_id.trim().is_empty() {
+        format!("{}|{}", msg.from, msg.date.trim())
+    } else {
+        format!("id:{}", msg.message_id.trim().to_lowercase())
+    }
+}
+
+/// Fill headers-only messages in `thread` from `bodies` (by
+/// [`message_key`]). Returns how many were filled.
+pub fn fill_bodies(thread: &mut Thread, bodies: &HashMap<String, String>) -> usize {
+    let mut filled = 0;
+    for msg in thread.messages.iter_mut().filter(|m| is_headers_only(m)) {
+        if let Some(body) = bodies.get(&message_key(msg)) {
+            msg.body = body.clone();
+            filled += 1;
+        }
+    }
+    filled
+}
+
+/// Root conversations to hydrate: `slug`, or every one labeled `label`
+/// that still has headers-only messages.
+fn targets(slug: Option<&str>, label: Option<&str>) -> Result<Vec<PathBuf>> {
+    if let Some(slug) = slug {
+        return Ok(vec![resolve::find_conversation(slug)?]);
+    }
+    let Some(label) = label else {
+        bail!("Pass a conversation SLUG or --label LABEL");
+    };
+    let mut files = Vec::new();
+    let Ok(entries) = std::fs::read_dir(resolve::conversations_dir()) else {
+        return Ok(files);
+    };
+    for path in entries.flatten().map(|e| e.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("md") || is_part_file(&path) {
+            continue;
+        }
+        let Some(thread) = read_thread(&path)? else {
+            continue;
+        };
+        if thread.labels.iter().any(|l| l == label) && thread.messages.iter().any(is_headers_only) {
+            files.push(path);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Fetch `uids` from `label` and return their bodies by [`message_key`].
+fn fetch_bodies(
+    session: &mut ImapSession,
+    label: &str,
+    uids: &[u32],
+    account: &str,
+    sync: &SyncConfig,
+) -> Result<HashMap<String, String>> {
+    let mut bodies = HashMap::new();
+    if session.select(label).is_err() {
+        crate::progress!("  Label \"{}\" not found \u{2014} skipping", label);
+        return Ok(bodies);
+    }
+    // BODY.PEEK[] leaves \Seen alone; BODY[] sets it.
+    let item = if sync.mark_read {
+        "(UID BODY[])"
+    } else {
+        "(UID BODY.PEEK[])"
+    };
+    for chunk in uids.chunks(FETCH_BATCH) {
+        let set: Vec<String> = chunk.iter().map(u32::to_string).collect();
+        let fetches = session.uid_fetch(set.join(","), item)?;
+        for fetch in fetches.iter() {
+            let (Some(uid), Some(raw)) = (fetch.uid, fetch.body()) else {
+                continue;
+            };
+            let Ok(parsed) = mailparse::parse_mail(raw) else {
+                eprintln!("  Warning: failed to parse message UID {}", uid);
+                continue;
+            };
+            let body = truncate_body(
+                &extract_body(&parsed),
+                sync.max_body_bytes,
+                &format!("account {}, label {}, UID {}", account, label, uid),
+            );
+            bodies.insert(message_key(&message_headers(&parsed, uid)), body);
+        }
+    }
+    Ok(bodies)
+}
+
+/// Write `bodies` into the conversation at `path`, redacted if it is a
+/// mailbox copy with rules. Returns how many messages were filled.
+fn hydrate_file(
+    path: &Path,
+    bodies: &HashMap<String, String>,
+    redactor: Option<&Redactor>,
+    sync: &SyncConfig,
+) -> Result<usize> {
+    let Some(mut thread) = read_thread(path)? else {
+        return Ok(0);
+    };
+    let bodies = match redactor {
+        Some(r) => bodies
+            .iter()
+            .map(|(key, body)| (key.clone(), r.redact_text(body)))
+            .collect(),
+        None => bodies.clone(),
+    };
+    let filled = fill_bodies(&mut thread, &bodies);
+    if filled > 0 {
+        write_thread(path, &thread, sync)?;
+    }
+    Ok(filled)
+}
+
+/// corky hydrate [SLUG] [--label LABEL]
+pub fn run(slug: Option<&str>, label: Option<&str>) -> Result<()> {
+    let files = targets(slug, label)?;
+    if files.is_empty() {
+        println!("No headers-only conversations to hydrate.");
+        return Ok(());
+    }
+    let accounts = load_accounts(None)?;
+    let state = super::load_state()?;
+    let sync = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    let redactors = Redactors::load()?;
+    let mut sessions: BTreeMap<String, ImapSession> = BTreeMap::new();
+    let mut total = 0;
+
+    for path in &files {
+        let Some(thread) = read_thread(path)? else {
+            continue;
+        };
+        let wanted: HashSet<String> = thread
+            .messages
+            .iter()
+            .filter(|m| is_headers_only(m))
+            .map(message_key)
+            .collect();
+        if wanted.is_empty() {
+            continue;
+        }
+        let mut bodies: HashMap<String, String> = HashMap::new();
+        let mut names: Vec<&String> = state.accounts.keys().collect();
+        names.sort();
+        for name in names {
+            let Some(labels) = state.accounts[name].threads.get(&thread.id) else {
+                continue;
+            };
+            let Some(acct) = accounts.get(name) else {
+                continue;
+            };
+            if !sessions.contains_key(name) {
+                crate::progress!(
+                    "Connecting to {}:{} as {}",
+                    acct.imap_host,
+                    acct.imap_port,
+                    acct.user
+                );
+                let secret = account_secret(name, acct)?;
+                let session = connect_imap_pub(
+                    &acct.imap_host,
+                    acct.imap_port,
+                    acct.imap_starttls,
+                    &acct.user,
+                    &secret,
+                )?;
+                sessions.insert(name.clone(), session);
+            }
+            let session = sessions.get_mut(name).expect("session just inserted");
+            let mut labels: Vec<(&String, &Vec<u32>)> = labels.iter().collect();
+            labels.sort();
+            for (label, uids) in labels {
+                bodies.extend(fetch_bodies(session, label, uids, name, &sync)?);
+            }
+            if wanted.iter().all(|k| bodies.contains_key(k)) {
+                break;
+            }
+        }
+        bodies.retain(|k, _| wanted.contains(k));
+        for body in bodies.values_mut() {
+            *body = redactors.redact_secrets(body).0;
+        }
+
+        let name = path
+            .file_stem()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        let filled = hydrate_file(path, &bodies, None, &sync)?;
+        // Routed copies, found by Thread ID as during sync
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for account in &thread.accounts {
+            let routes = build_label_routes(account)?;
+            for dir in thread.labels.iter().filter_map(|l| routes.get(l)).flatten() {
+                if !dirs.contains(dir) {
+                    dirs.push(dir.clone());
+                }
+            }
+        }
+        for dir in &dirs {
+            if let Some(copy) = find_thread_file(dir, &thread.id) {
+                hydrate_file(&copy, &bodies, redactors.for_dir(dir), &sync)?;
+            }
+        }
+        println!(
+            "{}: {} of {} message(s) hydrated",
+            name,
+            filled,
+            wanted.len()
+        );
+        total += filled;
+    }
+
+    for (_, mut session) in sessions {
+        let _ = session.logout();
+    }
+    generate_manifest(&resolve::conversations_dir())?;
+    if total == 0 {
+        println!(
+            "Nothing fetched; bodies are looked up through sync state, so sync the thread's labels first."
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::imap_sync::HEADERS_ONLY_BODY;
+    use crate::sync::types::fixtures::message;
+
+    fn msg(from: &str, message_id: &str, body: &str) -> Message {
+        Message {
+            message_id: message_id.to_string(),
+            ..message(from, "Mon, 10 Feb 2025 10:00:00 +0000", body)
+        }
+    }
+
+    #[test]
+    fn test_fill_bodies_by_message_id_or_sender() {
+        let mut thread = Thread {
+            id: "t1".to_string(),
+            messages: vec![
+                msg(
+                    "Alice <alice@example.com>",
+                    "<A1@example.com>",
+                    HEADERS_ONLY_BODY,
+                ),
+                msg("Bob <bob@example.com>", "", HEADERS_ONLY_BODY),
+                msg(
+                    "Carol <carol@example.com>",
+                    "<c1@example.com>",
+                    "Already here",
+                ),
+            ],
+            ..Default::default()
+        };
+        let mut bodies = HashMap::new();
+        bodies.insert(
+            message_key(&msg("", "<a1@example.com>", "")),
+            "Hi Bob".to_string(),
+        );
+        bodies.insert(
+            message_key(&msg("Bob <bob@example.com>", "", "")),
+            "Hi Alice".to_string(),
+        );
+        bodies.insert(
+            message_key(&msg("", "<c1@example.com>", "")),
+            "Replaced?".to_string(),
+        );
+        assert_eq!(fill_bodies(&mut thread, &bodies), 2);
+        let texts: Vec<&str> = thread.messages.iter().map(|m| m.body.as_str()).collect();
+        assert_eq!(texts, vec!["Hi Bob", "Hi Alice", "Already here"]);
+        assert!(!thread.messages.iter().any(is_headers_only));
+    }
+}
@@ -2,25 +2,165 @@
 
 use anyhow::Result;
 use chrono::{DateTime, Datelike, Utc};
-use imap::Session;
-use native_tls::TlsStream;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::HashSet;
-use std::net::TcpStream;
+use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
 
-use super::markdown::{parse_thread_markdown, thread_to_markdown};
-use super::types::{AccountSyncState, LabelState, Message, SyncState, Thread};
-use crate::config::corky_config;
+use super::markdown::is_part_file;
+use super::store::open_store_with;
+use super::types::{Message, MessageSource, Thread};
+use crate::config::corky_config::{self, SyncConfig};
 use crate::resolve;
-use crate::util::{slugify, thread_key_from_subject};
+use crate::util::{
+    slugify, split_address_list, strip_subject_prefixes, subject_key, subject_prefix_regex,
+};
+
+#[cfg(feature = "net")]
+use {
+    super::auto_label::AutoLabels,
+    super::blocklist::Blocklist,
+    super::index_hook::IndexHook,
+    super::provenance,
+    super::redact::Redactors,
+    super::report::LabelReport,
+    super::throttle,
+    super::types::{AccountSyncState, LabelState, SyncState},
+    crate::accounts::{LabelOverride, Secret},
+    crate::error::CorkyError,
+    crate::profile,
+    crate::util::thread_key_from_subject,
+    imap::Session,
+    std::time::Instant,
+};
+#[cfg(all(feature = "net", not(feature = "tls-rustls")))]
+use {native_tls::TlsStream, std::net::TcpStream};
 
 static THREAD_ID_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?m)^\*\*Thread ID\*\*:\s*(.+)$").unwrap());
 
+static MESSAGE_ID_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?m)^\*\*Message-ID\*\*:\s*(.+)$").unwrap());
+
+/// One `<id@host>` in an In-Reply-To or References header.
+#[cfg(feature = "net")]
+static REFERENCE_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<[^<>\s]+>").unwrap());
+
+/// A reply marker at the start of a subject (`Re:`, `AW:`, `Re[2]:`, ...).
+#[cfg(feature = "net")]
+static REPLY_PREFIX_RE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?i)^\s*(?:re|aw|sv|vs)(?:\[\d+\])?\s*:").unwrap());
+
+/// Body of a message stored by `sync --headers-only`, until `corky hydrate`
+/// fetches the real one.
+pub const HEADERS_ONLY_BODY: &str = "*[Body not fetched yet: corky hydrate]*";
+
+/// Whether `msg` was stored by a headers-only sync and still needs its body.
+pub fn is_headers_only(msg: &Message) -> bool {
+    msg.body.trim() == HEADERS_ONLY_BODY
+}
+
+/// UIDs per `UID FETCH (UID ENVELOPE)` command.
+#[cfg(feature = "net")]
+const ENVELOPE_BATCH: usize = 200;
+
+/// A header value as text. Raw UTF-8 (sent with SMTPUTF8) is read as UTF-8
+/// rather than Latin-1; encoded words are decoded either way.
+#[cfg(feature = "net")]
+fn header_text(h: &mailparse::MailHeader) -> String {
+    h.get_value_utf8().unwrap_or_else(|_| h.get_value())
+}
+
+/// A message's header fields from a parsed email (or just its header
+/// block); the body is left empty.
+#[cfg(feature = "net")]
+pub(crate) fn message_headers(parsed: &mailparse::ParsedMail, uid: u32) -> Message {
+    let header = |name: &str| {
+        parsed
+            .headers
+            .iter()
+            .find(|h| h.get_key_ref().eq_ignore_ascii_case(name))
+    };
+    let address = |name: &str| {
+        header(name)
+            .map(|h| crate::address::display_header(&header_text(h)))
+            .unwrap_or_default()
+    };
+    let raw = |name: &str| {
+        header(name)
+            .map(|h| h.get_value().trim().to_string())
+            .unwrap_or_default()
+    };
+    let subject = header("Subject")
+        .map(header_text)
+        .unwrap_or_else(|| "(no subject)".to_string());
+    Message {
+        id: uid.to_string(),
+        thread_id: thread_key_from_subject(&subject),
+        from: address("From"),
+        to: address("To"),
+        cc: address("Cc"),
+        message_id: raw("Message-ID"),
+        list_unsubscribe: raw("List-Unsubscribe"),
+        date: header("Date").map(|h| h.get_value()).unwrap_or_default(),
+        subject,
+        ..Default::default()
+    }
+}
+
+/// Message-IDs (lowercased) a message refers to, nearest first: In-Reply-To,
+/// then References from the parent back to the thread root.
+#[cfg(feature = "net")]
+pub(crate) fn reference_ids(parsed: &mailparse::ParsedMail) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for name in ["In-Reply-To", "References"] {
+        let Some(h) = parsed.headers.iter().find(|h| h.get_key_ref().eq_ignore_ascii_case(name))
+        else {
+            continue;
+        };
+        let value = h.get_value();
+        for id in REFERENCE_RE.find_iter(&value).collect::<Vec<_>>().into_iter().rev() {
+            let id = id.as_str().to_lowercase();
+            if !ids.contains(&id) {
+                ids.push(id);
+            }
+        }
+    }
+    ids
+}
+
+/// Thread ID for a newly fetched message. The thread already holding the
+/// message, or else the nearest message it references, wins. Otherwise it
+/// falls back to the subject key (prefixes per `prefix_re`), except that a
+/// thread root (no references, no reply prefix) whose subject key is taken
+/// by another conversation in `out_dir` starts its own thread,
+/// `{key} {Message-ID}`.
+#[cfg(feature = "net")]
+pub(crate) fn thread_key_for(
+    message: &Message,
+    references: &[String],
+    indexes: &[HashMap<String, String>],
+    out_dir: &Path,
+    prefix_re: &Regex,
+) -> String {
+    let own = message.message_id.trim().to_lowercase();
+    let ids = std::iter::once(&own).filter(|id| !id.is_empty()).chain(references);
+    for id in ids {
+        if let Some(thread_id) = indexes.iter().find_map(|idx| idx.get(id)) {
+            return thread_id.clone();
+        }
+    }
+    let key = subject_key(&message.subject, prefix_re);
+    let is_root = references.is_empty() && !REPLY_PREFIX_RE.is_match(&message.subject);
+    if is_root && !own.is_empty() && find_thread_by_key(out_dir, &key, prefix_re).is_some() {
+        return format!("{} {}", key, message.message_id.trim());
+    }
+    key
+}
+
 /// Extract text/plain body from a parsed email.
-fn extract_body(parsed: &mailparse::ParsedMail) -> String {
+#[cfg(feature = "net")]
+pub(crate) fn extract_body(parsed: &mailparse::ParsedMail) -> String {
     if parsed.subparts.is_empty() {
         if let Ok(body) = parsed.get_body() {
             return body;
@@ -48,9 +188,30 @@ fn extract_body(parsed: &mailparse::ParsedMail) -> String {
     String::new()
 }
 
+/// Truncate a message body to at most `max_bytes`, appending a marker that
+/// points at the original message on the server. A limit of 0 disables truncation.
+pub fn truncate_body(body: &str, max_bytes: usize, origin: &str) -> String {
+    if max_bytes == 0 || body.len() <= max_bytes {
+        return body.to_string();
+    }
+    let mut end = max_bytes;
+    while !body.is_char_boundary(end) && end > 0 {
+        end -= 1;
+    }
+    let kept = body[..end].trim_end();
+    format!(
+        "{}\n\n[Truncated: kept {} of {} bytes. Full message: {}]",
+        kept,
+        kept.len(),
+        body.len(),
+        origin
+    )
+}
+
 /// Parse an RFC 2822 date string, falling back to epoch on failure.
 pub fn parse_msg_date(date_str: &str) -> DateTime<Utc> {
     DateTime::parse_from_rfc2822(date_str)
+        .or_else(|_| DateTime::parse_from_rfc3339(date_str))
         .map(|dt| dt.with_timezone(&Utc))
         .or_else(|_| {
             mailparse::dateparse(date_str)
@@ -61,7 +222,7 @@ pub fn parse_msg_date(date_str: &str) -> DateTime<Utc> {
 
 /// Set file mtime to the parsed date.
 #[allow(unused_variables)]
-fn set_mtime(path: &Path, date_str: &str) -> Result<()> {
+pub(crate) fn set_mtime(path: &Path, date_str: &str) -> Result<()> {
     let dt = parse_msg_date(date_str);
     if dt.year() <= 1970 {
         return Ok(());
@@ -87,29 +248,73 @@ fn set_mtime(path: &Path, date_str: &str) -> Result<()> {
     Ok(())
 }
 
-/// Find an existing thread file by its Thread ID metadata.
-fn find_thread_file(out_dir: &Path, thread_id: &str) -> Option<PathBuf> {
-    if !out_dir.exists() {
-        return None;
+/// Every conversation in `out_dir` (first parts only) with its stored
+/// Thread ID, read lazily.
+fn stored_thread_ids(out_dir: &Path) -> impl Iterator<Item = (PathBuf, String)> {
+    std::fs::read_dir(out_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("md") && !is_part_file(p))
+        .filter_map(|path| {
+            let text = std::fs::read_to_string(&path).ok()?;
+            let id = THREAD_ID_RE.captures(&text)?[1].trim().to_string();
+            Some((path, id))
+        })
+}
+
+/// Find an existing thread file by its Thread ID metadata. For a split
+/// conversation this is the first part.
+pub(crate) fn find_thread_file(out_dir: &Path, thread_id: &str) -> Option<PathBuf> {
+    stored_thread_ids(out_dir)
+        .find(|(_, id)| id == thread_id)
+        .map(|(path, _)| path)
+}
+
+/// Find the conversation for the subject key `key`: the one whose Thread ID
+/// is `key`, or else one whose stored Thread ID reduces to `key` under
+/// `prefix_re`. Conversations written before the current prefix rules keep
+/// IDs like `re: hello` or `[external] plans`; replies still join them.
+pub(crate) fn find_thread_by_key(out_dir: &Path, key: &str, prefix_re: &Regex) -> Option<PathBuf> {
+    let mut normalized = None;
+    for (path, id) in stored_thread_ids(out_dir) {
+        if id == key {
+            return Some(path);
+        }
+        if normalized.is_none() && subject_key(&id, prefix_re) == key {
+            normalized = Some(path);
+        }
     }
-    for entry in std::fs::read_dir(out_dir).ok()?.flatten() {
-        let path = entry.path();
+    normalized
+}
+
+/// Message-ID (lowercased) → Thread ID for every conversation in `dir`,
+/// read from the metadata lines without parsing whole threads.
+pub fn message_id_index(dir: &Path) -> HashMap<String, String> {
+    let mut index = HashMap::new();
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return index;
+    };
+    for path in entries.flatten().map(|e| e.path()) {
         if path.extension().and_then(|e| e.to_str()) != Some("md") {
             continue;
         }
-        if let Ok(text) = std::fs::read_to_string(&path) {
-            if let Some(cap) = THREAD_ID_RE.captures(&text) {
-                if cap[1].trim() == thread_id {
-                    return Some(path);
-                }
-            }
+        let Ok(text) = std::fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some(thread_id) = THREAD_ID_RE.captures(&text).map(|c| c[1].trim().to_string()) else {
+            continue;
+        };
+        for cap in MESSAGE_ID_RE.captures_iter(&text) {
+            index.insert(cap[1].trim().to_lowercase(), thread_id.clone());
         }
     }
-    None
+    index
 }
 
 /// Return a slug that doesn't collide with existing files.
-fn unique_slug(out_dir: &Path, slug: &str) -> String {
+pub(crate) fn unique_slug(out_dir: &Path, slug: &str) -> String {
     if !out_dir.join(format!("{}.md", slug)).exists() {
         return slug.to_string();
     }
@@ -120,7 +325,145 @@ fn unique_slug(out_dir: &Path, slug: &str) -> String {
     format!("{}-{}", slug, n)
 }
 
-/// Merge a single message into its thread file on disk.
+/// Build a new thread's file stem from `[sync] filename_scheme`.
+///
+/// `{slug}` is the slug of the subject without reply markers, gateway tags
+/// and `strip_prefixes` (`[sync] strip_prefixes`); `{date}` is the first
+/// message's date as `YYYY-MM-DD` (UTC), or `undated` if it can't be
+/// parsed. An empty scheme means `{slug}`.
+pub fn thread_file_stem(
+    scheme: &str,
+    subject: &str,
+    first_date: &str,
+    strip_prefixes: &[String],
+) -> String {
+    let scheme = if scheme.trim().is_empty() { "{slug}" } else { scheme.trim() };
+    let stem = if scheme.contains("{date}") {
+        let dt = parse_msg_date(first_date);
+        let date = if dt.year() <= 1970 {
+            "undated".to_string()
+        } else {
+            dt.format("%Y-%m-%d").to_string()
+        };
+        scheme.replace("{date}", &date)
+    } else {
+        scheme.to_string()
+    };
+    let subject = strip_subject_prefixes(subject, &subject_prefix_regex(strip_prefixes));
+    stem.replace("{slug}", &slugify(&subject))
+        .replace(['/', '\\'], "-")
+}
+
+/// Collect unique participants (From, To, CC) across a thread's messages.
+///
+/// Unique by normalized email (`address::normalize`), first spelling wins, in order of appearance.
+/// Entries without an address (chat display names) are kept verbatim.
+pub fn collect_participants(messages: &[Message]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut participants = Vec::new();
+    for msg in messages {
+        for field in [&msg.from, &msg.to, &msg.cc] {
+            for addr in split_address_list(field) {
+                if seen.insert(crate::address::normalize(&addr)) {
+                    participants.push(addr);
+                }
+            }
+        }
+    }
+    participants
+}
+
+/// Tag a thread `newsletter` if any message carries a List-Unsubscribe header.
+pub(crate) fn tag_newsletter(thread: &mut Thread) {
+    let is_newsletter = thread.messages.iter().any(|m| !m.list_unsubscribe.is_empty());
+    if is_newsletter && !thread.tags.iter().any(|t| t == "newsletter") {
+        thread.tags.push("newsletter".to_string());
+    }
+}
+
+/// Compare two Message-IDs, ignoring surrounding whitespace and case.
+fn same_message_id(a: &str, b: &str) -> bool {
+    a.trim().eq_ignore_ascii_case(b.trim())
+}
+
+/// Whether `a` and `b` are the same message: equal Message-IDs, or for
+/// messages without one, the same `(from, date)`.
+fn is_same_message(a: &Message, b: &Message) -> bool {
+    if !a.message_id.is_empty() && !b.message_id.is_empty() {
+        same_message_id(&a.message_id, &b.message_id)
+    } else {
+        a.from == b.from && a.date == b.date
+    }
+}
+
+fn push_missing(into: &mut Vec<String>, from: &[String]) {
+    for item in from {
+        if !into.contains(item) {
+            into.push(item.clone());
+        }
+    }
+}
+
+/// Record `source` on a message. A message has one UID per account and
+/// label, so an existing entry for them (e.g. from before a UIDVALIDITY
+/// change) is updated rather than duplicated.
+pub fn add_source(sources: &mut Vec<MessageSource>, source: &MessageSource) {
+    match sources
+        .iter_mut()
+        .find(|s| s.account == source.account && s.label == source.label)
+    {
+        Some(existing) => existing.uid = source.uid,
+        None => sources.push(source.clone()),
+    }
+}
+
+/// Union of two copies of one thread, e.g. written by two owners into a
+/// shared mailbox. Messages are deduplicated like [`merge_message_to_file`];
+/// labels, accounts, tags, notes, `received_by`, and sources are combined.
+pub fn merge_threads(ours: &Thread, theirs: &Thread) -> Thread {
+    let mut merged = ours.clone();
+    for msg in &theirs.messages {
+        match merged.messages.iter_mut().find(|m| is_same_message(m, msg)) {
+            Some(existing) => {
+                if existing.message_id.is_empty() {
+                    existing.message_id = msg.message_id.clone();
+                }
+                push_missing(&mut existing.received_by, &msg.received_by);
+                for source in &msg.sources {
+                    add_source(&mut existing.sources, source);
+                }
+            }
+            None => merged.messages.push(msg.clone()),
+        }
+    }
+    push_missing(&mut merged.labels, &theirs.labels);
+    push_missing(&mut merged.accounts, &theirs.accounts);
+    push_missing(&mut merged.tags, &theirs.tags);
+    push_missing(&mut merged.notes, &theirs.notes);
+    merged.messages.sort_by_key(|m| parse_msg_date(&m.date));
+    merged.last_date = merged
+        .messages
+        .last()
+        .map(|m| m.date.clone())
+        .unwrap_or_default();
+    merged.participants = collect_participants(&merged.messages);
+    tag_newsletter(&mut merged);
+    merged
+}
+
+/// What [`merge_message`] did with a message.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeOutcome {
+    /// Started a new conversation file.
+    Created,
+    /// Added the message to an existing conversation file.
+    Appended,
+    /// Already stored; only labels/accounts were updated.
+    Duplicate,
+}
+
+/// Merge a single message into its thread file on disk, with `[sync]`
+/// from .corky.toml.
 ///
 /// Returns the path of the written file, or None if only metadata updated.
 pub fn merge_message_to_file(
@@ -130,148 +473,270 @@ pub fn merge_message_to_file(
     message: &Message,
     thread_key: &str,
 ) -> Result<Option<PathBuf>> {
-    std::fs::create_dir_all(out_dir)?;
+    let sync_config = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    merge_message(
+        out_dir,
+        label_name,
+        &[],
+        account_name,
+        message,
+        thread_key,
+        &sync_config,
+    )
+    .map(|(path, _)| path)
+}
 
-    let existing_file = find_thread_file(out_dir, thread_key);
-    let mut thread: Thread = if let Some(ref ef) = existing_file {
-        let text = std::fs::read_to_string(ef)?;
-        parse_thread_markdown(&text).unwrap_or_else(|| Thread {
-            id: thread_key.to_string(),
-            subject: message.subject.clone(),
-            ..Default::default()
-        })
-    } else {
-        Thread {
-            id: thread_key.to_string(),
-            subject: message.subject.clone(),
-            ..Default::default()
-        }
+/// [`merge_message_to_file`] with the caller's `[sync]` settings, also
+/// reporting whether the message started a thread, joined one, or was
+/// already there. `extra_labels` (trusted sender auto-labels) are added to
+/// the thread along with `label_name`.
+pub fn merge_message(
+    out_dir: &Path,
+    label_name: &str,
+    extra_labels: &[String],
+    account_name: &str,
+    message: &Message,
+    thread_key: &str,
+    sync_config: &SyncConfig,
+) -> Result<(Option<PathBuf>, MergeOutcome)> {
+    std::fs::create_dir_all(out_dir)?;
+    let store = open_store_with(out_dir, false, sync_config);
+    let file_of = |key: &str| out_dir.join(format!("{}.md", key));
+    let existing = store.find(thread_key);
+    let loaded = match existing.as_deref() {
+        Some(key) => store.load(key)?,
+        None => None,
     };
+    let mut thread: Thread = loaded.unwrap_or_else(|| Thread {
+        id: thread_key.to_string(),
+        subject: message.subject.clone(),
+        ..Default::default()
+    });
 
     // Accumulate labels and accounts
-    if !label_name.is_empty() && !thread.labels.contains(&label_name.to_string()) {
-        thread.labels.push(label_name.to_string());
+    for label in std::iter::once(label_name).chain(extra_labels.iter().map(String::as_str)) {
+        if !label.is_empty() && !thread.labels.iter().any(|l| l == label) {
+            thread.labels.push(label.to_string());
+        }
     }
     if !account_name.is_empty() && !thread.accounts.contains(&account_name.to_string()) {
         thread.accounts.push(account_name.to_string());
     }
 
-    // Deduplicate by (from, date)
-    let seen: HashSet<(&str, &str)> = thread
-        .messages
-        .iter()
-        .map(|m| (m.from.as_str(), m.date.as_str()))
-        .collect();
-    if seen.contains(&(message.from.as_str(), message.date.as_str())) {
+    // Deduplicate by Message-ID (same message seen via several accounts),
+    // falling back to (from, date) for messages without one.
+    let dupe = thread.messages.iter_mut().find(|m| is_same_message(m, message));
+    if let Some(existing) = dupe {
         // Still update labels/accounts even if message is a dupe
-        if let Some(ref ef) = existing_file {
-            std::fs::write(ef, thread_to_markdown(&thread))?;
-            let _ = set_mtime(ef, &thread.last_date);
+        if existing.message_id.is_empty() {
+            existing.message_id = message.message_id.clone();
         }
-        return Ok(existing_file);
+        if !account_name.is_empty() && !existing.received_by.iter().any(|a| a == account_name) {
+            existing.received_by.push(account_name.to_string());
+        }
+        for source in &message.sources {
+            add_source(&mut existing.sources, source);
+        }
+        thread.participants = collect_participants(&thread.messages);
+        tag_newsletter(&mut thread);
+        if let Some(ref key) = existing {
+            store.save(key, &thread)?;
+        }
+        return Ok((existing.as_deref().map(file_of), MergeOutcome::Duplicate));
     }
 
-    thread.messages.push(message.clone());
+    let mut message = message.clone();
+    if !account_name.is_empty() && !message.received_by.iter().any(|a| a == account_name) {
+        message.received_by.push(account_name.to_string());
+    }
+    thread.messages.push(message);
     thread.messages.sort_by_key(|m| parse_msg_date(&m.date));
     thread.last_date = thread
         .messages
         .last()
         .map(|m| m.date.clone())
         .unwrap_or_default();
+    thread.participants = collect_participants(&thread.messages);
+    tag_newsletter(&mut thread);
 
-    let file_path = if let Some(ef) = existing_file {
-        ef
+    let outcome = if existing.is_some() {
+        MergeOutcome::Appended
     } else {
-        let slug = unique_slug(out_dir, &slugify(&thread.subject));
-        out_dir.join(format!("{}.md", slug))
+        MergeOutcome::Created
+    };
+    let key = if let Some(key) = existing {
+        key
+    } else {
+        let first_date = thread.messages.first().map(|m| m.date.as_str()).unwrap_or("");
+        let stem = thread_file_stem(
+            &sync_config.filename_scheme,
+            &thread.subject,
+            first_date,
+            &sync_config.strip_prefixes,
+        );
+        store.new_key(&stem)
     };
 
-    std::fs::write(&file_path, thread_to_markdown(&thread))?;
-    let _ = set_mtime(&file_path, &thread.last_date);
+    store.save(&key, &thread)?;
+    Ok((Some(file_of(&key)), outcome))
+}
 
-    println!(
-        "  Wrote: {}",
-        file_path.file_name().unwrap_or_default().to_string_lossy()
-    );
-    Ok(Some(file_path))
+/// Resolve one `[routing]` value to the directory threads are written to.
+///
+/// Absolute and `~/` values are external directories (e.g. a notes vault
+/// folder) used as-is; they must already exist. Anything else is a mailbox
+/// path relative to data_dir, with `conversations/` appended.
+pub fn route_target(data_dir: &Path, label: &str, value: &str) -> Result<PathBuf> {
+    if value != "~" && !value.starts_with("~/") && !Path::new(value).is_absolute() {
+        return Ok(data_dir.join(value).join("conversations"));
+    }
+    let dir = resolve::expand_tilde(value);
+    if !dir.is_dir() {
+        let problem = if dir.exists() { "is not a directory" } else { "does not exist" };
+        return Err(crate::error::CorkyError::Config(format!(
+            "[routing] target for '{}' {}: {} (external targets must be existing directories)",
+            label,
+            problem,
+            dir.display()
+        ))
+        .into());
+    }
+    Ok(dir)
 }
 
 /// Build label→output_dirs map from .corky.toml [routing].
 ///
 /// Fan-out: one label can route to multiple mailbox directories.
 /// Supports `account:label` syntax for per-account binding.
-pub fn build_label_routes(account_name: &str) -> std::collections::HashMap<String, Vec<PathBuf>> {
+pub fn build_label_routes(
+    account_name: &str,
+) -> Result<std::collections::HashMap<String, Vec<PathBuf>>> {
     let mut routes: std::collections::HashMap<String, Vec<PathBuf>> = std::collections::HashMap::new();
     let config = match corky_config::try_load_config(None) {
         Some(c) => c,
-        None => return routes,
+        None => return Ok(routes),
     };
     let data_dir = resolve::data_dir();
     for (label_key, mailbox_paths) in &config.routing {
-        if label_key.contains(':') {
-            let parts: Vec<&str> = label_key.splitn(2, ':').collect();
-            let label_account = parts[0];
-            let label_name = parts[1];
-            if !account_name.is_empty() && label_account != account_name {
-                continue;
+        let label_name = match label_key.split_once(':') {
+            Some((label_account, label_name)) => {
+                if !account_name.is_empty() && label_account != account_name {
+                    continue;
+                }
+                label_name
             }
-            let dirs: Vec<PathBuf> = mailbox_paths
-                .iter()
-                .map(|p| data_dir.join(p).join("conversations"))
-                .collect();
-            routes.entry(label_name.to_string()).or_default().extend(dirs);
-        } else {
-            let dirs: Vec<PathBuf> = mailbox_paths
-                .iter()
-                .map(|p| data_dir.join(p).join("conversations"))
-                .collect();
-            routes.entry(label_key.clone()).or_default().extend(dirs);
-        }
+            None => label_key.as_str(),
+        };
+        let dirs = mailbox_paths
+            .iter()
+            .map(|p| route_target(&data_dir, label_key, p))
+            .collect::<Result<Vec<PathBuf>>>()?;
+        routes.entry(label_name.to_string()).or_default().extend(dirs);
     }
-    routes
+    Ok(routes)
 }
 
+#[cfg(all(feature = "net", not(feature = "tls-rustls")))]
 pub type ImapSession = Session<TlsStream<TcpStream>>;
+#[cfg(all(feature = "net", feature = "tls-rustls"))]
+pub type ImapSession = Session<super::tls::TlsStream>;
 
 /// Connect to IMAP server (public API for other modules).
+#[cfg(feature = "net")]
 pub fn connect_imap_pub(
     host: &str,
     port: u16,
     starttls: bool,
     user: &str,
-    password: &str,
+    secret: &Secret,
 ) -> Result<ImapSession> {
-    connect_imap(host, port, starttls, user, password)
+    connect_imap(host, port, starttls, user, secret)
 }
 
-/// Connect to IMAP server.
+/// Connect to IMAP server. Failures surface as [`CorkyError::Network`].
+#[cfg(feature = "net")]
 fn connect_imap(
     host: &str,
     port: u16,
     starttls: bool,
     user: &str,
-    password: &str,
+    secret: &Secret,
 ) -> Result<ImapSession> {
-    let mut tls_builder = native_tls::TlsConnector::builder();
+    open_imap(host, port, starttls, user, secret).map_err(|e| {
+        let msg = format!("IMAP connection to {}:{} failed: {:#}", host, port, e);
+        CorkyError::Network(msg).into()
+    })
+}
+
+/// SASL XOAUTH2 (Gmail, Office365). The imap crate base64-encodes the
+/// response. A failed login gets a challenge carrying the error, which is
+/// answered with an empty response so the server sends its NO.
+#[cfg(feature = "net")]
+struct XOAuth2<'a> {
+    user: &'a str,
+    token: &'a str,
+}
 
-    if starttls || host == "127.0.0.1" || host == "localhost" {
-        tls_builder.danger_accept_invalid_certs(true);
-        tls_builder.danger_accept_invalid_hostnames(true);
+#[cfg(feature = "net")]
+impl imap::Authenticator for XOAuth2<'_> {
+    type Response = String;
+
+    fn process(&self, challenge: &[u8]) -> String {
+        if challenge.is_empty() {
+            format!("user={}\x01auth=Bearer {}\x01\x01", self.user, self.token)
+        } else {
+            String::new()
+        }
     }
+}
 
-    let tls = tls_builder.build()?;
+/// Open a TLS connection and log in with the password, or with XOAUTH2.
+#[cfg(feature = "net")]
+fn open_imap(
+    host: &str,
+    port: u16,
+    starttls: bool,
+    user: &str,
+    secret: &Secret,
+) -> Result<ImapSession> {
+    // Bridges and local test servers commonly use self-signed certs.
+    let accept_invalid = starttls || host == "127.0.0.1" || host == "localhost";
 
-    let client = if starttls {
-        imap::connect_starttls((host, port), host, &tls)?
-    } else {
-        imap::connect((host, port), host, &tls)?
+    #[cfg(feature = "tls-rustls")]
+    let client = super::tls::connect(host, port, starttls, accept_invalid)?;
+
+    #[cfg(not(feature = "tls-rustls"))]
+    let client = {
+        let mut tls_builder = native_tls::TlsConnector::builder();
+        if accept_invalid {
+            tls_builder.danger_accept_invalid_certs(true);
+            tls_builder.danger_accept_invalid_hostnames(true);
+        }
+        let tls = tls_builder.build()?;
+        if starttls {
+            imap::connect_starttls((host, port), host, &tls)?
+        } else {
+            imap::connect((host, port), host, &tls)?
+        }
     };
 
-    let session = client.login(user, password).map_err(|e| e.0)?;
+    let session = match secret {
+        Secret::Password(password) => client.login(user, password).map_err(|e| e.0)?,
+        Secret::OAuth2(token) => client
+            .authenticate("XOAUTH2", &XOAuth2 { user, token })
+            .map_err(|e| e.0)?,
+    };
     Ok(session)
 }
 
 /// Sync all labels for one account.
+///
+/// Returns one report per label synced; see [`LabelReport::skipped`] for
+/// messages that could not be parsed. With `headers_only`, only header
+/// blocks are fetched and new messages get [`HEADERS_ONLY_BODY`].
+#[cfg(feature = "net")]
 #[allow(clippy::too_many_arguments)]
 pub fn sync_account(
     account_name: &str,
@@ -279,14 +744,62 @@ pub fn sync_account(
     port: u16,
     starttls: bool,
     user: &str,
-    password: &str,
+    secret: &Secret,
+    labels: &[String],
+    sync_days: u32,
+    label_overrides: &HashMap<String, LabelOverride>,
+    state: &mut SyncState,
+    full: bool,
+    headers_only: bool,
+    base_dir: Option<&Path>,
+    touched: Option<&mut HashSet<PathBuf>>,
+) -> Result<Vec<LabelReport>> {
+    let (reports, session) = sync_account_session(
+        account_name,
+        host,
+        port,
+        starttls,
+        user,
+        secret,
+        labels,
+        sync_days,
+        label_overrides,
+        state,
+        full,
+        headers_only,
+        base_dir,
+        touched,
+    )?;
+    // Logout errors are non-fatal — data is already fetched and merged.
+    // Some servers (e.g. ProtonMail Bridge) return responses the imap
+    // crate cannot parse during logout.
+    if let Some(mut session) = session {
+        let _ = session.logout();
+    }
+    Ok(reports)
+}
+
+/// [`sync_account`], handing back the logged-in session (None when the
+/// account has no labels to sync) so the caller can reuse it, e.g. to push
+/// drafts (`sync --then-push`), and log out itself.
+#[cfg(feature = "net")]
+#[allow(clippy::too_many_arguments)]
+pub fn sync_account_session(
+    account_name: &str,
+    host: &str,
+    port: u16,
+    starttls: bool,
+    user: &str,
+    secret: &Secret,
     labels: &[String],
     sync_days: u32,
+    label_overrides: &HashMap<String, LabelOverride>,
     state: &mut SyncState,
     full: bool,
+    headers_only: bool,
     base_dir: Option<&Path>,
     mut touched: Option<&mut HashSet<PathBuf>>,
-) -> Result<()> {
+) -> Result<(Vec<LabelReport>, Option<ImapSession>)> {
     let base_dir = base_dir
         .map(PathBuf::from)
         .unwrap_or_else(resolve::conversations_dir);
@@ -295,7 +808,19 @@ pub fn sync_account(
         .entry(account_name.to_string())
         .or_default();
 
-    let routes = build_label_routes(account_name);
+    let routes = build_label_routes(account_name)?;
+    let config = corky_config::try_load_config(None);
+    let sync_config = config
+        .as_ref()
+        .and_then(|c| c.sync.clone())
+        .unwrap_or_default();
+    let blocklist = Blocklist::load()?;
+    let redactors = Redactors::load()?;
+    let auto_labels = AutoLabels::load()?;
+    let index_hook = match config.as_ref().and_then(|c| c.accounts.get(account_name)) {
+        Some(acct) => IndexHook::from_account(acct)?,
+        None => None,
+    };
 
     // Merge shared labels into sync set (preserving order, no dupes)
     let mut all_labels: Vec<String> = Vec::new();
@@ -307,44 +832,192 @@ pub fn sync_account(
     }
 
     if all_labels.is_empty() {
-        println!(
+        crate::progress!(
             "  No labels configured for account '{}' \u{2014} skipping",
             account_name
         );
-        return Ok(());
+        return Ok((Vec::new(), None));
     }
 
-    println!("Connecting to {}:{} as {}", host, port, user);
+    crate::progress!("Connecting to {}:{} as {}", host, port, user);
 
-    let mut session = connect_imap(host, port, starttls, user, password)?;
+    let mut session = profile::time(account_name, "connect", || {
+        connect_imap(host, port, starttls, user, secret)
+    })?;
 
-    for label in &all_labels {
+    let mut reports = Vec::new();
+    let mut attempt = 0;
+    let mut i = 0;
+    let mut started = Instant::now();
+    let mut report = LabelReport::default();
+    while i < all_labels.len() {
+        let label = &all_labels[i];
+        if report.label != *label {
+            started = Instant::now();
+            report = LabelReport {
+                account: account_name.to_string(),
+                label: label.clone(),
+                ..Default::default()
+            };
+        }
         // Collect all output dirs: base + any fan-out routes
         let mut out_dirs = vec![base_dir.clone()];
         if let Some(dirs) = routes.get(label) {
             out_dirs.extend(dirs.iter().cloned());
         }
 
-        sync_label(
+        let limits = label_overrides.get(label).cloned().unwrap_or_default();
+        let result = sync_label(
             &mut session,
             label,
             account_name,
             acct_state,
             full,
-            sync_days,
+            headers_only,
+            limits.days.unwrap_or(sync_days),
+            limits.max_messages,
             &out_dirs,
+            &routes,
+            &sync_config,
+            &blocklist,
+            &redactors,
+            &auto_labels,
+            index_hook.as_ref(),
             &mut touched,
-        )?;
+            &mut report,
+        );
+        match result {
+            Ok(()) => {
+                report.elapsed_secs = started.elapsed().as_secs_f64();
+                reports.push(std::mem::take(&mut report));
+                attempt = 0;
+                i += 1;
+            }
+            Err(e) if throttle::is_throttle(&e) && attempt < sync_config.throttle_retries => {
+                // Progress up to the last checkpointed UID is kept in
+                // acct_state; the retry resumes the label from there.
+                session = reconnect_after_throttle(
+                    &e,
+                    label,
+                    &mut attempt,
+                    &sync_config,
+                    (host, port, starttls, user, secret),
+                )?;
+            }
+            Err(e) if throttle::is_throttle(&e) => {
+                return Err(CorkyError::Network(format!(
+                    "Still throttled after {} retries syncing label {}: {:#}",
+                    sync_config.throttle_retries, label, e
+                ))
+                .into());
+            }
+            Err(e) => return Err(e),
+        }
     }
 
-    // Logout errors are non-fatal — data is already fetched and merged.
-    // Some servers (e.g. ProtonMail Bridge) return responses the imap
-    // crate cannot parse during logout.
-    let _ = session.logout();
+    // Labels no longer synced for this account are no longer sources.
+    let dropped: HashSet<String> = acct_state
+        .threads
+        .values()
+        .flat_map(|labels| labels.keys())
+        .filter(|label| !seen_labels.contains(*label))
+        .cloned()
+        .collect();
+    for label in dropped {
+        provenance::forget_label(acct_state, &label);
+    }
+
+    Ok((reports, Some(session)))
+}
+
+/// Wait out a throttle response (doubling the delay per attempt) and log
+/// in again. A reconnect that is itself throttled waits and tries again.
+#[cfg(feature = "net")]
+fn reconnect_after_throttle(
+    err: &anyhow::Error,
+    label: &str,
+    attempt: &mut u32,
+    sync_config: &SyncConfig,
+    (host, port, starttls, user, secret): (&str, u16, bool, &str, &Secret),
+) -> Result<ImapSession> {
+    let mut reason = format!("{:#}", err);
+    loop {
+        let wait = throttle::delay(sync_config.throttle_backoff, *attempt);
+        *attempt += 1;
+        crate::progress!(
+            "  Throttled by server during \"{}\" ({}); retry {}/{} in {}s",
+            label,
+            reason.trim(),
+            attempt,
+            sync_config.throttle_retries,
+            wait.as_secs()
+        );
+        std::thread::sleep(wait);
+        match connect_imap(host, port, starttls, user, secret) {
+            Ok(session) => {
+                crate::progress!("  Reconnected; resuming \"{}\"", label);
+                return Ok(session);
+            }
+            Err(e) if throttle::is_throttle(&e) && *attempt < sync_config.throttle_retries => {
+                reason = format!("{:#}", e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Message-IDs of `uids` from their envelopes, fetched in batches.
+#[cfg(feature = "net")]
+fn fetch_message_ids(session: &mut ImapSession, uids: &[u32]) -> Result<HashMap<u32, String>> {
+    let mut ids = HashMap::new();
+    for chunk in uids.chunks(ENVELOPE_BATCH) {
+        let set: Vec<String> = chunk.iter().map(u32::to_string).collect();
+        let fetches = session.uid_fetch(set.join(","), "(UID ENVELOPE)")?;
+        for fetch in fetches.iter() {
+            let (Some(uid), Some(envelope)) = (fetch.uid, fetch.envelope()) else {
+                continue;
+            };
+            let Some(raw) = envelope.message_id.as_ref() else {
+                continue;
+            };
+            let id = String::from_utf8_lossy(raw).trim().to_string();
+            if !id.is_empty() {
+                ids.insert(uid, id);
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// `[sync] pin_flagged`: pin the conversation in `base_dir` of every thread
+/// with a `\Flagged` message in the selected label. Pins are only added.
+#[cfg(feature = "net")]
+fn pin_flagged(
+    session: &mut ImapSession,
+    acct_state: &AccountSyncState,
+    label_name: &str,
+    base_dir: &Path,
+    sync_config: &SyncConfig,
+) -> Result<()> {
+    let flagged: HashSet<u32> = session.uid_search("FLAGGED")?.into_iter().collect();
+    if flagged.is_empty() {
+        return Ok(());
+    }
+    for thread_id in provenance::threads_with_uids(acct_state, label_name, &flagged) {
+        let Some(path) = find_thread_file(base_dir, &thread_id) else {
+            continue;
+        };
+        if crate::thread::pin::set_pinned(&path, true, sync_config)? {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            crate::progress!("  Pinned (\\Flagged): {}", name);
+        }
+    }
     Ok(())
 }
 
 /// Sync a single IMAP label/folder, writing to multiple output dirs (fan-out).
+/// Counts go into `report`, which survives a throttle retry of the label.
+#[cfg(feature = "net")]
 #[allow(clippy::too_many_arguments)]
 fn sync_label(
     session: &mut ImapSession,
@@ -352,149 +1025,358 @@ fn sync_label(
     account_name: &str,
     acct_state: &mut AccountSyncState,
     full: bool,
+    headers_only: bool,
     sync_days: u32,
+    max_messages: Option<u32>,
     out_dirs: &[PathBuf],
+    routes: &HashMap<String, Vec<PathBuf>>,
+    sync_config: &SyncConfig,
+    blocklist: &Blocklist,
+    redactors: &Redactors,
+    auto_labels: &AutoLabels,
+    index_hook: Option<&IndexHook>,
     touched: &mut Option<&mut HashSet<PathBuf>>,
+    report: &mut LabelReport,
 ) -> Result<()> {
-    println!("Syncing label: {}", label_name);
+    crate::progress!("Syncing label: {}", label_name);
+    let scope = format!("{}/{}", account_name, label_name);
 
-    let mailbox = match session.select(label_name) {
+    let mailbox = match profile::time(&scope, "select", || session.select(label_name)) {
         Ok(mb) => mb,
         Err(_) => {
-            println!("  Label \"{}\" not found \u{2014} skipping", label_name);
+            crate::progress!("  Label \"{}\" not found \u{2014} skipping", label_name);
+            provenance::forget_label(acct_state, label_name);
             return Ok(());
         }
     };
 
     let uidvalidity = mailbox.uid_validity.unwrap_or(0);
-    let prior = acct_state.labels.get(label_name);
+    let uid_next = mailbox.uid_next;
+    let prior = acct_state.labels.get(label_name).cloned();
+    let prior = prior.as_ref();
 
-    let do_full = full || prior.is_none() || prior.map(|p| p.uidvalidity) != Some(uidvalidity);
+    // UIDNEXT at or below our last UID means the server renumbered the
+    // folder without bumping UIDVALIDITY; either way the old UIDs are void.
+    // UIDVALIDITY 0 is state rebuilt by `sync repair`: take the server's.
+    let same_validity = |p: &LabelState| p.uidvalidity == uidvalidity || p.uidvalidity == 0;
+    let renumbered = prior
+        .is_some_and(|p| same_validity(p) && uid_next.is_some_and(|next| next <= p.last_uid));
+    let uids_void = renumbered || prior.is_some_and(|p| !same_validity(p));
+    let last_uid = match prior {
+        Some(p) if !uids_void => p.last_uid,
+        _ => 0,
+    };
 
-    let uids: Vec<u32> = if do_full {
+    // Drop provenance for messages that have left the label. Void UIDs
+    // mean nothing; the resync records them anew.
+    if uids_void {
+        provenance::forget_label(acct_state, label_name);
+    } else {
+        let known = provenance::label_uids(acct_state, label_name);
+        if let (Some(first), Some(last)) = (known.first(), known.last()) {
+            let query = format!("UID {}:{}", first, last);
+            let found = profile::time(&scope, "search", || session.uid_search(query))?;
+            let present: HashSet<u32> = found.into_iter().collect();
+            let gone = provenance::retain_uids(acct_state, label_name, &present);
+            if gone > 0 {
+                crate::progress!("  {} synced message(s) no longer in label", gone);
+            }
+        }
+    }
+
+    let do_full = full || prior.is_none() || uids_void;
+
+    let mut uids: Vec<u32> = if do_full {
         if let Some(p) = prior {
-            if p.uidvalidity != uidvalidity {
-                println!("  UIDVALIDITY changed \u{2014} doing full resync");
+            if !same_validity(p) {
+                crate::progress!("  UIDVALIDITY changed \u{2014} doing full resync");
+            } else if renumbered {
+                crate::progress!("  UIDNEXT went backwards \u{2014} doing full resync");
             } else if full {
-                println!("  Full sync requested");
+                crate::progress!("  Full sync requested");
             }
         } else {
-            println!("  No prior state \u{2014} doing full sync");
+            crate::progress!("  No prior state \u{2014} doing full sync");
         }
 
         let since_date = Utc::now() - chrono::Duration::days(sync_days as i64);
         let since_str = since_date.format("%d-%b-%Y").to_string();
-        let search_result = session.uid_search(format!("SINCE {}", since_str))?;
+        let query = format!("SINCE {}", since_str);
+        let search_result = profile::time(&scope, "search", || session.uid_search(query))?;
         search_result.into_iter().collect()
+    } else if uid_next == Some(last_uid + 1) {
+        // Nothing has arrived since last_uid; skip the search. (`UID n:*`
+        // returns the highest existing UID on some servers even when n is
+        // past it.)
+        Vec::new()
     } else {
-        let prior = prior.unwrap();
-        let search_result = session.uid_search(format!("UID {}:*", prior.last_uid + 1))?;
+        let query = format!("UID {}:*", last_uid + 1);
+        let search_result = profile::time(&scope, "search", || session.uid_search(query))?;
         search_result
             .into_iter()
-            .filter(|&u| u > prior.last_uid)
+            .filter(|&u| u > last_uid)
             .collect()
     };
+    // Ascending, so a checkpointed last_uid means everything up to it is stored.
+    uids.sort_unstable();
+    if let Some(max) = max_messages.map(|m| m as usize) {
+        if do_full && uids.len() > max {
+            crate::progress!(
+                "  Keeping the newest {} of {} message(s) (max_messages)",
+                max,
+                uids.len()
+            );
+            uids.drain(..uids.len() - max);
+        }
+    }
 
     if uids.is_empty() {
-        println!("  No new messages");
+        crate::progress!("  No new messages");
+        if sync_config.pin_flagged {
+            pin_flagged(session, acct_state, label_name, &out_dirs[0], sync_config)?;
+        }
         acct_state.labels.insert(
             label_name.to_string(),
             LabelState {
                 uidvalidity,
-                last_uid: prior.map(|p| p.last_uid).unwrap_or(0),
+                last_uid,
             },
         );
         return Ok(());
     }
 
-    println!("  Fetching {} message(s)", uids.len());
+    crate::progress!("  Fetching {} message(s)", uids.len());
+
+    // Envelopes first: messages already stored in every output dir only
+    // need their labels/accounts updated, not their bodies downloaded.
+    // The indexes also thread new messages by their references.
+    let message_ids = profile::time(&scope, "fetch", || fetch_message_ids(session, &uids))?;
+    let mut indexes: Vec<HashMap<String, String>> = profile::time(&scope, "scan", || {
+        out_dirs.iter().map(|d| message_id_index(d)).collect()
+    });
+    let prefix_re = subject_prefix_regex(&sync_config.strip_prefixes);
 
-    let mut max_uid = prior.map(|p| p.last_uid).unwrap_or(0);
+    let mut max_uid = last_uid;
+    let mut known = 0;
+    let mut created: HashSet<PathBuf> = HashSet::new();
+    let mut updated: HashSet<PathBuf> = HashSet::new();
 
     for uid in &uids {
-        let fetches = session.uid_fetch(uid.to_string(), "RFC822")?;
+        // Checkpoint before each message: if the server throttles or drops
+        // us mid-label, the retry (or next sync) resumes from here. Nothing
+        // stored yet means the retry must search from scratch.
+        if max_uid > 0 {
+            acct_state.labels.insert(
+                label_name.to_string(),
+                LabelState {
+                    uidvalidity,
+                    last_uid: max_uid,
+                },
+            );
+        }
+
+        let stored = message_ids.get(uid).and_then(|mid| {
+            let key = mid.to_lowercase();
+            let thread_ids: Option<Vec<String>> =
+                indexes.iter().map(|idx| idx.get(&key).cloned()).collect();
+            thread_ids.map(|ids| (mid, ids))
+        });
+        let source = MessageSource {
+            account: account_name.to_string(),
+            label: label_name.to_string(),
+            uid: *uid,
+        };
+        if let Some((mid, thread_ids)) = stored {
+            for (out_dir, thread_id) in out_dirs.iter().zip(&thread_ids) {
+                let seen = Message {
+                    id: uid.to_string(),
+                    thread_id: thread_id.clone(),
+                    message_id: mid.clone(),
+                    sources: vec![source.clone()],
+                    ..Default::default()
+                };
+                let (file_path, _) = profile::time(&scope, "merge", || {
+                    merge_message(
+                        out_dir,
+                        label_name,
+                        &[],
+                        account_name,
+                        &seen,
+                        thread_id,
+                        sync_config,
+                    )
+                })?;
+                if let (Some(touched_set), Some(fp)) = (touched.as_mut(), file_path) {
+                    touched_set.insert(fp);
+                }
+            }
+            if let Some(thread_id) = thread_ids.first() {
+                provenance::record(acct_state, thread_id, label_name, *uid);
+            }
+            known += 1;
+            report.already_stored += 1;
+            max_uid = max_uid.max(*uid);
+            continue;
+        }
+
+        // BODY.PEEK[] leaves \Seen alone; RFC822 and BODY[] set it.
+        let body_item = if headers_only {
+            "BODY.PEEK[HEADER]"
+        } else if sync_config.mark_read {
+            "BODY[]"
+        } else {
+            "BODY.PEEK[]"
+        };
+        let fetches =
+            profile::time(&scope, "fetch", || session.uid_fetch(uid.to_string(), body_item))?;
         let fetch = match fetches.iter().next() {
             Some(f) => f,
             None => continue,
         };
 
-        let body_raw = match fetch.body() {
+        let raw = if headers_only { fetch.header() } else { fetch.body() };
+        let body_raw = match raw {
             Some(b) => b,
             None => continue,
         };
 
-        let parsed = match mailparse::parse_mail(body_raw) {
+        let parsed = match profile::time(&scope, "parse", || mailparse::parse_mail(body_raw)) {
             Ok(p) => p,
             Err(e) => {
                 eprintln!("  Warning: failed to parse message UID {}: {}", uid, e);
+                report.skipped += 1;
                 continue;
             }
         };
 
-        let subject = parsed
-            .headers
-            .iter()
-            .find(|h| h.get_key_ref().eq_ignore_ascii_case("Subject"))
-            .map(|h| h.get_value())
-            .unwrap_or_else(|| "(no subject)".to_string());
-
-        let from = parsed
-            .headers
-            .iter()
-            .find(|h| h.get_key_ref().eq_ignore_ascii_case("From"))
-            .map(|h| h.get_value())
-            .unwrap_or_default();
-
-        let to = parsed
-            .headers
-            .iter()
-            .find(|h| h.get_key_ref().eq_ignore_ascii_case("To"))
-            .map(|h| h.get_value())
-            .unwrap_or_default();
-
-        let cc = parsed
-            .headers
-            .iter()
-            .find(|h| h.get_key_ref().eq_ignore_ascii_case("Cc"))
-            .map(|h| h.get_value())
-            .unwrap_or_default();
+        let mut message = message_headers(&parsed, *uid);
+        message.sources.push(source);
+        if blocklist.matches(&message.from, &message.subject) {
+            crate::progress!("  Skipped (blocklist): UID {} from {}", uid, message.from);
+            if *uid > max_uid {
+                max_uid = *uid;
+            }
+            continue;
+        }
 
-        let date = parsed
-            .headers
-            .iter()
-            .find(|h| h.get_key_ref().eq_ignore_ascii_case("Date"))
-            .map(|h| h.get_value())
-            .unwrap_or_default();
-
-        let thread_key = thread_key_from_subject(&subject);
-        let body = extract_body(&parsed);
-
-        let message = Message {
-            id: uid.to_string(),
-            thread_id: thread_key.clone(),
-            from,
-            to,
-            cc,
-            date,
-            subject,
-            body,
+        let thread_key = thread_key_for(
+            &message,
+            &reference_ids(&parsed),
+            &indexes,
+            &out_dirs[0],
+            &prefix_re,
+        );
+        message.thread_id = thread_key.clone();
+        message.body = if headers_only {
+            HEADERS_ONLY_BODY.to_string()
+        } else {
+            truncate_body(
+                &extract_body(&parsed),
+                sync_config.max_body_bytes,
+                &format!("account {}, label {}, UID {}", account_name, label_name, uid),
+            )
         };
+        let (body, secrets) = redactors.redact_secrets(&message.body);
+        if secrets > 0 {
+            crate::progress!("  Redacted {} secret(s) in UID {}", secrets, uid);
+            message.body = body;
+        }
 
-        for out_dir in out_dirs {
-            let file_path =
-                merge_message_to_file(out_dir, label_name, account_name, &message, &thread_key)?;
+        // Trusted senders: the contact's labels, and their routes.
+        let extra_labels = auto_labels.labels_for(&message.from, label_name);
+        let mut dirs = out_dirs.to_vec();
+        for dir in extra_labels.iter().filter_map(|l| routes.get(l)).flatten() {
+            if !dirs.contains(dir) {
+                dirs.push(dir.clone());
+            }
+        }
+        if !extra_labels.is_empty() {
+            crate::progress!(
+                "  Auto-labeled UID {} from {}: {}",
+                uid,
+                message.from,
+                extra_labels.join(", ")
+            );
+        }
+
+        for (n, out_dir) in dirs.iter().enumerate() {
+            let redacted = redactors.for_dir(out_dir).map(|r| r.redact_message(&message));
+            let (file_path, outcome) = profile::time(&scope, "merge", || {
+                merge_message(
+                    out_dir,
+                    label_name,
+                    &extra_labels,
+                    account_name,
+                    redacted.as_ref().unwrap_or(&message),
+                    &thread_key,
+                    sync_config,
+                )
+            })?;
+            // out_dirs[0] is the base dir; the rest are routed copies.
+            match (n, outcome, &file_path) {
+                (_, MergeOutcome::Duplicate, _) => {}
+                (0, MergeOutcome::Created, Some(fp)) => {
+                    report.new_messages += 1;
+                    created.insert(fp.clone());
+                }
+                (0, _, Some(fp)) => {
+                    report.new_messages += 1;
+                    updated.insert(fp.clone());
+                }
+                (0, _, None) => report.new_messages += 1,
+                _ => report.routed_copies += 1,
+            }
             if let Some(touched_set) = touched {
                 if let Some(ref fp) = file_path {
                     touched_set.insert(fp.clone());
                 }
             }
         }
+        provenance::record(acct_state, &thread_key, label_name, *uid);
+        if !message.message_id.is_empty() {
+            let key = message.message_id.trim().to_lowercase();
+            for index in indexes.iter_mut() {
+                index.insert(key.clone(), thread_key.clone());
+            }
+        }
+
+        // A headers-only fetch has no raw message to index.
+        if let Some(hook) = index_hook.filter(|_| !headers_only) {
+            if let Err(e) = hook.deliver(
+                account_name,
+                label_name,
+                uidvalidity,
+                *uid,
+                &message.message_id,
+                body_raw,
+            ) {
+                eprintln!("  Warning: index hook failed for UID {}: {}", uid, e);
+            }
+        }
 
         if *uid > max_uid {
             max_uid = *uid;
         }
     }
 
+    let new_threads = created.len();
+    let updated_threads = updated.difference(&created).count();
+    crate::progress!(
+        "  {} new message(s): {} new thread(s), {} updated",
+        report.new_messages,
+        new_threads,
+        updated_threads
+    );
+    if report.routed_copies > 0 {
+        crate::progress!("  {} routed copy(ies)", report.routed_copies);
+    }
+    if known > 0 {
+        crate::progress!("  {} already stored (envelope only)", known);
+    }
+    if sync_config.pin_flagged {
+        pin_flagged(session, acct_state, label_name, &out_dirs[0], sync_config)?;
+    }
+
     acct_state.labels.insert(
         label_name.to_string(),
         LabelState {
@@ -503,5 +1385,7 @@ fn sync_label(
         },
     );
 
+    report.new_threads += new_threads;
+    report.updated_threads += updated_threads;
     Ok(())
 }
@@ -0,0 +1,165 @@
+//! Per-account index hook: mirror synced messages into a Maildir or notmuch.
+//!
+//! Configured with `index_hook` / `index_path` on an `[accounts.NAME]` entry.
+//! Hook failures are reported as warnings and never fail the sync.
+
+use anyhow::{bail, Result};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::accounts::Account;
+use crate::resolve;
+use crate::util::slugify;
+
+/// Where raw messages go after they're merged into conversation files.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IndexHook {
+    /// Write each message to `{root}/{label-slug}/new/` (Maildir layout).
+    Maildir(PathBuf),
+    /// Pipe each message to `notmuch insert`, into `{folder}/{label-slug}`.
+    Notmuch(String),
+}
+
+impl IndexHook {
+    /// Build the hook for an account. `None` when `index_hook` is unset.
+    pub fn from_account(account: &Account) -> Result<Option<Self>> {
+        match account.index_hook.trim() {
+            "" => Ok(None),
+            "maildir" => {
+                if account.index_path.trim().is_empty() {
+                    bail!("index_hook = \"maildir\" requires index_path");
+                }
+                Ok(Some(IndexHook::Maildir(resolve::expand_tilde(account.index_path.trim()))))
+            }
+            "notmuch" => Ok(Some(IndexHook::Notmuch(
+                account.index_path.trim().trim_matches('/').to_string(),
+            ))),
+            other => bail!(
+                "Unknown index_hook '{}' (expected \"maildir\" or \"notmuch\")",
+                other
+            ),
+        }
+    }
+
+    /// Deliver one raw RFC822 message.
+    ///
+    /// Idempotent: Maildir files are named by account/UIDVALIDITY/UID and
+    /// skipped if present; notmuch is skipped when the Message-ID is indexed.
+    pub fn deliver(
+        &self,
+        account_name: &str,
+        label: &str,
+        uidvalidity: u32,
+        uid: u32,
+        message_id: &str,
+        raw: &[u8],
+    ) -> Result<()> {
+        match self {
+            IndexHook::Maildir(root) => {
+                let dir = root.join(slugify(label));
+                for sub in ["tmp", "new", "cur"] {
+                    std::fs::create_dir_all(dir.join(sub))?;
+                }
+                let name = format!("{}.{}_{}.corky", uidvalidity, slugify(account_name), uid);
+                if dir.join("new").join(&name).exists() || already_in_cur(&dir, &name)? {
+                    return Ok(());
+                }
+                let tmp_path = dir.join("tmp").join(&name);
+                std::fs::write(&tmp_path, raw)?;
+                std::fs::rename(&tmp_path, dir.join("new").join(&name))?;
+                Ok(())
+            }
+            IndexHook::Notmuch(folder) => {
+                let id = message_id.trim().trim_start_matches('<').trim_end_matches('>');
+                if !id.is_empty() {
+                    let output = Command::new("notmuch")
+                        .args(["count", &format!("id:{}", id)])
+                        .output()?;
+                    let count = String::from_utf8_lossy(&output.stdout).trim().parse::<u64>();
+                    if output.status.success() && count.map(|c| c > 0).unwrap_or(false) {
+                        return Ok(());
+                    }
+                }
+                let target = if folder.is_empty() {
+                    slugify(label)
+                } else {
+                    format!("{}/{}", folder, slugify(label))
+                };
+                let mut child = Command::new("notmuch")
+                    .args(["insert", "--create-folder", &format!("--folder={}", target)])
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::null())
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+                if let Some(mut stdin) = child.stdin.take() {
+                    stdin.write_all(raw)?;
+                }
+                let output = child.wait_with_output()?;
+                if !output.status.success() {
+                    bail!(
+                        "notmuch insert failed: {}",
+                        String::from_utf8_lossy(&output.stderr).trim()
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Maildir readers move files to cur/ with a `:2,FLAGS` suffix.
+fn already_in_cur(dir: &std::path::Path, name: &str) -> Result<bool> {
+    let prefix = format!("{}:", name);
+    for entry in std::fs::read_dir(dir.join("cur"))? {
+        let file_name = entry?.file_name();
+        let file_name = file_name.to_string_lossy();
+        if file_name == name || file_name.starts_with(&prefix) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_account() {
+        let mut acct = Account::default();
+        assert_eq!(IndexHook::from_account(&acct).unwrap(), None);
+
+        acct.index_hook = "maildir".to_string();
+        assert!(IndexHook::from_account(&acct).is_err());
+        acct.index_path = "/tmp/mirror".to_string();
+        assert_eq!(
+            IndexHook::from_account(&acct).unwrap(),
+            Some(IndexHook::Maildir(PathBuf::from("/tmp/mirror")))
+        );
+
+        acct.index_hook = "mu".to_string();
+        assert!(IndexHook::from_account(&acct).is_err());
+    }
+
+    #[test]
+    fn test_maildir_deliver_is_idempotent() {
+        let tmp = tempfile::TempDir::new().unwrap();
+        let hook = IndexHook::Maildir(tmp.path().to_path_buf());
+        let raw = b"Subject: Hi\r\n\r\nBody\r\n";
+        hook.deliver("personal", "INBOX", 7, 42, "<a@b>", raw).unwrap();
+
+        let new_dir = tmp.path().join("inbox").join("new");
+        let name = "7.personal_42.corky";
+        assert_eq!(std::fs::read(new_dir.join(name)).unwrap(), raw);
+
+        // A reader moved it to cur/ with flags; redelivery must not duplicate.
+        std::fs::rename(
+            new_dir.join(name),
+            tmp.path().join("inbox").join("cur").join(format!("{}:2,S", name)),
+        )
+        .unwrap();
+        hook.deliver("personal", "INBOX", 7, 42, "<a@b>", raw).unwrap();
+        assert_eq!(std::fs::read_dir(&new_dir).unwrap().count(), 0);
+    }
+}
@@ -0,0 +1,110 @@
+//! Issue and ticket references found in message bodies.
+//!
+//! Two kinds are detected: issue-tracker URLs (GitHub/GitLab issues and
+//! pull/merge requests, Jira `browse/KEY-1`, `/ticket(s)/N`) and bracketed
+//! references like `[#123]`. They're shown as `**Links**` in the thread
+//! header and as `links` in manifest.toml.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+use super::types::Message;
+
+static URL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r#"https?://[^\s<>"'()\[\]]+"#).unwrap());
+static ISSUE_PATH_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"(?i)/(?:issues|pull|pulls|merge_requests|browse|tickets?)/[A-Za-z0-9_-]*\d[A-Za-z0-9_-]*$",
+    )
+    .unwrap()
+});
+static REF_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[#(\d+)\]").unwrap());
+
+/// The issue URL in `raw`, without query, fragment, or trailing punctuation.
+fn issue_url(raw: &str) -> Option<String> {
+    let url = raw.split(['#', '?']).next().unwrap_or(raw);
+    let url = url.trim_end_matches(['.', ',', ';', ':', '!', '/']);
+    ISSUE_PATH_RE.is_match(url).then(|| url.to_string())
+}
+
+/// Issue links in `messages`' bodies, in order of first appearance.
+pub fn extract_links(messages: &[Message]) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    for msg in messages {
+        let urls = URL_RE.find_iter(&msg.body).filter_map(|m| issue_url(m.as_str()));
+        let refs = REF_RE.captures_iter(&msg.body).map(|c| format!("#{}", &c[1]));
+        for link in urls.chain(refs) {
+            if !links.contains(&link) {
+                links.push(link);
+            }
+        }
+    }
+    links
+}
+
+/// Whether `link` matches a `--link` filter: a case-insensitive substring
+/// of the link without its scheme (`github.com/org/repo`, `#123`) that ends
+/// at a `/`, `?`, `#`, or the end of the link, so `#12` doesn't match `#123`.
+pub fn link_matches(link: &str, filter: &str) -> bool {
+    let bare = link
+        .strip_prefix("https://")
+        .or_else(|| link.strip_prefix("http://"))
+        .unwrap_or(link);
+    let filter = filter
+        .strip_prefix("https://")
+        .or_else(|| filter.strip_prefix("http://"))
+        .unwrap_or(filter);
+    let (bare, filter) = (bare.to_lowercase(), filter.to_lowercase());
+    if filter.is_empty() || filter.ends_with(['/', '?', '#']) {
+        return bare.contains(&filter);
+    }
+    bare.match_indices(&filter).any(|(i, _)| {
+        bare[i + filter.len()..]
+            .chars()
+            .next()
+            .is_none_or(|c| matches!(c, '/' | '?' | '#'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::types::fixtures::message;
+
+    fn msg(body: &str) -> Message {
+        message("Alice <alice@example.com>", "", body)
+    }
+
+    #[test]
+    fn test_extract_links() {
+        let messages = vec![
+            msg("See https://github.com/org/repo/issues/42#issuecomment-1, and [#7].\n\
+                 Docs: https://github.com/org/repo/blob/main/README.md"),
+            msg("> See https://github.com/org/repo/issues/42\n\
+                 Fixed in (https://gitlab.com/g/p/-/merge_requests/3).\n\
+                 Tracked as https://acme.atlassian.net/browse/OPS-12?focus=1"),
+        ];
+        assert_eq!(
+            extract_links(&messages),
+            vec![
+                "https://github.com/org/repo/issues/42",
+                "#7",
+                "https://gitlab.com/g/p/-/merge_requests/3",
+                "https://acme.atlassian.net/browse/OPS-12",
+            ]
+        );
+        assert!(extract_links(&[msg("Nothing to see at https://example.com/about")]).is_empty());
+    }
+
+    #[test]
+    fn test_link_matches() {
+        let link = "https://github.com/Org/Repo/pull/5";
+        assert!(link_matches(link, "github.com/org/repo"));
+        assert!(link_matches(link, "https://github.com/org"));
+        assert!(!link_matches(link, "github.com/org/other"));
+        assert!(link_matches("#7", "#7"));
+        assert!(!link_matches("#70", "#7"));
+        assert!(!link_matches(link, "github.com/org/rep"));
+        assert!(link_matches(link, "github.com/org/repo/"));
+        assert!(link_matches("https://acme.atlassian.net/browse/OPS-12?focus=1", "OPS-12"));
+    }
+}
@@ -3,11 +3,16 @@
 use anyhow::Result;
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 
-use super::markdown::parse_thread_markdown;
-use crate::config::contact;
+use super::links::extract_links;
+use super::store::{ConversationStore, MarkdownStore};
+use super::types::Thread;
+use crate::address;
+use crate::config::contact::{self, ContactIndex};
+use crate::thread::pin::is_pinned;
+use crate::util::split_address_list;
 
 static EMAIL_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([^>]+)>").unwrap());
 
@@ -17,54 +22,74 @@ pub fn generate_manifest(conversations_dir: &Path) -> Result<()> {
         return Ok(());
     }
 
-    let contacts = contact::load_contacts(None).unwrap_or_default();
+    let threads = manifest_threads(conversations_dir)?;
 
-    // Build email→contact-name lookup
-    let mut email_to_contact: BTreeMap<String, String> = BTreeMap::new();
-    for (cname, c) in &contacts {
-        for addr in &c.emails {
-            email_to_contact.insert(addr.to_lowercase(), cname.clone());
-        }
+    let manifest_path = conversations_dir
+        .parent()
+        .unwrap_or(conversations_dir)
+        .join("manifest.toml");
+    let mut manifest = toml::map::Map::new();
+    let threads_table: toml::map::Map<String, toml::Value> = threads.into_iter().collect();
+    manifest.insert(
+        "threads".to_string(),
+        toml::Value::Table(threads_table),
+    );
+    let content = toml::to_string_pretty(&toml::Value::Table(manifest))?;
+    std::fs::write(&manifest_path, content)?;
+    crate::progress!("  Generated {}", manifest_path.display());
+    Ok(())
+}
+
+/// Build the `[threads]` entries of manifest.toml, keyed by file slug.
+pub fn manifest_threads(conversations_dir: &Path) -> Result<BTreeMap<String, toml::Value>> {
+    if !conversations_dir.exists() {
+        return Ok(BTreeMap::new());
     }
+    store_manifest_threads(&MarkdownStore::new(conversations_dir))
+}
+
+/// [`manifest_threads`] for any conversation store, keyed by store key.
+pub fn store_manifest_threads(
+    store: &dyn ConversationStore,
+) -> Result<BTreeMap<String, toml::Value>> {
+    let contacts = contact::load_contacts(None).unwrap_or_default();
+    let contact_index = ContactIndex::new(&contacts);
 
     let mut threads: BTreeMap<String, toml::Value> = BTreeMap::new();
 
-    let mut entries: Vec<_> = std::fs::read_dir(conversations_dir)?
-        .filter_map(|e| e.ok())
-        .filter(|e| {
-            e.path()
-                .extension()
-                .and_then(|ext| ext.to_str())
-                .map(|ext| ext == "md")
-                .unwrap_or(false)
-        })
-        .collect();
-    entries.sort_by_key(|e| e.file_name());
-
-    for entry in entries {
-        let path = entry.path();
-        let text = std::fs::read_to_string(&path)?;
-        let thread = match parse_thread_markdown(&text) {
-            Some(t) => t,
-            None => continue,
-        };
+    for (slug, thread) in store.threads()? {
+        let parts = store.parts(&slug);
 
         // Match contacts by email addresses in from, to, and cc
         let mut thread_contacts: Vec<String> = Vec::new();
         for msg in &thread.messages {
             for field in [&msg.from, &msg.to, &msg.cc] {
                 for cap in EMAIL_RE.captures_iter(field) {
-                    let addr = cap[1].to_lowercase();
-                    if let Some(cname) = email_to_contact.get(&addr) {
-                        if !thread_contacts.contains(cname) {
-                            thread_contacts.push(cname.clone());
+                    if let Some(cname) = contact_index.lookup(&cap[1]) {
+                        if !thread_contacts.iter().any(|c| c == cname) {
+                            thread_contacts.push(cname.to_string());
                         }
                     }
                 }
             }
         }
 
-        let slug = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        // Unique To/CC recipients across messages (for reply-all)
+        let mut to_addrs: Vec<String> = Vec::new();
+        let mut cc_addrs: Vec<String> = Vec::new();
+        let mut seen_recipients: HashSet<String> = HashSet::new();
+        for msg in &thread.messages {
+            for (field, out) in [(&msg.to, &mut to_addrs), (&msg.cc, &mut cc_addrs)] {
+                for addr in split_address_list(field) {
+                    if seen_recipients.insert(address::normalize(&addr)) {
+                        out.push(addr);
+                    }
+                }
+            }
+        }
+
+        let unsubscribe = latest_list_unsubscribe(&thread);
+
         let mut entry_map = toml::map::Map::new();
         entry_map.insert(
             "subject".to_string(),
@@ -94,6 +119,36 @@ pub fn generate_manifest(conversations_dir: &Path) -> Result<()> {
                     .collect(),
             ),
         );
+        entry_map.insert(
+            "to".to_string(),
+            toml::Value::Array(to_addrs.into_iter().map(toml::Value::String).collect()),
+        );
+        entry_map.insert(
+            "cc".to_string(),
+            toml::Value::Array(cc_addrs.into_iter().map(toml::Value::String).collect()),
+        );
+        if is_pinned(&thread) {
+            entry_map.insert("pinned".to_string(), toml::Value::Boolean(true));
+        }
+        if !thread.tags.is_empty() {
+            entry_map.insert(
+                "tags".to_string(),
+                toml::Value::Array(thread.tags.iter().cloned().map(toml::Value::String).collect()),
+            );
+        }
+        let links = extract_links(&thread.messages);
+        if !links.is_empty() {
+            entry_map.insert(
+                "links".to_string(),
+                toml::Value::Array(links.into_iter().map(toml::Value::String).collect()),
+            );
+        }
+        if let Some(unsub) = unsubscribe {
+            entry_map.insert("unsubscribe".to_string(), toml::Value::String(unsub));
+        }
+        if parts > 1 {
+            entry_map.insert("parts".to_string(), toml::Value::Integer(parts as i64));
+        }
         entry_map.insert(
             "last_updated".to_string(),
             toml::Value::String(thread.last_date),
@@ -111,18 +166,15 @@ pub fn generate_manifest(conversations_dir: &Path) -> Result<()> {
         threads.insert(slug, toml::Value::Table(entry_map));
     }
 
-    let manifest_path = conversations_dir
-        .parent()
-        .unwrap_or(conversations_dir)
-        .join("manifest.toml");
-    let mut manifest = toml::map::Map::new();
-    let threads_table: toml::map::Map<String, toml::Value> = threads.into_iter().collect();
-    manifest.insert(
-        "threads".to_string(),
-        toml::Value::Table(threads_table),
-    );
-    let content = toml::to_string_pretty(&toml::Value::Table(manifest))?;
-    std::fs::write(&manifest_path, content)?;
-    println!("  Generated {}", manifest_path.display());
-    Ok(())
+    Ok(threads)
+}
+
+/// The most recent non-empty List-Unsubscribe header in a thread.
+pub fn latest_list_unsubscribe(thread: &Thread) -> Option<String> {
+    thread
+        .messages
+        .iter()
+        .rev()
+        .map(|m| m.list_unsubscribe.clone())
+        .find(|u| !u.is_empty())
 }
@@ -1,17 +1,99 @@
 //! Thread ↔ Markdown serialization/parsing.
 
+use anyhow::Result;
+use chrono::{DateTime, FixedOffset, Local, SecondsFormat, Utc};
 use once_cell::sync::Lazy;
 use regex::Regex;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 
-use super::types::{Message, Thread};
-use crate::util::thread_key_from_subject;
+use super::links::extract_links;
+use super::types::{Message, MessageSource, Thread};
+use crate::config::corky_config::SyncConfig;
+use crate::util::{split_address_list, thread_key_from_subject};
 
 static META_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\*\*(.+?)\*\*:\s*(.+)$").unwrap());
 static MSG_HEADER_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^## (.+?) \u{2014} (.+)$").unwrap());
+/// Hidden per-message metadata: `<!-- source: account=A uid=N label=L -->`.
+/// The label comes last since it may contain spaces.
+static SOURCE_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^<!-- source: account=(\S+) uid=(\d+) label=(.+?) -->$").unwrap()
+});
+static PART_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\.part\d+\.md$").unwrap());
 
-/// Serialize a Thread to Markdown.
+/// Set once an unusable `[sync] timezone` has been warned about.
+static TIMEZONE_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Format a raw message date for display per `[sync] date_format` / `timezone`.
+///
+/// Returns the raw string unchanged when no normalization is configured or
+/// the date can't be parsed.
+pub fn format_display_date(raw: &str, sync: &SyncConfig) -> String {
+    let iso = matches!(sync.date_format.to_lowercase().as_str(), "iso8601" | "iso");
+    let tz = sync.timezone.trim();
+    if !iso && tz.is_empty() {
+        return raw.to_string();
+    }
+    let dt: DateTime<FixedOffset> = match DateTime::parse_from_rfc2822(raw)
+        .or_else(|_| DateTime::parse_from_rfc3339(raw))
+    {
+        Ok(dt) => dt,
+        Err(_) => match mailparse::dateparse(raw)
+            .ok()
+            .and_then(|ts| DateTime::from_timestamp(ts, 0))
+        {
+            Some(dt) => dt.fixed_offset(),
+            None => return raw.to_string(),
+        },
+    };
+    let dt = match tz.to_lowercase().as_str() {
+        "" => dt,
+        "local" => dt.with_timezone(&Local).fixed_offset(),
+        "utc" | "z" => dt.with_timezone(&Utc).fixed_offset(),
+        other => match other.parse::<FixedOffset>() {
+            Ok(offset) => dt.with_timezone(&offset),
+            Err(_) => {
+                if !TIMEZONE_WARNED.swap(true, Ordering::Relaxed) {
+                    let msg = format!(
+                        "Ignoring [sync] timezone \"{}\": use \"local\", \"utc\", or \"+HH:MM\"",
+                        tz
+                    );
+                    eprintln!("{}", crate::output::warning(&msg));
+                }
+                dt
+            }
+        },
+    };
+    if iso {
+        dt.to_rfc3339_opts(SecondsFormat::Secs, false)
+    } else {
+        dt.to_rfc2822()
+    }
+}
+
+/// Serialize a Thread to Markdown, dates as stored.
 pub fn thread_to_markdown(thread: &Thread) -> String {
+    thread_to_markdown_with(thread, &SyncConfig::default())
+}
+
+/// Serialize a Thread to Markdown, displaying dates per `[sync]` config.
+///
+/// When a message's displayed date differs from the original, the original
+/// is kept in a per-message `**Date**` line so parsing round-trips.
+pub fn thread_to_markdown_with(thread: &Thread, sync: &SyncConfig) -> String {
+    render(thread, &thread.messages, sync, &[], true)
+}
+
+/// Header lines, then `messages`. `continuation` lines go after
+/// `**Last updated**`; notes are only written when `with_notes`.
+fn render(
+    thread: &Thread,
+    messages: &[Message],
+    sync: &SyncConfig,
+    continuation: &[String],
+    with_notes: bool,
+) -> String {
     let labels_str = thread.labels.join(", ");
     let accounts_str = thread.accounts.join(", ");
     let mut lines = vec![
@@ -19,28 +101,204 @@ pub fn thread_to_markdown(thread: &Thread) -> String {
         String::new(),
         format!("**Labels**: {}", labels_str),
         format!("**Accounts**: {}", accounts_str),
-        format!("**Thread ID**: {}", thread.id),
-        format!("**Last updated**: {}", thread.last_date),
-        String::new(),
     ];
-    for msg in &thread.messages {
-        lines.push("---".to_string());
-        lines.push(String::new());
-        lines.push(format!("## {} \u{2014} {}", msg.from, msg.date));
+    if !thread.participants.is_empty() {
+        lines.push(format!("**Participants**: {}", thread.participants.join(", ")));
+    }
+    if !thread.tags.is_empty() {
+        lines.push(format!("**Tags**: {}", thread.tags.join(", ")));
+    }
+    // Derived from the whole thread, so every part lists the same links
+    let links = extract_links(&thread.messages);
+    if !links.is_empty() {
+        lines.push(format!("**Links**: {}", links.join(", ")));
+    }
+    lines.push(format!("**Thread ID**: {}", thread.id));
+    lines.push(format!(
+        "**Last updated**: {}",
+        format_display_date(&thread.last_date, sync)
+    ));
+    lines.extend(continuation.iter().cloned());
+    if with_notes && !thread.notes.is_empty() {
+        lines.push("**Notes**:".to_string());
+        for note in &thread.notes {
+            lines.push(format!("- {}", note));
+        }
+    }
+    lines.push(String::new());
+    for msg in messages {
+        lines.extend(message_lines(msg, sync));
+    }
+    lines.join("\n")
+}
+
+/// One message section, from its `---` separator to the blank line after the body.
+fn message_lines(msg: &Message, sync: &SyncConfig) -> Vec<String> {
+    let mut lines = vec!["---".to_string(), String::new()];
+    let shown_date = format_display_date(&msg.date, sync);
+    lines.push(format!("## {} \u{2014} {}", msg.from, shown_date));
+    lines.push(String::new());
+    let mut msg_meta = Vec::new();
+    if shown_date != msg.date {
+        msg_meta.push(format!("**Date**: {}", msg.date));
+    }
+    if !msg.to.is_empty() {
+        msg_meta.push(format!("**To**: {}", msg.to));
+    }
+    if !msg.cc.is_empty() {
+        msg_meta.push(format!("**CC**: {}", msg.cc));
+    }
+    if !msg.message_id.is_empty() {
+        msg_meta.push(format!("**Message-ID**: {}", msg.message_id));
+    }
+    if !msg.received_by.is_empty() {
+        msg_meta.push(format!("**Received by**: {}", msg.received_by.join(", ")));
+    }
+    if !msg.list_unsubscribe.is_empty() {
+        msg_meta.push(format!("**List-Unsubscribe**: {}", msg.list_unsubscribe));
+    }
+    for src in &msg.sources {
+        msg_meta.push(format!(
+            "<!-- source: account={} uid={} label={} -->",
+            src.account, src.uid, src.label
+        ));
+    }
+    if !msg_meta.is_empty() {
+        lines.extend(msg_meta);
         lines.push(String::new());
-        if !msg.to.is_empty() {
-            lines.push(format!("**To**: {}", msg.to));
+    }
+    lines.push(msg.body.trim().to_string());
+    lines.push(String::new());
+    lines
+}
+
+/// Whether `path` is a continuation part of a split conversation
+/// (`slug.part2.md`, `slug.part3.md`, ...).
+pub fn is_part_file(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| PART_RE.is_match(n))
+}
+
+/// Path of part `n` of the conversation whose first part is `main`
+/// (`n = 1` is `main` itself).
+pub fn part_path(main: &Path, n: usize) -> PathBuf {
+    if n <= 1 {
+        return main.to_path_buf();
+    }
+    let stem = main.file_stem().unwrap_or_default().to_string_lossy();
+    main.with_file_name(format!("{}.part{}.md", stem, n))
+}
+
+/// Every file of the conversation at `main`: `main`, then its existing
+/// continuation parts in order.
+pub fn thread_files(main: &Path) -> Vec<PathBuf> {
+    let mut files = vec![main.to_path_buf()];
+    let mut n = 2;
+    loop {
+        let part = part_path(main, n);
+        if !part.exists() {
+            return files;
+        }
+        files.push(part);
+        n += 1;
+    }
+}
+
+/// Header lines linking one file of a split conversation to the others.
+const PART_LINKS: &[&str] = &["**Part**:", "**Previous part**:", "**Next part**:"];
+
+/// The `**Part**`, `**Previous part**` and `**Next part**` lines in the
+/// header of one file of a split conversation.
+pub fn part_links(text: &str) -> Vec<String> {
+    text.lines()
+        .take_while(|line| !MSG_HEADER_RE.is_match(line))
+        .filter(|line| PART_LINKS.iter().any(|p| line.starts_with(p)))
+        .map(str::to_string)
+        .collect()
+}
+
+/// Serialize `thread` as a single file of a split conversation, keeping
+/// `links` (from [`part_links`]) in its header.
+pub fn part_to_markdown(thread: &Thread, sync: &SyncConfig, links: &[String]) -> String {
+    render(thread, &thread.messages, sync, links, true)
+}
+
+/// Group messages into parts per `[sync] split_messages` / `split_bytes`
+/// (0 = no limit). A part always holds at least one message.
+fn split_parts<'a>(messages: &'a [Message], sync: &SyncConfig) -> Vec<&'a [Message]> {
+    if sync.split_messages == 0 && sync.split_bytes == 0 {
+        return vec![messages];
+    }
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut bytes = 0;
+    for (i, msg) in messages.iter().enumerate() {
+        let size = message_lines(msg, sync).join("\n").len() + 1;
+        let full_count = sync.split_messages > 0 && i - start >= sync.split_messages;
+        let full_bytes = sync.split_bytes > 0 && i > start && bytes + size > sync.split_bytes;
+        if full_count || full_bytes {
+            parts.push(&messages[start..i]);
+            start = i;
+            bytes = 0;
         }
-        if !msg.cc.is_empty() {
-            lines.push(format!("**CC**: {}", msg.cc));
+        bytes += size;
+    }
+    parts.push(&messages[start..]);
+    parts
+}
+
+/// Write a conversation to `main`, split into `slug.partN.md` files when
+/// `[sync] split_messages` or `split_bytes` is set. Each part repeats the
+/// thread header and links its neighbours with `**Part**`, `**Previous
+/// part**` and `**Next part**` lines; parts left over from a longer split
+/// are removed. Returns the files written, in order.
+pub fn write_thread(main: &Path, thread: &Thread, sync: &SyncConfig) -> Result<Vec<PathBuf>> {
+    let parts = split_parts(&thread.messages, sync);
+    let total = parts.len();
+    let name = |n: usize| {
+        part_path(main, n)
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string()
+    };
+    let mut written = Vec::new();
+    for (i, messages) in parts.into_iter().enumerate() {
+        let n = i + 1;
+        let mut continuation = Vec::new();
+        if total > 1 {
+            continuation.push(format!("**Part**: {} of {}", n, total));
+            if n > 1 {
+                continuation.push(format!("**Previous part**: {}", name(n - 1)));
+            }
+            if n < total {
+                continuation.push(format!("**Next part**: {}", name(n + 1)));
+            }
         }
-        if !msg.to.is_empty() || !msg.cc.is_empty() {
-            lines.push(String::new());
+        let path = part_path(main, n);
+        std::fs::write(&path, render(thread, messages, sync, &continuation, n == 1))?;
+        written.push(path);
+    }
+    for stale in thread_files(main).into_iter().skip(total) {
+        std::fs::remove_file(stale)?;
+    }
+    Ok(written)
+}
+
+/// Read a conversation written by [`write_thread`]: the header from `main`,
+/// the messages from `main` and every continuation part in order. Returns
+/// None when `main` doesn't parse.
+pub fn read_thread(main: &Path) -> Result<Option<Thread>> {
+    let Some(mut thread) = parse_thread_markdown(&std::fs::read_to_string(main)?) else {
+        return Ok(None);
+    };
+    for part in thread_files(main).into_iter().skip(1) {
+        if let Some(t) = parse_thread_markdown(&std::fs::read_to_string(&part)?) {
+            thread.messages.extend(t.messages);
         }
-        lines.push(msg.body.trim().to_string());
-        lines.push(String::new());
     }
-    lines.join("\n")
+    Ok(Some(thread))
 }
 
 /// Parse a conversation markdown file back into a Thread.
@@ -57,9 +315,15 @@ pub fn parse_thread_markdown(text: &str) -> Option<Thread> {
         return None;
     }
 
-    // Extract metadata
+    // Extract thread metadata (only the header, before the first message,
+    // so per-message metadata can't shadow it)
+    let header_end = lines
+        .iter()
+        .position(|line| MSG_HEADER_RE.is_match(line))
+        .unwrap_or(lines.len());
+    let header = lines[..header_end].join("\n");
     let mut meta = std::collections::HashMap::new();
-    for cap in META_RE.captures_iter(text) {
+    for cap in META_RE.captures_iter(&header) {
         meta.insert(
             cap[1].to_string(),
             cap[2].trim().to_string(),
@@ -84,12 +348,36 @@ pub fn parse_thread_markdown(text: &str) -> Option<Thread> {
         })
         .unwrap_or_default();
 
+    let participants = meta
+        .get("Participants")
+        .map(|s| split_address_list(s))
+        .unwrap_or_default();
+
+    let tags = meta
+        .get("Tags")
+        .map(|s| s.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+        .unwrap_or_default();
+
+    // Notes: "- " bullets under a bare "**Notes**:" line in the header
+    let notes = lines[..header_end]
+        .iter()
+        .skip_while(|line| line.trim() != "**Notes**:")
+        .skip(1)
+        .map_while(|line| line.strip_prefix("- "))
+        .map(|s| s.trim().to_string())
+        .collect();
+
     // Split into message sections on "## Sender — Date"
     let mut messages: Vec<Message> = Vec::new();
     let mut current_from = String::new();
     let mut current_date = String::new();
     let mut current_to = String::new();
     let mut current_cc = String::new();
+    let mut current_original_date = String::new();
+    let mut current_message_id = String::new();
+    let mut current_received_by: Vec<String> = Vec::new();
+    let mut current_sources: Vec<MessageSource> = Vec::new();
+    let mut current_list_unsubscribe = String::new();
     let mut body_lines: Vec<&str> = Vec::new();
     let mut in_message = false;
     let mut in_msg_meta = false; // after header, before body
@@ -99,20 +387,33 @@ pub fn parse_thread_markdown(text: &str) -> Option<Thread> {
             // Save previous message
             if in_message {
                 messages.push(Message {
-                    id: String::new(),
                     thread_id: thread_key_from_subject(&subject),
                     from: current_from.clone(),
                     to: current_to.clone(),
                     cc: current_cc.clone(),
-                    date: current_date.clone(),
+                    message_id: current_message_id.clone(),
+                    received_by: current_received_by.clone(),
+                    sources: current_sources.clone(),
+                    list_unsubscribe: current_list_unsubscribe.clone(),
+                    date: if current_original_date.is_empty() {
+                        current_date.clone()
+                    } else {
+                        current_original_date.clone()
+                    },
                     subject: subject.clone(),
                     body: body_lines.join("\n").trim().to_string(),
+                    ..Default::default()
                 });
             }
             current_from = cap[1].to_string();
             current_date = cap[2].to_string();
             current_to = String::new();
             current_cc = String::new();
+            current_original_date = String::new();
+            current_message_id = String::new();
+            current_received_by = Vec::new();
+            current_sources = Vec::new();
+            current_list_unsubscribe = String::new();
             body_lines.clear();
             in_message = true;
             in_msg_meta = true;
@@ -122,8 +423,26 @@ pub fn parse_thread_markdown(text: &str) -> Option<Thread> {
                     match cap[1].to_string().as_str() {
                         "To" => current_to = cap[2].trim().to_string(),
                         "CC" => current_cc = cap[2].trim().to_string(),
+                        "Date" => current_original_date = cap[2].trim().to_string(),
+                        "Message-ID" => current_message_id = cap[2].trim().to_string(),
+                        "List-Unsubscribe" => {
+                            current_list_unsubscribe = cap[2].trim().to_string()
+                        }
+                        "Received by" => {
+                            current_received_by = cap[2]
+                                .split(',')
+                                .map(|s| s.trim().to_string())
+                                .filter(|s| !s.is_empty())
+                                .collect()
+                        }
                         _ => {} // ignore other per-message metadata
                     }
+                } else if let Some(cap) = SOURCE_RE.captures(line.trim()) {
+                    current_sources.push(MessageSource {
+                        account: cap[1].to_string(),
+                        label: cap[3].to_string(),
+                        uid: cap[2].parse().unwrap_or(0),
+                    });
                 } else if line.trim().is_empty() {
                     // blank lines between metadata and body are consumed
                 } else {
@@ -142,14 +461,22 @@ pub fn parse_thread_markdown(text: &str) -> Option<Thread> {
     // Save last message
     if in_message {
         messages.push(Message {
-            id: String::new(),
             thread_id: thread_key_from_subject(&subject),
             from: current_from,
             to: current_to,
             cc: current_cc,
-            date: current_date,
+            message_id: current_message_id,
+            received_by: current_received_by,
+            sources: current_sources,
+            list_unsubscribe: current_list_unsubscribe,
+            date: if current_original_date.is_empty() {
+                current_date
+            } else {
+                current_original_date
+            },
             subject: subject.clone(),
             body: body_lines.join("\n").trim().to_string(),
+            ..Default::default()
         });
     }
 
@@ -158,6 +485,9 @@ pub fn parse_thread_markdown(text: &str) -> Option<Thread> {
         subject,
         labels,
         accounts,
+        participants,
+        tags,
+        notes,
         messages,
         last_date,
     })
@@ -166,6 +496,7 @@ pub fn parse_thread_markdown(text: &str) -> Option<Thread> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::sync::types::fixtures::{message, thread};
 
     #[test]
     fn test_roundtrip() {
@@ -174,15 +505,17 @@ mod tests {
             subject: "Hello World".to_string(),
             labels: vec!["inbox".to_string()],
             accounts: vec!["personal".to_string()],
+            participants: vec![],
+            tags: vec![],
+            notes: vec![],
             messages: vec![Message {
                 id: "1".to_string(),
                 thread_id: "hello world".to_string(),
                 from: "Alice <alice@example.com>".to_string(),
-                to: String::new(),
-                cc: String::new(),
                 date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
                 subject: "Hello World".to_string(),
                 body: "Hello there!".to_string(),
+                ..Default::default()
             }],
             last_date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         };
@@ -206,6 +539,9 @@ mod tests {
             subject: "Hello World".to_string(),
             labels: vec!["inbox".to_string()],
             accounts: vec!["personal".to_string()],
+            participants: vec![],
+            tags: vec![],
+            notes: vec![],
             messages: vec![Message {
                 id: "1".to_string(),
                 thread_id: "hello world".to_string(),
@@ -215,6 +551,7 @@ mod tests {
                 date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
                 subject: "Hello World".to_string(),
                 body: "Hello there!".to_string(),
+                ..Default::default()
             }],
             last_date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         };
@@ -230,6 +567,42 @@ mod tests {
         assert_eq!(parsed.messages[0].body, "Hello there!");
     }
 
+    #[test]
+    fn test_sources_roundtrip() {
+        let source = |account: &str, label: &str, uid: u32| MessageSource {
+            account: account.to_string(),
+            label: label.to_string(),
+            uid,
+        };
+        let thread = Thread {
+            id: "test-thread".to_string(),
+            subject: "Hello World".to_string(),
+            messages: vec![Message {
+                id: "1".to_string(),
+                thread_id: "hello world".to_string(),
+                from: "Alice <alice@example.com>".to_string(),
+                message_id: "<a1@example.com>".to_string(),
+                received_by: vec!["personal".to_string()],
+                sources: vec![
+                    source("personal", "INBOX", 42),
+                    source("personal", "[Gmail]/All Mail", 977),
+                ],
+                date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+                subject: "Hello World".to_string(),
+                body: "Hello there!".to_string(),
+                ..Default::default()
+            }],
+            last_date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+            ..Default::default()
+        };
+
+        let md = thread_to_markdown(&thread);
+        assert!(md.contains("<!-- source: account=personal uid=977 label=[Gmail]/All Mail -->"));
+        let parsed = parse_thread_markdown(&md).unwrap();
+        assert_eq!(parsed.messages[0].sources, thread.messages[0].sources);
+        assert_eq!(parsed.messages[0].body, "Hello there!");
+    }
+
     #[test]
     fn test_parse_old_format_no_to_cc() {
         // Old format without To/CC lines should parse with empty to/cc
@@ -247,4 +620,150 @@ mod tests {
         let parsed = parse_thread_markdown(md).unwrap();
         assert_eq!(parsed.labels, vec!["label1", "label2"]);
     }
+
+    #[test]
+    fn test_display_date_iso_utc_roundtrip() {
+        let sync = SyncConfig {
+            date_format: "iso8601".to_string(),
+            timezone: "utc".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            format_display_date("Mon, 10 Feb 2025 12:00:00 +0200", &sync),
+            "2025-02-10T10:00:00+00:00"
+        );
+        assert_eq!(format_display_date("not a date", &sync), "not a date");
+
+        let thread = Thread {
+            id: "t".to_string(),
+            subject: "Dates".to_string(),
+            messages: vec![Message {
+                id: "1".to_string(),
+                thread_id: "dates".to_string(),
+                from: "Alice <alice@example.com>".to_string(),
+                date: "Mon, 10 Feb 2025 12:00:00 +0200".to_string(),
+                subject: "Dates".to_string(),
+                body: "Hi".to_string(),
+                ..Default::default()
+            }],
+            last_date: "Mon, 10 Feb 2025 12:00:00 +0200".to_string(),
+            ..Default::default()
+        };
+        let md = thread_to_markdown_with(&thread, &sync);
+        assert!(md.contains("## Alice <alice@example.com> \u{2014} 2025-02-10T10:00:00+00:00"));
+        assert!(md.contains("**Date**: Mon, 10 Feb 2025 12:00:00 +0200"));
+        assert!(md.contains("**Last updated**: 2025-02-10T10:00:00+00:00"));
+
+        let parsed = parse_thread_markdown(&md).unwrap();
+        assert_eq!(parsed.messages[0].date, "Mon, 10 Feb 2025 12:00:00 +0200");
+        assert_eq!(parsed.messages[0].body, "Hi");
+    }
+
+    #[test]
+    fn test_display_date_unconfigured_keeps_raw() {
+        let raw = "Mon, 10 Feb 2025 12:00:00 +0200 (CEST)";
+        assert_eq!(format_display_date(raw, &SyncConfig::default()), raw);
+    }
+
+    #[test]
+    fn test_notes_roundtrip() {
+        let thread = Thread {
+            id: "notes".to_string(),
+            subject: "Notes".to_string(),
+            notes: vec!["2026-01-02: Unsubscribed via mailto:x@example.com".to_string()],
+            messages: vec![Message {
+                id: "1".to_string(),
+                thread_id: "notes".to_string(),
+                from: "Alice <alice@example.com>".to_string(),
+                date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+                subject: "Notes".to_string(),
+                body: "- not a note".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let md = thread_to_markdown(&thread);
+        assert!(md.contains("**Notes**:\n- 2026-01-02: Unsubscribed via mailto:x@example.com\n"));
+        let parsed = parse_thread_markdown(&md).unwrap();
+        assert_eq!(parsed.notes, thread.notes);
+        assert_eq!(parsed.messages[0].body, "- not a note");
+    }
+
+    fn numbered_thread(count: usize) -> Thread {
+        let messages = (1..=count)
+            .map(|i| Message {
+                message_id: format!("<{}@example.com>", i),
+                ..message(
+                    "Alice <alice@example.com>",
+                    &format!("Mon, 10 Feb 2025 10:{:02}:00 +0000", i),
+                    &format!("Message {}", i),
+                )
+            })
+            .collect();
+        Thread {
+            labels: vec!["inbox".to_string()],
+            notes: vec!["2026-01-02: kept once".to_string()],
+            ..thread("long", "Long", messages)
+        }
+    }
+
+    #[test]
+    fn test_split_by_message_count_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("long.md");
+        let sync = SyncConfig {
+            split_messages: 2,
+            ..Default::default()
+        };
+        let thread = numbered_thread(5);
+        let written = write_thread(&main, &thread, &sync).unwrap();
+        let names: Vec<String> = written
+            .iter()
+            .map(|p| p.file_name().unwrap().to_string_lossy().to_string())
+            .collect();
+        assert_eq!(names, vec!["long.md", "long.part2.md", "long.part3.md"]);
+        assert!(is_part_file(&written[1]));
+        assert!(!is_part_file(&main));
+
+        let first = std::fs::read_to_string(&main).unwrap();
+        assert!(first.contains("**Part**: 1 of 3\n**Next part**: long.part2.md\n"));
+        assert!(first.contains("**Notes**:"));
+        let second = std::fs::read_to_string(&written[1]).unwrap();
+        assert!(second.contains("**Previous part**: long.md\n**Next part**: long.part3.md"));
+        assert!(second.contains("**Thread ID**: long"));
+        assert!(!second.contains("**Notes**:"));
+
+        let read = read_thread(&main).unwrap().unwrap();
+        assert_eq!(read.messages.len(), 5);
+        assert_eq!(read.messages[4].body, "Message 5");
+        assert_eq!(read.notes, thread.notes);
+
+        // Fewer messages: stale parts are removed
+        write_thread(&main, &numbered_thread(3), &sync).unwrap();
+        assert_eq!(thread_files(&main).len(), 2);
+        assert!(!dir.path().join("long.part3.md").exists());
+    }
+
+    #[test]
+    fn test_split_by_bytes_and_unsplit() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("long.md");
+        let thread = numbered_thread(4);
+        let one = message_lines(&thread.messages[0], &SyncConfig::default())
+            .join("\n")
+            .len();
+        let sync = SyncConfig {
+            split_bytes: one * 2 + 2,
+            ..Default::default()
+        };
+        assert_eq!(write_thread(&main, &thread, &sync).unwrap().len(), 2);
+        assert_eq!(read_thread(&main).unwrap().unwrap().messages.len(), 4);
+
+        // Splitting turned off: one file, no continuation lines
+        write_thread(&main, &thread, &SyncConfig::default()).unwrap();
+        assert_eq!(thread_files(&main), vec![main.clone()]);
+        let text = std::fs::read_to_string(&main).unwrap();
+        assert!(!text.contains("**Part**"));
+        assert_eq!(text, thread_to_markdown(&thread));
+    }
 }
@@ -0,0 +1,592 @@
+//! SQLite mirror of conversations (`[sync] mirror = "sqlite"`).
+//!
+//! Markdown stays the source of truth. The mirror keeps each conversation's
+//! threads, messages, labels, and participants in `.corky-mirror.sqlite`
+//! under the data dir, stamped with the mtimes, sizes, and count of its
+//! files (every split part). A row whose stamp still matches its file is used as is; anything
+//! else is re-read from Markdown and written back, so a stale or deleted
+//! mirror only costs a re-parse. Sync updates it at merge time.
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use super::imap_sync::parse_msg_date;
+use super::markdown::thread_files;
+use super::store::{ConversationStore, MarkdownStore};
+use super::types::{Message, Thread};
+use crate::config::corky_config::SyncConfig;
+use crate::output;
+use crate::resolve;
+
+/// Mirror database file, in the data dir.
+pub const MIRROR_FILE: &str = ".corky-mirror.sqlite";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS threads (
+    dir TEXT NOT NULL,
+    key TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    last_date TEXT NOT NULL,
+    last_ts INTEGER NOT NULL,
+    labels TEXT NOT NULL,
+    accounts TEXT NOT NULL,
+    participants TEXT NOT NULL,
+    tags TEXT NOT NULL,
+    notes TEXT NOT NULL,
+    mtime INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    parts INTEGER NOT NULL,
+    PRIMARY KEY (dir, key)
+);
+CREATE TABLE IF NOT EXISTS messages (
+    dir TEXT NOT NULL,
+    key TEXT NOT NULL,
+    seq INTEGER NOT NULL,
+    thread_id TEXT NOT NULL,
+    message_id TEXT NOT NULL,
+    from_addr TEXT NOT NULL,
+    to_addr TEXT NOT NULL,
+    cc TEXT NOT NULL,
+    date TEXT NOT NULL,
+    ts INTEGER NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    received_by TEXT NOT NULL,
+    sources TEXT NOT NULL,
+    list_unsubscribe TEXT NOT NULL,
+    PRIMARY KEY (dir, key, seq)
+);
+CREATE TABLE IF NOT EXISTS labels (
+    dir TEXT NOT NULL,
+    key TEXT NOT NULL,
+    label TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS participants (
+    dir TEXT NOT NULL,
+    key TEXT NOT NULL,
+    address TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS messages_from ON messages (from_addr);
+CREATE INDEX IF NOT EXISTS labels_label ON labels (label);
+CREATE INDEX IF NOT EXISTS participants_address ON participants (address);
+";
+
+/// A conversation's (summed mtimes in ns, total size, parts) over every
+/// part file, compared against the mirrored row. Touching any part changes it.
+type Stamp = (i64, i64, i64);
+
+fn file_stamp(path: &Path) -> Option<Stamp> {
+    let files = thread_files(path);
+    let (mut mtime, mut size) = (0i64, 0i64);
+    for file in &files {
+        let meta = std::fs::metadata(file).ok()?;
+        let modified = meta
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .ok()?
+            .as_nanos() as i64;
+        mtime = mtime.wrapping_add(modified);
+        size += meta.len() as i64;
+    }
+    if files.is_empty() {
+        return None;
+    }
+    Some((mtime, size, files.len() as i64))
+}
+
+fn to_json(list: &[String]) -> String {
+    serde_json::to_string(list).unwrap_or_else(|_| "[]".to_string())
+}
+
+fn from_json<T: serde::de::DeserializeOwned + Default>(text: &str) -> T {
+    serde_json::from_str(text).unwrap_or_default()
+}
+
+/// Whether `[sync] mirror = "sqlite"` is set.
+pub fn enabled(sync: &SyncConfig) -> bool {
+    sync.mirror.trim().eq_ignore_ascii_case("sqlite")
+}
+
+/// One conversation matching a [`Mirror::search`].
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub dir: String,
+    pub key: String,
+    pub subject: String,
+    pub last_date: String,
+    /// Messages whose subject, sender, or body matched.
+    pub matches: usize,
+}
+
+/// Counts for `corky stats`.
+#[derive(Debug, Clone, Default)]
+pub struct Stats {
+    pub threads: usize,
+    pub messages: usize,
+    /// Oldest and newest message dates (Unix seconds), if any are dated.
+    pub first_ts: Option<i64>,
+    pub last_ts: Option<i64>,
+    /// (label, threads), most threads first.
+    pub labels: Vec<(String, usize)>,
+    /// (sender, messages), most messages first.
+    pub senders: Vec<(String, usize)>,
+}
+
+pub struct Mirror {
+    conn: Connection,
+}
+
+impl Mirror {
+    pub fn open(path: &Path) -> Result<Self> {
+        let conn = Connection::open(path)?;
+        conn.execute_batch(SCHEMA)?;
+        Ok(Self { conn })
+    }
+
+    /// The mirror in the data dir.
+    pub fn open_default() -> Result<Self> {
+        Self::open(&resolve::data_dir().join(MIRROR_FILE))
+    }
+
+    fn dir_key(dir: &Path) -> String {
+        dir.display().to_string()
+    }
+
+    /// Mirror `thread`, just written to `path`, as `key` in `dir`.
+    pub fn upsert(&self, dir: &Path, key: &str, thread: &Thread, path: &Path) -> Result<()> {
+        let Some((mtime, size, parts)) = file_stamp(path) else {
+            return Ok(());
+        };
+        let d = Self::dir_key(dir);
+        let tx = self.conn.unchecked_transaction()?;
+        Self::delete_rows(&tx, &d, key)?;
+        tx.execute(
+            "INSERT INTO threads VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14)",
+            params![
+                d,
+                key,
+                thread.id,
+                thread.subject,
+                thread.last_date,
+                parse_msg_date(&thread.last_date).timestamp(),
+                to_json(&thread.labels),
+                to_json(&thread.accounts),
+                to_json(&thread.participants),
+                to_json(&thread.tags),
+                to_json(&thread.notes),
+                mtime,
+                size,
+                parts,
+            ],
+        )?;
+        for (seq, msg) in thread.messages.iter().enumerate() {
+            tx.execute(
+                "INSERT INTO messages VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15)",
+                params![
+                    d,
+                    key,
+                    seq as i64,
+                    msg.thread_id,
+                    msg.message_id,
+                    msg.from,
+                    msg.to,
+                    msg.cc,
+                    msg.date,
+                    parse_msg_date(&msg.date).timestamp(),
+                    msg.subject,
+                    msg.body,
+                    to_json(&msg.received_by),
+                    serde_json::to_string(&msg.sources)?,
+                    msg.list_unsubscribe,
+                ],
+            )?;
+        }
+        for label in &thread.labels {
+            tx.execute(
+                "INSERT INTO labels VALUES (?1, ?2, ?3)",
+                params![d, key, label],
+            )?;
+        }
+        for address in &thread.participants {
+            tx.execute(
+                "INSERT INTO participants VALUES (?1, ?2, ?3)",
+                params![d, key, address],
+            )?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn delete_rows(conn: &Connection, dir: &str, key: &str) -> Result<()> {
+        for table in ["threads", "messages", "labels", "participants"] {
+            conn.execute(
+                &format!("DELETE FROM {} WHERE dir = ?1 AND key = ?2", table),
+                params![dir, key],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// The mirrored thread for `key` in `dir`, if its stamp still matches
+    /// the file at `path`.
+    pub fn cached(&self, dir: &Path, key: &str, path: &Path) -> Result<Option<Thread>> {
+        let Some(stamp) = file_stamp(path) else {
+            return Ok(None);
+        };
+        let d = Self::dir_key(dir);
+        let row: Option<(Thread, Stamp)> = self
+            .conn
+            .query_row(
+                "SELECT thread_id, subject, last_date, labels, accounts, participants, tags, notes,
+                        mtime, size, parts
+                 FROM threads WHERE dir = ?1 AND key = ?2",
+                params![d, key],
+                |r| {
+                    Ok((
+                        Thread {
+                            id: r.get(0)?,
+                            subject: r.get(1)?,
+                            last_date: r.get(2)?,
+                            labels: from_json(&r.get::<_, String>(3)?),
+                            accounts: from_json(&r.get::<_, String>(4)?),
+                            participants: from_json(&r.get::<_, String>(5)?),
+                            tags: from_json(&r.get::<_, String>(6)?),
+                            notes: from_json(&r.get::<_, String>(7)?),
+                            messages: Vec::new(),
+                        },
+                        (r.get(8)?, r.get(9)?, r.get(10)?),
+                    ))
+                },
+            )
+            .optional()?;
+        let Some((mut thread, row_stamp)) = row else {
+            return Ok(None);
+        };
+        if row_stamp != stamp {
+            return Ok(None);
+        }
+        let mut stmt = self.conn.prepare(
+            "SELECT thread_id, message_id, from_addr, to_addr, cc, date, subject, body,
+                    received_by, sources, list_unsubscribe
+             FROM messages WHERE dir = ?1 AND key = ?2 ORDER BY seq",
+        )?;
+        let messages = stmt.query_map(params![d, key], |r| {
+            Ok(Message {
+                thread_id: r.get(0)?,
+                message_id: r.get(1)?,
+                from: r.get(2)?,
+                to: r.get(3)?,
+                cc: r.get(4)?,
+                date: r.get(5)?,
+                subject: r.get(6)?,
+                body: r.get(7)?,
+                received_by: from_json(&r.get::<_, String>(8)?),
+                sources: from_json(&r.get::<_, String>(9)?),
+                list_unsubscribe: r.get(10)?,
+                ..Default::default()
+            })
+        })?;
+        for msg in messages {
+            thread.messages.push(msg?);
+        }
+        Ok(Some(thread))
+    }
+
+    /// Bring the mirror of `files` up to date: re-read conversations whose
+    /// file changed and drop rows for files that are gone. Returns how many
+    /// were re-read.
+    pub fn refresh(&self, files: &MarkdownStore) -> Result<usize> {
+        let keys = files.keys()?;
+        let present: HashSet<&str> = keys.iter().map(String::as_str).collect();
+        let d = Self::dir_key(files.dir());
+        let mut stmt = self
+            .conn
+            .prepare("SELECT key, mtime, size, parts FROM threads WHERE dir = ?1")?;
+        let mirrored: HashMap<String, Stamp> = stmt
+            .query_map(params![d], |r| {
+                Ok((r.get(0)?, (r.get(1)?, r.get(2)?, r.get(3)?)))
+            })?
+            .collect::<Result<_, _>>()?;
+        for gone in mirrored.keys().filter(|k| !present.contains(k.as_str())) {
+            Self::delete_rows(&self.conn, &d, gone)?;
+        }
+        let mut reread = 0;
+        for key in &keys {
+            let path = files.path(key);
+            let stamp = file_stamp(&path);
+            if stamp.is_some() && mirrored.get(key) == stamp.as_ref() {
+                continue;
+            }
+            if let Some(thread) = files.load(key)? {
+                self.upsert(files.dir(), key, &thread, &path)?;
+                reread += 1;
+            }
+        }
+        Ok(reread)
+    }
+
+    /// Conversations in `dirs` with a message whose subject, sender, or
+    /// body contains `query` (case-insensitive), optionally only those
+    /// labeled `label`. Newest first.
+    pub fn search(
+        &self,
+        dirs: &[PathBuf],
+        query: &str,
+        label: Option<&str>,
+    ) -> Result<Vec<SearchHit>> {
+        let pattern = format!(
+            "%{}%",
+            query
+                .replace('\\', "\\\\")
+                .replace('%', "\\%")
+                .replace('_', "\\_")
+        );
+        let mut stmt = self.conn.prepare(
+            "SELECT t.dir, t.key, t.subject, t.last_date, COUNT(*)
+             FROM messages m JOIN threads t ON t.dir = m.dir AND t.key = m.key
+             WHERE m.dir = ?1
+               AND (m.subject LIKE ?2 ESCAPE '\\' OR m.from_addr LIKE ?2 ESCAPE '\\'
+                    OR m.body LIKE ?2 ESCAPE '\\')
+               AND (?3 IS NULL OR EXISTS (
+                    SELECT 1 FROM labels l WHERE l.dir = t.dir AND l.key = t.key AND l.label = ?3))
+             GROUP BY t.dir, t.key
+             ORDER BY t.last_ts DESC, t.key",
+        )?;
+        let mut hits = Vec::new();
+        for dir in dirs {
+            let rows = stmt.query_map(params![Self::dir_key(dir), pattern, label], |r| {
+                Ok(SearchHit {
+                    dir: r.get(0)?,
+                    key: r.get(1)?,
+                    subject: r.get(2)?,
+                    last_date: r.get(3)?,
+                    matches: r.get::<_, i64>(4)? as usize,
+                })
+            })?;
+            for hit in rows {
+                hits.push(hit?);
+            }
+        }
+        Ok(hits)
+    }
+
+    /// Totals over the conversations in `dirs`; `top` caps the sender list.
+    pub fn stats(&self, dirs: &[PathBuf], top: usize) -> Result<Stats> {
+        let mut stats = Stats::default();
+        let mut labels: std::collections::BTreeMap<String, usize> = Default::default();
+        let mut senders: std::collections::BTreeMap<String, usize> = Default::default();
+        for dir in dirs {
+            let d = Self::dir_key(dir);
+            let (threads, messages, first, last): (i64, i64, Option<i64>, Option<i64>) =
+                self.conn.query_row(
+                    "SELECT (SELECT COUNT(*) FROM threads WHERE dir = ?1),
+                            COUNT(*),
+                            MIN(CASE WHEN ts > 0 THEN ts END),
+                            MAX(CASE WHEN ts > 0 THEN ts END)
+                     FROM messages WHERE dir = ?1",
+                    params![d],
+                    |r| Ok((r.get(0)?, r.get(1)?, r.get(2)?, r.get(3)?)),
+                )?;
+            stats.threads += threads as usize;
+            stats.messages += messages as usize;
+            stats.first_ts = match (stats.first_ts, first) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            };
+            stats.last_ts = match (stats.last_ts, last) {
+                (Some(a), Some(b)) => Some(a.max(b)),
+                (a, b) => a.or(b),
+            };
+            let mut stmt = self
+                .conn
+                .prepare("SELECT label, COUNT(*) FROM labels WHERE dir = ?1 GROUP BY label")?;
+            for row in stmt.query_map(params![d], |r| {
+                Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?))
+            })? {
+                let (label, n) = row?;
+                *labels.entry(label).or_default() += n as usize;
+            }
+            let mut stmt = self.conn.prepare(
+                "SELECT from_addr, COUNT(*) FROM messages WHERE dir = ?1 AND from_addr != ''
+                 GROUP BY from_addr",
+            )?;
+            for row in stmt.query_map(params![d], |r| {
+                Ok((r.get::<_, String>(0)?, r.get::<_, i64>(1)?))
+            })? {
+                let (sender, n) = row?;
+                *senders.entry(sender).or_default() += n as usize;
+            }
+        }
+        stats.labels = labels.into_iter().collect();
+        stats.labels.sort_by(|a, b| b.1.cmp(&a.1));
+        stats.senders = senders.into_iter().collect();
+        stats.senders.sort_by(|a, b| b.1.cmp(&a.1));
+        stats.senders.truncate(top);
+        Ok(stats)
+    }
+}
+
+/// A [`MarkdownStore`] read through the mirror: loads come from SQLite
+/// while the file is unchanged, and saves update both.
+pub struct MirrorStore {
+    mirror: Mirror,
+    files: MarkdownStore,
+}
+
+impl MirrorStore {
+    pub fn new(mirror: Mirror, files: MarkdownStore) -> Self {
+        Self { mirror, files }
+    }
+}
+
+impl ConversationStore for MirrorStore {
+    fn location(&self) -> String {
+        self.files.location()
+    }
+
+    fn keys(&self) -> Result<Vec<String>> {
+        self.files.keys()
+    }
+
+    fn load(&self, key: &str) -> Result<Option<Thread>> {
+        let path = self.files.path(key);
+        if let Some(thread) = self.mirror.cached(self.files.dir(), key, &path)? {
+            return Ok(Some(thread));
+        }
+        let thread = self.files.load(key)?;
+        if let Some(ref thread) = thread {
+            self.mirror.upsert(self.files.dir(), key, thread, &path)?;
+        }
+        Ok(thread)
+    }
+
+    fn save(&self, key: &str, thread: &Thread) -> Result<()> {
+        self.files.save(key, thread)?;
+        let path = self.files.path(key);
+        if let Err(e) = self.mirror.upsert(self.files.dir(), key, thread, &path) {
+            let msg = format!("SQLite mirror not updated for {}: {}", key, e);
+            eprintln!("  {}", output::warning(&msg));
+        }
+        Ok(())
+    }
+
+    fn find(&self, thread_id: &str) -> Option<String> {
+        self.files.find(thread_id)
+    }
+
+    fn new_key(&self, stem: &str) -> String {
+        self.files.new_key(stem)
+    }
+
+    fn parts(&self, key: &str) -> usize {
+        self.files.parts(key)
+    }
+
+    fn display_name(&self, key: &str) -> String {
+        self.files.display_name(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::types::MessageSource;
+    use crate::sync::types::fixtures::{message, thread};
+
+    fn plans(body: &str) -> Thread {
+        let msg = Message {
+            message_id: "<p1@example.com>".to_string(),
+            received_by: vec!["personal".to_string()],
+            sources: vec![MessageSource {
+                account: "personal".to_string(),
+                label: "inbox".to_string(),
+                uid: 4,
+            }],
+            ..message(
+                "Alice <alice@example.com>",
+                "Mon, 10 Feb 2025 10:00:00 +0000",
+                body,
+            )
+        };
+        Thread {
+            labels: vec!["inbox".to_string(), "work".to_string()],
+            accounts: vec!["personal".to_string()],
+            participants: vec!["Alice <alice@example.com>".to_string()],
+            ..thread("plans", "Plans", vec![msg])
+        }
+    }
+
+    #[test]
+    fn test_mirror_store_caches_until_file_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("conversations");
+        let files = MarkdownStore::with_config(&dir, SyncConfig::default());
+        let store = MirrorStore::new(Mirror::open(&tmp.path().join(MIRROR_FILE)).unwrap(), files);
+        store.save("plans", &plans("Lunch at noon?")).unwrap();
+
+        let cached = store.load("plans").unwrap().unwrap();
+        assert_eq!(cached.labels, vec!["inbox", "work"]);
+        assert_eq!(cached.messages[0].sources[0].uid, 4);
+        assert_eq!(cached.messages[0].body, "Lunch at noon?");
+
+        // Edited behind the mirror's back: the file wins.
+        let path = dir.join("plans.md");
+        let text = std::fs::read_to_string(&path).unwrap();
+        std::fs::write(&path, text.replace("Lunch at noon?", "Dinner at eight?")).unwrap();
+        assert_eq!(
+            store.load("plans").unwrap().unwrap().messages[0].body,
+            "Dinner at eight?"
+        );
+
+        let mirror = Mirror::open(&tmp.path().join(MIRROR_FILE)).unwrap();
+        let hits = mirror.search(&[dir.clone()], "DINNER", None).unwrap();
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].key, "plans");
+        assert!(mirror
+            .search(&[dir.clone()], "dinner", Some("archive"))
+            .unwrap()
+            .is_empty());
+        let stats = mirror.stats(&[dir.clone()], 10).unwrap();
+        assert_eq!((stats.threads, stats.messages), (1, 1));
+        assert_eq!(stats.labels.len(), 2);
+
+        std::fs::remove_file(&path).unwrap();
+        let files = MarkdownStore::with_config(&dir, SyncConfig::default());
+        mirror.refresh(&files).unwrap();
+        assert_eq!(mirror.stats(&[dir], 10).unwrap().threads, 0);
+    }
+
+    #[test]
+    fn test_refresh_rereads_when_a_later_part_changes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = tmp.path().join("conversations");
+        let sync = SyncConfig {
+            split_messages: 1,
+            ..Default::default()
+        };
+        let files = MarkdownStore::with_config(&dir, sync);
+        let mut split = plans("Lunch at noon?");
+        split.messages.push(message(
+            "Bob <bob@example.com>",
+            "Tue, 11 Feb 2025 10:00:00 +0000",
+            "Sounds good",
+        ));
+        files.save("plans", &split).unwrap();
+        assert_eq!(files.parts("plans"), 2);
+
+        let mirror = Mirror::open(&tmp.path().join(MIRROR_FILE)).unwrap();
+        assert_eq!(mirror.refresh(&files).unwrap(), 1);
+        assert_eq!(mirror.refresh(&files).unwrap(), 0);
+
+        let part = dir.join("plans.part2.md");
+        let text = std::fs::read_to_string(&part).unwrap();
+        std::fs::write(&part, text.replace("Sounds good", "Sounds great")).unwrap();
+        assert_eq!(mirror.refresh(&files).unwrap(), 1);
+        let hits = mirror.search(&[dir], "great", None).unwrap();
+        assert_eq!(hits.len(), 1);
+    }
+}
@@ -1,48 +1,258 @@
 //! IMAP email sync — fetch threads from IMAP and write to Markdown.
 
 pub mod auth;
+pub mod auto_label;
+pub mod blocklist;
+#[cfg(feature = "net")]
 pub mod folders;
+#[cfg(feature = "net")]
+pub mod hydrate;
 pub mod imap_sync;
+pub mod index_hook;
+pub mod links;
 pub mod manifest;
 pub mod markdown;
+#[cfg(feature = "sqlite")]
+pub mod mirror;
+pub mod provenance;
+pub mod redact;
+pub mod report;
 pub mod routes;
 pub mod slack_import;
 pub mod sms_import;
+pub mod store;
 pub mod telegram_import;
+pub mod throttle;
+#[cfg(all(feature = "net", feature = "tls-rustls"))]
+pub mod tls;
 pub mod types;
 
-use anyhow::Result;
-use std::collections::HashSet;
-use std::path::PathBuf;
+use anyhow::{bail, Result};
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
-use crate::accounts::{load_accounts, resolve_password};
 use crate::resolve;
 
-use self::imap_sync::sync_account;
-use self::manifest::generate_manifest;
 use self::types::SyncState;
+#[cfg(feature = "net")]
+use {
+    self::imap_sync::{build_label_routes, sync_account_session, ImapSession},
+    self::manifest::generate_manifest,
+    self::report::{FailedAccount, SyncReport},
+    crate::accounts::{account_secret, load_accounts, Secret},
+    crate::error::CorkyError,
+    std::collections::HashSet,
+    std::io::IsTerminal,
+    std::time::Instant,
+};
 
-/// Load sync state from disk.
+/// The previous good copy of state file `path`, kept by [`save_state`].
+pub fn state_backup_file(path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(".bak");
+    PathBuf::from(name)
+}
+
+fn read_state_file(path: &Path) -> Result<SyncState> {
+    let data = std::fs::read(path)?;
+    types::decode_state(&data)
+}
+
+/// Load sync state from disk. A state file that is truncated or fails its
+/// checksum is replaced by the backup from the previous save, with
+/// `last_uid` re-derived from provenance (§6.6).
 pub fn load_state() -> Result<SyncState> {
-    let sf = resolve::sync_state_file();
-    if sf.exists() {
-        let data = std::fs::read(&sf)?;
-        let state = types::load_state(&data)?;
-        Ok(state)
-    } else {
-        Ok(SyncState::default())
+    let sf = resolve::sync_state_read_file();
+    let bak = state_backup_file(&sf);
+    if !sf.exists() && !bak.exists() {
+        return Ok(SyncState::default());
     }
+    let err = match read_state_file(&sf) {
+        Ok(state) => return Ok(state),
+        Err(e) => e,
+    };
+    let Ok(mut state) = read_state_file(&bak) else {
+        bail!(
+            "Sync state {} is unreadable ({}) and has no usable backup.\n\
+             Run `corky sync full` to rebuild it from the server.",
+            sf.display(),
+            err
+        );
+    };
+    let repaired = provenance::repair_last_uids(&mut state);
+    eprintln!(
+        "{}",
+        crate::output::warning(&format!(
+            "Warning: sync state {} is unreadable ({}); using backup {} ({} label(s) repaired)",
+            sf.display(),
+            err,
+            bak.display(),
+            repaired.len()
+        ))
+    );
+    Ok(state)
 }
 
-/// Save sync state to disk.
+/// Save sync state to disk. The new state goes to a temp file that is
+/// renamed into place, and the file it replaces becomes the backup if it
+/// still checks out, so a crash mid-write never loses the last good copy.
 pub fn save_state(state: &SyncState) -> Result<()> {
-    let data = serde_json::to_vec(state)?;
-    std::fs::write(resolve::sync_state_file(), data)?;
+    let sf = resolve::sync_state_file();
+    let data = types::encode_state(state)?;
+    let mut tmp_name = sf.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = PathBuf::from(tmp_name);
+    {
+        let mut file = std::fs::File::create(&tmp)?;
+        file.write_all(&data)?;
+        file.sync_all()?;
+    }
+    if read_state_file(&sf).is_ok() {
+        std::fs::rename(&sf, state_backup_file(&sf))?;
+    }
+    std::fs::rename(&tmp, &sf)?;
+    Ok(())
+}
+
+/// corky sync repair
+pub fn repair() -> Result<()> {
+    let sf = resolve::sync_state_read_file();
+    let bak = state_backup_file(&sf);
+    let (mut state, source) = match read_state_file(&sf) {
+        Ok(state) => (state, sf.clone()),
+        Err(err) => match read_state_file(&bak) {
+            Ok(state) => {
+                println!("{} is unreadable ({}); using {}", sf.display(), err, bak.display());
+                (state, bak.clone())
+            }
+            Err(_) if !sf.exists() && !bak.exists() => {
+                println!("No sync state yet; nothing to repair.");
+                return Ok(());
+            }
+            Err(_) => bail!(
+                "Neither {} ({}) nor its backup is readable.\n\
+                 Run `corky sync full` to rebuild sync state from the server.",
+                sf.display(),
+                err
+            ),
+        },
+    };
+    let repaired = provenance::repair_last_uids(&mut state);
+    let rows: Vec<Vec<String>> = repaired
+        .iter()
+        .map(|r| {
+            vec![
+                r.account.clone(),
+                r.label.clone(),
+                r.from.map(|u| u.to_string()).unwrap_or_else(|| "-".to_string()),
+                r.to.to_string(),
+            ]
+        })
+        .collect();
+    if !rows.is_empty() {
+        crate::output::print_table(&rows);
+    }
+    save_state(&state)?;
+    println!(
+        "Sync state rebuilt from {}: {} label(s) repaired",
+        source.display(),
+        repaired.len()
+    );
+    Ok(())
+}
+
+/// `--then-push`: push the account's pending drafts in `dir`
+/// (`draft::pending`) over the session sync used, logging in only when
+/// sync had none. The session is logged out either way.
+#[cfg(all(feature = "net", feature = "smtp"))]
+fn push_drafts(
+    name: &str,
+    acct: &crate::accounts::Account,
+    secret: &Secret,
+    dir: Option<&Path>,
+    session: Option<ImapSession>,
+) -> Result<()> {
+    let session = match dir {
+        Some(dir) => {
+            let drafts = crate::draft::pending::pending_drafts(dir)?;
+            let drafts = crate::draft::pending::for_account(&drafts, name)?;
+            if drafts.is_empty() {
+                session
+            } else {
+                let mut session = match session {
+                    Some(session) => session,
+                    None => imap_sync::connect_imap_pub(
+                        &acct.imap_host,
+                        acct.imap_port,
+                        acct.imap_starttls,
+                        &acct.user,
+                        secret,
+                    )?,
+                };
+                let (pushed, failed) =
+                    crate::draft::pending::push_pending(&drafts, name, acct, &mut session);
+                crate::progress!("  Drafts: {} pushed, {} failed", pushed, failed);
+                Some(session)
+            }
+        }
+        None => session,
+    };
+    // Logout errors are non-fatal — data is already fetched and merged.
+    if let Some(mut session) = session {
+        let _ = session.logout();
+    }
+    Ok(())
+}
+
+/// Without `smtp` there is nothing to push (`run` rejects `--then-push`).
+#[cfg(all(feature = "net", not(feature = "smtp")))]
+fn push_drafts(
+    _name: &str,
+    _acct: &crate::accounts::Account,
+    _secret: &Secret,
+    _dir: Option<&Path>,
+    session: Option<ImapSession>,
+) -> Result<()> {
+    if let Some(mut session) = session {
+        let _ = session.logout();
+    }
     Ok(())
 }
 
-/// corky sync [--full] [--account NAME]
-pub fn run(full: bool, account: Option<&str>) -> Result<()> {
+/// corky sync [--json] [--headers-only] [--fail-fast] [--interactive] [--then-push DIR]
+/// [full | account NAME]
+///
+/// An account that fails (bad password, login refused, connection lost) is
+/// reported and skipped; the others still sync. `fail_fast` stops at the
+/// first failure instead. An account without labels gets its folders listed
+/// (`folders::discover`); `interactive` lets the user pick some to sync.
+/// `then_push` pushes pending drafts from that directory after each
+/// account syncs, on the same connection.
+#[cfg(feature = "net")]
+pub fn run(
+    full: bool,
+    account: Option<&str>,
+    json: bool,
+    headers_only: bool,
+    fail_fast: bool,
+    interactive: bool,
+    then_push: Option<&Path>,
+) -> Result<()> {
+    if interactive && !std::io::stdin().is_terminal() {
+        bail!("--interactive needs a terminal");
+    }
+    if let Some(dir) = then_push {
+        if !cfg!(feature = "smtp") {
+            return Err(crate::util::missing_feature("smtp"));
+        }
+        if !dir.is_dir() {
+            bail!("Drafts directory not found: {}", dir.display());
+        }
+    }
+    if json {
+        crate::output::set_quiet();
+    }
+    let started = Instant::now();
     let accounts = load_accounts(None)?;
     let mut state = if full {
         SyncState::default()
@@ -66,55 +276,164 @@ pub fn run(full: bool, account: Option<&str>) -> Result<()> {
     // Track touched files for --full orphan cleanup
     let mut touched: Option<HashSet<PathBuf>> = if full { Some(HashSet::new()) } else { None };
 
+    let mut report = SyncReport::default();
+    let mut errors: Vec<anyhow::Error> = Vec::new();
     for name in &names {
         let acct = &accounts[name];
-        println!("\n=== Account: {} ({}) ===", name, acct.user);
-        let password = resolve_password(acct)?;
-        sync_account(
-            name,
-            &acct.imap_host,
-            acct.imap_port,
-            acct.imap_starttls,
-            &acct.user,
-            &password,
-            &acct.labels,
-            acct.sync_days,
-            &mut state,
-            full,
-            None,
-            touched.as_mut(),
-        )?;
-    }
-
-    // Orphan cleanup on --full
+        crate::progress!("\n=== Account: {} ({}) ===", name, acct.user);
+        auth::refresh_stored(name, acct);
+        let result = account_secret(name, acct).and_then(|secret| {
+            let mut labels = acct.labels.clone();
+            if labels.is_empty() && build_label_routes(name)?.is_empty() {
+                labels = folders::discover(name, acct, &secret, interactive)?;
+                if labels.is_empty() {
+                    push_drafts(name, acct, &secret, then_push, None)?;
+                    return Ok(Vec::new());
+                }
+            }
+            let (reports, session) = sync_account_session(
+                name,
+                &acct.imap_host,
+                acct.imap_port,
+                acct.imap_starttls,
+                &acct.user,
+                &secret,
+                &labels,
+                acct.sync_days,
+                &acct.label_overrides,
+                &mut state,
+                full,
+                headers_only,
+                None,
+                touched.as_mut(),
+            )?;
+            push_drafts(name, acct, &secret, then_push, session)?;
+            Ok(reports)
+        });
+        match result {
+            Ok(labels) => report.labels.extend(labels),
+            Err(e) => {
+                // Keep what was fetched so the next sync resumes, not restarts.
+                save_state(&state)?;
+                if fail_fast {
+                    return Err(e);
+                }
+                eprintln!(
+                    "{}",
+                    crate::output::error(&format!("Account {} failed: {:#}", name, e))
+                );
+                report.failures.push(FailedAccount {
+                    account: name.clone(),
+                    error: format!("{:#}", e),
+                });
+                errors.push(e);
+            }
+        }
+    }
+    if !errors.is_empty() && errors.len() == names.len() {
+        // Nothing synced: fail with the first error (and its exit code).
+        if !json && !crate::output::quiet() && errors.len() > 1 {
+            print!("{}", report.failures_table());
+        }
+        return Err(errors.remove(0));
+    }
+
+    // Orphan cleanup on --full; a failed account's files weren't touched,
+    // so cleanup would delete them.
     let conv_dir = resolve::conversations_dir();
     if let Some(ref touched_set) = touched {
-        cleanup_orphans(&conv_dir, touched_set)?;
+        if report.failures.is_empty() {
+            cleanup_orphans(&conv_dir, touched_set)?;
+        } else {
+            crate::progress!("\nSkipping orphan cleanup: not every account synced.");
+        }
     }
+    provenance::flag_root_orphans(&state)?;
 
     // Generate manifest
-    generate_manifest(&conv_dir)?;
+    crate::profile::time("run", "manifest", || generate_manifest(&conv_dir))?;
+    crate::contact::threads::refresh_all();
 
-    save_state(&state)?;
-    println!("\nSync complete.");
+    crate::profile::time("run", "state", || save_state(&state))?;
+    report.elapsed_secs = started.elapsed().as_secs_f64();
+    if json {
+        println!("{}", report.to_json());
+    } else if !crate::output::quiet() {
+        println!("\n{}", crate::output::paint("Summary", crate::output::Style::Bold));
+        print!("{}", report.table());
+        if !report.failures.is_empty() {
+            println!("\n{}", crate::output::paint("Failed", crate::output::Style::Bold));
+            print!("{}", report.failures_table());
+        }
+    }
+    if !report.failures.is_empty() {
+        let failed: Vec<&str> = report.failures.iter().map(|f| f.account.as_str()).collect();
+        return Err(CorkyError::AccountsFailed(format!(
+            "Sync finished, but {} of {} account(s) failed: {}",
+            failed.len(),
+            names.len(),
+            failed.join(", ")
+        ))
+        .into());
+    }
+    let skipped = report.skipped();
+    if skipped > 0 {
+        return Err(CorkyError::PartialSync(format!(
+            "Sync finished, but {} message(s) could not be parsed and were skipped",
+            skipped
+        ))
+        .into());
+    }
+    crate::progress!("\nSync complete.");
     Ok(())
 }
 
+#[cfg(not(feature = "net"))]
+pub fn run(
+    _full: bool,
+    _account: Option<&str>,
+    _json: bool,
+    _headers_only: bool,
+    _fail_fast: bool,
+    _interactive: bool,
+    _then_push: Option<&Path>,
+) -> Result<()> {
+    Err(crate::util::missing_feature("net"))
+}
+
 /// Delete conversation files not touched during a --full sync.
+#[cfg(feature = "net")]
 fn cleanup_orphans(conversations_dir: &PathBuf, touched: &HashSet<PathBuf>) -> Result<()> {
     if !conversations_dir.exists() {
         return Ok(());
     }
+    // Continuation parts of a touched conversation are kept with it.
+    let keep: HashSet<PathBuf> = touched.iter().flat_map(|p| markdown::thread_files(p)).collect();
+    let mut orphans = Vec::new();
     for entry in std::fs::read_dir(conversations_dir)? {
         let entry = entry?;
         let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) == Some("md") && !touched.contains(&path) {
-            std::fs::remove_file(&path)?;
-            println!(
-                "  Removed orphan: {}",
-                path.file_name().unwrap_or_default().to_string_lossy()
-            );
+        if path.extension().and_then(|e| e.to_str()) == Some("md") && !keep.contains(&path) {
+            orphans.push(path);
         }
     }
+    if orphans.is_empty() {
+        return Ok(());
+    }
+    orphans.sort();
+    let name = |p: &PathBuf| p.file_name().unwrap_or_default().to_string_lossy().to_string();
+    crate::progress!("\nNot found on the server by the full sync:");
+    for path in &orphans {
+        crate::progress!("  {}", name(path));
+    }
+    let question = format!("Delete these {} conversation file(s)?", orphans.len());
+    if !crate::output::confirm(&question)? {
+        crate::progress!("Kept {} orphan(s)", orphans.len());
+        return Ok(());
+    }
+    for path in &orphans {
+        std::fs::remove_file(path)?;
+        crate::progress!("  Removed orphan: {}", name(path));
+    }
     Ok(())
 }
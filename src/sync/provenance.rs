@@ -0,0 +1,363 @@
+//! Thread provenance: the (account, label, UIDs) each thread was synced from.
+//!
+//! Kept in sync state so an incremental sync can notice threads whose
+//! sources are gone — messages expunged, a label deleted on the server or
+//! dropped from the config — without the `--full` touched set. Such threads
+//! are tagged `orphan` in their conversation file, never deleted.
+
+use anyhow::Result;
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+
+use super::imap_sync::set_mtime;
+use super::markdown::{is_part_file, read_thread, write_thread};
+use super::types::{AccountSyncState, LabelState, SyncState};
+use crate::config::corky_config::{self, SyncConfig};
+use crate::resolve;
+
+/// Tag marking a thread with no remaining source on any server.
+pub const ORPHAN_TAG: &str = "orphan";
+
+/// Note that `uid` in `label` was synced into thread `thread_id`.
+pub fn record(acct: &mut AccountSyncState, thread_id: &str, label: &str, uid: u32) {
+    let uids = acct
+        .threads
+        .entry(thread_id.to_string())
+        .or_default()
+        .entry(label.to_string())
+        .or_default();
+    if !uids.contains(&uid) {
+        uids.push(uid);
+    }
+}
+
+/// Every UID recorded for `label`, across threads.
+pub fn label_uids(acct: &AccountSyncState, label: &str) -> Vec<u32> {
+    let mut uids: Vec<u32> = acct
+        .threads
+        .values()
+        .filter_map(|labels| labels.get(label))
+        .flatten()
+        .copied()
+        .collect();
+    uids.sort_unstable();
+    uids.dedup();
+    uids
+}
+
+/// Threads with any of `uids` recorded for `label`, sorted.
+pub fn threads_with_uids(acct: &AccountSyncState, label: &str, uids: &HashSet<u32>) -> Vec<String> {
+    let mut threads: Vec<String> = acct
+        .threads
+        .iter()
+        .filter(|(_, labels)| {
+            labels
+                .get(label)
+                .is_some_and(|recorded| recorded.iter().any(|u| uids.contains(u)))
+        })
+        .map(|(thread_id, _)| thread_id.clone())
+        .collect();
+    threads.sort();
+    threads
+}
+
+/// Keep only the UIDs of `label` still on the server. Returns how many were
+/// dropped. Threads stay listed (with no UIDs) so they can be flagged.
+pub fn retain_uids(acct: &mut AccountSyncState, label: &str, present: &HashSet<u32>) -> usize {
+    let mut dropped = 0;
+    for labels in acct.threads.values_mut() {
+        if let Some(uids) = labels.get_mut(label) {
+            let before = uids.len();
+            uids.retain(|u| present.contains(u));
+            dropped += before - uids.len();
+            if uids.is_empty() {
+                labels.remove(label);
+            }
+        }
+    }
+    dropped
+}
+
+/// Forget every UID of `label`: it is gone from the server or the config,
+/// or its UIDVALIDITY changed (a full resync records them again).
+pub fn forget_label(acct: &mut AccountSyncState, label: &str) -> usize {
+    retain_uids(acct, label, &HashSet::new())
+}
+
+/// Threads that were synced at some point but have no source left in any
+/// account.
+pub fn orphaned_threads(state: &SyncState) -> BTreeSet<String> {
+    let mut known = BTreeSet::new();
+    let mut sourced = HashSet::new();
+    for acct in state.accounts.values() {
+        for (thread_id, labels) in &acct.threads {
+            known.insert(thread_id.clone());
+            if !labels.is_empty() {
+                sourced.insert(thread_id.clone());
+            }
+        }
+    }
+    known.retain(|id| !sourced.contains(id));
+    known
+}
+
+/// A label whose `last_uid` was re-derived by [`repair_last_uids`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Repaired {
+    pub account: String,
+    pub label: String,
+    /// `last_uid` before the repair; None if the label had no state.
+    pub from: Option<u32>,
+    pub to: u32,
+}
+
+/// Re-derive `last_uid` per label from the UIDs recorded in threads: a
+/// label behind its newest recorded UID is moved up to it, and a label
+/// only provenance knows about gets state with UIDVALIDITY 0, which the
+/// next sync adopts from the server. `last_uid` is never lowered, since
+/// skipped and expunged messages leave no record.
+pub fn repair_last_uids(state: &mut SyncState) -> Vec<Repaired> {
+    let mut repaired = Vec::new();
+    let mut names: Vec<String> = state.accounts.keys().cloned().collect();
+    names.sort();
+    for name in names {
+        let acct = state.accounts.get_mut(&name).expect("account listed");
+        let mut labels: Vec<String> = acct
+            .threads
+            .values()
+            .flat_map(|labels| labels.keys().cloned())
+            .collect();
+        labels.sort();
+        labels.dedup();
+        for label in labels {
+            let Some(newest) = label_uids(acct, &label).last().copied() else {
+                continue;
+            };
+            let from = acct.labels.get(&label).map(|l| l.last_uid);
+            if from.is_some_and(|uid| uid >= newest) {
+                continue;
+            }
+            acct.labels
+                .entry(label.clone())
+                .or_insert(LabelState {
+                    uidvalidity: 0,
+                    last_uid: 0,
+                })
+                .last_uid = newest;
+            repaired.push(Repaired {
+                account: name.clone(),
+                label,
+                from,
+                to: newest,
+            });
+        }
+    }
+    repaired
+}
+
+/// Add the orphan tag to files in `dir` whose thread is in `orphans`, and
+/// remove it from those no longer orphaned. Returns (flagged, cleared).
+pub fn flag_orphans(
+    dir: &Path,
+    orphans: &BTreeSet<String>,
+    sync_config: &SyncConfig,
+) -> Result<(Vec<String>, Vec<String>)> {
+    let mut flagged = Vec::new();
+    let mut cleared = Vec::new();
+    if !dir.is_dir() {
+        return Ok((flagged, cleared));
+    }
+    let mut files: Vec<_> = std::fs::read_dir(dir)?
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("md") && !is_part_file(p))
+        .collect();
+    files.sort();
+    for path in files {
+        let Some(mut thread) = read_thread(&path)? else {
+            continue;
+        };
+        let tagged = thread.tags.iter().any(|t| t == ORPHAN_TAG);
+        let orphan = orphans.contains(&thread.id);
+        if orphan == tagged {
+            continue;
+        }
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .to_string();
+        if orphan {
+            thread.tags.push(ORPHAN_TAG.to_string());
+            flagged.push(name);
+        } else {
+            thread.tags.retain(|t| t != ORPHAN_TAG);
+            cleared.push(name);
+        }
+        for written in write_thread(&path, &thread, sync_config)? {
+            let _ = set_mtime(&written, &thread.last_date);
+        }
+    }
+    Ok((flagged, cleared))
+}
+
+/// Flag orphaned threads in the root `conversations/` and report changes.
+pub fn flag_root_orphans(state: &SyncState) -> Result<()> {
+    let sync_config = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    let orphans = orphaned_threads(state);
+    let (flagged, cleared) = flag_orphans(&resolve::conversations_dir(), &orphans, &sync_config)?;
+    for name in &flagged {
+        crate::progress!("  Orphaned (no source left): {}", name);
+    }
+    for name in &cleared {
+        crate::progress!("  No longer orphaned: {}", name);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::types::fixtures::{message, thread};
+
+    #[test]
+    fn test_orphans_need_every_source_gone() {
+        let mut state = SyncState::default();
+        let work = state.accounts.entry("work".to_string()).or_default();
+        record(work, "lunch", "INBOX", 4);
+        record(work, "lunch", "INBOX", 4);
+        record(work, "lunch", "Archive", 9);
+        record(work, "plans", "INBOX", 5);
+        assert_eq!(label_uids(work, "INBOX"), vec![4, 5]);
+
+        let flagged: HashSet<u32> = [4, 9].into_iter().collect();
+        assert_eq!(threads_with_uids(work, "INBOX", &flagged), vec!["lunch"]);
+        assert!(threads_with_uids(work, "Sent", &flagged).is_empty());
+
+        let present: HashSet<u32> = [5].into_iter().collect();
+        assert_eq!(retain_uids(work, "INBOX", &present), 1);
+        assert!(orphaned_threads(&state).is_empty());
+
+        let work = state.accounts.get_mut("work").unwrap();
+        assert_eq!(forget_label(work, "Archive"), 1);
+        let orphans: Vec<String> = orphaned_threads(&state).into_iter().collect();
+        assert_eq!(orphans, vec!["lunch"]);
+
+        // Another account still holding the thread keeps it alive.
+        let home = state.accounts.entry("home".to_string()).or_default();
+        record(home, "lunch", "INBOX", 1);
+        assert!(orphaned_threads(&state).is_empty());
+    }
+
+    #[test]
+    fn test_repair_last_uids_from_threads() {
+        let mut state = SyncState::default();
+        let work = state.accounts.entry("work".to_string()).or_default();
+        record(work, "lunch", "INBOX", 4);
+        record(work, "plans", "INBOX", 12);
+        record(work, "plans", "Archive", 30);
+        work.labels.insert(
+            "INBOX".to_string(),
+            LabelState {
+                uidvalidity: 7,
+                last_uid: 9,
+            },
+        );
+        let home = state.accounts.entry("home".to_string()).or_default();
+        record(home, "lunch", "INBOX", 3);
+        home.labels.insert(
+            "INBOX".to_string(),
+            LabelState {
+                uidvalidity: 2,
+                last_uid: 8,
+            },
+        );
+
+        let repaired = repair_last_uids(&mut state);
+        assert_eq!(
+            repaired,
+            vec![
+                Repaired {
+                    account: "work".to_string(),
+                    label: "Archive".to_string(),
+                    from: None,
+                    to: 30,
+                },
+                Repaired {
+                    account: "work".to_string(),
+                    label: "INBOX".to_string(),
+                    from: Some(9),
+                    to: 12,
+                },
+            ]
+        );
+        let work = &state.accounts["work"];
+        assert_eq!(work.labels["INBOX"].uidvalidity, 7);
+        assert_eq!(work.labels["Archive"].uidvalidity, 0);
+        // Ahead of its records (skipped or expunged mail) is left alone.
+        assert_eq!(state.accounts["home"].labels["INBOX"].last_uid, 8);
+        assert!(repair_last_uids(&mut state).is_empty());
+    }
+
+    #[test]
+    fn test_flag_orphans_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let text = "# Lunch\n\n**Thread ID**: lunch\n**Labels**: inbox\n\n---\n\n\
+                    ## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\nNoon?\n";
+        std::fs::write(dir.path().join("lunch.md"), text).unwrap();
+        let config = SyncConfig::default();
+
+        let orphans: BTreeSet<String> = ["lunch".to_string()].into_iter().collect();
+        let (flagged, cleared) = flag_orphans(dir.path(), &orphans, &config).unwrap();
+        assert_eq!(flagged, vec!["lunch.md"]);
+        assert!(cleared.is_empty());
+        let thread = read_thread(&dir.path().join("lunch.md")).unwrap().unwrap();
+        assert_eq!(thread.tags, vec![ORPHAN_TAG]);
+        assert_eq!(thread.messages.len(), 1);
+
+        // Flagging is idempotent, and clears once the thread has a source again.
+        assert!(
+            flag_orphans(dir.path(), &orphans, &config)
+                .unwrap()
+                .0
+                .is_empty()
+        );
+        let (_, cleared) = flag_orphans(dir.path(), &BTreeSet::new(), &config).unwrap();
+        assert_eq!(cleared, vec!["lunch.md"]);
+    }
+
+    #[test]
+    fn test_flag_orphans_split_thread() {
+        let dir = tempfile::tempdir().unwrap();
+        let main = dir.path().join("lunch.md");
+        let config = SyncConfig {
+            split_messages: 1,
+            ..Default::default()
+        };
+        let messages = vec![
+            message(
+                "Alice <alice@example.com>",
+                "Mon, 10 Feb 2025 10:00:00 +0000",
+                "Noon?",
+            ),
+            message(
+                "Bob <bob@example.com>",
+                "Mon, 10 Feb 2025 11:00:00 +0000",
+                "Sure",
+            ),
+        ];
+        write_thread(&main, &thread("lunch", "Lunch", messages), &config).unwrap();
+
+        let orphans: BTreeSet<String> = ["lunch".to_string()].into_iter().collect();
+        let (flagged, _) = flag_orphans(dir.path(), &orphans, &config).unwrap();
+        assert_eq!(flagged, vec!["lunch.md"]);
+        let part = std::fs::read_to_string(dir.path().join("lunch.part2.md")).unwrap();
+        assert!(part.contains("**Tags**: orphan\n"));
+        assert!(part.contains("**Part**: 2 of 2\n**Previous part**: lunch.md\n"));
+        let thread = read_thread(&main).unwrap().unwrap();
+        assert_eq!(thread.messages.len(), 2);
+        assert_eq!(thread.tags, vec![ORPHAN_TAG]);
+    }
+}
@@ -0,0 +1,177 @@
+//! End-of-run sync summary: one row per account and label.
+
+use serde::Serialize;
+
+use crate::output;
+
+/// What one label contributed to a sync run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct LabelReport {
+    pub account: String,
+    pub label: String,
+    /// Messages added to the base conversations dir.
+    pub new_messages: usize,
+    /// Conversation files created.
+    pub new_threads: usize,
+    /// Existing conversation files that gained messages.
+    pub updated_threads: usize,
+    /// Messages written to routed mailbox dirs (`[routing]`).
+    pub routed_copies: usize,
+    /// Messages already stored, updated from their envelope only.
+    pub already_stored: usize,
+    /// Messages that could not be parsed.
+    pub skipped: usize,
+    pub elapsed_secs: f64,
+}
+
+/// An account whose sync failed; the run went on without it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FailedAccount {
+    pub account: String,
+    /// The error with its causes, on one line.
+    pub error: String,
+}
+
+/// A whole `corky sync` run.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SyncReport {
+    pub labels: Vec<LabelReport>,
+    pub failures: Vec<FailedAccount>,
+    pub elapsed_secs: f64,
+}
+
+impl SyncReport {
+    /// Sum of every label's counts, as an `account = "total"` row.
+    pub fn totals(&self) -> LabelReport {
+        let mut t = LabelReport {
+            account: "total".to_string(),
+            elapsed_secs: self.elapsed_secs,
+            ..Default::default()
+        };
+        for r in &self.labels {
+            t.new_messages += r.new_messages;
+            t.new_threads += r.new_threads;
+            t.updated_threads += r.updated_threads;
+            t.routed_copies += r.routed_copies;
+            t.already_stored += r.already_stored;
+            t.skipped += r.skipped;
+        }
+        t
+    }
+
+    pub fn skipped(&self) -> usize {
+        self.labels.iter().map(|r| r.skipped).sum()
+    }
+
+    /// The summary table printed at the end of a sync.
+    pub fn table(&self) -> String {
+        let mut rows = vec![vec![
+            output::paint("Account", output::Style::Bold),
+            output::paint("Label", output::Style::Bold),
+            output::paint("New", output::Style::Bold),
+            output::paint("Threads+", output::Style::Bold),
+            output::paint("Updated", output::Style::Bold),
+            output::paint("Routed", output::Style::Bold),
+            output::paint("Known", output::Style::Bold),
+            output::paint("Skipped", output::Style::Bold),
+            output::paint("Time", output::Style::Bold),
+        ]];
+        let totals = self.totals();
+        for r in self.labels.iter().chain(std::iter::once(&totals)) {
+            rows.push(vec![
+                r.account.clone(),
+                r.label.clone(),
+                r.new_messages.to_string(),
+                r.new_threads.to_string(),
+                r.updated_threads.to_string(),
+                r.routed_copies.to_string(),
+                r.already_stored.to_string(),
+                r.skipped.to_string(),
+                format!("{:.1}s", r.elapsed_secs),
+            ]);
+        }
+        output::table(&rows)
+    }
+
+    /// The failed accounts, printed after the summary table.
+    pub fn failures_table(&self) -> String {
+        let mut rows = vec![vec![
+            output::paint("Account", output::Style::Bold),
+            output::paint("Error", output::Style::Bold),
+        ]];
+        for f in &self.failures {
+            rows.push(vec![f.account.clone(), output::error(&f.error)]);
+        }
+        output::table(&rows)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&serde_json::json!({
+            "labels": self.labels,
+            "totals": self.totals(),
+            "failures": self.failures,
+            "elapsed_secs": self.elapsed_secs,
+        }))
+        .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> SyncReport {
+        SyncReport {
+            labels: vec![
+                LabelReport {
+                    account: "personal".to_string(),
+                    label: "INBOX".to_string(),
+                    new_messages: 3,
+                    new_threads: 1,
+                    updated_threads: 2,
+                    routed_copies: 1,
+                    elapsed_secs: 1.3,
+                    ..Default::default()
+                },
+                LabelReport {
+                    account: "work".to_string(),
+                    label: "INBOX".to_string(),
+                    already_stored: 4,
+                    skipped: 1,
+                    elapsed_secs: 0.5,
+                    ..Default::default()
+                },
+            ],
+            failures: vec![FailedAccount {
+                account: "old".to_string(),
+                error: "LOGIN failed".to_string(),
+            }],
+            elapsed_secs: 2.0,
+        }
+    }
+
+    #[test]
+    fn test_totals_and_table() {
+        let r = report();
+        let t = r.totals();
+        assert_eq!(t.new_messages, 3);
+        assert_eq!(t.already_stored, 4);
+        assert_eq!(r.skipped(), 1);
+        let table = r.table();
+        let lines: Vec<&str> = table.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert!(lines[0].trim_start().starts_with("Account"));
+        assert!(lines[1].contains("personal") && lines[1].ends_with("1.3s"));
+        assert!(lines[3].trim_start().starts_with("total") && lines[3].ends_with("2.0s"));
+    }
+
+    #[test]
+    fn test_json_has_labels_and_totals() {
+        let v: serde_json::Value = serde_json::from_str(&report().to_json()).unwrap();
+        assert_eq!(v["labels"][0]["new_threads"], 1);
+        assert_eq!(v["labels"][1]["account"], "work");
+        assert_eq!(v["totals"]["already_stored"], 4);
+        assert_eq!(v["failures"][0]["account"], "old");
+        assert_eq!(v["elapsed_secs"], 2.0);
+    }
+}
@@ -2,19 +2,27 @@
 
 use anyhow::Result;
 
-use super::imap_sync::build_label_routes;
-use super::markdown::parse_thread_markdown;
+use super::imap_sync::{build_label_routes, merge_threads};
+use super::markdown::thread_to_markdown_with;
+use super::redact::{Redactor, Redactors};
+use super::store::{ConversationStore, MarkdownStore};
+use super::types::Thread;
+use crate::config::corky_config::{self, SyncConfig};
 use crate::resolve;
 
 /// Apply `[routing]` rules to conversations already on disk.
 ///
 /// Scans `conversations/*.md`, checks each thread's labels against the
 /// routing table, and copies matching files into the corresponding
-/// mailbox `conversations/` directories.
+/// mailbox `conversations/` directories, applying each mailbox's
+/// `[mailboxes.NAME.redact]` rules to its copy. A copy of the same thread
+/// already in the mailbox (possibly written by another owner) is merged
+/// with, not overwritten. A copy the merge would not change is left alone,
+/// so re-running leaves no churn in mailbox repos.
 pub fn run() -> Result<()> {
-    let routes = build_label_routes("");
+    let routes = build_label_routes("")?;
     if routes.is_empty() {
-        println!("No routing rules configured in .corky.toml");
+        crate::progress!("No routing rules configured in .corky.toml");
         return Ok(());
     }
 
@@ -26,18 +34,18 @@ pub fn run() -> Result<()> {
         );
     }
 
+    let redactors = Redactors::load()?;
+    let sync_config = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    let root = MarkdownStore::with_config(&conv_dir, sync_config.clone());
+
     let mut copied = 0u32;
+    let mut unchanged = 0u32;
     let mut skipped = 0u32;
 
-    for entry in std::fs::read_dir(&conv_dir)? {
-        let entry = entry?;
-        let path = entry.path();
-        if path.extension().and_then(|e| e.to_str()) != Some("md") {
-            continue;
-        }
-
-        let text = std::fs::read_to_string(&path)?;
-        let thread = match parse_thread_markdown(&text) {
+    for stem in root.keys()? {
+        let thread = match root.load(&stem)? {
             Some(t) => t,
             None => {
                 skipped += 1;
@@ -45,31 +53,69 @@ pub fn run() -> Result<()> {
             }
         };
 
-        let filename = match path.file_name() {
-            Some(f) => f,
-            None => continue,
-        };
-
         for label in &thread.labels {
             if let Some(dest_dirs) = routes.get(label) {
                 for dest_dir in dest_dirs {
-                    std::fs::create_dir_all(dest_dir)?;
-                    let dest = dest_dir.join(filename);
-                    std::fs::copy(&path, &dest)?;
-                    println!(
-                        "  {} -> {}",
-                        filename.to_string_lossy(),
-                        dest_dir.display()
-                    );
-                    copied += 1;
+                    let dest = MarkdownStore::with_config(dest_dir, sync_config.clone());
+                    let redactor = redactors.for_dir(dest_dir);
+                    if route_thread(&thread, &stem, &dest, redactor, &sync_config)? {
+                        copied += 1;
+                    } else {
+                        unchanged += 1;
+                    }
                 }
             }
         }
     }
 
     if skipped > 0 {
-        println!("Skipped {} unparseable file(s)", skipped);
+        crate::progress!("Skipped {} unparseable file(s)", skipped);
     }
-    println!("Routing complete: {} file(s) copied", copied);
+    crate::progress!(
+        "Routing complete: {} file(s) copied, {} unchanged",
+        copied,
+        unchanged
+    );
     Ok(())
 }
+
+/// Copy `thread` (stored as `stem` in the root) into `dest`, redacted by
+/// `redactor`, merging with the copy already there. Returns false, writing
+/// nothing, when the merged copy renders the same as the existing one.
+fn route_thread(
+    thread: &Thread,
+    stem: &str,
+    dest: &dyn ConversationStore,
+    redactor: Option<&Redactor>,
+    sync_config: &SyncConfig,
+) -> Result<bool> {
+    let routed = match redactor {
+        Some(r) => r.redact_thread(thread),
+        None => thread.clone(),
+    };
+    let (key, routed) = match dest.find(&thread.id) {
+        Some(key) => {
+            let merged = match dest.load(&key)? {
+                Some(theirs) => {
+                    let merged = merge_threads(&theirs, &routed);
+                    let render = |t: &Thread| thread_to_markdown_with(t, sync_config);
+                    if render(&merged) == render(&theirs) {
+                        return Ok(false);
+                    }
+                    merged
+                }
+                None => routed,
+            };
+            (key, merged)
+        }
+        None => (dest.new_key(stem), routed),
+    };
+    dest.save(&key, &routed)?;
+    crate::progress!(
+        "  {}.md -> {}/{}",
+        stem,
+        dest.location(),
+        dest.display_name(&key)
+    );
+    Ok(true)
+}
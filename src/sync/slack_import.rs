@@ -107,7 +107,7 @@ pub fn run(zip_path: &Path, label: &str, out_dir: &Path, account_name: &str) ->
     // 3. Discover channel directories from file paths
     let channel_dirs = discover_channel_dirs(&mut archive, &channels);
 
-    println!(
+    crate::progress!(
         "Slack import: {} users, {} channels",
         users.len(),
         channel_dirs.len()
@@ -125,7 +125,7 @@ pub fn run(zip_path: &Path, label: &str, out_dir: &Path, account_name: &str) ->
             continue;
         }
 
-        println!("  #{}: {} messages", channel_name, messages.len());
+        crate::progress!("  #{}: {} messages", channel_name, messages.len());
 
         // Group by thread_ts (or own ts for standalone messages)
         let threads = group_into_threads(&messages);
@@ -148,11 +148,10 @@ pub fn run(zip_path: &Path, label: &str, out_dir: &Path, account_name: &str) ->
                     id: msg.ts.clone(),
                     thread_id: thread_key.clone(),
                     from,
-                    to: String::new(),
-                    cc: String::new(),
                     date,
                     subject: subject.clone(),
                     body,
+                    ..Default::default()
                 };
 
                 merge_message_to_file(out_dir, &label_name, account_name, &message, &thread_key)?;
@@ -160,7 +159,7 @@ pub fn run(zip_path: &Path, label: &str, out_dir: &Path, account_name: &str) ->
         }
     }
 
-    println!("Slack import complete.");
+    crate::progress!("Slack import complete.");
     Ok(())
 }
 
@@ -124,7 +124,7 @@ fn normalize_phone(raw: &str) -> String {
 
 /// Import SMS messages from an SMS Backup & Restore XML file.
 pub fn run(path: &Path, label: &str, out_dir: &Path, account_name: &str) -> Result<()> {
-    println!("SMS import: {}", path.display());
+    crate::progress!("SMS import: {}", path.display());
 
     let data =
         std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
@@ -166,13 +166,13 @@ pub fn run(path: &Path, label: &str, out_dir: &Path, account_name: &str) -> Resu
                     thread_id: format!("sms:{}", phone),
                     from,
                     to: if is_sent { sms.address.clone() } else { String::new() },
-                    cc: String::new(),
                     date: ms_to_rfc2822(&sms.date),
                     subject: contact_names
                         .get(&phone)
                         .cloned()
                         .unwrap_or_else(|| sms.address.clone()),
                     body: sms.body.clone(),
+                    ..Default::default()
                 };
 
                 threads.entry(phone).or_default().push(message);
@@ -241,13 +241,13 @@ pub fn run(path: &Path, label: &str, out_dir: &Path, account_name: &str) -> Resu
                     } else {
                         String::new()
                     },
-                    cc: String::new(),
                     date: ms_to_rfc2822(&mms.date),
                     subject: contact_names
                         .get(&phone)
                         .cloned()
                         .unwrap_or_else(|| phone.clone()),
                     body,
+                    ..Default::default()
                 };
 
                 threads.entry(phone).or_default().push(message);
@@ -271,10 +271,10 @@ pub fn run(path: &Path, label: &str, out_dir: &Path, account_name: &str) -> Resu
             total += 1;
         }
 
-        println!("  {} ({}) — {} message(s)", display_name, phone, messages.len());
+        crate::progress!("  {} ({}) — {} message(s)", display_name, phone, messages.len());
     }
 
-    println!("SMS import complete: {} message(s) total.", total);
+    crate::progress!("SMS import complete: {} message(s) total.", total);
     Ok(())
 }
 
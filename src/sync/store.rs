@@ -0,0 +1,250 @@
+//! Conversation storage behind a trait.
+//!
+//! [`ConversationStore`] is what readers and writers of conversations go
+//! through: load and save a thread by key, list keys, and find a thread by
+//! its Thread ID. [`MarkdownStore`] (one `.md` file per conversation, split
+//! into parts per `[sync]`, §3.1) is the default and only built-in backend;
+//! another one (SQLite, an object store) implements the trait and the
+//! manifest, `sync routes`, and `unanswered` work on it unchanged.
+//! [`open_store`] reads through the SQLite mirror (`mirror::MirrorStore`)
+//! when `[sync] mirror = "sqlite"` and the `sqlite` feature is built.
+
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+use super::imap_sync::{find_thread_by_key, set_mtime, unique_slug};
+use super::markdown::{is_part_file, read_thread, thread_files, write_thread};
+use super::types::Thread;
+use crate::config::corky_config::{self, SyncConfig};
+use crate::util::subject_prefix_regex;
+
+/// A place conversations are kept. Keys are backend-specific, stable
+/// names for one conversation (for [`MarkdownStore`], the file path
+/// relative to the directory, without `.md`).
+pub trait ConversationStore {
+    /// Where the store lives, for messages.
+    fn location(&self) -> String;
+
+    /// Every conversation key, sorted.
+    fn keys(&self) -> Result<Vec<String>>;
+
+    /// The conversation at `key`; None if it is missing or unreadable.
+    fn load(&self, key: &str) -> Result<Option<Thread>>;
+
+    /// Create or replace the conversation at `key`.
+    fn save(&self, key: &str, thread: &Thread) -> Result<()>;
+
+    /// The key of the conversation with Thread ID `thread_id`.
+    fn find(&self, thread_id: &str) -> Option<String>;
+
+    /// An unused key for a new conversation, based on `stem`.
+    fn new_key(&self, stem: &str) -> String;
+
+    /// How many stored pieces the conversation spans (split parts).
+    fn parts(&self, _key: &str) -> usize {
+        1
+    }
+
+    /// The name a user sees for `key` (a filename, for files).
+    fn display_name(&self, key: &str) -> String {
+        key.to_string()
+    }
+
+    /// Every conversation that loads, with its key, in key order.
+    fn threads(&self) -> Result<Vec<(String, Thread)>> {
+        let mut threads = Vec::new();
+        for key in self.keys()? {
+            if let Some(thread) = self.load(&key)? {
+                threads.push((key, thread));
+            }
+        }
+        Ok(threads)
+    }
+}
+
+/// The store for `dir` (with subdirectories if `recursive`): the Markdown
+/// files, read through the SQLite mirror when it is enabled. A mirror that
+/// fails to open is skipped with a warning.
+pub fn open_store(dir: &Path, recursive: bool) -> Box<dyn ConversationStore> {
+    let sync = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    open_store_with(dir, recursive, &sync)
+}
+
+/// [`open_store`] with an already-loaded `[sync]` section, for callers
+/// that open a store per message.
+pub fn open_store_with(
+    dir: &Path,
+    recursive: bool,
+    sync: &SyncConfig,
+) -> Box<dyn ConversationStore> {
+    #[cfg(feature = "sqlite")]
+    let mirror = super::mirror::enabled(sync);
+    let mut files = MarkdownStore::with_config(dir, sync.clone());
+    if recursive {
+        files = files.recursive();
+    }
+    #[cfg(feature = "sqlite")]
+    if mirror {
+        match super::mirror::Mirror::open_default() {
+            Ok(m) => return Box::new(super::mirror::MirrorStore::new(m, files)),
+            Err(e) => {
+                let msg = format!("SQLite mirror unavailable, reading files: {}", e);
+                eprintln!("{}", crate::output::warning(&msg));
+            }
+        }
+    }
+    Box::new(files)
+}
+
+/// Conversations as Markdown files in one directory (the default store).
+pub struct MarkdownStore {
+    dir: PathBuf,
+    sync: SyncConfig,
+    recursive: bool,
+}
+
+impl MarkdownStore {
+    /// The store in `dir`, written with `[sync]` from .corky.toml.
+    pub fn new(dir: &Path) -> Self {
+        let sync = corky_config::try_load_config(None)
+            .and_then(|c| c.sync)
+            .unwrap_or_default();
+        Self::with_config(dir, sync)
+    }
+
+    pub fn with_config(dir: &Path, sync: SyncConfig) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+            sync,
+            recursive: false,
+        }
+    }
+
+    /// Also list conversations in subdirectories.
+    pub fn recursive(mut self) -> Self {
+        self.recursive = true;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The main file of the conversation at `key`.
+    pub fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{}.md", key))
+    }
+
+    fn key_of(&self, path: &Path) -> String {
+        let rel = path.strip_prefix(&self.dir).unwrap_or(path);
+        rel.with_extension("").to_string_lossy().to_string()
+    }
+
+    fn collect(&self, dir: &Path, out: &mut Vec<PathBuf>) -> Result<()> {
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if self.recursive {
+                    self.collect(&path, out)?;
+                }
+            } else if path.extension().and_then(|e| e.to_str()) == Some("md")
+                && !is_part_file(&path)
+            {
+                out.push(path);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ConversationStore for MarkdownStore {
+    fn location(&self) -> String {
+        self.dir.display().to_string()
+    }
+
+    fn keys(&self) -> Result<Vec<String>> {
+        let mut files = Vec::new();
+        if self.dir.is_dir() {
+            self.collect(&self.dir, &mut files)?;
+        }
+        files.sort();
+        Ok(files.iter().map(|p| self.key_of(p)).collect())
+    }
+
+    fn load(&self, key: &str) -> Result<Option<Thread>> {
+        let path = self.path(key);
+        if !path.is_file() {
+            return Ok(None);
+        }
+        read_thread(&path)
+    }
+
+    fn save(&self, key: &str, thread: &Thread) -> Result<()> {
+        let path = self.path(key);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        for written in write_thread(&path, thread, &self.sync)? {
+            let _ = set_mtime(&written, &thread.last_date);
+        }
+        Ok(())
+    }
+
+    fn find(&self, thread_id: &str) -> Option<String> {
+        let prefix_re = subject_prefix_regex(&self.sync.strip_prefixes);
+        find_thread_by_key(&self.dir, thread_id, &prefix_re).map(|p| self.key_of(&p))
+    }
+
+    fn new_key(&self, stem: &str) -> String {
+        unique_slug(&self.dir, stem)
+    }
+
+    fn parts(&self, key: &str) -> usize {
+        thread_files(&self.path(key)).len()
+    }
+
+    fn display_name(&self, key: &str) -> String {
+        format!("{}.md", key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_markdown_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = MarkdownStore::with_config(dir.path(), SyncConfig::default());
+        assert!(store.keys().unwrap().is_empty());
+
+        let thread = Thread {
+            id: "plans".to_string(),
+            subject: "Plans".to_string(),
+            labels: vec!["inbox".to_string()],
+            ..Default::default()
+        };
+        let key = store.new_key("plans");
+        store.save(&key, &thread).unwrap();
+        assert_eq!(store.new_key("plans"), "plans-2");
+        assert_eq!(store.keys().unwrap(), vec!["plans"]);
+        assert_eq!(store.find("plans").as_deref(), Some("plans"));
+        assert_eq!(store.find("other"), None);
+        assert_eq!(store.load("plans").unwrap().unwrap().labels, vec!["inbox"]);
+        assert_eq!(store.display_name("plans"), "plans.md");
+        assert!(store.load("missing").unwrap().is_none());
+
+        std::fs::create_dir(dir.path().join("old")).unwrap();
+        std::fs::write(
+            dir.path().join("old/lunch.md"),
+            "# Lunch\n\n**Thread ID**: lunch\n",
+        )
+        .unwrap();
+        assert_eq!(store.keys().unwrap(), vec!["plans"]);
+        let nested = MarkdownStore::with_config(dir.path(), SyncConfig::default()).recursive();
+        assert_eq!(nested.keys().unwrap(), vec!["old/lunch", "plans"]);
+        assert_eq!(nested.load("old/lunch").unwrap().unwrap().id, "lunch");
+    }
+}
@@ -164,11 +164,10 @@ fn import_chat(
             id: msg.id.to_string(),
             thread_id: thread_id.clone(),
             from,
-            to: String::new(),
-            cc: String::new(),
             date,
             subject: subject.clone(),
             body,
+            ..Default::default()
         };
 
         merge_message_to_file(out_dir, label, account_name, &message, &thread_id)?;
@@ -192,7 +191,7 @@ fn import_file(
 
     // Multi-chat export (has `chats.list`)
     if let Some(chats) = export.chats {
-        println!("Found {} chat(s) in {}", chats.list.len(), path.display());
+        crate::progress!("Found {} chat(s) in {}", chats.list.len(), path.display());
         for chat in &chats.list {
             let count = import_chat(
                 &chat.name,
@@ -203,7 +202,7 @@ fn import_file(
                 account_name,
             )?;
             if count > 0 {
-                println!("  {} — {} message(s)", chat.name, count);
+                crate::progress!("  {} — {} message(s)", chat.name, count);
             }
         }
         return Ok(());
@@ -214,7 +213,7 @@ fn import_file(
         (export.name, export.id, export.messages)
     {
         let count = import_chat(&name, id, &messages, label, out_dir, account_name)?;
-        println!("{} — {} message(s)", name, count);
+        crate::progress!("{} — {} message(s)", name, count);
         return Ok(());
     }
 
@@ -359,18 +358,17 @@ fn import_html_file(
             id: msg_id.clone(),
             thread_id: thread_id.clone(),
             from,
-            to: String::new(),
-            cc: String::new(),
             date,
             subject: subject.clone(),
             body,
+            ..Default::default()
         };
 
         merge_message_to_file(out_dir, label, account_name, &message, &thread_id)?;
         count += 1;
     }
 
-    println!("{} — {} message(s) (HTML)", chat_name, count);
+    crate::progress!("{} — {} message(s) (HTML)", chat_name, count);
     Ok(())
 }
 
@@ -395,7 +393,7 @@ fn html_decode(s: &str) -> String {
 ///
 /// `path` can be a JSON file, HTML file, or a directory containing export files.
 pub fn run(path: &Path, label: &str, out_dir: &Path, account_name: &str) -> Result<()> {
-    println!("Telegram import: {}", path.display());
+    crate::progress!("Telegram import: {}", path.display());
 
     if path.is_dir() {
         let mut found = false;
@@ -430,7 +428,7 @@ pub fn run(path: &Path, label: &str, out_dir: &Path, account_name: &str) -> Resu
         }
     }
 
-    println!("Telegram import complete.");
+    crate::progress!("Telegram import complete.");
     Ok(())
 }
 
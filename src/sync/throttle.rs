@@ -0,0 +1,104 @@
+//! Backing off when the server throttles a sync.
+//!
+//! Gmail answers large syncs with `NO [THROTTLED]` / "Account exceeded
+//! command or bandwidth limits", or simply drops the connection. Sync treats
+//! those as temporary: it waits (doubling the delay each time), reconnects,
+//! and resumes the label from the last checkpointed UID.
+
+use std::time::Duration;
+
+/// Longest single wait, however many attempts have failed.
+const MAX_DELAY_SECS: u64 = 15 * 60;
+
+/// Server response fragments that mean "slow down", matched case-insensitively.
+const THROTTLE_MARKERS: &[&str] = &[
+    "throttled",
+    "bandwidth limits",
+    "too many simultaneous connections",
+    "[unavailable]",
+    "try again later",
+];
+
+/// Whether a server response or error message reads as throttling.
+pub fn is_throttle_text(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    THROTTLE_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+/// Whether an I/O error is the connection going away mid-sync.
+pub fn is_disconnect(err: &std::io::Error) -> bool {
+    use std::io::ErrorKind::*;
+    matches!(
+        err.kind(),
+        ConnectionReset | ConnectionAborted | BrokenPipe | UnexpectedEof | TimedOut
+    )
+}
+
+/// Whether a sync error is worth backing off and retrying: a throttle
+/// response or a dropped connection.
+#[cfg(feature = "net")]
+pub fn is_throttle(err: &anyhow::Error) -> bool {
+    for cause in err.chain() {
+        if let Some(e) = cause.downcast_ref::<imap::Error>() {
+            match e {
+                imap::Error::ConnectionLost => return true,
+                imap::Error::Io(io) if is_disconnect(io) => return true,
+                imap::Error::No(text) | imap::Error::Bad(text) if is_throttle_text(text) => {
+                    return true;
+                }
+                _ => {}
+            }
+        }
+        if let Some(io) = cause.downcast_ref::<std::io::Error>() {
+            if is_disconnect(io) {
+                return true;
+            }
+        }
+    }
+    is_throttle_text(&format!("{:#}", err))
+}
+
+/// Wait before retry number `attempt` (0-based): `base_secs` doubled per
+/// attempt, capped at 15 minutes.
+pub fn delay(base_secs: u64, attempt: u32) -> Duration {
+    let factor = 1u64.checked_shl(attempt).unwrap_or(u64::MAX);
+    Duration::from_secs(base_secs.saturating_mul(factor).min(MAX_DELAY_SECS))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_throttle_text() {
+        assert!(is_throttle_text("[THROTTLED] Please slow down"));
+        assert!(is_throttle_text(
+            "Account exceeded command or bandwidth limits. abc123"
+        ));
+        assert!(is_throttle_text(
+            "Too many simultaneous connections. (Failure)"
+        ));
+        assert!(!is_throttle_text(
+            "[AUTHENTICATIONFAILED] Invalid credentials"
+        ));
+        assert!(!is_throttle_text("Unknown command"));
+    }
+
+    #[test]
+    fn test_delay_doubles_and_caps() {
+        assert_eq!(delay(30, 0), Duration::from_secs(30));
+        assert_eq!(delay(30, 1), Duration::from_secs(60));
+        assert_eq!(delay(30, 3), Duration::from_secs(240));
+        assert_eq!(delay(30, 10), Duration::from_secs(MAX_DELAY_SECS));
+        assert_eq!(delay(30, 200), Duration::from_secs(MAX_DELAY_SECS));
+        assert_eq!(delay(0, 4), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_disconnect_kinds() {
+        let reset = std::io::Error::new(std::io::ErrorKind::ConnectionReset, "reset");
+        assert!(is_disconnect(&reset));
+        let denied = std::io::Error::new(std::io::ErrorKind::PermissionDenied, "no");
+        assert!(!is_disconnect(&denied));
+    }
+}
@@ -0,0 +1,128 @@
+//! rustls transport for IMAP (`--features tls-rustls`).
+
+use anyhow::{bail, Result};
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::CryptoProvider;
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{
+    ClientConfig, ClientConnection, DigitallySignedStruct, RootCertStore, SignatureScheme,
+    StreamOwned,
+};
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+pub type TlsStream = StreamOwned<ClientConnection, TcpStream>;
+
+/// Skips certificate validation (still checks handshake signatures).
+/// Mirrors native-tls `danger_accept_invalid_certs` for bridges and loopback.
+#[derive(Debug)]
+struct AcceptAnyCert(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for AcceptAnyCert {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
+
+fn client_config(accept_invalid: bool) -> Result<Arc<ClientConfig>> {
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(Arc::clone(&provider))
+        .with_safe_default_protocol_versions()?;
+    let config = if accept_invalid {
+        builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(AcceptAnyCert(provider)))
+            .with_no_client_auth()
+    } else {
+        let roots = RootCertStore {
+            roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+        };
+        builder.with_root_certificates(roots).with_no_client_auth()
+    };
+    Ok(Arc::new(config))
+}
+
+/// Send STARTTLS on a fresh connection and return the socket, ready for the handshake.
+fn starttls_upgrade(tcp: TcpStream) -> Result<TcpStream> {
+    let mut reader = BufReader::new(tcp);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    if !line.starts_with("* OK") {
+        bail!("Unexpected IMAP greeting: {}", line.trim());
+    }
+    reader.get_mut().write_all(b"a0 STARTTLS\r\n")?;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line)? == 0 {
+            bail!("Connection closed during STARTTLS");
+        }
+        if let Some(status) = line.strip_prefix("a0 ") {
+            if status.starts_with("OK") {
+                break;
+            }
+            bail!("STARTTLS refused: {}", status.trim());
+        }
+    }
+    Ok(reader.into_inner())
+}
+
+/// Connect over implicit TLS (or STARTTLS) and read the server greeting.
+pub fn connect(
+    host: &str,
+    port: u16,
+    starttls: bool,
+    accept_invalid: bool,
+) -> Result<imap::Client<TlsStream>> {
+    let mut tcp = TcpStream::connect((host, port))?;
+    if starttls {
+        tcp = starttls_upgrade(tcp)?;
+    }
+    let server_name = ServerName::try_from(host.to_string())?;
+    let conn = ClientConnection::new(client_config(accept_invalid)?, server_name)?;
+    let mut client = imap::Client::new(StreamOwned::new(conn, tcp));
+    // After STARTTLS the greeting was already consumed on the plain socket.
+    if !starttls {
+        client.read_greeting()?;
+    }
+    Ok(client)
+}
@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Message {
     pub id: String,
     pub thread_id: String,
@@ -12,11 +12,31 @@ pub struct Message {
     pub to: String,
     #[serde(default)]
     pub cc: String,
+    /// RFC 5322 Message-ID header, used to dedup across accounts.
+    #[serde(default)]
+    pub message_id: String,
+    /// Accounts this message was synced from.
+    #[serde(default)]
+    pub received_by: Vec<String>,
+    /// Server copies this message was synced from, one per account and label.
+    #[serde(default)]
+    pub sources: Vec<MessageSource>,
+    /// List-Unsubscribe header (mailing lists / newsletters).
+    #[serde(default)]
+    pub list_unsubscribe: String,
     pub date: String,
     pub subject: String,
     pub body: String,
 }
 
+/// Where a message lives on the server: its UID in one account's label.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MessageSource {
+    pub account: String,
+    pub label: String,
+    pub uid: u32,
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct Thread {
     pub id: String,
@@ -25,6 +45,15 @@ pub struct Thread {
     pub labels: Vec<String>,
     #[serde(default)]
     pub accounts: Vec<String>,
+    /// Unique senders/recipients across all messages (`Name <email>`).
+    #[serde(default)]
+    pub participants: Vec<String>,
+    /// Derived thread tags (e.g. `newsletter`).
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Free-form thread notes (e.g. actions taken), one bullet each.
+    #[serde(default)]
+    pub notes: Vec<String>,
     #[serde(default)]
     pub messages: Vec<Message>,
     #[serde(default)]
@@ -41,6 +70,9 @@ pub struct LabelState {
 pub struct AccountSyncState {
     #[serde(default)]
     pub labels: HashMap<String, LabelState>,
+    /// Provenance: thread ID -> label -> UIDs synced into that thread.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub threads: HashMap<String, HashMap<String, Vec<u32>>>,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -63,3 +95,70 @@ pub fn load_state(data: &[u8]) -> anyhow::Result<SyncState> {
     let state: SyncState = serde_json::from_slice(data)?;
     Ok(state)
 }
+
+/// Top-level key holding the checksum of the rest of the state file.
+const CHECKSUM_KEY: &str = "checksum";
+
+/// FNV-1a hash of `data`, returned as a 16-char hex string.
+fn checksum(data: &[u8]) -> String {
+    const FNV_OFFSET: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x00000100000001B3;
+    let mut hash = FNV_OFFSET;
+    for byte in data {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    format!("{:016x}", hash)
+}
+
+/// Serialize `state` for the state file, with a checksum of its contents.
+pub fn encode_state(state: &SyncState) -> anyhow::Result<Vec<u8>> {
+    let mut value = serde_json::to_value(state)?;
+    let sum = checksum(&serde_json::to_vec(&value)?);
+    if let Some(map) = value.as_object_mut() {
+        map.insert(CHECKSUM_KEY.to_string(), sum.into());
+    }
+    Ok(serde_json::to_vec(&value)?)
+}
+
+/// Parse a state file written by [`encode_state`], rejecting it if the
+/// checksum doesn't match. Files from before checksums have none and are
+/// accepted as they are.
+pub fn decode_state(data: &[u8]) -> anyhow::Result<SyncState> {
+    let mut value: serde_json::Value = serde_json::from_slice(data)?;
+    let stored = value.as_object_mut().and_then(|m| m.remove(CHECKSUM_KEY));
+    if let Some(stored) = stored {
+        let actual = checksum(&serde_json::to_vec(&value)?);
+        if stored.as_str() != Some(actual.as_str()) {
+            anyhow::bail!("checksum mismatch (stored {}, computed {})", stored, actual);
+        }
+    }
+    Ok(serde_json::from_value(value)?)
+}
+
+/// Messages and threads for unit tests.
+#[cfg(test)]
+pub mod fixtures {
+    use super::{Message, Thread};
+
+    /// A message from `from` sent at `date`.
+    pub fn message(from: &str, date: &str, body: &str) -> Message {
+        Message {
+            from: from.to_string(),
+            date: date.to_string(),
+            body: body.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Thread `id` holding `messages`, last updated at the last one's date.
+    pub fn thread(id: &str, subject: &str, messages: Vec<Message>) -> Thread {
+        Thread {
+            id: id.to_string(),
+            subject: subject.to_string(),
+            last_date: messages.last().map(|m| m.date.clone()).unwrap_or_default(),
+            messages,
+            ..Default::default()
+        }
+    }
+}
@@ -0,0 +1,734 @@
+//! In-process mock IMAP server for integration tests (`--features testserver`).
+//!
+//! Speaks just enough IMAP4rev1 over implicit TLS to exercise `sync_account`
+//! and `push_to_drafts`: CAPABILITY, LOGIN, AUTHENTICATE XOAUTH2 (the
+//! password is the valid bearer token), SELECT/EXAMINE, LIST, STATUS,
+//! UID SEARCH, UID FETCH (ENVELOPE carries only the Message-ID), APPEND, STORE,
+//! EXPUNGE, NOOP, and LOGOUT. Uses a bundled self-signed certificate for `localhost` /
+//! `127.0.0.1`, which the IMAP clients already accept for loopback hosts.
+//!
+//! ```ignore
+//! let server = MockImapServer::start("me@example.com", "secret")?;
+//! server.add_mailbox("INBOX", 1);
+//! server.add_message("INBOX", raw_bytes);
+//! // connect to 127.0.0.1:server.port() ...
+//! ```
+
+use anyhow::Result;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+const CERT_PEM: &[u8] = include_bytes!("localhost.crt");
+const KEY_PEM: &[u8] = include_bytes!("localhost.key");
+
+/// A stored message.
+#[derive(Debug, Clone)]
+pub struct MockMessage {
+    pub uid: u32,
+    pub flags: Vec<String>,
+    pub raw: Vec<u8>,
+}
+
+#[derive(Debug, Default)]
+struct MockMailbox {
+    uidvalidity: u32,
+    uidnext: u32,
+    messages: Vec<MockMessage>,
+    /// SPECIAL-USE attribute listed by LIST (e.g. `\Drafts`).
+    special_use: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct ServerState {
+    user: String,
+    password: String,
+    mailboxes: BTreeMap<String, MockMailbox>,
+    /// Every command line received, for assertions (LOGIN passwords redacted).
+    log: Vec<String>,
+}
+
+/// Handle to a running mock server. The listener thread lives until the process exits.
+#[derive(Clone)]
+pub struct MockImapServer {
+    port: u16,
+    state: Arc<Mutex<ServerState>>,
+}
+
+impl MockImapServer {
+    /// Bind to an ephemeral loopback port and start accepting connections.
+    pub fn start(user: &str, password: &str) -> Result<Self> {
+        let identity = native_tls::Identity::from_pkcs8(CERT_PEM, KEY_PEM)?;
+        let acceptor = Arc::new(native_tls::TlsAcceptor::new(identity)?);
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let port = listener.local_addr()?.port();
+        let state = Arc::new(Mutex::new(ServerState {
+            user: user.to_string(),
+            password: password.to_string(),
+            ..Default::default()
+        }));
+
+        let thread_state = Arc::clone(&state);
+        std::thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let acceptor = Arc::clone(&acceptor);
+                let state = Arc::clone(&thread_state);
+                std::thread::spawn(move || {
+                    if let Ok(tls) = acceptor.accept(stream) {
+                        let _ = Connection::new(tls, state).serve();
+                    }
+                });
+            }
+        });
+
+        Ok(Self { port, state })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Create (or reset) a mailbox with the given UIDVALIDITY.
+    pub fn add_mailbox(&self, name: &str, uidvalidity: u32) {
+        let mut state = self.state.lock().unwrap();
+        state.mailboxes.insert(
+            name.to_string(),
+            MockMailbox {
+                uidvalidity,
+                uidnext: 1,
+                messages: Vec::new(),
+                special_use: None,
+            },
+        );
+    }
+
+    /// Mark a mailbox with a SPECIAL-USE attribute (e.g. `\Drafts`) in LIST.
+    pub fn set_special_use(&self, name: &str, attribute: &str) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(mb) = state.mailboxes.get_mut(name) {
+            mb.special_use = Some(attribute.to_string());
+        }
+    }
+
+    /// Append a raw RFC822 message to a mailbox (created if missing). Returns its UID.
+    pub fn add_message(&self, mailbox: &str, raw: &[u8]) -> u32 {
+        let mut state = self.state.lock().unwrap();
+        let mb = state
+            .mailboxes
+            .entry(mailbox.to_string())
+            .or_insert_with(|| MockMailbox {
+                uidvalidity: 1,
+                uidnext: 1,
+                messages: Vec::new(),
+                special_use: None,
+            });
+        store_message(mb, raw.to_vec(), Vec::new())
+    }
+
+    /// Remove a message, as if expunged by another client.
+    pub fn remove_message(&self, mailbox: &str, uid: u32) {
+        let mut state = self.state.lock().unwrap();
+        if let Some(mb) = state.mailboxes.get_mut(mailbox) {
+            mb.messages.retain(|m| m.uid != uid);
+        }
+    }
+
+    /// Messages currently in a mailbox (empty if it doesn't exist).
+    pub fn messages(&self, mailbox: &str) -> Vec<MockMessage> {
+        let state = self.state.lock().unwrap();
+        state
+            .mailboxes
+            .get(mailbox)
+            .map(|mb| mb.messages.clone())
+            .unwrap_or_default()
+    }
+
+    /// Command lines received so far, across all connections.
+    pub fn log(&self) -> Vec<String> {
+        self.state.lock().unwrap().log.clone()
+    }
+}
+
+fn store_message(mb: &mut MockMailbox, raw: Vec<u8>, flags: Vec<String>) -> u32 {
+    if mb.uidnext == 0 {
+        mb.uidnext = 1;
+    }
+    let uid = mb.uidnext;
+    mb.uidnext += 1;
+    mb.messages.push(MockMessage { uid, flags, raw });
+    uid
+}
+
+/// Split a command's arguments into atoms, quoted strings, and parenthesized lists.
+fn tokenize(s: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut chars = s.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            ' ' => {
+                chars.next();
+            }
+            '"' => {
+                chars.next();
+                let mut tok = String::new();
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => {
+                            if let Some(escaped) = chars.next() {
+                                tok.push(escaped);
+                            }
+                        }
+                        '"' => break,
+                        _ => tok.push(c),
+                    }
+                }
+                tokens.push(tok);
+            }
+            '(' => {
+                let mut depth = 0;
+                let mut tok = String::new();
+                for c in chars.by_ref() {
+                    tok.push(c);
+                    match c {
+                        '(' => depth += 1,
+                        ')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                tokens.push(tok);
+            }
+            _ => {
+                let mut tok = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c == ' ' {
+                        break;
+                    }
+                    tok.push(c);
+                    chars.next();
+                }
+                tokens.push(tok);
+            }
+        }
+    }
+    tokens
+}
+
+/// Decode standard base64, skipping padding and anything outside the alphabet.
+fn decode_base64(s: &str) -> Vec<u8> {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = Vec::new();
+    let (mut buf, mut bits) = (0u32, 0);
+    for c in s.bytes() {
+        let Some(v) = ALPHABET.iter().position(|a| *a == c) else {
+            continue;
+        };
+        buf = (buf << 6) | v as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buf >> bits) as u8);
+            buf &= (1 << bits) - 1;
+        }
+    }
+    out
+}
+
+/// Parse an IMAP sequence set (`1,3:5,7:*`) against the highest value `max`.
+fn parse_set(set: &str, max: u32) -> Vec<(u32, u32)> {
+    let value = |v: &str| if v == "*" { max } else { v.parse().unwrap_or(0) };
+    set.split(',')
+        .map(|part| match part.split_once(':') {
+            Some((a, b)) => {
+                let (a, b) = (value(a), value(b));
+                (a.min(b), a.max(b))
+            }
+            None => {
+                let v = value(part);
+                (v, v)
+            }
+        })
+        .collect()
+}
+
+fn in_set(ranges: &[(u32, u32)], v: u32) -> bool {
+    ranges.iter().any(|&(a, b)| v >= a && v <= b)
+}
+
+/// Split a raw message into its header block (including the blank line) and body.
+fn split_header(raw: &[u8]) -> (&[u8], &[u8]) {
+    let pos = raw
+        .windows(4)
+        .position(|w| w == b"\r\n\r\n")
+        .map(|p| p + 4)
+        .or_else(|| raw.windows(2).position(|w| w == b"\n\n").map(|p| p + 2))
+        .unwrap_or(raw.len());
+    raw.split_at(pos)
+}
+
+struct Connection<S: Read + Write> {
+    stream: BufReader<S>,
+    state: Arc<Mutex<ServerState>>,
+    authenticated: bool,
+    selected: Option<String>,
+}
+
+impl<S: Read + Write> Connection<S> {
+    fn new(stream: S, state: Arc<Mutex<ServerState>>) -> Self {
+        Self {
+            stream: BufReader::new(stream),
+            state,
+            authenticated: false,
+            selected: None,
+        }
+    }
+
+    fn send(&mut self, data: &[u8]) -> std::io::Result<()> {
+        let stream = self.stream.get_mut();
+        stream.write_all(data)?;
+        stream.flush()
+    }
+
+    fn line(&mut self, s: &str) -> std::io::Result<()> {
+        self.send(format!("{}\r\n", s).as_bytes())
+    }
+
+    fn serve(mut self) -> std::io::Result<()> {
+        self.line("* OK [CAPABILITY IMAP4rev1] corky mock IMAP ready")?;
+        loop {
+            let mut raw_line = String::new();
+            if self.stream.read_line(&mut raw_line)? == 0 {
+                return Ok(());
+            }
+            let line = raw_line.trim_end_matches(['\r', '\n']).to_string();
+            let (tag, rest) = line.split_once(' ').unwrap_or((line.as_str(), ""));
+            let (cmd, args) = rest.split_once(' ').unwrap_or((rest, ""));
+            let cmd = cmd.to_uppercase();
+
+            {
+                let mut state = self.state.lock().unwrap();
+                let logged = if cmd == "LOGIN" {
+                    format!("{} LOGIN ***", tag)
+                } else {
+                    line.clone()
+                };
+                state.log.push(logged);
+            }
+
+            if !self.dispatch(tag, &cmd, args)? {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Handle one command. Returns false when the connection should close.
+    fn dispatch(&mut self, tag: &str, cmd: &str, args: &str) -> std::io::Result<bool> {
+        match cmd {
+            "CAPABILITY" => {
+                self.line("* CAPABILITY IMAP4rev1 UIDPLUS")?;
+                self.line(&format!("{} OK CAPABILITY completed", tag))?;
+            }
+            "NOOP" => self.line(&format!("{} OK NOOP completed", tag))?,
+            "LOGOUT" => {
+                self.line("* BYE corky mock IMAP signing off")?;
+                self.line(&format!("{} OK LOGOUT completed", tag))?;
+                return Ok(false);
+            }
+            "LOGIN" => {
+                let tokens = tokenize(args);
+                let ok = {
+                    let state = self.state.lock().unwrap();
+                    tokens.len() == 2 && tokens[0] == state.user && tokens[1] == state.password
+                };
+                if ok {
+                    self.authenticated = true;
+                    self.line(&format!("{} OK LOGIN completed", tag))?;
+                } else {
+                    self.line(&format!("{} NO [AUTHENTICATIONFAILED] Invalid credentials", tag))?;
+                }
+            }
+            "AUTHENTICATE" => self.authenticate(tag, args)?,
+            _ if !self.authenticated => {
+                self.line(&format!("{} NO Not authenticated", tag))?;
+            }
+            "SELECT" | "EXAMINE" => self.select(tag, cmd, args)?,
+            "LIST" => {
+                let names: Vec<(String, String)> = {
+                    let state = self.state.lock().unwrap();
+                    state
+                        .mailboxes
+                        .iter()
+                        .map(|(name, mb)| {
+                            let special = mb
+                                .special_use
+                                .as_ref()
+                                .map(|a| format!(" {}", a))
+                                .unwrap_or_default();
+                            (name.clone(), special)
+                        })
+                        .collect()
+                };
+                for (name, special) in names {
+                    self.line(&format!(
+                        "* LIST (\\HasNoChildren{}) \"/\" \"{}\"",
+                        special, name
+                    ))?;
+                }
+                self.line(&format!("{} OK LIST completed", tag))?;
+            }
+            "APPEND" => self.append(tag, args)?,
+            "STATUS" => {
+                let name = tokenize(args).into_iter().next().unwrap_or_default();
+                let counts = {
+                    let state = self.state.lock().unwrap();
+                    state.mailboxes.get(&name).map(|mb| {
+                        let unseen = mb
+                            .messages
+                            .iter()
+                            .filter(|m| !m.flags.iter().any(|f| f == "\\Seen"))
+                            .count();
+                        (mb.messages.len(), unseen)
+                    })
+                };
+                match counts {
+                    Some((messages, unseen)) => {
+                        self.line(&format!(
+                            "* STATUS \"{}\" (MESSAGES {} UNSEEN {})",
+                            name, messages, unseen
+                        ))?;
+                        self.line(&format!("{} OK STATUS completed", tag))?;
+                    }
+                    None => self.line(&format!("{} NO Mailbox does not exist", tag))?,
+                }
+            }
+            "UID" => {
+                let (sub, sub_args) = args.split_once(' ').unwrap_or((args, ""));
+                let sub = sub.to_uppercase();
+                self.with_selected(tag, |conn, mailbox| match sub.as_str() {
+                    "SEARCH" => conn.search(tag, mailbox, sub_args, true),
+                    "FETCH" => conn.fetch(tag, mailbox, sub_args, true),
+                    "STORE" => conn.store(tag, mailbox, sub_args, true),
+                    _ => conn.line(&format!("{} BAD Unsupported UID command", tag)),
+                })?;
+            }
+            "SEARCH" => self.with_selected(tag, |conn, mb| conn.search(tag, mb, args, false))?,
+            "FETCH" => self.with_selected(tag, |conn, mb| conn.fetch(tag, mb, args, false))?,
+            "STORE" => self.with_selected(tag, |conn, mb| conn.store(tag, mb, args, false))?,
+            "EXPUNGE" | "CLOSE" => {
+                self.with_selected(tag, |conn, mailbox| {
+                    let expunged: Vec<usize> = {
+                        let mut state = conn.state.lock().unwrap();
+                        let mb = state.mailboxes.get_mut(mailbox).unwrap();
+                        let mut seqs = Vec::new();
+                        let mut seq = 1;
+                        mb.messages.retain(|m| {
+                            let deleted = m.flags.iter().any(|f| f == "\\Deleted");
+                            if deleted {
+                                seqs.push(seq);
+                            } else {
+                                seq += 1;
+                            }
+                            !deleted
+                        });
+                        seqs
+                    };
+                    if cmd == "EXPUNGE" {
+                        for seq in expunged {
+                            conn.line(&format!("* {} EXPUNGE", seq))?;
+                        }
+                    }
+                    conn.line(&format!("{} OK {} completed", tag, cmd))
+                })?;
+                if cmd == "CLOSE" {
+                    self.selected = None;
+                }
+            }
+            _ => self.line(&format!("{} BAD Unsupported command", tag))?,
+        }
+        Ok(true)
+    }
+
+    fn with_selected<F>(&mut self, tag: &str, f: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&mut Self, &str) -> std::io::Result<()>,
+    {
+        match self.selected.clone() {
+            Some(mailbox) => f(self, &mailbox),
+            None => self.line(&format!("{} BAD No mailbox selected", tag)),
+        }
+    }
+
+    fn select(&mut self, tag: &str, cmd: &str, args: &str) -> std::io::Result<()> {
+        let name = tokenize(args).into_iter().next().unwrap_or_default();
+        let info = {
+            let state = self.state.lock().unwrap();
+            state
+                .mailboxes
+                .get(&name)
+                .map(|mb| (mb.messages.len(), mb.uidvalidity, mb.uidnext))
+        };
+        let Some((exists, uidvalidity, uidnext)) = info else {
+            self.selected = None;
+            return self.line(&format!("{} NO Mailbox doesn't exist", tag));
+        };
+        self.selected = Some(name);
+        self.line("* FLAGS (\\Answered \\Flagged \\Deleted \\Seen \\Draft)")?;
+        self.line(&format!("* {} EXISTS", exists))?;
+        self.line("* 0 RECENT")?;
+        self.line(&format!("* OK [UIDVALIDITY {}] UIDs valid", uidvalidity))?;
+        self.line(&format!("* OK [UIDNEXT {}] Predicted next UID", uidnext))?;
+        let mode = if cmd == "EXAMINE" { "READ-ONLY" } else { "READ-WRITE" };
+        self.line(&format!("{} OK [{}] {} completed", tag, mode, cmd))
+    }
+
+    /// Supports `ALL`, `SINCE date` (matches everything), and `UID set` criteria.
+    fn search(&mut self, tag: &str, mailbox: &str, args: &str, by_uid: bool) -> std::io::Result<()> {
+        let tokens = tokenize(args);
+        let hits: Vec<u32> = {
+            let state = self.state.lock().unwrap();
+            let mb = &state.mailboxes[mailbox];
+            let max_uid = mb.messages.last().map(|m| m.uid).unwrap_or(0);
+            let uid_ranges = tokens
+                .iter()
+                .position(|t| t.eq_ignore_ascii_case("UID"))
+                .and_then(|i| tokens.get(i + 1))
+                .map(|set| parse_set(set, max_uid));
+            mb.messages
+                .iter()
+                .enumerate()
+                .filter(|(_, m)| uid_ranges.as_ref().map(|r| in_set(r, m.uid)).unwrap_or(true))
+                .map(|(i, m)| if by_uid { m.uid } else { i as u32 + 1 })
+                .collect()
+        };
+        let mut line = "* SEARCH".to_string();
+        for hit in hits {
+            line.push_str(&format!(" {}", hit));
+        }
+        self.line(&line)?;
+        self.line(&format!("{} OK SEARCH completed", tag))
+    }
+
+    fn fetch(&mut self, tag: &str, mailbox: &str, args: &str, by_uid: bool) -> std::io::Result<()> {
+        let (set, items) = args.split_once(' ').unwrap_or((args, ""));
+        let items = items.to_uppercase();
+        let wants_rfc822 = items
+            .split(|c: char| c == ' ' || c == '(' || c == ')')
+            .any(|i| i == "RFC822");
+        let selected: Vec<(usize, MockMessage)> = {
+            let state = self.state.lock().unwrap();
+            let mb = &state.mailboxes[mailbox];
+            let max = if by_uid {
+                mb.messages.last().map(|m| m.uid).unwrap_or(0)
+            } else {
+                mb.messages.len() as u32
+            };
+            let ranges = parse_set(set, max);
+            mb.messages
+                .iter()
+                .enumerate()
+                .filter(|(i, m)| in_set(&ranges, if by_uid { m.uid } else { *i as u32 + 1 }))
+                .map(|(i, m)| (i + 1, m.clone()))
+                .collect()
+        };
+        // Like real servers, a non-PEEK body fetch marks the message \Seen.
+        if wants_rfc822 || items.contains("BODY[]") {
+            let mut state = self.state.lock().unwrap();
+            let mb = state.mailboxes.get_mut(mailbox).unwrap();
+            for m in mb.messages.iter_mut() {
+                let hit = selected.iter().any(|(_, s)| s.uid == m.uid);
+                if hit && !m.flags.iter().any(|f| f == "\\Seen") {
+                    m.flags.push("\\Seen".to_string());
+                }
+            }
+        }
+
+        for (seq, msg) in selected {
+            let mut out: Vec<u8> = format!("* {} FETCH (UID {}", seq, msg.uid).into_bytes();
+            if items.contains("FLAGS") {
+                out.extend(format!(" FLAGS ({})", msg.flags.join(" ")).into_bytes());
+            }
+            if items.contains("RFC822.SIZE") {
+                out.extend(format!(" RFC822.SIZE {}", msg.raw.len()).into_bytes());
+            }
+            let (header, _) = split_header(&msg.raw);
+            if items.contains("ENVELOPE") {
+                // Only Message-ID is filled in; sync reads nothing else.
+                let message_id = String::from_utf8_lossy(header)
+                    .lines()
+                    .find_map(|l| {
+                        let (name, value) = l.split_once(':')?;
+                        name.eq_ignore_ascii_case("Message-ID")
+                            .then(|| value.trim().to_string())
+                    })
+                    .map(|id| format!("\"{}\"", id))
+                    .unwrap_or_else(|| "NIL".to_string());
+                out.extend(
+                    format!(" ENVELOPE (NIL NIL NIL NIL NIL NIL NIL NIL NIL {})", message_id)
+                        .into_bytes(),
+                );
+            }
+            let literal = |name: &str, data: &[u8], out: &mut Vec<u8>| {
+                out.extend(format!(" {} {{{}}}\r\n", name, data.len()).into_bytes());
+                out.extend_from_slice(data);
+            };
+            if items.contains("RFC822.HEADER") || items.contains("BODY.PEEK[HEADER]") {
+                let name = if items.contains("RFC822.HEADER") {
+                    "RFC822.HEADER"
+                } else {
+                    "BODY[HEADER]"
+                };
+                literal(name, header, &mut out);
+            } else if items.contains("BODY[]") || items.contains("BODY.PEEK[]") {
+                literal("BODY[]", &msg.raw, &mut out);
+            } else if wants_rfc822 {
+                literal("RFC822", &msg.raw, &mut out);
+            }
+            out.extend_from_slice(b")\r\n");
+            self.send(&out)?;
+        }
+        self.line(&format!("{} OK FETCH completed", tag))
+    }
+
+    fn store(&mut self, tag: &str, mailbox: &str, args: &str, by_uid: bool) -> std::io::Result<()> {
+        let tokens = tokenize(args);
+        if tokens.len() < 3 {
+            return self.line(&format!("{} BAD STORE needs set, item, and flags", tag));
+        }
+        let op = tokens[1].to_uppercase();
+        let flags: Vec<String> = tokens[2]
+            .trim_matches(|c| c == '(' || c == ')')
+            .split_whitespace()
+            .map(String::from)
+            .collect();
+        {
+            let mut state = self.state.lock().unwrap();
+            let mb = state.mailboxes.get_mut(mailbox).unwrap();
+            let max = if by_uid {
+                mb.messages.last().map(|m| m.uid).unwrap_or(0)
+            } else {
+                mb.messages.len() as u32
+            };
+            let ranges = parse_set(&tokens[0], max);
+            for (i, m) in mb.messages.iter_mut().enumerate() {
+                if !in_set(&ranges, if by_uid { m.uid } else { i as u32 + 1 }) {
+                    continue;
+                }
+                if op.starts_with("+FLAGS") {
+                    for f in &flags {
+                        if !m.flags.contains(f) {
+                            m.flags.push(f.clone());
+                        }
+                    }
+                } else if op.starts_with("-FLAGS") {
+                    m.flags.retain(|f| !flags.contains(f));
+                } else {
+                    m.flags = flags.clone();
+                }
+            }
+        }
+        self.line(&format!("{} OK STORE completed", tag))
+    }
+
+    /// `AUTHENTICATE XOAUTH2`: an empty challenge, then the base64 client
+    /// response. A bad token gets the error challenge Gmail sends, which the
+    /// client answers with an empty line, before the NO.
+    fn authenticate(&mut self, tag: &str, args: &str) -> std::io::Result<()> {
+        if !args.eq_ignore_ascii_case("XOAUTH2") {
+            return self.line(&format!("{} NO Unsupported mechanism", tag));
+        }
+        self.line("+ ")?;
+        let mut response = String::new();
+        self.stream.read_line(&mut response)?;
+        let expected = {
+            let state = self.state.lock().unwrap();
+            format!("user={}\x01auth=Bearer {}\x01\x01", state.user, state.password)
+        };
+        if decode_base64(response.trim_end()) == expected.as_bytes() {
+            self.authenticated = true;
+            return self.line(&format!("{} OK AUTHENTICATE completed", tag));
+        }
+        self.line("+ eyJzdGF0dXMiOiI0MDEiLCJzY2hlbWVzIjoiQmVhcmVyIn0=")?;
+        let mut empty = String::new();
+        self.stream.read_line(&mut empty)?;
+        self.line(&format!("{} NO [AUTHENTICATIONFAILED] Invalid credentials", tag))
+    }
+
+    /// `APPEND mailbox [(flags)] [date] {n}` followed by an n-byte literal.
+    fn append(&mut self, tag: &str, args: &str) -> std::io::Result<()> {
+        let tokens = tokenize(args);
+        let size = tokens
+            .last()
+            .and_then(|t| t.strip_prefix('{'))
+            .and_then(|t| t.trim_end_matches('}').trim_end_matches('+').parse::<usize>().ok());
+        let Some(size) = size else {
+            return self.line(&format!("{} BAD APPEND needs a literal", tag));
+        };
+        let name = tokens.first().cloned().unwrap_or_default();
+        let flags: Vec<String> = tokens
+            .iter()
+            .find(|t| t.starts_with('('))
+            .map(|t| {
+                t.trim_matches(|c| c == '(' || c == ')')
+                    .split_whitespace()
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        self.line("+ Ready for literal data")?;
+        let mut raw = vec![0u8; size];
+        self.stream.read_exact(&mut raw)?;
+        let mut rest = String::new();
+        self.stream.read_line(&mut rest)?;
+
+        let appended = {
+            let mut state = self.state.lock().unwrap();
+            state
+                .mailboxes
+                .get_mut(&name)
+                .map(|mb| (mb.uidvalidity, store_message(mb, raw, flags)))
+        };
+        match appended {
+            Some((uidvalidity, uid)) => self.line(&format!(
+                "{} OK [APPENDUID {} {}] APPEND completed",
+                tag, uidvalidity, uid
+            )),
+            None => self.line(&format!("{} NO [TRYCREATE] Mailbox doesn't exist", tag)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize(r#""me@example.com" "pa\"ss" (\Seen \Draft) {12}"#),
+            vec!["me@example.com", "pa\"ss", "(\\Seen \\Draft)", "{12}"]
+        );
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        assert_eq!(decode_base64("dXNlcj1tZQE="), b"user=me\x01");
+        assert_eq!(decode_base64("eyJzdGF0dXMiOiI0MDEifQ=="), b"{\"status\":\"401\"}");
+    }
+
+    #[test]
+    fn test_parse_set() {
+        let ranges = parse_set("1,3:5,9:*", 12);
+        assert!(in_set(&ranges, 1));
+        assert!(!in_set(&ranges, 2));
+        assert!(in_set(&ranges, 4));
+        assert!(in_set(&ranges, 12));
+        // `n:*` with n past the end still matches the last message, per RFC 3501.
+        assert!(in_set(&parse_set("20:*", 12), 12));
+    }
+}
@@ -0,0 +1,100 @@
+//! `corky thread list` — conversations with their issue links.
+
+use anyhow::Result;
+use std::path::PathBuf;
+
+use crate::mailbox::find_unanswered::{resolve_dirs, Scope};
+use crate::output::{self, Style};
+use crate::sync::links::{extract_links, link_matches};
+use crate::sync::store::open_store;
+use crate::thread::pin::is_pinned;
+
+/// One conversation file, as listed.
+#[derive(Debug, Clone)]
+pub struct ThreadEntry {
+    pub path: PathBuf,
+    pub subject: String,
+    pub last_updated: String,
+    pub links: Vec<String>,
+    pub pinned: bool,
+}
+
+/// Conversations in `scope`, keeping only those with a link matching
+/// `link` when given. Grouped by dir (root first); pinned threads first,
+/// then by filename.
+pub fn scan(scope: &Scope, link: Option<&str>) -> Result<Vec<(String, Vec<ThreadEntry>)>> {
+    let mut groups = Vec::new();
+    for (label, dir) in resolve_dirs(scope)? {
+        let store = open_store(&dir, true);
+        let mut entries = Vec::new();
+        for (key, thread) in store.threads()? {
+            let links = extract_links(&thread.messages);
+            if let Some(filter) = link {
+                if !links.iter().any(|l| link_matches(l, filter)) {
+                    continue;
+                }
+            }
+            entries.push(ThreadEntry {
+                pinned: is_pinned(&thread),
+                path: dir.join(store.display_name(&key)),
+                subject: thread.subject,
+                last_updated: thread.last_date,
+                links,
+            });
+        }
+        entries.sort_by_key(|e| !e.pinned);
+        groups.push((label, entries));
+    }
+    Ok(groups)
+}
+
+/// corky thread list [SCOPE] [--link PATTERN]
+pub fn run(scope: Scope, link: Option<&str>) -> Result<()> {
+    let groups = scan(&scope, link)?;
+    let multi = groups.len() > 1;
+    let mut total = 0;
+    for (label, entries) in &groups {
+        if entries.is_empty() {
+            continue;
+        }
+        total += entries.len();
+        if multi {
+            println!("{} ({} threads):", label, entries.len());
+        }
+        let mut rows = vec![vec![
+            output::paint("File", Style::Bold),
+            output::paint("Subject", Style::Bold),
+            output::paint("Last updated", Style::Bold),
+            output::paint("Links", Style::Bold),
+        ]];
+        for e in entries {
+            let filename = e
+                .path
+                .file_name()
+                .map(|f| f.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let subject = if e.pinned {
+                format!("* {}", e.subject)
+            } else {
+                e.subject.clone()
+            };
+            rows.push(vec![
+                filename,
+                subject,
+                output::paint(&e.last_updated, Style::Dim),
+                e.links.join(", "),
+            ]);
+        }
+        output::print_table(&rows);
+        if multi {
+            println!();
+        }
+    }
+    if total == 0 {
+        match link {
+            Some(l) => println!("No threads link to {}.", l),
+            None => println!("No threads found."),
+        }
+    }
+    Ok(())
+}
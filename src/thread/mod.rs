@@ -0,0 +1,4 @@
+pub mod list;
+pub mod open_web;
+pub mod pin;
+pub mod rename;
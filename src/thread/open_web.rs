@@ -0,0 +1,230 @@
+//! `corky thread open-web` — open a conversation in the provider's web UI.
+//!
+//! The link is built from what sync already records: the message's
+//! Message-ID and `received_by` account, and the (label, UID) provenance in
+//! sync state (§6.5). Gmail and Fastmail have built-in link formats; any
+//! other account needs `web_url` in its `[accounts.NAME]` table.
+
+use anyhow::{bail, Result};
+
+use crate::accounts::{get_default_account, load_accounts, Account};
+use crate::cal::auth::urlencode_pub as urlencode;
+use crate::resolve;
+use crate::sync::markdown::read_thread;
+use crate::sync::types::{SyncState, Thread};
+
+/// What a web link can point at, for one account.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WebTarget {
+    pub account: String,
+    /// Message-ID without angle brackets; empty if the message had none.
+    pub message_id: String,
+    pub subject: String,
+    /// A label the thread was synced from, with the newest UID seen there.
+    pub folder: String,
+    pub uid: Option<u32>,
+}
+
+/// The message to link to (the newest with a Message-ID, else the newest)
+/// and the account it came from.
+pub fn web_target(thread: &Thread, accounts: &[String], state: &SyncState) -> WebTarget {
+    let msg = thread
+        .messages
+        .iter()
+        .rev()
+        .find(|m| !m.message_id.is_empty())
+        .or(thread.messages.last());
+    let account = msg
+        .into_iter()
+        .flat_map(|m| m.received_by.iter())
+        .chain(thread.accounts.iter())
+        .find(|a| accounts.contains(a))
+        .cloned()
+        .unwrap_or_default();
+    let mut target = WebTarget {
+        message_id: msg
+            .map(|m| {
+                m.message_id
+                    .trim()
+                    .trim_start_matches('<')
+                    .trim_end_matches('>')
+            })
+            .unwrap_or_default()
+            .to_string(),
+        subject: thread.subject.clone(),
+        ..Default::default()
+    };
+    let labels = state
+        .accounts
+        .get(&account)
+        .and_then(|a| a.threads.get(&thread.id));
+    if let Some(labels) = labels {
+        let folder = thread
+            .labels
+            .iter()
+            .find(|l| labels.contains_key(*l))
+            .or_else(|| labels.keys().min());
+        if let Some(folder) = folder {
+            target.uid = labels[folder].iter().max().copied();
+            target.folder = folder.clone();
+        }
+    }
+    target.account = account;
+    target
+}
+
+/// The web UI link for `target` in `acct`, or None if the provider has no
+/// known link format and `web_url` isn't set.
+pub fn web_url(acct: &Account, target: &WebTarget) -> Option<String> {
+    let host = acct.imap_host.to_lowercase();
+    let msgid = urlencode(&target.message_id);
+    if !acct.web_url.is_empty() {
+        return Some(
+            acct.web_url
+                .replace("{message_id}", &msgid)
+                .replace("{user}", &urlencode(&acct.user))
+                .replace("{folder}", &urlencode(&target.folder))
+                .replace(
+                    "{uid}",
+                    &target.uid.map(|u| u.to_string()).unwrap_or_default(),
+                )
+                .replace("{subject}", &urlencode(&target.subject)),
+        );
+    }
+    if acct.provider == "gmail" || host.ends_with("gmail.com") || host.ends_with("googlemail.com") {
+        let query = if target.message_id.is_empty() {
+            format!("subject:\"{}\"", target.subject)
+        } else {
+            format!("rfc822msgid:{}", target.message_id)
+        };
+        return Some(format!(
+            "https://mail.google.com/mail/u/{}/#search/{}",
+            urlencode(&acct.user),
+            urlencode(&query)
+        ));
+    }
+    if host.ends_with("fastmail.com") && !target.message_id.is_empty() {
+        return Some(format!(
+            "https://app.fastmail.com/mail/search:{}",
+            urlencode(&format!("msgid:{}", target.message_id))
+        ));
+    }
+    None
+}
+
+/// corky thread open-web SLUG [--print]
+pub fn run(slug: &str, print_only: bool) -> Result<()> {
+    let path = resolve::find_conversation(slug)?;
+    let thread = read_thread(&path)?
+        .ok_or_else(|| anyhow::anyhow!("Failed to parse conversation: {}", path.display()))?;
+    let accounts = load_accounts(None)?;
+    let names: Vec<String> = accounts.keys().cloned().collect();
+    let state = crate::sync::load_state()?;
+    let mut target = web_target(&thread, &names, &state);
+    let acct = match accounts.get(&target.account) {
+        Some(acct) => acct.clone(),
+        None => {
+            let (name, acct) = get_default_account(&accounts)?;
+            target.account = name;
+            acct
+        }
+    };
+    let Some(url) = web_url(&acct, &target) else {
+        bail!(
+            "No web link format for account '{}' (provider {}, host {}); set web_url in [accounts.{}]",
+            target.account,
+            acct.provider,
+            acct.imap_host,
+            target.account
+        );
+    };
+    println!("{}", url);
+    if !print_only && open::that(&url).is_err() {
+        eprintln!("Could not open browser automatically.");
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::provenance;
+    use crate::sync::types::Message;
+    use crate::sync::types::fixtures::{message, thread};
+
+    fn plans() -> Thread {
+        let msg = |id: &str, account: &str| Message {
+            message_id: id.to_string(),
+            received_by: vec![account.to_string()],
+            ..message("Alice <alice@example.com>", "", "")
+        };
+        let messages = vec![msg("<a@example.com>", "personal"), msg("", "work")];
+        Thread {
+            labels: vec!["INBOX".to_string(), "work".to_string()],
+            accounts: vec!["personal".to_string()],
+            ..thread("t1", "Plans", messages)
+        }
+    }
+
+    #[test]
+    fn test_web_target_uses_message_id_and_provenance() {
+        let mut state = SyncState::default();
+        let acct = state.accounts.entry("personal".to_string()).or_default();
+        provenance::record(acct, "t1", "work", 7);
+        provenance::record(acct, "t1", "work", 9);
+        let names = vec!["personal".to_string(), "work".to_string()];
+        let target = web_target(&plans(), &names, &state);
+        assert_eq!(
+            target,
+            WebTarget {
+                account: "personal".to_string(),
+                message_id: "a@example.com".to_string(),
+                subject: "Plans".to_string(),
+                folder: "work".to_string(),
+                uid: Some(9),
+            }
+        );
+    }
+
+    #[test]
+    fn test_web_urls() {
+        let target = WebTarget {
+            account: "personal".to_string(),
+            message_id: "a+b@example.com".to_string(),
+            subject: "Plans".to_string(),
+            folder: "INBOX".to_string(),
+            uid: Some(9),
+        };
+        let gmail = Account {
+            provider: "gmail".to_string(),
+            user: "me@gmail.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            web_url(&gmail, &target).unwrap(),
+            "https://mail.google.com/mail/u/me%40gmail.com/#search/rfc822msgid%3Aa%2Bb%40example.com"
+        );
+        let fastmail = Account {
+            imap_host: "imap.fastmail.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            web_url(&fastmail, &target).unwrap(),
+            "https://app.fastmail.com/mail/search:msgid%3Aa%2Bb%40example.com"
+        );
+        let custom = Account {
+            imap_host: "imap.example.com".to_string(),
+            web_url: "https://mail.example.com/{folder}/{uid}".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(
+            web_url(&custom, &target).unwrap(),
+            "https://mail.example.com/INBOX/9"
+        );
+        let plain = Account {
+            imap_host: "imap.example.com".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(web_url(&plain, &target), None);
+    }
+}
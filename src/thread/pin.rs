@@ -0,0 +1,131 @@
+//! `corky thread pin` / `unpin` — keep a conversation at the top of lists.
+//!
+//! Pinning is the `pinned` tag in the conversation's `**Tags**` line, so it
+//! survives merges like any tag and shows up in the manifest as
+//! `pinned = true`. With `[sync] pin_flagged = true`, sync also pins
+//! threads that have a `\Flagged` message on the server.
+
+use anyhow::{bail, Result};
+use std::path::Path;
+
+use crate::config::corky_config::{self, SyncConfig};
+use crate::resolve;
+use crate::sync::imap_sync::set_mtime;
+use crate::sync::manifest::generate_manifest;
+use crate::sync::markdown::{read_thread, write_thread};
+use crate::sync::types::Thread;
+
+/// Tag marking a pinned thread.
+pub const PINNED_TAG: &str = "pinned";
+
+pub fn is_pinned(thread: &Thread) -> bool {
+    thread.tags.iter().any(|t| t == PINNED_TAG)
+}
+
+/// Pin or unpin the conversation at `path`. Returns false if it already
+/// was in that state (the file is left alone).
+pub fn set_pinned(path: &Path, pinned: bool, sync: &SyncConfig) -> Result<bool> {
+    let Some(mut thread) = read_thread(path)? else {
+        bail!("Failed to parse conversation: {}", path.display());
+    };
+    if is_pinned(&thread) == pinned {
+        return Ok(false);
+    }
+    if pinned {
+        thread.tags.push(PINNED_TAG.to_string());
+    } else {
+        thread.tags.retain(|t| t != PINNED_TAG);
+    }
+    for written in write_thread(path, &thread, sync)? {
+        let _ = set_mtime(&written, &thread.last_date);
+    }
+    Ok(true)
+}
+
+/// Pinned threads in `manifest.toml` at `path`, as (slug, subject) sorted
+/// by slug. Empty if the manifest is missing or unreadable.
+pub fn pinned_in_manifest(path: &Path) -> Vec<(String, String)> {
+    let Some(manifest) = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|text| text.parse::<toml::Table>().ok())
+    else {
+        return Vec::new();
+    };
+    let Some(threads) = manifest.get("threads").and_then(|t| t.as_table()) else {
+        return Vec::new();
+    };
+    threads
+        .iter()
+        .filter(|(_, t)| t.get("pinned").and_then(|p| p.as_bool()) == Some(true))
+        .map(|(slug, t)| {
+            let subject = t.get("subject").and_then(|s| s.as_str()).unwrap_or("");
+            (slug.clone(), subject.to_string())
+        })
+        .collect()
+}
+
+/// corky thread pin SLUG / corky thread unpin SLUG
+pub fn run(slug: &str, pinned: bool) -> Result<()> {
+    let path = resolve::find_conversation(slug)?;
+    let sync = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    let name = path
+        .file_name()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let verb = if pinned { "Pinned" } else { "Unpinned" };
+    if set_pinned(&path, pinned, &sync)? {
+        println!("{}: {}", verb, name);
+    } else {
+        println!("Already {}: {}", verb.to_lowercase(), name);
+    }
+    generate_manifest(&resolve::conversations_dir())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_pinned_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("plans.md");
+        std::fs::write(
+            &path,
+            "# Plans\n\n**Thread ID**: plans\n**Tags**: newsletter\n",
+        )
+        .unwrap();
+        let sync = SyncConfig::default();
+
+        assert!(set_pinned(&path, true, &sync).unwrap());
+        assert!(!set_pinned(&path, true, &sync).unwrap());
+        let thread = read_thread(&path).unwrap().unwrap();
+        assert_eq!(thread.tags, vec!["newsletter", PINNED_TAG]);
+        assert!(is_pinned(&thread));
+
+        assert!(set_pinned(&path, false, &sync).unwrap());
+        let thread = read_thread(&path).unwrap().unwrap();
+        assert_eq!(thread.tags, vec!["newsletter"]);
+        assert!(!set_pinned(&path, false, &sync).unwrap());
+    }
+
+    #[test]
+    fn test_pinned_in_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("manifest.toml");
+        assert!(pinned_in_manifest(&path).is_empty());
+        std::fs::write(
+            &path,
+            "[threads.lunch]\nsubject = \"Lunch\"\n\n\
+             [threads.plans]\nsubject = \"Plans\"\npinned = true\n",
+        )
+        .unwrap();
+        assert_eq!(
+            pinned_in_manifest(&path),
+            vec![("plans".to_string(), "Plans".to_string())]
+        );
+    }
+}
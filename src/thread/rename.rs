@@ -0,0 +1,146 @@
+//! `corky thread rename` — give a conversation a new subject and filename.
+//!
+//! The Thread ID is left alone: sync finds conversation files by Thread ID,
+//! not by name, so later messages keep merging into the renamed file.
+//! Routed mailbox copies are renamed the same way.
+
+use anyhow::{bail, Result};
+use std::path::{Path, PathBuf};
+
+use crate::config::corky_config::{self, SyncConfig};
+use crate::resolve;
+use crate::sync::imap_sync::{build_label_routes, find_thread_file, set_mtime, thread_file_stem};
+use crate::sync::manifest::generate_manifest;
+use crate::sync::markdown::{read_thread, thread_files, write_thread};
+
+/// `{stem}.md` in `dir`, or the first free `{stem}-N.md`. `own` (the file
+/// being renamed) counts as free.
+fn target_path(dir: &Path, stem: &str, own: &Path) -> PathBuf {
+    let mut candidate = dir.join(format!("{}.md", stem));
+    let mut n = 2;
+    while candidate.exists() && candidate != own {
+        candidate = dir.join(format!("{}-{}.md", stem, n));
+        n += 1;
+    }
+    candidate
+}
+
+/// Retitle the conversation at `path` and move it (with its continuation
+/// parts and `{stem}/` folder) to the filename `subject` gets under
+/// `[sync] filename_scheme`. Returns the new path.
+pub fn rename_file(path: &Path, subject: &str, sync: &SyncConfig) -> Result<PathBuf> {
+    let Some(mut thread) = read_thread(path)? else {
+        bail!("Failed to parse conversation: {}", path.display());
+    };
+    thread.subject = subject.to_string();
+    for msg in &mut thread.messages {
+        msg.subject = subject.to_string();
+    }
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let old_stem = path
+        .file_stem()
+        .unwrap_or_default()
+        .to_string_lossy()
+        .to_string();
+    let first_date = thread
+        .messages
+        .first()
+        .map(|m| m.date.as_str())
+        .unwrap_or("");
+    let stem = thread_file_stem(
+        &sync.filename_scheme,
+        subject,
+        first_date,
+        &sync.strip_prefixes,
+    );
+    let new_path = target_path(dir, &stem, path);
+
+    let old_files = thread_files(path);
+    for written in write_thread(&new_path, &thread, sync)? {
+        let _ = set_mtime(&written, &thread.last_date);
+    }
+    if new_path != path {
+        for old in old_files {
+            std::fs::remove_file(old)?;
+        }
+        let old_dir = dir.join(&old_stem);
+        let new_dir = new_path.with_extension("");
+        if old_dir.is_dir() && !new_dir.exists() {
+            std::fs::rename(&old_dir, &new_dir)?;
+        }
+    }
+    Ok(new_path)
+}
+
+/// corky thread rename SLUG "New Subject"
+pub fn run(slug: &str, subject: &str) -> Result<()> {
+    let subject = subject.trim();
+    if subject.is_empty() {
+        bail!("Subject must not be empty");
+    }
+    let path = resolve::find_conversation(slug)?;
+    let Some(thread) = read_thread(&path)? else {
+        bail!("Failed to parse conversation: {}", path.display());
+    };
+    let sync = corky_config::try_load_config(None)
+        .and_then(|c| c.sync)
+        .unwrap_or_default();
+    let data_dir = resolve::data_dir();
+    let shown = |p: &Path| p.strip_prefix(&data_dir).unwrap_or(p).display().to_string();
+
+    // Routed copies, found by Thread ID as during sync. Only the owner's
+    // root conversation has them.
+    let mut copies: Vec<PathBuf> = Vec::new();
+    if path.parent() == Some(resolve::conversations_dir().as_path()) {
+        let mut dirs: Vec<PathBuf> = Vec::new();
+        for account in &thread.accounts {
+            let routes = build_label_routes(account)?;
+            for dir in thread.labels.iter().filter_map(|l| routes.get(l)).flatten() {
+                if !dirs.contains(dir) {
+                    dirs.push(dir.clone());
+                }
+            }
+        }
+        copies.extend(dirs.iter().filter_map(|d| find_thread_file(d, &thread.id)));
+    }
+
+    for file in std::iter::once(&path).chain(copies.iter()) {
+        let renamed = rename_file(file, subject, &sync)?;
+        println!("{} -> {}", shown(file), shown(&renamed));
+    }
+    generate_manifest(&resolve::conversations_dir())?;
+    println!("Thread ID unchanged: {}", thread.id);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sync::markdown::parse_thread_markdown;
+
+    #[test]
+    fn test_rename_file_keeps_thread_id_and_moves_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        let old = dir.path().join("re-external-plans.md");
+        let text = "# Re: [EXTERNAL] Plans\n\n**Thread ID**: plans\n**Labels**: inbox\n\n---\n\n\
+                    ## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\nNoon?\n";
+        std::fs::write(&old, text).unwrap();
+        std::fs::create_dir(dir.path().join("re-external-plans")).unwrap();
+        std::fs::write(dir.path().join("re-external-plans/agenda.pdf"), "pdf").unwrap();
+        std::fs::write(dir.path().join("lunch-plans.md"), "# Other\n").unwrap();
+
+        let sync = SyncConfig::default();
+        let new = rename_file(&old, "Lunch plans", &sync).unwrap();
+        assert_eq!(new, dir.path().join("lunch-plans-2.md"));
+        assert!(!old.exists());
+        assert!(dir.path().join("lunch-plans-2/agenda.pdf").exists());
+        let thread = parse_thread_markdown(&std::fs::read_to_string(&new).unwrap()).unwrap();
+        assert_eq!(thread.subject, "Lunch plans");
+        assert_eq!(thread.id, "plans");
+        assert_eq!(thread.messages[0].body, "Noon?");
+
+        // Same stem: retitled in place
+        let again = rename_file(&new, "Lunch Plans", &sync).unwrap();
+        assert_eq!(again, new);
+    }
+}
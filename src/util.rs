@@ -1,10 +1,18 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::process::Command;
+use std::io::Read;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::error::{CommandError, CorkyError};
 
 static SLUG_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^a-z0-9]+").unwrap());
-static THREAD_KEY_RE: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"(?i)^(re|fwd?):\s*").unwrap());
+/// Built-in subject noise: reply/forward markers (`Re:`, `Fwd:`, `AW:`,
+/// `Re[2]:`, ...) and mail gateway tags (`[EXTERNAL]`, `[SPAM?]`, ...).
+const SUBJECT_NOISE: &str = r"(?:re|fwd?|aw|wg|sv|vs|tr)(?:\[\d+\])?\s*:|\[(?:external|ext|spam\??|suspicious|caution|warning)\]|\*?external\*?\s*:";
+/// One leading prefix of built-in noise.
+static SUBJECT_PREFIX_RE: Lazy<Regex> = Lazy::new(|| subject_prefix_regex(&[]));
+static ANGLE_ADDR_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"<([^>]+)>").unwrap());
 
 /// Generate a URL-safe slug from text.
 ///
@@ -31,12 +39,96 @@ pub fn slugify(text: &str) -> String {
     }
 }
 
+/// Regex for one leading subject prefix: the built-in noise or one of
+/// `extra` (case-insensitive literals; `*` matches a run of non-space
+/// characters, so `[PROJ-*]` covers every ticket tag of a project).
+pub fn subject_prefix_regex(extra: &[String]) -> Regex {
+    let mut alternatives = vec![SUBJECT_NOISE.to_string()];
+    for prefix in extra.iter().map(|p| p.trim()).filter(|p| !p.is_empty()) {
+        alternatives.push(regex::escape(prefix).replace(r"\*", r"[^\s\]]*"));
+    }
+    Regex::new(&format!(r"(?i)^\s*(?:{})\s*", alternatives.join("|"))).unwrap()
+}
+
+/// `subject` without any run of leading prefixes matching `prefix_re`.
+/// A subject that is nothing but prefixes keeps its last one.
+pub fn strip_subject_prefixes(subject: &str, prefix_re: &Regex) -> String {
+    let mut rest = subject.trim();
+    while let Some(m) = prefix_re.find(rest) {
+        if m.end() == rest.len() {
+            break;
+        }
+        rest = &rest[m.end()..];
+    }
+    rest.to_string()
+}
+
+/// A subject without its noisy leading prefixes: any run of reply/forward
+/// markers and mail gateway tags (`Re: [EXTERNAL] Fwd: Plans` → `Plans`).
+/// Callers with `[sync] strip_prefixes` use [`strip_subject_prefixes`] with
+/// [`subject_prefix_regex`] instead.
+pub fn clean_subject(subject: &str) -> String {
+    strip_subject_prefixes(subject, &SUBJECT_PREFIX_RE)
+}
+
 /// Derive a thread key from a subject line.
 ///
-/// Strips one `Re:` or `Fwd:` prefix (case-insensitive), then lowercases.
+/// Strips every leading prefix [`clean_subject`] does (`Re: Re: Fwd:`,
+/// `[EXTERNAL]`), then lowercases.
 pub fn thread_key_from_subject(subject: &str) -> String {
-    let trimmed = subject.trim().to_lowercase();
-    THREAD_KEY_RE.replace(&trimmed, "").to_string()
+    subject_key(subject, &SUBJECT_PREFIX_RE)
+}
+
+/// [`thread_key_from_subject`] with the prefixes of `prefix_re`
+/// (built by [`subject_prefix_regex`] from `[sync] strip_prefixes`).
+pub fn subject_key(subject: &str, prefix_re: &Regex) -> String {
+    strip_subject_prefixes(subject, prefix_re).to_lowercase()
+}
+
+/// Split an address header (`To`, `CC`, ...) into individual addresses.
+///
+/// Commas inside quoted display names or angle brackets don't split.
+pub fn split_address_list(field: &str) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut in_angle = false;
+    for c in field.chars() {
+        match c {
+            '"' if !in_angle => in_quotes = !in_quotes,
+            '<' if !in_quotes => in_angle = true,
+            '>' if !in_quotes => in_angle = false,
+            ',' if !in_quotes && !in_angle => {
+                let addr = current.trim();
+                if !addr.is_empty() {
+                    out.push(addr.to_string());
+                }
+                current.clear();
+                continue;
+            }
+            _ => {}
+        }
+        current.push(c);
+    }
+    let addr = current.trim();
+    if !addr.is_empty() {
+        out.push(addr.to_string());
+    }
+    out
+}
+
+/// Extract the bare email from `Name <email>` or a bare `email`.
+///
+/// Returns None when the string holds no address (e.g. a chat display name).
+pub fn extract_email(addr: &str) -> Option<String> {
+    if let Some(cap) = ANGLE_ADDR_RE.captures(addr) {
+        return Some(cap[1].trim().to_string());
+    }
+    let trimmed = addr.trim();
+    if trimmed.contains('@') && !trimmed.contains(char::is_whitespace) {
+        return Some(trimmed.to_string());
+    }
+    None
 }
 
 /// Run a shell command, returning (stdout, stderr, exit_code).
@@ -48,6 +140,109 @@ pub fn run_cmd(args: &[&str]) -> anyhow::Result<(String, String, i32)> {
     Ok((stdout, stderr, code))
 }
 
+/// Default time limit for [`run_git`]; long enough for a slow push.
+pub const GIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Run a command with extra environment, killing it after `timeout`.
+///
+/// stdin is closed so the child can never block on a prompt. Fails with
+/// [`CommandError`] if the program can't be started or runs too long.
+pub fn run_cmd_timeout(
+    args: &[&str],
+    env: &[(&str, &str)],
+    timeout: Duration,
+) -> anyhow::Result<(String, String, i32)> {
+    let mut child = Command::new(args[0])
+        .args(&args[1..])
+        .envs(env.iter().copied())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|source| CommandError::Spawn {
+            program: args[0].to_string(),
+            source,
+        })?;
+
+    // Drain both pipes on threads so a chatty child can't fill them and stall.
+    let stdout = child.stdout.take().map(drain);
+    let stderr = child.stderr.take().map(drain);
+
+    let deadline = Instant::now() + timeout;
+    let status = loop {
+        if let Some(status) = child.try_wait()? {
+            break status;
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(CommandError::Timeout {
+                command: args.join(" "),
+                secs: timeout.as_secs(),
+            }
+            .into());
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    };
+
+    let join = |h: Option<std::thread::JoinHandle<String>>| {
+        h.and_then(|h| h.join().ok()).unwrap_or_default()
+    };
+    Ok((join(stdout), join(stderr), status.code().unwrap_or(-1)))
+}
+
+fn drain<R: Read + Send + 'static>(mut pipe: R) -> std::thread::JoinHandle<String> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = pipe.read_to_end(&mut buf);
+        String::from_utf8_lossy(&buf).to_string()
+    })
+}
+
+/// Profile phase for `git ARGS...`: `git` for the subcommands that talk to
+/// a remote (pull, push, fetch, clone), `git-local` for everything else.
+fn git_phase(args: &[&str]) -> &'static str {
+    let mut rest = args;
+    while let ["-C" | "-c", _, tail @ ..] = rest {
+        rest = tail;
+    }
+    match rest.first() {
+        Some(&("pull" | "push" | "fetch" | "clone")) => "git",
+        _ => "git-local",
+    }
+}
+
+/// Run `git ARGS...` non-interactively with [`GIT_TIMEOUT`].
+///
+/// Credential and SSH passphrase prompts are disabled, so a repo that
+/// needs interactive auth fails fast instead of hanging. With `--profile`,
+/// the time counts as `git` or `git-local` ([`git_phase`]) for the `-C`
+/// directory's name.
+pub fn run_git(args: &[&str]) -> anyhow::Result<(String, String, i32)> {
+    run_git_env(args, &[])
+}
+
+/// [`run_git`] with extra environment for git and its hooks.
+pub fn run_git_env(
+    args: &[&str],
+    extra: &[(&str, &str)],
+) -> anyhow::Result<(String, String, i32)> {
+    let mut full = vec!["git"];
+    full.extend_from_slice(args);
+    let mut env = vec![("GIT_TERMINAL_PROMPT", "0")];
+    env.extend_from_slice(extra);
+    if std::env::var_os("GIT_SSH_COMMAND").is_none() {
+        env.push(("GIT_SSH_COMMAND", "ssh -o BatchMode=yes"));
+    }
+    let scope = match args {
+        ["-C", dir, ..] => std::path::Path::new(dir)
+            .file_name()
+            .map_or(dir.to_string(), |n| n.to_string_lossy().to_string()),
+        _ => "run".to_string(),
+    };
+    crate::profile::time(&scope, git_phase(args), || run_cmd_timeout(&full, &env, GIT_TIMEOUT))
+}
+
 /// Run a shell command, printing it first. Returns Ok on success, Err on failure.
 pub fn run_cmd_checked(args: &[&str]) -> anyhow::Result<String> {
     let cmd_str = args.join(" ");
@@ -102,10 +297,87 @@ pub fn truncate_preview(s: &str, max: usize) -> String {
     }
 }
 
+/// Error for a command whose Cargo feature was compiled out.
+pub fn missing_feature(feature: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "This command requires the '{}' feature.\n\
+         Reinstall with: cargo install corky --features {}",
+        feature,
+        feature
+    )
+}
+
+/// Environment variable that turns on restricted mode (`--restricted`
+/// sets it, so hooks and child corky processes inherit it).
+pub const RESTRICTED_ENV: &str = "CORKY_RESTRICTED";
+
+/// Whether restricted mode is on: `CORKY_RESTRICTED` set to anything but
+/// empty or `0`.
+pub fn restricted() -> bool {
+    std::env::var(RESTRICTED_ENV).is_ok_and(|v| !v.trim().is_empty() && v.trim() != "0")
+}
+
+/// Refuse `action` (e.g. "Sending email") in restricted mode, for handing
+/// the CLI to an agent: sending, server-side deletion, and repo deletion
+/// fail with exit code 9 however they are reached.
+pub fn ensure_unrestricted(action: &str) -> anyhow::Result<()> {
+    if restricted() {
+        return Err(CorkyError::Restricted(format!(
+            "{} is disabled in restricted mode ({} is set)",
+            action, RESTRICTED_ENV
+        ))
+        .into());
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_git_phase_counts_only_remote_subcommands_as_network() {
+        assert_eq!(git_phase(&["-C", "mb", "pull", "--rebase"]), "git");
+        assert_eq!(git_phase(&["-C", "mb", "-c", "user.name=Me", "push"]), "git");
+        assert_eq!(git_phase(&["clone", "url", "dir"]), "git");
+        assert_eq!(git_phase(&["-C", "mb", "status", "--porcelain"]), "git-local");
+        assert_eq!(git_phase(&["-C", "mb", "commit", "-m", "x"]), "git-local");
+        assert_eq!(git_phase(&[]), "git-local");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_cmd_timeout_kills_slow_command() {
+        let err = run_cmd_timeout(&["sleep", "5"], &[], Duration::from_millis(100)).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CommandError>(),
+            Some(CommandError::Timeout { .. })
+        ));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_cmd_timeout_passes_env_and_output() {
+        let (stdout, _, code) = run_cmd_timeout(
+            &["sh", "-c", "echo $CORKY_TEST_VAR"],
+            &[("CORKY_TEST_VAR", "hello")],
+            Duration::from_secs(10),
+        )
+        .unwrap();
+        assert_eq!(stdout.trim(), "hello");
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn test_run_cmd_timeout_missing_program() {
+        let err = run_cmd_timeout(&["corky-no-such-binary"], &[], Duration::from_secs(1))
+            .unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<CommandError>(),
+            Some(CommandError::Spawn { .. })
+        ));
+    }
+
     #[test]
     fn test_slugify_basic() {
         assert_eq!(slugify("Hello World"), "hello-world");
@@ -128,6 +400,33 @@ mod tests {
         assert_eq!(slugify("!!!"), "untitled");
     }
 
+    #[test]
+    fn test_clean_subject() {
+        assert_eq!(clean_subject("Re: [EXTERNAL] Fwd: Plans"), "Plans");
+        assert_eq!(clean_subject("AW: WG: Angebot"), "Angebot");
+        assert_eq!(clean_subject("RE[2]: [SPAM?] Lunch?"), "Lunch?");
+        assert_eq!(clean_subject("*EXTERNAL*: Invoice [#42]"), "Invoice [#42]");
+        assert_eq!(clean_subject("Refund request"), "Refund request");
+        // Nothing but noise: keep the last prefix rather than go empty
+        assert_eq!(clean_subject("Re: [EXTERNAL]"), "[EXTERNAL]");
+    }
+
+    #[test]
+    fn test_thread_key_strips_chains_and_tags() {
+        assert_eq!(thread_key_from_subject("Re: Re: Fwd: Hello World"), "hello world");
+        assert_eq!(thread_key_from_subject("[EXTERNAL] RE: Hello World"), "hello world");
+        assert_eq!(thread_key_from_subject("Re: [Hello] World"), "[hello] world");
+    }
+
+    #[test]
+    fn test_configured_prefixes() {
+        let re = subject_prefix_regex(&["[PROJ-*]".to_string(), "[JIRA]".to_string()]);
+        assert_eq!(strip_subject_prefixes("Re: [PROJ-123] [jira] Deploy", &re), "Deploy");
+        assert_eq!(strip_subject_prefixes("[PROJ 1] Deploy", &re), "[PROJ 1] Deploy");
+        assert_eq!(strip_subject_prefixes("[OTHER-1] Deploy", &re), "[OTHER-1] Deploy");
+        assert_eq!(subject_key("RE: [Proj-7] Deploy", &re), "deploy");
+    }
+
     #[test]
     fn test_thread_key_strips_re() {
         assert_eq!(
@@ -168,6 +467,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_split_address_list_quoted_comma() {
+        assert_eq!(
+            split_address_list("\"Doe, Jane\" <jane@example.com>, bob@example.com"),
+            vec!["\"Doe, Jane\" <jane@example.com>", "bob@example.com"]
+        );
+        assert!(split_address_list("  ").is_empty());
+    }
+
+    #[test]
+    fn test_extract_email() {
+        assert_eq!(extract_email("Alice <alice@example.com>").as_deref(), Some("alice@example.com"));
+        assert_eq!(extract_email("bob@example.com").as_deref(), Some("bob@example.com"));
+        assert_eq!(extract_email("Just A Name"), None);
+    }
+
     #[test]
     fn test_resolve_secret_inline() {
         let result = resolve_secret("my-secret", "", "unused context");
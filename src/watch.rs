@@ -2,35 +2,225 @@
 
 use anyhow::Result;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
 
-use crate::accounts::{load_accounts, load_watch_config, resolve_password};
+use crate::accounts::{account_secret, load_accounts, load_watch_config};
+use crate::address::Address;
 use crate::config::corky_config;
+use crate::error::CorkyError;
+use crate::journal::{self, WatchLog};
+use crate::mailbox::sync::SyncMode;
 use crate::resolve;
-use crate::sync::imap_sync::sync_account;
+use crate::sync::imap_sync::{find_thread_file, parse_msg_date, sync_account};
+use crate::sync::markdown::read_thread;
+use crate::sync::provenance;
 use crate::sync::types::SyncState;
 
-/// Desktop notification (best-effort).
+/// What new-mail notifications show (`[watch] notify_detail`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum NotifyDetail {
+    /// Sender and subject of the newest messages.
+    Full,
+    /// Sender only.
+    Sender,
+    /// Only how many labels got mail.
+    Count,
+}
+
+fn parse_notify_detail(value: &str) -> Result<NotifyDetail> {
+    match value.trim() {
+        "" | "full" => Ok(NotifyDetail::Full),
+        "sender" => Ok(NotifyDetail::Sender),
+        "count" => Ok(NotifyDetail::Count),
+        other => Err(CorkyError::Config(format!(
+            "Invalid [watch] notify_detail '{}': expected full, sender, or count",
+            other
+        ))
+        .into()),
+    }
+}
+
+/// Notification settings for the poll loop.
+#[derive(Debug, Clone, Copy)]
+struct Notify {
+    detail: NotifyDetail,
+    count: usize,
+    /// Wait for clicks in the background (the daemon, not `--once`).
+    wait: bool,
+}
+
+/// Escape text for an AppleScript string literal.
+#[cfg(target_os = "macos")]
+fn applescript_quote(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// `file://` URL for `path`, percent-encoding all but unreserved bytes and `/`.
+#[cfg(target_os = "macos")]
+fn file_url(path: &Path) -> String {
+    let mut url = String::from("file://");
+    for b in path.to_string_lossy().bytes() {
+        if b.is_ascii_alphanumeric() || b"-._~/".contains(&b) {
+            url.push(b as char);
+        } else {
+            url.push_str(&format!("%{:02X}", b));
+        }
+    }
+    url
+}
+
+/// Desktop notification (best-effort). With `target`, clicking it opens
+/// that file or directory: through `terminal-notifier` on macOS (plain
+/// `osascript` notifications without it), and a `notify-send --wait`
+/// action on Linux, waited for on a background thread. That thread only
+/// lives as long as the process, so without `wait` (`watch --once`) Linux
+/// shows a plain notification and returns once it is sent.
 #[allow(unused_variables)]
-fn notify(title: &str, body: &str) {
+fn notify(title: &str, body: &str, target: Option<&Path>, wait: bool) {
     #[cfg(target_os = "macos")]
     {
+        if let Some(path) = target {
+            let shown = std::process::Command::new("terminal-notifier")
+                .args(["-title", title, "-message", body, "-open", &file_url(path)])
+                .output();
+            if shown.is_ok_and(|o| o.status.success()) {
+                return;
+            }
+        }
         let _ = std::process::Command::new("osascript")
             .arg("-e")
             .arg(format!(
                 "display notification \"{}\" with title \"{}\"",
-                body, title
+                applescript_quote(body),
+                applescript_quote(title)
             ))
             .output();
     }
     #[cfg(target_os = "linux")]
     {
-        let _ = std::process::Command::new("notify-send")
-            .arg(title)
-            .arg(body)
-            .output();
+        // notify-send bodies may be rendered as markup
+        let body = body
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;");
+        let title = title.to_string();
+        let target = target.filter(|_| wait).map(Path::to_path_buf);
+        let waits = target.is_some();
+        let show = move || {
+            if let Some(path) = target {
+                let clicked = std::process::Command::new("notify-send")
+                    .args(["--action=default=Open", "--wait", &title, &body])
+                    .output();
+                // Older notify-send without --action fails; fall through.
+                if let Ok(out) = clicked {
+                    if out.status.success() {
+                        if String::from_utf8_lossy(&out.stdout).trim() == "default" {
+                            let _ = open::that(&path);
+                        }
+                        return;
+                    }
+                }
+            }
+            let _ = std::process::Command::new("notify-send")
+                .arg(&title)
+                .arg(&body)
+                .output();
+        };
+        if waits {
+            std::thread::spawn(show);
+        } else {
+            show();
+        }
+    }
+}
+
+/// The newest message of a thread with new mail.
+#[derive(Debug, Clone)]
+struct NewMail {
+    sender: String,
+    subject: String,
+    path: PathBuf,
+}
+
+/// Thread IDs with a UID above the `before` snapshot of its label, most
+/// recent first (by that UID; UIDs grow with arrival within a label).
+fn new_thread_ids(
+    state: &SyncState,
+    before: &HashMap<String, HashMap<String, u32>>,
+) -> Vec<String> {
+    let mut newest: HashMap<&str, u32> = HashMap::new();
+    for (acct_name, acct) in &state.accounts {
+        let seen = before.get(acct_name);
+        for (thread_id, labels) in &acct.threads {
+            for (label, uids) in labels {
+                let last = seen.and_then(|s| s.get(label)).copied().unwrap_or(0);
+                if let Some(max) = uids.iter().copied().filter(|u| *u > last).max() {
+                    let entry = newest.entry(thread_id.as_str()).or_default();
+                    *entry = (*entry).max(max);
+                }
+            }
+        }
+    }
+    let mut ids: Vec<(&str, u32)> = newest.into_iter().collect();
+    ids.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    ids.into_iter().map(|(id, _)| id.to_string()).collect()
+}
+
+/// Newest message of up to `count` of `thread_ids`, read from `dir`,
+/// newest first.
+fn newest_mail(dir: &Path, thread_ids: &[String], count: usize) -> Vec<NewMail> {
+    let mut mail = Vec::new();
+    for id in thread_ids.iter().take(count) {
+        let Some(path) = find_thread_file(dir, id) else {
+            continue;
+        };
+        let Ok(Some(thread)) = read_thread(&path) else {
+            continue;
+        };
+        let Some(msg) = thread.messages.last() else {
+            continue;
+        };
+        let sender = match Address::parse(&msg.from) {
+            Some(a) if !a.name.is_empty() => a.name,
+            Some(a) => a.email,
+            None => msg.from.clone(),
+        };
+        let subject = if msg.subject.is_empty() {
+            thread.subject.clone()
+        } else {
+            msg.subject.clone()
+        };
+        mail.push((parse_msg_date(&msg.date), NewMail { sender, subject, path }));
+    }
+    mail.sort_by(|a, b| b.0.cmp(&a.0));
+    mail.into_iter().map(|(_, m)| m).collect()
+}
+
+/// Notification body: the label count, then one line per message as
+/// `detail` allows, and how many threads weren't listed.
+fn notification_body(
+    new_count: usize,
+    threads: usize,
+    mail: &[NewMail],
+    detail: NotifyDetail,
+) -> String {
+    let head = format!("{} label(s) with new messages", new_count);
+    if detail == NotifyDetail::Count {
+        return head;
+    }
+    let mut lines = vec![head];
+    for m in mail {
+        lines.push(match detail {
+            NotifyDetail::Sender => m.sender.clone(),
+            _ => format!("{}: {}", m.sender, m.subject),
+        });
+    }
+    if threads > mail.len() && !mail.is_empty() {
+        lines.push(format!("+{} more", threads - mail.len()));
     }
+    lines.join("\n")
 }
 
 /// Snapshot {account: {label: last_uid}} from current sync state.
@@ -67,58 +257,84 @@ fn count_new_messages(
     count
 }
 
-fn load_state() -> SyncState {
-    let sf = resolve::sync_state_file();
-    if sf.exists() {
-        if let Ok(data) = std::fs::read(&sf) {
-            if let Ok(state) = crate::sync::types::load_state(&data) {
-                return state;
-            }
-        }
+/// Parse `[watch] mailbox_sync`; `None` means mailbox sync is off.
+fn parse_mailbox_sync(value: &str) -> Result<Option<SyncMode>> {
+    match value.trim() {
+        "" | "both" => Ok(Some(SyncMode::Both)),
+        "pull" => Ok(Some(SyncMode::PullOnly)),
+        "push" => Ok(Some(SyncMode::PushOnly)),
+        "off" => Ok(None),
+        other => Err(CorkyError::Config(format!(
+            "Invalid [watch] mailbox_sync '{}': expected both, pull, push, or off",
+            other
+        ))
+        .into()),
     }
-    SyncState::default()
 }
 
-fn save_state(state: &SyncState) {
-    if let Ok(data) = serde_json::to_vec(state) {
-        let _ = std::fs::write(resolve::sync_state_file(), data);
-    }
+/// `[watch] notify` settings; `None` when notifications are off.
+fn notify_settings(config: &crate::accounts::WatchConfig) -> Result<Option<Notify>> {
+    let detail = parse_notify_detail(&config.notify_detail)?;
+    Ok(config.notify.then_some(Notify {
+        detail,
+        count: config.notify_count,
+        wait: true,
+    }))
 }
 
-fn sync_mailboxes() {
+/// Sync each mailbox repo in `mode`. Pull-only visits every repo; otherwise
+/// only repos with local changes are synced. A broken mailbox (missing git,
+/// auth prompt, timeout) is logged and skipped so the loop keeps running.
+/// Returns how many drafts arrived for review.
+fn sync_mailboxes(mode: SyncMode, log: &WatchLog) -> usize {
     let config = match corky_config::try_load_config(None) {
         Some(c) => c,
-        None => return,
+        None => return 0,
     };
+    let mut review_count = 0;
     for name in config.mailboxes.keys() {
         let mb_path = resolve::mailbox_dir(name);
         if !mb_path.exists() || !mb_path.join(".git").exists() {
             continue;
         }
-        let output = std::process::Command::new("git")
-            .arg("-C")
-            .arg(mb_path.to_string_lossy().as_ref())
-            .arg("status")
-            .arg("--porcelain")
-            .output();
-        if let Ok(out) = output {
-            if !String::from_utf8_lossy(&out.stdout).trim().is_empty() {
-                let _ = crate::mailbox::sync::sync_one(name);
+        let sp = mb_path.to_string_lossy().to_string();
+        let dirty = crate::util::run_git(&["-C", &sp, "status", "--porcelain"])
+            .map(|(stdout, _, _)| !stdout.trim().is_empty());
+        let result = match dirty {
+            Ok(true) => crate::mailbox::sync::sync_one(name, mode, false),
+            Ok(false) if mode == SyncMode::PullOnly => {
+                crate::mailbox::sync::sync_one(name, mode, false)
             }
+            Ok(false) => Ok(Vec::new()),
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(drafts) => review_count += drafts.len(),
+            Err(e) => log.error(&format!("mailbox {}", name), &format!("{:#}", e)),
         }
     }
+    review_count
 }
 
 /// Run pending scheduled items (best-effort, never crashes the watch loop).
-fn schedule_tick() {
+fn schedule_tick(log: &WatchLog) {
     if let Err(e) = crate::schedule::run(false) {
-        eprintln!("schedule: {}", e);
+        log.error("schedule", &e.to_string());
+    }
+}
+
+/// Send the `[digest]` email when one is due (best-effort).
+fn digest_tick(log: &WatchLog) {
+    match crate::digest::run_if_due(chrono::Local::now()) {
+        Ok(Some(to)) => log.line(&format!("digest sent to {}", to)),
+        Ok(None) => {}
+        Err(e) => log.error("digest", &format!("{:#}", e)),
     }
 }
 
 /// Check for upgrade and self-restart if a newer version is available.
 /// Returns true if the process should restart (exec failed as fallback).
-fn try_auto_upgrade() -> bool {
+fn try_auto_upgrade(log: &WatchLog) -> bool {
     let latest = match crate::upgrade::check_for_update() {
         Some(v) => v,
         None => return false,
@@ -131,11 +347,12 @@ fn try_auto_upgrade() -> bool {
     );
 
     if let Err(e) = crate::upgrade::run() {
-        eprintln!("Auto-upgrade failed: {}", e);
+        log.error("upgrade", &format!("Auto-upgrade to {} failed: {}", latest, e));
         return false;
     }
 
     eprintln!("corky watch: restarting with new version...");
+    log.line(&format!("upgraded to {}, restarting", latest));
 
     // Re-exec self with the same arguments
     #[cfg(unix)]
@@ -148,7 +365,7 @@ fn try_auto_upgrade() -> bool {
         let args: Vec<String> = std::env::args().skip(1).collect();
         let err = std::process::Command::new(exe).args(&args).exec();
         // exec() only returns on error
-        eprintln!("exec failed: {}", err);
+        log.error("upgrade", &format!("exec failed: {}", err));
     }
 
     false
@@ -156,42 +373,59 @@ fn try_auto_upgrade() -> bool {
 
 /// Check for Gmail filter drift (best-effort, never crashes the watch loop).
 /// Uses non-interactive auth — never opens a browser.
-fn check_filter_drift() {
+fn check_filter_drift(log: &WatchLog) {
     match crate::filter::check::run_noninteractive(None) {
         Ok(true) => {} // in sync, no output needed
         Ok(false) => {
             eprintln!("corky watch: filter drift detected — run `corky filter push` to sync");
+            log.line("filter drift detected");
         }
         Err(e) => {
             let msg = e.to_string();
             if msg.contains("Run `corky filter auth`") {
-                eprintln!("corky watch: {}", msg);
+                log.error("filters", &msg);
             } else if !msg.contains("No [gmail] section") && !msg.contains("not found at") {
-                eprintln!("corky watch: filter check failed: {}", msg);
+                log.error("filters", &format!("filter check failed: {}", msg));
             }
         }
     }
 }
 
 /// One sync + mailbox sync cycle. Returns count of labels with new messages.
-fn poll_once(notify_enabled: bool) -> usize {
+fn poll_once(
+    notify_with: Option<Notify>,
+    mailbox_mode: Option<SyncMode>,
+    log: &WatchLog,
+) -> usize {
     let accounts = match load_accounts(None) {
         Ok(a) => a,
         Err(e) => {
-            eprintln!("Failed to load accounts: {}", e);
+            log.error("accounts", &format!("Failed to load accounts: {}", e));
             return 0;
         }
     };
 
-    let mut state = load_state();
+    // A corrupt state file falls back to its backup; with neither usable,
+    // skip the cycle rather than silently resync everything.
+    let mut state = match crate::sync::load_state() {
+        Ok(state) => state,
+        Err(e) => {
+            log.error("sync", &format!("{}", e));
+            return 0;
+        }
+    };
     let before = snapshot_uids(&state);
 
     for (acct_name, acct) in &accounts {
-        println!("\n=== Account: {} ({}) ===", acct_name, acct.user);
-        let password = match resolve_password(acct) {
-            Ok(p) => p,
+        crate::progress!("\n=== Account: {} ({}) ===", acct_name, acct.user);
+        crate::sync::auth::refresh_stored(acct_name, acct);
+        let secret = match account_secret(acct_name, acct) {
+            Ok(s) => s,
             Err(e) => {
-                eprintln!("  Error resolving password for {}: {}", acct_name, e);
+                log.error(
+                    &format!("account {}", acct_name),
+                    &format!("Error resolving credentials: {}", e),
+                );
                 continue;
             }
         };
@@ -201,45 +435,115 @@ fn poll_once(notify_enabled: bool) -> usize {
             acct.imap_port,
             acct.imap_starttls,
             &acct.user,
-            &password,
+            &secret,
             &acct.labels,
             acct.sync_days,
+            &acct.label_overrides,
             &mut state,
             false,
+            false,
             None,
             None,
         ) {
-            eprintln!("  Error syncing {}: {}", acct_name, e);
+            log.error(&format!("account {}", acct_name), &format!("Error syncing: {}", e));
             continue;
         }
     }
 
-    save_state(&state);
+    if let Err(e) = crate::sync::save_state(&state) {
+        log.error("sync", &format!("Saving sync state failed: {}", e));
+    }
+    if let Err(e) = provenance::flag_root_orphans(&state) {
+        log.error("sync", &format!("Flagging orphaned threads failed: {}", e));
+    }
+    crate::contact::threads::refresh_all();
 
     let after = snapshot_uids(&state);
     let new_count = count_new_messages(&before, &after);
 
     if new_count > 0 {
-        println!("\n{} label(s) with new messages", new_count);
-        sync_mailboxes();
-        if notify_enabled {
+        crate::progress!("\n{} label(s) with new messages", new_count);
+        log.line(&format!("poll: {} label(s) with new messages", new_count));
+        let review_count = mailbox_mode
+            .map(|mode| sync_mailboxes(mode, log))
+            .unwrap_or(0);
+        if let Some(settings) = notify_with {
+            let conversations = resolve::conversations_dir();
+            let ids = new_thread_ids(&state, &before);
+            let mail = match settings.detail {
+                NotifyDetail::Count => Vec::new(),
+                _ => newest_mail(&conversations, &ids, settings.count),
+            };
+            // One thread: open it; several: the conversations folder.
+            let target = match mail.as_slice() {
+                [one] if ids.len() == 1 => Some(one.path.as_path()),
+                [] => None,
+                _ => Some(conversations.as_path()),
+            };
             notify(
                 "corky",
-                &format!("{} label(s) with new messages", new_count),
+                &notification_body(new_count, ids.len(), &mail, settings.detail),
+                target,
+                settings.wait,
             );
+            if review_count > 0 {
+                notify(
+                    "corky",
+                    &format!("{} draft(s) ready for review", review_count),
+                    None,
+                    settings.wait,
+                );
+            }
         }
     } else {
-        println!("\nNo new messages");
+        crate::progress!("\nNo new messages");
+        log.line("poll: no new messages");
     }
 
     new_count
 }
 
+/// corky watch --once: one poll cycle (sync, mailbox sync, notifications,
+/// scheduled items, a due digest) for cron and systemd timers. Returns whether new mail
+/// arrived. Errors are logged as in the daemon, then reported together as
+/// a partial sync.
+pub fn run_once() -> Result<bool> {
+    let config = load_watch_config(None)?;
+    let mailbox_mode = parse_mailbox_sync(&config.mailbox_sync)?;
+    // The process exits right after the poll, taking a waiting thread with it
+    let notify_with = notify_settings(&config)?.map(|n| Notify { wait: false, ..n });
+    let log = WatchLog::new(
+        journal::watch_log_file(),
+        config.log_max_bytes,
+        config.log_keep,
+    );
+    log.line(&format!("once {}", env!("CARGO_PKG_VERSION")));
+    let new_count = poll_once(notify_with, mailbox_mode, &log);
+    schedule_tick(&log);
+    digest_tick(&log);
+    let errors = log.error_count();
+    if errors > 0 {
+        return Err(CorkyError::PartialSync(format!(
+            "{} error(s) during the poll; see `corky watch status`",
+            errors
+        ))
+        .into());
+    }
+    Ok(new_count > 0)
+}
+
 /// corky watch [--interval N]
 #[tokio::main]
 pub async fn run(interval_override: Option<u64>) -> Result<()> {
     let config = load_watch_config(None)?;
     let interval = interval_override.unwrap_or(config.poll_interval);
+    let mailbox_mode = parse_mailbox_sync(&config.mailbox_sync)?;
+    let notify_with = notify_settings(&config)?;
+    let log = WatchLog::new(
+        journal::watch_log_file(),
+        config.log_max_bytes,
+        config.log_keep,
+    );
 
     let shutdown = Arc::new(AtomicBool::new(false));
     let shutdown_clone = shutdown.clone();
@@ -248,17 +552,22 @@ pub async fn run(interval_override: Option<u64>) -> Result<()> {
     let (shutdown_tx, mut shutdown_rx) = tokio::sync::watch::channel(false);
     tokio::spawn(async move {
         tokio::signal::ctrl_c().await.ok();
-        println!("\nReceived signal, shutting down...");
+        crate::progress!("\nReceived signal, shutting down...");
         shutdown_clone.store(true, Ordering::Relaxed);
         let _ = shutdown_tx.send(true);
     });
 
     let auto_upgrade = config.auto_upgrade;
-    println!(
+    crate::progress!(
         "corky watch: polling every {}s{} (Ctrl-C to stop)",
         interval,
         if auto_upgrade { ", auto-upgrade on" } else { "" }
     );
+    log.line(&format!(
+        "started {} (interval {}s)",
+        env!("CARGO_PKG_VERSION"),
+        interval
+    ));
 
     let mut cycles_since_upgrade_check: u64 = 0;
     let mut cycles_since_filter_check: u64 = 0;
@@ -273,9 +582,9 @@ pub async fn run(interval_override: Option<u64>) -> Result<()> {
         }
 
         // Run sync in a blocking context
-        let notify_enabled = config.notify;
+        let poll_log = log.clone();
         tokio::task::spawn_blocking(move || {
-            poll_once(notify_enabled);
+            poll_once(notify_with, mailbox_mode, &poll_log);
         })
         .await?;
 
@@ -283,8 +592,13 @@ pub async fn run(interval_override: Option<u64>) -> Result<()> {
             break;
         }
 
-        // Scheduled publishing
-        tokio::task::spawn_blocking(schedule_tick).await?;
+        // Scheduled publishing and the digest email
+        let tick_log = log.clone();
+        tokio::task::spawn_blocking(move || {
+            schedule_tick(&tick_log);
+            digest_tick(&tick_log);
+        })
+        .await?;
 
         if shutdown.load(Ordering::Relaxed) {
             break;
@@ -295,7 +609,8 @@ pub async fn run(interval_override: Option<u64>) -> Result<()> {
             cycles_since_upgrade_check += 1;
             if cycles_since_upgrade_check >= upgrade_check_every {
                 cycles_since_upgrade_check = 0;
-                tokio::task::spawn_blocking(try_auto_upgrade).await?;
+                let upgrade_log = log.clone();
+                tokio::task::spawn_blocking(move || try_auto_upgrade(&upgrade_log)).await?;
                 // If we get here, exec() didn't happen (no upgrade or failed)
             }
         }
@@ -308,7 +623,8 @@ pub async fn run(interval_override: Option<u64>) -> Result<()> {
         cycles_since_filter_check += 1;
         if cycles_since_filter_check >= filter_check_every {
             cycles_since_filter_check = 0;
-            tokio::task::spawn_blocking(check_filter_drift).await?;
+            let filter_log = log.clone();
+            tokio::task::spawn_blocking(move || check_filter_drift(&filter_log)).await?;
         }
 
         if shutdown.load(Ordering::Relaxed) {
@@ -322,7 +638,8 @@ pub async fn run(interval_override: Option<u64>) -> Result<()> {
         }
     }
 
-    println!("corky watch: stopped");
+    crate::progress!("corky watch: stopped");
+    log.line("stopped");
     Ok(())
 }
 
@@ -436,4 +753,48 @@ mod tests {
         ]));
         assert_eq!(count_new_messages(&before, &after), 0);
     }
+
+    #[test]
+    fn parse_mailbox_sync_modes() {
+        assert_eq!(parse_mailbox_sync("both").unwrap(), Some(SyncMode::Both));
+        assert_eq!(parse_mailbox_sync("pull").unwrap(), Some(SyncMode::PullOnly));
+        assert_eq!(parse_mailbox_sync("push").unwrap(), Some(SyncMode::PushOnly));
+        assert_eq!(parse_mailbox_sync("off").unwrap(), None);
+        assert!(parse_mailbox_sync("sometimes").is_err());
+    }
+
+    #[test]
+    fn new_thread_ids_newest_uid_first() {
+        let before = snapshot_uids(&make_state(vec![
+            ("gmail", vec![("INBOX", 1, 100)]),
+        ]));
+        let mut state = make_state(vec![("gmail", vec![("INBOX", 1, 105)])]);
+        let threads = &mut state.accounts.get_mut("gmail").unwrap().threads;
+        for (id, uids) in [("old", vec![90]), ("lunch", vec![99, 101]), ("plans", vec![105])] {
+            threads.insert(id.to_string(), HashMap::from([("INBOX".to_string(), uids)]));
+        }
+        assert_eq!(new_thread_ids(&state, &before), vec!["plans", "lunch"]);
+    }
+
+    #[test]
+    fn notification_body_by_detail() {
+        let mail = vec![NewMail {
+            sender: "Alice".to_string(),
+            subject: "Lunch plans".to_string(),
+            path: PathBuf::from("lunch-plans.md"),
+        }];
+        assert_eq!(
+            notification_body(2, 3, &mail, NotifyDetail::Full),
+            "2 label(s) with new messages\nAlice: Lunch plans\n+2 more"
+        );
+        assert_eq!(
+            notification_body(1, 1, &mail, NotifyDetail::Sender),
+            "1 label(s) with new messages\nAlice"
+        );
+        assert_eq!(
+            notification_body(2, 3, &mail, NotifyDetail::Count),
+            "2 label(s) with new messages"
+        );
+        assert!(parse_notify_detail("everything").is_err());
+    }
 }
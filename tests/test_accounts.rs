@@ -6,8 +6,8 @@ use std::collections::HashMap;
 use tempfile::TempDir;
 
 use corky::accounts::{
-    self, get_account_for_email, get_default_account, load_accounts, load_owner,
-    load_watch_config, resolve_password, Account,
+    self, account_secret, get_account_for_email, get_default_account, load_accounts, load_owner,
+    load_watch_config, resolve_password, Account, LabelOverride, OAuthConfig, Secret,
 };
 
 #[test]
@@ -122,6 +122,40 @@ fn test_resolve_password_missing() {
     assert!(result.is_err());
 }
 
+#[test]
+fn test_account_secret_by_auth() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(
+        &path,
+        r#"
+[accounts.personal]
+provider = "gmail"
+user = "me@gmail.com"
+password = "app-password"
+
+[accounts.work]
+provider = "office365"
+user = "me@work.com"
+auth = "oauth2"
+
+[accounts.typo]
+user = "me@example.com"
+password = "x"
+auth = "oauth"
+"#,
+    )
+    .unwrap();
+    let accounts = load_accounts(Some(&path)).unwrap();
+    assert_eq!(accounts["work"].auth, "oauth2");
+    assert_eq!(
+        account_secret("personal", &accounts["personal"]).unwrap(),
+        Secret::Password("app-password".to_string())
+    );
+    let err = account_secret("typo", &accounts["typo"]).unwrap_err().to_string();
+    assert!(err.contains("Unknown auth 'oauth' for account 'typo'"), "{}", err);
+}
+
 #[test]
 fn test_get_default_account() {
     let mut accounts = HashMap::new();
@@ -278,6 +312,8 @@ fn test_load_watch_config_defaults() {
     let wc = load_watch_config(Some(&path)).unwrap();
     assert_eq!(wc.poll_interval, 300);
     assert!(!wc.notify);
+    assert_eq!(wc.log_max_bytes, 1024 * 1024);
+    assert_eq!(wc.log_keep, 3);
 }
 
 #[test]
@@ -290,6 +326,8 @@ fn test_load_watch_config_custom() {
 [watch]
 poll_interval = 60
 notify = true
+log_max_bytes = 0
+log_keep = 7
 "#,
     )
     .unwrap();
@@ -297,6 +335,8 @@ notify = true
     let wc = load_watch_config(Some(&path)).unwrap();
     assert_eq!(wc.poll_interval, 60);
     assert!(wc.notify);
+    assert_eq!(wc.log_max_bytes, 0);
+    assert_eq!(wc.log_keep, 7);
 }
 
 #[test]
@@ -323,6 +363,10 @@ fn test_account_defaults() {
     assert_eq!(acct.drafts_folder, "Drafts");
     assert_eq!(acct.sync_days, 3650);
     assert!(!acct.default);
+    assert!(!acct.bcc_self);
+    assert!(acct.archive_copy_folder.is_empty());
+    assert!(acct.web_url.is_empty());
+    assert!(acct.display_name.is_empty());
 }
 
 #[test]
@@ -350,6 +394,49 @@ imap_host = "custom.imap.host"
     assert_eq!(acct.smtp_host, "smtp.gmail.com");
 }
 
+#[test]
+fn test_label_overrides() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(
+        &path,
+        r#"
+[accounts.personal]
+provider = "gmail"
+user = "alice@gmail.com"
+password = "test"
+sync_days = 365
+labels = [
+    { name = "correspondence", days = 3650 },
+    { name = "inbox", days = 90, max_messages = 500 },
+    "sent",
+]
+"#,
+    )
+    .unwrap();
+
+    let accounts = load_accounts(Some(&path)).unwrap();
+    let acct = accounts.get("personal").unwrap();
+    assert_eq!(acct.labels, vec!["correspondence", "inbox", "sent"]);
+    assert_eq!(acct.label_overrides.len(), 2);
+    assert_eq!(
+        acct.label_overrides["inbox"],
+        LabelOverride {
+            days: Some(90),
+            max_messages: Some(500),
+        }
+    );
+    assert_eq!(acct.label_overrides["correspondence"].days, Some(3650));
+    assert!(!acct.label_overrides.contains_key("sent"));
+
+    // Adding a plain label keeps the table entries intact.
+    assert!(accounts::add_label_to_account("personal", "archive", Some(&path)).unwrap());
+    let accounts = load_accounts(Some(&path)).unwrap();
+    let acct = accounts.get("personal").unwrap();
+    assert_eq!(acct.labels.len(), 4);
+    assert_eq!(acct.label_overrides["inbox"].days, Some(90));
+}
+
 #[test]
 fn test_add_label_to_account() {
     let tmp = TempDir::new().unwrap();
@@ -375,6 +462,26 @@ labels = ["inbox"]
     assert!(acct.labels.contains(&"new-label".to_string()));
 }
 
+#[test]
+fn test_add_label_creates_missing_labels_key() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(
+        &path,
+        r#"
+[accounts.personal]
+provider = "gmail"
+user = "alice@gmail.com"
+password = "test"
+"#,
+    )
+    .unwrap();
+
+    assert!(accounts::add_label_to_account("personal", "Projects/2025", Some(&path)).unwrap());
+    let accounts = load_accounts(Some(&path)).unwrap();
+    assert_eq!(accounts["personal"].labels, vec!["Projects/2025"]);
+}
+
 #[test]
 fn test_add_label_already_exists() {
     let tmp = TempDir::new().unwrap();
@@ -440,3 +547,97 @@ labels = ["inbox"]
     assert_eq!(acct.smtp_port, 1025);
     assert_eq!(acct.drafts_folder, "Drafts");
 }
+
+#[test]
+fn test_sent_copy_options() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(
+        &path,
+        r#"
+[accounts.proton]
+provider = "protonmail-bridge"
+user = "user@proton.me"
+password = "bridge-password"
+bcc_self = true
+archive_copy_folder = "Folders/Sent copies"
+"#,
+    )
+    .unwrap();
+
+    let accounts = load_accounts(Some(&path)).unwrap();
+    let acct = accounts.get("proton").unwrap();
+    assert!(acct.bcc_self);
+    assert_eq!(acct.archive_copy_folder, "Folders/Sent copies");
+}
+
+#[test]
+fn test_display_name_from_address() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(
+        &path,
+        r#"
+[accounts.personal]
+provider = "gmail"
+user = "brian@example.com"
+display_name = "Brian T"
+
+[accounts.work]
+provider = "gmail"
+user = "brian@work.com"
+display_name = "Takita, Brian"
+
+[accounts.bare]
+provider = "gmail"
+user = "me@example.com"
+"#,
+    )
+    .unwrap();
+
+    let accounts = load_accounts(Some(&path)).unwrap();
+    assert_eq!(accounts["personal"].from_address(), "Brian T <brian@example.com>");
+    assert_eq!(accounts["work"].from_address(), "\"Takita, Brian\" <brian@work.com>");
+    assert_eq!(accounts["bare"].from_address(), "me@example.com");
+}
+
+#[test]
+fn test_oauth_config() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(
+        &path,
+        r#"
+[accounts.work]
+provider = "imap"
+user = "alice@contoso.com"
+imap_host = "outlook.office365.com"
+
+[accounts.work.oauth]
+provider = "microsoft"
+client_id = "00000000-aaaa-bbbb-cccc-000000000000"
+tenant = "contoso.onmicrosoft.com"
+flow = "device"
+
+[accounts.personal]
+provider = "gmail"
+user = "alice@gmail.com"
+password = "test"
+"#,
+    )
+    .unwrap();
+
+    let accounts = load_accounts(Some(&path)).unwrap();
+    let oauth = accounts["work"].oauth.as_ref().unwrap();
+    assert_eq!(
+        *oauth,
+        OAuthConfig {
+            provider: "microsoft".to_string(),
+            client_id: "00000000-aaaa-bbbb-cccc-000000000000".to_string(),
+            tenant: "contoso.onmicrosoft.com".to_string(),
+            flow: "device".to_string(),
+            ..Default::default()
+        }
+    );
+    assert!(accounts["personal"].oauth.is_none());
+}
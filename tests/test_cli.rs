@@ -61,6 +61,15 @@ fn test_cli_audit_docs_subcommand() {
     assert!(output.status.code().is_some());
 }
 
+#[test]
+fn test_cli_audit_docs_rejects_unknown_format() {
+    let mut cmd = corky_cmd();
+    cmd.args(["audit-docs", "--format", "yaml"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("Unsupported format"));
+}
+
 #[test]
 fn test_cli_init_requires_user() {
     let mut cmd = corky_cmd();
@@ -150,6 +159,197 @@ fn test_cli_sync_routes() {
         .exists());
 }
 
+#[test]
+fn test_cli_quiet_sync_routes_prints_nothing() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::create_dir_all(data_dir.join("conversations")).unwrap();
+    std::fs::write(
+        data_dir.join("conversations/test-thread.md"),
+        "# Test Thread\n\n\
+         **Labels**: for-alex\n\
+         **Thread ID**: test thread\n\
+         **Last updated**: Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+         ---\n\n\
+         ## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+         Hello there!\n",
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.join(".corky.toml"),
+        "[routing]\nfor-alex = [\"mailboxes/alex\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.args(["-q", "sync", "routes"]);
+    cmd.assert().success().stdout(predicate::str::is_empty());
+    assert!(data_dir
+        .join("mailboxes/alex/conversations/test-thread.md")
+        .exists());
+}
+
+#[test]
+fn test_cli_sync_routes_skips_unchanged_copies() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::create_dir_all(data_dir.join("conversations")).unwrap();
+    std::fs::write(
+        data_dir.join("conversations/test-thread.md"),
+        "# Test Thread\n\n\
+         **Labels**: for-alex\n\
+         **Thread ID**: test thread\n\
+         **Last updated**: Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+         ---\n\n\
+         ## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+         Hello there!\n",
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.join(".corky.toml"),
+        "[routing]\nfor-alex = [\"mailboxes/alex\"]\n",
+    )
+    .unwrap();
+    let routes = || {
+        let mut cmd = corky_cmd();
+        cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+        cmd.current_dir(&data_dir);
+        cmd.args(["sync", "routes"]);
+        cmd.assert().success()
+    };
+
+    routes().stdout(predicate::str::contains("1 file(s) copied, 0 unchanged"));
+    let copy = data_dir.join("mailboxes/alex/conversations/test-thread.md");
+    let written = std::fs::read_to_string(&copy).unwrap();
+    // The collaborator's edit to the copy survives: nothing new to merge
+    std::fs::write(&copy, written.replace("Hello there!", "Hello there! (seen)")).unwrap();
+    routes().stdout(predicate::str::contains("0 file(s) copied, 1 unchanged"));
+    assert!(std::fs::read_to_string(&copy).unwrap().contains("(seen)"));
+}
+
+#[test]
+fn test_cli_sync_routes_to_external_dir() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().join("data");
+    let vault = tmp.path().join("vault/Email");
+    std::fs::create_dir_all(data_dir.join("conversations")).unwrap();
+    std::fs::create_dir_all(&vault).unwrap();
+    std::fs::write(
+        data_dir.join("conversations/test-thread.md"),
+        "# Test Thread\n\n\
+         **Labels**: notes\n\
+         **Thread ID**: test thread\n\
+         **Last updated**: Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+         ---\n\n\
+         ## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+         Hello there!\n",
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.join(".corky.toml"),
+        "[routing]\nnotes = [\"~/vault/Email\"]\n",
+    )
+    .unwrap();
+
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.env("HOME", tmp.path().to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.args(["sync", "routes"]);
+    cmd.assert().success();
+    // External targets are used as-is, without conversations/
+    assert!(vault.join("test-thread.md").exists());
+
+    // A missing external target is a config error, not created on the fly
+    std::fs::remove_dir_all(&vault).unwrap();
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.env("HOME", tmp.path().to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.args(["sync", "routes"]);
+    cmd.assert()
+        .failure()
+        .stderr(predicate::str::contains("[routing] target for 'notes' does not exist"));
+    assert!(!vault.exists());
+}
+
+#[test]
+fn test_cli_thread_list_filters_by_link() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::create_dir_all(data_dir.join("conversations")).unwrap();
+    let thread = |subject: &str, body: &str| {
+        format!(
+            "# {}\n\n**Labels**: inbox\n**Thread ID**: {}\n\
+             **Last updated**: Mon, 10 Feb 2025 10:00:00 +0000\n\n---\n\n\
+             ## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\n{}\n",
+            subject, subject, body
+        )
+    };
+    std::fs::write(
+        data_dir.join("conversations/crash.md"),
+        thread("Crash on start", "Filed https://github.com/acme/app/issues/12 for this."),
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.join("conversations/lunch.md"),
+        thread("Lunch", "Tuesday works, see [#3]."),
+    )
+    .unwrap();
+
+    let list = |args: &[&str]| {
+        let mut cmd = corky_cmd();
+        cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+        cmd.current_dir(&data_dir);
+        cmd.args(["thread", "list", "."]).args(args);
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    };
+    let out = list(&["--link", "github.com/acme/app"]);
+    assert!(out.contains("crash.md"));
+    assert!(out.contains("https://github.com/acme/app/issues/12"));
+    assert!(!out.contains("lunch.md"));
+    let out = list(&["--link", "#3"]);
+    assert!(out.contains("lunch.md") && !out.contains("crash.md"));
+    assert!(list(&["--link", "gitlab.com"]).contains("No threads link to gitlab.com."));
+}
+
+#[test]
+fn test_cli_thread_pin_lists_first() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::create_dir_all(data_dir.join("conversations")).unwrap();
+    for (slug, subject) in [("crash", "Crash on start"), ("lunch", "Lunch")] {
+        std::fs::write(
+            data_dir.join(format!("conversations/{}.md", slug)),
+            format!("# {}\n\n**Labels**: inbox\n**Thread ID**: {}\n", subject, slug),
+        )
+        .unwrap();
+    }
+    let run = |args: &[&str]| {
+        let mut cmd = corky_cmd();
+        cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+        cmd.current_dir(&data_dir);
+        cmd.args(args);
+        String::from_utf8(cmd.assert().success().get_output().stdout.clone()).unwrap()
+    };
+
+    assert!(run(&["thread", "pin", "lunch"]).contains("Pinned: lunch.md"));
+    assert!(run(&["thread", "pin", "lunch"]).contains("Already pinned: lunch.md"));
+    let out = run(&["thread", "list", "."]);
+    let (lunch, crash) = (out.find("lunch.md").unwrap(), out.find("crash.md").unwrap());
+    assert!(lunch < crash);
+    assert!(out.contains("* Lunch"));
+    let manifest = std::fs::read_to_string(data_dir.join("manifest.toml")).unwrap();
+    assert!(manifest.contains("pinned = true"));
+    assert!(run(&["status"]).contains("  lunch.md  Lunch"));
+
+    assert!(run(&["thread", "unpin", "lunch"]).contains("Unpinned: lunch.md"));
+    let out = run(&["thread", "list", "."]);
+    assert!(out.find("crash.md").unwrap() < out.find("lunch.md").unwrap());
+}
+
 #[test]
 fn test_cli_unknown_subcommand() {
     let mut cmd = corky_cmd();
@@ -209,3 +409,293 @@ labels = ["inbox"]
     cmd.args(["sync", "account", "nonexistent"]);
     cmd.assert().failure();
 }
+
+#[test]
+fn test_cli_newsletters_and_unanswered_exclusion() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+
+    std::fs::create_dir_all(data_dir.join("conversations")).unwrap();
+    std::fs::write(data_dir.join(".corky.toml"), "").unwrap();
+    std::fs::write(
+        data_dir.join("conversations/weekly-digest.md"),
+        "# Weekly Digest\n\n\
+         **Labels**: inbox\n\
+         **Accounts**: personal\n\
+         **Tags**: newsletter\n\
+         **Thread ID**: weekly digest\n\
+         **Last updated**: Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+         ---\n\n\
+         ## News <news@example.com> \u{2014} Mon, 10 Feb 2025 10:00:00 +0000\n\n\
+         **List-Unsubscribe**: <mailto:leave@example.com>, <https://example.com/u>\n\n\
+         This week...\n",
+    )
+    .unwrap();
+
+    let mut cmd = corky_cmd();
+    cmd.current_dir(&data_dir);
+    cmd.arg("newsletters");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("weekly-digest.md"))
+        .stdout(predicate::str::contains("Unsubscribe: mailto:leave@example.com"))
+        .stdout(predicate::str::contains("Unsubscribe: https://example.com/u"));
+
+    let mut cmd = corky_cmd();
+    cmd.current_dir(&data_dir);
+    cmd.args(["unanswered", ".", "--from", "me"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("No unanswered threads found."));
+
+    let mut cmd = corky_cmd();
+    cmd.current_dir(&data_dir);
+    cmd.args(["unanswered", ".", "--from", "me", "--include-newsletters"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("weekly-digest.md"));
+}
+
+#[test]
+fn test_cli_draft_validate_failure_exit_code() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let draft = dir.path().join("bad.md");
+    std::fs::write(&draft, "# No metadata\n").unwrap();
+    let mut cmd = corky_cmd();
+    cmd.args(["draft", "validate"]).arg(&draft);
+    cmd.assert().code(5);
+}
+
+#[test]
+fn test_cli_draft_validate_invalid_address() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let draft = dir.path().join("bad-to.md");
+    let text = "---\nto: \"Smith, Bob\" <bob@example.com>, alice@\nstatus: review\nauthor: Me\n---\n\n# Hi\n\nBody\n";
+    std::fs::write(&draft, text).unwrap();
+    let mut cmd = corky_cmd();
+    cmd.args(["--no-color", "draft", "validate"]).arg(&draft);
+    cmd.assert()
+        .code(5)
+        .stdout(predicate::str::contains("Invalid address in to: alice@"))
+        .stdout(predicate::str::contains("bob@example.com").not());
+}
+
+#[test]
+fn test_cli_draft_validate_accepts_group_names() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let data_dir = dir.path().join("data");
+    std::fs::create_dir_all(&data_dir).unwrap();
+    std::fs::write(
+        data_dir.join(".corky.toml"),
+        "[groups]\nteam-leads = [\"alex@example.com\", \"sam@example.com\"]\n",
+    )
+    .unwrap();
+    let draft = data_dir.join("group.md");
+    let text = "---\nto: team-leads\ncc: team-lead\nstatus: review\nauthor: Me\n---\n\n# Hi\n\nBody\n";
+    std::fs::write(&draft, text).unwrap();
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.args(["--no-color", "draft", "validate"]).arg(&draft);
+    cmd.assert()
+        .code(5)
+        .stdout(predicate::str::contains("Invalid address in cc: team-lead"))
+        .stdout(predicate::str::contains("in to:").not());
+}
+
+#[test]
+fn test_cli_draft_validate_directory_quiet() {
+    let dir = tempfile::TempDir::new().unwrap();
+    let drafts = dir.path().join("drafts");
+    std::fs::create_dir_all(drafts.join("nested")).unwrap();
+    std::fs::create_dir_all(drafts.join("templates")).unwrap();
+    let good = "---\nto: a@example.com\nstatus: review\nauthor: Me\n---\n\n# Hi\n\nBody\n";
+    std::fs::write(drafts.join("good.md"), good).unwrap();
+    std::fs::write(drafts.join("nested/bad.md"), "# No metadata\n").unwrap();
+    std::fs::write(drafts.join("templates/intro.md"), "Hi {{to_name}}\n").unwrap();
+
+    let mut cmd = corky_cmd();
+    cmd.args(["--no-color", "draft", "validate", "--quiet"]).arg(&drafts);
+    cmd.assert()
+        .code(5)
+        .stdout(predicate::str::contains("bad.md:"))
+        .stdout(predicate::str::contains("good.md").not())
+        .stdout(predicate::str::contains("intro.md").not());
+
+    let mut cmd = corky_cmd();
+    cmd.args(["--no-color", "validate-draft"]).arg(&drafts);
+    cmd.assert()
+        .code(5)
+        .stdout(predicate::str::contains("good.md: OK"))
+        .stdout(predicate::str::is_match(r"Files\s+2\n").unwrap())
+        .stdout(predicate::str::is_match(r"Errors\s+1\n").unwrap());
+}
+
+#[test]
+fn test_cli_init_existing_config_exit_code() {
+    let dir = tempfile::TempDir::new().unwrap();
+    std::fs::create_dir_all(dir.path().join("mail")).unwrap();
+    std::fs::write(dir.path().join("mail/.corky.toml"), "").unwrap();
+    let mut cmd = corky_cmd();
+    cmd.args(["init", "--user", "me@example.com", "--no-detect"]).arg(dir.path());
+    cmd.assert()
+        .code(3)
+        .stderr(predicate::str::contains("already exists"));
+}
+
+#[test]
+fn test_cli_contact_reset_keeps_filled_sections() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::create_dir_all(data_dir.join("contacts/alice")).unwrap();
+    std::fs::create_dir_all(data_dir.join("templates")).unwrap();
+    std::fs::write(
+        data_dir.join(".corky.toml"),
+        "[contacts.alice]\nemails = [\"alice@example.com\"]\n",
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.join("contacts/alice/AGENTS.md"),
+        "# Contact: alice\n\n## Relationship\n\nMet at RustConf.\n\n## Notes\n\n<!-- old -->\n",
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.join("templates/contact-AGENTS.md"),
+        "# Contact: {name}\n\n## Relationship\n\n<!-- hint -->\n\n## Notes\n\n<!-- new -->\n",
+    )
+    .unwrap();
+
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.args(["contact", "reset", "alice"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("alice: updated AGENTS.md"));
+
+    let text = std::fs::read_to_string(data_dir.join("contacts/alice/AGENTS.md")).unwrap();
+    assert_eq!(
+        text,
+        "# Contact: alice\n\n## Relationship\n\nMet at RustConf.\n\n## Notes\n\n<!-- new -->\n"
+    );
+}
+
+#[test]
+fn test_cli_watch_status_shows_recent_errors() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::create_dir_all(data_dir.join("logs")).unwrap();
+    std::fs::write(
+        data_dir.join("logs/watch.log"),
+        "2025-02-10T10:00:00Z started 0.1.0 (interval 300s)\n2025-02-10T10:00:05Z poll: no new messages\n",
+    )
+    .unwrap();
+    std::fs::write(
+        data_dir.join("logs/errors.jsonl"),
+        "{\"time\":\"2025-02-10T09:00:00Z\",\"source\":\"account work\",\"message\":\"old failure\"}\n\
+         {\"time\":\"2025-02-10T10:00:03Z\",\"source\":\"mailbox alex\",\"message\":\"git pull timed out\"}\n",
+    )
+    .unwrap();
+
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.args(["watch", "status", "-n", "1"]);
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Last activity: 2025-02-10T10:00:05Z poll: no new messages"))
+        .stdout(predicate::str::contains("mailbox alex: git pull timed out"))
+        .stdout(predicate::str::contains("old failure").not());
+
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.arg("status");
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Last sync: never"))
+        .stdout(predicate::str::contains("Recent errors (2):"));
+}
+
+#[test]
+fn test_cli_watch_once_reports_cycle_errors() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::write(
+        data_dir.join(".corky.toml"),
+        "[accounts.work]\n\
+         user = \"me@example.com\"\n\
+         imap_host = \"127.0.0.1\"\n\
+         password_cmd = \"false\"\n",
+    )
+    .unwrap();
+
+    // The password can't be resolved: the cycle logs it and exits 6
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.args(["watch", "--once"]);
+    cmd.assert()
+        .code(6)
+        .stderr(predicate::str::contains("error(s) during the poll"));
+    let log = std::fs::read_to_string(data_dir.join("logs/watch.log")).unwrap();
+    assert!(log.contains("ERROR account work"));
+
+    let mut cmd = corky_cmd();
+    cmd.args(["watch", "--once", "--interval", "60"]);
+    cmd.assert().code(2);
+}
+
+#[test]
+fn test_cli_mailbox_verify_reports_missing_dirs() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::write(data_dir.join(".corky.toml"), "[mailboxes.alex]\n[mailboxes.sam]\n").unwrap();
+    for name in ["alex", "sam"] {
+        let mb = data_dir.join("mailboxes").join(name);
+        std::fs::create_dir_all(mb.join("conversations")).unwrap();
+        std::fs::write(mb.join("AGENTS.md"), "# Mailbox\n").unwrap();
+        std::fs::write(mb.join("README.md"), "# Mailbox\n").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("AGENTS.md", mb.join("CLAUDE.md")).unwrap();
+    }
+    std::fs::create_dir_all(data_dir.join("mailboxes/sam/drafts")).unwrap();
+
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.args(["--no-color", "mailbox", "verify"]);
+    cmd.assert()
+        .code(5)
+        .stdout(predicate::str::contains("alex:\n  ERROR: Missing directory: drafts/"))
+        .stdout(predicate::str::contains("sam: OK"));
+}
+
+#[test]
+fn test_cli_restricted_refuses_send_and_label_clear() {
+    let tmp = tempfile::TempDir::new().unwrap();
+    let data_dir = tmp.path().to_path_buf();
+    std::fs::create_dir_all(data_dir.join("drafts")).unwrap();
+    std::fs::write(data_dir.join(".corky.toml"), "").unwrap();
+    let draft = data_dir.join("drafts/reply.md");
+    std::fs::write(&draft, "---\nto: alice@example.com\nstatus: approved\n---\n\n# Hi\n\nBody.\n")
+        .unwrap();
+
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.current_dir(&data_dir);
+    cmd.args(["--restricted", "draft", "push"]);
+    cmd.arg(&draft);
+    cmd.arg("--send");
+    cmd.assert()
+        .code(9)
+        .stderr(predicate::str::contains("Sending email is disabled in restricted mode"));
+
+    let mut cmd = corky_cmd();
+    cmd.env("CORKY_DATA", data_dir.to_string_lossy().as_ref());
+    cmd.env("CORKY_RESTRICTED", "1");
+    cmd.current_dir(&data_dir);
+    cmd.args(["label", "clear", "old-label"]);
+    cmd.assert()
+        .code(9)
+        .stderr(predicate::str::contains("disabled in restricted mode"));
+}
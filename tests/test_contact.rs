@@ -4,7 +4,7 @@ mod common;
 
 use tempfile::TempDir;
 
-use corky::config::contact::{self, Contact};
+use corky::config::contact::{self, Contact, ContactIndex};
 
 #[test]
 fn test_load_contacts_empty_file() {
@@ -243,3 +243,55 @@ user = "test@gmail.com"
     assert!(content.contains("[contacts.alice]"));
     assert!(content.contains("\"alice@example.com\""));
 }
+
+#[test]
+fn test_concurrent_saves_keep_every_contact() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(&path, "[owner]\nname = \"Me\"\n").unwrap();
+
+    let handles: Vec<_> = (0..8)
+        .map(|i| {
+            let path = path.clone();
+            std::thread::spawn(move || {
+                let c = Contact {
+                    emails: vec![format!("user{}@example.com", i)],
+                    ..Default::default()
+                };
+                contact::save_contact(&format!("user{}", i), &c, Some(&path)).unwrap();
+            })
+        })
+        .collect();
+    for h in handles {
+        h.join().unwrap();
+    }
+
+    let contacts = contact::load_contacts(Some(&path)).unwrap();
+    assert_eq!(contacts.len(), 8);
+    assert!(std::fs::read_to_string(&path).unwrap().contains("name = \"Me\""));
+}
+
+#[test]
+fn test_contact_index_wildcard_domain() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(
+        &path,
+        "[contacts.clientcorp]\nemails = [\"*@clientcorp.com\"]\n\n\
+         [contacts.dana]\nemails = [\"dana@clientcorp.com\"]\n",
+    )
+    .unwrap();
+    let contacts = contact::load_contacts(Some(&path)).unwrap();
+    assert_eq!(contacts["clientcorp"].first_address(), None);
+    assert_eq!(
+        contacts["dana"].first_address().map(String::as_str),
+        Some("dana@clientcorp.com")
+    );
+
+    let index = ContactIndex::new(&contacts);
+    // The contact listing the address itself wins over the wildcard
+    assert_eq!(index.lookup("Dana <Dana+x@clientcorp.com>"), Some("dana"));
+    assert_eq!(index.lookup("Jo <jo@CLIENTCORP.com>"), Some("clientcorp"));
+    assert_eq!(index.lookup("jo@eu.clientcorp.com"), None);
+    assert_eq!(index.lookup("jo@example.com"), None);
+}
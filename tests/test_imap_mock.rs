@@ -0,0 +1,854 @@
+//! End-to-end IMAP tests against the in-process mock server.
+//!
+//! Run with: cargo test --features testserver --test test_imap_mock
+
+#![cfg(feature = "testserver")]
+
+use corky::accounts::{LabelOverride, Secret};
+use corky::draft::push_to_drafts;
+use corky::sync::folders;
+use corky::sync::imap_sync::{connect_imap_pub, sync_account, HEADERS_ONLY_BODY};
+use corky::sync::markdown::parse_thread_markdown;
+use corky::sync::provenance::orphaned_threads;
+use corky::sync::report::LabelReport;
+use corky::sync::types::SyncState;
+use corky::testserver::MockImapServer;
+use std::collections::HashMap;
+use tempfile::TempDir;
+
+const USER: &str = "me@example.com";
+const PASSWORD: &str = "secret";
+
+fn raw_message(from: &str, subject: &str, date: &str, message_id: &str, body: &str) -> Vec<u8> {
+    format!(
+        "From: {from}\r\nTo: {USER}\r\nSubject: {subject}\r\nDate: {date}\r\n\
+         Message-ID: {message_id}\r\n\r\n{body}\r\n"
+    )
+    .into_bytes()
+}
+
+fn sync(
+    server: &MockImapServer,
+    state: &mut SyncState,
+    dir: &TempDir,
+) -> anyhow::Result<Vec<LabelReport>> {
+    sync_account(
+        "mock",
+        "127.0.0.1",
+        server.port(),
+        false,
+        USER,
+        &PASSWORD.into(),
+        &["INBOX".to_string()],
+        3650,
+        &HashMap::new(),
+        state,
+        false,
+        false,
+        Some(dir.path()),
+        None,
+    )
+}
+
+fn md_files(dir: &TempDir) -> Vec<std::path::PathBuf> {
+    let mut files: Vec<_> = std::fs::read_dir(dir.path())
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| p.extension().and_then(|e| e.to_str()) == Some("md"))
+        .collect();
+    files.sort();
+    files
+}
+
+#[test]
+fn test_sync_account_full_then_incremental() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 7);
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Alice <alice@example.com>",
+            "Project kickoff",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "<kickoff-1@example.com>",
+            "Shall we start Monday?",
+        ),
+    );
+
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    let reports = sync(&server, &mut state, &dir).unwrap();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].label, "INBOX");
+    assert_eq!(reports[0].new_messages, 1);
+    assert_eq!(reports[0].new_threads, 1);
+    assert_eq!(reports[0].updated_threads, 0);
+
+    let files = md_files(&dir);
+    assert_eq!(files.len(), 1);
+    let thread = parse_thread_markdown(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+    assert_eq!(thread.subject, "Project kickoff");
+    assert_eq!(thread.messages.len(), 1);
+    assert_eq!(thread.messages[0].message_id, "<kickoff-1@example.com>");
+
+    let label_state = &state.accounts["mock"].labels["INBOX"];
+    assert_eq!(label_state.uidvalidity, 7);
+    assert_eq!(label_state.last_uid, 1);
+
+    // A reply arrives; incremental sync fetches only the new UID.
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Bob <bob@example.com>",
+            "Re: Project kickoff",
+            "Tue, 11 Feb 2025 09:00:00 +0000",
+            "<kickoff-2@example.com>",
+            "Monday works.",
+        ),
+    );
+    let reports = sync(&server, &mut state, &dir).unwrap();
+    assert_eq!(reports[0].new_messages, 1);
+    assert_eq!(reports[0].new_threads, 0);
+    assert_eq!(reports[0].updated_threads, 1);
+
+    let files = md_files(&dir);
+    assert_eq!(files.len(), 1);
+    let thread = parse_thread_markdown(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+    assert_eq!(thread.messages.len(), 2);
+    assert_eq!(state.accounts["mock"].labels["INBOX"].last_uid, 2);
+    assert!(server.log().iter().any(|l| l.ends_with("UID SEARCH UID 2:*")));
+
+    // UIDNEXT shows nothing new: no search for new UIDs at all.
+    sync(&server, &mut state, &dir).unwrap();
+    assert!(!server.log().iter().any(|l| l.ends_with("UID SEARCH UID 3:*")));
+    assert_eq!(state.accounts["mock"].labels["INBOX"].last_uid, 2);
+}
+
+#[test]
+fn test_sync_threads_by_references() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 7);
+    // `day` is e.g. "Mon, 10": the rest of the date is fixed.
+    let add = |subject: &str, day: &str, id: &str, refs: &str| {
+        let date = format!("{day} Feb 2025 10:00:00 +0000");
+        let from = "Acme Billing <billing@acme.example>";
+        let mut raw = raw_message(from, subject, &date, id, "See attached.");
+        if !refs.is_empty() {
+            let headers = format!("In-Reply-To: {refs}\r\nReferences: {refs}\r\n");
+            raw = [headers.into_bytes(), raw].concat();
+        }
+        server.add_message("INBOX", &raw);
+    };
+    // Two unrelated invoices with the same subject, a reply to the second,
+    // and a reply to the first under a new subject
+    add("Invoice", "Mon, 10", "<inv-1@acme.example>", "");
+    add("Invoice", "Mon, 17", "<inv-2@acme.example>", "");
+    add("Re: Invoice", "Tue, 18", "<reply-2@acme.example>", "<inv-2@acme.example>");
+    add("Payment plan", "Wed, 19", "<plan-1@acme.example>", "<inv-1@acme.example>");
+
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    sync(&server, &mut state, &dir).unwrap();
+
+    assert_eq!(md_files(&dir).len(), 2);
+    let read = |name: &str| {
+        let text = std::fs::read_to_string(dir.path().join(name)).unwrap();
+        parse_thread_markdown(&text).unwrap()
+    };
+    let ids = |thread: &corky::sync::types::Thread| -> Vec<String> {
+        thread.messages.iter().map(|m| m.message_id.clone()).collect()
+    };
+    let first = read("invoice.md");
+    assert_eq!(first.id, "invoice");
+    assert_eq!(ids(&first), vec!["<inv-1@acme.example>", "<plan-1@acme.example>"]);
+    let second = read("invoice-2.md");
+    assert_eq!(second.id, "invoice <inv-2@acme.example>");
+    assert_eq!(ids(&second), vec!["<inv-2@acme.example>", "<reply-2@acme.example>"]);
+
+    // A reply without references falls back to the subject
+    add("RE: Invoice", "Thu, 20", "<bare@acme.example>", "");
+    sync(&server, &mut state, &dir).unwrap();
+    assert_eq!(md_files(&dir).len(), 2);
+    assert_eq!(read("invoice.md").messages.len(), 3);
+
+    // A full resync finds every message in the thread it was stored in
+    sync(&server, &mut SyncState::default(), &dir).unwrap();
+    assert_eq!(md_files(&dir).len(), 2);
+    assert_eq!(read("invoice-2.md").messages.len(), 2);
+}
+
+#[test]
+fn test_sync_account_headers_only() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 7);
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Alice <alice@example.com>",
+            "Archive",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "<archive-1@example.com>",
+            "A long body we don't want yet.",
+        ),
+    );
+
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    sync_account(
+        "mock",
+        "127.0.0.1",
+        server.port(),
+        false,
+        USER,
+        &PASSWORD.into(),
+        &["INBOX".to_string()],
+        3650,
+        &HashMap::new(),
+        &mut state,
+        false,
+        true,
+        Some(dir.path()),
+        None,
+    )
+    .unwrap();
+
+    let files = md_files(&dir);
+    let thread = parse_thread_markdown(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+    assert_eq!(thread.messages[0].from, "Alice <alice@example.com>");
+    assert_eq!(thread.messages[0].message_id, "<archive-1@example.com>");
+    assert_eq!(thread.messages[0].body, HEADERS_ONLY_BODY);
+    assert!(server.log().iter().any(|l| l.contains("BODY.PEEK[HEADER]")));
+    assert!(!server.log().iter().any(|l| l.contains("BODY.PEEK[]")));
+    // Provenance is recorded so `hydrate` can find the message later
+    assert_eq!(state.accounts["mock"].threads[&thread.id]["INBOX"], vec![1]);
+}
+
+#[test]
+fn test_sync_account_resyncs_when_uidnext_goes_backwards() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 7);
+    for n in 1..=3 {
+        server.add_message(
+            "INBOX",
+            &raw_message(
+                "Alice <alice@example.com>",
+                &format!("Note {}", n),
+                "Mon, 10 Feb 2025 10:00:00 +0000",
+                &format!("<note-{}@example.com>", n),
+                "Hi",
+            ),
+        );
+    }
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    sync(&server, &mut state, &dir).unwrap();
+    assert_eq!(state.accounts["mock"].labels["INBOX"].last_uid, 3);
+
+    // Same UIDVALIDITY, but the folder was rebuilt and UIDs restart at 1.
+    server.add_mailbox("INBOX", 7);
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Bob <bob@example.com>",
+            "Fresh start",
+            "Tue, 11 Feb 2025 10:00:00 +0000",
+            "<fresh@example.com>",
+            "Hello again",
+        ),
+    );
+    sync(&server, &mut state, &dir).unwrap();
+    assert_eq!(state.accounts["mock"].labels["INBOX"].last_uid, 1);
+    assert_eq!(md_files(&dir).len(), 4);
+}
+
+#[test]
+fn test_incremental_sync_tracks_provenance() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 7);
+    let kickoff = server.add_message(
+        "INBOX",
+        &raw_message(
+            "Alice <alice@example.com>",
+            "Project kickoff",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "<kickoff-1@example.com>",
+            "Shall we start Monday?",
+        ),
+    );
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Bob <bob@example.com>",
+            "Budget",
+            "Mon, 10 Feb 2025 11:00:00 +0000",
+            "<budget-1@example.com>",
+            "Numbers attached.",
+        ),
+    );
+
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    sync(&server, &mut state, &dir).unwrap();
+    let threads = &state.accounts["mock"].threads;
+    assert_eq!(threads["project kickoff"]["INBOX"], vec![kickoff]);
+    assert!(orphaned_threads(&state).is_empty());
+
+    // Expunged elsewhere: the next incremental sync notices and flags it.
+    server.remove_message("INBOX", kickoff);
+    sync(&server, &mut state, &dir).unwrap();
+    let orphans: Vec<String> = orphaned_threads(&state).into_iter().collect();
+    assert_eq!(orphans, vec!["project kickoff"]);
+    assert_eq!(md_files(&dir).len(), 2);
+}
+
+#[test]
+fn test_label_overrides_limit_full_sync() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 7);
+    for (i, subject) in ["Oldest", "Middle", "Newest"].iter().enumerate() {
+        server.add_message(
+            "INBOX",
+            &raw_message(
+                "Alice <alice@example.com>",
+                subject,
+                &format!("Mon, 1{} Feb 2025 10:00:00 +0000", i),
+                &format!("<msg-{}@example.com>", i),
+                "Hello",
+            ),
+        );
+    }
+    let overrides = HashMap::from([(
+        "INBOX".to_string(),
+        LabelOverride {
+            days: Some(90),
+            max_messages: Some(2),
+        },
+    )]);
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    sync_account(
+        "mock",
+        "127.0.0.1",
+        server.port(),
+        false,
+        USER,
+        &PASSWORD.into(),
+        &["INBOX".to_string()],
+        3650,
+        &overrides,
+        &mut state,
+        false,
+        false,
+        Some(dir.path()),
+        None,
+    )
+    .unwrap();
+
+    let since = (chrono::Utc::now() - chrono::Duration::days(90))
+        .format("%d-%b-%Y")
+        .to_string();
+    assert!(
+        server
+            .log()
+            .iter()
+            .any(|l| l.contains(&format!("UID SEARCH SINCE {}", since)))
+    );
+    let subjects: Vec<String> = md_files(&dir)
+        .iter()
+        .map(|f| {
+            parse_thread_markdown(&std::fs::read_to_string(f).unwrap())
+                .unwrap()
+                .subject
+        })
+        .collect();
+    assert_eq!(subjects, vec!["Middle", "Newest"]);
+    assert_eq!(state.accounts["mock"].labels["INBOX"].last_uid, 3);
+}
+
+#[test]
+fn test_sync_leaves_messages_unread() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 7);
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Alice <alice@example.com>",
+            "Project kickoff",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "<kickoff-1@example.com>",
+            "Shall we start Monday?",
+        ),
+    );
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    sync(&server, &mut state, &dir).unwrap();
+
+    assert_eq!(md_files(&dir).len(), 1);
+    let log = server.log();
+    assert!(log.iter().any(|l| l.contains("UID FETCH") && l.contains("BODY.PEEK[]")));
+    assert!(!log.iter().any(|l| l.contains("RFC822")));
+    assert!(server.messages("INBOX")[0].flags.is_empty());
+}
+
+#[test]
+fn test_full_resync_skips_stored_bodies() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 7);
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Alice <alice@example.com>",
+            "Project kickoff",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "<kickoff-1@example.com>",
+            "Shall we start Monday?",
+        ),
+    );
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    sync(&server, &mut state, &dir).unwrap();
+    let body_fetches = |server: &MockImapServer| {
+        server
+            .log()
+            .iter()
+            .filter(|l| l.contains("UID FETCH") && l.contains("BODY.PEEK[]"))
+            .count()
+    };
+    assert_eq!(body_fetches(&server), 1);
+
+    let mut state = SyncState::default();
+    sync_account(
+        "mock",
+        "127.0.0.1",
+        server.port(),
+        false,
+        USER,
+        &PASSWORD.into(),
+        &["INBOX".to_string()],
+        3650,
+        &HashMap::new(),
+        &mut state,
+        true,
+        false,
+        Some(dir.path()),
+        None,
+    )
+    .unwrap();
+    assert_eq!(body_fetches(&server), 1);
+    assert!(server.log().iter().any(|l| l.ends_with("UID FETCH 1 (UID ENVELOPE)")));
+    let files = md_files(&dir);
+    assert_eq!(files.len(), 1);
+    let thread = parse_thread_markdown(&std::fs::read_to_string(&files[0]).unwrap()).unwrap();
+    assert_eq!(thread.messages.len(), 1);
+    assert_eq!(state.accounts["mock"].labels["INBOX"].last_uid, 1);
+}
+
+#[test]
+fn test_sync_account_rejects_bad_password() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    let dir = TempDir::new().unwrap();
+    let mut state = SyncState::default();
+    let result = sync_account(
+        "mock",
+        "127.0.0.1",
+        server.port(),
+        false,
+        USER,
+        &"wrong".into(),
+        &["INBOX".to_string()],
+        3650,
+        &HashMap::new(),
+        &mut state,
+        false,
+        false,
+        Some(dir.path()),
+        None,
+    );
+    assert!(result.is_err());
+    assert!(md_files(&dir).is_empty());
+}
+
+#[test]
+fn test_cli_sync_continues_past_failed_account() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Alice <alice@example.com>",
+            "Project kickoff",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "<kickoff-1@example.com>",
+            "Shall we start Monday?",
+        ),
+    );
+    let data = TempDir::new().unwrap();
+    let account = |name: &str, password: &str| {
+        format!(
+            "[accounts.{name}]\nuser = \"{USER}\"\npassword = \"{password}\"\n\
+             imap_host = \"127.0.0.1\"\nimap_port = {}\nimap_starttls = false\n\
+             labels = [\"INBOX\"]\n\n",
+            server.port()
+        )
+    };
+    std::fs::write(
+        data.path().join(".corky.toml"),
+        account("broken", "wrong") + &account("good", PASSWORD),
+    )
+    .unwrap();
+
+    let run = |args: &[&str]| {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("corky");
+        cmd.env("CORKY_DATA", data.path());
+        cmd.current_dir(data.path());
+        cmd.args(args);
+        cmd.assert()
+    };
+    run(&["sync"])
+        .code(8)
+        .stderr(predicates::str::contains("1 of 2 account(s) failed: broken"));
+    assert!(data.path().join("conversations/project-kickoff.md").exists());
+
+    // --fail-fast stops at the first failure, with its own exit code
+    run(&["sync", "--fail-fast"]).code(predicates::ord::ne(8));
+}
+
+#[test]
+fn test_cli_sync_lists_folders_for_account_without_labels() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    server.add_mailbox("Archive", 1);
+    let data = TempDir::new().unwrap();
+    let config = format!(
+        "[accounts.fresh]\nuser = \"{USER}\"\npassword = \"{PASSWORD}\"\n\
+         imap_host = \"127.0.0.1\"\nimap_port = {}\nimap_starttls = false\n",
+        server.port()
+    );
+    std::fs::write(data.path().join(".corky.toml"), &config).unwrap();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("corky");
+    cmd.env("CORKY_DATA", data.path());
+    cmd.current_dir(data.path());
+    cmd.arg("sync")
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("Archive"))
+        .stderr(predicates::str::contains(
+            "No labels configured for account 'fresh'; skipping. Suggested: INBOX",
+        ));
+    // Nothing is written back without --interactive
+    let after = std::fs::read_to_string(data.path().join(".corky.toml")).unwrap();
+    assert_eq!(after, config);
+}
+
+#[test]
+fn test_cli_full_sync_confirms_orphan_deletion() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Alice <alice@example.com>",
+            "Project kickoff",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "<kickoff-1@example.com>",
+            "Shall we start Monday?",
+        ),
+    );
+    let data = TempDir::new().unwrap();
+    std::fs::write(
+        data.path().join(".corky.toml"),
+        format!(
+            "[accounts.personal]\nuser = \"{USER}\"\npassword = \"{PASSWORD}\"\n\
+             imap_host = \"127.0.0.1\"\nimap_port = {}\nimap_starttls = false\n\
+             labels = [\"INBOX\"]\n",
+            server.port()
+        ),
+    )
+    .unwrap();
+    let conversations = data.path().join("conversations");
+    std::fs::create_dir_all(&conversations).unwrap();
+    let stale = conversations.join("stale.md");
+    std::fs::write(&stale, "# Stale\n\n**Thread ID**: stale\n").unwrap();
+
+    let full_sync = |extra: &[&str]| {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("corky");
+        cmd.env("CORKY_DATA", data.path());
+        cmd.current_dir(data.path());
+        cmd.args(["sync", "full"]).args(extra);
+        cmd.assert().success()
+    };
+    // No terminal to ask on: the orphan is kept
+    full_sync(&[])
+        .stdout(predicates::str::contains("Kept 1 orphan(s)"))
+        .stderr(predicates::str::contains("Not confirmed: pass --yes to proceed."));
+    assert!(stale.exists());
+    assert!(conversations.join("project-kickoff.md").exists());
+
+    full_sync(&["--yes"]).stdout(predicates::str::contains("Removed orphan: stale.md"));
+    assert!(!stale.exists());
+    assert!(conversations.join("project-kickoff.md").exists());
+}
+
+#[test]
+fn test_cli_sync_profile_reports_phases() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    for i in 0..2 {
+        server.add_message(
+            "INBOX",
+            &raw_message(
+                "Alice <alice@example.com>",
+                &format!("Topic {}", i),
+                "Mon, 10 Feb 2025 10:00:00 +0000",
+                &format!("<topic-{}@example.com>", i),
+                "Hi",
+            ),
+        );
+    }
+    let data = TempDir::new().unwrap();
+    std::fs::write(
+        data.path().join(".corky.toml"),
+        format!(
+            "[accounts.personal]\nuser = \"{USER}\"\npassword = \"{PASSWORD}\"\n\
+             imap_host = \"127.0.0.1\"\nimap_port = {}\nimap_starttls = false\n\
+             labels = [\"INBOX\"]\n",
+            server.port()
+        ),
+    )
+    .unwrap();
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("corky");
+    cmd.env("CORKY_DATA", data.path());
+    cmd.current_dir(data.path());
+    cmd.args(["sync", "--json", "--profile"]);
+    let out = cmd.assert().success().get_output().clone();
+    // The profile goes to stderr; stdout is still just the JSON summary
+    let summary: serde_json::Value = serde_json::from_slice(&out.stdout).unwrap();
+    assert_eq!(summary["totals"]["new_messages"], 2);
+    let stderr = String::from_utf8_lossy(&out.stderr);
+    let rows: Vec<Vec<&str>> = stderr.lines().map(|l| l.split_whitespace().collect()).collect();
+    let row = |scope: &str, phase: &str| {
+        rows.iter()
+            .find(|r| r.len() >= 2 && r[0] == scope && r[1] == phase)
+            .cloned()
+            .unwrap_or_else(|| panic!("no {} {} row in:\n{}", scope, phase, stderr))
+    };
+    assert_eq!(row("personal", "connect")[2], "1");
+    assert_eq!(row("personal/INBOX", "search")[2], "1");
+    // One envelope batch, then one body per message
+    assert_eq!(row("personal/INBOX", "fetch")[2], "3");
+    assert_eq!(row("personal/INBOX", "parse")[2], "2");
+    assert_eq!(row("personal/INBOX", "merge")[2], "2");
+    assert_eq!(row("run", "manifest")[2], "1");
+    for kind in ["network", "server", "local", "other"] {
+        row("total", kind);
+    }
+
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("corky");
+    cmd.env("CORKY_DATA", data.path());
+    cmd.args(["sync", "--profile", "routes"]);
+    cmd.assert().failure().stderr(predicates::str::contains(
+        "--profile is only supported by `sync`, `sync full` and `sync account`",
+    ));
+}
+
+#[test]
+fn test_cli_sync_then_push_reuses_connection() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    server.add_mailbox("Drafts", 2);
+    server.add_message(
+        "INBOX",
+        &raw_message(
+            "Alice <alice@example.com>",
+            "Project kickoff",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            "<kickoff-1@example.com>",
+            "Shall we start Monday?",
+        ),
+    );
+    let data = TempDir::new().unwrap();
+    std::fs::write(
+        data.path().join(".corky.toml"),
+        format!(
+            "[accounts.personal]\nuser = \"{USER}\"\npassword = \"{PASSWORD}\"\n\
+             imap_host = \"127.0.0.1\"\nimap_port = {}\nimap_starttls = false\n\
+             labels = [\"INBOX\"]\n",
+            server.port()
+        ),
+    )
+    .unwrap();
+    let drafts = data.path().join("drafts");
+    std::fs::create_dir_all(&drafts).unwrap();
+    let draft = |name: &str, status: &str| {
+        let text = format!(
+            "---\nto: alice@example.com\nstatus: {status}\n---\n\n# Re: {name}\n\nSure.\n"
+        );
+        std::fs::write(drafts.join(format!("{name}.md")), text).unwrap();
+    };
+    draft("kickoff", "review");
+    draft("wip", "draft");
+
+    let run = || {
+        let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("corky");
+        cmd.env("CORKY_DATA", data.path());
+        cmd.current_dir(data.path());
+        cmd.args(["sync", "--then-push", "drafts"]);
+        cmd.assert().success()
+    };
+    run().stdout(predicates::str::contains("Drafts: 1 pushed, 0 failed"));
+
+    let logins = server.log().iter().filter(|l| l.contains("LOGIN")).count();
+    assert_eq!(logins, 1);
+    assert_eq!(server.messages("Drafts").len(), 1);
+    assert!(data.path().join("conversations/project-kickoff.md").exists());
+    let text = std::fs::read_to_string(drafts.join("kickoff.md")).unwrap();
+    assert!(text.contains("pushed via personal (me@example.com) to Drafts"));
+
+    // Already pushed: the next sync leaves it alone
+    run();
+    assert_eq!(server.messages("Drafts").len(), 1);
+
+    // The push is in the audit log
+    let mut cmd = assert_cmd::cargo::cargo_bin_cmd!("corky");
+    cmd.env("CORKY_DATA", data.path());
+    cmd.current_dir(data.path());
+    cmd.args(["audit", "tail", "--json"]);
+    let out = cmd.assert().success().get_output().stdout.clone();
+    let entries: serde_json::Value = serde_json::from_slice(&out).unwrap();
+    let entries = entries.as_array().unwrap();
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0]["action"], "imap append");
+    assert_eq!(entries[0]["target"], "me@example.com:Drafts");
+    assert_eq!(entries[0]["result"], "ok");
+    assert_eq!(entries[0]["command"][0], "sync");
+}
+
+#[test]
+fn test_push_to_drafts_appends_message() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("Drafts", 3);
+
+    let email = lettre::Message::builder()
+        .from(USER.parse().unwrap())
+        .to("alice@example.com".parse().unwrap())
+        .subject("Draft subject")
+        .body("Draft body".to_string())
+        .unwrap();
+    let folder = push_to_drafts(
+        &email,
+        "127.0.0.1",
+        server.port(),
+        false,
+        USER,
+        &PASSWORD.into(),
+        "Drafts",
+    )
+    .unwrap();
+    assert_eq!(folder, "Drafts");
+
+    let drafts = server.messages("Drafts");
+    assert_eq!(drafts.len(), 1);
+    assert_eq!(drafts[0].flags, vec!["\\Draft"]);
+    let raw = String::from_utf8_lossy(&drafts[0].raw);
+    assert!(raw.contains("Subject: Draft subject"));
+    assert!(raw.contains("Draft body"));
+}
+
+#[test]
+fn test_push_to_drafts_finds_drafts_folder() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    server.add_mailbox("Brouillons", 2);
+    let email = lettre::Message::builder()
+        .from(USER.parse().unwrap())
+        .to("alice@example.com".parse().unwrap())
+        .subject("Draft subject")
+        .body("Draft body".to_string())
+        .unwrap();
+    let push = || {
+        push_to_drafts(&email, "127.0.0.1", server.port(), false, USER, &PASSWORD.into(), "Drafts")
+    };
+
+    // A localized name is found when the configured folder is missing
+    assert_eq!(push().unwrap(), "Brouillons");
+    assert_eq!(server.messages("Brouillons").len(), 1);
+
+    // The folder marked \Drafts wins over names
+    server.add_mailbox("Work Drafts", 3);
+    server.set_special_use("Work Drafts", "\\Drafts");
+    assert_eq!(push().unwrap(), "Work Drafts");
+
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    server.add_mailbox("Archive", 2);
+    let err = push_to_drafts(
+        &email,
+        "127.0.0.1",
+        server.port(),
+        false,
+        USER,
+        &PASSWORD.into(),
+        "Drafts",
+    )
+    .unwrap_err()
+    .to_string();
+    assert!(err.contains("No drafts folder on the server"), "{}", err);
+    assert!(err.contains("Folders: Archive, INBOX"), "{}", err);
+}
+
+#[test]
+fn test_connect_with_xoauth2() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    let token = Secret::OAuth2(PASSWORD.to_string());
+    let mut session = connect_imap_pub("127.0.0.1", server.port(), false, USER, &token).unwrap();
+    session.select("INBOX").unwrap();
+    let _ = session.logout();
+    let log = server.log();
+    assert!(log.iter().any(|l| l.ends_with(" AUTHENTICATE XOAUTH2")), "{:?}", log);
+    assert!(!log.iter().any(|l| l.contains(" LOGIN ")), "{:?}", log);
+
+    // A rejected token is a login failure, not a hang on the error challenge
+    let expired = Secret::OAuth2("expired".to_string());
+    assert!(connect_imap_pub("127.0.0.1", server.port(), false, USER, &expired).is_err());
+}
+
+#[test]
+fn test_fetch_folders_counts_and_config() {
+    let server = MockImapServer::start(USER, PASSWORD).unwrap();
+    server.add_mailbox("INBOX", 1);
+    server.add_mailbox("Projects/2025", 1);
+    for i in 0..2 {
+        server.add_message(
+            "INBOX",
+            &raw_message(
+                "Alice <alice@example.com>",
+                "Hello",
+                "Mon, 10 Feb 2025 10:00:00 +0000",
+                &format!("<hello-{}@example.com>", i),
+                "Hi",
+            ),
+        );
+    }
+    // Sync reads with BODY.PEEK[], so both stay unseen.
+    let dir = TempDir::new().unwrap();
+    sync(&server, &mut SyncState::default(), &dir).unwrap();
+
+    let mut session =
+        connect_imap_pub("127.0.0.1", server.port(), false, USER, &PASSWORD.into()).unwrap();
+    let routes = HashMap::from([("Projects/2025".to_string(), Vec::new())]);
+    let found = folders::fetch_folders(&mut session, &["INBOX".to_string()], &routes).unwrap();
+    let _ = session.logout();
+
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[0].name, "INBOX");
+    assert_eq!((found[0].messages, found[0].unseen), (Some(2), Some(2)));
+    assert!(found[0].label && !found[0].route);
+    assert_eq!((found[1].depth, found[1].leaf.as_str()), (1, "2025"));
+    assert_eq!(found[1].messages, Some(0));
+    assert!(found[1].route && !found[1].label);
+}
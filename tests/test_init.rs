@@ -36,6 +36,8 @@ fn run_init_isolated(
         user, path, provider, password_cmd, labels, github_user, name,
         false, // sync
         mailbox, force,
+        &[], // with_skills
+        None, // import
     );
     // Restore HOME
     if let Some(h) = old_home {
@@ -236,3 +238,58 @@ fn test_init_installs_skill() {
 
     assert!(path.join(".claude/skills/corky/SKILL.md").exists());
 }
+
+#[test]
+fn test_init_add_account_preserves_existing_config() {
+    let tmp = TempDir::new().unwrap();
+    let config_path = tmp.path().join(".corky.toml");
+    std::fs::write(
+        &config_path,
+        "# my settings\n[owner]\nname = \"Alice\"\n\n[accounts.default]\nprovider = \"gmail\"\nuser = \"alice@gmail.com\"\nlabels = [\"inbox\"]\ndefault = true\n",
+    )
+    .unwrap();
+
+    corky::init::add_account(
+        &config_path,
+        "work",
+        "alice@work.example",
+        "imap",
+        "pass work",
+        &["INBOX".to_string(), "Projects".to_string()],
+        false,
+    )
+    .unwrap();
+
+    let content = std::fs::read_to_string(&config_path).unwrap();
+    assert!(content.starts_with("# my settings\n[owner]\nname = \"Alice\"\n"));
+    assert!(content.contains("[accounts.work]"));
+    let accounts = load_accounts(Some(&config_path)).unwrap();
+    assert!(accounts["default"].default);
+    let work = &accounts["work"];
+    assert_eq!(work.user, "alice@work.example");
+    assert_eq!(work.labels, vec!["INBOX", "Projects"]);
+    assert!(!work.default);
+
+    // Same name again is refused unless forced.
+    assert!(corky::init::add_account(
+        &config_path, "work", "x@y.z", "imap", "", &[], false,
+    )
+    .is_err());
+    corky::init::add_account(&config_path, "work", "x@y.z", "imap", "", &[], true).unwrap();
+    assert_eq!(load_accounts(Some(&config_path)).unwrap()["work"].user, "x@y.z");
+}
+
+#[test]
+fn test_init_add_account_requires_config() {
+    let tmp = TempDir::new().unwrap();
+    let result = corky::init::add_account(
+        &tmp.path().join(".corky.toml"),
+        "work",
+        "a@b.c",
+        "imap",
+        "",
+        &[],
+        false,
+    );
+    assert!(result.is_err());
+}
@@ -217,3 +217,54 @@ github_user = "testuser"
     let config = corky_config::load_config(Some(&path)).unwrap();
     assert!(config.routing.contains_key("personal:for-alex"));
 }
+
+#[cfg(unix)]
+#[test]
+fn test_pre_push_hook_honors_allow_secrets() {
+    use corky::mailbox::hooks;
+
+    let tmp = TempDir::new().unwrap();
+    let remote = tmp.path().join("remote.git").to_string_lossy().to_string();
+    let repo = tmp.path().join("mb");
+    let sp = repo.to_string_lossy().to_string();
+    let git = |args: &[&str]| {
+        let (_, stderr, code) = corky::util::run_git(args).unwrap();
+        assert_eq!(code, 0, "{}", stderr);
+    };
+    git(&["init", "-q", "--bare", &remote]);
+    git(&["clone", "-q", &remote, &sp]);
+    std::fs::write(repo.join("notes.md"), "password = hunter2hunter2\n").unwrap();
+    git(&["-C", &sp, "add", "-A"]);
+    git(&["-C", &sp, "-c", "user.name=T", "-c", "user.email=t@x", "commit", "-qm", "notes"]);
+    hooks::install_hook(&repo, false).unwrap();
+
+    // The hook calls `corky` from PATH; use the binary under test.
+    let bin_dir = std::path::Path::new(env!("CARGO_BIN_EXE_corky")).parent().unwrap();
+    let path = format!("{}:{}", bin_dir.display(), std::env::var("PATH").unwrap_or_default());
+    // SAFETY: Test-only; no concurrent env access.
+    unsafe { std::env::set_var("PATH", path) };
+
+    let (_, stderr, code) = hooks::push(&repo, false).unwrap();
+    assert_ne!(code, 0);
+    assert!(stderr.contains("Possible credentials"), "{}", stderr);
+
+    let (_, stderr, code) = hooks::push(&repo, true).unwrap();
+    assert_eq!(code, 0, "{}", stderr);
+}
+
+#[test]
+fn test_load_config_rejects_named_timezone() {
+    let tmp = TempDir::new().unwrap();
+    let path = tmp.path().join(".corky.toml");
+    std::fs::write(&path, "[sync]\ntimezone = \"Europe/Paris\"\n").unwrap();
+    let err = corky_config::load_config(Some(&path)).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<corky::error::CorkyError>(),
+        Some(corky::error::CorkyError::Config(_))
+    ));
+
+    for tz in ["", "local", "UTC", "+05:30", "-08:00"] {
+        assert!(corky_config::valid_timezone(tz), "{}", tz);
+    }
+    assert!(!corky_config::valid_timezone("Europe/Paris"));
+}
@@ -161,3 +161,28 @@ fn test_config_paths() {
     unsafe { std::env::remove_var("CORKY_DATA") };
 }
 
+#[test]
+fn test_sync_state_file_per_owner() {
+    let tmp = TempDir::new().unwrap();
+    let data = tmp.path().to_path_buf();
+    std::fs::write(
+        data.join(".corky.toml"),
+        "[owner]\ngithub_user = \"Dana\"\n",
+    )
+    .unwrap();
+    // SAFETY: Test-only; no concurrent env access.
+    unsafe { std::env::set_var("CORKY_DATA", data.to_string_lossy().as_ref()) };
+
+    let sf = resolve::sync_state_file();
+    assert_eq!(sf.file_name().unwrap(), ".sync-state.dana.json");
+    // Until the owner's file exists, state is read from the shared legacy file.
+    std::fs::write(data.join(".sync-state.json"), "{}").unwrap();
+    assert_eq!(
+        resolve::sync_state_read_file().file_name().unwrap(),
+        ".sync-state.json"
+    );
+    std::fs::write(&sf, "{}").unwrap();
+    assert_eq!(resolve::sync_state_read_file(), sf);
+
+    unsafe { std::env::remove_var("CORKY_DATA") };
+}
@@ -6,9 +6,13 @@ mod common;
 use std::collections::HashSet;
 use tempfile::TempDir;
 
-use corky::sync::imap_sync::{merge_message_to_file, parse_msg_date};
+use corky::config::corky_config::SyncConfig;
+use corky::sync::imap_sync::{
+    collect_participants, merge_message, merge_message_to_file, merge_threads, message_id_index, parse_msg_date,
+    thread_file_stem, truncate_body,
+};
 use corky::sync::markdown::{parse_thread_markdown, thread_to_markdown};
-use corky::sync::types::{Message, SyncState, Thread};
+use corky::sync::types::{Message, MessageSource, SyncState, Thread};
 use corky::util::slugify;
 use pretty_assertions::assert_eq;
 
@@ -23,15 +27,17 @@ fn test_roundtrip_single_message() {
         subject: "Meeting Tomorrow".to_string(),
         labels: vec!["inbox".to_string(), "important".to_string()],
         accounts: vec!["personal".to_string()],
+        participants: vec![],
+        tags: vec![],
+        notes: vec![],
         messages: vec![Message {
             id: "msg-1".to_string(),
             thread_id: "meeting tomorrow".to_string(),
             from: "Alice <alice@example.com>".to_string(),
-            to: String::new(),
-            cc: String::new(),
             date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
             subject: "Meeting Tomorrow".to_string(),
             body: "Let's meet at 3pm.".to_string(),
+            ..Default::default()
         }],
         last_date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
     };
@@ -63,36 +69,36 @@ fn test_roundtrip_multiple_messages() {
         subject: "Project Update".to_string(),
         labels: vec!["work".to_string()],
         accounts: vec!["work".to_string()],
+        participants: vec![],
+        tags: vec![],
+        notes: vec![],
         messages: vec![
             Message {
                 id: "msg-1".to_string(),
                 thread_id: "project update".to_string(),
                 from: "Bob <bob@work.com>".to_string(),
-                to: String::new(),
-                cc: String::new(),
                 date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
                 subject: "Project Update".to_string(),
                 body: "Here's the update.".to_string(),
+                ..Default::default()
             },
             Message {
                 id: "msg-2".to_string(),
                 thread_id: "project update".to_string(),
                 from: "Alice <alice@work.com>".to_string(),
-                to: String::new(),
-                cc: String::new(),
                 date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
                 subject: "Re: Project Update".to_string(),
                 body: "Thanks for the update!".to_string(),
+                ..Default::default()
             },
             Message {
                 id: "msg-3".to_string(),
                 thread_id: "project update".to_string(),
                 from: "Bob <bob@work.com>".to_string(),
-                to: String::new(),
-                cc: String::new(),
                 date: "Mon, 10 Feb 2025 11:00:00 +0000".to_string(),
                 subject: "Re: Project Update".to_string(),
                 body: "No problem. Let me know if you need more.".to_string(),
+                ..Default::default()
             },
         ],
         last_date: "Mon, 10 Feb 2025 11:00:00 +0000".to_string(),
@@ -148,11 +154,10 @@ fn test_merge_message_creates_new_file() {
         id: "1".to_string(),
         thread_id: "hello world".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Hello World".to_string(),
         body: "Hi there!".to_string(),
+        ..Default::default()
     };
 
     let result = merge_message_to_file(
@@ -188,22 +193,20 @@ fn test_merge_message_appends_to_existing() {
         id: "1".to_string(),
         thread_id: "test thread".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
         subject: "Test Thread".to_string(),
         body: "First message".to_string(),
+        ..Default::default()
     };
 
     let msg2 = Message {
         id: "2".to_string(),
         thread_id: "test thread".to_string(),
         from: "Bob <bob@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Re: Test Thread".to_string(),
         body: "Second message".to_string(),
+        ..Default::default()
     };
 
     merge_message_to_file(&out_dir, "inbox", "personal", &msg1, "test thread").unwrap();
@@ -224,6 +227,38 @@ fn test_merge_message_appends_to_existing() {
     assert_eq!(parsed.messages[1].from, "Bob <bob@example.com>");
 }
 
+#[test]
+fn test_merge_joins_thread_with_legacy_key() {
+    let tmp = TempDir::new().unwrap();
+    let out_dir = tmp.path().join("conversations");
+    std::fs::create_dir_all(&out_dir).unwrap();
+    // Written when only one Re: was stripped from thread keys
+    std::fs::write(
+        out_dir.join("hello.md"),
+        "# Re: Re: Hello\n\n**Labels**: inbox\n**Thread ID**: re: hello\n\n---\n\n\
+         ## Alice <alice@example.com> \u{2014} Mon, 10 Feb 2025 09:00:00 +0000\n\nHi\n",
+    )
+    .unwrap();
+
+    let reply = Message {
+        id: "2".to_string(),
+        thread_id: "hello".to_string(),
+        from: "Bob <bob@example.com>".to_string(),
+        date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+        subject: "Re: Re: Re: Hello".to_string(),
+        body: "Hi back".to_string(),
+        ..Default::default()
+    };
+    let path = merge_message_to_file(&out_dir, "inbox", "personal", &reply, "hello")
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(path, out_dir.join("hello.md"));
+    let parsed = parse_thread_markdown(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(parsed.id, "re: hello");
+    assert_eq!(parsed.messages.len(), 2);
+}
+
 #[test]
 fn test_dedup_same_sender_date_skipped() {
     let tmp = TempDir::new().unwrap();
@@ -234,11 +269,10 @@ fn test_dedup_same_sender_date_skipped() {
         id: "1".to_string(),
         thread_id: "dedup test".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Dedup Test".to_string(),
         body: "Original message".to_string(),
+        ..Default::default()
     };
 
     // Merge the same message twice
@@ -268,22 +302,20 @@ fn test_dedup_different_sender_same_date_not_skipped() {
         id: "1".to_string(),
         thread_id: "multi sender".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Multi Sender".to_string(),
         body: "From Alice".to_string(),
+        ..Default::default()
     };
 
     let msg2 = Message {
         id: "2".to_string(),
         thread_id: "multi sender".to_string(),
         from: "Bob <bob@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Multi Sender".to_string(),
         body: "From Bob".to_string(),
+        ..Default::default()
     };
 
     merge_message_to_file(&out_dir, "inbox", "personal", &msg1, "multi sender").unwrap();
@@ -299,6 +331,152 @@ fn test_dedup_different_sender_same_date_not_skipped() {
     assert_eq!(parsed.messages.len(), 2);
 }
 
+#[test]
+fn test_dedup_by_message_id_across_accounts() {
+    let tmp = TempDir::new().unwrap();
+    let out_dir = tmp.path().join("conversations");
+    std::fs::create_dir_all(&out_dir).unwrap();
+
+    let msg1 = Message {
+        id: "10".to_string(),
+        thread_id: "shared".to_string(),
+        from: "Alice <alice@example.com>".to_string(),
+        message_id: "<abc123@example.com>".to_string(),
+        date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+        subject: "Shared".to_string(),
+        body: "Hello both".to_string(),
+        ..Default::default()
+    };
+
+    // Same message delivered to a second account; servers may rewrite the Date
+    let msg2 = Message {
+        id: "77".to_string(),
+        date: "Mon, 10 Feb 2025 10:00:05 +0000".to_string(),
+        message_id: "<ABC123@example.com>".to_string(),
+        ..msg1.clone()
+    };
+
+    merge_message_to_file(&out_dir, "inbox", "personal", &msg1, "shared").unwrap();
+    merge_message_to_file(&out_dir, "inbox", "work", &msg2, "shared").unwrap();
+
+    let entries: Vec<_> = std::fs::read_dir(&out_dir)
+        .unwrap()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.path().extension().map(|ext| ext == "md").unwrap_or(false))
+        .collect();
+    assert_eq!(entries.len(), 1);
+    let content = std::fs::read_to_string(entries[0].path()).unwrap();
+    assert!(content.contains("**Message-ID**: <abc123@example.com>"));
+    assert!(content.contains("**Received by**: personal, work"));
+
+    let parsed = parse_thread_markdown(&content).unwrap();
+    assert_eq!(parsed.messages.len(), 1);
+    assert_eq!(parsed.messages[0].received_by, vec!["personal", "work"]);
+    assert_eq!(parsed.accounts, vec!["personal", "work"]);
+}
+
+#[test]
+fn test_dedup_distinct_message_ids_same_sender_date_kept() {
+    let tmp = TempDir::new().unwrap();
+    let out_dir = tmp.path().join("conversations");
+    std::fs::create_dir_all(&out_dir).unwrap();
+
+    let msg1 = Message {
+        id: "1".to_string(),
+        thread_id: "burst".to_string(),
+        from: "Alice <alice@example.com>".to_string(),
+        message_id: "<one@example.com>".to_string(),
+        date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+        subject: "Burst".to_string(),
+        body: "First".to_string(),
+        ..Default::default()
+    };
+    let msg2 = Message {
+        id: "2".to_string(),
+        message_id: "<two@example.com>".to_string(),
+        body: "Second".to_string(),
+        ..msg1.clone()
+    };
+
+    merge_message_to_file(&out_dir, "inbox", "personal", &msg1, "burst").unwrap();
+    merge_message_to_file(&out_dir, "inbox", "personal", &msg2, "burst").unwrap();
+
+    let path = out_dir.join("burst.md");
+    let parsed = parse_thread_markdown(&std::fs::read_to_string(path).unwrap()).unwrap();
+    assert_eq!(parsed.messages.len(), 2);
+}
+
+// ---------------------------------------------------------------------------
+// Participants
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_collect_participants_unique_by_email() {
+    let msg1 = Message {
+        id: "1".to_string(),
+        thread_id: "people".to_string(),
+        from: "Alice <alice@example.com>".to_string(),
+        to: "\"Doe, Bob\" <bob@example.com>, carol@example.com".to_string(),
+        date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+        subject: "People".to_string(),
+        body: "Hi".to_string(),
+        ..Default::default()
+    };
+    let msg2 = Message {
+        id: "2".to_string(),
+        from: "Bob <BOB@example.com>".to_string(),
+        to: "Alice <alice@example.com>".to_string(),
+        cc: "Carol <carol@example.com>".to_string(),
+        ..msg1.clone()
+    };
+
+    assert_eq!(
+        collect_participants(&[msg1, msg2]),
+        vec![
+            "Alice <alice@example.com>",
+            "\"Doe, Bob\" <bob@example.com>",
+            "carol@example.com",
+        ]
+    );
+}
+
+#[test]
+fn test_merge_writes_participants_line() {
+    let tmp = TempDir::new().unwrap();
+    let out_dir = tmp.path().join("conversations");
+
+    let msg1 = Message {
+        id: "1".to_string(),
+        thread_id: "team".to_string(),
+        from: "Alice <alice@example.com>".to_string(),
+        to: "Bob <bob@example.com>".to_string(),
+        date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+        subject: "Team".to_string(),
+        body: "Hi Bob".to_string(),
+        ..Default::default()
+    };
+    let msg2 = Message {
+        id: "2".to_string(),
+        from: "Bob <bob@example.com>".to_string(),
+        to: "Alice <alice@example.com>".to_string(),
+        cc: "Dave <dave@example.com>".to_string(),
+        date: "Mon, 10 Feb 2025 11:00:00 +0000".to_string(),
+        body: "Hi Alice".to_string(),
+        ..msg1.clone()
+    };
+
+    merge_message_to_file(&out_dir, "inbox", "personal", &msg1, "team").unwrap();
+    merge_message_to_file(&out_dir, "inbox", "personal", &msg2, "team").unwrap();
+
+    let content = std::fs::read_to_string(out_dir.join("team.md")).unwrap();
+    assert!(content.contains(
+        "**Participants**: Alice <alice@example.com>, Bob <bob@example.com>, Dave <dave@example.com>"
+    ));
+    let parsed = parse_thread_markdown(&content).unwrap();
+    assert_eq!(parsed.participants.len(), 3);
+    assert_eq!(parsed.id, "team");
+}
+
 // ---------------------------------------------------------------------------
 // Label accumulation
 // ---------------------------------------------------------------------------
@@ -313,22 +491,20 @@ fn test_label_accumulation() {
         id: "1".to_string(),
         thread_id: "label acc".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
         subject: "Label Accumulation".to_string(),
         body: "First".to_string(),
+        ..Default::default()
     };
 
     let msg2 = Message {
         id: "2".to_string(),
         thread_id: "label acc".to_string(),
         from: "Bob <bob@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Re: Label Accumulation".to_string(),
         body: "Second".to_string(),
+        ..Default::default()
     };
 
     // Merge from different labels and accounts
@@ -362,22 +538,20 @@ fn test_label_not_duplicated() {
         id: "1".to_string(),
         thread_id: "no dup label".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
         subject: "No Dup Label".to_string(),
         body: "First".to_string(),
+        ..Default::default()
     };
 
     let msg2 = Message {
         id: "2".to_string(),
         thread_id: "no dup label".to_string(),
         from: "Bob <bob@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Re: No Dup Label".to_string(),
         body: "Second".to_string(),
+        ..Default::default()
     };
 
     // Same label used twice
@@ -399,6 +573,110 @@ fn test_label_not_duplicated() {
     );
 }
 
+#[test]
+fn test_merge_adds_auto_labels() {
+    let tmp = TempDir::new().unwrap();
+    let out_dir = tmp.path().join("conversations");
+
+    let msg = Message {
+        id: "1".to_string(),
+        thread_id: "auto label".to_string(),
+        from: "Alex <alex@example.com>".to_string(),
+        message_id: "<a1@example.com>".to_string(),
+        date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
+        subject: "Auto Label".to_string(),
+        body: "Hi".to_string(),
+        ..Default::default()
+    };
+
+    // Arrived in the inbox, from a contact labeled for-alex
+    let labels = vec!["for-alex".to_string(), "inbox".to_string()];
+    let (path, _) = merge_message(
+        &out_dir,
+        "inbox",
+        &labels,
+        "personal",
+        &msg,
+        "auto label",
+        &SyncConfig::default(),
+    )
+    .unwrap();
+    let content = std::fs::read_to_string(path.unwrap()).unwrap();
+    let parsed = parse_thread_markdown(&content).unwrap();
+    assert_eq!(parsed.labels, vec!["inbox", "for-alex"]);
+}
+
+#[test]
+fn test_merge_uses_given_sync_config() {
+    let tmp = TempDir::new().unwrap();
+    let out_dir = tmp.path().join("conversations");
+    let sync = SyncConfig {
+        filename_scheme: "{date}-{slug}".to_string(),
+        strip_prefixes: vec!["[PROJ-*]".to_string()],
+        ..Default::default()
+    };
+    let msg = Message {
+        id: "1".to_string(),
+        from: "Alice <alice@example.com>".to_string(),
+        date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
+        subject: "[PROJ-7] Deploy".to_string(),
+        body: "Hi".to_string(),
+        ..Default::default()
+    };
+
+    let (path, _) =
+        merge_message(&out_dir, "inbox", &[], "personal", &msg, "deploy", &sync).unwrap();
+    assert_eq!(path.unwrap(), out_dir.join("2025-02-10-deploy.md"));
+}
+
+#[test]
+fn test_merge_records_sources() {
+    let tmp = TempDir::new().unwrap();
+    let out_dir = tmp.path().join("conversations");
+    let source = |account: &str, label: &str, uid: u32| MessageSource {
+        account: account.to_string(),
+        label: label.to_string(),
+        uid,
+    };
+    let msg = |sources: Vec<MessageSource>| Message {
+        id: "1".to_string(),
+        thread_id: "sources".to_string(),
+        from: "Alice <alice@example.com>".to_string(),
+        message_id: "<s1@example.com>".to_string(),
+        sources,
+        date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
+        subject: "Sources".to_string(),
+        body: "Hi".to_string(),
+        ..Default::default()
+    };
+
+    let sync = SyncConfig::default();
+    let first = msg(vec![source("personal", "INBOX", 5)]);
+    let (path, _) =
+        merge_message(&out_dir, "INBOX", &[], "personal", &first, "sources", &sync).unwrap();
+    let path = path.unwrap();
+    // Same message seen in another label and account, then renumbered
+    for (label, account, uid) in [
+        ("work", "personal", 9),
+        ("INBOX", "work", 3),
+        ("INBOX", "personal", 7),
+    ] {
+        let again = msg(vec![source(account, label, uid)]);
+        merge_message(&out_dir, label, &[], account, &again, "sources", &sync).unwrap();
+    }
+
+    let parsed = parse_thread_markdown(&std::fs::read_to_string(&path).unwrap()).unwrap();
+    assert_eq!(parsed.messages.len(), 1);
+    assert_eq!(
+        parsed.messages[0].sources,
+        vec![
+            source("personal", "INBOX", 7),
+            source("personal", "work", 9),
+            source("work", "INBOX", 3),
+        ]
+    );
+}
+
 // ---------------------------------------------------------------------------
 // Slug collision
 // ---------------------------------------------------------------------------
@@ -414,22 +692,20 @@ fn test_slug_collision_suffix() {
         id: "1".to_string(),
         thread_id: "thread-a".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
         subject: "Same Subject".to_string(),
         body: "Thread A".to_string(),
+        ..Default::default()
     };
 
     let msg2 = Message {
         id: "2".to_string(),
         thread_id: "thread-b".to_string(),
         from: "Bob <bob@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Same Subject".to_string(),
         body: "Thread B".to_string(),
+        ..Default::default()
     };
 
     let path1 = merge_message_to_file(&out_dir, "inbox", "personal", &msg1, "thread-a")
@@ -464,22 +740,20 @@ fn test_messages_sorted_by_date() {
         id: "2".to_string(),
         thread_id: "order test".to_string(),
         from: "Bob <bob@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Tue, 11 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Order Test".to_string(),
         body: "Late message".to_string(),
+        ..Default::default()
     };
 
     let msg_early = Message {
         id: "1".to_string(),
         thread_id: "order test".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 09:00:00 +0000".to_string(),
         subject: "Order Test".to_string(),
         body: "Early message".to_string(),
+        ..Default::default()
     };
 
     // Insert late first, then early
@@ -554,11 +828,10 @@ fn test_mtime_set_on_write() {
         id: "1".to_string(),
         thread_id: "mtime test".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Tue, 15 Jul 2025 12:00:00 +0000".to_string(),
         subject: "Mtime Test".to_string(),
         body: "Test body".to_string(),
+        ..Default::default()
     };
 
     let path = merge_message_to_file(&out_dir, "inbox", "personal", &msg, "mtime test")
@@ -601,6 +874,35 @@ fn test_sync_state_roundtrip() {
     assert!(loaded.accounts.is_empty());
 }
 
+#[test]
+fn test_sync_state_checksum() {
+    use corky::sync::types::{decode_state, encode_state, LabelState};
+
+    let mut state = SyncState::default();
+    state.accounts.entry("personal".to_string()).or_default().labels.insert(
+        "INBOX".to_string(),
+        LabelState {
+            uidvalidity: 7,
+            last_uid: 42,
+        },
+    );
+    let data = encode_state(&state).unwrap();
+    let loaded = decode_state(&data).unwrap();
+    assert_eq!(loaded.accounts["personal"].labels["INBOX"].last_uid, 42);
+
+    // A flipped digit still parses but fails the checksum.
+    let text = String::from_utf8(data.clone())
+        .unwrap()
+        .replace("\"last_uid\":42", "\"last_uid\":24");
+    let err = decode_state(text.as_bytes()).unwrap_err();
+    assert!(err.to_string().contains("checksum mismatch"));
+    // Truncated
+    assert!(decode_state(&data[..data.len() / 2]).is_err());
+    // State files from before checksums load as they are.
+    let legacy = serde_json::to_vec(&state).unwrap();
+    assert_eq!(decode_state(&legacy).unwrap().accounts.len(), 1);
+}
+
 // ---------------------------------------------------------------------------
 // parse_msg_date
 // ---------------------------------------------------------------------------
@@ -615,6 +917,12 @@ fn test_parse_msg_date_rfc2822() {
 
 use chrono::Datelike;
 
+#[test]
+fn test_parse_msg_date_iso8601() {
+    let dt = parse_msg_date("2025-02-10T12:00:00+02:00");
+    assert_eq!(dt.to_rfc3339(), "2025-02-10T10:00:00+00:00");
+}
+
 #[test]
 fn test_parse_msg_date_invalid_returns_epoch() {
     let dt = parse_msg_date("not a date");
@@ -622,6 +930,95 @@ fn test_parse_msg_date_invalid_returns_epoch() {
     assert_eq!(dt.year(), 1970);
 }
 
+// ---------------------------------------------------------------------------
+// Filename scheme
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_thread_file_stem_default_is_slug() {
+    assert_eq!(
+        thread_file_stem("", "Hello World", "Mon, 10 Feb 2025 10:00:00 +0000", &[]),
+        "hello-world"
+    );
+    assert_eq!(
+        thread_file_stem("{slug}", "Hello World", "", &[]),
+        "hello-world"
+    );
+}
+
+#[test]
+fn test_thread_file_stem_with_date() {
+    assert_eq!(
+        thread_file_stem(
+            "{date}-{slug}",
+            "Hello World",
+            "Mon, 10 Feb 2025 10:00:00 +0000",
+            &[]
+        ),
+        "2025-02-10-hello-world"
+    );
+    assert_eq!(
+        thread_file_stem("{date}-{slug}", "Hello World", "garbage", &[]),
+        "undated-hello-world"
+    );
+}
+
+#[test]
+fn test_thread_file_stem_strips_path_separators() {
+    assert_eq!(thread_file_stem("x/{slug}", "Hi", "", &[]), "x-hi");
+}
+
+#[test]
+fn test_thread_file_stem_strips_configured_prefixes() {
+    let prefixes = vec!["[PROJ-*]".to_string()];
+    assert_eq!(
+        thread_file_stem("", "Re: [PROJ-12] Deploy", "", &prefixes),
+        "deploy"
+    );
+    assert_eq!(
+        thread_file_stem("", "[PROJ-12] Deploy", "", &[]),
+        "proj-12-deploy"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Body truncation
+// ---------------------------------------------------------------------------
+
+#[test]
+fn test_truncate_body_under_limit_unchanged() {
+    assert_eq!(truncate_body("short body", 100, "origin"), "short body");
+}
+
+#[test]
+fn test_truncate_body_zero_disables() {
+    let body = "x".repeat(10_000);
+    assert_eq!(truncate_body(&body, 0, "origin"), body);
+}
+
+#[test]
+fn test_truncate_body_adds_marker() {
+    let body = "a".repeat(50);
+    let out = truncate_body(&body, 10, "account personal, label inbox, UID 7");
+    assert!(out.starts_with(&"a".repeat(10)));
+    assert!(out.contains("[Truncated: kept 10 of 50 bytes. Full message: account personal, label inbox, UID 7]"));
+}
+
+#[test]
+fn test_truncate_body_respects_char_boundary() {
+    // "é" is two bytes; a limit of 3 would split the second one
+    let out = truncate_body("éé", 3, "origin");
+    assert!(out.starts_with("é\n"));
+    assert!(out.contains("kept 2 of 4 bytes"));
+}
+
+#[test]
+fn test_truncate_body_counts_only_kept_text() {
+    let body = format!("abc   {}", "x".repeat(50));
+    let out = truncate_body(&body, 6, "origin");
+    assert!(out.starts_with("abc\n\n[Truncated: kept 3 of 56 bytes."), "{}", out);
+}
+
 // ---------------------------------------------------------------------------
 // Slugify edge cases for sync filenames
 // ---------------------------------------------------------------------------
@@ -656,15 +1053,17 @@ fn test_thread_to_markdown_format() {
         subject: "Format Test".to_string(),
         labels: vec!["label1".to_string(), "label2".to_string()],
         accounts: vec!["acct1".to_string()],
+        participants: vec![],
+        tags: vec![],
+        notes: vec![],
         messages: vec![Message {
             id: "1".to_string(),
             thread_id: "format test".to_string(),
             from: "Sender <sender@test.com>".to_string(),
-            to: String::new(),
-            cc: String::new(),
             date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
             subject: "Format Test".to_string(),
             body: "Body text here.".to_string(),
+            ..Default::default()
         }],
         last_date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
     };
@@ -690,6 +1089,9 @@ fn test_thread_to_markdown_empty_labels() {
         subject: "Empty Labels".to_string(),
         labels: vec![],
         accounts: vec![],
+        participants: vec![],
+        tags: vec![],
+        notes: vec![],
         messages: vec![],
         last_date: String::new(),
     };
@@ -723,15 +1125,19 @@ fn test_manifest_generation() {
         subject: "Manifest Subject".to_string(),
         labels: vec!["inbox".to_string()],
         accounts: vec!["personal".to_string()],
+        participants: vec![],
+        tags: vec![],
+        notes: vec![],
         messages: vec![Message {
             id: "1".to_string(),
             thread_id: "manifest-test".to_string(),
             from: "Alice <alice@example.com>".to_string(),
-            to: String::new(),
-            cc: String::new(),
+            to: "Bob <bob@example.com>".to_string(),
+            cc: "carol@example.com".to_string(),
             date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
             subject: "Manifest Subject".to_string(),
             body: "Test body".to_string(),
+            ..Default::default()
         }],
         last_date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
     };
@@ -751,6 +1157,11 @@ fn test_manifest_generation() {
     assert!(content.contains("manifest-subject"));
     assert!(content.contains("Manifest Subject"));
 
+    let manifest: toml::Value = toml::from_str(&content).unwrap();
+    let entry = &manifest["threads"]["manifest-subject"];
+    assert_eq!(entry["to"].as_array().unwrap()[0].as_str(), Some("Bob <bob@example.com>"));
+    assert_eq!(entry["cc"].as_array().unwrap()[0].as_str(), Some("carol@example.com"));
+
     unsafe { std::env::remove_var("CORKY_DATA") };
 }
 
@@ -768,11 +1179,10 @@ fn test_empty_label_not_added() {
         id: "1".to_string(),
         thread_id: "empty label".to_string(),
         from: "Alice <alice@example.com>".to_string(),
-        to: String::new(),
-        cc: String::new(),
         date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
         subject: "Empty Label".to_string(),
         body: "Test".to_string(),
+        ..Default::default()
     };
 
     merge_message_to_file(&out_dir, "", "", &msg, "empty label").unwrap();
@@ -789,3 +1199,74 @@ fn test_empty_label_not_added() {
     assert!(parsed.labels.is_empty() || !parsed.labels.contains(&String::new()));
     assert!(parsed.accounts.is_empty() || !parsed.accounts.contains(&String::new()));
 }
+
+#[test]
+fn test_merge_threads_from_two_owners() {
+    let msg = |id: &str, date: &str, account: &str| Message {
+        thread_id: "shared plans".to_string(),
+        from: "Alice <alice@example.com>".to_string(),
+        message_id: id.to_string(),
+        received_by: vec![account.to_string()],
+        date: date.to_string(),
+        subject: "Shared Plans".to_string(),
+        body: format!("Body {}", id),
+        ..Default::default()
+    };
+    let first = "Mon, 10 Feb 2025 10:00:00 +0000";
+    let second = "Tue, 11 Feb 2025 10:00:00 +0000";
+    let ours = Thread {
+        id: "shared plans".to_string(),
+        subject: "Shared Plans".to_string(),
+        labels: vec!["team".to_string()],
+        accounts: vec!["brian".to_string()],
+        messages: vec![msg("<2@x>", second, "brian")],
+        last_date: second.to_string(),
+        ..Default::default()
+    };
+    let theirs = Thread {
+        labels: vec!["clients".to_string()],
+        accounts: vec!["dana".to_string()],
+        messages: vec![msg("<1@x>", first, "dana"), msg("<2@x>", second, "dana")],
+        ..ours.clone()
+    };
+
+    let merged = merge_threads(&ours, &theirs);
+    let ids: Vec<&str> = merged
+        .messages
+        .iter()
+        .map(|m| m.message_id.as_str())
+        .collect();
+    assert_eq!(ids, vec!["<1@x>", "<2@x>"]);
+    assert_eq!(merged.messages[1].received_by, vec!["brian", "dana"]);
+    assert_eq!(merged.labels, vec!["team", "clients"]);
+    assert_eq!(merged.accounts, vec!["brian", "dana"]);
+    assert_eq!(merged.last_date, second);
+    // Merging is idempotent
+    assert_eq!(
+        thread_to_markdown(&merge_threads(&merged, &theirs)),
+        thread_to_markdown(&merged)
+    );
+}
+
+#[test]
+fn test_message_id_index() {
+    let tmp = TempDir::new().unwrap();
+    let out_dir = tmp.path().join("conversations");
+    let msg = |id: &str, subject: &str| Message {
+        id: "1".to_string(),
+        thread_id: slugify(subject),
+        from: "Alice <alice@example.com>".to_string(),
+        message_id: id.to_string(),
+        date: "Mon, 10 Feb 2025 10:00:00 +0000".to_string(),
+        subject: subject.to_string(),
+        body: "Hi".to_string(),
+        ..Default::default()
+    };
+    merge_message_to_file(&out_dir, "inbox", "work", &msg("<A@x>", "Lunch"), "lunch").unwrap();
+    merge_message_to_file(&out_dir, "inbox", "work", &msg("", "Plans"), "plans").unwrap();
+
+    let index = message_id_index(&out_dir);
+    assert_eq!(index.len(), 1);
+    assert_eq!(index["<a@x>"], "lunch");
+    assert!(message_id_index(&tmp.path().join("missing")).is_empty());
+}